@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use crate::executor::PlanStep;
+
+// [Action Budget] Replans and forced recovery steps can keep sending the
+// executor back to the same step: another screenshot, the same URL again,
+// the same button clicked over and over. Each goal run gets a budget per
+// action type; steps that act on something (a URL, an app, a button) are
+// counted per target, so opening two different pages is fine but opening one
+// page three times is not. A limit counts repeats, not work the plan asked
+// for: a plan that clicks "Next" six times gets five more clicks on "Next"
+// than the limit. A step over budget ends the run with the reason instead of
+// looping until the time limit.
+//
+//   STEER_ACTION_BUDGET="URL=2,SCREENSHOT=3"   override limits (0 = unlimited, "off" disables)
+
+/// Actions counted per target (their `target`/`value`), not per action type.
+const PER_TARGET: &[&str] = &["URL", "ACTIVATE", "OPEN_FILE", "CLICK", "TYPE", "WAIT_FOR", "FOCUS_WINDOW"];
+
+const DEFAULT_LIMITS: &[(&str, u32)] = &[
+    ("SCREENSHOT", 3),
+    ("EXTRACT", 4),
+    ("READ_SELECTION", 4),
+    ("DISMISS_DIALOGS", 3),
+    ("URL", 2),
+    ("ACTIVATE", 3),
+    ("OPEN_FILE", 2),
+    ("CLICK", 4),
+    ("WAIT_FOR", 3),
+    ("FOCUS_WINDOW", 3),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct ActionBudget {
+    limits: HashMap<String, u32>,
+    used: HashMap<(String, String), u32>,
+}
+
+impl ActionBudget {
+    pub fn with_limits(limits: HashMap<String, u32>) -> Self {
+        Self { limits, used: HashMap::new() }
+    }
+
+    /// Defaults overridden by `STEER_ACTION_BUDGET`.
+    pub fn from_env() -> Self {
+        Self::with_limits(limits_from(std::env::var("STEER_ACTION_BUDGET").ok().as_deref()))
+    }
+
+    /// Count `step` of the current `plan`; `Err` with the reason once its
+    /// action is over budget. Each extra step of `plan` with the same action
+    /// (and target) raises the limit by one.
+    pub fn spend(&mut self, step: &PlanStep, plan: &[PlanStep]) -> Result<(), String> {
+        let action = step.action_type.as_str();
+        let Some(&limit) = self.limits.get(action).filter(|l| **l > 0) else {
+            return Ok(());
+        };
+        let target = if PER_TARGET.contains(&action) { target_of(step) } else { String::new() };
+        let planned = plan
+            .iter()
+            .filter(|s| s.action_type == action && (target.is_empty() || target_of(s) == target))
+            .count() as u32;
+        let limit = limit + planned.saturating_sub(1);
+        let used = self.used.entry((action.to_string(), target.clone())).or_insert(0);
+        *used += 1;
+        if *used <= limit {
+            return Ok(());
+        }
+        Err(if target.is_empty() {
+            format!("Action budget exceeded: {} ran {} times in this run (limit {})", action, used, limit)
+        } else {
+            format!("Action budget exceeded: {} '{}' ran {} times in this run (limit {})", action, target, used, limit)
+        })
+    }
+}
+
+/// What a per-target step acts on, normalized so "Notes" and " notes " count together.
+fn target_of(step: &PlanStep) -> String {
+    let arg = match step.action_type.as_str() {
+        "URL" | "ACTIVATE" | "TYPE" => step.value.as_deref(),
+        "FOCUS_WINDOW" => step.value.as_deref().or(step.target.as_deref()),
+        _ => step.target.as_deref(),
+    };
+    arg.unwrap_or_default().trim().trim_end_matches('/').to_lowercase()
+}
+
+/// Default limits with `spec` ("URL=2, SCREENSHOT=3") applied on top.
+/// Malformed entries are ignored; "off" turns every limit off.
+pub fn limits_from(spec: Option<&str>) -> HashMap<String, u32> {
+    let mut limits: HashMap<String, u32> = DEFAULT_LIMITS.iter().map(|(a, n)| (a.to_string(), *n)).collect();
+    let Some(spec) = spec.map(str::trim).filter(|s| !s.is_empty()) else {
+        return limits;
+    };
+    if matches!(spec.to_lowercase().as_str(), "off" | "0" | "false" | "no") {
+        return HashMap::new();
+    }
+    for entry in spec.split(',') {
+        let Some((action, limit)) = entry.split_once('=') else { continue };
+        if let Ok(limit) = limit.trim().parse::<u32>() {
+            limits.insert(action.trim().to_uppercase(), limit);
+        }
+    }
+    limits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(action_type: &str, target: Option<&str>, value: Option<&str>) -> PlanStep {
+        PlanStep {
+            description: action_type.to_lowercase(),
+            action_type: action_type.to_string(),
+            target: target.map(str::to_string),
+            value: value.map(str::to_string),
+            verification: String::new(),
+            pre_check: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_is_exhausted_per_action_and_target() {
+        let mut budget = ActionBudget::with_limits(limits_from(None));
+
+        let shot = step("SCREENSHOT", Some("before"), None);
+        for _ in 0..3 {
+            assert!(budget.spend(&shot, &[]).is_ok());
+        }
+        // Counted per action type: a new label doesn't reset it
+        let err = budget.spend(&step("SCREENSHOT", Some("after"), None), &[]).unwrap_err();
+        assert_eq!(err, "Action budget exceeded: SCREENSHOT ran 4 times in this run (limit 3)");
+
+        // URLs are counted per page
+        let page = step("URL", None, Some("https://example.com/"));
+        assert!(budget.spend(&page, &[]).is_ok());
+        assert!(budget.spend(&step("URL", None, Some("https://other.example")), &[]).is_ok());
+        assert!(budget.spend(&step("URL", None, Some(" https://EXAMPLE.com")), &[]).is_ok());
+        let err = budget.spend(&page, &[]).unwrap_err();
+        assert!(err.contains("URL 'https://example.com' ran 3 times"), "{}", err);
+
+        // Unlisted actions are never limited
+        for _ in 0..20 {
+            assert!(budget.spend(&step("WAIT", None, Some("1")), &[]).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_limit_scales_with_repeats_in_the_plan() {
+        let mut budget = ActionBudget::with_limits(limits_from(None));
+        let next = step("CLICK", Some("Next"), None);
+        let mut plan = vec![next.clone(); 6];
+        plan.push(step("CLICK", Some("Done"), None));
+
+        // Six planned clicks on "Next" plus the default four repeats
+        for _ in 0..9 {
+            assert!(budget.spend(&next, &plan).is_ok());
+        }
+        let err = budget.spend(&next, &plan).unwrap_err();
+        assert!(err.contains("CLICK 'next' ran 10 times in this run (limit 9)"), "{}", err);
+
+        // Other targets keep the plain limit
+        let done = step("CLICK", Some("Done"), None);
+        for _ in 0..4 {
+            assert!(budget.spend(&done, &plan).is_ok());
+        }
+        assert!(budget.spend(&done, &plan).is_err());
+    }
+
+    #[test]
+    fn test_limits_from_spec() {
+        let limits = limits_from(Some("url=5, SCREENSHOT=0, bogus, CLICK=x"));
+        assert_eq!(limits["URL"], 5);
+        assert_eq!(limits["CLICK"], 4);
+        let mut budget = ActionBudget::with_limits(limits);
+        for _ in 0..10 {
+            assert!(budget.spend(&step("SCREENSHOT", Some("s"), None), &[]).is_ok());
+        }
+        assert!(limits_from(Some("off")).is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+use serde_json::{json, Value};
+use crate::screen_extract::ExtractSchema;
+use crate::run_state::ClipboardUse;
+use crate::text_input::InjectionMethod;
+
+// [Action Schema] The one definition of the executor's plan-step vocabulary.
+// Plan validation, the "Available Actions" line in the planning prompts and
+// the exported JSON Schema are all derived from ACTIONS, so adding an action
+// here is the only change needed to advertise and accept it.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Text,
+    Seconds,
+    OneOf(&'static [&'static str]),
+    /// Field list for EXTRACT (see `screen_extract::ExtractSchema::parse`).
+    Fields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arg {
+    Unused,
+    Required(&'static str),
+    Optional(&'static str),
+}
+
+impl Arg {
+    fn describe(&self) -> Option<&'static str> {
+        match self {
+            Arg::Unused => None,
+            Arg::Required(d) | Arg::Optional(d) => Some(d),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ActionSpec {
+    pub name: &'static str,
+    pub target: Arg,
+    pub target_kind: ValueKind,
+    pub value: Arg,
+    pub value_kind: ValueKind,
+}
+
+pub const ACTIONS: &[ActionSpec] = &[
+    ActionSpec { name: "CLICK", target: Arg::Required("element to click"), target_kind: ValueKind::Text, value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "TYPE", target: Arg::Optional("typing method keystroke|paste|ax_set_value"), target_kind: ValueKind::OneOf(InjectionMethod::NAMES), value: Arg::Required("text to type"), value_kind: ValueKind::Text },
+    ActionSpec { name: "URL", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Required("link"), value_kind: ValueKind::Text },
+    ActionSpec { name: "WAIT", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Optional("seconds"), value_kind: ValueKind::Seconds },
+    ActionSpec { name: "WAIT_FOR", target: Arg::Required("text to appear"), target_kind: ValueKind::Text, value: Arg::Optional("timeout seconds"), value_kind: ValueKind::Seconds },
+    ActionSpec { name: "SCROLL", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Optional("up or down"), value_kind: ValueKind::OneOf(&["up", "down"]) },
+    ActionSpec { name: "ACTIVATE", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Required("app name"), value_kind: ValueKind::Text },
+    ActionSpec { name: "EXTRACT", target: Arg::Required("fields to read, e.g. name:string, email:email, date:date"), target_kind: ValueKind::Fields, value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "READ_SELECTION", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "USE_CLIPBOARD", target: Arg::Optional("what the copied text is: read_number|search_query|text"), target_kind: ValueKind::OneOf(ClipboardUse::NAMES), value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "SCREENSHOT", target: Arg::Required("label, e.g. before_submit"), target_kind: ValueKind::Text, value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "REPLY", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Required("answer for the user"), value_kind: ValueKind::Text },
+    ActionSpec { name: "REPORT", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Required("status for the user, e.g. what is blocking the goal"), value_kind: ValueKind::Text },
+    ActionSpec { name: "DISMISS_DIALOGS", target: Arg::Unused, target_kind: ValueKind::Text, value: Arg::Unused, value_kind: ValueKind::Text },
+    ActionSpec { name: "OPEN_FILE", target: Arg::Required("file path, e.g. ~/Desktop/report.pdf"), target_kind: ValueKind::Text, value: Arg::Optional("app to open it with"), value_kind: ValueKind::Text },
+    ActionSpec { name: "FOCUS_WINDOW", target: Arg::Required("part of the window title"), target_kind: ValueKind::Text, value: Arg::Required("app name"), value_kind: ValueKind::Text },
+];
+
+pub fn lookup(name: &str) -> Option<&'static ActionSpec> {
+    ACTIONS.iter().find(|a| a.name == name)
+}
+
+pub fn names() -> Vec<&'static str> {
+    ACTIONS.iter().map(|a| a.name).collect()
+}
+
+/// Steps that only read or talk to the user and leave the screen as it was.
+pub fn only_reads(action_type: &str) -> bool {
+    matches!(action_type, "EXTRACT" | "READ_SELECTION" | "USE_CLIPBOARD" | "REPLY" | "REPORT" | "SCREENSHOT")
+}
+
+impl ActionSpec {
+    /// Problem with a step's `target`/`value` for this action, if any.
+    pub fn check(&self, target: Option<&str>, value: Option<&str>) -> Option<String> {
+        let target = target.map(str::trim).filter(|v| !v.is_empty());
+        let value = value.map(str::trim).filter(|v| !v.is_empty());
+        check_arg(self.name, "target", self.target, self.target_kind, target)
+            .or_else(|| check_arg(self.name, "value", self.value, self.value_kind, value))
+    }
+
+    fn kind_of(&self, field: &str) -> ValueKind {
+        if field == "target" { self.target_kind } else { self.value_kind }
+    }
+
+    /// `WAIT_FOR(target=text to appear, value=timeout seconds)`
+    fn prompt_signature(&self) -> String {
+        let args: Vec<String> = [("target", self.target), ("value", self.value)]
+            .iter()
+            .filter_map(|(field, arg)| arg.describe().map(|d| format!("{}={}", field, d)))
+            .collect();
+        format!("{}({})", self.name, args.join(", "))
+    }
+}
+
+fn check_arg(action: &str, field: &str, arg: Arg, kind: ValueKind, given: Option<&str>) -> Option<String> {
+    match (arg, given) {
+        (Arg::Required(what), None) => Some(format!("{} needs the {} in `{}`", action, what, field)),
+        (Arg::Unused, _) | (_, None) => None,
+        (_, Some(v)) => match kind {
+            ValueKind::Text => None,
+            ValueKind::Seconds if v.parse::<u64>().is_ok() => None,
+            ValueKind::Seconds => Some(format!("{} `{}` must be a number of seconds", action, field)),
+            ValueKind::OneOf(options) if options.contains(&v) => None,
+            ValueKind::OneOf(options) => Some(format!("{} `{}` must be one of: {}", action, field, options.join(", "))),
+            ValueKind::Fields => ExtractSchema::parse(v).err().map(|e| format!("{} `{}`: {}", action, field, e)),
+        },
+    }
+}
+
+/// The "Available Actions" line shared by the planning prompts.
+pub fn prompt_action_list() -> String {
+    let actions: Vec<String> = ACTIONS.iter().map(|a| a.prompt_signature()).collect();
+    format!("Available Actions: {}.", actions.join(", "))
+}
+
+/// JSON Schema for a plan (array of steps), served at `/api/action-schema`.
+pub fn json_schema() -> Value {
+    let variants: Vec<Value> = ACTIONS
+        .iter()
+        .map(|a| {
+            let mut properties = serde_json::Map::new();
+            properties.insert("action_type".to_string(), json!({ "const": a.name }));
+            let mut required = vec!["description", "action_type", "verification"];
+            for (field, arg) in [("target", a.target), ("value", a.value)] {
+                let Some(desc) = arg.describe() else { continue };
+                let mut prop = match a.kind_of(field) {
+                    ValueKind::Seconds => json!({ "type": "string", "pattern": "^[0-9]+$" }),
+                    ValueKind::OneOf(options) => json!({ "type": "string", "enum": options }),
+                    ValueKind::Text | ValueKind::Fields => json!({ "type": "string", "minLength": 1 }),
+                };
+                prop["description"] = json!(desc);
+                properties.insert(field.to_string(), prop);
+                if matches!(arg, Arg::Required(_)) {
+                    required.push(field);
+                }
+            }
+            json!({ "type": "object", "properties": properties, "required": required })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Plan",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "description": { "type": "string" },
+                "action_type": { "enum": names() },
+                "target": { "type": ["string", "null"] },
+                "value": { "type": ["string", "null"] },
+                "pre_check": { "type": ["string", "null"] },
+                "verification": { "type": "string" }
+            },
+            "oneOf": variants
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::PlanStep;
+    use crate::plan_validation::validate_plan;
+
+    fn advertised_actions() -> Vec<String> {
+        let re = regex::Regex::new(r"([A-Z_]+)\(").unwrap();
+        re.captures_iter(&prompt_action_list()).map(|c| c[1].to_string()).collect()
+    }
+
+    fn example_step(spec: &ActionSpec) -> PlanStep {
+        let sample = |arg: Arg, kind: ValueKind| match (arg, kind) {
+            (Arg::Unused, _) => None,
+            (_, ValueKind::Seconds) => Some("5".to_string()),
+            (_, ValueKind::OneOf(options)) => Some(options[0].to_string()),
+            (_, ValueKind::Text) => Some("sample".to_string()),
+            (_, ValueKind::Fields) => Some("name:string, email:email".to_string()),
+        };
+        PlanStep {
+            description: format!("example {}", spec.name),
+            action_type: spec.name.to_string(),
+            target: sample(spec.target, spec.target_kind),
+            value: sample(spec.value, spec.value_kind),
+            verification: String::new(),
+            pre_check: None,
+        }
+    }
+
+    #[test]
+    fn test_prompt_and_validation_agree() {
+        let advertised = advertised_actions();
+        assert_eq!(advertised, names());
+
+        for name in &advertised {
+            let spec = lookup(name).expect("advertised action must be known");
+            let mut plan = vec![example_step(spec)];
+            assert!(validate_plan(&mut plan).is_empty(), "{} rejected", name);
+        }
+
+        let mut unknown = vec![PlanStep { action_type: "HOVER".to_string(), ..example_step(&ACTIONS[0]) }];
+        assert!(!advertised.contains(&"HOVER".to_string()));
+        assert!(!validate_plan(&mut unknown).is_empty());
+    }
+
+    #[test]
+    fn test_json_schema_lists_every_action() {
+        let schema = json_schema();
+        let variants = schema["items"]["oneOf"].as_array().unwrap();
+        assert_eq!(variants.len(), ACTIONS.len());
+        assert_eq!(schema["items"]["properties"]["action_type"]["enum"], json!(names()));
+        let wait_for = variants.iter().find(|v| v["properties"]["action_type"]["const"] == "WAIT_FOR").unwrap();
+        assert_eq!(wait_for["required"], json!(["description", "action_type", "verification", "target"]));
+    }
+}
@@ -0,0 +1,141 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Error categories surfaced at the public boundary (API / REPL) so callers
+/// can branch on the kind of failure instead of parsing message strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentError {
+    Permission(String),
+    Llm(String),
+    Network(String),
+    Policy(String),
+    /// The daily LLM token budget is spent (`llm_budget::BudgetExceeded`)
+    Budget(String),
+    Verification(String),
+    Timeout(String),
+    Cancelled(String),
+    Other(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorPayload {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl AgentError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AgentError::Permission(_) => "permission",
+            AgentError::Llm(_) => "llm",
+            AgentError::Network(_) => "network",
+            AgentError::Policy(_) => "policy",
+            AgentError::Budget(_) => "budget",
+            AgentError::Verification(_) => "verification",
+            AgentError::Timeout(_) => "timeout",
+            AgentError::Cancelled(_) => "cancelled",
+            AgentError::Other(_) => "other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AgentError::Permission(m)
+            | AgentError::Llm(m)
+            | AgentError::Network(m)
+            | AgentError::Policy(m)
+            | AgentError::Budget(m)
+            | AgentError::Verification(m)
+            | AgentError::Timeout(m)
+            | AgentError::Cancelled(m)
+            | AgentError::Other(m) => m,
+        }
+    }
+
+    pub fn payload(&self) -> ErrorPayload {
+        ErrorPayload {
+            code: self.code(),
+            message: self.message().to_string(),
+        }
+    }
+
+    /// Best-effort categorization of an untyped error message.
+    pub fn classify(message: &str) -> Self {
+        let msg = message.to_lowercase();
+        let m = message.to_string();
+        if msg.contains("permission") || msg.contains("denied") || msg.contains("not authorized") {
+            AgentError::Permission(m)
+        } else if msg.contains("timed out after") || msg.contains("time limit") {
+            AgentError::Timeout(m)
+        } else if msg.contains("cancel") {
+            AgentError::Cancelled(m)
+        } else if msg.contains("network") || msg.contains("connection") || msg.contains("dns") || msg.contains("timeout") || msg.contains("rate limited") {
+            AgentError::Network(m)
+        } else if msg.contains("policy") || msg.contains("blocked") {
+            AgentError::Policy(m)
+        } else if msg.contains("verification failed") {
+            AgentError::Verification(m)
+        } else if msg.contains("llm") || msg.contains("openai") || msg.contains("plan generation") || msg.contains("plan json") {
+            AgentError::Llm(m)
+        } else {
+            AgentError::Other(m)
+        }
+    }
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+impl From<anyhow::Error> for AgentError {
+    fn from(err: anyhow::Error) -> Self {
+        if let Some(budget) = err.downcast_ref::<crate::llm_budget::BudgetExceeded>() {
+            return AgentError::Budget(budget.to_string());
+        }
+        match err.downcast::<AgentError>() {
+            Ok(typed) => typed,
+            Err(err) => AgentError::classify(&format!("{:#}", err)),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for AgentError {
+    fn from(err: rusqlite::Error) -> Self {
+        AgentError::Other(format!("DB error: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_messages() {
+        assert_eq!(AgentError::classify("Accessibility permission denied").code(), "permission");
+        assert_eq!(AgentError::classify("connection refused").code(), "network");
+        assert_eq!(AgentError::classify("Plan generation failed").code(), "llm");
+        assert_eq!(AgentError::classify("Goal timed out after 600s").code(), "timeout");
+        assert_eq!(AgentError::classify("gmail rate limited, retry after 30s").code(), "network");
+        assert_eq!(AgentError::classify("something odd").code(), "other");
+    }
+
+    #[test]
+    fn test_typed_error_survives_anyhow() {
+        let err: anyhow::Error = AgentError::Policy("write lock".to_string()).into();
+        assert_eq!(AgentError::from(err), AgentError::Policy("write lock".to_string()));
+
+        let spent = crate::llm_budget::BudgetExceeded { used: 10, limit: 5 };
+        let err = anyhow::Error::new(spent.clone()).context("Failed to summarize email");
+        assert_eq!(AgentError::from(err), AgentError::Budget(spent.to_string()));
+    }
+
+    #[test]
+    fn test_payload_shape() {
+        let json = serde_json::to_value(AgentError::Llm("bad json".to_string()).payload()).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "llm", "message": "bad json" }));
+    }
+}
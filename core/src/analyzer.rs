@@ -7,20 +7,292 @@ use crate::recommendation::TemplateMatcher;
 use crate::memory::MemoryStore; // Added for RAG
 use crate::schema::EventEnvelope;
 use tokio::sync::mpsc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+// [Backpressure] Pipeline counters (process-wide, lock-free)
+static EVENTS_RECEIVED: AtomicU64 = AtomicU64::new(0);
+static EVENTS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+static EVENTS_DROPPED: AtomicU64 = AtomicU64::new(0);
+static EVENTS_DEDUPED: AtomicU64 = AtomicU64::new(0);
+static EVENTS_COALESCED: AtomicU64 = AtomicU64::new(0);
+static LAST_DROP_WARN_SECS: AtomicU64 = AtomicU64::new(0);
+
+const DROP_WARN_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzerStatus {
+    pub received: u64,
+    pub processed: u64,
+    pub dropped: u64,
+    /// Identical consecutive events discarded by the dedup window.
+    pub deduped: u64,
+    /// Events folded into a `repeat` count while the channel was full.
+    pub coalesced: u64,
+    pub in_flight: u64,
+    /// Analysis jobs waiting for or running on a worker.
+    pub queued: u64,
+    pub workers: u64,
+}
+
+pub fn status() -> AnalyzerStatus {
+    let received = EVENTS_RECEIVED.load(Ordering::Relaxed);
+    let processed = EVENTS_PROCESSED.load(Ordering::Relaxed);
+    AnalyzerStatus {
+        received,
+        processed,
+        dropped: EVENTS_DROPPED.load(Ordering::Relaxed),
+        deduped: EVENTS_DEDUPED.load(Ordering::Relaxed),
+        coalesced: EVENTS_COALESCED.load(Ordering::Relaxed),
+        in_flight: received.saturating_sub(processed),
+        queued: ANALYSIS_QUEUE_DEPTH.load(Ordering::Relaxed),
+        workers: ANALYSIS_WORKERS.load(Ordering::Relaxed),
+    }
+}
+
+pub fn record_received() {
+    EVENTS_RECEIVED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_processed() {
+    EVENTS_PROCESSED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count an event a sender folded into the previous one instead of dropping it.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))] // only the native event tap coalesces
+pub fn record_coalesced() {
+    EVENTS_COALESCED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count an event that never reached the analyzer (channel full/closed).
+/// Warnings are throttled so a burst doesn't flood stderr.
+pub fn record_dropped(source: &str) {
+    let total = EVENTS_DROPPED.fetch_add(1, Ordering::Relaxed) + 1;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let last = LAST_DROP_WARN_SECS.load(Ordering::Relaxed);
+    if now.saturating_sub(last) >= DROP_WARN_INTERVAL_SECS
+        && LAST_DROP_WARN_SECS
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    {
+        eprintln!(
+            "⚠️ [Analyzer] Event channel backpressure: dropped event from {} ({} dropped total)",
+            source, total
+        );
+    }
+}
+
+/// Prometheus-style text exposition of the analyzer counters.
+pub fn render_metrics() -> String {
+    let s = status();
+    format!(
+        "# TYPE steer_analyzer_events_received_total counter\n\
+steer_analyzer_events_received_total {}\n\
+# TYPE steer_analyzer_events_processed_total counter\n\
+steer_analyzer_events_processed_total {}\n\
+# TYPE steer_analyzer_events_dropped_total counter\n\
+steer_analyzer_events_dropped_total {}\n\
+# TYPE steer_analyzer_events_deduped_total counter\n\
+steer_analyzer_events_deduped_total {}\n\
+# TYPE steer_analyzer_events_coalesced_total counter\n\
+steer_analyzer_events_coalesced_total {}\n\
+# TYPE steer_analyzer_events_in_flight gauge\n\
+steer_analyzer_events_in_flight {}\n\
+# TYPE steer_analyzer_queue_depth gauge\n\
+steer_analyzer_queue_depth {}\n\
+# TYPE steer_analyzer_workers gauge\n\
+steer_analyzer_workers {}\n",
+        s.received, s.processed, s.dropped, s.deduped, s.coalesced, s.in_flight, s.queued, s.workers
+    )
+}
+
+/// Drops an event identical to the last one kept from the same source when it
+/// arrives within `window_ms` of it (by event time), e.g. a watcher reporting
+/// one change twice. A steady stream of repeats keeps one event per window.
+/// Input events are never dropped: two identical keystrokes are two keystrokes.
+pub struct Deduper {
+    window_ms: i64,
+    last: HashMap<String, EventEnvelope>,
+}
+
+impl Deduper {
+    /// `window_ms` of 0 disables deduplication.
+    pub fn new(window_ms: i64) -> Self {
+        Self { window_ms, last: HashMap::new() }
+    }
+
+    pub fn from_env() -> Self {
+        let window_ms = std::env::var("STEER_EVENT_DEDUP_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(100);
+        Self::new(window_ms)
+    }
+
+    pub fn is_duplicate(&mut self, event: &EventEnvelope) -> bool {
+        if self.window_ms <= 0 || event.is_input() {
+            return false;
+        }
+        let ts = |e: &EventEnvelope| chrono::DateTime::parse_from_rfc3339(&e.ts).ok();
+        let duplicate = self.last.get(&event.source).is_some_and(|prev| {
+            prev.same_content(event)
+                && matches!((ts(prev), ts(event)), (Some(a), Some(b)) if (b - a).num_milliseconds().abs() <= self.window_ms)
+        });
+        if !duplicate {
+            self.last.insert(event.source.clone(), event.clone());
+        }
+        duplicate
+    }
+}
+
+/// Jobs queued across all analysis workers but not finished yet.
+static ANALYSIS_QUEUE_DEPTH: AtomicU64 = AtomicU64::new(0);
+static ANALYSIS_WORKERS: AtomicU64 = AtomicU64::new(0);
+
+/// Jobs each worker holds before `submit` waits for it.
+const QUEUE_PER_WORKER: usize = 32;
+
+/// Workers for the LLM-bound part of analysis, from STEER_ANALYZER_CONCURRENCY
+/// (default 2, at least 1).
+fn analyzer_concurrency() -> usize {
+    std::env::var("STEER_ANALYZER_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(2)
+        .max(1)
+}
+
+// [Worker Pool] A fixed number of workers, each with a bounded queue. Jobs
+// with the same key always go to the same worker, so they run in submission
+// order; different keys can run at the same time. When a worker's queue is
+// full `submit` waits, which stops the analyzer reading the event channel and
+// lets the senders' own backpressure (coalescing, drop counts) take over.
+pub struct WorkerPool<J> {
+    lanes: Vec<mpsc::Sender<J>>,
+    depth: &'static AtomicU64,
+}
+
+impl<J: Send + 'static> WorkerPool<J> {
+    pub fn spawn<F, Fut>(workers: usize, queue: usize, depth: &'static AtomicU64, handler: F) -> Self
+    where
+        F: Fn(J) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let lanes = (0..workers.max(1))
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<J>(queue.max(1));
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    while let Some(job) = rx.recv().await {
+                        handler(job).await;
+                        depth.fetch_sub(1, Ordering::Relaxed);
+                    }
+                });
+                tx
+            })
+            .collect();
+        Self { lanes, depth }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// Queue `job` behind earlier jobs with the same `key`; waits while that worker is full.
+    pub async fn submit(&self, key: &str, job: J) {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        let lane = &self.lanes[(hasher.finish() % self.lanes.len() as u64) as usize];
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        if lane.send(job).await.is_err() {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            eprintln!("⚠️ [Analyzer] Worker for '{}' stopped; job lost", key);
+        }
+    }
+}
+
+enum AnalysisJob {
+    /// Store one event (and index changed files into memory)
+    Ingest(Box<EventEnvelope>),
+    /// Sessionize, detect patterns and recommend over a full buffer
+    Batch(Vec<EventEnvelope>),
+}
+
+/// Batches share one key so recommendations are made one batch at a time.
+const BATCH_KEY: &str = "analysis:batch";
+
+struct AnalysisContext {
+    sessionizer: Sessionizer,
+    detector: PatternDetector,
+    matcher: TemplateMatcher,
+    memory: Option<MemoryStore>,
+    /// `None` in lite mode: patterns are matched against templates only
+    llm: Option<Arc<llm_gateway::LLMClient>>,
+}
+
+impl AnalysisContext {
+    async fn run(&self, job: AnalysisJob) {
+        match job {
+            AnalysisJob::Ingest(event) => self.ingest(*event).await,
+            AnalysisJob::Batch(mut buffer) => {
+                process_buffer(&mut buffer, &self.sessionizer, &self.detector, &self.matcher, &self.memory, self.llm.as_ref()).await;
+            }
+        }
+    }
+
+    async fn ingest(&self, masked_event: EventEnvelope) {
+        // [RAG] Ingest File System Changes (Active Watcher)
+        if masked_event.source == "filesystem" && (masked_event.event_type == "file_created" || masked_event.event_type == "file_modified") {
+            if let Some(path) = masked_event.payload.get("path").and_then(|v| v.as_str()) {
+                // Filter extensions
+                let p = std::path::Path::new(path);
+                if let Some(ext) = p.extension().and_then(|s| s.to_str()) {
+                    if ["md", "txt", "rs", "py", "ts", "tsx", "js", "json"].contains(&ext) {
+                        if let Some(mem) = &self.memory {
+                            // Read file content
+                            match tokio::fs::read_to_string(path).await {
+                                Ok(content) => {
+                                     let meta = serde_json::json!({
+                                         "source": "file_watcher",
+                                         "path": path,
+                                         "timestamp": masked_event.ts
+                                     });
+                                     println!("📄 [Analyzer] Ingesting file update: {}", path);
+                                     if let Err(e) = mem.add(&content, meta).await {
+                                         eprintln!("⚠️ [Analyzer] Mem Ingest Failed: {}", e);
+                                     }
+                                },
+                                Err(e) => eprintln!("⚠️ [Analyzer] Failed to read file {}: {}", path, e),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // 3. Persist to V2 Table
+        if let Err(e) = db::insert_event_v2(&masked_event) {
+            eprintln!("⚠️ [Analyzer] DB Insert Error: {}", e);
+        }
+        record_processed();
+    }
+}
+
+/// Start the analyzer. Without an LLM (lite mode) events are still
+/// sanitized, stored, sessionized and matched against recommendation
+/// templates; only vector memory and AI-written recommendations are skipped.
 pub fn spawn(
     mut log_rx: mpsc::Receiver<String>,
-    #[allow(unused)] // LLM might be unused if we rely solely on patterns for now
-    llm_client: Arc<llm_gateway::LLMClient>
+    llm_client: Option<Arc<llm_gateway::LLMClient>>
 ) {
     tokio::spawn(async move {
         // Buffers
         let mut session_buffer: Vec<EventEnvelope> = Vec::new();
-        let sessionizer = Sessionizer::new(15 * 60); // 15 min idle gap
-        let detector = PatternDetector::new();
-        let matcher = TemplateMatcher::new();
         
         let batch_size = 50; 
         let mut last_process_at = Instant::now();
@@ -29,74 +301,73 @@ pub fn spawn(
         // [Privacy] Initialize Guard with Salt (Env or Default)
         let salt = std::env::var("PRIVACY_SALT").unwrap_or_else(|_| "default_salt".to_string());
         let guard = crate::privacy::PrivacyGuard::new(salt);
+        let mut deduper = Deduper::from_env();
 
         // [Memory] Initialize Vector DB (Non-blocking fail)
-        let memory = match MemoryStore::new("steer_mem", llm_client.clone()).await {
-            Ok(m) => {
-                println!("🧠 [Memory] Visual Cortex Online");
-                Some(m)
+        let memory = match &llm_client {
+            Some(llm) => match MemoryStore::new("steer_mem", llm.clone()).await {
+                Ok(m) => {
+                    println!("🧠 [Memory] Visual Cortex Online");
+                    Some(m)
+                },
+                Err(e) => {
+                    eprintln!("⚠️ [Memory] Failed to init Vector DB: {}", e);
+                    None
+                }
             },
-            Err(e) => {
-                eprintln!("⚠️ [Memory] Failed to init Vector DB: {}", e);
-                None
-            }
+            None => None,
         };
 
+        let context = Arc::new(AnalysisContext {
+            sessionizer: Sessionizer::new(15 * 60), // 15 min idle gap
+            detector: PatternDetector::new(),
+            matcher: TemplateMatcher::new(),
+            memory,
+            llm: llm_client,
+        });
+        let pool = WorkerPool::spawn(analyzer_concurrency(), QUEUE_PER_WORKER, &ANALYSIS_QUEUE_DEPTH, move |job| {
+            let context = context.clone();
+            async move { context.run(job).await }
+        });
+        ANALYSIS_WORKERS.store(pool.workers() as u64, Ordering::Relaxed);
+        println!("🧠 [Analyzer] {} analysis worker(s)", pool.workers());
+
         while let Some(log_json) = log_rx.recv().await {
+            record_received();
             // [Pipeline Upgrade] Parse -> Sanitize -> Store V2
             // 1. Parse Event
             if let Ok(mut event) = serde_json::from_str::<EventEnvelope>(&log_json) {
+                if deduper.is_duplicate(&event) {
+                    EVENTS_DEDUPED.fetch_add(1, Ordering::Relaxed);
+                    record_processed();
+                    continue;
+                }
                 
                 // 2. Apply Privacy Guard
                 if let Some(masked_event) = guard.apply(event) {
+                    // Storage runs on the pool, in order per source
+                    pool.submit(&masked_event.source, AnalysisJob::Ingest(Box::new(masked_event.clone()))).await;
                     
-                    // [RAG] Ingest File System Changes (Active Watcher)
-                    if masked_event.source == "filesystem" && (masked_event.event_type == "file_created" || masked_event.event_type == "file_modified") {
-                        if let Some(path) = masked_event.payload.get("path").and_then(|v| v.as_str()) {
-                            // Filter extensions
-                            let p = std::path::Path::new(path);
-                            if let Some(ext) = p.extension().and_then(|s| s.to_str()) {
-                                if ["md", "txt", "rs", "py", "ts", "tsx", "js", "json"].contains(&ext) {
-                                    if let Some(mem) = &memory {
-                                        // Read file content
-                                        match tokio::fs::read_to_string(path).await {
-                                            Ok(content) => {
-                                                 let meta = serde_json::json!({
-                                                     "source": "file_watcher",
-                                                     "path": path,
-                                                     "timestamp": masked_event.ts
-                                                 });
-                                                 println!("📄 [Analyzer] Ingesting file update: {}", path);
-                                                 if let Err(e) = mem.add(&content, meta).await {
-                                                     eprintln!("⚠️ [Analyzer] Mem Ingest Failed: {}", e);
-                                                 }
-                                            },
-                                            Err(e) => eprintln!("⚠️ [Analyzer] Failed to read file {}: {}", path, e),
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                    // The agent's own actions are kept for history but are not user routines
+                    if masked_event.source == crate::schema::AGENT_EVENT_SOURCE {
+                        continue;
                     }
 
-                    // 3. Persist to V2 Table
-                    if let Err(e) = db::insert_event_v2(&masked_event) {
-                        eprintln!("⚠️ [Analyzer] DB Insert Error: {}", e);
-                    }
-                    
                     // 4. Buffer Sanitized Event for Intelligence
                     let is_idle = masked_event.event_type.contains("idle");
                     session_buffer.push(masked_event);
                     
                     // 5. Trigger Check
                     if session_buffer.len() >= batch_size || last_process_at.elapsed() >= max_buffer_age || is_idle {
-                        process_buffer(&mut session_buffer, &sessionizer, &detector, &matcher, &memory, &llm_client).await;
+                        pool.submit(BATCH_KEY, AnalysisJob::Batch(std::mem::take(&mut session_buffer))).await;
                         last_process_at = Instant::now();
                     }
                 } else {
-                    // Dropped by Privacy Guard
+                    // Dropped by Privacy Guard (intentional, still counts as handled)
+                    record_processed();
                 }
             } else {
+                 record_processed();
                  eprintln!("⚠️ [Analyzer] Failed to parse log as EventEnvelope: {}", log_json);
             }
         }
@@ -110,7 +381,7 @@ async fn process_buffer(
     detector: &PatternDetector,
     matcher: &TemplateMatcher,
     memory: &Option<MemoryStore>,
-    llm: &Arc<llm_gateway::LLMClient>
+    llm: Option<&Arc<llm_gateway::LLMClient>>
 ) {
     if buffer.is_empty() { return; }
 
@@ -181,16 +452,24 @@ async fn process_buffer(
         if let Some(proposal) = matcher.match_pattern(&pattern) {
             if proposal.confidence >= min_confidence {
                 println!("✨ [Analyzer] Matched Template: {}", proposal.title);
-                if let Err(e) = db::insert_recommendation(&proposal) {
-                     eprintln!("⚠️ [Analyzer] DB Error: {}", e);
-                } else {
-                     let _ = notifier::send("Steer OS", &format!("💡 New Idea: {}", proposal.title));
-                     remaining_budget -= 1;
+                let similar = db::recommendation_exists_like(&proposal.trigger).unwrap_or(false);
+                match db::insert_recommendation(&proposal) {
+                    Ok(true) => {
+                        if !similar {
+                            let _ = notifier::notify("recommendations", &proposal.title, "💡 New automation idea");
+                        }
+                        remaining_budget -= 1;
+                    }
+                    Ok(false) => {}
+                    Err(e) => eprintln!("⚠️ [Analyzer] DB Error: {}", e),
                 }
                 continue; // Skip LLM if template matched
             }
         } 
         
+        // Lite mode: templates are all there is
+        let Some(llm) = llm else { continue };
+
         // 2. Hybrid Intelligence (Router)
         // Rule: Use AI if budget exists and pattern is strong.
         // We use the new LLMClient::route_task to decide Local vs Cloud.
@@ -224,16 +503,22 @@ async fn process_buffer(
         match proposal_result {
             Ok(mut proposal) => {
                 proposal.pattern_id = Some(pattern.pattern_id.clone());
-                if proposal.evidence.is_empty() {
-                    proposal.evidence = build_evidence(&pattern);
+                for item in crate::recommendation::pattern_evidence(&pattern) {
+                    proposal.add_evidence(item);
                 }
+                proposal.apply_evidence();
                 if proposal.confidence >= min_confidence {
                     println!("✨ [Analyzer] AI Generated Idea: {}", proposal.title);
-                    if let Err(e) = db::insert_recommendation(&proposal) {
-                        eprintln!("⚠️ [Analyzer] DB Error: {}", e);
-                    } else {
-                         let _ = notifier::send("Steer OS", &format!("✨ New Idea (AI): {}", proposal.title));
-                         remaining_budget -= 1;
+                    let similar = db::recommendation_exists_like(&proposal.trigger).unwrap_or(false);
+                    match db::insert_recommendation(&proposal) {
+                        Ok(true) => {
+                            if !similar {
+                                let _ = notifier::notify("recommendations", &proposal.title, "✨ New automation idea (AI)");
+                            }
+                            remaining_budget -= 1;
+                        }
+                        Ok(false) => {}
+                        Err(e) => eprintln!("⚠️ [Analyzer] DB Error: {}", e),
                     }
                 }
             },
@@ -243,6 +528,31 @@ async fn process_buffer(
         }
     }
 
+    // D. Analyzer rules: a lower-confidence suggestion from one salient event
+    for pattern in detector.detect_rule_events(&logs) {
+        if remaining_budget == 0 {
+            break;
+        }
+        if cooldown_hours > 0 {
+            if let Ok(true) = db::has_recent_pattern_recommendation(&pattern.pattern_id, cooldown_hours) {
+                continue;
+            }
+        }
+        let proposal = crate::recommendation::proposal_from_pattern(&pattern);
+        let similar = db::recommendation_exists_like(&proposal.trigger).unwrap_or(false);
+        match db::insert_recommendation(&proposal) {
+            Ok(true) => {
+                println!("💡 [Analyzer] Rule suggestion: {}", proposal.title);
+                if !similar {
+                    let _ = notifier::notify("recommendations", &proposal.title, "💡 New automation idea (from a rule)");
+                }
+                remaining_budget -= 1;
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("⚠️ [Analyzer] DB Error: {}", e),
+        }
+    }
+
     // Clear buffer after processing
     buffer.clear();
 }
@@ -278,18 +588,128 @@ fn pattern_is_recommendable(pattern: &crate::pattern_detector::DetectedPattern)
             env_u32("REC_MIN_OCCURRENCES_TIME", 4),
             env_f64("REC_MIN_SIMILARITY_TIME", 0.8),
         ),
+        // Analyzer rules fire on one event by design
+        SingleEvent => (1, 0.0),
     };
     pattern.occurrences >= min_occ && pattern.similarity_score >= min_sim
 }
 
-fn build_evidence(pattern: &crate::pattern_detector::DetectedPattern) -> Vec<String> {
-    let mut evidence = vec![
-        format!("Pattern: {}", pattern.description),
-        format!("Frequency: Found {} occurrences", pattern.occurrences),
-    ];
-    if let Some(sample) = pattern.sample_events.first() {
-        let snippet = if sample.len() > 140 { format!("{}...", &sample[..140]) } else { sample.clone() };
-        evidence.push(format!("Sample: {}", snippet));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(source: &str, n: i64, ts: &str) -> EventEnvelope {
+        let mut e = EventEnvelope::new(source, "Finder", "file_created", "P1", None, serde_json::json!({ "n": n }));
+        e.ts = ts.to_string();
+        e
+    }
+
+    #[tokio::test]
+    async fn test_worker_pool_keeps_per_key_order_and_applies_backpressure() {
+        static DEPTH: AtomicU64 = AtomicU64::new(0);
+        let done = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicU64::new(0));
+        let peak = Arc::new(AtomicU64::new(0));
+        let pool = {
+            let (done, running, peak) = (done.clone(), running.clone(), peak.clone());
+            WorkerPool::spawn(2, 1, &DEPTH, move |(key, n): (String, u32)| {
+                let (done, running, peak) = (done.clone(), running.clone(), peak.clone());
+                async move {
+                    peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    // Earlier jobs take longer, so reordering would show
+                    tokio::time::sleep(Duration::from_millis(20 - n as u64 * 4)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    done.lock().unwrap().push((key, n));
+                }
+            })
+        };
+        for n in 0..4 {
+            for key in ["tap", "filesystem", "app_watcher"] {
+                pool.submit(key, (key.to_string(), n)).await;
+            }
+        }
+        while DEPTH.load(Ordering::SeqCst) > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let done = done.lock().unwrap().clone();
+        assert_eq!(done.len(), 12);
+        for key in ["tap", "filesystem", "app_watcher"] {
+            let order: Vec<u32> = done.iter().filter(|(k, _)| k == key).map(|(_, n)| *n).collect();
+            assert_eq!(order, vec![0, 1, 2, 3], "{} ran out of order", key);
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        // A full worker makes submit wait instead of queueing without bound
+        let (release, gate) = tokio::sync::oneshot::channel::<()>();
+        let gate = Arc::new(tokio::sync::Mutex::new(Some(gate)));
+        static BLOCKED_DEPTH: AtomicU64 = AtomicU64::new(0);
+        let blocked = WorkerPool::spawn(1, 1, &BLOCKED_DEPTH, move |_: u32| {
+            let gate = gate.clone();
+            async move {
+                if let Some(gate) = gate.lock().await.take() {
+                    let _ = gate.await;
+                }
+            }
+        });
+        blocked.submit("k", 1).await; // running
+        blocked.submit("k", 2).await; // queued
+        assert!(tokio::time::timeout(Duration::from_millis(50), blocked.submit("k", 3)).await.is_err());
+        assert!(BLOCKED_DEPTH.load(Ordering::SeqCst) >= 2);
+        let _ = release.send(());
+    }
+
+    #[test]
+    fn test_status_counts_the_pipeline() {
+        // Process-wide counters: other tests may add to them concurrently
+        let before = status();
+        record_received();
+        record_received();
+        record_processed();
+        record_dropped("test");
+        let after = status();
+        assert!(after.received >= before.received + 2);
+        assert!(after.processed > before.processed);
+        assert!(after.dropped > before.dropped);
+        assert_eq!(after.in_flight, after.received.saturating_sub(after.processed));
+
+        let metrics = render_metrics();
+        for name in ["received_total", "processed_total", "dropped_total", "deduped_total", "coalesced_total", "in_flight"] {
+            assert!(metrics.contains(&format!("# TYPE steer_analyzer_events_{} ", name)), "{}", metrics);
+        }
+        let dropped: u64 = metrics
+            .lines()
+            .find_map(|l| l.strip_prefix("steer_analyzer_events_dropped_total "))
+            .and_then(|v| v.parse().ok())
+            .unwrap();
+        assert!(dropped >= after.dropped);
+    }
+
+    #[test]
+    fn test_deduper_drops_identical_events_within_window() {
+        let mut dedup = Deduper::new(100);
+        let at = |ms: u32| format!("2026-01-01T09:00:00.{:03}Z", ms);
+        let burst = [
+            event("tap", 1, &at(0)),
+            event("tap", 1, &at(40)),   // duplicate
+            event("files", 1, &at(50)), // other source
+            event("tap", 2, &at(60)),
+            event("tap", 2, &at(90)),   // duplicate
+            event("tap", 2, &at(300)),  // outside the window
+        ];
+        let kept: Vec<bool> = burst.iter().map(|e| !dedup.is_duplicate(e)).collect();
+        assert_eq!(kept, vec![true, false, true, true, false, true]);
+
+        let mut off = Deduper::new(0);
+        assert!(burst.iter().all(|e| !off.is_duplicate(e)));
+
+        // Typing "ll" is two keystrokes, however fast
+        let key = |ts: &str| {
+            let mut e = EventEnvelope::new("tap", "system", "key_input", "P2", None, serde_json::json!({ "keycode": 37, "kind": "down" }));
+            e.ts = ts.to_string();
+            e
+        };
+        assert!(!dedup.is_duplicate(&key(&at(500))));
+        assert!(!dedup.is_duplicate(&key(&at(520))));
     }
-    evidence
 }
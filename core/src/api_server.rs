@@ -9,7 +9,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::{consistency_check, db, llm_gateway, monitor, pattern_detector, feedback_collector, integrations, n8n_api, chat_sanitize, context_pruning, project_scanner, runtime_verification, quality_scorer, visual_verification, semantic_verification, performance_verification, judgment, release_gate, tool_result_guard, intent_router, slot_filler, plan_builder, execution_controller, verification_engine, approval_gate, nl_store};
+use crate::{analyzer, consistency_check, db, llm_gateway, monitor, pattern_detector, feedback_collector, integrations, n8n_api, chat_sanitize, context_pruning, project_scanner, runtime_verification, quality_scorer, visual_verification, semantic_verification, performance_verification, judgment, release_gate, tool_result_guard, intent_router, slot_filler, plan_builder, execution_controller, verification_engine, approval_gate, nl_store, workflow_schema};
+use crate::reporting::{QualityView, RecommendationMetricsView, RecommendationView, RoutineView};
 use sysinfo::System;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -18,6 +19,8 @@ use std::sync::{Arc, Mutex};
 pub struct AppState {
     pub llm_client: Option<llm_gateway::LLMClient>,
     pub current_goal: Arc<Mutex<Option<String>>>,
+    /// Analyzer channel; goal runs report their steps here as `agent_action` events.
+    pub events: Option<tokio::sync::mpsc::Sender<String>>,
 }
 
 // Request/Response types
@@ -168,6 +171,47 @@ pub struct ExecResultsQuery {
     pub limit: Option<i64>,
 }
 
+#[derive(Deserialize)]
+pub struct DaySummaryQuery {
+    pub hours: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    /// `YYYY-MM-DD`, local time; today when omitted.
+    pub day: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SessionsQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct SessionPurgeRequest {
+    pub older_than_days: i64,
+}
+
+/// `ids` and/or `below_confidence` (pending recommendations under it) pick the targets.
+#[derive(Deserialize)]
+pub struct BulkRecommendationRequest {
+    pub action: String,
+    #[serde(default)]
+    pub ids: Vec<i64>,
+    pub below_confidence: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct CommandChannelToggle {
+    pub enabled: bool,
+}
+
+#[derive(Deserialize)]
+pub struct LlmCallsQuery {
+    pub session_key: Option<String>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Deserialize)]
 pub struct VerificationRunsQuery {
     pub limit: Option<i64>,
@@ -250,30 +294,18 @@ pub struct LogEntry {
     pub message: String,
 }
 
-#[derive(Serialize)]
-pub struct RecommendationItem {
-    pub id: i64,
-    pub status: String, // [NEW] Status field
-    pub title: String,
-    pub summary: String,
-    pub confidence: f64,
-    pub evidence: Vec<String>, // [NEW] Explainability field
-    pub last_error: Option<String>,
-}
-
-#[derive(Serialize)]
-pub struct QualityMetrics {
-    pub total: u32,
-    pub success: u32,
-    pub rate: f64,
-}
-
 /// Start the HTTP API server for desktop GUI
 /// Start the HTTP API server for desktop GUI
-pub async fn start_api_server(llm_client: Option<llm_gateway::LLMClient>) -> anyhow::Result<()> {
+/// Bind the API server and serve it in the background, returning the bound address.
+/// Binding errors (e.g. port already in use) are returned to the caller instead of exiting.
+pub async fn start_api_server(
+    llm_client: Option<llm_gateway::LLMClient>,
+    events: Option<tokio::sync::mpsc::Sender<String>>,
+) -> anyhow::Result<std::net::SocketAddr> {
     let state = AppState {
         llm_client,
         current_goal: Arc::new(Mutex::new(None)),
+        events,
     };
     
     // SECURITY: Restrict CORS to localhost only (Tauri/Dev Server)
@@ -294,22 +326,46 @@ pub async fn start_api_server(llm_client: Option<llm_gateway::LLMClient>) -> any
     let app = Router::new()
         .route("/", get(root_handler))
         .route("/api/health", get(health_check))
+        .route("/health", get(live_health_check))
         .route("/events", post(ingest_events)) // Replaces Python Ingest
         .route("/api/status", get(get_system_status))
         .route("/api/logs", get(get_recent_logs))
         .route("/api/system/health", get(get_system_health))
+        .route("/api/integrations/test", get(test_integrations))
+        .route("/api/analyzer/status", get(get_analyzer_status))
+        .route("/api/permissions", get(permissions_handler))
+        .route("/api/monitoring", get(monitoring_status_handler))
+        .route("/api/monitoring/pause", post(pause_monitoring_handler))
+        .route("/api/monitoring/resume", post(resume_monitoring_handler))
+        .route("/metrics", get(get_metrics))
         .route("/api/chat", post(handle_chat))
         .route("/api/recommendations", get(list_recommendations))
+        .route("/api/recommendations/bulk", post(bulk_recommendations))
         .route("/api/recommendations/:id/approve", post(approve_recommendation))
         .route("/api/recommendations/:id/reject", post(reject_recommendation))
         .route("/api/recommendations/:id/later", post(later_recommendation))
         .route("/api/recommendations/:id/restore", post(restore_recommendation))
+        .route("/api/recommendations/:id/explain", get(explain_recommendation))
         .route("/api/exec-approvals", get(list_exec_approvals))
         .route("/api/exec-approvals/:id/approve", post(approve_exec_approval))
         .route("/api/exec-approvals/:id/reject", post(reject_exec_approval))
+        .route("/api/config/history", get(config_history_handler))
+        .route("/api/ui/snapshot", get(ui_snapshot_handler))
+        .route("/api/screen/describe", get(describe_screen_handler))
         .route("/api/exec-allowlist", get(list_exec_allowlist).post(add_exec_allowlist))
         .route("/api/exec-allowlist/:id", axum::routing::delete(remove_exec_allowlist))
         .route("/api/exec-results", get(list_exec_results))
+        .route("/api/exec-results/:id", get(get_exec_result_handler))
+        .route("/api/llm-calls", get(list_llm_calls_handler))
+        .route("/api/goal-steps/:session_key", get(list_goal_steps_handler))
+        .route("/api/goal-steps/:session_key/screenshots", get(list_session_screenshots))
+        .route("/api/sessions", get(list_sessions_handler))
+        .route("/api/sessions/purge", post(purge_sessions_handler))
+        .route("/api/sessions/:session_key/artifacts", get(list_session_artifacts))
+        .route("/api/discord/commands", get(discord_commands_status).post(toggle_discord_commands))
+        .route("/api/action-schema", get(action_schema_handler))
+        .route("/api/summary/day", get(day_summary_handler))
+        .route("/api/activity/timeline", get(activity_timeline_handler))
         .route("/api/project/scan", get(scan_project_handler))
         .route("/api/verify/runtime", post(run_runtime_verification_handler))
         .route("/api/verify/visual", post(run_visual_verification_handler))
@@ -324,11 +380,18 @@ pub async fn start_api_server(llm_client: Option<llm_gateway::LLMClient>) -> any
         .route("/api/quality/score", post(score_quality_handler))
         .route("/api/quality/latest", get(latest_quality_handler))
         .route("/api/patterns/analyze", post(analyze_patterns))
+        .route("/api/analyze", post(analyze_handler))
+        .route("/api/patterns/job", get(pattern_job_status_handler))
+        .route("/api/patterns/clusters", get(event_clusters_handler))
         //.route("/api/patterns/analyze", post(analyze_patterns)) // Removed duplicate
         .route("/api/quality", get(get_quality_metrics))
         .route("/api/recommendations/metrics", get(get_recommendation_metrics))
         .route("/api/routines", get(list_routines).post(create_routine_handler))
         .route("/api/routines/:id", axum::routing::patch(toggle_routine_handler))
+        .route("/api/routines/:id/run", post(run_routine_now_handler))
+        .route("/api/routines/:id/steps", get(routine_steps_handler))
+        .route("/api/routines/:id/check", axum::routing::put(set_routine_check_handler))
+        .route("/api/routines/natural", post(create_natural_routine_handler))
         .route("/api/routine-runs", get(list_routine_runs))
         .route("/api/agent/intent", post(agent_intent_handler))
         .route("/api/agent/plan", post(agent_plan_handler))
@@ -346,24 +409,48 @@ pub async fn start_api_server(llm_client: Option<llm_gateway::LLMClient>) -> any
             axum::routing::delete(remove_nl_approval_policy),
         )
         .route("/api/agent/goal", post(execute_goal_handler))
+        .route("/api/agent/goal/outline", post(outline_goal_handler))
+        .route("/api/tasks", get(list_tasks))
+        .route("/api/tasks/:id/cancel", post(cancel_task))
         .route("/api/agent/goal/current", get(get_current_goal))
+        .route("/api/agent/undo", post(undo_handler))
+        .route("/api/agent/confirmations", get(list_step_confirmations))
+        .route("/api/agent/confirmations/:id", post(resolve_step_confirmation))
         .route("/api/agent/feedback", post(handle_feedback))
         .route("/api/context/selection", get(get_selection_context)) // New Endpoint
         .layer(cors)
         .with_state(state);
 
+    let host = std::env::var("STEER_API_HOST")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_string());
     let port = std::env::var("STEER_API_PORT")
         .ok()
         .and_then(|v| v.parse::<u16>().ok())
         .unwrap_or(5680);
-    println!("🌐 Desktop API server running on http://localhost:{}", port);
-    
-    let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port))
+
+    let listener = tokio::net::TcpListener::bind((host.as_str(), port))
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to bind port {}: {}", port, e))?;
-        
-    axum::serve(listener, app).await.map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
-    Ok(())
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AddrInUse {
+                anyhow::anyhow!(
+                    "Port {} on {} is already in use (another Steer instance?). Set STEER_API_PORT to use a different port.",
+                    port, host
+                )
+            } else {
+                anyhow::anyhow!("Failed to bind {}:{}: {}", host, port, e)
+            }
+        })?;
+    let addr = listener.local_addr()?;
+    println!("🌐 Desktop API server running on http://{}", addr);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("❌ Desktop API server stopped: {}", e);
+        }
+    });
+    Ok(addr)
 }
 
 async fn root_handler() -> Json<serde_json::Value> {
@@ -380,6 +467,139 @@ async fn health_check() -> &'static str {
     "ok"
 }
 
+#[derive(Serialize)]
+struct SubsystemCheck {
+    name: &'static str,
+    ok: bool,
+    critical: bool,
+    detail: String,
+}
+
+/// The LLM is only critical when one is configured; lite mode (no
+/// `OPENAI_API_KEY`) is a supported way to run, not an outage.
+async fn llm_check(llm: Option<&llm_gateway::LLMClient>, timeout: std::time::Duration) -> SubsystemCheck {
+    let Some(llm) = llm else {
+        return SubsystemCheck { name: "llm", ok: false, critical: false, detail: "not configured (lite mode)".to_string() };
+    };
+    let ok = llm.ping(timeout).await;
+    SubsystemCheck {
+        name: "llm",
+        ok,
+        critical: true,
+        detail: if ok { "reachable".to_string() } else { "unreachable or key rejected".to_string() },
+    }
+}
+
+/// Live readiness: 200 only when every critical subsystem is OK.
+async fn live_health_check(State(state): State<AppState>) -> impl IntoResponse {
+    let timeout = std::time::Duration::from_secs(2);
+    let mut checks = Vec::new();
+
+    let db_ok = db::is_healthy();
+    checks.push(SubsystemCheck {
+        name: "db",
+        ok: db_ok,
+        critical: true,
+        detail: if db_ok { "open".to_string() } else { "not initialized".to_string() },
+    });
+
+    checks.push(llm_check(state.llm_client.as_ref(), timeout).await);
+
+    let n8n_ok = match n8n_api::N8nApi::from_env() {
+        Ok(api) => api.is_reachable(timeout).await,
+        Err(_) => false,
+    };
+    checks.push(SubsystemCheck {
+        name: "n8n",
+        ok: n8n_ok,
+        critical: false,
+        detail: if n8n_ok { "reachable".to_string() } else { "unreachable".to_string() },
+    });
+
+    let breaker = crate::n8n_fallback::breaker_status();
+    checks.push(SubsystemCheck {
+        name: "n8n_breaker",
+        ok: breaker.state != "open",
+        critical: false,
+        detail: match breaker.retry_in_secs {
+            Some(secs) => format!(
+                "open after {} consecutive failures; retry in {}s ({})",
+                breaker.consecutive_failures,
+                secs,
+                breaker.last_error.as_deref().unwrap_or("unknown error")
+            ),
+            None => format!("{} ({}/{} failures)", breaker.state, breaker.consecutive_failures, breaker.threshold),
+        },
+    });
+
+    let permissions = crate::permissions::preflight();
+    checks.push(SubsystemCheck { name: "permissions", ok: permissions.ready, critical: false, detail: permissions.summary() });
+
+    let healthy = checks.iter().all(|c| c.ok || !c.critical);
+    let code = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        code,
+        Json(json!({
+            "status": if healthy { "ok" } else { "degraded" },
+            "checks": checks,
+        })),
+    )
+}
+
+async fn permissions_handler() -> Json<crate::permissions::PermissionReport> {
+    Json(crate::permissions::preflight())
+}
+
+async fn get_analyzer_status() -> Json<analyzer::AnalyzerStatus> {
+    Json(analyzer::status())
+}
+
+async fn monitoring_status_handler() -> Json<crate::monitoring::MonitoringStatus> {
+    Json(crate::monitoring::status())
+}
+
+#[derive(serde::Deserialize, Default)]
+struct PauseMonitoringRequest {
+    /// Stay paused after a restart
+    #[serde(default)]
+    persist: bool,
+}
+
+async fn pause_monitoring_handler(
+    State(state): State<AppState>,
+    payload: Option<Json<PauseMonitoringRequest>>,
+) -> Json<serde_json::Value> {
+    let persist = payload.map(|Json(p)| p.persist).unwrap_or_default();
+    match crate::monitoring::pause(state.events.as_ref(), persist) {
+        Ok(changed) => Json(serde_json::json!({ "status": "ok", "changed": changed, "monitoring": crate::monitoring::status() })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn resume_monitoring_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    match crate::monitoring::resume(state.events.as_ref()) {
+        Ok(changed) => Json(serde_json::json!({ "status": "ok", "changed": changed, "monitoring": crate::monitoring::status() })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn get_metrics() -> impl IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        format!(
+            "{}{}{}{}{}{}{}{}",
+            analyzer::render_metrics(),
+            crate::applescript::render_metrics(),
+            crate::llm_budget::render_metrics(),
+            crate::llm_pacing::render_metrics(),
+            integrations::telegram::render_metrics(),
+            crate::plan_validation::render_metrics(),
+            crate::screen_cache::render_metrics(),
+            crate::step_delay::render_metrics()
+        ),
+    )
+}
+
 async fn get_system_status() -> Json<SystemStatus> {
     let mut sys = System::new_all();
     sys.refresh_cpu(); // First refresh just gathers data
@@ -425,6 +645,10 @@ async fn get_system_health() -> Json<crate::dependency_check::SystemHealth> {
     Json(health)
 }
 
+async fn test_integrations() -> Json<Vec<integrations::connection_test::ConnectionReport>> {
+    Json(integrations::connection_test::test_all().await)
+}
+
 async fn scan_project_handler(
     Query(query): Query<ProjectScanQuery>,
 ) -> Json<ProjectScanResponse> {
@@ -666,6 +890,9 @@ async fn ingest_events(
     };
 
     let count = events.len();
+    if crate::monitoring::is_capture_suppressed() {
+        return Json(serde_json::json!({ "status": "paused", "received": count, "processed": 0 }));
+    }
     
     // 2. Process & Insert
     // In a real high-perf scenario, we would push to a channel (EventBus).
@@ -950,6 +1177,11 @@ async fn handle_chat(
                                             // Parse JSON string to Value
                                             match serde_json::from_str::<serde_json::Value>(&json_str) {
                                                 Ok(json_val) => {
+                                                    let problems = workflow_schema::validate_n8n_workflow(&json_val);
+                                                    if !problems.is_empty() {
+                                                        println!("❌ Workflow validation failed: {}", problems.join("; "));
+                                                        return;
+                                                    }
                                                     // 3. Create in n8n (Inactive)
                                                     match n8n.create_workflow("Chat Generated Workflow", &json_val, false).await {
                                                         Ok(id) => println!("✅ Chat-triggered Workflow Created: {}", id),
@@ -972,16 +1204,16 @@ async fn handle_chat(
                         let params = intent["params"].as_object();
                         if let Some(p) = params {
                             let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("New Routine");
-                            let cron = p.get("cron").and_then(|v| v.as_str()).unwrap_or("* * * * *");
+                            let cron = p.get("cron").and_then(|v| v.as_str()).unwrap_or("0 * * * * *");
                             
                             // Validate Cron
-                            if std::str::FromStr::from_str(&cron as &str).map(|_: cron::Schedule| ()).is_err() {
-                                format!("❌ 잘못된 Cron 표현식입니다: {}", cron)
+                            if let Err(reason) = crate::db::validate_cron(cron) {
+                                format!("❌ 잘못된 Cron 표현식입니다: {}\n• {}", cron, reason)
                             } else {
                                 let prompt = p.get("prompt").and_then(|v| v.as_str()).unwrap_or("Check status");
                             
                             match crate::db::create_routine(name, cron, prompt) {
-                                Ok(_id) => format!("✅ 루틴이 등록되었습니다!\n• 이름: {}\n• 주기: {}\n• 명령: {}", name, cron, prompt),
+                                Ok(id) => format!("✅ 루틴이 등록되었습니다!\n• ID: #{}\n• 이름: {}\n• 주기: {}\n• 명령: {}", id, name, cron, prompt),
                                 Err(e) => format!("❌ 루틴 등록 실패: {}", e),
                             }
                             }
@@ -989,6 +1221,17 @@ async fn handle_chat(
                              "❌ 루틴 정보를 파악할 수 없습니다.".to_string()
                         }
                     },
+                    "run_routine" => {
+                        let id = intent["params"]["id"].as_i64()
+                            .or_else(|| intent["params"]["id"].as_str().and_then(|s| s.trim_start_matches('#').parse().ok()));
+                        match crate::scheduler::resolve_routine(id, intent["params"]["name"].as_str()) {
+                            Ok(id) => match crate::scheduler::run_routine_now(Some(brain), id).await {
+                                Ok(res) => format!("✅ 루틴 #{} 실행 완료: {}", id, res),
+                                Err(e) => format!("❌ 루틴 #{} 실행 실패: {}", id, e),
+                            },
+                            Err(e) => format!("❌ {}", e),
+                        }
+                    },
                     "help" => "💡 사용 가능한 명령:\n• '이메일 보여줘'\n• '오늘 일정 뭐야?'\n• '매일 아침 9시 뉴스 요약해줘' (New!)".to_string(),
                     _ => format!("✅ '{}' 명령을 실행합니다.", command),
                 };
@@ -1020,8 +1263,8 @@ async fn handle_chat(
 
 // --- Routine Handlers ---
 
-async fn list_routines() -> Json<Vec<crate::db::Routine>> {
-    match crate::db::get_all_routines() {
+async fn list_routines() -> Json<Vec<RoutineView>> {
+    match RoutineView::load_all() {
         Ok(routines) => Json(routines),
         Err(e) => {
             eprintln!("Failed to list routines: {}", e);
@@ -1030,21 +1273,134 @@ async fn list_routines() -> Json<Vec<crate::db::Routine>> {
     }
 }
 
+async fn routine_steps_handler(Path(id): Path<i64>) -> Json<serde_json::Value> {
+    match crate::db::get_routine_steps(id) {
+        Ok(steps) => Json(serde_json::json!({
+            "status": "ok",
+            "routine_id": id,
+            "preview": crate::scheduler::step_preview(&steps),
+            "steps": steps,
+        })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct CreateRoutineRequest {
     name: String,
     #[serde(alias = "cron_expression")] // Accept both "cron" and "cron_expression"
     cron: String,
     prompt: String,
+    /// Check that decides whether a run succeeded, e.g. {"kind": "telegram_sent"}
+    #[serde(default)]
+    success_check: Option<serde_json::Value>,
+}
+
+/// Validated JSON text of a routine success check.
+fn routine_check_text(check: &serde_json::Value) -> Result<String, String> {
+    let text = check.to_string();
+    crate::routine_check::parse(&text)?;
+    Ok(text)
 }
 
-async fn create_routine_handler(Json(payload): Json<CreateRoutineRequest>) -> Json<serde_json::Value> {
+/// 400 for input the database refused to store (bad cron, ...), 404 for a
+/// row that doesn't exist, 500 otherwise.
+fn write_error_response(e: crate::db::WriteError) -> (StatusCode, Json<serde_json::Value>) {
+    let code = match e {
+        crate::db::WriteError::Invalid(_) => StatusCode::BAD_REQUEST,
+        crate::db::WriteError::NotFound(_) => StatusCode::NOT_FOUND,
+        crate::db::WriteError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (code, Json(serde_json::json!({ "status": "error", "message": e.to_string() })))
+}
+
+async fn create_routine_handler(Json(payload): Json<CreateRoutineRequest>) -> (StatusCode, Json<serde_json::Value>) {
+    let check = match payload.success_check.as_ref().map(routine_check_text).transpose() {
+        Ok(check) => check,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "message": e }))),
+    };
     match crate::db::create_routine(&payload.name, &payload.cron, &payload.prompt) {
-        Ok(id) => Json(serde_json::json!({ "status": "ok", "id": id })),
+        Ok(id) => {
+            if let Some(check) = check {
+                if let Err(e) = crate::db::set_routine_success_check(id, Some(&check)) {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "status": "error", "message": e.to_string() })));
+                }
+            }
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "id": id })))
+        }
+        Err(e) => write_error_response(e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RoutineCheckRequest {
+    /// `null` removes the check
+    success_check: Option<serde_json::Value>,
+}
+
+async fn set_routine_check_handler(
+    Path(id): Path<i64>,
+    Json(payload): Json<RoutineCheckRequest>,
+) -> Json<serde_json::Value> {
+    let check = match payload.success_check.as_ref().map(routine_check_text).transpose() {
+        Ok(check) => check,
+        Err(e) => return Json(serde_json::json!({ "status": "error", "message": e })),
+    };
+    match crate::db::set_routine_success_check(id, check.as_deref()) {
+        Ok(()) => Json(serde_json::json!({ "status": "ok", "success_check": payload.success_check })),
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            Json(serde_json::json!({ "status": "error", "message": format!("Routine #{} not found", id) }))
+        }
         Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
     }
 }
 
+#[derive(serde::Deserialize)]
+struct NaturalRoutineRequest {
+    name: Option<String>,
+    prompt: String,
+    schedule: String,
+    /// When true, only return the parsed cron without creating the routine
+    #[serde(default)]
+    preview: bool,
+}
+
+async fn create_natural_routine_handler(Json(payload): Json<NaturalRoutineRequest>) -> (StatusCode, Json<serde_json::Value>) {
+    let cron = match crate::scheduler::parse_natural_schedule(&payload.schedule) {
+        Ok(cron) => cron,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "message": e }))),
+    };
+    if payload.preview {
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "preview", "cron": cron })));
+    }
+    let name = payload
+        .name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| payload.prompt.chars().take(40).collect());
+    match crate::db::create_routine(&name, &cron, &payload.prompt) {
+        Ok(id) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "id": id, "cron": cron }))),
+        Err(e) => write_error_response(e),
+    }
+}
+
+async fn run_routine_now_handler(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Json<serde_json::Value> {
+    // Without an LLM the routine's stored plan is replayed
+    match crate::scheduler::run_routine_now(state.llm_client.as_ref(), id).await {
+        Ok(result) => Json(serde_json::json!({ "status": "ok", "result": result.to_string(), "reply": result.reply() })),
+        Err(e) => Json(agent_error_body(&e)),
+    }
+}
+
+/// `{"status": "error", "code", "message"}` for a failed agent run.
+fn agent_error_body(e: &crate::agent_error::AgentError) -> serde_json::Value {
+    let mut body = json!(e.payload());
+    body["status"] = json!("error");
+    body
+}
+
 // --- Issue #2 Fix: Toggle Routine ---
 #[derive(serde::Deserialize)]
 struct ToggleRoutineRequest {
@@ -1064,32 +1420,29 @@ async fn toggle_routine_handler(
 #[derive(serde::Deserialize)]
 struct RecQueryParams {
     status: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
 }
 
 async fn list_recommendations(
     Query(params): Query<RecQueryParams>,
-) -> Json<Vec<RecommendationItem>> {
-    // Treat empty string as None; default to "all" for history view.
-    let filter = params
+) -> Json<Vec<RecommendationView>> {
+    // Comma-separated statuses (e.g. "pending,failed"); empty or "all" means every status.
+    let requested: Vec<&str> = params
         .status
         .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim())
         .filter(|s| !s.is_empty())
-        .or(Some("all"));
-
-    match db::get_recommendations_with_filter(filter) {
-        Ok(recs) => Json(
-            recs.into_iter()
-                .map(|r| RecommendationItem {
-                    id: r.id,
-                    status: r.status,
-                    title: r.title,
-                    summary: r.summary,
-                    confidence: r.confidence,
-                    evidence: r.evidence, // [NEW] Pass evidence
-                    last_error: r.last_error,
-                })
-                .collect()
-        ),
+        .collect();
+    let statuses: &[&str] = if requested.contains(&"all") { &[] } else { &requested };
+
+    let limit = params.limit.unwrap_or(200).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match db::get_recommendations_by_statuses(statuses, limit, offset) {
+        Ok(recs) => Json(recs.into_iter().map(RecommendationView::from).collect()),
         Err(_) => Json(vec![]),
     }
 }
@@ -1100,6 +1453,10 @@ async fn approve_recommendation(
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     println!("🔔 Received approval request for Recommendation ID: {}", id);
 
+    if let Err(e) = crate::safe_mode::check("new workflows are") {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({ "error": e.to_string() }))));
+    }
+
     // 1. Get recommendation from DB
     let rec = match db::get_recommendation(id) {
         Ok(Some(r)) => r,
@@ -1196,6 +1553,13 @@ async fn approve_recommendation(
             }
         };
 
+        let problems = workflow_schema::validate_n8n_workflow(&workflow_data);
+        if !problems.is_empty() {
+            last_error = format!("Workflow validation failed: {}", problems.join("; "));
+            println!("❌ {}", last_error);
+            continue;
+        }
+
         // Extract name
         let name = workflow_data["name"].as_str().unwrap_or(&rec.title).to_string();
         
@@ -1252,6 +1616,49 @@ async fn reject_recommendation(
     }
 }
 
+async fn bulk_recommendations(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkRecommendationRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let mut ids = payload.ids.clone();
+    if let Some(threshold) = payload.below_confidence {
+        match db::pending_recommendation_ids_below(threshold) {
+            Ok(found) => ids.extend(found),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+        }
+    }
+    ids.sort_unstable();
+    ids.dedup();
+
+    let status = match payload.action.as_str() {
+        "reject" => "rejected",
+        "later" => "later",
+        "restore" => "pending",
+        "approve" => {
+            // Each approval builds and imports a workflow, so they run one by one
+            let mut results = Vec::new();
+            for id in &ids {
+                let result = match approve_recommendation(State(state.clone()), axum::extract::Path(*id)).await {
+                    Ok(Json(body)) => json!({ "id": id, "ok": true, "result": body }),
+                    Err((code, Json(body))) => json!({ "id": id, "ok": false, "status": code.as_u16(), "error": body }),
+                };
+                results.push(result);
+            }
+            return (StatusCode::OK, Json(json!({ "action": "approve", "results": results })));
+        }
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("unknown action '{}' (approve, reject, later, restore)", other) })),
+            )
+        }
+    };
+    match db::update_recommendations_status(&ids, status) {
+        Ok(updated) => (StatusCode::OK, Json(json!({ "action": payload.action, "ids": ids, "updated": updated }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    }
+}
+
 async fn later_recommendation(
     axum::extract::Path(id): axum::extract::Path<i64>,
 ) -> StatusCode {
@@ -1270,6 +1677,19 @@ async fn restore_recommendation(
     }
 }
 
+async fn explain_recommendation(Path(id): Path<i64>) -> (StatusCode, Json<serde_json::Value>) {
+    let rec = match db::get_recommendation(id) {
+        Ok(Some(rec)) => rec,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "Recommendation not found"}))),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))),
+    };
+    let pattern = rec.pattern_id.as_deref().and_then(|p| db::get_pattern_info(p).ok().flatten());
+    let explanation = crate::recommendation::explain(&rec, pattern);
+    let mut body = serde_json::to_value(&explanation).unwrap_or_default();
+    body["text"] = serde_json::Value::String(explanation.render());
+    (StatusCode::OK, Json(body))
+}
+
 async fn list_exec_approvals(
     Query(query): Query<ExecApprovalQuery>,
 ) -> Json<Vec<db::ExecApproval>> {
@@ -1295,6 +1715,7 @@ async fn approve_exec_approval(
     if decision == "allow-always" {
         if let Ok(Some(approval)) = db::get_exec_approval(&id) {
             let _ = db::add_exec_allowlist(&approval.command, approval.cwd.as_deref());
+            approval_gate::remember_exec_decision(&approval.command, approval.cwd.as_deref(), decision);
         }
     }
 
@@ -1309,7 +1730,13 @@ async fn reject_exec_approval(
     payload: Option<Json<ExecApprovalResolve>>,
 ) -> StatusCode {
     let resolved_by = payload.as_ref().and_then(|p| p.resolved_by.as_deref());
-    match db::resolve_exec_approval(&id, "rejected", resolved_by, Some("deny")) {
+    let decision = payload.as_ref().and_then(|p| p.decision.as_deref()).unwrap_or("deny");
+    if decision == "deny-always" {
+        if let Ok(Some(approval)) = db::get_exec_approval(&id) {
+            approval_gate::remember_exec_decision(&approval.command, approval.cwd.as_deref(), decision);
+        }
+    }
+    match db::resolve_exec_approval(&id, "rejected", resolved_by, Some(decision)) {
         Ok(_) => StatusCode::OK,
         Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
     }
@@ -1340,6 +1767,108 @@ async fn list_exec_results(
     Json(results)
 }
 
+async fn get_exec_result_handler(Path(id): Path<String>) -> Json<serde_json::Value> {
+    match db::get_exec_result(&id) {
+        Ok(Some(result)) => Json(serde_json::json!({ "status": "ok", "result": result })),
+        Ok(None) => Json(serde_json::json!({ "status": "error", "message": "Exec result not found" })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn day_summary_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DaySummaryQuery>,
+) -> Json<serde_json::Value> {
+    let Some(llm) = state.llm_client else {
+        return Json(serde_json::json!({ "status": "error", "message": "LLM Client not available" }));
+    };
+    let hours = query.hours.unwrap_or(24).clamp(1, 168);
+    match crate::day_summary::summarize_day(&llm, hours).await {
+        Ok(summary) => Json(serde_json::json!({ "status": "ok", "summary": summary })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn activity_timeline_handler(
+    Query(query): Query<TimelineQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let day = match query.day.as_deref() {
+        None => chrono::Local::now().date_naive(),
+        Some(raw) => match chrono::NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d") {
+            Ok(day) => day,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "error": format!("invalid day '{}', expected YYYY-MM-DD", raw) })),
+                )
+            }
+        },
+    };
+    match db::activity_timeline(day) {
+        Ok(timeline) => (StatusCode::OK, Json(serde_json::json!(timeline))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))),
+    }
+}
+
+async fn list_llm_calls_handler(
+    Query(query): Query<LlmCallsQuery>,
+) -> Json<Vec<db::LlmCall>> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let calls = db::list_llm_calls(query.session_key.as_deref(), limit).unwrap_or_default();
+    Json(calls)
+}
+
+async fn list_goal_steps_handler(Path(session_key): Path<String>) -> Json<Vec<db::GoalStep>> {
+    Json(db::list_goal_steps(&session_key, 500).unwrap_or_default())
+}
+
+async fn list_session_screenshots(Path(session_key): Path<String>) -> Json<Vec<crate::session_artifacts::Screenshot>> {
+    Json(crate::session_artifacts::screenshots(&session_key))
+}
+
+async fn list_session_artifacts(Path(session_key): Path<String>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "dir": crate::session_artifacts::session_dir(&session_key),
+        "artifacts": crate::session_artifacts::artifacts(&session_key),
+    }))
+}
+
+async fn list_sessions_handler(Query(query): Query<SessionsQuery>) -> Json<serde_json::Value> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let (total, bytes) = db::run_session_totals().unwrap_or((0, 0));
+    let sessions = db::list_run_sessions(limit).unwrap_or_default();
+    Json(serde_json::json!({ "total": total, "total_bytes": bytes, "sessions": sessions }))
+}
+
+async fn purge_sessions_handler(Json(payload): Json<SessionPurgeRequest>) -> Json<serde_json::Value> {
+    match db::purge_run_sessions(payload.older_than_days) {
+        Ok(keys) => {
+            crate::session_artifacts::remove_sessions(&keys);
+            Json(serde_json::json!({ "status": "ok", "purged": keys.len() }))
+        }
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn discord_commands_status() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "running": integrations::discord::command_channel_running() }))
+}
+
+async fn toggle_discord_commands(Json(payload): Json<CommandChannelToggle>) -> Json<serde_json::Value> {
+    if !payload.enabled {
+        integrations::discord::stop_command_channel();
+        return Json(serde_json::json!({ "status": "ok", "running": false }));
+    }
+    match integrations::discord::start_command_channel() {
+        Ok(_) => Json(serde_json::json!({ "status": "ok", "running": true })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
+async fn action_schema_handler() -> Json<serde_json::Value> {
+    Json(crate::action_schema::json_schema())
+}
+
 async fn list_verification_runs(
     Query(query): Query<VerificationRunsQuery>,
 ) -> Json<Vec<db::VerificationRun>> {
@@ -1417,6 +1946,19 @@ fn log_verification_run(kind: &str, ok: bool, summary: &str, details: Option<ser
     let _ = db::insert_verification_run(kind, ok, summary, details_str.as_deref());
 }
 
+#[derive(serde::Deserialize)]
+struct ConfigHistoryQuery {
+    key: Option<String>,
+    limit: Option<i64>,
+}
+
+async fn config_history_handler(Query(query): Query<ConfigHistoryQuery>) -> Result<Json<Vec<db::ConfigChange>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    db::list_config_changes(query.key.as_deref(), limit)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 async fn add_exec_allowlist(
     Json(payload): Json<ExecAllowlistRequest>,
 ) -> StatusCode {
@@ -1438,73 +1980,53 @@ async fn remove_exec_allowlist(
     }
 }
 
-#[derive(Serialize)]
-struct RecommendationMetricsResponse {
-    total: i64,
-    approved: i64,
-    rejected: i64,
-    failed: i64,
-    pending: i64,
-    later: i64,
-    approval_rate: f64,
-    last_created_at: Option<String>,
-}
-
-async fn get_recommendation_metrics() -> Json<RecommendationMetricsResponse> {
-    let metrics = db::get_recommendation_metrics().unwrap_or(crate::db::RecommendationMetrics {
-        total: 0,
-        approved: 0,
-        rejected: 0,
-        failed: 0,
-        pending: 0,
-        later: 0,
-        last_created_at: None,
-    });
+async fn get_recommendation_metrics() -> Json<RecommendationMetricsView> {
+    Json(RecommendationMetricsView::load())
+}
 
-    let approval_rate = if metrics.total > 0 {
-        (metrics.approved as f64 / metrics.total as f64) * 100.0
-    } else {
-        0.0
-    };
+// Add at top: use crate::recommendation::AutomationProposal; 
 
-    Json(RecommendationMetricsResponse {
-        total: metrics.total,
-        approved: metrics.approved,
-        rejected: metrics.rejected,
-        failed: metrics.failed,
-        pending: metrics.pending,
-        later: metrics.later,
-        approval_rate,
-        last_created_at: metrics.last_created_at,
-    })
+async fn pattern_job_status_handler() -> Json<crate::scheduler::PatternJobStatus> {
+    Json(crate::scheduler::pattern_job_status())
 }
 
-// Add at top: use crate::recommendation::AutomationProposal; 
+async fn event_clusters_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "enabled": crate::event_clusters::enabled(),
+        "clusters": crate::event_clusters::last_clusters(),
+    }))
+}
 
 async fn analyze_patterns() -> Json<Vec<String>> {
     Json(run_analysis_internal())
 }
 
+#[derive(Deserialize)]
+pub struct AnalyzeQuery {
+    /// `false` previews the proposals without saving them (default `true`).
+    pub persist: Option<bool>,
+}
+
+/// Detect patterns and return the recommendations they would produce; saved
+/// as pending unless `?persist=false`.
+async fn analyze_handler(Query(query): Query<AnalyzeQuery>) -> Json<serde_json::Value> {
+    let persist = query.persist.unwrap_or(true);
+    let proposals = pattern_detector::PatternDetector::new().propose();
+    let inserted = if persist { crate::recommendation::persist_proposals(&proposals) } else { 0 };
+    Json(serde_json::json!({
+        "persisted": persist,
+        "inserted": inserted,
+        "proposals": proposals,
+    }))
+}
+
 fn run_analysis_internal() -> Vec<String> {
     let detector = pattern_detector::PatternDetector::new();
     let patterns = detector.analyze();
-    
+
     // 1. Save detected patterns to DB
-    for p in &patterns {
-        let proposal = crate::recommendation::AutomationProposal {
-            title: format!("New Pattern: {}", p.description),
-            summary: format!("Detected {} repeats. AI suggests automating this.", p.occurrences),
-            trigger: format!("Pattern Type: {:?}", p.pattern_type),
-            actions: vec!["Analyze".to_string(), "Automate".to_string()],
-            n8n_prompt: format!("Create an automation for: {}", p.description),
-            confidence: p.similarity_score,
-            evidence: vec![format!("Pattern: {}", p.description)],
-            pattern_id: Some(p.pattern_id.clone()),
-        };
-        if let Err(e) = db::insert_recommendation(&proposal) {
-            eprintln!("Failed to save pattern: {}", e);
-        }
-    }
+    let proposals: Vec<_> = patterns.iter().map(crate::recommendation::proposal_from_pattern).collect();
+    crate::recommendation::persist_proposals(&proposals);
 
     // 2. Fallback: If empty, create a random demo recommendation (For User Experience)
     // DISABLED: Random spam fix
@@ -1531,20 +2053,19 @@ fn run_analysis_internal() -> Vec<String> {
         .collect()
 }
 
-async fn get_quality_metrics() -> Json<QualityMetrics> {
+async fn get_quality_metrics() -> Json<QualityView> {
     let collector = feedback_collector::FeedbackCollector::new();
-    let metrics = collector.get_quality_metrics();
-    
-    Json(QualityMetrics {
-        total: metrics.total_executions,
-        success: metrics.successful_executions,
-        rate: metrics.success_rate,
-    })
+    Json(QualityView::from(collector.get_quality_metrics()))
 }
 
 #[derive(serde::Deserialize)]
 struct GoalRequest {
     goal: String,
+    #[serde(default)]
+    confirm_each: bool,
+    /// Read-only run: write actions are skipped at dispatch.
+    #[serde(default)]
+    observe: bool,
 }
 
 async fn execute_goal_handler(
@@ -1555,18 +2076,40 @@ async fn execute_goal_handler(
         *guard = Some(payload.goal.clone());
     }
     if let Some(llm) = state.llm_client {
+        let executor = crate::executor::AgentExecutor::new(llm).with_confirm_each(payload.confirm_each)
+            .with_observe(payload.observe)
+            .with_events(state.events.clone());
+        let session_key = executor.session_key().to_string();
+        let artifact_dir = executor.artifact_dir();
+        let task = crate::task_registry::register(crate::task_registry::TaskKind::Goal, &payload.goal)
+            .with_session(&session_key);
+        let task_id = task.id().to_string();
+        let current_goal = state.current_goal.clone();
         // Spawn background task for OODA loop
         tokio::spawn(async move {
-            let executor = crate::executor::AgentExecutor::new(llm);
-            match executor.execute_goal(&payload.goal).await {
-                Ok(res) => println!("✅ Goal Execution Success: {}", res),
-                Err(e) => println!("❌ Goal Execution Failed: {}", e),
+            match task.run(executor.execute_goal(&payload.goal)).await {
+                None => {
+                    println!("🛑 Goal cancelled: {}", payload.goal);
+                    // Stop reporting it as the current goal (unless another one started since)
+                    if let Ok(mut guard) = current_goal.lock() {
+                        if guard.as_deref() == Some(payload.goal.as_str()) {
+                            *guard = None;
+                        }
+                    }
+                }
+                Some(Ok(crate::executor::GoalOutcome::Replied(text))) => println!("💬 Goal answered: {}", text),
+                Some(Ok(crate::executor::GoalOutcome::Reported(text))) => println!("📣 Goal stopped and reported: {}", text),
+                Some(Ok(res)) => println!("✅ Goal Execution Success: {}", res),
+                Some(Err(e)) => println!("❌ Goal Execution Failed [{}]: {}", e.code(), e),
             }
         });
 
         Json(serde_json::json!({
             "status": "started",
-            "message": "Autonmous Agent started. Monitor logs for progress."
+            "message": "Autonmous Agent started. Monitor logs for progress.",
+            "session_key": session_key,
+            "artifact_dir": artifact_dir,
+            "task_id": task_id
         }))
     } else {
         Json(serde_json::json!({
@@ -1576,6 +2119,75 @@ async fn execute_goal_handler(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct OutlineRequest {
+    goal: String,
+}
+
+/// The plan a goal would start with, for a "here's my plan" confirmation
+/// before `POST /api/agent/goal`. Nothing is executed.
+async fn outline_goal_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<OutlineRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let Some(llm) = state.llm_client else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "status": "error", "message": "LLM Client not available" })));
+    };
+    match crate::executor::AgentExecutor::new(llm).outline(&payload.goal).await {
+        Ok(steps) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "goal": payload.goal, "steps": steps }))),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(agent_error_body(&e)),
+        ),
+    }
+}
+
+async fn list_tasks() -> Json<Vec<crate::task_registry::TaskInfo>> {
+    Json(crate::task_registry::list())
+}
+
+async fn cancel_task(Path(id): Path<String>) -> (StatusCode, Json<serde_json::Value>) {
+    if crate::task_registry::cancel(&id) {
+        (StatusCode::OK, Json(serde_json::json!({ "status": "cancelling", "id": id })))
+    } else {
+        (StatusCode::NOT_FOUND, Json(serde_json::json!({ "status": "error", "message": format!("No running task {}", id) })))
+    }
+}
+
+async fn list_step_confirmations() -> Json<Vec<crate::step_confirmation::PendingConfirmation>> {
+    Json(crate::step_confirmation::list_pending())
+}
+
+#[derive(Deserialize)]
+struct ResolveConfirmationRequest {
+    allow: bool,
+}
+
+async fn resolve_step_confirmation(
+    Path(id): Path<String>,
+    Json(payload): Json<ResolveConfirmationRequest>,
+) -> StatusCode {
+    if crate::step_confirmation::resolve(&id, payload.allow) {
+        StatusCode::OK
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Revert the most recent reversible goal step (see undo_stack).
+async fn undo_handler() -> Json<serde_json::Value> {
+    match crate::command_queue::enqueue_ui(crate::undo_stack::undo_last).await {
+        Ok(Some(entry)) => Json(serde_json::json!({
+            "status": "ok",
+            "undone": entry.step,
+            "inverse": entry.inverse.describe(),
+            "remaining": crate::undo_stack::pending(),
+        })),
+        Ok(None) => Json(serde_json::json!({ "status": "empty", "message": "Nothing to undo" })),
+        Err(e) => Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+    }
+}
+
 async fn get_current_goal(
     State(state): State<AppState>,
 ) -> Json<serde_json::Value> {
@@ -1783,15 +2395,102 @@ async fn handle_feedback(
     })
 }
 
+#[derive(serde::Deserialize)]
+struct UiSnapshotQuery {
+    scope: Option<String>,
+}
+
+async fn describe_screen_handler(State(state): State<AppState>) -> Json<crate::screen_describe::ScreenDescription> {
+    Json(crate::screen_describe::describe(state.llm_client.as_ref()).await)
+}
+
+async fn ui_snapshot_handler(Query(query): Query<UiSnapshotQuery>) -> Json<serde_json::Value> {
+    let tree = if crate::monitoring::is_capture_suppressed() {
+        json!({ "error": crate::monitoring::CAPTURE_SUPPRESSED })
+    } else {
+        crate::ui_snapshot::capture(query.scope.clone()).await
+    };
+    Json(crate::ui_snapshot::document(tree, query.scope.as_deref()))
+}
+
 // [Context] Selection Handler
 async fn get_selection_context() -> Json<serde_json::Value> {
     #[cfg(target_os = "macos")]
     {
-        match crate::macos::accessibility::get_selected_text() {
-            Some(text) => Json(serde_json::json!({ "found": true, "text": text })),
-            None => Json(serde_json::json!({ "found": false, "text": "" })),
+        match tokio::task::spawn_blocking(crate::selection::read).await {
+            Ok(Ok(text)) => Json(serde_json::json!({ "found": true, "text": text })),
+            _ => Json(serde_json::json!({ "found": false, "text": "" })),
         }
     }
     #[cfg(not(target_os = "macos"))]
     Json(serde_json::json!({ "found": false, "text": "", "error": "Not supported on this OS" }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_invalid_cron_is_a_bad_request() {
+        db::init().ok();
+        let payload = CreateRoutineRequest {
+            name: "bad cron".to_string(),
+            cron: "0 9 * * *".to_string(),
+            prompt: "noop".to_string(),
+            success_check: None,
+        };
+        let (code, Json(body)) = create_routine_handler(Json(payload)).await;
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert!(body["message"].as_str().unwrap().contains("5 fields"), "{}", body);
+
+        let (code, Json(body)) = write_error_response(crate::db::WriteError::NotFound("Routine #7 not found".to_string()));
+        assert_eq!(code, StatusCode::NOT_FOUND);
+        assert_eq!(body["message"], "Routine #7 not found");
+    }
+
+    #[test]
+    fn test_agent_error_body_shape() {
+        let body = agent_error_body(&crate::agent_error::AgentError::Timeout("too slow".to_string()));
+        assert_eq!(body, json!({ "status": "error", "code": "timeout", "message": "too slow" }));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_preview_saves_nothing() {
+        db::init().ok();
+        // A rule of its own, so its suggestion can't already be saved by another run
+        let tag = uuid::Uuid::new_v4().to_string();
+        let rules = std::env::temp_dir().join(format!("steer-rules-{}.json", tag));
+        let rule = json!([{ "category": "preview", "event_type": "file_created", "contains": tag, "suggestion": format!("File {}", tag) }]);
+        std::fs::write(&rules, rule.to_string()).unwrap();
+        std::env::set_var("STEER_ANALYZER_RULES_FILE", &rules);
+        let path = format!("/tmp/{}/report.pdf", tag);
+        let event = crate::schema::EventEnvelope::new("file_watcher", "filesystem", "file_created", "P2", None, json!({ "path": path }));
+        db::insert_event_v2(&event).unwrap();
+        let saved = || {
+            db::get_recommendations_with_filter(Some("all"))
+                .unwrap()
+                .iter()
+                .filter(|r| r.title.contains(&tag))
+                .count()
+        };
+
+        let Json(body) = analyze_handler(Query(AnalyzeQuery { persist: Some(false) })).await;
+        assert_eq!((body["persisted"].clone(), body["inserted"].clone()), (json!(false), json!(0)));
+        let proposals = body["proposals"].as_array().unwrap();
+        assert!(proposals.iter().any(|p| p["title"].as_str().unwrap_or_default().contains(&tag)), "{}", body);
+        assert_eq!(saved(), 0);
+
+        // The default saves it
+        let Json(body) = analyze_handler(Query(AnalyzeQuery { persist: None })).await;
+        assert!(body["inserted"].as_u64().unwrap() >= 1, "{}", body);
+        assert_eq!(saved(), 1);
+        std::env::remove_var("STEER_ANALYZER_RULES_FILE");
+        std::fs::remove_file(&rules).ok();
+    }
+
+    #[tokio::test]
+    async fn test_missing_llm_is_not_critical_in_lite_mode() {
+        let check = llm_check(None, std::time::Duration::from_millis(10)).await;
+        assert_eq!((check.name, check.ok, check.critical), ("llm", false, false));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::applescript::FrontApp;
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+
+// [App Focus] ACTIVATE used to send one `activate` and move on, so a step
+// after a slow-launching app typed into whatever was in front. Focus is now
+// confirmed: activate, wait for the screen to settle (not a fixed sleep), and
+// check the frontmost app, up to a configured number of attempts. The app in
+// front is compared by bundle id, since an app's name and its process name
+// can differ ("Visual Studio Code" runs as "Code"). Only the activation
+// itself takes the UI lane; settle waits run outside it, so a slow launch
+// doesn't hold up other goals' input. Apps on the slow list get twice the
+// settle time.
+//
+//   STEER_FOCUS_ATTEMPTS     attempts per ACTIVATE (default 3)
+//   STEER_FOCUS_SETTLE_MS    longest settle wait after each attempt (default 1500)
+//   STEER_SLOW_LAUNCH_APPS   apps whose settle wait is doubled (default Xcode, Microsoft Word, Microsoft Excel, Microsoft PowerPoint, Android Studio)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusRetry {
+    pub attempts: u32,
+    pub settle: Duration,
+}
+
+impl FocusRetry {
+    /// Settings for `app`, from the environment.
+    pub fn for_app(app: &str) -> Self {
+        let num = |key: &str, default: u64| std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(default);
+        let base = Self {
+            attempts: num("STEER_FOCUS_ATTEMPTS", 3).max(1) as u32,
+            settle: Duration::from_millis(num("STEER_FOCUS_SETTLE_MS", 1500)),
+        };
+        let slow = std::env::var("STEER_SLOW_LAUNCH_APPS").unwrap_or_else(|_| {
+            "Xcode, Microsoft Word, Microsoft Excel, Microsoft PowerPoint, Android Studio".to_string()
+        });
+        if slow.split(',').any(|s| same_app(s, app)) {
+            base.slowed()
+        } else {
+            base
+        }
+    }
+
+    fn slowed(self) -> Self {
+        Self { attempts: self.attempts, settle: self.settle * 2 }
+    }
+
+    /// Upper bound on how long `ensure_focus` can wait in settles.
+    pub fn worst_case(&self) -> Duration {
+        self.settle * self.attempts
+    }
+}
+
+/// Whether `front` is `app`: by bundle id when both are known, by name otherwise.
+fn is_app(front: &FrontApp, app: &str, bundle_id: Option<&str>) -> bool {
+    match bundle_id {
+        Some(id) if !front.bundle_id.is_empty() => front.bundle_id.eq_ignore_ascii_case(id),
+        _ => same_app(&front.name, app),
+    }
+}
+
+fn same_app(a: &str, b: &str) -> bool {
+    let norm = |s: &str| s.trim().trim_end_matches(".app").to_lowercase();
+    !norm(a).is_empty() && norm(a) == norm(b)
+}
+
+/// Activate `app` (bundle id `bundle_id`, when known) until it is frontmost;
+/// returns the attempts it took. `activate`, `frontmost` and `settle` are the
+/// side effects, so the retry logic can be tested without a desktop.
+pub async fn ensure_focus_with<A, F, S>(
+    app: &str,
+    bundle_id: Option<&str>,
+    retry: FocusRetry,
+    mut activate: impl FnMut() -> A,
+    mut frontmost: impl FnMut() -> F,
+    mut settle: impl FnMut(Duration) -> S,
+) -> Result<u32>
+where
+    A: Future<Output = Result<()>>,
+    F: Future<Output = Option<FrontApp>>,
+    S: Future<Output = ()>,
+{
+    let mut last_error = None;
+    let mut last_front = None;
+    for attempt in 1..=retry.attempts {
+        if let Err(e) = activate().await {
+            last_error = Some(e);
+        }
+        settle(retry.settle).await;
+        last_front = frontmost().await;
+        if last_front.as_ref().is_some_and(|front| is_app(front, app, bundle_id)) {
+            return Ok(attempt);
+        }
+    }
+    let front = last_front.map(|front| front.name).unwrap_or_else(|| "unknown".to_string());
+    match last_error {
+        Some(e) => Err(anyhow::anyhow!("{} not frontmost after {} attempt(s) (frontmost: {}): {}", app, retry.attempts, front, e)),
+        None => Err(anyhow::anyhow!("{} not frontmost after {} attempt(s) (frontmost: {})", app, retry.attempts, front)),
+    }
+}
+
+/// `ensure_focus_with` on the real desktop.
+pub async fn ensure_app_focus(app: &str, retry: FocusRetry) -> Result<u32> {
+    let lookup = app.to_string();
+    let bundle_id = tokio::task::spawn_blocking(move || crate::applescript::bundle_id_of(&lookup).ok())
+        .await
+        .ok()
+        .flatten();
+    ensure_focus_with(
+        app,
+        bundle_id.as_deref(),
+        retry,
+        || {
+            let app = app.to_string();
+            async move {
+                match crate::command_queue::enqueue_ui_timeout(Duration::from_secs(5), move || crate::applescript::activate_app(&app)).await {
+                    Ok(result) => result.map(|_| ()),
+                    Err(_) => Err(anyhow::anyhow!("activate timed out")),
+                }
+            }
+        },
+        || async {
+            tokio::task::spawn_blocking(|| {
+                crate::applescript::frontmost_app().ok().or_else(|| {
+                    let name = crate::applescript::frontmost_app_name().ok()?;
+                    Some(FrontApp { name: name.trim().to_string(), bundle_id: String::new() })
+                })
+            })
+            .await
+            .ok()
+            .flatten()
+        },
+        |max| async move {
+            let _ = tokio::task::spawn_blocking(move || crate::screen_cache::wait_for_screen_settle(max)).await;
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn front(name: &str, bundle_id: &str) -> Option<FrontApp> {
+        Some(FrontApp { name: name.to_string(), bundle_id: bundle_id.to_string() })
+    }
+
+    #[tokio::test]
+    async fn test_focus_retries_until_the_app_settles_in_front() {
+        let retry = FocusRetry { attempts: 3, settle: Duration::from_millis(200) };
+        let activations = Cell::new(0);
+        let settles = Cell::new(0);
+        // Xcode only comes to the front on the second attempt
+        let attempts = ensure_focus_with(
+            "Xcode",
+            Some("com.apple.dt.Xcode"),
+            retry,
+            || {
+                activations.set(activations.get() + 1);
+                async { Ok(()) }
+            },
+            || async {
+                if activations.get() >= 2 { front("Xcode", "com.apple.dt.Xcode") } else { front("Finder", "com.apple.finder") }
+            },
+            |max| {
+                assert_eq!(max, Duration::from_millis(200));
+                settles.set(settles.get() + 1);
+                async {}
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!((attempts, activations.get(), settles.get()), (2, 2, 2));
+
+        let err = ensure_focus_with("Notes", None, retry, || async { Ok(()) }, || async { front("Finder", "") }, |_| async {})
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Notes not frontmost after 3 attempt(s) (frontmost: Finder)");
+        let err = ensure_focus_with("Notes", None, retry, || async { Err(anyhow::anyhow!("no such app")) }, || async { None }, |_| async {})
+            .await
+            .unwrap_err();
+        assert!(err.to_string().ends_with("(frontmost: unknown): no such app"), "{}", err);
+
+        assert!(ensure_focus_with("notes.app", None, retry, || async { Ok(()) }, || async { front("Notes", "") }, |_| async {})
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_focus_is_confirmed_by_bundle_id() {
+        let retry = FocusRetry { attempts: 1, settle: Duration::ZERO };
+        // The process of "Visual Studio Code" is called "Code"
+        let vscode = Some("com.microsoft.VSCode");
+        assert!(ensure_focus_with("Visual Studio Code", vscode, retry, || async { Ok(()) }, || async { front("Code", "com.microsoft.VSCode") }, |_| async {})
+            .await
+            .is_ok());
+        // Same name, another app
+        assert!(ensure_focus_with("Notes", Some("com.apple.Notes"), retry, || async { Ok(()) }, || async { front("Notes", "com.example.notes") }, |_| async {})
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_slow_apps_get_more_time() {
+        let base = FocusRetry { attempts: 3, settle: Duration::from_millis(1500) };
+        // Longer settles, not more of them
+        assert_eq!(base.slowed(), FocusRetry { attempts: 3, settle: Duration::from_millis(3000) });
+        assert_eq!(base.worst_case(), Duration::from_millis(4500));
+    }
+}
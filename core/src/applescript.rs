@@ -1,7 +1,107 @@
 use std::process::Command;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+// --- Timing Metrics (opt-in via STEER_APPLESCRIPT_METRICS) ---
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScriptStats {
+    pub calls: u64,
+    pub failures: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+lazy_static! {
+    static ref SCRIPT_STATS: Mutex<HashMap<String, ScriptStats>> = Mutex::new(HashMap::new());
+    /// Read once; every AppleScript call goes through `timed`.
+    static ref METRICS_ENABLED: bool = std::env::var("STEER_APPLESCRIPT_METRICS")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false);
+}
+
+/// Run `f` and, when metrics are enabled, record its duration under `purpose`.
+pub fn timed<T>(purpose: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    timed_if(*METRICS_ENABLED, purpose, f)
+}
+
+/// Run `command` like `timed`; a non-zero exit is an error (and a failure in the stats).
+pub fn timed_output(purpose: &str, command: &mut Command) -> Result<std::process::Output> {
+    timed(purpose, || checked_output(command))
+}
+
+fn checked_output(command: &mut Command) -> Result<std::process::Output> {
+    let output = command.output().with_context(|| format!("Failed to run {:?}", command.get_program()))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{:?} exited with {}: {}",
+            command.get_program(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output)
+}
+
+fn timed_if<T>(enabled: bool, purpose: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    if !enabled {
+        return f();
+    }
+    let started = std::time::Instant::now();
+    let result = f();
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    if let Ok(mut stats) = SCRIPT_STATS.lock() {
+        let entry = stats.entry(purpose.to_string()).or_default();
+        entry.calls += 1;
+        entry.total_ms += elapsed_ms;
+        entry.max_ms = entry.max_ms.max(elapsed_ms);
+        if result.is_err() {
+            entry.failures += 1;
+        }
+    }
+    result
+}
+
+pub fn script_stats() -> HashMap<String, ScriptStats> {
+    SCRIPT_STATS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Prometheus-style text exposition (empty when metrics are disabled).
+pub fn render_metrics() -> String {
+    let stats = script_stats();
+    if stats.is_empty() {
+        return String::new();
+    }
+    let mut names: Vec<&String> = stats.keys().collect();
+    names.sort();
+    let mut out = String::from(
+        "# TYPE steer_applescript_calls_total counter\n\
+# TYPE steer_applescript_failures_total counter\n\
+# TYPE steer_applescript_duration_ms_total counter\n\
+# TYPE steer_applescript_duration_ms_max gauge\n",
+    );
+    for name in names {
+        let s = &stats[name];
+        out.push_str(&format!("steer_applescript_calls_total{{purpose=\"{}\"}} {}\n", name, s.calls));
+        out.push_str(&format!("steer_applescript_failures_total{{purpose=\"{}\"}} {}\n", name, s.failures));
+        out.push_str(&format!("steer_applescript_duration_ms_total{{purpose=\"{}\"}} {}\n", name, s.total_ms));
+        out.push_str(&format!("steer_applescript_duration_ms_max{{purpose=\"{}\"}} {}\n", name, s.max_ms));
+    }
+    out
+}
 
 pub fn run(script: &str) -> Result<String> {
+    run_named("run", script)
+}
+
+pub fn run_named(purpose: &str, script: &str) -> Result<String> {
+    timed(purpose, || run_raw(script))
+}
+
+fn run_raw(script: &str) -> Result<String> {
     #[cfg(target_os = "macos")]
     {
         let output = Command::new("osascript")
@@ -36,12 +136,310 @@ pub fn control_app(app: &str, command: &str) -> Result<String> {
     };
     
     
-    run(script)
+    run_named("control_app", script)
 }
 
 pub fn activate_app(app: &str) -> Result<String> {
     let script = format!("tell application {:?} to activate", app);
-    run(&script)
+    run_named("activate_app", &script)
+}
+
+/// Bundle id of the app installed as `app`, from Launch Services; the app
+/// isn't launched.
+pub fn bundle_id_of(app: &str) -> Result<String> {
+    let script = format!("id of application {:?}", app);
+    let id = run_named("bundle_id_of", &script)?.trim().to_string();
+    if id.is_empty() || id == "missing value" {
+        return Err(anyhow::anyhow!("No bundle id for {}", app));
+    }
+    Ok(id)
+}
+
+/// Bring the app with `bundle_id` to the front.
+pub fn activate_bundle(bundle_id: &str) -> Result<()> {
+    let script = format!("tell application id {:?} to activate", bundle_id);
+    run_named("activate_bundle", &script).map(|_| ())
+}
+
+/// Hide the app with `bundle_id` (like Cmd+H); it keeps running. By bundle
+/// id, since an app's name ("Visual Studio Code") and its process name
+/// ("Code") can differ.
+pub fn hide_app(bundle_id: &str) -> Result<()> {
+    let script = format!(
+        "tell application \"System Events\" to set visible of (first application process whose bundle identifier is {:?}) to false",
+        bundle_id
+    );
+    run_named("hide_app", &script).map(|_| ())
+}
+
+/// Press Delete `chars` times in the frontmost app.
+pub fn delete_backward(chars: usize) -> Result<()> {
+    if chars == 0 {
+        return Ok(());
+    }
+    let script = format!(
+        "tell application \"System Events\"\nrepeat {} times\nkey code 51\nend repeat\nend tell",
+        chars
+    );
+    run_named("delete_backward", &script).map(|_| ())
+}
+
+/// Raise the first window of `app` whose title contains `title_contains`
+/// (case-insensitive). Returns false when no window matches.
+pub fn focus_window(app: &str, title_contains: &str) -> Result<bool> {
+    let lines = [
+        "on run argv",
+        "set appName to item 1 of argv",
+        "set needle to item 2 of argv",
+        "tell application appName to activate",
+        "tell application \"System Events\" to tell process appName",
+        "repeat with w in windows",
+        "if (name of w as text) contains needle then",
+        "perform action \"AXRaise\" of w",
+        "set frontmost to true",
+        "return \"true\"",
+        "end if",
+        "end repeat",
+        "end tell",
+        "return \"false\"",
+        "end run",
+    ];
+    let out = timed("focus_window", || {
+        run_lines_with_args(&lines, &[app.to_string(), title_contains.to_string()])
+    })?;
+    Ok(out.trim() == "true")
+}
+
+/// Paste `text` into the frontmost app via the clipboard, restoring the
+/// previous clipboard text afterwards. Only the Cmd+V keystroke is AppleScript.
+pub fn paste_text(text: &str) -> Result<()> {
+    crate::clipboard::paste_with(
+        text,
+        crate::clipboard::get_text,
+        crate::clipboard::set_text,
+        || run_named("paste_text", "tell application \"System Events\" to keystroke \"v\" using command down").map(|_| ()),
+        || {
+            crate::screen_cache::wait_for_ui_settle(std::time::Duration::from_millis(1500));
+        },
+    )
+}
+
+/// The value of the focused UI element of the frontmost app (a text field's
+/// or document's text).
+pub fn focused_value() -> Result<String> {
+    run_named(
+        "focused_value",
+        "tell application \"System Events\"\n\
+         set frontProc to first application process whose frontmost is true\n\
+         set focusedElement to value of attribute \"AXFocusedUIElement\" of frontProc\n\
+         return value of focusedElement as text\n\
+         end tell",
+    )
+}
+
+/// Replace the value of the focused UI element of the frontmost app.
+pub fn set_focused_value(text: &str) -> Result<()> {
+    let lines = [
+        "on run argv",
+        "tell application \"System Events\"",
+        "set frontProc to first application process whose frontmost is true",
+        "set focusedElement to value of attribute \"AXFocusedUIElement\" of frontProc",
+        "set value of focusedElement to item 1 of argv",
+        "end tell",
+        "end run",
+    ];
+    timed("set_focused_value", || run_lines_with_args(&lines, &[text.to_string()]))?;
+    Ok(())
+}
+
+/// Buttons tried, in order, on each dialog before falling back to Escape.
+pub const DISMISS_BUTTONS: &[&str] = &["Cancel", "취소", "닫기", "Close", "Not Now", "나중에"];
+
+/// Close the sheets and dialog windows of the frontmost app, at most `max`
+/// of them. Each is closed with the first of `DISMISS_BUTTONS` it has, or
+/// Escape when it has none (after which we stop, since Escape can't be
+/// confirmed). Returns what was pressed, one entry per dialog.
+pub fn dismiss_dialogs(max: usize) -> Result<Vec<String>> {
+    let lines = [
+        "on run argv",
+        "set maxCount to (item 1 of argv) as integer",
+        "set buttonNames to rest of argv",
+        "set dismissed to {}",
+        "tell application \"System Events\"",
+        "set frontProc to first application process whose frontmost is true",
+        "repeat maxCount times",
+        "set dlg to missing value",
+        "try",
+        "set dlg to sheet 1 of window 1 of frontProc",
+        "end try",
+        "if dlg is missing value then",
+        "repeat with w in windows of frontProc",
+        "try",
+        "if subrole of w is in {\"AXDialog\", \"AXSystemDialog\"} then",
+        "set dlg to contents of w",
+        "exit repeat",
+        "end if",
+        "end try",
+        "end repeat",
+        "end if",
+        "if dlg is missing value then exit repeat",
+        "set pressed to missing value",
+        "repeat with b in buttonNames",
+        "if exists button (b as text) of dlg then",
+        "click button (b as text) of dlg",
+        "set pressed to (b as text)",
+        "exit repeat",
+        "end if",
+        "end repeat",
+        "if pressed is missing value then",
+        "key code 53",
+        "set end of dismissed to \"Escape\"",
+        "exit repeat",
+        "end if",
+        "set end of dismissed to pressed",
+        "delay 0.3",
+        "end repeat",
+        "end tell",
+        "set AppleScript's text item delimiters to linefeed",
+        "return dismissed as text",
+        "end run",
+    ];
+    let mut args = vec![max.to_string()];
+    args.extend(DISMISS_BUTTONS.iter().map(|b| b.to_string()));
+    let out = timed("dismiss_dialogs", || run_lines_with_args(&lines, &args))?;
+    Ok(out.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect())
+}
+
+/// Named UI elements of the frontmost window, as (role, name), at most `limit`.
+pub fn window_elements(limit: usize) -> Result<Vec<(String, String)>> {
+    let lines = [
+        "on run argv",
+        "set maxCount to (item 1 of argv) as integer",
+        "set found to {}",
+        "tell application \"System Events\"",
+        "set frontProc to first application process whose frontmost is true",
+        "repeat with el in (entire contents of window 1 of frontProc)",
+        "try",
+        "set elName to name of el",
+        "if elName is not missing value and elName is not \"\" then",
+        "set end of found to (role of el as text) & tab & (elName as text)",
+        "if (count of found) >= maxCount then exit repeat",
+        "end if",
+        "end try",
+        "end repeat",
+        "end tell",
+        "set AppleScript's text item delimiters to linefeed",
+        "return found as text",
+        "end run",
+    ];
+    let out = timed("window_elements", || run_lines_with_args(&lines, &[limit.to_string()]))?;
+    Ok(out
+        .lines()
+        .filter_map(|l| l.split_once('\t'))
+        .map(|(role, name)| (role.trim().to_string(), name.trim().to_string()))
+        .collect())
+}
+
+/// Click the first element of the frontmost window with this role and name.
+/// False if there is none.
+pub fn click_window_element(role: &str, name: &str) -> Result<bool> {
+    let lines = [
+        "on run argv",
+        "set wantRole to item 1 of argv",
+        "set wantName to item 2 of argv",
+        "tell application \"System Events\"",
+        "set frontProc to first application process whose frontmost is true",
+        "repeat with el in (entire contents of window 1 of frontProc)",
+        "try",
+        "if (role of el as text) is wantRole and (name of el as text) is wantName then",
+        "click el",
+        "return \"true\"",
+        "end if",
+        "end try",
+        "end repeat",
+        "end tell",
+        "return \"false\"",
+        "end run",
+    ];
+    let out = timed("click_window_element", || {
+        run_lines_with_args(&lines, &[role.to_string(), name.to_string()])
+    })?;
+    Ok(out.trim() == "true")
+}
+
+/// Screen frame ("x, y, width, height", top-left origin) of the first element of
+/// the frontmost window with this role and name. None if there is none.
+pub fn window_element_frame(role: &str, name: &str) -> Result<Option<String>> {
+    let lines = [
+        "on run argv",
+        "set wantRole to item 1 of argv",
+        "set wantName to item 2 of argv",
+        "tell application \"System Events\"",
+        "set frontProc to first application process whose frontmost is true",
+        "repeat with el in (entire contents of window 1 of frontProc)",
+        "try",
+        "if (role of el as text) is wantRole and (name of el as text) is wantName then",
+        "set {x, y} to position of el",
+        "set {w, h} to size of el",
+        "return (x as text) & \", \" & (y as text) & \", \" & (w as text) & \", \" & (h as text)",
+        "end if",
+        "end try",
+        "end repeat",
+        "end tell",
+        "return \"\"",
+        "end run",
+    ];
+    let out = timed("window_element_frame", || {
+        run_lines_with_args(&lines, &[role.to_string(), name.to_string()])
+    })?;
+    Ok(Some(out.trim().to_string()).filter(|o| !o.is_empty()))
+}
+
+/// Frame ("x, y, width, height") of each window of `app`, or of the
+/// frontmost app's first window when `app` is None. One line per window.
+/// `app` matches a process by name, displayed name or bundle id (an app's
+/// process can be named differently from the app); None when nothing does.
+pub fn window_frames(app: Option<&str>) -> Result<Option<Vec<String>>> {
+    let lines = [
+        "on run argv",
+        "set out to \"\"",
+        "tell application \"System Events\"",
+        "if (count of argv) is 0 then",
+        "set targetWindows to {window 1 of (first application process whose frontmost is true)}",
+        "else",
+        "set target to item 1 of argv",
+        "set procs to (every process whose name is target or displayed name is target or bundle identifier is target)",
+        "if (count of procs) is 0 then return \"no process\"",
+        "set targetWindows to {}",
+        "repeat with p in procs",
+        "set targetWindows to targetWindows & (windows of p)",
+        "end repeat",
+        "end if",
+        "repeat with w in targetWindows",
+        "set {x, y} to position of w",
+        "set {ww, hh} to size of w",
+        "set out to out & (x as text) & \", \" & (y as text) & \", \" & (ww as text) & \", \" & (hh as text) & linefeed",
+        "end repeat",
+        "end tell",
+        "return out",
+        "end run",
+    ];
+    let args: Vec<String> = app.map(|a| vec![a.to_string()]).unwrap_or_default();
+    let out = timed("window_frames", || run_lines_with_args(&lines, &args))?;
+    if out.trim() == "no process" {
+        return Ok(None);
+    }
+    Ok(Some(out.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect()))
+}
+
+/// Size of the main display in points ("width, height").
+pub fn desktop_size() -> Result<String> {
+    let out = run_named(
+        "desktop_size",
+        r#"tell application "Finder" to set {x1, y1, x2, y2} to bounds of window of desktop
+return ((x2 - x1) as text) & ", " & ((y2 - y1) as text)"#,
+    )?;
+    Ok(out.trim().to_string())
 }
 
 pub fn execute_js_in_chrome(script: &str) -> Result<String> {
@@ -52,7 +450,18 @@ pub fn execute_js_in_chrome(script: &str) -> Result<String> {
         "tell application \"Google Chrome\" to execute javascript js in active tab of front window",
         "end run",
     ];
-    run_lines_with_args(&lines, &[script.to_string()])
+    timed("chrome_js", || run_lines_with_args(&lines, &[script.to_string()]))
+}
+
+pub fn execute_js_in_safari(script: &str) -> Result<String> {
+    // Needs "Allow JavaScript from Apple Events" in Safari's Develop menu.
+    let lines = [
+        "on run argv",
+        "set js to item 1 of argv",
+        "tell application \"Safari\" to do JavaScript js in front document",
+        "end run",
+    ];
+    timed("safari_js", || run_lines_with_args(&lines, &[script.to_string()]))
 }
 
 pub fn activate_frontmost_app() -> Result<String> {
@@ -63,7 +472,54 @@ pub fn activate_frontmost_app() -> Result<String> {
         tell application frontApp to activate
         return frontApp
     "#;
-    run(script)
+    run_named("activate_frontmost_app", script)
+}
+
+pub fn frontmost_app_name() -> Result<String> {
+    run_named(
+        "frontmost_app",
+        r#"tell application "System Events" to name of first application process whose frontmost is true"#,
+    )
+}
+
+/// The frontmost app's process name and bundle id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontApp {
+    pub name: String,
+    pub bundle_id: String,
+}
+
+pub fn frontmost_app() -> Result<FrontApp> {
+    let out = run_named(
+        "frontmost_app_id",
+        "tell application \"System Events\"\n\
+         set p to first application process whose frontmost is true\n\
+         return (name of p) & tab & (bundle identifier of p)\n\
+         end tell",
+    )?;
+    match out.trim().split_once('\t') {
+        Some((name, bundle_id)) if !bundle_id.is_empty() && bundle_id != "missing value" => {
+            Ok(FrontApp { name: name.to_string(), bundle_id: bundle_id.to_string() })
+        }
+        _ => Err(anyhow::anyhow!("No bundle id for the frontmost app: {}", out.trim())),
+    }
+}
+
+/// Identity of the frontmost app's focused UI element (app, role, name and
+/// position), to tell whether focus moved since.
+pub fn focused_element() -> Result<String> {
+    run_named(
+        "focused_element",
+        "tell application \"System Events\"\n\
+         set p to first application process whose frontmost is true\n\
+         set e to value of attribute \"AXFocusedUIElement\" of p\n\
+         set info to \"\"\n\
+         try\n\
+         set info to (role of e) & \"|\" & (name of e as text) & \"|\" & ((position of e) as text)\n\
+         end try\n\
+         return (bundle identifier of p) & \"|\" & info\n\
+         end tell",
+    )
 }
 
 pub fn get_active_window_context() -> Result<(String, String)> {
@@ -104,7 +560,7 @@ pub fn get_active_window_context() -> Result<(String, String)> {
         return windowTitle & "|||" & browserUrl
     "#;
 
-    let output = run(script)?;
+    let output = run_named("window_context", script)?;
     let parts: Vec<&str> = output.split("|||").collect();
     let title = parts.get(0).unwrap_or(&"").trim().to_string();
     let url = parts.get(1).unwrap_or(&"").trim().to_string();
@@ -139,3 +595,21 @@ fn run_lines_with_args(lines: &[&str], args: &[String]) -> Result<String> {
         Ok("AppleScript functionality is only available on macOS.".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_zero_exit_is_a_failure() {
+        let purpose = format!("test_exit_{}", uuid::Uuid::new_v4());
+        let exit = |code: &str| timed_if(true, &purpose, || checked_output(Command::new("sh").arg("-c").arg(format!("echo oops >&2; exit {}", code))));
+
+        let err = exit("3").unwrap_err();
+        assert!(err.to_string().contains("oops"), "{}", err);
+        assert!(exit("0").is_ok());
+        let stats = script_stats().remove(&purpose).unwrap();
+        assert_eq!((stats.calls, stats.failures), (2, 1));
+        assert!(render_metrics().contains(&format!("steer_applescript_failures_total{{purpose=\"{}\"}} 1", purpose)));
+    }
+}
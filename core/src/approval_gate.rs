@@ -147,3 +147,107 @@ fn risk_level(action: &str, plan: &Plan) -> String {
 fn policy_key(action: &str, plan: &Plan) -> String {
     format!("{}::{}", plan.intent.as_str(), action.to_lowercase())
 }
+
+// [Exec Policies] Exec approvals are time-boxed, so an "allow-always" answer
+// used to be forgotten after an hour (or a restart) and the command asked
+// again. The decision is now also stored in `nl_approval_policies` under an
+// exec key and checked before prompting, so it holds across sessions.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecPolicy {
+    AllowAlways,
+    DenyAlways,
+}
+
+/// `exec::<cwd>::<command>`: the command with whitespace collapsed and
+/// trailing `;` dropped, case kept (shell commands are case-sensitive); the
+/// cwd without a trailing `/`, or `*` when there is none.
+pub fn exec_policy_key(command: &str, cwd: Option<&str>) -> String {
+    let command = command.split_whitespace().collect::<Vec<_>>().join(" ");
+    let command = command.trim_end_matches(';').trim_end();
+    let cwd = cwd
+        .map(|c| c.trim().trim_end_matches('/'))
+        .filter(|c| !c.is_empty())
+        .unwrap_or("*");
+    format!("exec::{}::{}", cwd, command)
+}
+
+/// The saved decision for running `command` in `cwd`, if any.
+pub fn exec_policy(command: &str, cwd: Option<&str>) -> Option<ExecPolicy> {
+    match db::get_approval_policy_decision(&exec_policy_key(command, cwd)).ok()??.as_str() {
+        "allow_always" => Some(ExecPolicy::AllowAlways),
+        "deny_always" => Some(ExecPolicy::DenyAlways),
+        _ => None,
+    }
+}
+
+/// Blocks a saved allow-always can lift: the ones an approval is for (not on
+/// the shell allowlist, dangerous). Tool and app lists, safe mode, the write
+/// lock and disabled actions still apply.
+pub fn allow_always_overrides(reason: crate::policy::BlockReason) -> bool {
+    use crate::policy::BlockReason;
+    matches!(reason, BlockReason::ShellNotAllowed | BlockReason::DangerousCommand)
+}
+
+/// Save an `allow-always` / `deny-always` answer; other decisions are not kept.
+pub fn remember_exec_decision(command: &str, cwd: Option<&str>, decision: &str) -> Option<ExecPolicy> {
+    let (policy, stored) = match decision.trim().to_lowercase().replace('-', "_").as_str() {
+        "allow_always" => (ExecPolicy::AllowAlways, "allow_always"),
+        "deny_always" => (ExecPolicy::DenyAlways, "deny_always"),
+        _ => return None,
+    };
+    db::upsert_approval_policy(&exec_policy_key(command, cwd), stored).ok()?;
+    Some(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exec_policy_key_normalizes_the_command() {
+        assert_eq!(exec_policy_key("  git   status ; ", Some("/repo/")), "exec::/repo::git status");
+        assert_eq!(exec_policy_key("git status", Some("/repo")), exec_policy_key("git  status;", Some("/repo")));
+        assert_ne!(exec_policy_key("ls", Some("/a")), exec_policy_key("ls", Some("/b")));
+        assert_ne!(exec_policy_key("LS", None), exec_policy_key("ls", None));
+        assert_eq!(exec_policy_key("ls", None), "exec::*::ls");
+    }
+
+    #[test]
+    fn test_allow_always_skips_the_prompt_on_a_later_run() {
+        db::init().ok();
+        let cwd = format!("/tmp/steer-exec-{}", uuid::Uuid::new_v4());
+        let cwd = Some(cwd.as_str());
+        assert_eq!(exec_policy("make deploy", cwd), None);
+
+        // Only "always" answers are kept
+        assert_eq!(remember_exec_decision("make deploy", cwd, "allow-once"), None);
+        assert_eq!(exec_policy("make deploy", cwd), None);
+
+        assert_eq!(remember_exec_decision("make deploy", cwd, "allow-always"), Some(ExecPolicy::AllowAlways));
+        // A later run (new session, same store) finds it, however it is spaced
+        assert_eq!(exec_policy("make   deploy;", cwd), Some(ExecPolicy::AllowAlways));
+        assert_eq!(exec_policy("make deploy", Some("/elsewhere")), None);
+
+        remember_exec_decision("make deploy", cwd, "deny-always");
+        assert_eq!(exec_policy("make deploy", cwd), Some(ExecPolicy::DenyAlways));
+        db::delete_approval_policy(&exec_policy_key("make deploy", cwd)).unwrap();
+        assert_eq!(exec_policy("make deploy", cwd), None);
+    }
+
+    #[test]
+    fn test_allow_always_only_lifts_shell_approval_blocks() {
+        use crate::policy::BlockReason;
+        assert!(allow_always_overrides(BlockReason::ShellNotAllowed));
+        assert!(allow_always_overrides(BlockReason::DangerousCommand));
+        for reason in [
+            BlockReason::ToolPolicy,
+            BlockReason::AppNotAllowed,
+            BlockReason::SafeMode,
+            BlockReason::WriteLock,
+            BlockReason::ActionDisabled,
+        ] {
+            assert!(!allow_always_overrides(reason), "{:?}", reason);
+        }
+    }
+}
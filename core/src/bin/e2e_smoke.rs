@@ -18,8 +18,11 @@ mod project_scanner;
 mod llm_gateway;
 #[path = "../context_pruning.rs"]
 mod context_pruning;
+#[path = "../llm_budget.rs"]
+mod llm_budget;
+#[path = "../llm_pacing.rs"]
+mod llm_pacing;
 
-use chrono::Utc;
 use recommendation::TemplateMatcher;
 use schema::{EventEnvelope, ResourceContext};
 
@@ -68,99 +71,61 @@ fn main() {
 }
 
 fn build_sample_events() -> Vec<EventEnvelope> {
-    let now = Utc::now().to_rfc3339();
+    let resource = |resource_type: &str, id: &str| {
+        Some(ResourceContext {
+            resource_type: resource_type.to_string(),
+            id: id.to_string(),
+        })
+    };
     let mut events = Vec::new();
 
     // App switch flow (Slack <-> Chrome) repeated to trigger AppSequence
     for _ in 0..5 {
-        events.push(EventEnvelope {
-            schema_version: "1.0".to_string(),
-            event_id: uuid::Uuid::new_v4().to_string(),
-            ts: now.clone(),
-            source: "e2e_smoke".to_string(),
-            app: "Slack".to_string(),
-            event_type: "app_switch".to_string(),
-            priority: "P2".to_string(),
-            resource: Some(ResourceContext {
-                resource_type: "app".to_string(),
-                id: "Slack".to_string(),
-            }),
-            payload: serde_json::json!({"app":"Slack","window_title":"Inbox","browser_url":"https://mail.google.com"}),
-            privacy: None,
-            pid: None,
-            window_id: None,
-            window_title: None,
-            browser_url: Some("https://mail.google.com".to_string()),
-            raw: None,
-        });
-        events.push(EventEnvelope {
-            schema_version: "1.0".to_string(),
-            event_id: uuid::Uuid::new_v4().to_string(),
-            ts: now.clone(),
-            source: "e2e_smoke".to_string(),
-            app: "Chrome".to_string(),
-            event_type: "app_switch".to_string(),
-            priority: "P2".to_string(),
-            resource: Some(ResourceContext {
-                resource_type: "app".to_string(),
-                id: "Chrome".to_string(),
-            }),
-            payload: serde_json::json!({"app":"Chrome","window_title":"Docs","browser_url":"https://docs.google.com"}),
-            privacy: None,
-            pid: None,
-            window_id: None,
-            window_title: None,
-            browser_url: Some("https://docs.google.com".to_string()),
-            raw: None,
-        });
+        let mut slack = EventEnvelope::new(
+            "e2e_smoke",
+            "Slack",
+            "app_switch",
+            "P2",
+            resource("app", "Slack"),
+            serde_json::json!({"app":"Slack","window_title":"Inbox","browser_url":"https://mail.google.com"}),
+        );
+        slack.browser_url = Some("https://mail.google.com".to_string());
+        events.push(slack);
+        let mut chrome = EventEnvelope::new(
+            "e2e_smoke",
+            "Chrome",
+            "app_switch",
+            "P2",
+            resource("app", "Chrome"),
+            serde_json::json!({"app":"Chrome","window_title":"Docs","browser_url":"https://docs.google.com"}),
+        );
+        chrome.browser_url = Some("https://docs.google.com".to_string());
+        events.push(chrome);
     }
 
     // File pattern (3 pdfs)
     for i in 1..=3 {
-        events.push(EventEnvelope {
-            schema_version: "1.0".to_string(),
-            event_id: uuid::Uuid::new_v4().to_string(),
-            ts: now.clone(),
-            source: "e2e_smoke".to_string(),
-            app: "Finder".to_string(),
-            event_type: "file_created".to_string(),
-            priority: "P2".to_string(),
-            resource: Some(ResourceContext {
-                resource_type: "file".to_string(),
-                id: format!("/Users/test/Downloads/report{}.pdf", i),
-            }),
-            payload: serde_json::json!({"path":format!("/Users/test/Downloads/report{}.pdf", i),"filename":format!("report{}.pdf", i)}),
-            privacy: None,
-            pid: None,
-            window_id: None,
-            window_title: None,
-            browser_url: None,
-            raw: None,
-        });
+        let path = format!("/Users/test/Downloads/report{}.pdf", i);
+        events.push(EventEnvelope::new(
+            "e2e_smoke",
+            "Finder",
+            "file_created",
+            "P2",
+            resource("file", &path),
+            serde_json::json!({"path":path,"filename":format!("report{}.pdf", i)}),
+        ));
     }
 
     // Keyword repeat (5 occurrences)
     for _ in 0..5 {
-        events.push(EventEnvelope {
-            schema_version: "1.0".to_string(),
-            event_id: uuid::Uuid::new_v4().to_string(),
-            ts: now.clone(),
-            source: "e2e_smoke".to_string(),
-            app: "Mail".to_string(),
-            event_type: "key_input".to_string(),
-            priority: "P2".to_string(),
-            resource: Some(ResourceContext {
-                resource_type: "input".to_string(),
-                id: "keyboard".to_string(),
-            }),
-            payload: serde_json::json!({"text":"invoice follow-up"}),
-            privacy: None,
-            pid: None,
-            window_id: None,
-            window_title: None,
-            browser_url: None,
-            raw: None,
-        });
+        events.push(EventEnvelope::new(
+            "e2e_smoke",
+            "Mail",
+            "key_input",
+            "P2",
+            resource("input", "keyboard"),
+            serde_json::json!({"text":"invoice follow-up"}),
+        ));
     }
 
     events
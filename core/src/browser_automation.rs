@@ -84,7 +84,7 @@ pub fn fill_flight_fields(from: &str, to: &str, date_start: &str, date_end: Opti
             return String(filled);
         }})()"#
     );
-    let res = applescript::execute_js_in_chrome(&js)?;
+    let res = execute_js(&js)?;
     Ok(res.trim().parse::<i32>().unwrap_or(0) > 0)
 }
 
@@ -110,7 +110,7 @@ pub fn fill_search_query(query: &str) -> Result<bool> {
             return '1';
         }})()"#
     );
-    let res = applescript::execute_js_in_chrome(&js)?;
+    let res = execute_js(&js)?;
     Ok(res.trim() == "1")
 }
 
@@ -139,7 +139,7 @@ pub fn click_search_button() -> Result<bool> {
         }
         return '0';
     })()"#;
-    let res = applescript::execute_js_in_chrome(js)?;
+    let res = execute_js(js)?;
     Ok(res.trim() == "1")
 }
 
@@ -217,7 +217,7 @@ pub fn autofill_form(name: Option<&str>, email: Option<&str>, phone: Option<&str
             return String(filled);
         }})()"#
     );
-    let res = applescript::execute_js_in_chrome(&js)?;
+    let res = execute_js(&js)?;
     Ok(res.trim().parse::<i32>().unwrap_or(0) > 0)
 }
 
@@ -228,7 +228,85 @@ pub fn get_page_context() -> Result<String> {
         const domain = location.hostname || '';
         return JSON.stringify({ title, url, domain });
     })()"#;
-    applescript::execute_js_in_chrome(js)
+    execute_js(js)
+}
+
+/// Click the first link matching `selector` in the active tab. Returns
+/// its URL, or None if the page has no such link (yet).
+pub fn click_first_result(selector: &str) -> Result<Option<String>> {
+    let js = format!(
+        r#"(() => {{
+            for (const el of document.querySelectorAll({selector:?})) {{
+                const link = el.closest('a') || el;
+                if (!link.href || !link.href.startsWith('http')) continue;
+                const rect = link.getBoundingClientRect();
+                if (rect.width === 0 || rect.height === 0) continue;
+                link.click();
+                return link.href;
+            }}
+            return '';
+        }})()"#
+    );
+    let res = execute_js(&js)?;
+    Ok(Some(res.trim().to_string()).filter(|url| !url.is_empty()))
+}
+
+/// Browsers whose active tab can be read with JavaScript.
+pub const BROWSER_APPS: &[&str] = &["Google Chrome", "Safari"];
+
+// [Preferred Browser] STEER_BROWSER picks the browser URLs open in and page
+// scripts (form filling, result clicks) run against, so a Firefox or Safari
+// user isn't switched to Chrome. Unset keeps the old behavior: URLs open in the
+// system default browser and page scripts target Chrome.
+
+/// Real app name for a browser written loosely ("chrome", "firefox").
+pub fn browser_app_name(name: &str) -> String {
+    match name.trim().to_lowercase().as_str() {
+        "chrome" | "google chrome" => "Google Chrome".to_string(),
+        "safari" => "Safari".to_string(),
+        "firefox" | "mozilla firefox" => "Firefox".to_string(),
+        "edge" | "microsoft edge" => "Microsoft Edge".to_string(),
+        "arc" => "Arc".to_string(),
+        "brave" | "brave browser" => "Brave Browser".to_string(),
+        _ => name.trim().to_string(),
+    }
+}
+
+/// Browser from STEER_BROWSER; `None` means the system default.
+pub fn preferred_browser() -> Option<String> {
+    std::env::var("STEER_BROWSER")
+        .ok()
+        .map(|name| browser_app_name(&name))
+        .filter(|name| !name.is_empty())
+}
+
+/// Browser page scripts target: `preferred`, or Chrome when none is set.
+/// Errors for browsers that can't run JavaScript from AppleScript.
+pub fn scripting_browser(preferred: Option<&str>) -> Result<&'static str> {
+    match preferred {
+        None => Ok("Google Chrome"),
+        Some(app) => BROWSER_APPS.iter().copied().find(|b| *b == app).ok_or_else(|| {
+            anyhow::anyhow!("{} can't run page scripts; set STEER_BROWSER to chrome or safari for page automation", app)
+        }),
+    }
+}
+
+/// Run `js` in the active tab of the scripting browser.
+fn execute_js(js: &str) -> Result<String> {
+    match scripting_browser(preferred_browser().as_deref())? {
+        "Safari" => applescript::execute_js_in_safari(js),
+        _ => applescript::execute_js_in_chrome(js),
+    }
+}
+
+/// Visible text of the active tab in `app` (Chrome or Safari).
+pub fn page_text(app: &str) -> Result<String> {
+    let js = "document.body ? document.body.innerText : ''";
+    match app {
+        "Google Chrome" => applescript::execute_js_in_chrome(js),
+        "Safari" => applescript::execute_js_in_safari(js),
+        other => Err(anyhow::anyhow!("{} is not a scriptable browser", other)),
+    }
 }
 
 pub fn scroll_page(pixels: i32) -> Result<bool> {
@@ -238,7 +316,7 @@ pub fn scroll_page(pixels: i32) -> Result<bool> {
             return '1';
         }})()"#
     );
-    let res = applescript::execute_js_in_chrome(&js)?;
+    let res = execute_js(&js)?;
     Ok(res.trim() == "1")
 }
 
@@ -279,7 +357,7 @@ pub fn extract_flight_summary() -> Result<String> {
         }
         return JSON.stringify({ prices, times, stops });
     })()"#;
-    applescript::execute_js_in_chrome(js)
+    execute_js(js)
 }
 
 pub fn extract_shopping_summary() -> Result<String> {
@@ -308,5 +386,5 @@ pub fn extract_shopping_summary() -> Result<String> {
         }
         return JSON.stringify({ prices, sellers });
     })()"#;
-    applescript::execute_js_in_chrome(js)
+    execute_js(js)
 }
@@ -0,0 +1,290 @@
+use anyhow::Result;
+
+// [Click Recovery] CLICK presses `button "<target>" of window 1` of the
+// frontmost app. When that fails it is usually because the window changed
+// since the plan was made: the button moved into a group or toolbar, turned
+// into a link, or its label grew ("Submit" → "Submit order"). Instead of
+// failing the step, re-read the window's elements and click the one that
+// best matches the target name, a few times before giving up.
+
+/// Snapshots taken to re-find an element when the direct click fails
+/// (`STEER_CLICK_RECOVERY_ATTEMPTS`, 0 = no recovery).
+pub fn max_attempts() -> u32 {
+    std::env::var("STEER_CLICK_RECOVERY_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(2)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiElement {
+    pub role: String,
+    pub name: String,
+}
+
+/// Where clicks land; the frontmost window in production, a script in tests.
+pub trait ClickSurface {
+    /// The direct click on a button named `name`.
+    fn click_named(&self, name: &str) -> Result<()>;
+    /// The window's named elements as they are now.
+    fn snapshot(&self) -> Result<Vec<UiElement>>;
+    fn click_element(&self, element: &UiElement) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClickOutcome {
+    Direct,
+    /// The direct click failed; `element` was re-found on snapshot `attempt`.
+    Recovered { element: UiElement, attempt: u32 },
+}
+
+impl ClickOutcome {
+    /// Transcript note for a recovered click.
+    pub fn note(&self, target: &str) -> Option<String> {
+        match self {
+            Self::Direct => None,
+            Self::Recovered { element, attempt } => Some(format!(
+                "'{}' re-found as {} '{}' on snapshot {}",
+                target, element.role, element.name, attempt
+            )),
+        }
+    }
+}
+
+/// What a fresh snapshot says about the target name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Found(UiElement),
+    Missing,
+    /// Several elements fit equally well; clicking one would be a guess.
+    Ambiguous(Vec<UiElement>),
+}
+
+/// Best match for `name`: the same name, then the same name ignoring case,
+/// then names containing it as whole words ("Submit order" for "Submit", not
+/// "Resubmit"). Within the first of these that matches anything, buttons
+/// win; more than one candidate left is ambiguous rather than a pick.
+pub fn resolve(elements: &[UiElement], name: &str) -> Resolution {
+    let wanted = words(name);
+    if wanted.is_empty() {
+        return Resolution::Missing;
+    }
+    let tiers: [&dyn Fn(&UiElement) -> bool; 3] = [
+        &|e| e.name.trim() == name.trim(),
+        &|e| e.name.trim().to_lowercase() == name.trim().to_lowercase(),
+        &|e| words(&e.name).windows(wanted.len()).any(|w| w == wanted.as_slice()),
+    ];
+    for fits in tiers {
+        let mut found: Vec<&UiElement> = elements.iter().filter(|e| fits(e)).collect();
+        if found.iter().any(|e| e.role == "AXButton") {
+            found.retain(|e| e.role == "AXButton");
+        }
+        // The same role and name twice is one target to the click script
+        found.dedup();
+        match found.as_slice() {
+            [] => continue,
+            [one] => return Resolution::Found((*one).clone()),
+            many => return Resolution::Ambiguous(many.iter().map(|e| (*e).clone()).collect()),
+        }
+    }
+    Resolution::Missing
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether a re-found element may be clicked in place of `target`: policy
+/// has to allow a click on its name, and it can't be a destructive button
+/// ("Delete all") when the planned one wasn't, since any review of the step
+/// only saw the planned name.
+pub fn allowed(target: &str, element: &UiElement) -> Result<()> {
+    use crate::schema::AgentAction;
+    let planned = AgentAction::UiClickText { text: target.to_string() };
+    let recovered = AgentAction::UiClickText { text: element.name.clone() };
+    crate::policy::PolicyEngine { write_lock: false }
+        .check(&recovered)
+        .map_err(|blocked| anyhow::anyhow!(blocked.message))?;
+    if crate::supervisor::is_risky(&recovered) && !crate::supervisor::is_risky(&planned) {
+        return Err(anyhow::anyhow!("{} '{}' is destructive and '{}' was not", element.role, element.name, target));
+    }
+    Ok(())
+}
+
+/// Click `name`, re-finding it on a fresh snapshot up to `attempts` times when
+/// the direct click fails. A re-found element is clicked only if `allow`
+/// accepts it; an ambiguous or refused match fails at once.
+pub fn click(
+    surface: &impl ClickSurface,
+    name: &str,
+    attempts: u32,
+    allow: impl Fn(&UiElement) -> Result<()>,
+) -> Result<ClickOutcome> {
+    let mut last_error = match surface.click_named(name) {
+        Ok(()) => return Ok(ClickOutcome::Direct),
+        Err(e) => e,
+    };
+    for attempt in 1..=attempts {
+        let element = match surface.snapshot() {
+            Ok(elements) => resolve(&elements, name),
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        let element = match element {
+            Resolution::Found(element) => element,
+            Resolution::Missing => {
+                last_error = anyhow::anyhow!("no element named '{}' in the window", name);
+                continue;
+            }
+            Resolution::Ambiguous(options) => {
+                let options: Vec<String> = options.iter().map(|e| format!("{} '{}'", e.role, e.name)).collect();
+                return Err(anyhow::anyhow!("'{}' matches several elements: {}", name, options.join(", ")));
+            }
+        };
+        if let Err(e) = allow(&element) {
+            return Err(e.context(format!("'{}' re-found as {} '{}' but not clicked", name, element.role, element.name)));
+        }
+        match surface.click_element(&element) {
+            Ok(()) => return Ok(ClickOutcome::Recovered { element, attempt }),
+            Err(e) => last_error = e,
+        }
+    }
+    if attempts == 0 {
+        return Err(last_error);
+    }
+    Err(last_error.context(format!("'{}' not found after {} re-snapshot(s)", name, attempts)))
+}
+
+/// The frontmost app's window 1, driven through System Events.
+pub struct FrontmostWindow;
+
+/// Most elements read per snapshot; `entire contents` of a web view is huge.
+const SNAPSHOT_LIMIT: usize = 400;
+
+impl ClickSurface for FrontmostWindow {
+    fn click_named(&self, name: &str) -> Result<()> {
+        let script = format!(
+            "tell application \"System Events\" to click button {:?} of window 1 of (first application process whose frontmost is true)",
+            name
+        );
+        crate::applescript::run(&script)?;
+        crate::debug_overlay::flash_element("AXButton", name);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Result<Vec<UiElement>> {
+        // Let whatever made the click fail (a page load, an animation) settle first
+        std::thread::sleep(crate::step_delay::current());
+        Ok(crate::applescript::window_elements(SNAPSHOT_LIMIT)?
+            .into_iter()
+            .map(|(role, name)| UiElement { role, name })
+            .collect())
+    }
+
+    fn click_element(&self, element: &UiElement) -> Result<()> {
+        if crate::applescript::click_window_element(&element.role, &element.name)? {
+            crate::debug_overlay::flash_element(&element.role, &element.name);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{} '{}' disappeared before it could be clicked", element.role, element.name))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn el(role: &str, name: &str) -> UiElement {
+        UiElement { role: role.to_string(), name: name.to_string() }
+    }
+
+    /// A window whose snapshots are played back in order; only elements in
+    /// the latest snapshot can be clicked.
+    struct ScriptedWindow {
+        snapshots: RefCell<Vec<Vec<UiElement>>>,
+        live: RefCell<Vec<UiElement>>,
+        clicked: RefCell<Vec<UiElement>>,
+    }
+
+    impl ScriptedWindow {
+        fn new(snapshots: Vec<Vec<UiElement>>) -> Self {
+            Self { snapshots: RefCell::new(snapshots), live: RefCell::new(Vec::new()), clicked: RefCell::new(Vec::new()) }
+        }
+    }
+
+    impl ClickSurface for ScriptedWindow {
+        fn click_named(&self, name: &str) -> Result<()> {
+            Err(anyhow::anyhow!("Can't get button \"{}\" of window 1", name))
+        }
+
+        fn snapshot(&self) -> Result<Vec<UiElement>> {
+            let next = self.snapshots.borrow_mut().remove(0);
+            *self.live.borrow_mut() = next.clone();
+            Ok(next)
+        }
+
+        fn click_element(&self, element: &UiElement) -> Result<()> {
+            if !self.live.borrow().contains(element) {
+                return Err(anyhow::anyhow!("stale element"));
+            }
+            self.clicked.borrow_mut().push(element.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stale_target_is_refound_on_a_later_snapshot() {
+        // Mid page-load the button is missing, then it is back as a link with a longer label
+        let window = ScriptedWindow::new(vec![
+            vec![el("AXStaticText", "Loading…")],
+            vec![el("AXStaticText", "Order total"), el("AXLink", "Submit order")],
+        ]);
+        let outcome = click(&window, "Submit", 2, |_| Ok(())).unwrap();
+        assert_eq!(outcome, ClickOutcome::Recovered { element: el("AXLink", "Submit order"), attempt: 2 });
+        assert_eq!(window.clicked.borrow().as_slice(), &[el("AXLink", "Submit order")]);
+        assert!(outcome.note("Submit").unwrap().contains("snapshot 2"));
+
+        let gone = ScriptedWindow::new(vec![vec![], vec![]]);
+        let err = click(&gone, "Submit", 2, |_| Ok(())).unwrap_err();
+        assert!(format!("{:#}", err).contains("after 2 re-snapshot(s)"));
+
+        let no_recovery = ScriptedWindow::new(vec![]);
+        assert!(click(&no_recovery, "Submit", 0, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_resolve_prefers_exact_then_case_then_whole_words() {
+        let elements = vec![el("AXLink", "Send"), el("AXButton", "Send"), el("AXButton", "send later"), el("AXButton", "Resend everything")];
+        assert_eq!(resolve(&elements, "Send"), Resolution::Found(el("AXButton", "Send")));
+        assert_eq!(resolve(&[el("AXButton", "SEND")], "send"), Resolution::Found(el("AXButton", "SEND")));
+        // "Resend" holds "send" but not as a word
+        assert_eq!(resolve(&elements[2..], "send"), Resolution::Found(el("AXButton", "send later")));
+        assert_eq!(resolve(&elements[3..], "send"), Resolution::Missing);
+        assert_eq!(resolve(&elements, "Archive"), Resolution::Missing);
+    }
+
+    #[test]
+    fn test_ambiguous_or_refused_matches_are_not_clicked() {
+        let two = vec![el("AXButton", "Submit order"), el("AXButton", "Submit and pay")];
+        assert_eq!(resolve(&two, "Submit"), Resolution::Ambiguous(two.clone()));
+        let window = ScriptedWindow::new(vec![two.clone(), two]);
+        let err = click(&window, "Submit", 2, |_| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("matches several elements"), "{}", err);
+        assert!(window.clicked.borrow().is_empty());
+
+        // A plain button that turned into a destructive one is refused
+        let window = ScriptedWindow::new(vec![vec![el("AXButton", "Archive and delete all")]]);
+        let err = click(&window, "Archive", 1, |e| allowed("Archive", e)).unwrap_err();
+        assert!(format!("{:#}", err).contains("not clicked"), "{:#}", err);
+        assert!(window.clicked.borrow().is_empty());
+        assert!(allowed("Save", &el("AXButton", "Save as")).is_ok());
+    }
+}
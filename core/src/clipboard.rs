@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+// [Clipboard] System clipboard text on every platform via arboard. Only the
+// synthetic paste keystroke (applescript::paste_text) is macOS-specific.
+//
+// One handle is kept for the life of the process: on X11/Wayland the text we
+// set is served by its owner, so dropping it right away can lose the contents.
+
+lazy_static! {
+    static ref CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+}
+
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T>) -> Result<T> {
+    let mut guard = CLIPBOARD.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if guard.is_none() {
+        *guard = Some(arboard::Clipboard::new().context("System clipboard unavailable")?);
+    }
+    f(guard.as_mut().expect("clipboard initialized above"))
+}
+
+pub fn get_text() -> Result<String> {
+    with_clipboard(|c| c.get_text().context("Failed to read clipboard text"))
+}
+
+pub fn set_text(text: &str) -> Result<()> {
+    with_clipboard(|c| c.set_text(text.to_string()).context("Failed to write clipboard text"))
+}
+
+/// Paste `text` through the clipboard: set it, check it reads back (an
+/// unavailable or empty clipboard would otherwise "paste" nothing, or stale
+/// content, and still succeed), press paste, wait for the target to settle,
+/// then restore what was there before. The paste keystroke isn't sent when
+/// the text didn't make it onto the clipboard.
+pub fn paste_with(
+    text: &str,
+    get: impl Fn() -> Result<String>,
+    set: impl Fn(&str) -> Result<()>,
+    press_paste: impl FnOnce() -> Result<()>,
+    settle: impl FnOnce(),
+) -> Result<()> {
+    let previous = get().ok();
+    set(text)?;
+    let copied = get().unwrap_or_default();
+    if copied.is_empty() || copied != text {
+        if let Some(previous) = &previous {
+            let _ = set(previous);
+        }
+        return Err(anyhow::anyhow!(
+            "Clipboard did not take the text ({} chars read back of {}); nothing was pasted",
+            copied.chars().count(),
+            text.chars().count()
+        ));
+    }
+    let pasted = press_paste();
+    settle();
+    if let Some(previous) = previous {
+        let _ = set(&previous);
+    }
+    pasted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// No display server means no clipboard (CI, ssh sessions).
+    fn headless() -> bool {
+        cfg!(target_os = "linux")
+            && std::env::var_os("DISPLAY").is_none()
+            && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    }
+
+    #[test]
+    fn test_paste_fails_when_nothing_reaches_the_clipboard() {
+        use std::cell::RefCell;
+        let pressed = RefCell::new(0);
+
+        // A clipboard that drops whatever is written to it
+        let dropped = paste_with("quarterly numbers", || Ok(String::new()), |_| Ok(()), || { *pressed.borrow_mut() += 1; Ok(()) }, || {});
+        assert!(dropped.unwrap_err().to_string().contains("nothing was pasted"));
+        assert_eq!(*pressed.borrow(), 0);
+
+        let board = RefCell::new("previous".to_string());
+        let pasted = RefCell::new(String::new());
+        paste_with(
+            "quarterly numbers",
+            || Ok(board.borrow().clone()),
+            |t| { *board.borrow_mut() = t.to_string(); Ok(()) },
+            || { *pasted.borrow_mut() = board.borrow().clone(); Ok(()) },
+            || {},
+        )
+        .unwrap();
+        assert_eq!(*pasted.borrow(), "quarterly numbers");
+        assert_eq!(*board.borrow(), "previous");
+    }
+
+    #[test]
+    fn test_clipboard_round_trip() {
+        if headless() {
+            println!("skipping clipboard round trip: no display");
+            return;
+        }
+        let previous = get_text().ok();
+        let text = format!("steer clipboard test {}", uuid::Uuid::new_v4());
+        set_text(&text).unwrap();
+        assert_eq!(get_text().unwrap(), text);
+        if let Some(previous) = previous {
+            let _ = set_text(&previous);
+        }
+    }
+}
@@ -6,11 +6,19 @@ use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 
 pub type CommandResult = Result<String>;
+
+/// Lane for everything that drives the mouse, keyboard or window focus. It
+/// runs one task at a time for the whole process, so a goal and a routine (or
+/// a chat-triggered task) running together take turns instead of mixing their
+/// clicks and keystrokes. Reads and shell/integration work stay off it.
+pub const UI_LANE: &str = "ui";
 type CommandTask = Box<dyn FnOnce() -> CommandResult + Send + 'static>;
 
 struct QueueEntry {
     task: CommandTask,
     tx: oneshot::Sender<CommandResult>,
+    /// Told when the task starts running (time limits count from there)
+    started: oneshot::Sender<()>,
     enqueued_at: Instant,
     warn_after: Duration,
 }
@@ -45,10 +53,27 @@ impl CommandQueue {
     }
 
     async fn enqueue_in_lane(&self, lane: &str, task: CommandTask, warn_after_ms: u64) -> CommandResult {
+        let (_started, rx) = self.submit(lane, task, warn_after_ms);
+        match rx.await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("Command queue dropped before returning a result")),
+        }
+    }
+
+    /// Queue `task`; returns receivers for its start and its result. Dropping
+    /// the result receiver before the task starts takes it off the queue.
+    fn submit(
+        &self,
+        lane: &str,
+        task: CommandTask,
+        warn_after_ms: u64,
+    ) -> (oneshot::Receiver<()>, oneshot::Receiver<CommandResult>) {
         let (tx, rx) = oneshot::channel();
+        let (started, started_rx) = oneshot::channel();
         let entry = QueueEntry {
             task,
             tx,
+            started,
             enqueued_at: Instant::now(),
             warn_after: Duration::from_millis(warn_after_ms),
         };
@@ -70,11 +95,7 @@ impl CommandQueue {
             let queue = Arc::clone(&COMMAND_QUEUE);
             queue.pump_lane(lane_name);
         }
-
-        match rx.await {
-            Ok(result) => result,
-            Err(_) => Err(anyhow!("Command queue dropped before returning a result")),
-        }
+        (started_rx, rx)
     }
 
     fn pump_lane(self: Arc<Self>, lane: String) {
@@ -87,7 +108,17 @@ impl CommandQueue {
                     return;
                 }
                 state.active += 1;
-                state.queue.pop_front()
+                // A caller that gave up (timed out, cancelled) doesn't want its
+                // click or keystroke anymore, least of all late and out of order
+                let mut next = state.queue.pop_front();
+                while next.as_ref().is_some_and(|e| e.tx.is_closed()) {
+                    eprintln!("⏭️ Command queue: dropping abandoned task in lane {}", lane);
+                    next = state.queue.pop_front();
+                }
+                if next.is_none() {
+                    state.active -= 1;
+                }
+                next
             };
 
             let Some(entry) = entry else { continue };
@@ -100,6 +131,7 @@ impl CommandQueue {
                 );
             }
 
+            let _ = entry.started.send(());
             let queue = Arc::clone(&COMMAND_QUEUE);
             let lane_name = lane.clone();
             tokio::spawn(async move {
@@ -134,6 +166,17 @@ impl CommandQueue {
         }
     }
 
+    fn drop_abandoned(&self) -> usize {
+        let mut lanes = self.lanes.lock().expect("command queue lock poisoned");
+        let mut dropped = 0;
+        for state in lanes.values_mut() {
+            let before = state.queue.len();
+            state.queue.retain(|e| !e.tx.is_closed());
+            dropped += before - state.queue.len();
+        }
+        dropped
+    }
+
     async fn set_lane_concurrency(&self, lane: &str, max_concurrent: usize) {
         let mut lanes = self.lanes.lock().expect("command queue lock poisoned");
         let state = lanes.entry(lane.to_string()).or_insert_with(LaneState::new);
@@ -163,6 +206,12 @@ pub async fn enqueue_command_in_lane(
     COMMAND_QUEUE.enqueue_in_lane(lane, task, warn_after).await
 }
 
+/// Take tasks nobody is waiting for anymore (their run was cancelled) off
+/// every lane now, instead of when they reach the front. Returns how many.
+pub fn drop_abandoned() -> usize {
+    COMMAND_QUEUE.drop_abandoned()
+}
+
 pub async fn set_lane_concurrency(lane: &str, max_concurrent: usize) {
     COMMAND_QUEUE.set_lane_concurrency(lane, max_concurrent).await
 }
@@ -170,3 +219,147 @@ pub async fn set_lane_concurrency(lane: &str, max_concurrent: usize) {
 pub async fn get_lane_size(lane: &str) -> usize {
     COMMAND_QUEUE.get_lane_size(lane).await
 }
+
+/// Run blocking UI work (a click, a keystroke burst, an activate) in `UI_LANE`.
+pub async fn enqueue_ui<T, F>(task: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    enqueue_command_in_lane(
+        UI_LANE,
+        Box::new(move || {
+            let _ = tx.send(task()?);
+            Ok(String::new())
+        }),
+        None,
+    )
+    .await?;
+    rx.await.map_err(|_| anyhow!("UI task ended without a result"))
+}
+
+/// A UI task ran longer than its limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedOut;
+
+/// `enqueue_ui` with a time limit that starts when the task starts running,
+/// not while it waits its turn. A task still queued when the caller goes
+/// away is dropped rather than run later.
+pub async fn enqueue_ui_timeout<T, F>(limit: Duration, task: F) -> std::result::Result<Result<T>, TimedOut>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (tx, value_rx) = oneshot::channel();
+    let (started, rx) = COMMAND_QUEUE.submit(
+        UI_LANE,
+        Box::new(move || {
+            let _ = tx.send(task()?);
+            Ok(String::new())
+        }),
+        2_000,
+    );
+    if started.await.is_err() {
+        return Ok(Err(anyhow!("Command queue dropped the UI task before it ran")));
+    }
+    match tokio::time::timeout(limit, rx).await {
+        Err(_) => Err(TimedOut),
+        Ok(Err(_)) => Ok(Err(anyhow!("Command queue dropped before returning a result"))),
+        Ok(Ok(Err(e))) => Ok(Err(e)),
+        Ok(Ok(Ok(_))) => Ok(value_rx.await.map_err(|_| anyhow!("UI task ended without a result"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_ui_tasks_take_turns_while_other_lanes_run() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let ui_task = |name: &'static str| {
+            let log = Arc::clone(&log);
+            enqueue_ui(move || {
+                for key in 0..3 {
+                    log.lock().unwrap().push(format!("{}{}", name, key));
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Ok(name)
+            })
+        };
+        let (shell_tx, shell_rx) = std::sync::mpsc::channel::<()>();
+        let shell = enqueue_command_in_lane(
+            "shell",
+            Box::new(move || {
+                shell_tx.send(()).ok();
+                Ok(String::new())
+            }),
+            None,
+        );
+
+        // A UI task that can only finish once the shell lane has run next to it
+        let waits_for_shell = enqueue_ui(move || {
+            shell_rx.recv_timeout(Duration::from_secs(5)).map_err(|_| anyhow!("shell lane was blocked by the UI lane"))
+        });
+        let (waited, _, a, b) = tokio::join!(waits_for_shell, shell, ui_task("a"), ui_task("b"));
+        waited.unwrap();
+        assert_eq!((a.unwrap(), b.unwrap()), ("a", "b"));
+
+        let keys = log.lock().unwrap().clone();
+        assert_eq!(keys.len(), 6);
+        for run in keys.chunks(3) {
+            let name = &run[0][..1];
+            assert!(run.iter().all(|k| k.starts_with(name)), "UI tasks interleaved: {:?}", keys);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_timed_out_ui_task_never_runs_and_limit_starts_at_run() {
+        let lane = format!("ui-test-{}", uuid::Uuid::new_v4());
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let task = |name: &'static str, ms: u64| {
+            let ran = Arc::clone(&ran);
+            Box::new(move || {
+                std::thread::sleep(Duration::from_millis(ms));
+                ran.lock().unwrap().push(name);
+                Ok(String::new())
+            }) as CommandTask
+        };
+
+        // "late" waits behind a slow task and its caller gives up first
+        let (_, slow) = COMMAND_QUEUE.submit(&lane, task("slow", 300), 2_000);
+        let (_, late) = COMMAND_QUEUE.submit(&lane, task("late", 0), 2_000);
+        assert!(tokio::time::timeout(Duration::from_millis(50), late).await.is_err());
+        slow.await.unwrap().unwrap();
+        let (_, after) = COMMAND_QUEUE.submit(&lane, task("after", 0), 2_000);
+        after.await.unwrap().unwrap();
+        assert_eq!(*ran.lock().unwrap(), vec!["slow", "after"]);
+
+        // Time spent queued behind another UI task doesn't count toward the limit
+        let busy = enqueue_ui(|| {
+            std::thread::sleep(Duration::from_millis(300));
+            Ok(())
+        });
+        let quick = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            enqueue_ui_timeout(Duration::from_millis(200), || Ok(7)).await
+        };
+        let (busy, quick) = tokio::join!(busy, quick);
+        busy.unwrap();
+        assert_eq!(quick.unwrap().unwrap(), 7);
+        assert_eq!(
+            enqueue_ui_timeout(Duration::from_millis(20), || {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(())
+            })
+            .await
+            .unwrap_err(),
+            TimedOut
+        );
+        // The timed-out task still finishes; let it, so the shared lane is free for other tests
+        while get_lane_size(UI_LANE).await > 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// [Self-Healing] Crash reports land in $STEER_HOME/logs/crash.log. Panic
+// messages can carry user content or credentials pulled from the environment,
+// so everything is passed through privacy::redact before it is written.
+// Environment variables are never dumped.
+
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let backtrace = std::backtrace::Backtrace::capture();
+
+        let msg = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            *s
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            &**s
+        } else {
+            "Unknown panic"
+        };
+
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let log_dir = crate::paths::steer_home().join("logs");
+        let backtrace = format!("{:#?}", backtrace);
+        if let Err(e) = write_report(&log_dir, msg, &location, &backtrace) {
+            eprintln!("⚠️ Failed to write crash report: {}", e);
+        }
+
+        eprintln!("❌ FATAL ERROR: {}", crate::privacy::redact(msg));
+        eprintln!("📄 Crash report saved to {}", log_dir.join("crash.log").display());
+    }));
+}
+
+/// Append a redacted crash entry to `<log_dir>/crash.log`.
+pub fn write_report(log_dir: &Path, message: &str, location: &str, backtrace: &str) -> std::io::Result<PathBuf> {
+    let entry = format!(
+        "[{}] CRASH REPORT\nMessage: {}\nLocation: {}\nBacktrace:\n{}\n--------------------------------------------------\n",
+        chrono::Utc::now().to_rfc3339(),
+        crate::privacy::redact(message),
+        location,
+        crate::privacy::redact(backtrace)
+    );
+
+    std::fs::create_dir_all(log_dir)?;
+    let log_file = log_dir.join("crash.log");
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&log_file)?;
+    writeln!(file, "{}", entry)?;
+    Ok(log_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_masks_api_key() {
+        let dir = std::env::temp_dir().join(format!("steer_crash_{}", uuid::Uuid::new_v4()));
+        let payload = "request failed with OPENAI_API_KEY=sk-live1234567890abcdefXYZ";
+
+        let path = write_report(&dir, payload, "src/main.rs:1", "<backtrace>").unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(written.contains("CRASH REPORT"));
+        assert!(!written.contains("sk-live1234567890abcdefXYZ"), "{}", written);
+        assert!(written.contains("***REDACTED***"));
+    }
+}
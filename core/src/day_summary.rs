@@ -0,0 +1,100 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::db;
+use crate::integrations::calendar::CalendarClient;
+use crate::integrations::google_auth;
+use crate::llm_gateway::LLMClient;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySummary {
+    pub headline: String,
+    pub highlights: Vec<String>,
+    pub meetings: Vec<String>,
+    pub suggestions: Vec<String>,
+    /// Most-seen apps in the captured events, busiest first.
+    pub top_apps: Vec<(String, usize)>,
+    pub event_count: usize,
+    pub calendar_available: bool,
+}
+
+/// Combine the last `hours` of captured events with today's calendar into a day report.
+/// Calendar access is optional; without it the summary is built from events alone.
+pub async fn summarize_day(llm: &LLMClient, hours: i64) -> Result<DaySummary> {
+    let logs = db::get_recent_events(hours)?;
+
+    // Only use calendar when already authorized; never start a consent flow from here
+    let calendar = if google_auth::has_cached_token() {
+        match CalendarClient::new().await {
+            Ok(client) => client.list_today().await.ok(),
+            Err(e) => {
+                println!("⚠️  Calendar unavailable for day summary: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let calendar_available = calendar.is_some();
+    let calendar = calendar.unwrap_or_default();
+
+    if logs.is_empty() && calendar.is_empty() {
+        return Ok(DaySummary {
+            headline: "No activity or calendar events recorded today.".to_string(),
+            highlights: vec![],
+            meetings: vec![],
+            suggestions: vec![],
+            top_apps: vec![],
+            event_count: 0,
+            calendar_available,
+        });
+    }
+
+    let report = llm.summarize_day(&logs, &calendar).await?;
+    Ok(DaySummary {
+        headline: report["headline"].as_str().unwrap_or_default().to_string(),
+        highlights: string_list(&report["highlights"]),
+        meetings: string_list(&report["meetings"]),
+        suggestions: string_list(&report["suggestions"]),
+        top_apps: top_apps(&logs, 5),
+        event_count: logs.len(),
+        calendar_available,
+    })
+}
+
+fn string_list(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+fn top_apps(logs: &[String], limit: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for log in logs {
+        if let Ok(event) = serde_json::from_str::<serde_json::Value>(log) {
+            if let Some(app) = event["app"].as_str().filter(|a| !a.is_empty() && *a != "unknown") {
+                *counts.entry(app.to_string()).or_default() += 1;
+            }
+        }
+    }
+    let mut apps: Vec<(String, usize)> = counts.into_iter().collect();
+    apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    apps.truncate(limit);
+    apps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_apps_ranks_by_count() {
+        let logs: Vec<String> = ["Code", "Safari", "Code", "unknown", "Code", "Safari", "Slack"]
+            .iter()
+            .map(|app| serde_json::json!({ "app": app }).to_string())
+            .collect();
+        let apps = top_apps(&logs, 2);
+        assert_eq!(apps, vec![("Code".to_string(), 3), ("Safari".to_string(), 2)]);
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(dead_code)] // Allow unused library functions for future use
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use std::sync::Mutex;
 use lazy_static::lazy_static;
 use crate::recommendation::AutomationProposal;
@@ -24,6 +24,15 @@ fn get_db_lock() -> std::sync::MutexGuard<'static, Option<Connection>> {
     }
 }
 
+/// True when the connection is open and answers a trivial query.
+pub fn is_healthy() -> bool {
+    let mut lock = get_db_lock();
+    match lock.as_mut() {
+        Some(conn) => conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)).is_ok(),
+        None => false,
+    }
+}
+
 pub fn init() -> Result<()> {
     // Open (or create) steer.db
     let conn = Connection::open("steer.db")?;
@@ -59,11 +68,24 @@ pub fn init() -> Result<()> {
             workflow_json TEXT,
             approved_at TEXT,
             evidence TEXT NOT NULL DEFAULT '[]',
-            last_error TEXT
+            last_error TEXT,
+            trigger_key TEXT
         )",
         [],
     )?;
 
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_recommendations_status_created ON recommendations(status, created_at)",
+        [],
+    )?;
+    // [Migration] Normalized trigger for recommendation_exists_like (backfilled
+    // by migrate_recommendation_fingerprints)
+    let _ = conn.execute("ALTER TABLE recommendations ADD COLUMN trigger_key TEXT", []);
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_recommendations_trigger_key ON recommendations(trigger_key, status)",
+        [],
+    )?;
+
     // Create 'chat_history' table (New Memory System)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS chat_history (
@@ -85,7 +107,9 @@ pub fn init() -> Result<()> {
             enabled BOOLEAN NOT NULL DEFAULT 1,
             last_run TEXT,
             next_run TEXT,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            success_check TEXT,
+            plan TEXT
         )",
         [],
     )?;
@@ -190,7 +214,8 @@ pub fn init() -> Result<()> {
             started_at TEXT NOT NULL,
             finished_at TEXT,
             status TEXT NOT NULL,
-            error TEXT
+            error TEXT,
+            session_key TEXT
         )",
         [],
     )?;
@@ -206,6 +231,51 @@ pub fn init() -> Result<()> {
         )",
         [],
     )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_calls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at TEXT NOT NULL,
+            session_key TEXT,
+            purpose TEXT NOT NULL,
+            model TEXT NOT NULL,
+            prompt_hash TEXT NOT NULL,
+            prompt TEXT,
+            tokens INTEGER,
+            latency_ms INTEGER NOT NULL,
+            response TEXT
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_llm_calls_session ON llm_calls(session_key, id)", [])?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS goal_steps (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at TEXT NOT NULL,
+            session_key TEXT NOT NULL,
+            step_index INTEGER NOT NULL,
+            action_type TEXT NOT NULL,
+            description TEXT NOT NULL,
+            decision_source TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            error TEXT,
+            output TEXT,
+            block_reason TEXT
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_goal_steps_session ON goal_steps(session_key, id)", [])?;
+    // Reusable n8n workflows with {{param}} placeholders (see instantiate_template)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS templates (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            workflow_json TEXT NOT NULL,
+            builtin INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
     // Store connection
     {
         let mut lock = get_db_lock();
@@ -226,8 +296,15 @@ pub fn init() -> Result<()> {
         eprintln!("Failed to init sessions_v2: {}", e);
     }
 
+    // [Migration] Re-fingerprint with normalized keys and drop near-duplicates (once)
+    match migrate_recommendation_fingerprints() {
+        Ok(n) if n > 0 => println!("🧹 Removed {} duplicate recommendations", n),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to dedupe recommendations: {}", e),
+    }
+
     // Seed templates if needed (now safe to call)
-    if let Err(e) = seed_advanced_examples() {
+    if let Err(e) = seed_default_templates() {
         eprintln!("Failed to seed templates: {}", e);
     }
     
@@ -241,6 +318,15 @@ pub fn init() -> Result<()> {
         let _ = conn.execute("ALTER TABLE recommendations ADD COLUMN pattern_id TEXT", []);
         let _ = conn.execute("ALTER TABLE recommendations ADD COLUMN last_error TEXT", []);
         let _ = conn.execute("ALTER TABLE exec_approvals ADD COLUMN decision TEXT", []);
+        let _ = conn.execute("ALTER TABLE exec_results ADD COLUMN impact TEXT", []);
+        let _ = conn.execute("ALTER TABLE goal_steps ADD COLUMN output TEXT", []);
+        let _ = conn.execute("ALTER TABLE goal_steps ADD COLUMN block_reason TEXT", []);
+        let _ = conn.execute("ALTER TABLE routine_runs ADD COLUMN session_key TEXT", []);
+        let _ = conn.execute("ALTER TABLE routines ADD COLUMN success_check TEXT", []);
+        // Steps of the routine's last successful run, replayed in lite mode (see set_routine_plan)
+        let _ = conn.execute("ALTER TABLE routines ADD COLUMN plan TEXT", []);
+        // Routine a recommendation was promoted to (see promote_recommendation_to_routine)
+        let _ = conn.execute("ALTER TABLE recommendations ADD COLUMN routine_id INTEGER", []);
         
         // 1-2. Routine Candidates Table
         let _ = conn.execute(
@@ -255,6 +341,30 @@ pub fn init() -> Result<()> {
             )",
             [],
         );
+
+        // Who changed which runtime setting, and from what (see record_config_change)
+        let _ = conn.execute(
+            "CREATE TABLE IF NOT EXISTS config_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                config_key TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT,
+                changed_at TEXT NOT NULL
+            )",
+            [],
+        );
+
+        // Embeddings of event descriptions for semantic clustering (event_clusters.rs)
+        let _ = conn.execute(
+            "CREATE TABLE IF NOT EXISTS event_embeddings (
+                event_id TEXT PRIMARY KEY,
+                description TEXT NOT NULL,
+                model TEXT NOT NULL,
+                vector TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        );
     }
 
     Ok(())
@@ -270,37 +380,175 @@ pub struct Routine {
     pub last_run: Option<String>,
     pub next_run: Option<String>,
     pub created_at: String,
+    /// JSON check that decides whether a run succeeded (see routine_check.rs)
+    pub success_check: Option<String>,
+}
+
+/// Validate a cron expression in the format the scheduler understands
+/// (`sec min hour day-of-month month day-of-week [year]`).
+/// Returns a human-readable description of the problem on failure.
+pub fn validate_cron(expr: &str) -> std::result::Result<(), String> {
+    let trimmed = expr.trim();
+    if trimmed.is_empty() {
+        return Err("Cron expression is empty".to_string());
+    }
+    let fields = trimmed.split_whitespace().count();
+    if fields == 5 {
+        return Err(format!(
+            "Cron expression '{}' has 5 fields; expected 6 or 7 (sec min hour day month weekday [year]). Try '0 {}'",
+            trimmed, trimmed
+        ));
+    }
+    if !(6..=7).contains(&fields) {
+        return Err(format!(
+            "Cron expression '{}' has {} fields; expected 6 or 7 (sec min hour day month weekday [year])",
+            trimmed, fields
+        ));
+    }
+    match cron::Schedule::from_str(trimmed) {
+        Ok(schedule) => {
+            if schedule.upcoming(chrono::Utc).next().is_none() {
+                return Err(format!("Cron expression '{}' never fires in the future", trimmed));
+            }
+            Ok(())
+        }
+        Err(e) => Err(format!("Invalid cron expression '{}': {}", trimmed, e)),
+    }
+}
+
+/// Error from a write that validates its input first: the input was
+/// refused before reaching SQLite (`Invalid`, a client error), the row it
+/// refers to doesn't exist (`NotFound`), or SQLite failed.
+#[derive(Debug)]
+pub enum WriteError {
+    Invalid(String),
+    NotFound(String),
+    Db(rusqlite::Error),
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::Invalid(msg) | WriteError::NotFound(msg) => f.write_str(msg),
+            WriteError::Db(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::Invalid(_) | WriteError::NotFound(_) => None,
+            WriteError::Db(e) => Some(e),
+        }
+    }
 }
 
-pub fn create_routine(name: &str, cron: &str, prompt: &str) -> Result<i64> {
+impl From<rusqlite::Error> for WriteError {
+    fn from(e: rusqlite::Error) -> Self {
+        WriteError::Db(e)
+    }
+}
+
+pub fn create_routine(name: &str, cron: &str, prompt: &str) -> std::result::Result<i64, WriteError> {
+    validate_cron(cron).map_err(WriteError::Invalid)?;
+
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        let created_at = chrono::Utc::now().to_rfc3339();
-        
-        // Calculate initial next_run
-        let next_run = match cron::Schedule::from_str(cron) {
-            Ok(s) => s.upcoming(chrono::Utc).next().map(|d: chrono::DateTime<chrono::Utc>| d.to_rfc3339()),
-            Err(_) => None, // Invalid cron, will never run (validation should happen before)
-        };
-        
-        conn.execute(
-            "INSERT INTO routines (name, cron_expression, prompt, created_at, next_run) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![name, cron, prompt, created_at, next_run],
-        )?;
-        Ok(conn.last_insert_rowid())
+        Ok(insert_routine(conn, name, cron, prompt)?)
     } else {
-        Err(rusqlite::Error::SqliteFailure(
+        Err(WriteError::Db(rusqlite::Error::SqliteFailure(
             rusqlite::ffi::Error::new(1),
             Some("DB not initialized".to_string()),
-        ))
+        )))
+    }
+}
+
+/// Insert a routine whose `cron` was already validated.
+fn insert_routine(conn: &Connection, name: &str, cron: &str, prompt: &str) -> Result<i64> {
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    // Calculate initial next_run
+    let next_run = cron::Schedule::from_str(cron.trim())
+        .ok()
+        .and_then(|s| s.upcoming(chrono::Utc).next())
+        .map(|d: chrono::DateTime<chrono::Utc>| d.to_rfc3339());
+
+    conn.execute(
+        "INSERT INTO routines (name, cron_expression, prompt, created_at, next_run) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![name, cron, prompt, created_at, next_run],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Goal text for a routine promoted from a recommendation: the automation
+/// request, plus the steps the recommendation listed.
+pub fn recommendation_routine_prompt(rec: &Recommendation) -> String {
+    let request = if rec.n8n_prompt.trim().is_empty() { rec.summary.trim() } else { rec.n8n_prompt.trim() };
+    let steps: Vec<&str> = rec.actions.iter().map(|a| a.trim()).filter(|a| !a.is_empty()).collect();
+    if steps.is_empty() {
+        request.to_string()
+    } else {
+        format!("{}\nSteps: {}", request, steps.join("; "))
+    }
+}
+
+/// Turn an approved recommendation into a scheduled routine named after it
+/// and link the two (`recommendations.routine_id`). Returns the new routine's
+/// id. A recommendation can be promoted once; promoting it again is an error
+/// naming the existing routine.
+pub fn promote_recommendation_to_routine(rec_id: i64, cron: &str) -> std::result::Result<i64, WriteError> {
+    validate_cron(cron).map_err(WriteError::Invalid)?;
+    let rec = get_recommendation(rec_id)?
+        .ok_or_else(|| WriteError::NotFound(format!("Recommendation #{} not found", rec_id)))?;
+    if rec.status != "approved" {
+        return Err(WriteError::Invalid(format!(
+            "Recommendation #{} is {}; approve it before scheduling it",
+            rec_id, rec.status
+        )));
+    }
+    let prompt = recommendation_routine_prompt(&rec);
+
+    let mut lock = get_db_lock();
+    let Some(conn) = lock.as_mut() else {
+        return Err(WriteError::Db(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(1),
+            Some("DB not initialized".to_string()),
+        )));
+    };
+    let tx = conn.transaction()?;
+    let linked: Option<i64> = tx.query_row(
+        "SELECT r.id FROM recommendations rec JOIN routines r ON r.id = rec.routine_id WHERE rec.id = ?1",
+        params![rec_id],
+        |row| row.get(0),
+    ).optional()?;
+    if let Some(routine_id) = linked {
+        return Err(WriteError::Invalid(format!("Recommendation #{} is already routine #{}", rec_id, routine_id)));
+    }
+    let routine_id = insert_routine(&tx, &rec.title, cron.trim(), &prompt)?;
+    tx.execute("UPDATE recommendations SET routine_id = ?1 WHERE id = ?2", params![routine_id, rec_id])?;
+    tx.commit()?;
+    Ok(routine_id)
+}
+
+/// Routine a recommendation was promoted to, if it still exists.
+pub fn get_recommendation_routine(rec_id: i64) -> Result<Option<i64>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        return conn.query_row(
+            "SELECT r.id FROM recommendations rec JOIN routines r ON r.id = rec.routine_id WHERE rec.id = ?1",
+            params![rec_id],
+            |row| row.get(0),
+        ).optional();
     }
+    Ok(None)
 }
 
 pub fn get_due_routines() -> Result<Vec<Routine>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
         let now = chrono::Utc::now().to_rfc3339();
-        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at FROM routines WHERE enabled = 1 AND next_run <= ?1")?;
+        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at, success_check FROM routines WHERE enabled = 1 AND next_run <= ?1")?;
         let rows = stmt.query_map(params![now], |row| {
             Ok(Routine {
                 id: row.get(0)?,
@@ -311,6 +559,7 @@ pub fn get_due_routines() -> Result<Vec<Routine>> {
                 last_run: row.get(5)?,
                 next_run: row.get(6)?,
                 created_at: row.get(7)?,
+                success_check: row.get(8)?,
             })
         })?;
 
@@ -341,7 +590,7 @@ pub fn update_routine_execution(id: i64, next: Option<String>) -> Result<()> {
 pub fn get_active_routines() -> Result<Vec<Routine>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at FROM routines WHERE enabled = 1")?;
+        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at, success_check FROM routines WHERE enabled = 1")?;
         let rows = stmt.query_map([], |row| {
             Ok(Routine {
                 id: row.get(0)?,
@@ -352,6 +601,7 @@ pub fn get_active_routines() -> Result<Vec<Routine>> {
                 last_run: row.get(5)?,
                 next_run: row.get(6)?,
                 created_at: row.get(7)?,
+                success_check: row.get(8)?,
             })
         })?;
         // ... (collect)
@@ -365,10 +615,45 @@ pub fn get_active_routines() -> Result<Vec<Routine>> {
     }
 }
 
+pub fn get_routine(id: i64) -> Result<Option<Routine>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at, success_check FROM routines WHERE id = ?1")?;
+        let mut rows = stmt.query(params![id])?;
+        if let Some(row) = rows.next()? {
+            return Ok(Some(Routine {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                cron_expression: row.get(2)?,
+                prompt: row.get(3)?,
+                enabled: row.get(4)?,
+                last_run: row.get(5)?,
+                next_run: row.get(6)?,
+                created_at: row.get(7)?,
+                success_check: row.get(8)?,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Ids of routines named `name` (case-insensitive), oldest first. Names are
+/// not unique, so callers must handle more than one match.
+pub fn find_routine_ids_by_name(name: &str) -> Result<Vec<i64>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare("SELECT id FROM routines WHERE lower(name) = lower(?1) ORDER BY id")?;
+        let rows = stmt.query_map(params![name.trim()], |row| row.get(0))?;
+        rows.collect()
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 pub fn get_all_routines() -> Result<Vec<Routine>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at FROM routines ORDER BY created_at DESC")?;
+        let mut stmt = conn.prepare("SELECT id, name, cron_expression, prompt, enabled, last_run, next_run, created_at, success_check FROM routines ORDER BY created_at DESC")?;
         let rows = stmt.query_map([], |row| {
             Ok(Routine {
                 id: row.get(0)?,
@@ -379,6 +664,7 @@ pub fn get_all_routines() -> Result<Vec<Routine>> {
                 last_run: row.get(5)?,
                 next_run: row.get(6)?,
                 created_at: row.get(7)?,
+                success_check: row.get(8)?,
             })
         })?;
         // ... (collect)
@@ -392,6 +678,48 @@ pub fn get_all_routines() -> Result<Vec<Routine>> {
     }
 }
 
+/// Set (or with `None`, clear) the check that decides whether a routine run succeeded.
+pub fn set_routine_success_check(id: i64, check: Option<&str>) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let updated = conn.execute(
+            "UPDATE routines SET success_check = ?1 WHERE id = ?2",
+            params![check, id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    } else {
+        Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(1),
+            Some("DB not initialized".to_string()),
+        ))
+    }
+}
+
+/// Store the plan (JSON steps) a routine ran to success, so it can be
+/// replayed without an LLM.
+pub fn set_routine_plan(id: i64, plan: &str) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        conn.execute("UPDATE routines SET plan = ?1 WHERE id = ?2", params![plan, id])?;
+    }
+    Ok(())
+}
+
+/// The plan stored by `set_routine_plan`, if any.
+pub fn get_routine_plan(id: i64) -> Result<Option<String>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let plan = conn
+            .query_row("SELECT plan FROM routines WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?;
+        return Ok(plan.flatten());
+    }
+    Ok(None)
+}
+
 /// Toggle routine enabled status
 pub fn toggle_routine(id: i64, enabled: bool) -> Result<()> {
     let mut lock = get_db_lock();
@@ -422,7 +750,7 @@ pub struct Recommendation {
     pub confidence: f64,
     pub workflow_id: Option<String>,
     pub workflow_json: Option<String>,
-    pub evidence: Vec<String>,
+    pub evidence: Vec<crate::recommendation::Evidence>,
     pub pattern_id: Option<String>,
     pub last_error: Option<String>,
 }
@@ -436,6 +764,8 @@ pub struct RecommendationMetrics {
     pub pending: i64,
     pub later: i64,
     pub last_created_at: Option<String>,
+    /// Counts for every status present, including ones without a dedicated field.
+    pub by_status: std::collections::BTreeMap<String, i64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -458,6 +788,17 @@ pub struct ApprovalPolicy {
     pub updated_at: String,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfigChange {
+    pub id: i64,
+    pub config_key: String,
+    /// `None` when the setting didn't exist before
+    pub old_value: Option<String>,
+    /// `None` when the setting was removed
+    pub new_value: Option<String>,
+    pub changed_at: String,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ExecAllowlistEntry {
     pub id: i64,
@@ -468,6 +809,41 @@ pub struct ExecAllowlistEntry {
     pub uses_count: i64,
 }
 
+/// One logged LLM request. `prompt` is only filled in verbose mode;
+/// otherwise `prompt_hash` identifies the prompt without storing screenshots.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct LlmCall {
+    pub id: i64,
+    pub created_at: String,
+    pub session_key: Option<String>,
+    pub purpose: String,
+    pub model: String,
+    pub prompt_hash: String,
+    pub prompt: Option<String>,
+    pub tokens: Option<i64>,
+    pub latency_ms: i64,
+    pub response: Option<String>,
+}
+
+/// One executed (or skipped) plan step, with where the step came from:
+/// `llm`, `forced_context`, `fallback:<failure>` or `llm_replan:<failure>`.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct GoalStep {
+    pub id: i64,
+    pub created_at: String,
+    pub session_key: String,
+    pub step_index: i64,
+    pub action_type: String,
+    pub description: String,
+    pub decision_source: String,
+    pub outcome: String,
+    pub error: Option<String>,
+    /// Data the step produced, e.g. EXTRACT's JSON object.
+    pub output: Option<String>,
+    /// Category of a blocked or denied step (`policy::BlockReason::code`).
+    pub block_reason: Option<String>,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ExecResult {
     pub id: String,
@@ -545,6 +921,111 @@ pub struct RoutineRun {
     pub error: Option<String>,
 }
 
+/// `PRAGMA user_version` at which stored fingerprints and trigger keys match
+/// the current normalization. Bump it when `AutomationProposal::fingerprint`
+/// or `trigger_key` changes.
+const RECOMMENDATION_FINGERPRINT_VERSION: i64 = 2;
+
+/// Run `dedupe_recommendations` once per fingerprint version instead of on
+/// every startup.
+fn migrate_recommendation_fingerprints() -> Result<usize> {
+    let version: i64 = match get_db_lock().as_ref() {
+        Some(conn) => conn.query_row("PRAGMA user_version", [], |row| row.get(0))?,
+        None => return Ok(0),
+    };
+    if version >= RECOMMENDATION_FINGERPRINT_VERSION {
+        return Ok(0);
+    }
+    let removed = dedupe_recommendations()?;
+    if let Some(conn) = get_db_lock().as_ref() {
+        conn.execute_batch(&format!("PRAGMA user_version = {}", RECOMMENDATION_FINGERPRINT_VERSION))?;
+    }
+    Ok(removed)
+}
+
+/// Recompute fingerprints and trigger keys with the current normalization and
+/// drop duplicates. Approved rows win over others; otherwise the oldest row is kept.
+pub fn dedupe_recommendations() -> Result<usize> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT id, title, trigger, actions FROM recommendations
+             ORDER BY (status = 'approved') DESC, id ASC",
+        )?;
+        let rows: Vec<(i64, String, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        drop(stmt);
+
+        let mut seen = std::collections::HashSet::new();
+        let mut keep: Vec<(i64, String, String)> = Vec::new();
+        let mut remove: Vec<i64> = Vec::new();
+        for (id, title, trigger, actions) in rows {
+            let proposal = AutomationProposal {
+                title,
+                trigger,
+                actions: serde_json::from_str(&actions).unwrap_or_default(),
+                ..Default::default()
+            };
+            let fp = proposal.fingerprint();
+            if seen.insert(fp.clone()) {
+                keep.push((id, fp, trigger_key(&proposal.trigger)));
+            } else {
+                remove.push(id);
+            }
+        }
+
+        let tx = conn.transaction()?;
+        for id in &remove {
+            tx.execute("DELETE FROM recommendations WHERE id = ?1", params![id])?;
+        }
+        // Park fingerprints first so the UNIQUE constraint can't trip mid-rewrite
+        tx.execute("UPDATE recommendations SET fingerprint = 'tmp::' || id", [])?;
+        for (id, fp, key) in &keep {
+            tx.execute(
+                "UPDATE recommendations SET fingerprint = ?1, trigger_key = ?2 WHERE id = ?3",
+                params![fp, key, id],
+            )?;
+        }
+        tx.commit()?;
+        return Ok(remove.len());
+    }
+    Ok(0)
+}
+
+/// True if an open (pending/later) recommendation already has an equivalent
+/// trigger after normalization (case, punctuation, word order). Different
+/// automations can share a trigger, so this is a hint (the analyzer doesn't
+/// notify again for it), not a reason to drop a proposal; that takes the
+/// full fingerprint (see `insert_recommendation`).
+pub fn recommendation_exists_like(trigger: &str) -> Result<bool> {
+    let key = trigger_key(trigger);
+    if key.is_empty() {
+        return Ok(false);
+    }
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let found: Option<i64> = conn
+            .query_row(
+                "SELECT 1 FROM recommendations WHERE trigger_key = ?1 AND status IN ('pending', 'later') LIMIT 1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        return Ok(found.is_some());
+    }
+    Ok(false)
+}
+
+fn trigger_key(trigger: &str) -> String {
+    let normalized = crate::recommendation::normalize_text(trigger);
+    let mut words: Vec<&str> = normalized.split_whitespace().collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+/// Insert unless a row with the same normalized fingerprint exists; the
+/// UNIQUE index on `fingerprint` does the lookup.
 pub fn insert_recommendation(proposal: &AutomationProposal) -> Result<bool> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -554,8 +1035,8 @@ pub fn insert_recommendation(proposal: &AutomationProposal) -> Result<bool> {
 
         let rows = conn.execute(
             "INSERT OR IGNORE INTO recommendations (
-                created_at, status, title, summary, trigger, actions, n8n_prompt, fingerprint, confidence, workflow_json, evidence, pattern_id, last_error
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                created_at, status, title, summary, trigger, actions, n8n_prompt, fingerprint, confidence, workflow_json, evidence, pattern_id, last_error, trigger_key
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 created_at,
                 "pending",
@@ -570,6 +1051,7 @@ pub fn insert_recommendation(proposal: &AutomationProposal) -> Result<bool> {
                 serde_json::to_string(&proposal.evidence).unwrap_or_else(|_| "[]".to_string()),
                 proposal.pattern_id,
                 None::<String>,
+                trigger_key(&proposal.trigger),
             ],
         )?;
         return Ok(rows > 0);
@@ -606,7 +1088,7 @@ pub fn get_recommendation_metrics() -> Result<RecommendationMetrics> {
              FROM recommendations"
         )?;
 
-        let metrics = stmt.query_row([], |row| {
+        let mut metrics = stmt.query_row([], |row| {
             Ok(RecommendationMetrics {
                 total: row.get(0)?,
                 approved: row.get(1)?,
@@ -615,9 +1097,17 @@ pub fn get_recommendation_metrics() -> Result<RecommendationMetrics> {
                 pending: row.get(4)?,
                 later: row.get(5)?,
                 last_created_at: row.get(6).ok(),
+                by_status: Default::default(),
             })
         })?;
 
+        let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM recommendations GROUP BY status")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (status, count) = row?;
+            metrics.by_status.insert(status, count);
+        }
+
         return Ok(metrics);
     }
     Ok(RecommendationMetrics {
@@ -628,6 +1118,7 @@ pub fn get_recommendation_metrics() -> Result<RecommendationMetrics> {
         pending: 0,
         later: 0,
         last_created_at: None,
+        by_status: Default::default(),
     })
 }
 
@@ -798,16 +1289,81 @@ pub fn get_exec_approval(id: &str) -> Result<Option<ExecApproval>> {
     Ok(None)
 }
 
+// [Config Audit] Settings that loosen or tighten what the agent may do
+// (approval policies, the exec allowlist, the monitoring pause, the write
+// lock, safe mode) are changed from the REPL, the API or at startup. Each change is written to
+// `config_audit` as key, old value, new value, so "why did it start
+// auto-approving?" has an answer. Keys are namespaced by setting, e.g.
+// `approval_policy:<policy_key>` or `exec_allowlist:<id>`.
+fn insert_config_change(conn: &Connection, config_key: &str, old_value: Option<&str>, new_value: Option<&str>) -> Result<()> {
+    if old_value == new_value {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO config_audit (config_key, old_value, new_value, changed_at) VALUES (?1, ?2, ?3, ?4)",
+        params![config_key, old_value, new_value, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Record a change to a setting that doesn't live in this database.
+pub fn record_config_change(config_key: &str, old_value: Option<&str>, new_value: Option<&str>) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        insert_config_change(conn, config_key, old_value, new_value)?;
+    }
+    Ok(())
+}
+
+/// Newest first, optionally only keys starting with `key_prefix`.
+pub fn list_config_changes(key_prefix: Option<&str>, limit: i64) -> Result<Vec<ConfigChange>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let pattern = format!("{}%", key_prefix.unwrap_or("").replace('%', "\\%").replace('_', "\\_"));
+        let mut stmt = conn.prepare(
+            "SELECT id, config_key, old_value, new_value, changed_at
+             FROM config_audit
+             WHERE config_key LIKE ?1 ESCAPE '\\'
+             ORDER BY id DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![pattern, limit], |row| {
+            Ok(ConfigChange {
+                id: row.get(0)?,
+                config_key: row.get(1)?,
+                old_value: row.get(2)?,
+                new_value: row.get(3)?,
+                changed_at: row.get(4)?,
+            })
+        })?;
+        return rows.collect();
+    }
+    Ok(Vec::new())
+}
+
+fn approval_policy_decision(conn: &Connection, policy_key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT decision FROM nl_approval_policies WHERE policy_key = ?1",
+        params![policy_key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
 pub fn upsert_approval_policy(policy_key: &str, decision: &str) -> Result<()> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
         let updated_at = chrono::Utc::now().to_rfc3339();
-        conn.execute(
+        let tx = conn.transaction()?;
+        let old = approval_policy_decision(&tx, policy_key)?;
+        tx.execute(
             "INSERT INTO nl_approval_policies (policy_key, decision, updated_at)
              VALUES (?1, ?2, ?3)
              ON CONFLICT(policy_key) DO UPDATE SET decision = excluded.decision, updated_at = excluded.updated_at",
             params![policy_key, decision, updated_at],
         )?;
+        insert_config_change(&tx, &format!("approval_policy:{}", policy_key), old.as_deref(), Some(decision))?;
+        tx.commit()?;
     }
     Ok(())
 }
@@ -815,10 +1371,14 @@ pub fn upsert_approval_policy(policy_key: &str, decision: &str) -> Result<()> {
 pub fn delete_approval_policy(policy_key: &str) -> Result<()> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        conn.execute(
+        let tx = conn.transaction()?;
+        let old = approval_policy_decision(&tx, policy_key)?;
+        tx.execute(
             "DELETE FROM nl_approval_policies WHERE policy_key = ?1",
             params![policy_key],
         )?;
+        insert_config_change(&tx, &format!("approval_policy:{}", policy_key), old.as_deref(), None)?;
+        tx.commit()?;
     }
     Ok(())
 }
@@ -866,11 +1426,15 @@ pub fn add_exec_allowlist(pattern: &str, cwd: Option<&str>) -> Result<i64> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
         let created_at = chrono::Utc::now().to_rfc3339();
-        conn.execute(
+        let tx = conn.transaction()?;
+        tx.execute(
             "INSERT INTO exec_allowlist (pattern, cwd, created_at) VALUES (?1, ?2, ?3)",
             params![pattern, cwd, created_at],
         )?;
-        return Ok(conn.last_insert_rowid());
+        let id = tx.last_insert_rowid();
+        insert_config_change(&tx, &format!("exec_allowlist:{}", id), None, Some(&allowlist_value(pattern, cwd)))?;
+        tx.commit()?;
+        return Ok(id);
     }
     Ok(0)
 }
@@ -904,36 +1468,315 @@ pub fn list_exec_allowlist(limit: i64) -> Result<Vec<ExecAllowlistEntry>> {
 pub fn remove_exec_allowlist(id: i64) -> Result<()> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        conn.execute("DELETE FROM exec_allowlist WHERE id = ?1", params![id])?;
+        let tx = conn.transaction()?;
+        let old: Option<(String, Option<String>)> = tx
+            .query_row("SELECT pattern, cwd FROM exec_allowlist WHERE id = ?1", params![id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?;
+        tx.execute("DELETE FROM exec_allowlist WHERE id = ?1", params![id])?;
+        let old = old.map(|(pattern, cwd)| allowlist_value(&pattern, cwd.as_deref()));
+        insert_config_change(&tx, &format!("exec_allowlist:{}", id), old.as_deref(), None)?;
+        tx.commit()?;
     }
     Ok(())
 }
 
-pub fn create_exec_result(command: &str, cwd: Option<&str>) -> Result<ExecResult> {
+/// Audit value of an allowlist entry: the pattern, plus the directory it's limited to.
+fn allowlist_value(pattern: &str, cwd: Option<&str>) -> String {
+    match cwd {
+        Some(cwd) => format!("{} (in {})", pattern, cwd),
+        None => pattern.to_string(),
+    }
+}
+
+/// Most rows one run session keeps in `table`, from `var` (0 = unlimited).
+/// Long runs then cost the same per step: each insert trims at most the
+/// oldest row instead of the session growing without bound.
+fn session_row_cap(var: &str, default: i64) -> i64 {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Delete all but the newest `cap` rows of `session_key` in `table`
+/// (`goal_steps` or `llm_calls`). Returns how many rows were removed.
+fn trim_session_rows(conn: &Connection, table: &str, session_key: &str, cap: i64) -> Result<usize> {
+    if cap <= 0 {
+        return Ok(0);
+    }
+    conn.execute(
+        &format!(
+            "DELETE FROM {t} WHERE session_key = ?1 AND id <= \
+             (SELECT id FROM {t} WHERE session_key = ?1 ORDER BY id DESC LIMIT 1 OFFSET ?2)",
+            t = table
+        ),
+        params![session_key, cap],
+    )
+}
+
+pub fn insert_llm_call(call: &LlmCall) -> Result<i64> {
     let mut lock = get_db_lock();
-    let id = uuid::Uuid::new_v4().to_string();
     if let Some(conn) = lock.as_mut() {
         let created_at = chrono::Utc::now().to_rfc3339();
         conn.execute(
-            "INSERT INTO exec_results (id, command, cwd, status, output, error, created_at, updated_at)
-             VALUES (?1, ?2, ?3, 'pending', NULL, NULL, ?4, NULL)",
-            params![id, command, cwd, created_at],
+            "INSERT INTO llm_calls (created_at, session_key, purpose, model, prompt_hash, prompt, tokens, latency_ms, response)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                created_at,
+                call.session_key,
+                call.purpose,
+                call.model,
+                call.prompt_hash,
+                call.prompt,
+                call.tokens,
+                call.latency_ms,
+                call.response
+            ],
         )?;
-        return Ok(ExecResult {
-            id,
-            command: command.to_string(),
-            cwd: cwd.map(|c| c.to_string()),
-            status: "pending".to_string(),
-            output: None,
-            error: None,
-            created_at,
-            updated_at: None,
-        });
+        let id = conn.last_insert_rowid();
+        if let Some(key) = &call.session_key {
+            trim_session_rows(conn, "llm_calls", key, session_row_cap("STEER_SESSION_MAX_LLM_CALLS", 200))?;
+        }
+        return Ok(id);
     }
-    Ok(ExecResult {
-        id,
-        command: command.to_string(),
-        cwd: cwd.map(|c| c.to_string()),
+    Ok(0)
+}
+
+/// Calls for one session in the order they were made (or the latest calls overall).
+pub fn list_llm_calls(session_key: Option<&str>, limit: i64) -> Result<Vec<LlmCall>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let map_row = |row: &rusqlite::Row| -> rusqlite::Result<LlmCall> {
+            Ok(LlmCall {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                session_key: row.get(2)?,
+                purpose: row.get(3)?,
+                model: row.get(4)?,
+                prompt_hash: row.get(5)?,
+                prompt: row.get(6)?,
+                tokens: row.get(7)?,
+                latency_ms: row.get(8)?,
+                response: row.get(9)?,
+            })
+        };
+        let columns = "id, created_at, session_key, purpose, model, prompt_hash, prompt, tokens, latency_ms, response";
+        let mut calls = Vec::new();
+        match session_key {
+            Some(key) => {
+                let sql = format!("SELECT {} FROM llm_calls WHERE session_key = ?1 ORDER BY id ASC LIMIT ?2", columns);
+                let mut stmt = conn.prepare(&sql)?;
+                for row in stmt.query_map(params![key, limit], map_row)? {
+                    calls.push(row?);
+                }
+            }
+            None => {
+                let sql = format!("SELECT {} FROM llm_calls ORDER BY id DESC LIMIT ?1", columns);
+                let mut stmt = conn.prepare(&sql)?;
+                for row in stmt.query_map(params![limit], map_row)? {
+                    calls.push(row?);
+                }
+            }
+        }
+        return Ok(calls);
+    }
+    Ok(Vec::new())
+}
+
+pub fn insert_goal_step(step: &GoalStep) -> Result<i64> {
+    insert_goal_step_capped(step, session_row_cap("STEER_SESSION_MAX_STEPS", 500)).map(|(id, _)| id)
+}
+
+/// Insert `step`, then trim its session to `cap` steps. Returns (id, rows trimmed).
+fn insert_goal_step_capped(step: &GoalStep, cap: i64) -> Result<(i64, usize)> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO goal_steps (created_at, session_key, step_index, action_type, description, decision_source, outcome, error, output, block_reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                created_at,
+                step.session_key,
+                step.step_index,
+                step.action_type,
+                step.description,
+                step.decision_source,
+                step.outcome,
+                step.error,
+                step.output,
+                step.block_reason
+            ],
+        )?;
+        let id = conn.last_insert_rowid();
+        let trimmed = trim_session_rows(conn, "goal_steps", &step.session_key, cap)?;
+        return Ok((id, trimmed));
+    }
+    Ok((0, 0))
+}
+
+/// Step transcript of one executor run, in execution order.
+pub fn list_goal_steps(session_key: &str, limit: i64) -> Result<Vec<GoalStep>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, session_key, step_index, action_type, description, decision_source, outcome, error, output, block_reason
+             FROM goal_steps WHERE session_key = ?1 ORDER BY id ASC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_key, limit], |row| {
+            Ok(GoalStep {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                session_key: row.get(2)?,
+                step_index: row.get(3)?,
+                action_type: row.get(4)?,
+                description: row.get(5)?,
+                decision_source: row.get(6)?,
+                outcome: row.get(7)?,
+                error: row.get(8)?,
+                output: row.get(9)?,
+                block_reason: row.get(10)?,
+            })
+        })?;
+        let mut steps = Vec::new();
+        for row in rows {
+            steps.push(row?);
+        }
+        return Ok(steps);
+    }
+    Ok(Vec::new())
+}
+
+/// Executor run sessions: every `session_key` with goal steps or LLM calls.
+const RUN_SESSION_ROWS: &str = "
+    SELECT session_key, created_at, 1 AS is_step, 0 AS is_call,
+           LENGTH(description) + IFNULL(LENGTH(error), 0) + IFNULL(LENGTH(output), 0) AS bytes
+    FROM goal_steps
+    UNION ALL
+    SELECT session_key, created_at, 0, 1,
+           IFNULL(LENGTH(prompt), 0) + IFNULL(LENGTH(response), 0)
+    FROM llm_calls WHERE session_key IS NOT NULL";
+
+/// Stored data for one executor run (goal text lives in its steps and prompts).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RunSession {
+    pub session_key: String,
+    pub started_at: String,
+    pub last_activity_at: String,
+    pub steps: i64,
+    pub llm_calls: i64,
+    pub bytes: i64,
+}
+
+/// Most recently active run sessions first.
+pub fn list_run_sessions(limit: i64) -> Result<Vec<RunSession>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let sql = format!(
+            "SELECT session_key, MIN(created_at), MAX(created_at), SUM(is_step), SUM(is_call), SUM(bytes)
+             FROM ({}) GROUP BY session_key ORDER BY MAX(created_at) DESC LIMIT ?1",
+            RUN_SESSION_ROWS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(RunSession {
+                session_key: row.get(0)?,
+                started_at: row.get(1)?,
+                last_activity_at: row.get(2)?,
+                steps: row.get(3)?,
+                llm_calls: row.get(4)?,
+                bytes: row.get(5)?,
+            })
+        })?;
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
+        }
+        return Ok(sessions);
+    }
+    Ok(Vec::new())
+}
+
+/// (session count, stored text bytes) across all run sessions.
+pub fn run_session_totals() -> Result<(i64, i64)> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let sql = format!(
+            "SELECT COUNT(DISTINCT session_key), IFNULL(SUM(bytes), 0) FROM ({})",
+            RUN_SESSION_ROWS
+        );
+        return conn.query_row(&sql, [], |row| Ok((row.get(0)?, row.get(1)?)));
+    }
+    Ok((0, 0))
+}
+
+/// Delete the steps and LLM calls of sessions idle for more than `older_than_days`.
+/// Returns the keys of the purged sessions.
+pub fn purge_run_sessions(older_than_days: i64) -> Result<Vec<String>> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(older_than_days.max(0))).to_rfc3339();
+    purge_run_sessions_before(&cutoff)
+}
+
+fn purge_run_sessions_before(cutoff: &str) -> Result<Vec<String>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let stale = format!(
+            "SELECT session_key FROM ({}) GROUP BY session_key HAVING MAX(created_at) < ?1",
+            RUN_SESSION_ROWS
+        );
+        let tx = conn.transaction()?;
+        let keys = {
+            let mut stmt = tx.prepare(&stale)?;
+            let rows = stmt.query_map(params![cutoff], |row| row.get::<_, String>(0))?;
+            rows.collect::<Result<Vec<String>>>()?
+        };
+        tx.execute(&format!("DELETE FROM goal_steps WHERE session_key IN ({})", stale), params![cutoff])?;
+        tx.execute(&format!("DELETE FROM llm_calls WHERE session_key IN ({})", stale), params![cutoff])?;
+        tx.commit()?;
+        return Ok(keys);
+    }
+    Ok(Vec::new())
+}
+
+/// Audit note describing what the command touches (see shell_analysis::assess_impact).
+pub fn set_exec_result_impact(id: &str, impact: &str) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        conn.execute(
+            "UPDATE exec_results SET impact = ?1 WHERE id = ?2",
+            params![impact, id],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn create_exec_result(command: &str, cwd: Option<&str>) -> Result<ExecResult> {
+    let mut lock = get_db_lock();
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Some(conn) = lock.as_mut() {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO exec_results (id, command, cwd, status, output, error, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 'pending', NULL, NULL, ?4, NULL)",
+            params![id, command, cwd, created_at],
+        )?;
+        return Ok(ExecResult {
+            id,
+            command: command.to_string(),
+            cwd: cwd.map(|c| c.to_string()),
+            status: "pending".to_string(),
+            output: None,
+            error: None,
+            created_at,
+            updated_at: None,
+        });
+    }
+    Ok(ExecResult {
+        id,
+        command: command.to_string(),
+        cwd: cwd.map(|c| c.to_string()),
         status: "pending".to_string(),
         output: None,
         error: None,
@@ -956,6 +1799,48 @@ pub fn update_exec_result(id: &str, status: &str, output: Option<&str>, error: O
     Ok(())
 }
 
+pub fn get_exec_result(id: &str) -> Result<Option<ExecResult>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT id, command, cwd, status, output, error, created_at, updated_at
+             FROM exec_results
+             WHERE id = ?1",
+        )?;
+        let mut rows = stmt.query(params![id])?;
+        if let Some(row) = rows.next()? {
+            return Ok(Some(ExecResult {
+                id: row.get(0)?,
+                command: row.get(1)?,
+                cwd: row.get(2).ok(),
+                status: row.get(3)?,
+                output: row.get(4).ok(),
+                error: row.get(5).ok(),
+                created_at: row.get(6)?,
+                updated_at: row.get(7).ok(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Mark pending results older than `ttl_secs` as "expired". Returns how many were updated.
+pub fn expire_stale_exec_results(ttl_secs: i64) -> Result<usize> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let now = chrono::Utc::now();
+        let cutoff = (now - chrono::Duration::seconds(ttl_secs)).to_rfc3339();
+        let updated = conn.execute(
+            "UPDATE exec_results
+             SET status = 'expired', error = COALESCE(error, 'Expired while pending'), updated_at = ?1
+             WHERE status = 'pending' AND created_at < ?2",
+            params![now.to_rfc3339(), cutoff],
+        )?;
+        return Ok(updated);
+    }
+    Ok(0)
+}
+
 pub fn list_pending_exec_results(limit: i64) -> Result<Vec<ExecResult>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -1411,6 +2296,40 @@ pub fn finish_routine_run(run_id: i64, status: &str, error: Option<&str>) -> Res
     Ok(())
 }
 
+/// Link a run to the executor session whose steps it recorded (`list_goal_steps`).
+pub fn set_routine_run_session(run_id: i64, session_key: &str) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        conn.execute(
+            "UPDATE routine_runs SET session_key = ?1 WHERE id = ?2",
+            params![session_key, run_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Steps a routine took on its latest successful run (or latest run, if none
+/// succeeded). Empty if it never ran with step recording.
+pub fn get_routine_steps(routine_id: i64) -> Result<Vec<GoalStep>> {
+    let session_key: String = {
+        let mut lock = get_db_lock();
+        let Some(conn) = lock.as_mut() else { return Ok(Vec::new()) };
+        let row = conn.query_row(
+            "SELECT session_key FROM routine_runs
+             WHERE routine_id = ?1 AND session_key IS NOT NULL AND status != 'running'
+             ORDER BY (status = 'success') DESC, id DESC LIMIT 1",
+            [routine_id],
+            |row| row.get(0),
+        );
+        match row {
+            Ok(key) => key,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        }
+    };
+    list_goal_steps(&session_key, 200)
+}
+
 pub fn list_routine_runs(limit: i64) -> Result<Vec<RoutineRun>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -1438,15 +2357,16 @@ pub fn list_routine_runs(limit: i64) -> Result<Vec<RoutineRun>> {
     Ok(Vec::new())
 }
 
+/// Returns true when the candidate is new (already-known pattern ids are ignored).
 pub fn insert_routine_candidate(
     pattern: &crate::pattern_detector::DetectedPattern,
-) -> Result<()> {
+) -> Result<bool> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
         let created_at = chrono::Utc::now().to_rfc3339();
         let samples_json = serde_json::to_string(&pattern.sample_events).unwrap_or_default();
 
-        conn.execute(
+        let inserted = conn.execute(
             "INSERT OR IGNORE INTO routine_candidates (
                 candidate_id, created_at, pattern_type, description, frequency, score, sample_events
             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
@@ -1460,37 +2380,245 @@ pub fn insert_routine_candidate(
                 samples_json
             ],
         )?;
+        return Ok(inserted > 0);
+    }
+    Ok(false)
+}
+
+/// The stored pattern a recommendation's `pattern_id` points at.
+pub fn get_pattern_info(pattern_id: &str) -> Result<Option<crate::recommendation::PatternInfo>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        return conn.query_row(
+            "SELECT candidate_id, pattern_type, description, frequency, score FROM routine_candidates WHERE candidate_id = ?1",
+            params![pattern_id],
+            |row| {
+                Ok(crate::recommendation::PatternInfo {
+                    pattern_id: row.get(0)?,
+                    pattern_type: row.get(1)?,
+                    description: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    occurrences: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                    similarity: row.get::<_, Option<f64>>(4)?.unwrap_or(0.0),
+                })
+            },
+        ).optional();
+    }
+    Ok(None)
+}
+
+pub fn count_routine_candidates() -> Result<i64> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM routine_candidates", [], |row| row.get(0))?;
+        return Ok(count);
+    }
+    Ok(0)
+}
+
+/// The fields of a stored event that its embedding text is built from.
+#[derive(Debug, Clone, Default)]
+pub struct EventText {
+    pub event_id: String,
+    pub app: String,
+    pub event_type: String,
+    pub window_title: Option<String>,
+    pub browser_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventEmbedding {
+    pub event_id: String,
+    pub description: String,
+    pub vector: Vec<f32>,
+}
+
+/// Most recent events that have no row in `event_embeddings` yet, newest first.
+pub fn events_without_embedding(limit: i64) -> Result<Vec<EventText>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT e.event_id, e.app, e.event_type, e.window_title, e.browser_url
+             FROM events_v2 e LEFT JOIN event_embeddings x ON x.event_id = e.event_id
+             WHERE x.event_id IS NULL ORDER BY e.ts DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            Ok(EventText {
+                event_id: row.get(0)?,
+                app: row.get(1)?,
+                event_type: row.get(2)?,
+                window_title: row.get(3).ok().flatten(),
+                browser_url: row.get(4).ok().flatten(),
+            })
+        })?;
+        rows.collect()
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Store an event's embedding. An empty `vector` marks an event with nothing
+/// worth embedding, so it isn't picked up again.
+pub fn insert_event_embedding(event_id: &str, description: &str, model: &str, vector: &[f32]) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let vector_json = serde_json::to_string(vector).unwrap_or_else(|_| "[]".to_string());
+        conn.execute(
+            "INSERT OR REPLACE INTO event_embeddings (event_id, description, model, vector, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![event_id, description, model, vector_json, chrono::Utc::now().to_rfc3339()],
+        )?;
     }
     Ok(())
 }
 
-// Function to seed advanced examples if DB is empty
-pub fn seed_advanced_examples() -> Result<()> {
+/// The `limit` most recently stored non-empty embeddings.
+pub fn get_event_embeddings(limit: i64) -> Result<Vec<EventEmbedding>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        // Check if any recommendations exist
-        let count: i64 = conn.query_row(
-            "SELECT count(*) FROM recommendations",
-            [],
-            |row| row.get(0),
+        let mut stmt = conn.prepare(
+            "SELECT event_id, description, vector FROM event_embeddings
+             WHERE vector != '[]' ORDER BY created_at DESC LIMIT ?1",
         )?;
+        let rows = stmt.query_map([limit], |row| {
+            let vector: String = row.get(2)?;
+            Ok(EventEmbedding {
+                event_id: row.get(0)?,
+                description: row.get(1)?,
+                vector: serde_json::from_str(&vector).unwrap_or_default(),
+            })
+        })?;
+        rows.collect()
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkflowTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub workflow_json: String,
+    /// Seeded by `seed_default_templates` rather than added by the user
+    pub builtin: bool,
+    pub created_at: String,
+}
+
+impl WorkflowTemplate {
+    /// Placeholder names in order of first use.
+    pub fn params(&self) -> Vec<String> {
+        template_params(&self.workflow_json)
+    }
+}
 
-        if count > 0 {
-            return Ok(());
+/// `{{name}}` placeholders; n8n expressions such as `{{ $json.subject }}` are
+/// left alone since they aren't plain identifiers.
+fn template_placeholder() -> regex::Regex {
+    regex::Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("Invalid placeholder pattern")
+}
+
+pub fn template_params(workflow_json: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for caps in template_placeholder().captures_iter(workflow_json) {
+        if !names.iter().any(|n| n == &caps[1]) {
+            names.push(caps[1].to_string());
         }
+    }
+    names
+}
 
-        println!("🌱 Seeding advanced workflow templates...");
-        
+/// Replace every placeholder with its value, escaped for a JSON string;
+/// `Err` lists the parameters `params` doesn't give.
+pub fn fill_template(
+    workflow_json: &str,
+    params: &std::collections::HashMap<String, String>,
+) -> std::result::Result<String, Vec<String>> {
+    let missing: Vec<String> = template_params(workflow_json)
+        .into_iter()
+        .filter(|name| !params.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+    let filled = template_placeholder().replace_all(workflow_json, |caps: &regex::Captures| {
+        let quoted = serde_json::to_string(&params[&caps[1]]).unwrap_or_default();
+        quoted[1..quoted.len() - 1].to_string()
+    });
+    Ok(filled.into_owned())
+}
+
+/// Add a template, or replace the one with the same id.
+pub fn add_template(id: &str, name: &str, description: &str, workflow_json: &str) -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        conn.execute(
+            "INSERT OR REPLACE INTO templates (id, name, description, workflow_json, builtin, created_at)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+            params![id, name, description, workflow_json, chrono::Utc::now().to_rfc3339()],
+        )?;
+    }
+    Ok(())
+}
+
+fn row_to_template(row: &rusqlite::Row) -> Result<WorkflowTemplate> {
+    Ok(WorkflowTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        workflow_json: row.get(3)?,
+        builtin: row.get::<_, i64>(4)? != 0,
+        created_at: row.get(5)?,
+    })
+}
+
+pub fn list_templates() -> Result<Vec<WorkflowTemplate>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description, workflow_json, builtin, created_at FROM templates ORDER BY builtin DESC, id",
+        )?;
+        let rows = stmt.query_map([], row_to_template)?;
+        return rows.collect();
+    }
+    Ok(Vec::new())
+}
+
+pub fn get_template(id: &str) -> Result<Option<WorkflowTemplate>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        return conn
+            .query_row(
+                "SELECT id, name, description, workflow_json, builtin, created_at FROM templates WHERE id = ?1",
+                params![id],
+                row_to_template,
+            )
+            .optional();
+    }
+    Ok(None)
+}
+
+/// The template's workflow JSON with `params` filled in. An unknown id is
+/// `NotFound`; missing parameters are `Invalid`.
+pub fn instantiate_template(id: &str, params: &std::collections::HashMap<String, String>) -> std::result::Result<String, WriteError> {
+    let template = get_template(id)?.ok_or_else(|| WriteError::NotFound(format!("No template '{}'", id)))?;
+    fill_template(&template.workflow_json, params)
+        .map_err(|missing| WriteError::Invalid(format!("template '{}' needs {}", id, missing.join(", "))))
+}
+
+/// The built-in templates, added once; later edits (same id) are kept.
+pub fn seed_default_templates() -> Result<()> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
         let created_at = chrono::Utc::now().to_rfc3339();
-        
-        // Example 1: Morning Briefing
+
+        // Calendar summary every morning at 9, sent to Telegram
         let briefing_json = r#"{
             "name": "Daily Morning Briefing",
             "nodes": [
                 { "type": "n8n-nodes-base.cron", "typeVersion": 1, "position": [100, 300], "parameters": { "triggerTimes": { "item": [{ "mode": "everyDay", "hour": 9 }] } }, "name": "Schedule (9 AM)" },
                 { "type": "n8n-nodes-base.googleCalendar", "typeVersion": 1, "position": [300, 300], "parameters": { "operation": "getAll", "calendar": { "__rl": true, "mode": "list", "value": "primary" }, "options": { "timeMin": "={{ $today }}", "timeMax": "={{ $today.end }}" } }, "name": "Get Appointments" },
                 { "type": "n8n-nodes-base.openAi", "typeVersion": 1, "position": [500, 300], "parameters": { "resource": "chat", "prompt": { "messages": [{ "role": "user", "content": "Summarize my day based on these events: {{ JSON.stringify($json) }}" }] } }, "name": "AI Summary" },
-                { "type": "n8n-nodes-base.telegram", "typeVersion": 1, "position": [700, 300], "parameters": { "chatId": "YOUR_CHAT_ID", "text": "🌞 *Morning Briefing*\n\n{{ $json.message.content }}", "additionalFields": { "parseMode": "Markdown" } }, "name": "Send to Telegram" }
+                { "type": "n8n-nodes-base.telegram", "typeVersion": 1, "position": [700, 300], "parameters": { "chatId": "{{chat_id}}", "text": "🌞 *Morning Briefing*\n\n{{ $json.message.content }}", "additionalFields": { "parseMode": "Markdown" } }, "name": "Send to Telegram" }
             ],
             "connections": {
                 "Schedule (9 AM)": { "main": [[{ "node": "Get Appointments", "type": "main", "index": 0 }]] },
@@ -1499,31 +2627,13 @@ pub fn seed_advanced_examples() -> Result<()> {
             }
         }"#;
 
-        conn.execute(
-            "INSERT INTO recommendations (
-                created_at, status, title, summary, trigger, actions, n8n_prompt, fingerprint, confidence, workflow_json
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                created_at,
-                "pending",
-                "🌞 Daily Morning Briefing",
-                "매일 아침 9시에 일정과 날씨를 요약해서 텔레그램으로 보냅니다.",
-                "Every Day at 9:00 AM",
-                "[\"Calendar Check\", \"AI Summary\", \"Telegram Notify\"]",
-                "Create a workflow that runs every day at 9am, fetches Google Calendar events, summarizes them using OpenAI, and sends it to Telegram.",
-                "seed_briefing_001",
-                1.0,
-                briefing_json
-            ],
-        )?;
-
-        // Example 2: Urgent Email Alert
+        // Telegram alert for unread mail containing a keyword (or "긴급" in the subject)
         let urgent_mail_json = r#"{
             "name": "Urgent Email Alert",
             "nodes": [
                 { "type": "n8n-nodes-base.gmail", "typeVersion": 2, "position": [100, 300], "parameters": { "pollTimes": { "item": [{ "mode": "everyMinute" }] }, "filters": { "labelIds": ["INBOX"], "readStatus": "unread" } }, "name": "Check Inbox" },
-                { "type": "n8n-nodes-base.if", "typeVersion": 1, "position": [300, 300], "parameters": { "conditions": { "string": [{ "value1": "={{ $json.snippet }}", "operation": "contains", "value2": "urgent" }, { "value1": "={{ $json.subject }}", "operation": "contains", "value2": "긴급" }] }, "combineOperation": "any" }, "name": "Is Urgent?" },
-                { "type": "n8n-nodes-base.telegram", "typeVersion": 1, "position": [500, 200], "parameters": { "chatId": "YOUR_CHAT_ID", "text": "🚨 *Urgent Email*\n\nFrom: {{ $json.from }}\nSubject: {{ $json.subject }}\nSnippet: {{ $json.snippet }}" }, "name": "Notify Telegram" }
+                { "type": "n8n-nodes-base.if", "typeVersion": 1, "position": [300, 300], "parameters": { "conditions": { "string": [{ "value1": "={{ $json.snippet }}", "operation": "contains", "value2": "{{keyword}}" }, { "value1": "={{ $json.subject }}", "operation": "contains", "value2": "긴급" }] }, "combineOperation": "any" }, "name": "Is Urgent?" },
+                { "type": "n8n-nodes-base.telegram", "typeVersion": 1, "position": [500, 200], "parameters": { "chatId": "{{chat_id}}", "text": "🚨 *Urgent Email*\n\nFrom: {{ $json.from }}\nSubject: {{ $json.subject }}\nSnippet: {{ $json.snippet }}" }, "name": "Notify Telegram" }
             ],
             "connections": {
                 "Check Inbox": { "main": [[{ "node": "Is Urgent?", "type": "main", "index": 0 }]] },
@@ -1531,54 +2641,68 @@ pub fn seed_advanced_examples() -> Result<()> {
             }
         }"#;
 
-        conn.execute(
-            "INSERT INTO recommendations (
-                created_at, status, title, summary, trigger, actions, n8n_prompt, fingerprint, confidence, workflow_json
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                created_at,
-                "pending",
-                "🚨 긴급 메일 알림",
-                "제목이나 내용에 '긴급'이 포함된 메일이 오면 즉시 알림을 보냅니다.",
-                "New Email in Inbox",
-                "[\"Check Keywords\", \"Telegram Alert\"]",
-                "Watch Gmail for new emails. If subject contains 'urgent' or '긴급', send a Telegram notification.",
-                "seed_urgent_001",
-                0.95,
-                urgent_mail_json
-            ],
-        )?;
+        let defaults = [
+            ("morning-briefing", "🌞 Daily Morning Briefing", "매일 아침 9시에 일정을 요약해서 텔레그램으로 보냅니다.", briefing_json),
+            ("urgent-mail", "🚨 긴급 메일 알림", "본문에 키워드가 있거나 제목에 '긴급'이 있는 메일이 오면 즉시 알림을 보냅니다.", urgent_mail_json),
+        ];
+        for (id, name, description, workflow_json) in defaults {
+            let rows = conn.execute(
+                "INSERT OR IGNORE INTO templates (id, name, description, workflow_json, builtin, created_at)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+                params![id, name, description, workflow_json, created_at],
+            )?;
+            if rows > 0 {
+                println!("🌱 Seeded workflow template '{}'", id);
+            }
+        }
     }
     Ok(())
 }
 
 pub fn get_recommendations_with_filter(status_filter: Option<&str>) -> Result<Vec<Recommendation>> {
+    // SQLite treats a negative LIMIT as "no limit"
+    get_recommendations_page(status_filter, -1, 0)
+}
+
+/// Paged variant. Ordering is `created_at DESC, id DESC` so pages are stable
+/// even when several rows share a timestamp (served by idx_recommendations_status_created).
+pub fn get_recommendations_page(status_filter: Option<&str>, limit: i64, offset: i64) -> Result<Vec<Recommendation>> {
+    match status_filter {
+        Some("all") => get_recommendations_by_statuses(&[], limit, offset),
+        Some(s) => get_recommendations_by_statuses(&[s], limit, offset),
+        None => get_recommendations_by_statuses(&["pending"], limit, offset),
+    }
+}
+
+/// Rows whose status is any of `statuses` (e.g. pending + failed for a
+/// "needs attention" view). An empty slice means every status.
+pub fn get_recommendations_by_statuses(statuses: &[&str], limit: i64, offset: i64) -> Result<Vec<Recommendation>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        let sql = match status_filter {
-            Some("all") => "SELECT id, status, title, summary, trigger, actions, n8n_prompt, confidence, workflow_id, workflow_json, evidence, pattern_id, last_error FROM recommendations ORDER BY created_at DESC",
-            Some(_) => "SELECT id, status, title, summary, trigger, actions, n8n_prompt, confidence, workflow_id, workflow_json, evidence, pattern_id, last_error FROM recommendations WHERE status = ?1 ORDER BY created_at DESC",
-            None => "SELECT id, status, title, summary, trigger, actions, n8n_prompt, confidence, workflow_id, workflow_json, evidence, pattern_id, last_error FROM recommendations WHERE status = 'pending' ORDER BY created_at DESC"
-        };
-        
-        let mut stmt = conn.prepare(sql)?;
-        
-        // Execute query map based on filter
-        let mut recs = Vec::new();
-
-        if let Some(s) = status_filter {
-            if s == "all" { 
-                let rows = stmt.query_map([], map_row)?;
-                for rec in rows { recs.push(rec?); }
-            } else { 
-                let rows = stmt.query_map([s], map_row)?;
-                for rec in rows { recs.push(rec?); }
-            }
+        let columns = "id, status, title, summary, trigger, actions, n8n_prompt, confidence, workflow_id, workflow_json, evidence, pattern_id, last_error";
+        let where_clause = if statuses.is_empty() {
+            String::new()
         } else {
-             let rows = stmt.query_map([], map_row)?;
-             for rec in rows { recs.push(rec?); }
+            let placeholders: Vec<String> = (1..=statuses.len()).map(|i| format!("?{}", i)).collect();
+            format!("WHERE status IN ({})", placeholders.join(", "))
         };
+        let sql = format!(
+            "SELECT {} FROM recommendations {} ORDER BY created_at DESC, id DESC LIMIT ?{} OFFSET ?{}",
+            columns,
+            where_clause,
+            statuses.len() + 1,
+            statuses.len() + 2
+        );
 
+        let offset = offset.max(0);
+        let mut bind: Vec<&dyn rusqlite::ToSql> = statuses.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        bind.push(&limit);
+        bind.push(&offset);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(bind.as_slice(), map_row)?;
+        let mut recs = Vec::new();
+        for rec in rows { recs.push(rec?); }
         Ok(recs)
     } else {
         Ok(Vec::new())
@@ -1590,8 +2714,8 @@ pub fn get_recommendations() -> Result<Vec<Recommendation>> {
 }
 
 // Deprecated wrapper
-pub fn list_recommendations(status: &str, _limit: i64) -> Result<Vec<Recommendation>> {
-    get_recommendations_with_filter(Some(status))
+pub fn list_recommendations(status: &str, limit: i64) -> Result<Vec<Recommendation>> {
+    get_recommendations_page(Some(status), limit, 0)
 }
 
 // Helper to map row to struct
@@ -1647,7 +2771,7 @@ pub fn get_recommendation(id: i64) -> Result<Option<Recommendation>> {
             let actions: Vec<String> = serde_json::from_str(&actions_json).unwrap_or_default();
             
             let evidence_json: String = row.get(10).unwrap_or_else(|_| "[]".to_string());
-            let evidence: Vec<String> = serde_json::from_str(&evidence_json).unwrap_or_default();
+            let evidence: Vec<crate::recommendation::Evidence> = serde_json::from_str(&evidence_json).unwrap_or_default();
 
             return Ok(Some(Recommendation {
                 id: row.get(0)?,
@@ -1691,6 +2815,24 @@ pub fn mark_recommendation_failed(id: i64, error: &str) -> Result<()> {
     Ok(())
 }
 
+/// Why the last workflow build for this recommendation failed, if it did.
+pub fn get_recommendation_last_error(id: i64) -> Result<Option<String>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        match conn.query_row(
+            "SELECT last_error FROM recommendations WHERE id = ?1",
+            params![id],
+            |row| row.get::<_, Option<String>>(0),
+        ) {
+            Ok(error) => Ok(error.filter(|e| !e.trim().is_empty())),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn mark_recommendation_approved(id: i64, workflow_id: &str, workflow_json: &str) -> Result<()> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -1705,6 +2847,41 @@ pub fn mark_recommendation_approved(id: i64, workflow_id: &str, workflow_json: &
     Ok(())
 }
 
+/// Set `status` on every id in one transaction; returns how many rows changed.
+pub fn update_recommendations_status(ids: &[i64], status: &str) -> Result<usize> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+        {
+            let mut stmt = tx.prepare("UPDATE recommendations SET status = ?1 WHERE id = ?2")?;
+            for id in ids {
+                changed += stmt.execute(params![status, id])?;
+            }
+        }
+        tx.commit()?;
+        return Ok(changed);
+    }
+    Ok(0)
+}
+
+/// Pending recommendations with confidence strictly below `threshold`.
+pub fn pending_recommendation_ids_below(threshold: f64) -> Result<Vec<i64>> {
+    let mut lock = get_db_lock();
+    if let Some(conn) = lock.as_mut() {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM recommendations WHERE status = 'pending' AND confidence < ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![threshold], |row| row.get(0))?;
+        let mut ids = Vec::new();
+        for row in rows {
+            ids.push(row?);
+        }
+        return Ok(ids);
+    }
+    Ok(Vec::new())
+}
+
 // --- V2 Event Ingestion (Matches Python Schema) ---
 
 pub fn init_v2() -> Result<()> {
@@ -1731,6 +2908,7 @@ pub fn init_v2() -> Result<()> {
             )",
             [],
         )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_events_v2_ts ON events_v2(ts)", [])?;
     }
     Ok(())
 }
@@ -1793,6 +2971,85 @@ pub fn init_sessions_table() -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Clone, serde::Serialize, PartialEq)]
+pub struct AppCount {
+    pub app: String,
+    pub events: i64,
+}
+
+/// One local hour of a day's activity.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TimelineBucket {
+    pub hour: u32,
+    pub events: i64,
+    pub top_apps: Vec<AppCount>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActivityTimeline {
+    pub day: String,
+    pub total_events: i64,
+    pub top_apps: Vec<AppCount>,
+    /// Always 24 entries, hour 0 to 23 in local time.
+    pub buckets: Vec<TimelineBucket>,
+}
+
+fn top_apps(counts: std::collections::HashMap<String, i64>, limit: usize) -> Vec<AppCount> {
+    let mut apps: Vec<AppCount> = counts.into_iter().map(|(app, events)| AppCount { app, events }).collect();
+    apps.sort_by(|a, b| b.events.cmp(&a.events).then_with(|| a.app.cmp(&b.app)));
+    apps.truncate(limit);
+    apps
+}
+
+/// `events_v2` for one local day, counted per hour with the busiest apps.
+pub fn activity_timeline(day: chrono::NaiveDate) -> Result<ActivityTimeline> {
+    use chrono::{Local, TimeZone, Timelike};
+    let mut rows: Vec<(String, String)> = Vec::new();
+    {
+        let mut lock = get_db_lock();
+        if let Some(conn) = lock.as_mut() {
+            // Widen the range by a day each side (ts may carry any offset), then filter on the local date
+            let from = (day - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+            let to = (day + chrono::Duration::days(2)).format("%Y-%m-%d").to_string();
+            let mut stmt = conn.prepare("SELECT ts, app FROM events_v2 WHERE ts >= ?1 AND ts < ?2")?;
+            let mapped = stmt.query_map(params![from, to], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            for row in mapped {
+                rows.push(row?);
+            }
+        }
+    }
+
+    let mut hourly: Vec<std::collections::HashMap<String, i64>> = vec![Default::default(); 24];
+    let mut daily: std::collections::HashMap<String, i64> = Default::default();
+    let mut total = 0;
+    for (ts, app) in rows {
+        let Ok(at) = chrono::DateTime::parse_from_rfc3339(&ts) else { continue };
+        let local = Local.from_utc_datetime(&at.naive_utc());
+        if local.date_naive() != day {
+            continue;
+        }
+        *hourly[local.hour() as usize].entry(app.clone()).or_default() += 1;
+        *daily.entry(app).or_default() += 1;
+        total += 1;
+    }
+
+    let buckets = hourly
+        .into_iter()
+        .enumerate()
+        .map(|(hour, counts)| TimelineBucket {
+            hour: hour as u32,
+            events: counts.values().sum(),
+            top_apps: top_apps(counts, 3),
+        })
+        .collect();
+    Ok(ActivityTimeline {
+        day: day.format("%Y-%m-%d").to_string(),
+        total_events: total,
+        top_apps: top_apps(daily, 5),
+        buckets,
+    })
+}
+
 pub fn fetch_all_events_v2(limit: i64) -> Result<Vec<crate::schema::EventEnvelope>> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -1830,12 +3087,16 @@ pub fn fetch_all_events_v2(limit: i64) -> Result<Vec<crate::schema::EventEnvelop
                  window_title: row.get(13).ok(),
                  browser_url: row.get(14).ok(),
                  raw,
+                 seq: None,
+                 repeat: None,
              })
         })?;
 
         let mut events = Vec::new();
         for r in rows {
-            events.push(r?);
+            if let Some(envelope) = upgrade_stored_event(r?) {
+                events.push(envelope);
+            }
         }
         Ok(events)
     } else {
@@ -1843,6 +3104,23 @@ pub fn fetch_all_events_v2(limit: i64) -> Result<Vec<crate::schema::EventEnvelop
     }
 }
 
+/// A stored event in the current envelope shape; `None` (logged) when it was
+/// written by a newer schema version than this build reads.
+fn upgrade_stored_event(envelope: crate::schema::EventEnvelope) -> Option<crate::schema::EventEnvelope> {
+    if envelope.schema_version == crate::schema::CURRENT_SCHEMA_VERSION {
+        return Some(envelope);
+    }
+    let event_id = envelope.event_id.clone();
+    let value = serde_json::to_value(&envelope).ok()?;
+    match crate::schema::EventEnvelope::normalize_value(value) {
+        Ok(upgraded) => Some(upgraded),
+        Err(e) => {
+            eprintln!("⚠️ Skipping stored event {}: {}", event_id, e);
+            None
+        }
+    }
+}
+
 pub fn insert_session(session: &crate::session::SessionRecord) -> Result<()> {
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
@@ -1857,28 +3135,19 @@ pub fn insert_session(session: &crate::session::SessionRecord) -> Result<()> {
 }
 
 // Legacy simple insert (kept for backward compat during migration)
-pub fn insert_event(event_json: &str) -> Result<()> {
+pub fn insert_event(event_json: &str) -> std::result::Result<(), WriteError> {
+    // Validate + normalize before touching the table so bad producers can't store junk
+    let envelope = crate::schema::EventEnvelope::normalize_json(event_json)
+        .map_err(|reason| WriteError::Invalid(format!("Rejected event: {}", reason)))?;
+
     let mut lock = get_db_lock();
     if let Some(conn) = lock.as_mut() {
-        // Parse basic fields
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(event_json) {
-            let timestamp_str = value["timestamp"].as_str().unwrap_or("");
-            let timestamp = if timestamp_str.is_empty() {
-                chrono::Utc::now().to_rfc3339()
-            } else {
-                timestamp_str.to_string()
-            };
-            
-            let source = value["source"].as_str().unwrap_or("unknown");
-            let type_ = value["type"].as_str().unwrap_or("unknown");
-            // Store full JSON in data
-            let data = event_json;
-
-            conn.execute(
-                "INSERT INTO events (timestamp, source, type, data) VALUES (?1, ?2, ?3, ?4)",
-                params![timestamp, source, type_, data],
-            )?;
-        }
+        // Store the normalized envelope in data
+        let data = serde_json::to_string(&envelope).unwrap_or_else(|_| event_json.to_string());
+        conn.execute(
+            "INSERT INTO events (timestamp, source, type, data) VALUES (?1, ?2, ?3, ?4)",
+            params![envelope.ts, envelope.source, envelope.event_type, data],
+        )?;
     }
     Ok(())
 }
@@ -1929,14 +3198,18 @@ pub fn get_recent_events(hours: i64) -> Result<Vec<String>> {
                 window_title: row.get(13).ok(),
                 browser_url: row.get(14).ok(),
                 raw,
+                seq: None,
+                repeat: None,
             };
 
-            Ok(serde_json::to_string(&envelope).unwrap_or_default())
+            Ok(envelope)
         })?;
 
         let mut events = Vec::new();
         for event in rows {
-            events.push(event?);
+            if let Some(envelope) = upgrade_stored_event(event?) {
+                events.push(serde_json::to_string(&envelope).unwrap_or_default());
+            }
         }
 
         if !events.is_empty() {
@@ -1950,10 +3223,14 @@ pub fn get_recent_events(hours: i64) -> Result<Vec<String>> {
              ORDER BY timestamp ASC"
         )?;
 
-        let rows = stmt.query_map([hours], |row| row.get(0))?;
+        let rows = stmt.query_map([hours], |row| row.get::<_, String>(0))?;
         let mut legacy_events = Vec::new();
         for event in rows {
-            legacy_events.push(event?);
+            let data = event?;
+            match crate::schema::EventEnvelope::normalize_json(&data) {
+                Ok(envelope) => legacy_events.push(serde_json::to_string(&envelope).unwrap_or(data)),
+                Err(e) => eprintln!("⚠️ Skipping stored event: {}", e),
+            }
         }
         return Ok(legacy_events);
     }
@@ -2017,6 +3294,40 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_templates_add_and_instantiate() {
+        init().ok();
+        let id = format!("test-{}", uuid::Uuid::new_v4());
+        let json = r#"{"name": "Ping", "nodes": [{"parameters": {"chatId": "{{chat_id}}", "text": "{{ note }} at ={{ $now }} / {{chat_id}}"}}]}"#;
+        add_template(&id, "Ping", "test", json).unwrap();
+
+        let stored = get_template(&id).unwrap().unwrap();
+        assert!(!stored.builtin);
+        assert_eq!(stored.params(), vec!["chat_id".to_string(), "note".to_string()]);
+        assert!(list_templates().unwrap().iter().any(|t| t.id == id));
+
+        let mut params = std::collections::HashMap::new();
+        params.insert("chat_id".to_string(), "42".to_string());
+        let err = instantiate_template(&id, &params).unwrap_err();
+        assert!(err.to_string().contains("needs note"), "{}", err);
+        assert!(matches!(err, WriteError::Invalid(_)));
+
+        // Values are escaped for JSON; n8n expressions stay as they are
+        params.insert("note".to_string(), "say \"hi\"".to_string());
+        let filled = instantiate_template(&id, &params).unwrap();
+        let val: serde_json::Value = serde_json::from_str(&filled).unwrap();
+        assert_eq!(val["nodes"][0]["parameters"]["chatId"], "42");
+        assert_eq!(val["nodes"][0]["parameters"]["text"], "say \"hi\" at ={{ $now }} / 42");
+
+        assert!(matches!(instantiate_template("no-such-template", &params), Err(WriteError::NotFound(_))));
+
+        // The old hardcoded examples are default templates
+        let briefing = get_template("morning-briefing").unwrap().unwrap();
+        assert!(briefing.builtin);
+        assert_eq!(briefing.params(), vec!["chat_id".to_string()]);
+        assert_eq!(get_template("urgent-mail").unwrap().unwrap().params(), vec!["keyword".to_string(), "chat_id".to_string()]);
+    }
+
     #[test]
     fn test_insert_event() {
         init().ok(); // Might error if already init
@@ -2025,4 +3336,496 @@ mod tests {
         let insert_result = insert_event(test_event);
         assert!(insert_result.is_ok());
     }
+
+    #[test]
+    fn test_insert_event_valid_envelope() {
+        init().ok();
+        let event = r#"{"schema_version":"1.0","event_id":"e1","ts":"2024-01-01T09:00:00Z","source":"unit_test","app":"Safari","event_type":"app_switch","priority":"P2","payload":{},"pid":null,"window_id":null,"window_title":null,"browser_url":null}"#;
+        assert!(insert_event(event).is_ok());
+    }
+
+    #[test]
+    fn test_insert_event_fills_missing_optional_fields() {
+        let env = crate::schema::EventEnvelope::normalize_json(
+            r#"{"source":"unit_test","event_type":"click"}"#,
+        )
+        .unwrap();
+        assert_eq!(env.schema_version, "1.0");
+        assert_eq!(env.app, "unknown");
+        assert_eq!(env.priority, "P2");
+        assert!(!env.event_id.is_empty());
+
+        // Required fields are still enforced
+        assert!(crate::schema::EventEnvelope::normalize_json(r#"{"source":"unit_test"}"#).is_err());
+    }
+
+    #[test]
+    fn test_insert_event_rejects_garbage() {
+        init().ok();
+        assert!(matches!(insert_event("not json at all"), Err(WriteError::Invalid(_))));
+        assert!(insert_event("[1,2,3]").is_err());
+        assert!(insert_event(r#"{"schema_version":"9.0","source":"x","event_type":"y"}"#).is_err());
+    }
+
+    #[test]
+    fn test_recommendation_pagination() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        for i in 0..5 {
+            let proposal = AutomationProposal {
+                title: format!("page test {}", i),
+                trigger: format!("paging trigger {} {}", tag, i),
+                actions: vec![format!("action {} {}", tag, i)],
+                ..Default::default()
+            };
+            insert_recommendation(&proposal).unwrap();
+        }
+
+        let all = get_recommendations_page(Some("all"), -1, 0).unwrap();
+        let page1 = get_recommendations_page(Some("all"), 2, 0).unwrap();
+        let page2 = get_recommendations_page(Some("all"), 2, 2).unwrap();
+        assert_eq!(page1.len(), 2);
+        assert_eq!(page2.len(), 2);
+
+        let ids1: Vec<i64> = page1.iter().map(|r| r.id).collect();
+        assert!(page2.iter().all(|r| !ids1.contains(&r.id)));
+        // Pages line up with the unpaged ordering
+        let expected: Vec<i64> = all.iter().take(4).map(|r| r.id).collect();
+        let got: Vec<i64> = page1.iter().chain(page2.iter()).map(|r| r.id).collect();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_exec_result_lifecycle() {
+        init().ok();
+        let created = create_exec_result("echo lifecycle", None).unwrap();
+        let fetched = get_exec_result(&created.id).unwrap().expect("result exists");
+        assert_eq!(fetched.status, "pending");
+        assert!(get_exec_result("no-such-id").unwrap().is_none());
+
+        // A fresh row survives a sweep; a zero TTL expires it
+        expire_stale_exec_results(3600).unwrap();
+        assert_eq!(get_exec_result(&created.id).unwrap().unwrap().status, "pending");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        expire_stale_exec_results(0).unwrap();
+        assert_eq!(get_exec_result(&created.id).unwrap().unwrap().status, "expired");
+    }
+
+    #[test]
+    fn test_llm_calls_by_session() {
+        init().ok();
+        let session = uuid::Uuid::new_v4().to_string();
+        for purpose in ["plan", "step"] {
+            insert_llm_call(&LlmCall {
+                session_key: Some(session.clone()),
+                purpose: purpose.to_string(),
+                model: "gpt-4o".to_string(),
+                prompt_hash: "abc".to_string(),
+                latency_ms: 12,
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let calls = list_llm_calls(Some(&session), 10).unwrap();
+        let purposes: Vec<&str> = calls.iter().map(|c| c.purpose.as_str()).collect();
+        assert_eq!(purposes, vec!["plan", "step"]);
+        assert!(calls.iter().all(|c| c.prompt.is_none()));
+    }
+
+    #[test]
+    fn test_goal_steps_are_capped_per_session_with_constant_work() {
+        init().ok();
+        let session = uuid::Uuid::new_v4().to_string();
+        let other = uuid::Uuid::new_v4().to_string();
+        let step = |key: &str, i: i64| GoalStep {
+            session_key: key.to_string(),
+            step_index: i,
+            description: format!("step {}", i),
+            action_type: "WAIT".to_string(),
+            outcome: "success".to_string(),
+            ..Default::default()
+        };
+        insert_goal_step_capped(&step(&other, 0), 5).unwrap();
+
+        let mut trimmed = Vec::new();
+        for i in 0..30 {
+            trimmed.push(insert_goal_step_capped(&step(&session, i), 5).unwrap().1);
+        }
+        // Nothing to trim until the cap is reached, then exactly one row per insert
+        assert_eq!(&trimmed[..5], &[0; 5]);
+        assert!(trimmed[5..].iter().all(|n| *n == 1));
+
+        let kept: Vec<i64> = list_goal_steps(&session, 100).unwrap().iter().map(|s| s.step_index).collect();
+        assert_eq!(kept, vec![25, 26, 27, 28, 29]);
+        assert_eq!(list_goal_steps(&other, 100).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_routine_steps_come_from_the_latest_successful_run() {
+        init().ok();
+        let routine_id = -1 - (uuid::Uuid::new_v4().as_u128() as u32 as i64);
+        let mut runs = Vec::new();
+        for (status, description) in [("success", "open the sales sheet"), ("failed", "open the wrong sheet")] {
+            let session = uuid::Uuid::new_v4().to_string();
+            let run_id = create_routine_run(routine_id).unwrap();
+            set_routine_run_session(run_id, &session).unwrap();
+            insert_goal_step(&GoalStep {
+                session_key: session.clone(),
+                description: description.to_string(),
+                action_type: "URL".to_string(),
+                outcome: "success".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+            finish_routine_run(run_id, status, None).unwrap();
+            runs.push(session);
+        }
+
+        let steps = get_routine_steps(routine_id).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].session_key, runs[0]);
+        assert!(get_routine_steps(routine_id - 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_purge_run_sessions_removes_only_stale() {
+        init().ok();
+        let stale = uuid::Uuid::new_v4().to_string();
+        let fresh = uuid::Uuid::new_v4().to_string();
+        for key in [&stale, &fresh] {
+            insert_goal_step(&GoalStep {
+                session_key: key.clone(),
+                description: "open the quarterly report".to_string(),
+                action_type: "URL".to_string(),
+                decision_source: "llm".to_string(),
+                outcome: "success".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+            insert_llm_call(&LlmCall {
+                session_key: Some(key.clone()),
+                purpose: "plan".to_string(),
+                response: Some("[]".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        {
+            let mut lock = get_db_lock();
+            let conn = lock.as_mut().unwrap();
+            for table in ["goal_steps", "llm_calls"] {
+                conn.execute(
+                    &format!("UPDATE {} SET created_at = '2000-01-01T00:00:00+00:00' WHERE session_key = ?1", table),
+                    params![stale],
+                )
+                .unwrap();
+            }
+        }
+
+        let listed = list_run_sessions(10_000).unwrap();
+        let entry = listed.iter().find(|s| s.session_key == fresh).unwrap();
+        assert_eq!((entry.steps, entry.llm_calls), (1, 1));
+        assert!(entry.bytes > 0);
+
+        let purged = purge_run_sessions_before("2001-01-01T00:00:00+00:00").unwrap();
+        assert!(purged.contains(&stale) && !purged.contains(&fresh));
+        assert!(list_goal_steps(&stale, 10).unwrap().is_empty());
+        assert!(list_llm_calls(Some(&stale), 10).unwrap().is_empty());
+        assert_eq!(list_goal_steps(&fresh, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_goal_steps_keep_decision_source() {
+        init().ok();
+        let session = uuid::Uuid::new_v4().to_string();
+        for (i, source) in ["llm", "fallback:element_missing"].iter().enumerate() {
+            insert_goal_step(&GoalStep {
+                session_key: session.clone(),
+                step_index: i as i64,
+                action_type: "CLICK".to_string(),
+                description: format!("step {}", i),
+                decision_source: source.to_string(),
+                outcome: "success".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let steps = list_goal_steps(&session, 10).unwrap();
+        let sources: Vec<&str> = steps.iter().map(|s| s.decision_source.as_str()).collect();
+        assert_eq!(sources, vec!["llm", "fallback:element_missing"]);
+    }
+
+    #[test]
+    fn test_bulk_recommendation_status() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let mut ids = Vec::new();
+        for (i, confidence) in [0.5, 0.6, 0.9].iter().enumerate() {
+            let proposal = AutomationProposal {
+                title: format!("bulk {}", i),
+                trigger: format!("bulk trigger {} {}", tag, i),
+                actions: vec![format!("bulk action {} {}", tag, i)],
+                confidence: *confidence,
+                ..Default::default()
+            };
+            assert!(insert_recommendation(&proposal).unwrap());
+            let id = get_recommendations_by_statuses(&[], -1, 0)
+                .unwrap()
+                .into_iter()
+                .find(|r| r.trigger == proposal.trigger)
+                .map(|r| r.id)
+                .expect("inserted row");
+            ids.push(id);
+        }
+
+        let below = pending_recommendation_ids_below(0.75).unwrap();
+        assert!(below.contains(&ids[0]) && below.contains(&ids[1]));
+        assert!(!below.contains(&ids[2]));
+
+        assert_eq!(update_recommendations_status(&ids[..2], "rejected").unwrap(), 2);
+        assert_eq!(get_recommendation(ids[0]).unwrap().unwrap().status, "rejected");
+        assert_eq!(get_recommendation(ids[1]).unwrap().unwrap().status, "rejected");
+        assert_eq!(get_recommendation(ids[2]).unwrap().unwrap().status, "pending");
+        assert_eq!(update_recommendations_status(&[-1], "rejected").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_activity_timeline_buckets_by_local_hour() {
+        use chrono::TimeZone;
+        init().ok();
+        let app = format!("TimelineApp-{}", uuid::Uuid::new_v4());
+        let other = format!("{}-other", app);
+        // A day of its own per run: the test DB is shared and only the top apps per hour are kept
+        let offset = (uuid::Uuid::new_v4().as_u128() % 10_000) as i64;
+        let day = chrono::NaiveDate::from_ymd_opt(1990, 1, 1).unwrap() + chrono::Duration::days(offset);
+        let at = |hour: u32, minute: u32| {
+            chrono::Local
+                .from_local_datetime(&day.and_hms_opt(hour, minute, 0).unwrap())
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339()
+        };
+        for (ts, app) in [(at(9, 5), &app), (at(9, 40), &app), (at(9, 50), &other), (at(14, 0), &app)] {
+            let mut event = crate::schema::EventEnvelope::new("unit_test", app, "app_switch", "P2", None, serde_json::json!({}));
+            event.ts = ts;
+            insert_event_v2(&event).unwrap();
+        }
+
+        let timeline = activity_timeline(day).unwrap();
+        assert_eq!(timeline.buckets.len(), 24);
+        let count = |hour: usize, name: &str| {
+            timeline.buckets[hour].top_apps.iter().find(|a| a.app == name).map(|a| a.events)
+        };
+        assert_eq!(count(9, &app), Some(2));
+        assert_eq!(count(9, &other), Some(1));
+        assert_eq!(count(14, &app), Some(1));
+        assert!(timeline.buckets[9].events >= 3);
+        assert!(timeline.total_events >= 4);
+    }
+
+    #[test]
+    fn test_routine_candidate_dedupes_by_pattern_id() {
+        init().ok();
+        let pattern = crate::pattern_detector::DetectedPattern {
+            pattern_id: format!("test-{}", uuid::Uuid::new_v4()),
+            pattern_type: crate::pattern_detector::PatternType::AppSequence,
+            description: "Chrome → Slack".to_string(),
+            occurrences: 6,
+            similarity_score: 0.9,
+            sample_events: vec![],
+            detected_at: chrono::Utc::now(),
+        };
+        let before = count_routine_candidates().unwrap();
+        assert!(insert_routine_candidate(&pattern).unwrap());
+        assert!(!insert_routine_candidate(&pattern).unwrap());
+        assert_eq!(count_routine_candidates().unwrap(), before + 1);
+    }
+
+    #[test]
+    fn test_recommendations_by_statuses() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let mut ids = Vec::new();
+        for (i, status) in ["pending", "failed", "approved"].iter().enumerate() {
+            let proposal = AutomationProposal {
+                title: format!("multi status {}", i),
+                trigger: format!("multi status trigger {} {}", tag, i),
+                actions: vec![format!("multi action {} {}", tag, i)],
+                ..Default::default()
+            };
+            assert!(insert_recommendation(&proposal).unwrap());
+            let id = get_recommendations_by_statuses(&[], -1, 0)
+                .unwrap()
+                .into_iter()
+                .find(|r| r.trigger == proposal.trigger)
+                .map(|r| r.id)
+                .expect("inserted row");
+            update_recommendation_status(id, status).unwrap();
+            ids.push(id);
+        }
+
+        let attention = get_recommendations_by_statuses(&["pending", "failed"], -1, 0).unwrap();
+        assert!(attention.iter().any(|r| r.id == ids[0]));
+        assert!(attention.iter().any(|r| r.id == ids[1]));
+        assert!(!attention.iter().any(|r| r.id == ids[2]));
+        assert!(attention.iter().all(|r| r.status == "pending" || r.status == "failed"));
+
+        let metrics = get_recommendation_metrics().unwrap();
+        assert_eq!(metrics.by_status.get("failed").copied(), Some(metrics.failed));
+    }
+
+    #[test]
+    fn test_recommendation_last_error_is_kept_for_retry() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let proposal = AutomationProposal {
+            title: "retry me".to_string(),
+            trigger: format!("retry trigger {}", tag),
+            actions: vec![format!("retry action {}", tag)],
+            ..Default::default()
+        };
+        assert!(insert_recommendation(&proposal).unwrap());
+        let id = get_recommendations_by_statuses(&[], -1, 0)
+            .unwrap()
+            .into_iter()
+            .find(|r| r.trigger == proposal.trigger)
+            .map(|r| r.id)
+            .expect("inserted row");
+
+        assert_eq!(get_recommendation_last_error(id).unwrap(), None);
+        mark_recommendation_failed(id, "Workflow validation failed: node 'Notify' has no type").unwrap();
+        assert_eq!(
+            get_recommendation_last_error(id).unwrap().as_deref(),
+            Some("Workflow validation failed: node 'Notify' has no type")
+        );
+        assert_eq!(get_recommendation(id).unwrap().unwrap().status, "failed");
+        assert_eq!(get_recommendation_last_error(i64::MIN).unwrap(), None);
+    }
+
+    #[test]
+    fn test_recommendations_dedupe_on_full_fingerprint() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let proposal = |action: &str| AutomationProposal {
+            title: "shared trigger".to_string(),
+            trigger: format!("Shared trigger {}", tag),
+            actions: vec![format!("{} {}", action, tag)],
+            ..Default::default()
+        };
+        assert!(insert_recommendation(&proposal("Archive the file")).unwrap());
+        // Same trigger, different actions: a separate suggestion
+        assert!(insert_recommendation(&proposal("Email the file")).unwrap());
+        // Same normalized fingerprint: skipped
+        assert!(!insert_recommendation(&proposal("archive  the FILE!")).unwrap());
+
+        // The trigger in other words and order is recognized as already open
+        assert!(recommendation_exists_like(&format!("{}, TRIGGER shared!", tag)).unwrap());
+        assert!(!recommendation_exists_like(&format!("Other trigger {}", tag)).unwrap());
+
+        // The one-time dedupe doesn't run again on the next startup
+        init().ok();
+        assert_eq!(migrate_recommendation_fingerprints().unwrap(), 0);
+        let version: i64 = get_db_lock()
+            .as_ref()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert!(version >= RECOMMENDATION_FINGERPRINT_VERSION);
+    }
+
+    #[test]
+    fn test_config_updates_are_audited_with_prior_value() {
+        init().ok();
+        let key = format!("test-policy-{}", uuid::Uuid::new_v4());
+        let audit_key = format!("approval_policy:{}", key);
+        upsert_approval_policy(&key, "ask").unwrap();
+        upsert_approval_policy(&key, "ask").unwrap(); // unchanged: not recorded
+        upsert_approval_policy(&key, "allow_always").unwrap();
+        delete_approval_policy(&key).unwrap();
+
+        let history = list_config_changes(Some(&audit_key), 10).unwrap();
+        let values: Vec<(Option<&str>, Option<&str>)> =
+            history.iter().map(|c| (c.old_value.as_deref(), c.new_value.as_deref())).collect();
+        assert_eq!(values, vec![(Some("allow_always"), None), (Some("ask"), Some("allow_always")), (None, Some("ask"))]);
+        assert!(history.iter().all(|c| c.config_key == audit_key && !c.changed_at.is_empty()));
+
+        let id = add_exec_allowlist(&format!("echo {}", key), Some("/tmp")).unwrap();
+        remove_exec_allowlist(id).unwrap();
+        let allowlist = list_config_changes(Some(&format!("exec_allowlist:{}", id)), 10).unwrap();
+        assert_eq!(allowlist[0].old_value, Some(format!("echo {} (in /tmp)", key)));
+        assert_eq!(allowlist[1].new_value, allowlist[0].old_value);
+    }
+
+    #[test]
+    fn test_validate_cron() {
+        // Six/seven-field expressions (with seconds) are accepted
+        assert!(validate_cron("0 0 9 * * *").is_ok());
+        assert!(validate_cron("0 30 8 * * Mon-Fri").is_ok());
+        assert!(validate_cron("0 0 9 * * * 2099").is_ok());
+
+        // Classic 5-field crontab is the most common mistake
+        let err = validate_cron("0 9 * * *").unwrap_err();
+        assert!(err.contains("5 fields"), "{}", err);
+
+        assert!(validate_cron("").is_err());
+        assert!(validate_cron("0 0 9 * * * * *").is_err());
+        assert!(validate_cron("0 0 25 * * *").is_err());
+
+        // Routines with a bad cron are refused as invalid input, not a SQLite error
+        init().ok();
+        let err = create_routine("bad cron", "0 9 * * *", "noop").unwrap_err();
+        assert!(matches!(err, WriteError::Invalid(ref msg) if msg.contains("5 fields")), "{}", err);
+    }
+
+    #[test]
+    fn test_promote_recommendation_links_a_routine() {
+        init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let proposal = AutomationProposal {
+            title: format!("Backup downloads {}", tag),
+            trigger: format!("promote trigger {}", tag),
+            actions: vec!["Find new PDFs".to_string(), "Upload them to Drive".to_string()],
+            n8n_prompt: "When a PDF lands in Downloads, upload it to Drive".to_string(),
+            ..Default::default()
+        };
+        assert!(insert_recommendation(&proposal).unwrap());
+        let id = get_recommendations_by_statuses(&[], -1, 0)
+            .unwrap()
+            .into_iter()
+            .find(|r| r.trigger == proposal.trigger)
+            .map(|r| r.id)
+            .expect("inserted row");
+
+        assert!(matches!(promote_recommendation_to_routine(id, "0 9 * * *"), Err(WriteError::Invalid(_))));
+        // Still pending: not scheduled until approved
+        let pending = promote_recommendation_to_routine(id, "0 0 9 * * *").unwrap_err();
+        assert!(pending.to_string().contains("approve it"), "{}", pending);
+        assert_eq!(get_recommendation_routine(id).unwrap(), None);
+        update_recommendations_status(&[id], "approved").unwrap();
+
+        let routine_id = promote_recommendation_to_routine(id, "0 0 9 * * *").unwrap();
+        assert_eq!(get_recommendation_routine(id).unwrap(), Some(routine_id));
+        let routine = get_all_routines().unwrap().into_iter().find(|r| r.id == routine_id).expect("routine created");
+        assert_eq!(routine.name, proposal.title);
+        assert_eq!(routine.cron_expression, "0 0 9 * * *");
+        assert_eq!(
+            routine.prompt,
+            "When a PDF lands in Downloads, upload it to Drive\nSteps: Find new PDFs; Upload them to Drive"
+        );
+        assert!(routine.next_run.is_some());
+
+        let again = promote_recommendation_to_routine(id, "0 0 9 * * *").unwrap_err();
+        assert!(again.to_string().contains(&format!("already routine #{}", routine_id)), "{}", again);
+        assert!(matches!(again, WriteError::Invalid(_)));
+        assert!(matches!(promote_recommendation_to_routine(i64::MIN, "0 0 9 * * *"), Err(WriteError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_create_routine_rejects_invalid_cron() {
+        init().ok();
+        let result = create_routine("bad", "* * * * *", "noop");
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,107 @@
+// [Debug Overlay] With STEER_DEBUG_OVERLAY=1 a red frame flashes for a moment
+// around each element the agent clicked, so a click that hit the wrong button
+// is visible while developing. The frame is drawn by a separate `osascript`
+// (JavaScript for Automation) process; clicks don't wait for it. Other
+// platforms have no drawing support yet, so there it does nothing.
+
+/// How long the frame stays up.
+const FLASH_MS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+pub fn enabled() -> bool {
+    std::env::var("STEER_DEBUG_OVERLAY")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Frame from "x, y, width, height" (as System Events reports position and size).
+pub fn parse_frame(text: &str) -> Option<Frame> {
+    let numbers: Vec<f64> = text
+        .split(',')
+        .map(|n| n.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match numbers[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => Some(Frame { x, y, width, height }),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+const FLASH_SCRIPT: &str = r#"ObjC.import('Cocoa');
+function run(argv) {
+  var x = +argv[0], y = +argv[1], w = +argv[2], h = +argv[3], ms = +argv[4];
+  var screenH = $.NSScreen.screens.objectAtIndex(0).frame.size.height;
+  var rect = $.NSMakeRect(x - 3, screenH - y - h - 3, w + 6, h + 6);
+  var win = $.NSWindow.alloc.initWithContentRectStyleMaskBackingDefer(rect, $.NSWindowStyleMaskBorderless, $.NSBackingStoreBuffered, false);
+  win.opaque = false;
+  win.backgroundColor = $.NSColor.clearColor;
+  win.level = $.NSStatusWindowLevel;
+  win.ignoresMouseEvents = true;
+  win.contentView.wantsLayer = true;
+  win.contentView.layer.borderWidth = 3;
+  win.contentView.layer.borderColor = $.NSColor.systemRedColor.CGColor;
+  win.orderFrontRegardless;
+  $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(ms / 1000));
+}"#;
+
+/// Flash a frame around `frame` (screen points, top-left origin) if the overlay is on.
+pub fn flash(frame: Frame) {
+    if !enabled() {
+        return;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let args = [frame.x, frame.y, frame.width, frame.height, FLASH_MS as f64].map(|n| n.to_string());
+        match std::process::Command::new("osascript").args(["-l", "JavaScript", "-e", FLASH_SCRIPT]).args(args).spawn() {
+            // Reap the helper in the background instead of waiting for it
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+            }
+            Err(e) => eprintln!("⚠️ Debug overlay failed: {}", e),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (frame, FLASH_MS);
+}
+
+/// Flash around the frontmost window's element with this role and name.
+pub fn flash_element(role: &str, name: &str) {
+    if !enabled() {
+        return;
+    }
+    match crate::applescript::window_element_frame(role, name) {
+        Ok(Some(text)) => {
+            if let Some(frame) = parse_frame(&text) {
+                println!("      🔴 [Overlay] {} '{}' at {:?}", role, name, frame);
+                flash(frame);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("⚠️ Debug overlay could not locate {} '{}': {}", role, name, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_read_from_position_and_size() {
+        assert_eq!(
+            parse_frame("120, 48, 80, 24"),
+            Some(Frame { x: 120.0, y: 48.0, width: 80.0, height: 24.0 })
+        );
+        assert_eq!(parse_frame("-1440, 10.5, 32, 32").map(|f| (f.x, f.y)), Some((-1440.0, 10.5)));
+        assert_eq!(parse_frame("120, 48, 0, 24"), None);
+        assert_eq!(parse_frame("120, 48"), None);
+        assert_eq!(parse_frame("missing value"), None);
+    }
+}
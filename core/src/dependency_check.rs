@@ -72,3 +72,109 @@ impl SystemHealth {
         println!("\nPlease install these tools for full functionality.\n");
     }
 }
+
+#[derive(Serialize, Clone)]
+pub struct Capability {
+    pub name: &'static str,
+    pub supported: bool,
+    /// Unavailable in lite mode (no LLM configured)
+    pub needs_llm: bool,
+    pub note: &'static str,
+}
+
+impl Capability {
+    fn new(name: &'static str, supported: bool, note: &'static str) -> Self {
+        Self { name, supported, needs_llm: false, note }
+    }
+
+    fn llm(name: &'static str, supported: bool, note: &'static str) -> Self {
+        Self { name, supported, needs_llm: true, note }
+    }
+}
+
+// [Lite Mode] Without OPENAI_API_KEY the agent still records, sanitizes and
+// sessionizes events, detects patterns, proposes template-based
+// recommendations and replays routines that already ran once; anything that
+// asks a model to plan, see or write is off.
+
+/// Which agent actions and features work here. LLM features are marked
+/// unsupported when `llm_available` is false (lite mode).
+pub fn capabilities(llm_available: bool) -> Vec<Capability> {
+    let macos = cfg!(target_os = "macos");
+    let screen = crate::screen_access::available();
+    vec![
+        Capability::new("snapshot", macos || cfg!(target_os = "linux"), "Accessibility tree (macOS AX API, Linux AT-SPI2)"),
+        Capability::new("click", macos, "AppleScript / cliclick"),
+        Capability::new("type", macos, "AppleScript keystroke"),
+        Capability::new("screen_capture", screen, "screencapture (used for vision verification)"),
+        Capability::llm("ocr", screen && llm_available, "Via LLM vision on captured screens; needs screen_capture"),
+        Capability::new("read_selection", macos, "Selected text via AX (leaves the clipboard alone)"),
+        Capability::new("clipboard", true, "arboard (paste keystroke is macOS only)"),
+        Capability::new("shell", true, "sh -c with safety analysis"),
+        Capability::new("event_capture", true, "Events are sanitized, stored and sessionized"),
+        Capability::new("pattern_detection", true, "Heuristic detector and routine candidates"),
+        Capability::new("template_recs", true, "Recommendations from built-in templates"),
+        Capability::new("integrations", true, "Calendar, Telegram, Notion and Gmail tools"),
+        Capability::llm("goals", llm_available, "Run natural-language goals (run, surf, observe, extract)"),
+        Capability::llm("routines", llm_available, "Scheduled routines planned by the LLM"),
+        Capability::new("routine_replay", true, "Routines replay the plan of their last successful run"),
+        Capability::llm("ai_recs", llm_available, "LLM-written recommendations and vector memory"),
+        Capability::llm("workflow_build", llm_available, "Turn approved recommendations into n8n workflows"),
+    ]
+}
+
+pub fn print_capabilities(llm_available: bool) {
+    let mode = if llm_available { "" } else { ", lite mode: no LLM" };
+    println!("🧰 Capabilities ({}{}):", std::env::consts::OS, mode);
+    for cap in capabilities(llm_available) {
+        let mark = if cap.supported { "✅" } else { "❌" };
+        println!("   {} {:<17} {}", mark, cap.name, cap.note);
+    }
+    if !llm_available {
+        println!("   Set OPENAI_API_KEY and restart to enable the ❌ LLM features.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supported(llm: bool, name: &str) -> bool {
+        capabilities(llm).iter().find(|c| c.name == name).map(|c| c.supported).unwrap()
+    }
+
+    #[test]
+    fn test_capabilities_follow_the_platform() {
+        let macos = cfg!(target_os = "macos");
+        assert_eq!(supported(true, "click"), macos);
+        assert_eq!(supported(true, "type"), macos);
+        assert_eq!(supported(true, "read_selection"), macos);
+        assert_eq!(supported(true, "snapshot"), macos || cfg!(target_os = "linux"));
+        assert_eq!(supported(true, "screen_capture"), crate::screen_access::available());
+        assert!(supported(true, "shell") && supported(true, "clipboard"));
+
+        let mut names: Vec<&str> = capabilities(true).iter().map(|c| c.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), capabilities(true).len(), "capability names must be unique");
+    }
+
+    #[test]
+    fn test_lite_mode_disables_only_llm_features() {
+        for name in ["goals", "routines", "ai_recs", "workflow_build", "ocr"] {
+            assert!(!supported(false, name), "{} should need an LLM", name);
+        }
+        for name in ["event_capture", "pattern_detection", "template_recs", "integrations", "shell", "routine_replay"] {
+            assert!(supported(false, name), "{} should work in lite mode", name);
+        }
+        assert!(supported(true, "goals"));
+        let lite = capabilities(false);
+        let full = capabilities(true);
+        for (a, b) in lite.iter().zip(&full) {
+            assert_eq!(a.name, b.name);
+            if !a.needs_llm {
+                assert_eq!(a.supported, b.supported, "{} changed without needing an LLM", a.name);
+            }
+        }
+    }
+}
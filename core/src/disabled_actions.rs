@@ -0,0 +1,87 @@
+// [Disabled Actions] Action types an operator has switched off entirely, for
+// deployments where UI automation is fine but running commands (or opening
+// files, or browsing) is not. Finer-grained than safe mode: everything not
+// listed keeps working. A disabled plan step is recorded as blocked
+// (`action_disabled`) and stops the run; a disabled shell command is refused
+// before approval is even offered. Default: nothing disabled.
+//
+//   STEER_DISABLED_ACTIONS="SHELL,OPEN_FILE"   plan action types (see action_schema) or SHELL
+
+/// Not a plan action: `exec` in the REPL and shell steps of other callers.
+pub const SHELL: &str = "SHELL";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisabledActions {
+    actions: Vec<String>,
+}
+
+impl DisabledActions {
+    /// Comma-separated action types, case-insensitive; unknown names are
+    /// warned about, since they disable nothing.
+    pub fn parse(raw: &str) -> Self {
+        let actions: Vec<String> = raw
+            .split(',')
+            .map(|a| a.trim().to_uppercase())
+            .filter(|a| !a.is_empty())
+            .collect();
+        for action in &actions {
+            let known = action == SHELL || crate::action_schema::ACTIONS.iter().any(|spec| spec.name == action);
+            if !known {
+                eprintln!("⚠️ [Disabled Actions] Unknown action type '{}' in STEER_DISABLED_ACTIONS", action);
+            }
+        }
+        Self { actions }
+    }
+
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("STEER_DISABLED_ACTIONS").unwrap_or_default())
+    }
+
+    pub fn is_disabled(&self, action_type: &str) -> bool {
+        let action_type = action_type.trim().to_uppercase();
+        self.actions.contains(&action_type)
+    }
+
+    /// Err with the reason when `action_type` is disabled.
+    pub fn check(&self, action_type: &str) -> Result<(), String> {
+        if self.is_disabled(action_type) {
+            return Err(blocked_message(action_type));
+        }
+        Ok(())
+    }
+}
+
+pub fn blocked_message(action_type: &str) -> String {
+    format!(
+        "{} actions are disabled on this machine (STEER_DISABLED_ACTIONS)",
+        action_type.trim().to_uppercase()
+    )
+}
+
+/// Error out when `action_type` is disabled; call right before acting.
+pub fn check(action_type: &str) -> anyhow::Result<()> {
+    DisabledActions::from_env().check(action_type).map_err(|e| anyhow::anyhow!(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_action_is_blocked() {
+        let disabled = DisabledActions::parse(" shell, Open_File ,,");
+        assert!(disabled.is_disabled("SHELL"));
+        assert!(disabled.is_disabled("OPEN_FILE"));
+        assert!(!disabled.is_disabled("CLICK"));
+        assert!(disabled.check("TYPE").is_ok());
+        assert_eq!(
+            disabled.check("open_file").unwrap_err(),
+            "OPEN_FILE actions are disabled on this machine (STEER_DISABLED_ACTIONS)"
+        );
+
+        // Default: everything enabled
+        let none = DisabledActions::parse("");
+        assert!(crate::action_schema::ACTIONS.iter().all(|spec| none.check(spec.name).is_ok()));
+        assert!(none.check(SHELL).is_ok());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::db;
+use crate::llm_gateway::{self, LLMClient, LlmTask};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// [Event Clusters] String similarity only groups events whose text matches
+// ("Slack" twice), not ones that mean the same thing ("Jira ticket" in Chrome,
+// then "Linear issue" in the desktop app). With STEER_EVENT_EMBEDDINGS=1 the
+// pattern job embeds short descriptions of new events into a side table
+// (`event_embeddings`) and clusters the recent ones with DBSCAN over cosine
+// distance. Each event costs one embedding call, so it's off by default and
+// each job run embeds at most STEER_EVENT_EMBEDDINGS_BATCH events.
+//
+//   STEER_EVENT_CLUSTER_EPS=0.15        max cosine distance between neighbours
+//   STEER_EVENT_CLUSTER_MIN_POINTS=4    neighbours needed to start a cluster
+
+/// Embeddings considered per clustering run.
+const CLUSTER_WINDOW: i64 = 500;
+/// Distinct descriptions kept as a cluster's samples.
+const MAX_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EventCluster {
+    /// Most common description in the cluster
+    pub label: String,
+    pub size: usize,
+    pub samples: Vec<String>,
+    /// Mean cosine similarity of members to the cluster centroid
+    pub cohesion: f32,
+}
+
+lazy_static! {
+    static ref LAST_CLUSTERS: Mutex<Vec<EventCluster>> = Mutex::new(Vec::new());
+}
+
+pub fn enabled() -> bool {
+    std::env::var("STEER_EVENT_EMBEDDINGS")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+/// Clusters from the last pattern job run (empty until clustering has run).
+pub fn last_clusters() -> Vec<EventCluster> {
+    LAST_CLUSTERS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Text embedded for an event: app, event type, window title and site, e.g.
+/// "Chrome · tab switched · PROJ-12 Login bug · jira.example.com".
+/// `None` when there is nothing beyond the event type to go on.
+pub fn describe(event: &db::EventText) -> Option<String> {
+    let site = event.browser_url.as_deref().and_then(|url| {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        rest.split(['/', '?', '#']).next().filter(|host| !host.is_empty())
+    });
+    let context: Vec<&str> = [Some(event.app.as_str()), event.window_title.as_deref(), site]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && !part.eq_ignore_ascii_case("unknown"))
+        .collect();
+    if context.is_empty() {
+        return None;
+    }
+    let mut parts = vec![context[0].to_string(), event.event_type.replace('_', " ")];
+    parts.extend(context[1..].iter().map(|part| part.to_string()));
+    Some(parts.join(" · "))
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// DBSCAN over cosine distance (`1 - similarity`). Returns clusters as member
+/// indices; points in no cluster (noise) are left out.
+pub fn dbscan(vectors: &[Vec<f32>], eps: f32, min_points: usize) -> Vec<Vec<usize>> {
+    let neighbours = |i: usize| -> Vec<usize> {
+        (0..vectors.len())
+            .filter(|&j| 1.0 - cosine_similarity(&vectors[i], &vectors[j]) <= eps)
+            .collect()
+    };
+    let mut label: Vec<Option<usize>> = vec![None; vectors.len()];
+    let mut visited = vec![false; vectors.len()];
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+
+    for start in 0..vectors.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let seeds = neighbours(start);
+        if seeds.len() < min_points {
+            continue; // noise unless a later core point reaches it
+        }
+        let cluster = clusters.len();
+        clusters.push(Vec::new());
+        let mut queue = seeds;
+        while let Some(point) = queue.pop() {
+            if label[point].is_none() {
+                label[point] = Some(cluster);
+                clusters[cluster].push(point);
+            }
+            if visited[point] {
+                continue;
+            }
+            visited[point] = true;
+            let reach = neighbours(point);
+            if reach.len() >= min_points {
+                queue.extend(reach.into_iter().filter(|&p| label[p].is_none()));
+            }
+        }
+    }
+    for cluster in &mut clusters {
+        cluster.sort_unstable();
+    }
+    clusters
+}
+
+/// Cluster stored embeddings, biggest cluster first.
+pub fn cluster(embeddings: &[db::EventEmbedding], eps: f32, min_points: usize) -> Vec<EventCluster> {
+    let vectors: Vec<Vec<f32>> = embeddings.iter().map(|e| e.vector.clone()).collect();
+    let mut clusters: Vec<EventCluster> = dbscan(&vectors, eps, min_points)
+        .into_iter()
+        .map(|members| {
+            let dim = vectors[members[0]].len();
+            let mut centroid = vec![0.0f32; dim];
+            for &m in &members {
+                for (c, x) in centroid.iter_mut().zip(&vectors[m]) {
+                    *c += x / members.len() as f32;
+                }
+            }
+            let cohesion = members.iter().map(|&m| cosine_similarity(&vectors[m], &centroid)).sum::<f32>() / members.len() as f32;
+
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for &m in &members {
+                *counts.entry(embeddings[m].description.as_str()).or_default() += 1;
+            }
+            let mut by_count: Vec<(&str, usize)> = counts.into_iter().collect();
+            by_count.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            EventCluster {
+                label: by_count[0].0.to_string(),
+                size: members.len(),
+                samples: by_count.iter().take(MAX_SAMPLES).map(|(d, _)| d.to_string()).collect(),
+                cohesion,
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.size.cmp(&a.size).then(a.label.cmp(&b.label)));
+    clusters
+}
+
+/// Embed up to `limit` events that have no embedding yet; returns how many were embedded.
+pub async fn embed_new_events(llm: &LLMClient, limit: i64) -> Result<usize> {
+    let model = llm_gateway::model_for(LlmTask::Embedding);
+    let mut embedded = 0;
+    for event in db::events_without_embedding(limit)? {
+        match describe(&event) {
+            Some(description) => {
+                let vector = llm.get_embedding(&description).await?;
+                db::insert_event_embedding(&event.event_id, &description, &model, &vector)?;
+                embedded += 1;
+            }
+            None => db::insert_event_embedding(&event.event_id, "", &model, &[])?,
+        }
+    }
+    Ok(embedded)
+}
+
+/// Embed new events, then re-cluster the recent ones. Called by the pattern job.
+pub async fn refresh(llm: &LLMClient) -> Result<Vec<EventCluster>> {
+    let embedded = embed_new_events(llm, env_or("STEER_EVENT_EMBEDDINGS_BATCH", 50)).await?;
+    let embeddings = db::get_event_embeddings(CLUSTER_WINDOW)?;
+    let clusters = cluster(
+        &embeddings,
+        env_or("STEER_EVENT_CLUSTER_EPS", 0.15),
+        env_or("STEER_EVENT_CLUSTER_MIN_POINTS", 4usize).max(2),
+    );
+    println!(
+        "🧬 [Clusters] Embedded {} new event(s); {} cluster(s) in the last {} embeddings",
+        embedded,
+        clusters.len(),
+        embeddings.len()
+    );
+    *LAST_CLUSTERS.lock().unwrap_or_else(|e| e.into_inner()) = clusters.clone();
+    Ok(clusters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding(description: &str, vector: Vec<f32>) -> db::EventEmbedding {
+        db::EventEmbedding { event_id: uuid::Uuid::new_v4().to_string(), description: description.to_string(), vector }
+    }
+
+    #[test]
+    fn test_synthetic_embeddings_form_two_clusters() {
+        // Two tight groups around different directions, plus one stray point
+        let mut embeddings = Vec::new();
+        for i in 0..5 {
+            let jitter = i as f32 * 0.02;
+            embeddings.push(embedding(
+                if i < 3 { "Chrome · tab switched · PROJ-12 · jira.example.com" } else { "Linear · app focused · Login bug" },
+                vec![1.0, jitter, 0.0, 0.01],
+            ));
+            embeddings.push(embedding("Finder · file created · invoice.pdf", vec![0.0, 0.01, 1.0, jitter]));
+        }
+        embeddings.push(embedding("Music · app focused", vec![-1.0, 1.0, -1.0, 0.0]));
+
+        let clusters = cluster(&embeddings, 0.15, 3);
+        assert_eq!(clusters.len(), 2);
+        assert!(clusters.iter().all(|c| c.size == 5 && c.cohesion > 0.99));
+
+        let tickets = clusters.iter().find(|c| c.label.starts_with("Chrome")).unwrap();
+        assert_eq!(tickets.samples.len(), 2, "the Jira and Linear events land together");
+        assert!(clusters.iter().any(|c| c.label == "Finder · file created · invoice.pdf"));
+        assert!(!clusters.iter().any(|c| c.samples.iter().any(|s| s.starts_with("Music"))));
+
+        // Too few neighbours anywhere: everything is noise
+        assert!(cluster(&embeddings, 0.15, 6).is_empty());
+    }
+
+    #[test]
+    fn test_events_are_described_from_app_title_and_site() {
+        let event = db::EventText {
+            app: "Google Chrome".to_string(),
+            event_type: "tab_switched".to_string(),
+            window_title: Some("PROJ-12 Login bug".to_string()),
+            browser_url: Some("https://jira.example.com/browse/PROJ-12?focus=1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(describe(&event).as_deref(), Some("Google Chrome · tab switched · PROJ-12 Login bug · jira.example.com"));
+
+        let bare = db::EventText { app: "unknown".to_string(), event_type: "key_input".to_string(), ..Default::default() };
+        assert_eq!(describe(&bare), None);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+    }
+}
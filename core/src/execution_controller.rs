@@ -12,6 +12,10 @@ pub async fn execute_plan(plan: &Plan) -> ExecutionResult {
     let mut blocked = false;
 
     logs.push(format!("Start plan {} ({})", plan.plan_id, plan.intent.as_str()));
+    if let Err(e) = crate::safe_mode::check("plan execution is") {
+        logs.push(e.to_string());
+        return ExecutionResult { status: "blocked".to_string(), logs };
+    }
     logs.push(summary_for_plan(plan));
 
     for (idx, step) in plan.steps.iter().enumerate() {
@@ -1,13 +1,73 @@
 use anyhow::{Result, Context};
 use crate::llm_gateway::LLMClient;
-use crate::{command_queue, db, replanning_config};
+use crate::agent_error::AgentError;
+use crate::goal_analysis::GoalAnalysis;
+use crate::goal_checklist::GoalChecklist;
+use crate::policy::{BlockReason, PolicyEngine};
+use crate::schema::AgentAction;
+use crate::step_confirmation::{self, ConfirmDecision};
+use crate::{action_schema, command_queue, db, llm_budget, plan_validation, prompt_templates, replanning_config, schema, screen_extract, session_artifacts, supervisor};
+use crate::action_budget::ActionBudget;
+use crate::disabled_actions::DisabledActions;
+use crate::run_state::{ClipboardUse, RunState};
+use crate::screen_extract::ExtractSchema;
+use crate::schema::EventEnvelope;
+use crate::text_input::InjectionMethod;
 use crate::visual_driver::{VisualDriver, SmartStep, UiAction};
 use std::sync::Arc;
 use tokio::sync::Mutex; 
 
+/// Upper bound for one DISMISS_DIALOGS step, so a dialog that reopens can't loop forever.
+const MAX_DIALOGS_DISMISSED: usize = 5;
+
 pub struct AgentExecutor {
     llm: Arc<LLMClient>,
     driver: Arc<Mutex<VisualDriver>>,
+    confirm_each: bool,
+    observe: bool,
+    session_key: String,
+    events: Option<tokio::sync::mpsc::Sender<String>>,
+    policy: PolicyEngine,
+    /// Planner replies and what the run did, when recording a golden flow
+    recording: Option<std::sync::Mutex<crate::golden::Golden>>,
+    /// Scripted planner and fake driver, when replaying a golden flow
+    script: Option<std::sync::Mutex<crate::golden::Script>>,
+    /// Delivers REPORT messages (title, text); `notifier::send` outside tests
+    notify: fn(&str, &str) -> Result<()>,
+    /// Steps the last successful run went through, as planned (see `completed_plan`)
+    completed: std::sync::Mutex<Vec<PlanStep>>,
+    /// Wall-clock limit for a goal, checked before each step (`STEER_MAX_DURATION_SECS`)
+    max_duration: std::time::Duration,
+}
+
+/// How a goal ended: a finished automation, an answer for the user (the
+/// text of the plan's REPLY steps) to show as a chat message, or only
+/// REPORT messages, i.e. the agent said it was stuck instead of acting.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", content = "text", rename_all = "snake_case")]
+pub enum GoalOutcome {
+    Completed,
+    Replied(String),
+    Reported(String),
+}
+
+impl GoalOutcome {
+    pub fn reply(&self) -> Option<&str> {
+        match self {
+            Self::Completed => None,
+            Self::Replied(text) | Self::Reported(text) => Some(text),
+        }
+    }
+}
+
+impl std::fmt::Display for GoalOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Completed => write!(f, "Goal Completed"),
+            Self::Replied(text) => write!(f, "{}", text),
+            Self::Reported(text) => write!(f, "Reported: {}", text),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -20,47 +80,593 @@ pub struct PlanStep {
     pub pre_check: Option<String>, // [NEW] Pre-check
 }
 
+/// One step of an `outline`: what the agent means to do, without the
+/// checks it would run around the step.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PlannedStep {
+    pub description: String,
+    /// e.g. `CLICK "Send"` or `ACTIVATE Notes`.
+    pub action: String,
+}
+
+impl From<&PlanStep> for PlannedStep {
+    fn from(step: &PlanStep) -> Self {
+        let mut action = step.action_type.clone();
+        if let Some(target) = step.target.as_deref().filter(|t| !t.is_empty()) {
+            action.push_str(&format!(" \"{}\"", target));
+        }
+        if let Some(value) = step.value.as_deref().filter(|v| !v.is_empty()) {
+            action.push_str(&format!(" {}", value));
+        }
+        Self { description: step.description.clone(), action }
+    }
+}
+
 impl AgentExecutor {
     pub fn new(llm: LLMClient) -> Self {
+        let session_key = uuid::Uuid::new_v4().to_string();
         Self {
-            llm: Arc::new(llm),
+            llm: Arc::new(llm.with_session(&session_key)),
             driver: Arc::new(Mutex::new(VisualDriver::new())),
+            confirm_each: false,
+            observe: false,
+            session_key,
+            events: None,
+            // A goal run is itself the user's approval for ordinary UI actions;
+            // tool policy, sensitive apps and critical actions still apply.
+            policy: PolicyEngine { write_lock: false },
+            recording: None,
+            script: None,
+            notify: crate::notifier::send,
+            completed: std::sync::Mutex::new(Vec::new()),
+            max_duration: std::time::Duration::from_secs(env_u32("STEER_MAX_DURATION_SECS", 600) as u64),
         }
     }
 
+    #[cfg(test)]
+    fn with_notifier(mut self, notify: fn(&str, &str) -> Result<()>) -> Self {
+        self.notify = notify;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_max_duration(mut self, max_duration: std::time::Duration) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Record planner replies and the actions derived from them for `goal`
+    /// (see golden.rs); collect them with `take_recording` after the run.
+    pub fn with_recording(mut self, goal: &str) -> Self {
+        let fix_attempts = env_u32("EXECUTOR_PLAN_FIX_ATTEMPTS", 1);
+        self.recording = Some(std::sync::Mutex::new(crate::golden::Golden::new(goal, fix_attempts)));
+        self
+    }
+
+    /// Replay `golden`: planner replies come from it, steps run on the fake
+    /// driver and nothing is sent. The run is recorded for comparison.
+    pub fn with_script(mut self, golden: &crate::golden::Golden) -> Self {
+        self.recording = Some(std::sync::Mutex::new(crate::golden::Golden::new(&golden.goal, golden.plan_fix_attempts)));
+        self.script = Some(std::sync::Mutex::new(crate::golden::Script::new(golden)));
+        self.notify = |_, _| Ok(());
+        self
+    }
+
+    pub fn take_recording(&self) -> Option<crate::golden::Golden> {
+        self.recording
+            .as_ref()
+            .map(|r| std::mem::take(&mut *r.lock().unwrap_or_else(|e| e.into_inner())))
+    }
+
+    /// Read-only run: steps are planned and verified but never click, type or open anything.
+    pub fn with_observe(mut self, observe: bool) -> Self {
+        self.observe = observe;
+        self
+    }
+
+    /// Report each step as an `agent_action` event on the analyzer channel.
+    pub fn with_events(mut self, events: Option<tokio::sync::mpsc::Sender<String>>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Key under which this run's LLM calls are logged (see `db::list_llm_calls`).
+    pub fn session_key(&self) -> &str {
+        &self.session_key
+    }
+
+    /// Ask for approval (via notifier + API) before every step; unanswered steps are skipped.
+    pub fn with_confirm_each(mut self, confirm_each: bool) -> Self {
+        self.confirm_each = confirm_each;
+        self
+    }
+
+    /// Folder holding this run's screenshots and transcript (see session_artifacts.rs).
+    pub fn artifact_dir(&self) -> std::path::PathBuf {
+        session_artifacts::session_dir(&self.session_key)
+    }
+
     /// Primary OODA Loop
-    pub async fn execute_goal(&self, goal: &str) -> Result<String> {
+    pub async fn execute_goal(&self, goal: &str) -> std::result::Result<GoalOutcome, AgentError> {
+        let result = self.run_goal(goal).await.map_err(AgentError::from);
+        self.note(|| match &result {
+            Ok(outcome) => format!("=> {}", outcome),
+            Err(e) => format!("=> [{}] {}", e.code(), e),
+        });
+        crate::undo_stack::end_goal();
+        if let Err(e) = session_artifacts::save_transcript(&self.session_key, goal) {
+            eprintln!("⚠️ Transcript not saved: {}", e);
+        }
+        result
+    }
+
+    /// Preview: the plan `goal` would start with, from one planner call. No
+    /// screenshot is taken and nothing is executed or recorded as a step.
+    pub async fn outline(&self, goal: &str) -> std::result::Result<Vec<PlannedStep>, AgentError> {
+        let analysis = GoalAnalysis::analyze(goal);
+        let plan = self.generate_plan(goal, &analysis).await.map_err(AgentError::from)?;
+        Ok(plan.iter().map(PlannedStep::from).collect())
+    }
+
+    /// Run a stored `plan` (e.g. a routine's `completed_plan`) for `goal`
+    /// without asking the planner first. Replans still need the LLM.
+    pub async fn replay_plan(&self, goal: &str, plan: Vec<PlanStep>) -> std::result::Result<GoalOutcome, AgentError> {
+        println!("🔁 [OODA] Replaying a stored plan of {} steps for '{}'", plan.len(), goal);
+        let result = self.run_plan(goal, &GoalAnalysis::analyze(goal), plan).await.map_err(AgentError::from);
+        crate::undo_stack::end_goal();
+        if let Err(e) = session_artifacts::save_transcript(&self.session_key, goal) {
+            eprintln!("⚠️ Transcript not saved: {}", e);
+        }
+        result
+    }
+
+    /// Steps the last successful run completed, across replans, before
+    /// placeholders were filled in. Empty until a run succeeds.
+    pub fn completed_plan(&self) -> Vec<PlanStep> {
+        self.completed.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    async fn run_goal(&self, goal: &str) -> Result<GoalOutcome> {
         println!("🧠 [OODA] Goal received: '{}'", goal);
 
         // 1. OBSERVE: Capture current state (omitted for MVP start, assuming start state)
-        
-        // 2. ORIENT & DECIDE: Generate Plan
-        let mut plan = self.generate_plan(goal).await?;
+
+        // 2. ORIENT: Parse the goal once; the planner and the checklist read this
+        let analysis = GoalAnalysis::analyze(goal);
+
+        // 3. DECIDE: Generate Plan
+        let plan = self.generate_plan(goal, &analysis).await?;
         println!("🧠 [OODA] Plan generated with {} steps.", plan.len());
+        self.run_plan(goal, &analysis, plan).await
+    }
+
+    /// Execute `plan` for `goal`, replanning on failure.
+    async fn run_plan(&self, goal: &str, analysis: &GoalAnalysis, mut plan: Vec<PlanStep>) -> Result<GoalOutcome> {
+        // Where each step came from, parallel to `plan` (stored in the step transcript)
+        let mut sources: Vec<String> = vec!["llm".to_string(); plan.len()];
 
         let mut step_index: usize = 0;
         let mut replan_attempts: u32 = 0;
         let max_replans = env_u32("EXECUTOR_MAX_REPLANS", 1);
+        let forced_gate = replanning_config::ForcedPlanGate::from_env();
+        // Times LLM-planned work fell short; gates the fixed recovery steps
+        let mut llm_failures: u32 = 0;
+        // Hard wall-clock cap so a goal that keeps retrying/replanning can't run forever
+        let max_duration = self.max_duration;
+        let started_at = std::time::Instant::now();
+
+        let checklist = GoalChecklist::from_analysis(analysis);
+        // Text of REPLY steps, returned to the caller as the answer
+        let mut replies: Vec<String> = Vec::new();
+        // Text of REPORT steps, sent to the user as they run
+        let mut reports: Vec<String> = Vec::new();
+        // Per-action limits, so replans can't repeat one step forever
+        let mut budget = ActionBudget::from_env();
+        let disabled = DisabledActions::from_env();
+        // Values later steps can refer to (`{{read_number}}`, ...)
+        let mut run_state = RunState::default();
+        // Steps finished by earlier plans of this run (see `completed_plan`)
+        let mut completed: Vec<PlanStep> = Vec::new();
+
+        // 4. ACT: Execute each step with SmartDriver
+        'outer: loop {
+            if step_index >= plan.len() {
+                // [Done Guard] Running out of steps isn't success until the goal checklist holds
+                // A run that only read and replied was answering a question, not
+                // changing the screen; one that only reported gave up on purpose and says why
+                let ran: Vec<PlanStep> = completed.iter().chain(plan.iter()).cloned().collect();
+                if checklist.is_empty() || self.observe || only_answered(&ran, &replies) || only_reported(&plan, &reports) {
+                    break 'outer;
+                }
+                let unmet = self.unmet(&checklist).await;
+                if unmet.is_empty() {
+                    break 'outer;
+                }
+                let unmet_desc: Vec<String> = unmet.iter().map(|c| c.describe()).collect();
+                println!("🧾 [Checklist] Goal not satisfied yet: {}", unmet_desc.join("; "));
+                if replan_attempts >= max_replans {
+                    return Err(AgentError::Verification(format!(
+                        "Goal checklist not satisfied: {}",
+                        unmet_desc.join("; ")
+                    ))
+                    .into());
+                }
 
-        // 3. ACT: Execute each step with SmartDriver
-        'outer: while step_index < plan.len() {
-            let step = plan[step_index].clone();
-            println!("🧠 [OODA] Executing Step {}: {}", step_index + 1, step.description);
+                llm_failures += 1;
+                let mut new_plan: Vec<PlanStep> = if forced_gate.allows(llm_failures) {
+                    unmet.iter().filter_map(|c| c.forced_step()).collect()
+                } else {
+                    Vec::new()
+                };
+                let mut new_sources = vec!["forced_context".to_string(); new_plan.len()];
+                if new_plan.len() < unmet.len() {
+                    let gap = PlanStep {
+                        description: format!("Finish the goal ({})", unmet_desc.join("; ")),
+                        action_type: "CHECK".to_string(),
+                        target: None,
+                        value: None,
+                        verification: unmet_desc.join("; "),
+                        pre_check: None,
+                    };
+                    if let Ok(llm_plan) = self.generate_plan_with_feedback(goal, analysis, &gap, "goal_incomplete", None).await {
+                        new_sources.extend(vec!["llm_replan:goal_incomplete".to_string(); llm_plan.len()]);
+                        new_plan.extend(llm_plan);
+                    }
+                }
+                if new_plan.is_empty() {
+                    return Err(AgentError::Verification(format!(
+                        "Goal checklist not satisfied: {}",
+                        unmet_desc.join("; ")
+                    ))
+                    .into());
+                }
+                completed.append(&mut plan);
+                plan = new_plan;
+                sources = new_sources;
+                step_index = 0;
+                replan_attempts += 1;
+                continue 'outer;
+            }
+
+            if started_at.elapsed() >= max_duration {
+                println!("⏱️ [OODA] Time limit reached ({}s). Stopping.", max_duration.as_secs());
+                return Err(AgentError::Timeout(format!(
+                    "Goal timed out after {}s (completed {}/{} steps)",
+                    max_duration.as_secs(),
+                    step_index,
+                    plan.len()
+                ))
+                .into());
+            }
+            let step = run_state.fill_step(&plan[step_index]);
+            let source = sources.get(step_index).cloned().unwrap_or_else(|| "llm".to_string());
+            if let Err(reason) = disabled.check(&step.action_type) {
+                println!("⛔️ Step {} not run: {}", step_index + 1, reason);
+                self.record_blocked(step_index, &step, &source, "blocked", BlockReason::ActionDisabled, Some(&reason));
+                return Err(AgentError::Policy(reason).into());
+            }
+            if let Err(e) = run_state.check_primed(&step) {
+                let reason = e.to_string();
+                println!("📋 Step {} not run: {}", step_index + 1, reason);
+                self.record_blocked(step_index, &step, &source, "blocked", BlockReason::NothingCopied, Some(&reason));
+                return Err(AgentError::Policy(reason).into());
+            }
+            if let Err(reason) = budget.spend(&step, &plan) {
+                println!("🧮 [OODA] Step {} not run: {}", step_index + 1, reason);
+                self.record_blocked(step_index, &step, &source, "blocked", BlockReason::ActionBudget, Some(&reason));
+                return Err(AgentError::Policy(reason).into());
+            }
+            println!("🧠 [OODA] Executing Step {} [{}]: {}", step_index + 1, source, step.description);
             
             let _driver = self.driver.lock().await;
             // Clear previous steps to run one by one (or batch them if desired)
             // For OODA, running, verify, then next is safer.
             // driver.clear_steps(); // (Future: Implement clear_steps in VisualDriver)
             
-            let action = match step.action_type.as_str() {
-                "CLICK" => UiAction::Click(step.target.clone().unwrap_or_default()),
-                "TYPE" => UiAction::Type(step.value.clone().unwrap_or_default()),
-                "URL" => UiAction::OpenUrl(step.value.clone().unwrap_or_default()),
-                "WAIT" => UiAction::Wait(step.value.as_ref().and_then(|v| v.parse().ok()).unwrap_or(2)),
-                "SCROLL" => UiAction::Scroll(step.value.clone().unwrap_or_else(|| "down".to_string())),
-                "ACTIVATE" => UiAction::ActivateApp(step.value.clone().unwrap_or_else(|| "frontmost".to_string())),
-                _ => UiAction::Wait(1),
-            };
+            let action = ui_action(&step);
+
+            if !self.observe {
+                if let Some(policy_action) = policy_action(&step) {
+                    if let Err(blocked) = self.policy.check(&policy_action) {
+                        let reason = blocked.message;
+                        println!("⛔️ Step {} blocked by policy: {}", step_index + 1, reason);
+                        self.record_blocked(step_index, &step, &source, "blocked", blocked.reason, Some(&reason));
+                        return Err(AgentError::Policy(format!(
+                            "Step {} ({}) blocked: {}",
+                            step_index + 1,
+                            step.action_type,
+                            reason
+                        ))
+                        .into());
+                    }
+                }
+            }
+
+            // [Supervisor] Second opinion on risky steps before they run
+            if !self.observe && supervisor::enabled() {
+                if let Some(risky) = policy_action(&step).filter(supervisor::is_risky) {
+                    let proposal = supervisor::proposal(goal, &step.action_type, &step.description, step.target.as_deref(), step.value.as_deref());
+                    println!("🧑‍⚖️ [Supervisor] Reviewing step {}: {:?}", step_index + 1, risky);
+                    let timeout = std::time::Duration::from_secs(env_u32("STEER_CONFIRM_TIMEOUT_SECS", 60) as u64);
+                    let step_ref = &step;
+                    let gate = supervisor::gate(
+                        || async { self.llm.review_step(&proposal).await.map(|reply| supervisor::parse_verdict(&reply)) },
+                        |question| async move {
+                            let description = format!("{} (supervisor: {})", step_ref.description, question);
+                            step_confirmation::request(goal, step_index, &step_ref.action_type, &description, timeout).await
+                        },
+                        replan_attempts < max_replans,
+                    )
+                    .await;
+                    match gate {
+                        supervisor::Gate::Proceed => {}
+                        supervisor::Gate::Revise(guidance) => {
+                            println!("🧑‍⚖️ [Supervisor] Step {} sent back: {}", step_index + 1, guidance);
+                            self.record_step(step_index, &step, &source, "revised", Some(&guidance));
+                            let new_plan = self
+                                .generate_plan_with_feedback(goal, analysis, &step, "supervisor_review", Some(&guidance))
+                                .await
+                                .unwrap_or_default();
+                            if new_plan.is_empty() {
+                                return Err(AgentError::Llm(format!("Replan after supervisor review failed: {}", guidance)).into());
+                            }
+                            sources = vec!["llm_replan:supervisor_review".to_string(); new_plan.len()];
+                            completed.extend(plan.drain(..step_index));
+                            plan = new_plan;
+                            step_index = 0;
+                            replan_attempts += 1;
+                            continue 'outer;
+                        }
+                        supervisor::Gate::Stop(reason) => {
+                            println!("🛑 Step {} stopped after supervisor escalation: {}", step_index + 1, reason);
+                            self.record_blocked(step_index, &step, &source, "denied", BlockReason::UserDenied, Some(&reason));
+                            return Err(AgentError::Cancelled(format!(
+                                "Step {} not approved: {}",
+                                step_index + 1,
+                                reason
+                            ))
+                            .into());
+                        }
+                    }
+                }
+            }
+
+            self.note(|| crate::golden::signature(&step));
+
+            // REPLY answers the user; nothing on screen changes
+            if step.action_type == "REPLY" {
+                let text = step.value.clone().unwrap_or_default();
+                println!("💬 Step {} reply: {}", step_index + 1, text);
+                self.record_step_output(step_index, &step, &source, "success", None, Some(&text));
+                replies.push(text);
+                step_index += 1;
+                continue;
+            }
+
+            // REPORT tells the user where the run stands (usually why it is stuck), right away
+            if step.action_type == "REPORT" {
+                let text = step.value.clone().unwrap_or_default();
+                println!("📣 Step {} report: {}", step_index + 1, text);
+                if let Some(tx) = &self.events {
+                    if let Err(e) = schema::emit(tx, &EventEnvelope::agent_report(goal, step_index, &text)) {
+                        eprintln!("⚠️ Agent event not sent: {}", e);
+                    }
+                }
+                // Sent directly: a report is addressed to the user, so it isn't
+                // rate-limited or deduplicated like proactive notifications
+                if let Err(e) = (self.notify)(&format!("Steer needs you: {}", goal), &text) {
+                    eprintln!("⚠️ Report notification failed: {}", e);
+                }
+                self.record_step_output(step_index, &step, &source, "success", None, Some(&text));
+                reports.push(text);
+                step_index += 1;
+                continue;
+            }
+
+            // [Golden Replay] The fake driver: the step succeeds and only
+            // moves what the checklist looks at
+            if let Some(script) = &self.script {
+                script.lock().unwrap_or_else(|e| e.into_inner()).screen.apply(&step);
+                self.record_step(step_index, &step, &source, "success", None);
+                step_index += 1;
+                continue;
+            }
+
+            if self.confirm_each {
+                let timeout = std::time::Duration::from_secs(env_u32("STEER_CONFIRM_TIMEOUT_SECS", 60) as u64);
+                match step_confirmation::request(goal, step_index, &step.action_type, &step.description, timeout).await {
+                    ConfirmDecision::Allow => {}
+                    ConfirmDecision::TimedOut => {
+                        println!("⏭️ Step {} skipped (no confirmation within {}s).", step_index + 1, timeout.as_secs());
+                        self.record_step(step_index, &step, &source, "skipped", None);
+                        step_index += 1;
+                        continue;
+                    }
+                    ConfirmDecision::Deny => {
+                        println!("🛑 Step {} denied by user.", step_index + 1);
+                        self.record_blocked(step_index, &step, &source, "denied", BlockReason::UserDenied, None);
+                        return Err(AgentError::Cancelled(format!(
+                            "User denied step {}: {}",
+                            step_index + 1,
+                            step.description
+                        ))
+                        .into());
+                    }
+                }
+            }
+
+            if let Some(tx) = &self.events {
+                let outline: Vec<String> = plan.iter().map(|s| s.action_type.clone()).collect();
+                let mut event = EventEnvelope::agent_action(goal, step_index, &step.action_type, &step.description, &outline);
+                // The tab's title and URL are only read for a browser, and not at all
+                // while capture is suppressed
+                let context = tokio::task::spawn_blocking(|| {
+                    if crate::monitoring::is_capture_suppressed() {
+                        return None;
+                    }
+                    let app = crate::applescript::frontmost_app_name().ok()?;
+                    if !crate::browser_automation::BROWSER_APPS.contains(&app.trim()) {
+                        return None;
+                    }
+                    let (title, url) = crate::applescript::get_active_window_context().unwrap_or_default();
+                    Some((app, title, url))
+                })
+                .await
+                .ok()
+                .flatten();
+                if let Some((app, title, url)) = context {
+                    event = with_browser_context(event, &app, &title, &url, &crate::monitoring::SensitiveContext::from_env());
+                }
+                if let Err(e) = schema::emit(tx, &event) {
+                    eprintln!("⚠️ Agent event not sent: {}", e);
+                }
+            }
+
+            // EXTRACT only reads the screen: no UI action, no self-healing retries
+            if step.action_type == "EXTRACT" {
+                let spec = step.target.clone().unwrap_or_default();
+                let result = match ExtractSchema::parse(&spec) {
+                    Ok(schema) => screen_extract::extract(&self.llm, &schema).await,
+                    Err(e) => Err(anyhow::anyhow!("Invalid extraction fields: {}", e)),
+                };
+                match result {
+                    Ok(data) => {
+                        println!("🔎 Step {} extracted: {}", step_index + 1, data);
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&data.to_string()));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = e.to_string();
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        return Err(AgentError::Verification(msg).into());
+                    }
+                }
+            }
+
+            // READ_SELECTION reads the selected text through accessibility, not the clipboard
+            if step.action_type == "READ_SELECTION" {
+                let read = tokio::task::spawn_blocking(crate::selection::read)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|r| r);
+                match read {
+                    Ok(text) => {
+                        println!("📋 Step {} read the selection ({} chars)", step_index + 1, text.chars().count());
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&text));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = e.to_string();
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        return Err(AgentError::Verification(msg).into());
+                    }
+                }
+            }
+
+            // USE_CLIPBOARD takes data the user copied into the run's state
+            if step.action_type == "USE_CLIPBOARD" {
+                let kind = ClipboardUse::parse(step.target.as_deref().unwrap_or_default()).unwrap_or(ClipboardUse::Text);
+                let read = tokio::task::spawn_blocking(crate::clipboard::get_text)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|r| r)
+                    .and_then(|text| run_state.use_clipboard(kind, &text));
+                match read {
+                    Ok(value) => {
+                        println!("📋 Step {} took {:?} from the clipboard ({} chars)", step_index + 1, kind, value.chars().count());
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&value));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = e.to_string();
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        return Err(AgentError::Verification(msg).into());
+                    }
+                }
+            }
+
+            // OPEN_FILE launches an app on a file; observe runs only check that the path exists
+            if step.action_type == "OPEN_FILE" {
+                let path = step.target.clone().unwrap_or_default();
+                let app = step.value.clone();
+                let opened = if self.observe {
+                    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+                    resolve_existing_path(&path, home.as_deref())
+                } else {
+                    command_queue::enqueue_ui(move || open_file(&path, app.as_deref())).await
+                };
+                match opened {
+                    Ok(resolved) => {
+                        println!("📂 Step {} opened {}", step_index + 1, resolved.display());
+                        let output = serde_json::json!({ "opened": resolved }).to_string();
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&output));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = format!("{:#}", e);
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        return Err(AgentError::Other(format!("Open file failed: {}", msg)).into());
+                    }
+                }
+            }
+
+            // SCREENSHOT keeps the current screen as a labelled artifact of this run
+            if step.action_type == "SCREENSHOT" {
+                let label = step.target.clone().unwrap_or_default();
+                let saved = tokio::task::spawn_blocking(VisualDriver::capture_screen)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|r| r)
+                    .and_then(|jpeg| session_artifacts::save_screenshot(&self.session_key, step_index, &label, &jpeg));
+                match saved {
+                    Ok(path) => {
+                        println!("📸 Step {} saved screenshot '{}': {}", step_index + 1, label, path.display());
+                        let output = session_artifacts::step_output(&label, &path);
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&output));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = e.to_string();
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        if !crate::screen_access::available() {
+                            return Err(AgentError::Permission(msg).into());
+                        }
+                        return Err(anyhow::anyhow!("Screenshot failed: {}", msg));
+                    }
+                }
+            }
+
+            // DISMISS_DIALOGS closes whatever modals are up; finding none is not a failure
+            if step.action_type == "DISMISS_DIALOGS" {
+                let result = if self.observe {
+                    Ok(Vec::new())
+                } else {
+                    command_queue::enqueue_ui(|| crate::applescript::dismiss_dialogs(MAX_DIALOGS_DISMISSED)).await
+                };
+                match result {
+                    Ok(pressed) => {
+                        if pressed.is_empty() {
+                            println!("🪟 Step {}: no dialogs to dismiss", step_index + 1);
+                        } else {
+                            println!("🪟 Step {} dismissed {} dialog(s): {}", step_index + 1, pressed.len(), pressed.join(", "));
+                        }
+                        let output = serde_json::json!({ "dismissed": pressed }).to_string();
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&output));
+                        step_index += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = e.to_string();
+                        self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                        return Err(anyhow::anyhow!("Dismissing dialogs failed: {}", msg));
+                    }
+                }
+            }
 
             let smart_step = SmartStep::new(action, &step.description)
                 .with_pre_check(&step.pre_check.clone().unwrap_or_default())
@@ -71,13 +677,16 @@ impl AgentExecutor {
             let max_retries = env_u32("EXECUTOR_MAX_RETRIES", 2);
             let mut last_error: Option<anyhow::Error> = None;
             let mut last_failure_type = "execution_error";
+            let mut recovered_clicks: Vec<String> = Vec::new();
             
             while attempts <= max_retries {
                 // Hack: Create a temporary mini-driver for this step to ensure isolation
-                let mut step_driver = VisualDriver::new();
+                let mut step_driver = VisualDriver::new().read_only(self.observe);
                 step_driver.add_step(smart_step.clone());
                 
-                    match step_driver.execute(Some(&self.llm)).await {
+                    let result = step_driver.execute(Some(&self.llm)).await;
+                    recovered_clicks.extend(step_driver.recoveries());
+                    match result {
                     Ok(_) => {
                         println!("✅ Step {} Success.", step_index + 1);
                         last_error = None;
@@ -93,7 +702,9 @@ impl AgentExecutor {
                         
                         if failure_type == "permission_denied" {
                             println!("⛔️ Critical Permission Error. Aborting Self-Healing to prevent spamming OS.");
-                            return Err(last_error.unwrap()); // Fail Fast on permissions
+                            let msg = last_error.map(|e| e.to_string()).unwrap_or_default();
+                            self.record_step(step_index, &step, &source, "failed", Some(&msg));
+                            return Err(AgentError::Permission(msg).into()); // Fail Fast on permissions
                         }
 
                         if attempts <= max_retries {
@@ -105,9 +716,28 @@ impl AgentExecutor {
             }
 
             if last_error.is_none() {
+                if !self.observe {
+                    let action_type = step.action_type.clone();
+                    let front = tokio::task::spawn_blocking(move || crate::undo_stack::observe_front(&action_type))
+                        .await
+                        .unwrap_or_default();
+                    crate::undo_stack::record(&step, &front);
+                }
+                match recovered_clicks.as_slice() {
+                    [] => self.record_step(step_index, &step, &source, "success", None),
+                    notes => {
+                        let output = serde_json::json!({ "click_recovered": notes }).to_string();
+                        self.record_step_output(step_index, &step, &source, "success", None, Some(&output));
+                    }
+                }
                 step_index += 1;
                 continue;
             }
+            let error_text = last_error.as_ref().map(|e| e.to_string());
+            self.record_step(step_index, &step, &source, "failed", error_text.as_deref());
+            if !self.observe {
+                crate::undo_stack::barrier();
+            }
 
             let strategy = replanning_config::get_replan_strategy(last_failure_type);
             if strategy.stop {
@@ -117,13 +747,20 @@ impl AgentExecutor {
 
             if replan_attempts < max_replans {
                 println!("🧭 [Replan] Attempting replanning after failure: {}", last_failure_type);
-                let mut new_plan = crate::replan_templates::build_replan_steps(last_failure_type, &step);
+                if source.starts_with("llm") {
+                    llm_failures += 1;
+                }
+                let mut new_plan = recovery_steps(forced_gate, llm_failures, last_failure_type, &step);
+                let mut new_source = format!("fallback:{}", last_failure_type);
                 if new_plan.is_empty() {
-                    if let Ok(llm_plan) = self.generate_plan_with_feedback(goal, &step, last_failure_type).await {
+                    if let Ok(llm_plan) = self.generate_plan_with_feedback(goal, analysis, &step, last_failure_type, None).await {
                         new_plan = llm_plan;
+                        new_source = format!("llm_replan:{}", last_failure_type);
                     }
                 }
                 if !new_plan.is_empty() {
+                    sources = vec![new_source; new_plan.len()];
+                    completed.extend(plan.drain(..step_index));
                     plan = new_plan;
                     step_index = 0;
                     replan_attempts += 1;
@@ -135,88 +772,165 @@ impl AgentExecutor {
             return Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Executor loop terminated without specific error")));
         }
 
-        Ok("Goal Completed".to_string())
+        completed.extend(plan.iter().cloned());
+        *self.completed.lock().unwrap_or_else(|e| e.into_inner()) = completed;
+
+        if !replies.is_empty() {
+            Ok(GoalOutcome::Replied(replies.join("\n")))
+        } else if only_reported(&plan, &reports) {
+            Ok(GoalOutcome::Reported(reports.join("\n")))
+        } else {
+            Ok(GoalOutcome::Completed)
+        }
+    }
+
+    /// Append a step to this run's transcript (`db::list_goal_steps`).
+    fn record_step(&self, index: usize, step: &PlanStep, source: &str, outcome: &str, error: Option<&str>) {
+        self.record_step_output(index, step, source, outcome, error, None);
     }
 
-    async fn generate_plan_with_feedback(&self, goal: &str, failed_step: &PlanStep, failure_type: &str) -> Result<Vec<PlanStep>> {
+    /// `record_step` plus the data a step produced (e.g. EXTRACT's JSON).
+    fn record_step_output(&self, index: usize, step: &PlanStep, source: &str, outcome: &str, error: Option<&str>, output: Option<&str>) {
+        self.insert_step(db::GoalStep {
+            output: output.map(|o| o.to_string()),
+            ..self.step_entry(index, step, source, outcome, error)
+        });
+    }
+
+    /// `record_step` for a step that was not run, with why.
+    fn record_blocked(&self, index: usize, step: &PlanStep, source: &str, outcome: &str, reason: BlockReason, error: Option<&str>) {
+        self.note(|| format!("x [{}] {}", reason.code(), crate::golden::signature(step)));
+        self.insert_step(db::GoalStep {
+            block_reason: Some(reason.code().to_string()),
+            ..self.step_entry(index, step, source, outcome, error)
+        });
+    }
+
+    fn step_entry(&self, index: usize, step: &PlanStep, source: &str, outcome: &str, error: Option<&str>) -> db::GoalStep {
+        db::GoalStep {
+            session_key: self.session_key.clone(),
+            step_index: index as i64,
+            action_type: step.action_type.clone(),
+            description: step.description.clone(),
+            decision_source: source.to_string(),
+            outcome: outcome.to_string(),
+            error: error.map(|e| e.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn insert_step(&self, entry: db::GoalStep) {
+        write_step(&entry);
+    }
+
+    /// Replan after `failed_step`; `guidance` (e.g. the supervisor's review) replaces the failure type's strategy hint.
+    async fn generate_plan_with_feedback(&self, goal: &str, analysis: &GoalAnalysis, failed_step: &PlanStep, failure_type: &str, guidance: Option<&str>) -> Result<Vec<PlanStep>> {
         let strategy = replanning_config::get_replan_strategy(failure_type);
-        let hint = strategy.fix_hint.unwrap_or("");
-        let prompt = format!(
-            "You are an autonomous GUI Agent. The previous plan failed.\n\
-            Goal: '{}'.\n\
-            Failed step: '{}' (type: {}, target: {:?}, value: {:?}).\n\
-            Failure type: {}.\n\
-            Strategy hint: {}.\n\
-            Replan with safer, simpler steps that avoid the failure.\n\
-            Available Actions: CLICK(target), TYPE(text), URL(link), WAIT(seconds), SCROLL(direction), ACTIVATE(app).\n\
-            Pre-Check: Visual cue to verify action is possible.\n\
-            Verification: Key visual cue to check success.\n\n\
-            Output ONLY valid JSON array of objects:\n\
-            [{{ \"description\": \"...\", \"action_type\": \"CLICK\", \"target\": \"Login Button\", \"pre_check\": \"Login page visible\", \"verification\": \"Login form appears\" }}, ...]",
-            goal,
-            failed_step.description,
-            failed_step.action_type,
-            failed_step.target,
-            failed_step.value,
-            failure_type,
-            hint
-        );
+        let hint = guidance.or(strategy.fix_hint).unwrap_or("");
+        let prompt = prompt_templates::render("replan", &[
+            ("goal", goal.to_string()),
+            ("goal_hint", analysis.prompt_hint()),
+            ("failed_step", failed_step.description.clone()),
+            ("failed_action", failed_step.action_type.clone()),
+            ("failed_target", format!("{:?}", failed_step.target)),
+            ("failed_value", format!("{:?}", failed_step.value)),
+            ("failure_type", failure_type.to_string()),
+            ("strategy_hint", hint.to_string()),
+            ("actions", action_schema::prompt_action_list()),
+        ]);
 
-        let response = self.llm.analyze_tendency(&[prompt]).await?;
-        let start = response.find('[').unwrap_or(0);
-        let end = response.rfind(']').map(|i| i + 1).unwrap_or(response.len());
-        let sliced = if start < end { response[start..end].to_string() } else { response };
-        let cleaned = sliced.replace("```json", "").replace("```", "").trim().to_string();
-        let steps: Vec<PlanStep> = serde_json::from_str(&cleaned)
-            .context(format!("Failed to parse replan JSON: {}", cleaned))?;
-        Ok(steps)
-    }
-
-    async fn generate_plan(&self, goal: &str) -> Result<Vec<PlanStep>> {
-        let prompt = format!(
-            "You are an autonomous GUI Agent. Your goal is: '{}'.\n\
-            Break this goal down into a linear sequence of concrete computer actions for macOS.\n\
-            Available Actions: CLICK(target), TYPE(text), URL(link), WAIT(seconds), SCROLL(direction), ACTIVATE(app).\n\
-            Pre-Check: Visual cue to verify action is possible (e.g. 'Search bar visible').\n\
-            Verification: Key visual cue to check success (e.g. 'Results appeared').\n\n\
-            Output ONLY valid JSON array of objects:\n\
-            [{{ \"description\": \"...\", \"action_type\": \"CLICK\", \"target\": \"Login Button\", \"pre_check\": \"Login page visible\", \"verification\": \"Login form appears\" }}, ...]",
-            goal
-        );
+        self.request_plan(&prompt).await
+    }
+
+    async fn generate_plan(&self, goal: &str, analysis: &GoalAnalysis) -> Result<Vec<PlanStep>> {
+        let prompt = prompt_templates::render("plan", &[
+            ("goal", goal.to_string()),
+            ("goal_hint", analysis.prompt_hint()),
+            ("actions", action_schema::prompt_action_list()),
+        ]);
 
-        // Mock JSON return for MVP fallback or real LLM call
-        // Here we call the LLM
-        // We reuse the build_n8n_workflow method's logic or add a new general JSON method.
-        // For now, let's use a specialized method call or just generic completion.
-        // Assuming we need to add `generate_plan_json` to LLMClient, or parse it here.
-        
-        // Using existing generic analyze method? No, let's assume we implement a helper.
-        // For MVP, implementing a dummy plan for testing if LLM not connected optimally.
-        
-        let response = match self.llm.analyze_tendency(&[prompt]).await {
-            Ok(json_str) => {
-                // Extract JSON array from Markdown or raw text
-                let start = json_str.find('[').unwrap_or(0);
-                let end = json_str.rfind(']').map(|i| i + 1).unwrap_or(json_str.len());
-                if start < end {
-                    json_str[start..end].to_string()
+        self.request_plan(&prompt)
+            .await
+            .map_err(|e| {
+                if e.is::<llm_budget::BudgetExceeded>() {
+                    AgentError::from(e).into()
                 } else {
-                    json_str
+                    AgentError::Llm(format!("Plan generation failed: {}", e)).into()
                 }
-            },
-            Err(_) => return Err(anyhow::anyhow!("Plan generation failed")),
+            })
+    }
+
+    /// Ask the LLM for a plan; invalid plans are re-requested with the validation errors attached.
+    async fn request_plan(&self, prompt: &str) -> Result<Vec<PlanStep>> {
+        let max_fixes = match &self.script {
+            Some(script) => script.lock().unwrap_or_else(|e| e.into_inner()).plan_fix_attempts,
+            None => env_u32("EXECUTOR_PLAN_FIX_ATTEMPTS", 1),
         };
-        
-        // Clean JSON formatting (remove markdown blocks)
-        let cleaned = response.replace("```json", "").replace("```", "").trim().to_string();
-        
-        let steps: Vec<PlanStep> = serde_json::from_str(&cleaned)
-            .context(format!("Failed to parse plan JSON: {}", cleaned))?;
-            
-        Ok(steps)
+        let plan = plan_validation::request_valid_plan(prompt, max_fixes, |p| async move {
+            let reply = match &self.script {
+                Some(script) => script.lock().unwrap_or_else(|e| e.into_inner()).next_reply(),
+                None => self.llm.analyze_tendency(&[p]).await,
+            };
+            if let (Some(recording), Ok(text)) = (&self.recording, &reply) {
+                recording.lock().unwrap_or_else(|e| e.into_inner()).responses.push(text.clone());
+            }
+            reply
+        })
+        .await;
+        // A planner that couldn't be reached left nothing to replay
+        if let Err(e) = &plan {
+            if e.to_string().starts_with(plan_validation::INVALID_PLAN) {
+                self.note(|| format!("! {}", e));
+            }
+        }
+        plan
+    }
+
+    /// Add a line to the golden flow being recorded, if any.
+    fn note(&self, line: impl FnOnce() -> String) {
+        if let Some(recording) = &self.recording {
+            recording.lock().unwrap_or_else(|e| e.into_inner()).note(line());
+        }
+    }
+
+    /// Checklist items the screen doesn't show yet (the fake screen's, in a replay).
+    async fn unmet(&self, checklist: &GoalChecklist) -> Vec<crate::goal_checklist::Check> {
+        if let Some(script) = &self.script {
+            let script = script.lock().unwrap_or_else(|e| e.into_inner());
+            let screen = &script.screen;
+            return checklist.unmet_on(screen.front_app.as_deref(), screen.url.as_deref(), &screen.typed);
+        }
+        checklist.unmet(Some(&self.llm)).await
     }
 }
 
+fn write_step(entry: &db::GoalStep) {
+    if let Err(e) = session_artifacts::append_transcript(entry) {
+        eprintln!("⚠️ Transcript line not written: {}", e);
+    }
+    if let Err(e) = db::insert_goal_step(entry) {
+        eprintln!("⚠️ Failed to record step: {}", e);
+    }
+}
+
+/// Close out run `session_key` after it was cancelled: the step that was in
+/// flight is recorded as "cancelled", so the session doesn't look unfinished.
+pub fn record_cancelled(session_key: &str) {
+    let next_index = db::list_goal_steps(session_key, -1)
+        .ok()
+        .and_then(|steps| steps.last().map(|s| s.step_index + 1))
+        .unwrap_or(0);
+    write_step(&db::GoalStep {
+        session_key: session_key.to_string(),
+        step_index: next_index,
+        action_type: "CANCEL".to_string(),
+        description: "Run cancelled".to_string(),
+        decision_source: "user".to_string(),
+        outcome: "cancelled".to_string(),
+        ..Default::default()
+    });
+}
+
 fn env_u32(key: &str, default_val: u32) -> u32 {
     std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default_val)
 }
@@ -243,43 +957,174 @@ fn classify_verify_failure(msg: &str) -> &'static str {
 
 // --- Utility Functions (Legacy Support) ---
 
+/// Open `url` in STEER_BROWSER, or the system default browser when unset.
 pub fn open_url(url: &str) -> Result<()> {
+    crate::safe_mode::check("opening URLs is")?;
     #[cfg(target_os = "macos")]
-    std::process::Command::new("open")
-        .arg(url)
-        .spawn()
-        .with_context(|| format!("Failed to open URL: {}", url))?;
+    {
+        let mut command = std::process::Command::new("open");
+        if let Some(browser) = crate::browser_automation::preferred_browser() {
+            command.arg("-a").arg(browser);
+        }
+        command
+            .arg(url)
+            .spawn()
+            .with_context(|| format!("Failed to open URL: {}", url))?;
+    }
     Ok(())
 }
 
-pub async fn run_shell(cmd: &str) -> Result<String> {
-    let workdir = std::env::current_dir()
+/// `path` with a leading `~` expanded against `home`, if that file or folder exists.
+pub(crate) fn resolve_existing_path(path: &str, home: Option<&std::path::Path>) -> Result<std::path::PathBuf> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow::anyhow!("No file path given"));
+    }
+    let resolved = match (trimmed.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => std::path::PathBuf::from(trimmed),
+    };
+    if !resolved.exists() {
+        return Err(anyhow::anyhow!("No such file: {}", resolved.display()));
+    }
+    Ok(resolved)
+}
+
+/// Program and arguments that open `path` (with `app`, if given) on `os`.
+/// Off macOS the app is never run as a program: it must name an installed
+/// desktop entry (`desktop_entry`), otherwise the desktop default opens the file.
+fn open_file_command(
+    os: &str,
+    path: &std::path::Path,
+    app: Option<&str>,
+    desktop_entry: impl Fn(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let path = path.display().to_string();
+    let app = app.map(str::trim).filter(|a| !a.is_empty());
+    match (os, app) {
+        ("macos", Some(app)) => ("open".to_string(), vec!["-a".to_string(), app.to_string(), path]),
+        ("macos", None) => ("open".to_string(), vec![path]),
+        (_, app) => match app.and_then(desktop_entry) {
+            Some(entry) => ("gtk-launch".to_string(), vec![entry, path]),
+            None => ("xdg-open".to_string(), vec![path]),
+        },
+    }
+}
+
+/// The id of an installed desktop entry named `app` (`evince` or `org.gnome.Evince.desktop`).
+fn installed_desktop_entry(app: &str) -> Option<String> {
+    let id = app.strip_suffix(".desktop").unwrap_or(app);
+    let plain = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !plain || id.starts_with('.') {
+        return None;
+    }
+    let home = std::env::var("HOME").unwrap_or_default();
+    let data_home = std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
+    let data_dirs = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    std::iter::once(data_home.as_str())
+        .chain(data_dirs.split(':'))
+        .filter(|dir| !dir.is_empty())
+        .any(|dir| std::path::Path::new(dir).join("applications").join(format!("{}.desktop", id)).is_file())
+        .then(|| id.to_string())
+}
+
+/// Name of the app that opens `path` by default, if the system says: asked
+/// from Launch Services on macOS, `xdg-mime` elsewhere.
+pub fn default_open_app(path: &std::path::Path) -> Option<String> {
+    let run = |program: &str, args: &[&str]| -> Option<String> {
+        let out = std::process::Command::new(program).args(args).output().ok()?;
+        let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        (out.status.success() && !text.is_empty()).then_some(text)
+    };
+    let path = path.to_str()?;
+    if cfg!(target_os = "macos") {
+        const JXA: &str = "function run(argv) { ObjC.import('AppKit'); \
+            const url = $.NSWorkspace.sharedWorkspace.URLForApplicationToOpenURL($.NSURL.fileURLWithPath(argv[0])); \
+            return url.isNil() ? '' : url.path.js; }";
+        let app_path = run("osascript", &["-l", "JavaScript", "-e", JXA, path])?;
+        crate::monitoring::app_name_from_path(&app_path)
+    } else {
+        let mime = run("xdg-mime", &["query", "filetype", path])?;
+        let entry = run("xdg-mime", &["query", "default", &mime])?;
+        // org.gnome.Terminal.desktop -> Terminal
+        let id = entry.strip_suffix(".desktop").unwrap_or(&entry);
+        id.rsplit('.').next().map(str::to_string)
+    }
+}
+
+/// Open a file or folder, with `app` or the system default. Fails before
+/// launching anything if the path doesn't exist or would run a program
+/// (see `policy::launches_program`). Returns the resolved path.
+pub fn open_file(path: &str, app: Option<&str>) -> Result<std::path::PathBuf> {
+    crate::safe_mode::check("opening files is")?;
+    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+    let resolved = resolve_existing_path(path, home.as_deref())?;
+    if let Some(kind) = crate::policy::launches_program(&resolved) {
+        return Err(AgentError::Policy(format!("Not opening {}: it is {}", resolved.display(), kind)).into());
+    }
+    let (program, args) = open_file_command(std::env::consts::OS, &resolved, app, installed_desktop_entry);
+    std::process::Command::new(&program)
+        .args(&args)
+        .spawn()
+        .with_context(|| format!("Failed to open {} with {}", resolved.display(), app.unwrap_or(&program)))?;
+    Ok(resolved)
+}
+
+/// Directory shell commands run in: `STEER_SHELL_CWD` if set, else the process cwd.
+/// Callers should use the same value for policy/approval checks and `run_shell_in`.
+pub fn shell_workdir() -> String {
+    std::env::var("STEER_SHELL_CWD")
         .ok()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| ".".to_string());
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::current_dir().ok().map(|p| p.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// Checks every shell run goes through before anything spawns. Returns the
+/// sanitized command and the directory to run it in.
+fn prepare_shell(cmd: &str, cwd: Option<&str>) -> Result<(crate::shell_actions::ShellAction, String)> {
+    crate::safe_mode::check("shell commands are")?;
+    crate::disabled_actions::check(crate::disabled_actions::SHELL)?;
+    let workdir = cwd.map(|c| c.to_string()).unwrap_or_else(shell_workdir);
+    if !std::path::Path::new(&workdir).is_dir() {
+        return Err(anyhow::anyhow!("Working directory does not exist: {}", workdir));
+    }
     let mut action = crate::shell_actions::ShellAction {
         instruction: cmd.to_string(),
         targets: Vec::new(),
         verify: Vec::new(),
     };
     action = crate::shell_actions::sanitize_shell_action(action, &workdir);
-    let cmd = action.instruction.clone();
 
     let allow_composites = env_bool("SHELL_ALLOW_COMPOSITES", false);
     let allow_substitution = env_bool("SHELL_ALLOW_SUBSTITUTION", false);
-    let analysis = crate::shell_analysis::analyze_shell_command(&cmd);
+    let analysis = crate::shell_analysis::analyze_shell_command(&action.instruction);
     if analysis.has_substitution && !allow_substitution {
         return Err(anyhow::anyhow!("❌ Command substitution is blocked for safety."));
     }
     if analysis.has_composites && !allow_composites {
         return Err(anyhow::anyhow!("❌ Composite commands are blocked for safety."));
     }
+    Ok((action, workdir))
+}
+
+/// Run `cmd` with `cwd` (default `shell_workdir()`) and extra environment variables.
+pub async fn run_shell_in(cmd: &str, cwd: Option<&str>, env: &[(String, String)]) -> Result<String> {
+    let (action, workdir) = prepare_shell(cmd, cwd)?;
+    let cmd = action.instruction.clone();
 
     let exec_record = db::create_exec_result(&cmd, Some(&workdir)).ok();
+    if let Some(record) = &exec_record {
+        let impact = crate::shell_analysis::assess_impact(&cmd);
+        let _ = db::set_exec_result_impact(&record.id, &impact.summary());
+    }
 
     let cmd_clone = cmd.clone();
     let workdir_clone = workdir.clone();
     let action_clone = action.clone();
+    let env_clone = env.to_vec();
 
     let result = command_queue::enqueue_command_in_lane(
         "shell",
@@ -287,6 +1132,8 @@ pub async fn run_shell(cmd: &str) -> Result<String> {
             let output = std::process::Command::new("sh")
                 .arg("-c")
                 .arg(&cmd_clone)
+                .current_dir(&workdir_clone)
+                .envs(env_clone)
                 .output()
                 .with_context(|| format!("Failed to run command: {}", cmd_clone))?;
 
@@ -328,6 +1175,47 @@ pub async fn run_shell(cmd: &str) -> Result<String> {
     result
 }
 
+/// Run `cmd` unattended and return its exit code, for checks nobody is
+/// watching. It goes through the same checks as `run_shell_in` plus the
+/// policy (the shell allowlist, or a saved approval), runs off the async
+/// workers and is killed after `timeout`.
+pub async fn run_shell_status(cmd: &str, timeout: std::time::Duration) -> Result<i32> {
+    let (action, workdir) = prepare_shell(cmd, None)?;
+    let cmd = action.instruction;
+    let shell = AgentAction::ShellExecution { command: cmd.clone() };
+    if let Err(blocked) = (PolicyEngine { write_lock: false }).check_with_context(&shell, Some(&workdir)) {
+        let approved = blocked.reason != BlockReason::ActionDisabled
+            && matches!(db::find_valid_exec_approval(&cmd, Some(&workdir)), Ok(Some(_)));
+        if !approved {
+            return Err(AgentError::Policy(blocked.message).into());
+        }
+    }
+    tokio::task::spawn_blocking(move || {
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&cmd)
+            .current_dir(&workdir)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to run command: {}", cmd))?;
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return status.code().ok_or_else(|| anyhow::anyhow!("killed by a signal"));
+            }
+            if std::time::Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(anyhow::anyhow!("timed out after {}s", timeout.as_secs()));
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    })
+    .await?
+}
+
 fn env_bool(key: &str, default_val: bool) -> bool {
     match std::env::var(key) {
         Ok(v) => {
@@ -337,3 +1225,415 @@ fn env_bool(key: &str, default_val: bool) -> bool {
         Err(_) => default_val,
     }
 }
+
+/// The run replied and otherwise only read: it answered a question, and a
+/// plan that also clicked or typed still has to meet the goal checklist.
+fn only_answered(ran: &[PlanStep], replies: &[String]) -> bool {
+    !replies.is_empty() && ran.iter().all(|s| action_schema::only_reads(&s.action_type))
+}
+
+/// The plan that ran reported something and otherwise only read: the agent
+/// stopped to tell the user instead of acting.
+fn only_reported(plan: &[PlanStep], reports: &[String]) -> bool {
+    !reports.is_empty() && plan.iter().all(|s| s.action_type == "REPORT" || action_schema::only_reads(&s.action_type))
+}
+
+/// Fixed replan template for a failed step, unless `gate` says the LLM should
+/// get to replan first.
+fn recovery_steps(
+    gate: replanning_config::ForcedPlanGate,
+    llm_failures: u32,
+    failure_type: &str,
+    failed: &PlanStep,
+) -> Vec<PlanStep> {
+    if !gate.allows(llm_failures) {
+        println!("🧭 [Replan] Letting the LLM replan first ({} failure(s) so far)", llm_failures);
+        return Vec::new();
+    }
+    crate::replan_templates::build_replan_steps(failure_type, failed)
+}
+
+/// Attach the window title and (redacted) URL when the frontmost `app` is a
+/// browser, so agent actions can be tied to sites. Nothing is attached in a
+/// sensitive context (see `monitoring::SensitiveContext`).
+fn with_browser_context(
+    mut event: EventEnvelope,
+    app: &str,
+    window_title: &str,
+    url: &str,
+    sensitive: &crate::monitoring::SensitiveContext,
+) -> EventEnvelope {
+    let is_browser = crate::browser_automation::BROWSER_APPS.contains(&app.trim());
+    if !is_browser || sensitive.matches(app, url) {
+        return event;
+    }
+    if !window_title.trim().is_empty() {
+        event.window_title = Some(crate::privacy::redact(window_title.trim()));
+    }
+    if !url.trim().is_empty() {
+        event.browser_url = Some(crate::privacy::redact_url(url.trim()));
+    }
+    event
+}
+
+/// The driver action a plan step dispatches to. Steps the executor handles
+/// itself (EXTRACT, REPLY, SCREENSHOT, ...) map to a short wait.
+pub fn ui_action(step: &PlanStep) -> UiAction {
+    match step.action_type.as_str() {
+        "CLICK" => UiAction::Click(step.target.clone().unwrap_or_default()),
+        "TYPE" => {
+            let text = step.value.clone().unwrap_or_default();
+            match step.target.as_deref().and_then(InjectionMethod::parse) {
+                Some(method) => UiAction::TypeWith { text, method },
+                None => UiAction::Type(text),
+            }
+        }
+        "URL" => UiAction::OpenUrl(step.value.clone().unwrap_or_default()),
+        "WAIT" => UiAction::Wait(step.value.as_ref().and_then(|v| v.parse().ok()).unwrap_or(2)),
+        "WAIT_FOR" => UiAction::WaitFor {
+            text: step.target.clone().unwrap_or_default(),
+            timeout_secs: step.value.as_ref().and_then(|v| v.parse().ok()).unwrap_or(10),
+        },
+        "SCROLL" => UiAction::Scroll(step.value.clone().unwrap_or_else(|| "down".to_string())),
+        "ACTIVATE" => UiAction::ActivateApp(step.value.clone().unwrap_or_else(|| "frontmost".to_string())),
+        "FOCUS_WINDOW" => UiAction::FocusWindow {
+            app: step.value.clone().unwrap_or_default(),
+            title_contains: step.target.clone().unwrap_or_default(),
+        },
+        _ => UiAction::Wait(1),
+    }
+}
+
+/// The policy action a plan step amounts to; `None` for plain waits.
+pub fn policy_action(step: &PlanStep) -> Option<AgentAction> {
+    let target = step.target.clone().unwrap_or_default();
+    let value = step.value.clone().unwrap_or_default();
+    Some(match step.action_type.as_str() {
+        "CLICK" => AgentAction::UiClickText { text: target },
+        "TYPE" => AgentAction::UiType { text: value },
+        "URL" => AgentAction::OpenUrl { url: value },
+        "SCROLL" => AgentAction::KeyboardType { text: value, submit: false },
+        "ACTIVATE" | "FOCUS_WINDOW" => AgentAction::SystemOpen { app: value },
+        "OPEN_FILE" => AgentAction::OpenFile {
+            path: target,
+            app: step.value.clone().filter(|a| !a.trim().is_empty()),
+        },
+        "WAIT_FOR" => AgentAction::UiFind { query: target },
+        "EXTRACT" | "READ_SELECTION" | "USE_CLIPBOARD" | "SCREENSHOT" => AgentAction::UiSnapshot { scope: None },
+        "DISMISS_DIALOGS" => AgentAction::UiClickText { text: "Cancel".to_string() },
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(action_type: &str, target: Option<&str>, value: Option<&str>) -> PlanStep {
+        PlanStep {
+            description: action_type.to_lowercase(),
+            action_type: action_type.to_string(),
+            target: target.map(str::to_string),
+            value: value.map(str::to_string),
+            verification: String::new(),
+            pre_check: None,
+        }
+    }
+
+    #[test]
+    fn test_agent_event_carries_redacted_browser_url() {
+        let plan = vec!["CLICK".to_string()];
+        let rules = crate::monitoring::SensitiveContext { apps: vec!["1Password".to_string()], url_patterns: vec!["*bank.example*".to_string()] };
+        let event = with_browser_context(
+            EventEnvelope::agent_action("pay invoice", 0, "CLICK", "Click Pay", &plan),
+            "Google Chrome",
+            "Invoice 42 - alice@example.com",
+            "https://billing.example.com/invoices/42?session=abc123#pay",
+            &rules,
+        );
+        assert_eq!(event.browser_url.as_deref(), Some("https://billing.example.com/invoices/42"));
+        assert_eq!(event.window_title.as_deref(), Some("Invoice 42 - [EMAIL REDACTED]"));
+        // Survives the trip through the analyzer channel
+        let round_trip = EventEnvelope::normalize_json(&serde_json::to_string(&event).unwrap()).unwrap();
+        assert_eq!(round_trip.browser_url, event.browser_url);
+
+        // Not a browser, or a sensitive site: nothing attached
+        let plain = |app: &str, url: &str| {
+            with_browser_context(EventEnvelope::agent_action("g", 0, "CLICK", "c", &plan), app, "Title", url, &rules)
+        };
+        assert_eq!(plain("Notes", "").browser_url, None);
+        assert_eq!(plain("Notes", "").window_title, None);
+        assert_eq!(plain("Safari", "https://bank.example/login").browser_url, None);
+        assert_eq!(plain("Safari", "").browser_url, None);
+    }
+
+    #[test]
+    fn test_planned_step_summarizes_the_action() {
+        let click = PlannedStep::from(&step("CLICK", Some("Send"), None));
+        assert_eq!(click.description, "click");
+        assert_eq!(click.action, "CLICK \"Send\"");
+        assert_eq!(PlannedStep::from(&step("TYPE", Some("Search"), Some("rust"))).action, "TYPE \"Search\" rust");
+        assert_eq!(PlannedStep::from(&step("WAIT", Some(""), Some("2"))).action, "WAIT 2");
+    }
+
+    #[test]
+    fn test_wait_for_waits_on_the_target_text() {
+        let action = ui_action(&step("WAIT_FOR", Some("Results"), Some("5")));
+        assert!(matches!(action, UiAction::WaitFor { ref text, timeout_secs: 5 } if text == "Results"), "{:?}", action);
+        let action = ui_action(&step("WAIT_FOR", Some("Results"), None));
+        assert!(matches!(action, UiAction::WaitFor { timeout_secs: 10, .. }), "{:?}", action);
+    }
+
+    #[test]
+    fn test_focus_window_raises_the_named_window() {
+        let action = ui_action(&step("FOCUS_WINDOW", Some("Q3 report"), Some("Microsoft Excel")));
+        assert!(
+            matches!(action, UiAction::FocusWindow { ref app, ref title_contains } if app == "Microsoft Excel" && title_contains == "Q3 report"),
+            "{:?}",
+            action
+        );
+        assert!(matches!(
+            policy_action(&step("FOCUS_WINDOW", Some("Q3 report"), Some("Microsoft Excel"))),
+            Some(AgentAction::SystemOpen { app }) if app == "Microsoft Excel"
+        ));
+
+        // Both the window title and the app are needed
+        let spec = crate::action_schema::lookup("FOCUS_WINDOW").unwrap();
+        assert!(spec.check(Some("Q3 report"), Some("Microsoft Excel")).is_none());
+        assert!(spec.check(Some("Q3 report"), None).unwrap().contains("app name"));
+        assert!(spec.check(None, Some("Microsoft Excel")).unwrap().contains("window title"));
+    }
+
+    #[test]
+    fn test_policy_sees_the_real_action() {
+        let policy = PolicyEngine { write_lock: false };
+        let check = |s: PlanStep| policy_action(&s).map(|a| policy.check(&a));
+
+        assert!(matches!(
+            policy_action(&step("ACTIVATE", None, Some("Notes"))),
+            Some(AgentAction::SystemOpen { app }) if app == "Notes"
+        ));
+        assert!(matches!(check(step("CLICK", Some("Send"), None)), Some(Ok(()))));
+        assert!(matches!(check(step("EXTRACT", Some("name"), None)), Some(Ok(()))));
+        assert!(matches!(check(step("DISMISS_DIALOGS", None, None)), Some(Ok(()))));
+        assert!(check(step("WAIT", None, Some("2"))).is_none());
+        if std::env::var("STEER_SENSITIVE_APPS").is_err() {
+            assert!(matches!(check(step("ACTIVATE", None, Some("Terminal"))), Some(Err(_))));
+            assert!(matches!(check(step("FOCUS_WINDOW", Some("bash"), Some("iTerm"))), Some(Err(_))));
+        }
+    }
+
+    #[test]
+    fn test_open_file_checks_the_path_and_picks_the_app() {
+        let dir = std::env::temp_dir().join(format!("steer_open_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("Desktop")).unwrap();
+        std::fs::write(dir.join("Desktop/report.pdf"), b"%PDF").unwrap();
+
+        let resolved = resolve_existing_path("~/Desktop/report.pdf", Some(&dir)).unwrap();
+        assert_eq!(resolved, dir.join("Desktop/report.pdf"));
+        let err = resolve_existing_path("~/Desktop/missing.pdf", Some(&dir)).unwrap_err();
+        assert!(err.to_string().starts_with("No such file"), "{}", err);
+        assert!(resolve_existing_path("  ", Some(&dir)).is_err());
+        assert!(resolve_existing_path("~other/report.pdf", Some(&dir)).is_err());
+
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let path = resolved.display().to_string();
+        let installed = |app: &str| (app == "evince").then(|| app.to_string());
+        assert_eq!(open_file_command("macos", &resolved, Some("Preview"), installed), ("open".to_string(), args(&["-a", "Preview", &path])));
+        assert_eq!(open_file_command("macos", &resolved, Some("  "), installed), ("open".to_string(), args(&[&path])));
+        assert_eq!(open_file_command("linux", &resolved, None, installed), ("xdg-open".to_string(), args(&[&path])));
+        assert_eq!(open_file_command("linux", &resolved, Some("evince"), installed), ("gtk-launch".to_string(), args(&["evince", &path])));
+        // Anything that isn't an installed desktop entry is never executed
+        assert_eq!(open_file_command("linux", &resolved, Some("/bin/sh"), installed), ("xdg-open".to_string(), args(&[&path])));
+        assert_eq!(installed_desktop_entry("../../bin/sh"), None);
+        assert_eq!(installed_desktop_entry("/bin/sh"), None);
+
+        let open_in_terminal = step("OPEN_FILE", Some(&path), Some("Terminal"));
+        assert!(PolicyEngine::new().check(&policy_action(&open_in_terminal).unwrap()).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_conservative_gate_respects_first_llm_plan() {
+        use replanning_config::ForcedPlanGate;
+        let failed = step("CLICK", Some("Submit"), None);
+        assert!(recovery_steps(ForcedPlanGate::CONSERVATIVE, 1, "element_missing", &failed).is_empty());
+        assert!(!recovery_steps(ForcedPlanGate::CONSERVATIVE, 2, "element_missing", &failed).is_empty());
+        assert!(!recovery_steps(ForcedPlanGate::AGGRESSIVE, 1, "element_missing", &failed).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reply_plan_returns_the_text() {
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+        }
+        let executor = AgentExecutor::new(LLMClient::new().unwrap());
+        let plan = vec![step("REPLY", None, Some("It is 21°C and sunny."))];
+        let outcome = executor.run_plan("what's the weather like", &GoalAnalysis::analyze("what's the weather like"), plan).await.unwrap();
+        assert_eq!(outcome, GoalOutcome::Replied("It is 21°C and sunny.".to_string()));
+        assert_eq!(outcome.reply(), Some("It is 21°C and sunny."));
+
+        let outcome = executor.run_plan("what's the weather like", &GoalAnalysis::analyze("what's the weather like"), Vec::new()).await.unwrap();
+        assert_eq!(outcome, GoalOutcome::Completed);
+        assert_eq!(outcome.to_string(), "Goal Completed");
+    }
+
+    #[tokio::test]
+    async fn test_goal_stops_at_the_time_limit() {
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+        }
+        let goal = "what's the weather like";
+        let plan = || vec![step("REPLY", None, Some("It is 21°C and sunny."))];
+        let executor = AgentExecutor::new(LLMClient::new().unwrap()).with_max_duration(std::time::Duration::ZERO);
+        let err = AgentError::from(executor.run_plan(goal, &GoalAnalysis::analyze(goal), plan()).await.unwrap_err());
+        assert_eq!(err.code(), "timeout");
+        assert_eq!(err.to_string(), "Goal timed out after 0s (completed 0/1 steps)");
+
+        let executor = AgentExecutor::new(LLMClient::new().unwrap()).with_max_duration(std::time::Duration::from_secs(60));
+        assert!(executor.run_plan(goal, &GoalAnalysis::analyze(goal), plan()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_each_stops_at_a_denied_step() {
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+        }
+        db::init().ok();
+        let goal = format!("send the draft {}", uuid::Uuid::new_v4());
+        let waiting = goal.clone();
+        let denied = tokio::spawn(async move {
+            for _ in 0..200 {
+                if let Some(pending) = step_confirmation::list_pending().into_iter().find(|p| p.goal == waiting) {
+                    assert_eq!((pending.step_index, pending.action_type.as_str()), (0, "CLICK"));
+                    return step_confirmation::resolve(&pending.id, false);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            false
+        });
+        let executor = AgentExecutor::new(LLMClient::new().unwrap()).with_confirm_each(true);
+        let plan = vec![step("CLICK", Some("Send"), None)];
+        let err = AgentError::from(executor.run_plan(&goal, &GoalAnalysis::analyze(&goal), plan).await.unwrap_err());
+        assert!(denied.await.unwrap());
+        assert_eq!(err.code(), "cancelled");
+        assert!(err.to_string().starts_with("User denied step 1"), "{}", err);
+
+        let steps = db::list_goal_steps(executor.session_key(), 10).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!((steps[0].action_type.as_str(), steps[0].outcome.as_str()), ("CLICK", "denied"));
+        assert_eq!(steps[0].block_reason.as_deref(), Some("user_denied"));
+        std::fs::remove_dir_all(executor.artifact_dir()).ok();
+    }
+
+    #[test]
+    fn test_only_a_read_only_answer_skips_the_checklist() {
+        let replies = vec!["Saved.".to_string()];
+        let answer = vec![step("EXTRACT", Some("price"), None), step("REPLY", None, Some("Saved."))];
+        assert!(only_answered(&answer, &replies));
+        assert!(!only_answered(&answer, &[]));
+
+        let acted = vec![step("CLICK", Some("Save"), None), step("REPLY", None, Some("Saved."))];
+        assert!(!only_answered(&acted, &replies));
+    }
+
+    #[tokio::test]
+    async fn test_report_plan_surfaces_the_message() {
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+        }
+        db::init().ok();
+        static SENT: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+        fn capture(title: &str, text: &str) -> Result<()> {
+            SENT.lock().unwrap().push(format!("{}: {}", title, text));
+            Ok(())
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let executor = AgentExecutor::new(LLMClient::new().unwrap()).with_events(Some(tx)).with_notifier(capture);
+        let goal = "pay the electricity bill";
+        let plan = vec![step("REPORT", None, Some("Stuck at: the bank asks for a one-time code"))];
+        let outcome = executor.run_plan(goal, &GoalAnalysis::analyze(goal), plan).await.unwrap();
+        assert_eq!(outcome, GoalOutcome::Reported("Stuck at: the bank asks for a one-time code".to_string()));
+        assert_eq!(outcome.reply(), Some("Stuck at: the bank asks for a one-time code"));
+
+        assert_eq!(
+            *SENT.lock().unwrap(),
+            vec!["Steer needs you: pay the electricity bill: Stuck at: the bank asks for a one-time code"]
+        );
+
+        let event: EventEnvelope = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(event.event_type, "agent_report");
+        assert_eq!(event.payload["text"], "Stuck at: the bank asks for a one-time code");
+
+        let steps = db::list_goal_steps(executor.session_key(), 10).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!((steps[0].action_type.as_str(), steps[0].output.as_deref()), ("REPORT", Some("Stuck at: the bank asks for a one-time code")));
+
+        // A reply still wins over reports
+        let plan = vec![step("REPORT", None, Some("Looking it up")), step("REPLY", None, Some("It's 42."))];
+        let outcome = executor.run_plan(goal, &GoalAnalysis::analyze(goal), plan).await.unwrap();
+        assert_eq!(outcome, GoalOutcome::Replied("It's 42.".to_string()));
+        // Every report reaches the user, even with the same goal a moment later
+        assert_eq!(SENT.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_transcript_log_is_written_while_the_run_goes() {
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+        }
+        db::init().ok();
+        let executor = AgentExecutor::new(LLMClient::new().unwrap());
+        let goal = "note the total in TextEdit";
+        // The run stops at the second step, before anything would save transcript.json
+        let plan = vec![step("REPORT", None, Some("Looking for the total")), step("TYPE", None, Some("{{text}}"))];
+        assert!(executor.run_plan(goal, &GoalAnalysis::analyze(goal), plan).await.is_err());
+
+        let dir = executor.artifact_dir();
+        let log = std::fs::read_to_string(dir.join("transcript.log")).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(lines.len(), 2, "{}", log);
+        assert!(lines[0].contains("step 1 [llm] REPORT success"), "{}", lines[0]);
+        assert!(lines[1].contains("step 2 [llm] TYPE blocked (nothing_copied)"), "{}", lines[1]);
+        assert!(chrono::DateTime::parse_from_rfc3339(lines[0].split(' ').next().unwrap()).is_ok());
+        assert!(!dir.join("transcript.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_status_checks_policy_and_times_out() {
+        let file = std::env::temp_dir().join(format!("steer_status_{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&file, "ok").unwrap();
+        let found = run_shell_status(&format!("cat {}", file.display()), std::time::Duration::from_secs(5)).await;
+        std::fs::remove_file(&file).ok();
+        assert_eq!(found.unwrap(), 0);
+        let missing = run_shell_status(&format!("cat {}", file.display()), std::time::Duration::from_secs(5)).await;
+        assert_ne!(missing.unwrap(), 0);
+
+        // Not on the allowlist and never approved: refused before it runs
+        let blocked = run_shell_status("curl -s https://example.com", std::time::Duration::from_secs(5)).await.unwrap_err();
+        assert!(blocked.to_string().contains("allowlist"), "{}", blocked);
+
+        let started = std::time::Instant::now();
+        let hung = run_shell_status("tail -f /dev/null", std::time::Duration::from_millis(200)).await.unwrap_err();
+        assert!(hung.to_string().contains("timed out"), "{}", hung);
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_in_resolves_relative_paths_against_cwd() {
+        let dir = std::env::temp_dir().join(format!("steer_shell_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("marker.txt"), "from-workdir").unwrap();
+        let env = vec![("STEER_TEST_GREETING".to_string(), "hello".to_string())];
+
+        let out = run_shell_in("cat marker.txt", Some(dir.to_str().unwrap()), &env).await.unwrap();
+        let greeting = run_shell_in("printenv STEER_TEST_GREETING", Some(dir.to_str().unwrap()), &env).await.unwrap();
+        let missing = run_shell_in("true", Some(dir.join("nope").to_str().unwrap()), &[]).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(out.trim(), "from-workdir");
+        assert_eq!(greeting.trim(), "hello");
+        assert!(missing.is_err());
+    }
+}
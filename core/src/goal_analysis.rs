@@ -0,0 +1,376 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+// [Goal Analysis] The goal text is parsed once, up front, into a GoalAnalysis
+// that the planner prompt and the done-checklist both read, instead of each
+// helper lowercasing the goal and looking for its own keywords. Keywords are
+// data: to support another app, site or language, add rows to the tables below.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Korean,
+}
+
+impl Language {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Korean => "ko",
+        }
+    }
+}
+
+/// Words (lowercase) naming an app → the app's real name.
+const APPS: &[(&[&str], &str)] = &[
+    (&["safari", "사파리"], "Safari"),
+    (&["chrome", "크롬"], "Google Chrome"),
+    (&["notes app", "apple notes", "메모 앱", "메모장"], "Notes"),
+    (&["calendar app", "캘린더 앱"], "Calendar"),
+    (&["finder", "파인더"], "Finder"),
+    (&["calculator", "계산기"], "Calculator"),
+];
+
+/// Words (lowercase) naming a site → a fragment of its URL.
+const SITES: &[(&[&str], &str)] = &[
+    (&["youtube", "유튜브"], "youtube.com"),
+    (&["gmail", "지메일"], "mail.google.com"),
+    (&["github", "깃허브"], "github.com"),
+];
+
+/// Endings that make a bare `name.tld` in the goal a site rather than a file
+/// name; with `http(s)://` or `www.` in front, any ending counts. Endings that
+/// are also file extensions (`.rs`, `.io`, `.me`, `.sh`) need one of those.
+const SITE_TLDS: &[&str] = &["com", "org", "net", "dev", "co", "kr", "app", "ai", "edu", "gov"];
+
+lazy_static! {
+    static ref DOMAIN_RE: Regex =
+        Regex::new(r"\b(https?://)?(www\.)?((?:[a-z0-9-]+\.)+([a-z]{2,}))\b").expect("Invalid domain pattern");
+    static ref EMAIL_RE: Regex = Regex::new(r"[a-z0-9._%+-]+@[a-z0-9.-]+").expect("Invalid email pattern");
+}
+
+/// Apps that are web browsers; a goal naming one already picked its browser.
+const BROWSERS: &[&str] = &["Safari", "Google Chrome"];
+
+/// Browser and search engine to assume when the goal doesn't name them
+/// (STEER_BROWSER, STEER_SEARCH_ENGINE / STEER_SEARCH_URL).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebPreferences {
+    /// `None` means the system default browser
+    pub browser: Option<String>,
+    /// Result page URL with a `{query}` placeholder
+    pub search_template: String,
+}
+
+impl WebPreferences {
+    pub fn from_env() -> Self {
+        Self {
+            browser: crate::browser_automation::preferred_browser(),
+            search_template: crate::web_search::search_template(crate::web_search::engine()),
+        }
+    }
+}
+
+const CALCULATION_WORDS: &[&str] = &["calculate", "calculator", "compute", "sum of", "계산", "합계", "더하기"];
+const FIRST_RESULT_WORDS: &[&str] = &["first result", "top result", "first link", "first video", "첫 번째", "첫번째", "첫 결과", "맨 위"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalAnalysis {
+    pub language: Language,
+    /// Apps named in the goal, in the order they appear.
+    pub apps: Vec<String>,
+    /// Domains of sites named in the goal, by name or as a URL.
+    pub sites: Vec<String>,
+    pub search_query: Option<String>,
+    pub note_title: Option<String>,
+    pub wants_calculation: bool,
+    pub wants_first_result: bool,
+    /// Double-quoted phrases, expected to end up on screen.
+    pub quoted: Vec<String>,
+    /// Result page for `search_query` on the preferred engine, when the goal
+    /// searches without naming a site to search on.
+    pub search_url: Option<String>,
+    /// Preferred browser, when the goal goes to the web without naming one.
+    pub browser: Option<String>,
+}
+
+impl GoalAnalysis {
+    pub fn analyze(goal: &str) -> Self {
+        Self::analyze_with(goal, &WebPreferences::from_env())
+    }
+
+    pub fn analyze_with(goal: &str, web: &WebPreferences) -> Self {
+        let lower = goal.to_lowercase();
+        let language = if goal.chars().any(is_hangul) { Language::Korean } else { Language::English };
+
+        let mut apps: Vec<(usize, String)> = Vec::new();
+        for (keywords, app) in APPS {
+            if let Some(pos) = keywords.iter().filter_map(|k| lower.find(k)).min() {
+                apps.push((pos, app.to_string()));
+            }
+        }
+        apps.sort_by_key(|(pos, _)| *pos);
+
+        // An address to write to ("mail bob@gmail.com") doesn't name a site to visit
+        let without_emails = EMAIL_RE.replace_all(&lower, " ");
+        let mut sites: Vec<String> = SITES
+            .iter()
+            .filter(|(keywords, _)| keywords.iter().any(|k| without_emails.contains(k)))
+            .map(|(_, fragment)| fragment.to_string())
+            .collect();
+        for domain in domains(&without_emails) {
+            if !sites.contains(&domain) {
+                sites.push(domain);
+            }
+        }
+
+        let quoted = quoted_phrases(goal);
+        let wants_calculation = CALCULATION_WORDS.iter().any(|w| lower.contains(w))
+            || Regex::new(r"\d\s*[+\-*/×÷]\s*\d").expect("Invalid arithmetic pattern").is_match(goal);
+
+        let apps: Vec<String> = apps.into_iter().map(|(_, app)| app).collect();
+        let search_query = search_query(goal, &quoted);
+        let search_url = search_query
+            .as_deref()
+            .filter(|_| sites.is_empty())
+            .map(|query| crate::web_search::fill_template(&web.search_template, query));
+        let on_web = search_query.is_some() || !sites.is_empty();
+        let names_browser = apps.iter().any(|app| BROWSERS.contains(&app.as_str()));
+        let browser = web.browser.clone().filter(|_| on_web && !names_browser);
+
+        Self {
+            language,
+            apps,
+            sites,
+            search_query,
+            note_title: note_title(goal),
+            wants_calculation,
+            wants_first_result: FIRST_RESULT_WORDS.iter().any(|w| lower.contains(w)),
+            quoted,
+            search_url,
+            browser,
+        }
+    }
+
+    /// The app the goal is mainly about: the first one it names.
+    pub fn primary_app(&self) -> Option<&str> {
+        self.apps.first().map(String::as_str)
+    }
+
+    /// What was understood, for the planner prompt. Empty when nothing was.
+    pub fn prompt_hint(&self) -> String {
+        let mut facts: Vec<String> = Vec::new();
+        if let Some(app) = self.primary_app() {
+            facts.push(format!("main app: {}", app));
+        }
+        if !self.sites.is_empty() {
+            facts.push(format!("site: {}", self.sites.join(", ")));
+        }
+        if let Some(query) = &self.search_query {
+            facts.push(format!("search query: \"{}\"", query));
+        }
+        if let Some(url) = &self.search_url {
+            facts.push(format!("search by opening {}", url));
+        }
+        if let Some(browser) = &self.browser {
+            facts.push(format!("use the browser {}", browser));
+        }
+        if let Some(title) = &self.note_title {
+            facts.push(format!("note title: \"{}\"", title));
+        }
+        if self.wants_calculation {
+            facts.push("needs a calculation".to_string());
+        }
+        if self.wants_first_result {
+            facts.push("open the first result".to_string());
+        }
+        if facts.is_empty() {
+            return String::new();
+        }
+        facts.push(format!("goal language: {}", self.language.code()));
+        format!("Parsed goal: {}.", facts.join("; "))
+    }
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{AC00}'..='\u{D7A3}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}')
+}
+
+fn quoted_phrases(goal: &str) -> Vec<String> {
+    let re = Regex::new(r#""([^"]{2,})"|“([^”]{2,})”"#).expect("Invalid quote pattern");
+    let mut out: Vec<String> = Vec::new();
+    for caps in re.captures_iter(goal) {
+        if let Some(m) = caps.get(1).or_else(|| caps.get(2)) {
+            let text = m.as_str().trim().to_string();
+            if !text.is_empty() && !out.contains(&text) {
+                out.push(text);
+            }
+        }
+    }
+    out
+}
+
+/// Domains written out in the goal: `https://docs.rs/tokio`, `www.example.com`,
+/// `go to example.org` (host only, without `www.`). A bare name inside a path
+/// (`~/src/main.com`) is a file, not a site.
+fn domains(lower: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for caps in DOMAIN_RE.captures_iter(lower) {
+        let explicit = caps.get(1).is_some() || caps.get(2).is_some();
+        let in_path = lower[..caps.get(0).map_or(0, |m| m.start())].ends_with(['/', '\\', '~']);
+        if in_path && !explicit {
+            continue;
+        }
+        let tld = caps.get(4).map_or("", |m| m.as_str());
+        let domain = caps[3].to_string();
+        if (explicit || SITE_TLDS.contains(&tld)) && !out.contains(&domain) {
+            out.push(domain);
+        }
+    }
+    out
+}
+
+/// `search "x"` / `search for x on YouTube` / `x 검색` / `"x" 검색해줘`.
+fn search_query(goal: &str, quoted: &[String]) -> Option<String> {
+    let lower = goal.to_lowercase();
+    if !(lower.contains("search") || lower.contains("look up") || goal.contains("검색") || goal.contains("찾아")) {
+        return None;
+    }
+    if let Some(q) = quoted.first() {
+        return Some(q.clone());
+    }
+    let patterns = [
+        r"(?i)(?:search|look up)\s+(?:for\s+)?(.+?)(?:\s+(?:on|in|with|using|and|then)\s|[,.?!]|$)",
+        r"(?:에서\s+)?([^\s].*?)\s*(?:을|를)?\s*(?:검색|찾아)",
+    ];
+    for pattern in patterns {
+        let re = Regex::new(pattern).expect("Invalid search pattern");
+        if let Some(m) = re.captures(goal).and_then(|c| c.get(1)) {
+            let query = strip_app_prefix(m.as_str());
+            if !query.is_empty() {
+                return Some(query);
+            }
+        }
+    }
+    None
+}
+
+/// For Korean goals the query sits after "<app>에서": drop that part.
+fn strip_app_prefix(text: &str) -> String {
+    let text = text.trim().trim_matches(|c| c == '.' || c == ',');
+    match text.rsplit_once("에서") {
+        Some((_, rest)) => rest.trim().to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// `note titled "x"` / `note called x` / `제목은 x` / `제목: x`.
+fn note_title(goal: &str) -> Option<String> {
+    let patterns = [
+        r#"(?i)\b(?:titled|called|named)\s+"?([^",.]+?)"?(?:\s+(?:with|and|that)\b|[,.]|$)"#,
+        r#"제목(?:은|을|으로|:)?\s*"?([^",.]+?)"?\s*(?:으로|로|이라고|라고|인|,|\.|$)"#,
+    ];
+    patterns.iter().find_map(|pattern| {
+        Regex::new(pattern)
+            .expect("Invalid title pattern")
+            .captures(goal)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().trim().to_string())
+            .filter(|t| !t.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_goals() {
+        let a = GoalAnalysis::analyze("Open Chrome and search for rust async traits on YouTube, then open the first result");
+        assert_eq!(a.language, Language::English);
+        assert_eq!(a.primary_app(), Some("Google Chrome"));
+        assert_eq!(a.sites, vec!["youtube.com".to_string()]);
+        assert_eq!(a.search_query.as_deref(), Some("rust async traits"));
+        assert!(a.wants_first_result);
+        assert!(!a.wants_calculation);
+
+        let note = GoalAnalysis::analyze("Create a note in Notes app titled Groceries with milk and eggs");
+        assert_eq!(note.primary_app(), Some("Notes"));
+        assert_eq!(note.note_title.as_deref(), Some("Groceries"));
+        assert_eq!(note.search_query, None);
+
+        let calc = GoalAnalysis::analyze("What is 128 * 42?");
+        assert!(calc.wants_calculation);
+        assert!(calc.prompt_hint().contains("needs a calculation"));
+
+        assert_eq!(GoalAnalysis::analyze("check the weather").prompt_hint(), "");
+    }
+
+    #[test]
+    fn test_sites_written_as_urls() {
+        let a = GoalAnalysis::analyze("Go to https://www.Docs.rs/tokio and then open example.org");
+        assert_eq!(a.sites, vec!["docs.rs".to_string(), "example.org".to_string()]);
+        // Named and written out, listed once
+        assert_eq!(GoalAnalysis::analyze("open github.com on GitHub").sites, vec!["github.com".to_string()]);
+        // File names are not sites
+        assert!(GoalAnalysis::analyze("attach report.pdf and notes.txt").sites.is_empty());
+        assert_eq!(GoalAnalysis::analyze("open www.intranet.corp").sites, vec!["intranet.corp".to_string()]);
+        // File names with endings shared with sites, names inside paths, and mail addresses
+        assert!(GoalAnalysis::analyze("open main.rs and config.sh, then read about.me").sites.is_empty());
+        assert!(GoalAnalysis::analyze("open ~/Downloads/invoice.com in Preview").sites.is_empty());
+        assert!(GoalAnalysis::analyze("send the notes to kim@gmail.com").sites.is_empty());
+        assert_eq!(GoalAnalysis::analyze("open https://tokio.rs").sites, vec!["tokio.rs".to_string()]);
+        assert_eq!(GoalAnalysis::analyze("open www.socket.io").sites, vec!["socket.io".to_string()]);
+    }
+
+    #[test]
+    fn test_search_goals_use_the_configured_engine_and_browser() {
+        let ddg_firefox = WebPreferences {
+            browser: Some("Firefox".to_string()),
+            search_template: crate::web_search::engine_named("duckduckgo").search_url.to_string(),
+        };
+        let a = GoalAnalysis::analyze_with("search for rust async traits", &ddg_firefox);
+        assert_eq!(a.search_url.as_deref(), Some("https://duckduckgo.com/?q=rust%20async%20traits"));
+        assert_eq!(a.browser.as_deref(), Some("Firefox"));
+        assert!(a.prompt_hint().contains("search by opening https://duckduckgo.com/?q=rust%20async%20traits"));
+        assert!(a.prompt_hint().contains("use the browser Firefox"));
+
+        // Defaults: Google in the system browser, as before
+        let defaults = WebPreferences {
+            browser: None,
+            search_template: crate::web_search::engine_named("").search_url.to_string(),
+        };
+        let google = GoalAnalysis::analyze_with("사파리에서 삼성전자 주가 검색해줘", &defaults);
+        assert_eq!(google.search_url.as_deref(), Some("https://www.google.com/search?q=%EC%82%BC%EC%84%B1%EC%A0%84%EC%9E%90%20%EC%A3%BC%EA%B0%80"));
+        assert_eq!(google.browser, None);
+
+        // A goal naming its browser or its site keeps them
+        let named = GoalAnalysis::analyze_with("Open Chrome and search for rust async traits on YouTube", &ddg_firefox);
+        assert_eq!((named.search_url, named.browser), (None, None));
+        assert_eq!(GoalAnalysis::analyze_with("check the weather", &ddg_firefox).prompt_hint(), "");
+    }
+
+    #[test]
+    fn test_korean_goals() {
+        let a = GoalAnalysis::analyze("크롬에서 유튜브 열고 \"lofi beats\" 검색해서 첫 번째 영상 틀어줘");
+        assert_eq!(a.language, Language::Korean);
+        assert_eq!(a.primary_app(), Some("Google Chrome"));
+        assert_eq!(a.sites, vec!["youtube.com".to_string()]);
+        assert_eq!(a.search_query.as_deref(), Some("lofi beats"));
+        assert_eq!(a.quoted, vec!["lofi beats".to_string()]);
+        assert!(a.wants_first_result);
+
+        let unquoted = GoalAnalysis::analyze("사파리에서 삼성전자 주가 검색해줘");
+        assert_eq!(unquoted.primary_app(), Some("Safari"));
+        assert_eq!(unquoted.search_query.as_deref(), Some("삼성전자 주가"));
+
+        let note = GoalAnalysis::analyze("메모장에 제목은 장보기로 새 메모 만들어줘");
+        assert_eq!(note.primary_app(), Some("Notes"));
+        assert_eq!(note.note_title.as_deref(), Some("장보기"));
+
+        let calc = GoalAnalysis::analyze("계산기로 37 곱하기 12 계산해줘");
+        assert_eq!(calc.primary_app(), Some("Calculator"));
+        assert!(calc.wants_calculation);
+        assert!(calc.prompt_hint().ends_with("goal language: ko."));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::executor::PlanStep;
+use crate::goal_analysis::GoalAnalysis;
+use crate::llm_gateway::LLMClient;
+use crate::visual_driver::VisualDriver;
+
+// [Done Guard] Finishing every planned step doesn't mean the goal is met.
+// A GoalChecklist is derived from the goal text and must hold before the
+// executor reports success. The apps, sites and quoted text it checks come
+// from the GoalAnalysis; to cover a new kind of goal, add a row to its tables
+// rather than another branch in the executor.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    /// One of these apps must be frontmost. A goal that names several
+    /// ("copy the link from Safari into Notes") passes through all of them,
+    /// so it can only end in one; the last is where it is expected to end.
+    AppFrontmost(Vec<String>),
+    /// The active browser tab must be on one of these sites (the domain or a
+    /// subdomain); like apps, a goal naming several ends on one of them.
+    UrlOnSite(Vec<String>),
+    /// This text must be visible on screen.
+    TextVisible(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GoalChecklist {
+    pub checks: Vec<Check>,
+}
+
+impl GoalChecklist {
+    pub fn from_analysis(analysis: &GoalAnalysis) -> Self {
+        let mut checks: Vec<Check> = Vec::new();
+        if !analysis.apps.is_empty() {
+            checks.push(Check::AppFrontmost(analysis.apps.clone()));
+        }
+        if !analysis.sites.is_empty() {
+            checks.push(Check::UrlOnSite(analysis.sites.clone()));
+        }
+        // Double-quoted text in the goal (type "hello") is expected to end up on screen
+        checks.extend(analysis.quoted.iter().cloned().map(Check::TextVisible));
+        Self { checks }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Checks that currently fail. Checks that can't be evaluated on this
+    /// platform are treated as satisfied rather than blocking completion.
+    pub async fn unmet(&self, llm: Option<&LLMClient>) -> Vec<Check> {
+        let mut unmet = Vec::new();
+        for check in &self.checks {
+            let ok = match check {
+                Check::AppFrontmost(apps) => match frontmost_app().await {
+                    Some(front) => apps.iter().any(|app| front.eq_ignore_ascii_case(app)),
+                    None => true,
+                },
+                Check::UrlOnSite(sites) => match active_url().await {
+                    Some(url) => sites.iter().any(|site| url_on_site(&url, site)),
+                    None => true,
+                },
+                Check::TextVisible(text) => {
+                    selection_contains(text).await
+                        || VisualDriver::wait_for_text(llm, text, 0).await
+                        || !crate::screen_access::available()
+                }
+            };
+            if !ok {
+                unmet.push(check.clone());
+            }
+        }
+        unmet
+    }
+
+    /// `unmet` against a known screen instead of the live one (golden
+    /// replays). As live, an app or URL that isn't known counts as met.
+    pub fn unmet_on(&self, front_app: Option<&str>, url: Option<&str>, visible: &[String]) -> Vec<Check> {
+        let holds = |check: &Check| match check {
+            Check::AppFrontmost(apps) => front_app.is_none_or(|front| apps.iter().any(|app| front.eq_ignore_ascii_case(app))),
+            Check::UrlOnSite(sites) => url.is_none_or(|url| sites.iter().any(|site| url_on_site(url, site))),
+            Check::TextVisible(text) => visible.iter().any(|v| v.contains(text.as_str())),
+        };
+        self.checks.iter().filter(|c| !holds(c)).cloned().collect()
+    }
+}
+
+/// Whether the focused element's selected text already shows `text`; read
+/// through accessibility, so verifying never clobbers the clipboard.
+async fn selection_contains(text: &str) -> bool {
+    let needle = text.to_lowercase();
+    tokio::task::spawn_blocking(crate::selection::read)
+        .await
+        .ok()
+        .and_then(Result::ok)
+        .is_some_and(|selected| selected.to_lowercase().contains(&needle))
+}
+
+impl Check {
+    pub fn describe(&self) -> String {
+        match self {
+            Check::AppFrontmost(apps) => match apps.as_slice() {
+                [app] => format!("{} is frontmost", app),
+                _ => format!("one of {} is frontmost", apps.join(", ")),
+            },
+            Check::UrlOnSite(sites) => match sites.as_slice() {
+                [site] => format!("browser is on {}", site),
+                _ => format!("browser is on one of {}", sites.join(", ")),
+            },
+            Check::TextVisible(text) => format!("'{}' is visible", text),
+        }
+    }
+
+    /// Deterministic step that fixes this check, when there is one.
+    pub fn forced_step(&self) -> Option<PlanStep> {
+        match self {
+            Check::AppFrontmost(apps) => apps.last().map(|app| PlanStep {
+                description: format!("Bring {} to the front", app),
+                action_type: "ACTIVATE".to_string(),
+                target: None,
+                value: Some(app.clone()),
+                verification: String::new(),
+                pre_check: None,
+            }),
+            Check::UrlOnSite(sites) => sites.last().map(|site| PlanStep {
+                description: format!("Go to {}", site),
+                action_type: "URL".to_string(),
+                target: None,
+                value: Some(format!("https://{}", site)),
+                verification: String::new(),
+                pre_check: None,
+            }),
+            Check::TextVisible(_) => None,
+        }
+    }
+}
+
+/// Whether `url`'s host is `site` or a subdomain of it; "github.com" in a
+/// query string or "notgithub.com" doesn't count. A site with a path
+/// ("mail.google.com/chat") also needs the URL path to start with it.
+pub fn url_on_site(url: &str, site: &str) -> bool {
+    let split = |s: &str| -> (String, String) {
+        let s = s.trim().to_lowercase();
+        let s = s.split_once("://").map(|(_, rest)| rest.to_string()).unwrap_or(s);
+        let end = s.find(['/', '?', '#']).unwrap_or(s.len());
+        let host = s[..end].rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+        (host.trim_start_matches("www.").to_string(), s[end..].to_string())
+    };
+    let (host, path) = split(url);
+    let (site_host, site_path) = split(site);
+    if site_host.is_empty() {
+        return false;
+    }
+    let host_ok = host == site_host || host.ends_with(&format!(".{}", site_host));
+    host_ok && path.starts_with(site_path.trim_end_matches('/'))
+}
+
+async fn frontmost_app() -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    tokio::task::spawn_blocking(crate::applescript::frontmost_app_name)
+        .await
+        .ok()?
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+async fn active_url() -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let (_, url) = tokio::task::spawn_blocking(crate::applescript::get_active_window_context)
+        .await
+        .ok()?
+        .ok()?;
+    if url.trim().is_empty() { None } else { Some(url) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checklist_for(goal: &str) -> GoalChecklist {
+        GoalChecklist::from_analysis(&GoalAnalysis::analyze(goal))
+    }
+
+    #[test]
+    fn test_checklist_from_goal() {
+        let list = checklist_for("Open Safari, go to YouTube and search \"lofi beats\"");
+        assert_eq!(
+            list.checks,
+            vec![
+                Check::AppFrontmost(vec!["Safari".to_string()]),
+                Check::UrlOnSite(vec!["youtube.com".to_string()]),
+                Check::TextVisible("lofi beats".to_string()),
+            ]
+        );
+        assert!(checklist_for("check the weather").is_empty());
+    }
+
+    #[test]
+    fn test_forced_step_for_app() {
+        let step = Check::AppFrontmost(vec!["Notes".to_string()]).forced_step().unwrap();
+        assert_eq!(step.action_type, "ACTIVATE");
+        assert_eq!(step.value.as_deref(), Some("Notes"));
+        assert!(Check::TextVisible("hello".to_string()).forced_step().is_none());
+    }
+
+    #[test]
+    fn test_goal_naming_several_apps_needs_only_one_in_front() {
+        let list = checklist_for("Copy the total from Calculator into the Notes app");
+        let apps = vec!["Calculator".to_string(), "Notes".to_string()];
+        assert_eq!(list.checks, vec![Check::AppFrontmost(apps.clone())]);
+        assert_eq!(list.checks[0].describe(), "one of Calculator, Notes is frontmost");
+        // One corrective step, to where the goal ends, instead of one per app
+        let fix = list.checks[0].forced_step().unwrap();
+        assert_eq!(fix.value.as_deref(), Some("Notes"));
+        assert!(Check::AppFrontmost(Vec::new()).forced_step().is_none());
+    }
+
+    #[test]
+    fn test_done_on_the_wrong_domain_is_rejected() {
+        let list = checklist_for("Open github.com and star the tokio repo");
+        assert_eq!(list.checks, vec![Check::UrlOnSite(vec!["github.com".to_string()])]);
+
+        assert!(url_on_site("https://github.com/tokio-rs/tokio", "github.com"));
+        assert!(url_on_site("https://www.github.com", "github.com"));
+        assert!(url_on_site("https://gist.github.com/x", "github.com"));
+        assert!(url_on_site("HTTPS://GitHub.com:443/", "github.com"));
+        assert!(!url_on_site("https://evil.example/?next=github.com", "github.com"));
+        assert!(!url_on_site("https://notgithub.com/", "github.com"));
+        assert!(!url_on_site("https://github.com.evil.example/", "github.com"));
+        assert!(url_on_site("https://mail.google.com/mail/u/0", "mail.google.com"));
+        assert!(!url_on_site("https://docs.google.com/", "mail.google.com"));
+        assert!(url_on_site("https://docs.rs/tokio/latest", "docs.rs/tokio"));
+        assert!(!url_on_site("https://docs.rs/serde", "docs.rs/tokio"));
+
+        // The corrective step goes back to the site
+        let fix = Check::UrlOnSite(vec!["github.com".to_string()]).forced_step().unwrap();
+        assert_eq!((fix.action_type.as_str(), fix.value.as_deref()), ("URL", Some("https://github.com")));
+    }
+
+    #[test]
+    fn test_goal_naming_several_sites_needs_only_one_open() {
+        let list = checklist_for("Copy the issue title from github.com into a reply on gitlab.com");
+        let sites = vec!["github.com".to_string(), "gitlab.com".to_string()];
+        assert_eq!(list.checks, vec![Check::UrlOnSite(sites)]);
+        assert_eq!(list.checks[0].describe(), "browser is on one of github.com, gitlab.com");
+        let fix = list.checks[0].forced_step().unwrap();
+        assert_eq!(fix.value.as_deref(), Some("https://gitlab.com"));
+
+        // An address to mail is not a site to go to
+        assert!(checklist_for("Email the report to ops@example.com").is_empty());
+    }
+}
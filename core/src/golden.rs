@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use crate::executor::{self, AgentExecutor, PlanStep, PlannedStep};
+use crate::llm_gateway::LLMClient;
+
+// [Golden Flows] Regression net for the executor. A recorded run
+// (`golden record <name> <goal>`) keeps every raw planner reply and what the
+// executor did with them: plans that failed validation, each step it
+// dispatched (the UI action and its policy action), steps that policy, the
+// action budget or a missing value stopped, and how the run ended. Replaying
+// runs the goal through the same `run_plan` with the replies scripted and a
+// fake driver, so no LLM, screen or permission is needed; a refactor that
+// changes what a recorded run turns into shows up as a diff.
+//
+// The fake driver lets every step succeed without touching the machine. It
+// only tracks what the goal checklist looks at: the app brought to the front,
+// the URL opened and the text typed. Steps that read the screen produce
+// nothing, so a recorded run that used what it read can replay differently.
+//
+//   STEER_GOLDEN_DIR    where goldens are written and read (default core/tests/goldens)
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Golden {
+    pub goal: String,
+    pub recorded_at: String,
+    /// `EXECUTOR_PLAN_FIX_ATTEMPTS` in effect when recorded
+    pub plan_fix_attempts: u32,
+    /// Planner replies, in the order they were received
+    pub responses: Vec<String>,
+    /// What the run did, one line each: a dispatched step (see `signature`),
+    /// `! <error>` for a plan that failed validation, `x [<reason>] <step>`
+    /// for a step that was stopped, and `=> <outcome>` at the end
+    pub actions: Vec<String>,
+}
+
+impl Golden {
+    pub fn new(goal: &str, plan_fix_attempts: u32) -> Self {
+        Self {
+            goal: goal.to_string(),
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            plan_fix_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub fn note(&mut self, line: String) {
+        self.actions.push(line);
+    }
+}
+
+/// A replay's stand-in for the planner and the screen (see `AgentExecutor::with_script`).
+#[derive(Debug, Default)]
+pub struct Script {
+    replies: VecDeque<String>,
+    pub plan_fix_attempts: u32,
+    pub screen: FakeScreen,
+}
+
+impl Script {
+    pub fn new(golden: &Golden) -> Self {
+        Self {
+            replies: golden.responses.iter().cloned().collect(),
+            plan_fix_attempts: golden.plan_fix_attempts,
+            screen: FakeScreen::default(),
+        }
+    }
+
+    /// The next recorded planner reply.
+    pub fn next_reply(&mut self) -> Result<String> {
+        self.replies.pop_front().ok_or_else(|| anyhow::anyhow!("golden has no more planner replies"))
+    }
+}
+
+/// What the fake driver's steps left on screen, as far as the checklist asks.
+#[derive(Debug, Clone, Default)]
+pub struct FakeScreen {
+    pub front_app: Option<String>,
+    pub url: Option<String>,
+    pub typed: Vec<String>,
+}
+
+impl FakeScreen {
+    pub fn apply(&mut self, step: &PlanStep) {
+        let value = step.value.clone().filter(|v| !v.trim().is_empty());
+        match step.action_type.as_str() {
+            "ACTIVATE" => self.front_app = value.or_else(|| self.front_app.take()),
+            "URL" => self.url = value.or_else(|| self.url.take()),
+            "TYPE" => self.typed.extend(value),
+            _ => {}
+        }
+    }
+}
+
+/// What a step turns into: `CLICK "Send" => Click("Send") [ui.click_text]`.
+pub fn signature(step: &PlanStep) -> String {
+    let policy = executor::policy_action(step)
+        .map(|a| crate::tool_policy::action_kind(&a))
+        .unwrap_or("none");
+    format!("{} => {:?} [{}]", PlannedStep::from(step).action, executor::ui_action(step), policy)
+}
+
+/// Run the golden's goal again with its replies scripted and the fake
+/// driver, and return what the run does now.
+pub async fn replay(golden: &Golden) -> Vec<String> {
+    let executor = AgentExecutor::new(LLMClient::offline()).with_script(golden);
+    // The outcome is noted in the recording
+    let _ = executor.execute_goal(&golden.goal).await;
+    executor.take_recording().map(|g| g.actions).unwrap_or_default()
+}
+
+/// Line-by-line differences between the recorded and the replayed actions.
+pub fn diff(expected: &[String], actual: &[String]) -> Vec<String> {
+    let mut differences = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {}
+            (Some(e), Some(a)) => differences.push(format!("action {}: expected `{}`, got `{}`", i + 1, e, a)),
+            (Some(e), None) => differences.push(format!("action {}: expected `{}`, got nothing", i + 1, e)),
+            (None, Some(a)) => differences.push(format!("action {}: unexpected `{}`", i + 1, a)),
+            (None, None) => {}
+        }
+    }
+    differences
+}
+
+pub fn dir() -> PathBuf {
+    std::env::var_os("STEER_GOLDEN_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("goldens"))
+}
+
+/// Write `golden` as `<dir>/<name>.json`. Names are limited to letters,
+/// digits, `-` and `_` so they can't point outside the folder.
+pub fn save(golden: &Golden, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("Golden names may only use letters, digits, '-' and '_'");
+    }
+    let folder = dir();
+    std::fs::create_dir_all(&folder).with_context(|| format!("Failed to create {}", folder.display()))?;
+    let path = folder.join(format!("{}.json", name));
+    std::fs::write(&path, serde_json::to_string_pretty(golden)?).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Every golden in `folder`, by file name.
+pub fn load_all(folder: &Path) -> Result<Vec<(String, Golden)>> {
+    let mut goldens = Vec::new();
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(goldens),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", folder.display())),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let text = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let golden = serde_json::from_str(&text).with_context(|| format!("Invalid golden {}", path.display()))?;
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        goldens.push((name, golden));
+    }
+    goldens.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(goldens)
+}
+
+/// Replay every golden in `folder`; returns each one that changed with its differences.
+pub async fn check_all(folder: &Path) -> Result<Vec<(String, Vec<String>)>> {
+    let mut changed = Vec::new();
+    for (name, golden) in load_all(folder)? {
+        let differences = diff(&golden.actions, &replay(&golden).await);
+        if !differences.is_empty() {
+            changed.push((name, differences));
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_checked_in_goldens_replay_unchanged() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("goldens");
+        let goldens = load_all(&folder).unwrap();
+        assert!(!goldens.is_empty(), "no goldens in {}", folder.display());
+        let changed = check_all(&folder).await.unwrap();
+        assert!(changed.is_empty(), "goldens changed: {:#?}", changed);
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_a_changed_action() {
+        let mut golden = Golden::new("Open Notes", 0);
+        golden.responses.push(r#"[{"description": "Open Notes", "action_type": "activate", "value": "Notes", "verification": "Notes is open"}]"#.to_string());
+        golden.actions = replay(&golden).await;
+        assert_eq!(golden.actions, vec![r#"ACTIVATE Notes => ActivateApp("Notes") [system.open]"#, "=> Goal Completed"]);
+
+        let drifted = vec![r#"ACTIVATE Notes => Wait(1) [system.open]"#.to_string(), "=> Goal Completed".to_string()];
+        assert_eq!(
+            diff(&drifted, &golden.actions),
+            vec![r#"action 1: expected `ACTIVATE Notes => Wait(1) [system.open]`, got `ACTIVATE Notes => ActivateApp("Notes") [system.open]`"#]
+        );
+        assert_eq!(diff(&[], &golden.actions).len(), 2);
+        assert!(save(&golden, "../escape").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_goes_through_the_executor_checks() {
+        // A placeholder used before anything was copied stops the run, as it would live
+        let mut golden = Golden::new("Type what I copied", 0);
+        golden.responses.push(r#"[{"description": "Type it", "action_type": "TYPE", "value": "{{text}}", "verification": "Typed"}]"#.to_string());
+        let actions = replay(&golden).await;
+        assert_eq!(actions.len(), 2, "{:#?}", actions);
+        assert!(actions[0].starts_with("x [nothing_copied] TYPE {{text}}"), "{}", actions[0]);
+        assert!(actions[1].starts_with("=> [policy]"), "{}", actions[1]);
+
+        // A plan still invalid after the fix-up requests is noted, and ends the run
+        let mut golden = Golden::new("Wait a moment", 0);
+        golden.responses.push(r#"[{"description": "Hover", "action_type": "HOVER", "target": "Sidebar", "verification": "Shown"}]"#.to_string());
+        let actions = replay(&golden).await;
+        assert_eq!(actions.len(), 2, "{:#?}", actions);
+        assert!(actions[0].starts_with("! Plan failed validation"), "{}", actions[0]);
+        assert!(actions[1].starts_with("=> [llm] Plan generation failed"), "{}", actions[1]);
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc, Duration};
 use reqwest::Client;
+use crate::integrations::rate_limit;
 use serde::{Deserialize, Serialize};
-use crate::integrations::google_auth;
 
 #[derive(Debug, Deserialize)]
 struct EventList {
@@ -32,14 +32,31 @@ pub struct CalendarClient {
 }
 
 impl CalendarClient {
+    /// Uses the shared, token-cached auth from `integrations::registry`.
     pub async fn new() -> Result<Self> {
-        let auth = google_auth::get_authenticator().await?;
-        let token = google_auth::get_access_token(&auth).await?;
-        
-        Ok(Self {
-            client: Client::new(),
-            access_token: token,
-        })
+        crate::integrations::registry::calendar().await
+    }
+
+    /// Build from an already-issued token (see `integrations::registry`).
+    pub fn with_token(client: Client, access_token: String) -> Self {
+        Self { client, access_token }
+    }
+
+    /// Read-only auth check: the primary calendar's name, without reading events.
+    pub async fn test_connection(&self) -> Result<String> {
+        let resp = rate_limit::send(
+            "calendar",
+            self.client
+                .get("https://www.googleapis.com/calendar/v3/calendars/primary")
+                .bearer_auth(&self.access_token),
+        )
+        .await?;
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            return Err(anyhow::anyhow!("Calendar API Error: {}", err));
+        }
+        let calendar: serde_json::Value = resp.json().await?;
+        Ok(calendar["summary"].as_str().unwrap_or("primary").to_string())
     }
 
     /// List today's events
@@ -80,13 +97,13 @@ impl CalendarClient {
             time_max.to_rfc3339()
         );
         
-        let resp: EventList = self.client
-            .get(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: EventList = rate_limit::send(
+            "calendar",
+            self.client.get(&url).bearer_auth(&self.access_token),
+        )
+        .await?
+        .json()
+        .await?;
 
         let mut events = Vec::new();
         
@@ -119,6 +136,7 @@ impl CalendarClient {
 
     /// Create a new event
     pub async fn create_event(&self, title: &str, start: &str, end: &str) -> Result<String> {
+        crate::safe_mode::check("calendar changes are")?;
         let url = "https://www.googleapis.com/calendar/v3/calendars/primary/events";
         
         let event = serde_json::json!({
@@ -133,14 +151,13 @@ impl CalendarClient {
             }
         });
 
-        let resp: serde_json::Value = self.client
-            .post(url)
-            .bearer_auth(&self.access_token)
-            .json(&event)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: serde_json::Value = rate_limit::send(
+            "calendar",
+            self.client.post(url).bearer_auth(&self.access_token).json(&event),
+        )
+        .await?
+        .json()
+        .await?;
 
         Ok(resp["id"].as_str().unwrap_or("created").to_string())
     }
@@ -148,16 +165,17 @@ impl CalendarClient {
     /// Delete an event
     #[allow(dead_code)]
     pub async fn delete_event(&self, event_id: &str) -> Result<()> {
+        crate::safe_mode::check("calendar changes are")?;
         let url = format!(
             "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
             event_id
         );
         
-        self.client
-            .delete(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await?;
+        rate_limit::send(
+            "calendar",
+            self.client.delete(&url).bearer_auth(&self.access_token),
+        )
+        .await?;
         
         Ok(())
     }
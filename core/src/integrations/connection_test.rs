@@ -0,0 +1,115 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use crate::integrations::{calendar::CalendarClient, discord::DiscordClient, gmail::GmailClient, google_auth, notion::NotionClient, telegram::TelegramBot};
+use crate::n8n_api::N8nApi;
+
+// `test integrations`: a cheap read-only call per service (profile, bot
+// identity, workflow list of one) so credentials can be checked during setup
+// without sending a message or reading anyone's mail.
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connected,
+    Failed,
+    NotConfigured,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionReport {
+    pub service: &'static str,
+    pub state: ConnectionState,
+    /// Who we are connected as, or why not.
+    pub detail: String,
+}
+
+impl ConnectionReport {
+    pub fn icon(&self) -> &'static str {
+        match self.state {
+            ConnectionState::Connected => "✅",
+            ConnectionState::Failed => "❌",
+            ConnectionState::NotConfigured => "⚪",
+        }
+    }
+}
+
+/// Build the client (a failure there means "not configured") and run its test
+/// within `timeout`.
+async fn check<C, F, Fut>(service: &'static str, client: Result<C>, test: F, timeout: Duration) -> ConnectionReport
+where
+    F: FnOnce(C) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let (state, detail) = match client {
+        Err(e) => (ConnectionState::NotConfigured, e.to_string()),
+        Ok(client) => match tokio::time::timeout(timeout, test(client)).await {
+            Ok(Ok(who)) => (ConnectionState::Connected, who),
+            Ok(Err(e)) => (ConnectionState::Failed, e.to_string()),
+            Err(_) => (ConnectionState::Failed, format!("no answer within {}s", timeout.as_secs())),
+        },
+    };
+    ConnectionReport { service, state, detail }
+}
+
+/// Google clients, but only when a token is already cached: testing must never
+/// open the browser consent flow.
+async fn google_client<C, Fut>(build: impl FnOnce() -> Fut) -> Result<C>
+where
+    Fut: Future<Output = Result<C>>,
+{
+    if !google_auth::has_cached_token() {
+        return Err(anyhow::anyhow!("not signed in (run `gmail list` once to authorize)"));
+    }
+    build().await
+}
+
+/// Test every integration concurrently.
+pub async fn test_all() -> Vec<ConnectionReport> {
+    let n8n = match std::env::var("N8N_API_KEY") {
+        Ok(key) if !key.trim().is_empty() => N8nApi::from_env(),
+        _ => Err(anyhow::anyhow!("N8N_API_KEY not set")),
+    };
+    let (gmail, calendar, notion, n8n, telegram, discord) = tokio::join!(
+        async { check("gmail", google_client(GmailClient::new).await, |c| async move { c.test_connection().await }, CHECK_TIMEOUT).await },
+        async { check("calendar", google_client(CalendarClient::new).await, |c| async move { c.test_connection().await }, CHECK_TIMEOUT).await },
+        check("notion", NotionClient::from_env(), |c| async move { c.test_connection().await }, CHECK_TIMEOUT),
+        check("n8n", n8n, |c| async move { c.test_connection().await }, CHECK_TIMEOUT),
+        check("telegram", TelegramBot::from_env(), |c| async move { c.test_connection().await }, CHECK_TIMEOUT),
+        check("discord", DiscordClient::from_env(), |c| async move { c.test_connection().await }, CHECK_TIMEOUT),
+    );
+    vec![gmail, calendar, notion, n8n, telegram, discord]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_maps_outcomes_to_states() {
+        let ok = check("svc", Ok(()), |_| async { Ok("bot@example".to_string()) }, CHECK_TIMEOUT).await;
+        assert_eq!((ok.state, ok.detail.as_str()), (ConnectionState::Connected, "bot@example"));
+
+        let failed = check("svc", Ok(()), |_| async { Err(anyhow::anyhow!("401 Unauthorized")) }, CHECK_TIMEOUT).await;
+        assert_eq!((failed.state, failed.detail.as_str()), (ConnectionState::Failed, "401 Unauthorized"));
+
+        let missing = check("svc", Err::<(), _>(anyhow::anyhow!("TOKEN not set")), |_| async { Ok(String::new()) }, CHECK_TIMEOUT).await;
+        assert_eq!(missing.state, ConnectionState::NotConfigured);
+
+        let slow = check(
+            "svc",
+            Ok(()),
+            |_| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(String::new())
+            },
+            Duration::from_millis(10),
+        )
+        .await;
+        assert_eq!(slow.state, ConnectionState::Failed);
+        assert!(slow.detail.starts_with("no answer"));
+    }
+}
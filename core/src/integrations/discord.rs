@@ -0,0 +1,415 @@
+use reqwest::Client;
+use anyhow::Result;
+use serde_json::json;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+const API_BASE: &str = "https://discord.com/api/v10";
+
+lazy_static::lazy_static! {
+    /// Stop signal of the running command channel, if any.
+    static ref COMMAND_CHANNEL: Mutex<Option<watch::Sender<bool>>> = Mutex::new(None);
+}
+
+/// How messages are delivered: an incoming webhook (send-only) or a bot
+/// token bound to a channel (send + command polling).
+enum Transport {
+    Webhook { url: String },
+    Bot { token: String, channel_id: String },
+}
+
+pub struct DiscordClient {
+    transport: Transport,
+    client: Client,
+}
+
+pub struct Embed {
+    pub title: String,
+    pub description: String,
+    pub color: Option<u32>,
+}
+
+impl DiscordClient {
+    pub fn with_webhook(url: &str) -> Self {
+        Self {
+            transport: Transport::Webhook { url: url.to_string() },
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_bot(token: &str, channel_id: &str) -> Self {
+        Self {
+            transport: Transport::Bot {
+                token: token.to_string(),
+                channel_id: channel_id.to_string(),
+            },
+            client: Client::new(),
+        }
+    }
+
+    /// Prefers `DISCORD_WEBHOOK_URL`; falls back to `DISCORD_BOT_TOKEN` + `DISCORD_CHANNEL_ID`.
+    pub fn from_env() -> Result<Self> {
+        dotenv::dotenv().ok();
+        if let Ok(url) = std::env::var("DISCORD_WEBHOOK_URL") {
+            if !url.trim().is_empty() {
+                return Ok(Self::with_webhook(url.trim()));
+            }
+        }
+        let token = std::env::var("DISCORD_BOT_TOKEN")
+            .map_err(|_| anyhow::anyhow!("DISCORD_WEBHOOK_URL or DISCORD_BOT_TOKEN not set"))?;
+        let channel_id = std::env::var("DISCORD_CHANNEL_ID")
+            .map_err(|_| anyhow::anyhow!("DISCORD_CHANNEL_ID not set"))?;
+        Ok(Self::with_bot(&token, &channel_id))
+    }
+
+    pub fn is_bot(&self) -> bool {
+        matches!(self.transport, Transport::Bot { .. })
+    }
+
+    /// Read-only auth check: the webhook's (or bot user's) name, without posting.
+    pub async fn test_connection(&self) -> Result<String> {
+        let req = match &self.transport {
+            Transport::Webhook { url } => self.client.get(url),
+            Transport::Bot { token, channel_id } => self
+                .client
+                .get(format!("{}/channels/{}", API_BASE, channel_id))
+                .header("Authorization", format!("Bot {}", token)),
+        };
+
+        // A webhook URL carries its token: keep it out of error messages
+        let resp = req.send().await.map_err(reqwest::Error::without_url)?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await.map_err(reqwest::Error::without_url)?;
+            return Err(anyhow::anyhow!("Discord API Error: {}", err));
+        }
+
+        let info: serde_json::Value = resp.json().await.map_err(reqwest::Error::without_url)?;
+        Ok(info["name"].as_str().unwrap_or("connected").to_string())
+    }
+
+    pub async fn send(&self, message: &str) -> Result<()> {
+        crate::safe_mode::check("sending messages is")?;
+        self.post(json!({ "content": truncate(message, 2000) })).await
+    }
+
+    /// Post `embed`, with `message` above it unless empty.
+    pub async fn send_embed(&self, message: &str, embed: &Embed) -> Result<()> {
+        crate::safe_mode::check("sending messages is")?;
+        self.post(embed_body(message, embed)).await
+    }
+
+    async fn post(&self, body: serde_json::Value) -> Result<()> {
+        let req = match &self.transport {
+            Transport::Webhook { url } => self.client.post(url),
+            Transport::Bot { token, channel_id } => self
+                .client
+                .post(format!("{}/channels/{}/messages", API_BASE, channel_id))
+                .header("Authorization", format!("Bot {}", token)),
+        };
+
+        let resp = req.json(&body).send().await.map_err(reqwest::Error::without_url)?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await.map_err(reqwest::Error::without_url)?;
+            return Err(anyhow::anyhow!("Discord API Error: {}", err));
+        }
+
+        Ok(())
+    }
+
+    /// Minimal command loop for bot mode: polls the configured channel and
+    /// hands each new non-bot message to `handler`, posting back any reply.
+    /// Only runs when the channel (and its guild, if `DISCORD_ALLOWED_GUILDS`
+    /// is set) is on the allow-list. Returns `Ok(())` once `shutdown` turns
+    /// true (or its sender is dropped).
+    pub async fn poll_commands<F, Fut>(&self, handler: F, mut shutdown: watch::Receiver<bool>) -> Result<()>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Option<String>>,
+    {
+        let (token, channel_id) = match &self.transport {
+            Transport::Bot { token, channel_id } => (token.clone(), channel_id.clone()),
+            Transport::Webhook { .. } => {
+                return Err(anyhow::anyhow!("Command polling requires DISCORD_BOT_TOKEN"))
+            }
+        };
+
+        let allowed_channels = env_list("DISCORD_ALLOWED_CHANNELS");
+        if !allowed_channels.contains(&channel_id) {
+            return Err(anyhow::anyhow!(
+                "Channel {} is not in DISCORD_ALLOWED_CHANNELS",
+                channel_id
+            ));
+        }
+        let allowed_guilds = env_list("DISCORD_ALLOWED_GUILDS");
+        if !allowed_guilds.is_empty() {
+            let channel: serde_json::Value = self
+                .client
+                .get(format!("{}/channels/{}", API_BASE, channel_id))
+                .header("Authorization", format!("Bot {}", token))
+                .send()
+                .await?
+                .json()
+                .await?;
+            let guild_id = channel["guild_id"].as_str().unwrap_or_default();
+            if !allowed_guilds.iter().any(|g| g == guild_id) {
+                return Err(anyhow::anyhow!(
+                    "Guild {} is not in DISCORD_ALLOWED_GUILDS",
+                    guild_id
+                ));
+            }
+        }
+
+        let interval_secs = std::env::var("DISCORD_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5)
+            .max(1);
+
+        // Start after the newest existing message so history isn't replayed.
+        let mut last_id = self
+            .fetch_messages(&token, &channel_id, None, 1)
+            .await?
+            .first()
+            .and_then(|m| m["id"].as_str().map(|s| s.to_string()));
+
+        loop {
+            if stop_requested(std::time::Duration::from_secs(interval_secs), &mut shutdown).await {
+                return Ok(());
+            }
+            let mut messages = match self
+                .fetch_messages(&token, &channel_id, last_id.as_deref(), 50)
+                .await
+            {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("⚠️ [Discord] Poll failed: {}", e);
+                    continue;
+                }
+            };
+            // API returns newest first.
+            messages.reverse();
+            for msg in messages {
+                if let Some(id) = msg["id"].as_str() {
+                    last_id = Some(id.to_string());
+                }
+                if msg["author"]["bot"].as_bool().unwrap_or(false) {
+                    continue;
+                }
+                let text = msg["content"].as_str().unwrap_or_default().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(reply) = handler(text).await {
+                    if let Err(e) = self.send(&reply).await {
+                        eprintln!("⚠️ [Discord] Reply failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn fetch_messages(
+        &self,
+        token: &str,
+        channel_id: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut url = format!(
+            "{}/channels/{}/messages?limit={}",
+            API_BASE, channel_id, limit
+        );
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", after));
+        }
+        let resp = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bot {}", token))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            return Err(anyhow::anyhow!("Discord API Error: {}", err));
+        }
+        Ok(resp.json().await?)
+    }
+}
+
+/// Replies for the command channel: `ping` and `status`; anything else is
+/// ignored. The Telegram poller answers with the same set.
+pub(crate) async fn command_reply(text: String) -> Option<String> {
+    match text.trim().to_lowercase().as_str() {
+        "ping" => Some("pong".to_string()),
+        "status" => {
+            let s = crate::analyzer::status();
+            Some(format!(
+                "Steer is running. Events: {} received, {} processed, {} dropped.",
+                s.received, s.processed, s.dropped
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Start the command channel in the background. Returns `Ok(false)` if it
+/// is already running.
+pub fn start_command_channel() -> Result<bool> {
+    let client = DiscordClient::from_env()?;
+    if !client.is_bot() {
+        return Err(anyhow::anyhow!(
+            "Command channel requires DISCORD_BOT_TOKEN (webhooks are send-only)"
+        ));
+    }
+    Ok(spawn_command_channel(move |rx| async move { client.poll_commands(command_reply, rx).await }))
+}
+
+/// Spawn `poll` with a fresh stop signal unless a poller is still running.
+fn spawn_command_channel<P, Fut>(poll: P) -> bool
+where
+    P: FnOnce(watch::Receiver<bool>) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let mut slot = COMMAND_CHANNEL.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.as_ref().is_some_and(|tx| !tx.is_closed()) {
+        return false;
+    }
+    let (tx, rx) = watch::channel(false);
+    let polling = poll(rx);
+    tokio::spawn(async move {
+        match polling.await {
+            Ok(()) => println!("💬 Discord command channel stopped."),
+            Err(e) => eprintln!("⚠️  Discord command channel stopped: {}", e),
+        }
+    });
+    *slot = Some(tx);
+    true
+}
+
+/// Signal the command channel to exit its poll loop. Returns whether it was running.
+pub fn stop_command_channel() -> bool {
+    let tx = COMMAND_CHANNEL.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match tx {
+        Some(tx) => tx.send(true).is_ok(),
+        None => false,
+    }
+}
+
+/// The poll loop exits on error too, which drops its receiver.
+pub fn command_channel_running() -> bool {
+    COMMAND_CHANNEL
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|tx| !tx.is_closed())
+}
+
+/// Wait out one poll interval; true if `shutdown` turned true (or its
+/// sender was dropped) before or during it.
+async fn stop_requested(interval: std::time::Duration, shutdown: &mut watch::Receiver<bool>) -> bool {
+    if *shutdown.borrow() {
+        return true;
+    }
+    tokio::select! {
+        _ = tokio::time::sleep(interval) => false,
+        changed = shutdown.changed() => changed.is_err() || *shutdown.borrow(),
+    }
+}
+
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn embed_body(message: &str, embed: &Embed) -> serde_json::Value {
+    let mut embed_json = json!({
+        "title": truncate(&embed.title, 256),
+        "description": truncate(&embed.description, 4096),
+    });
+    if let Some(color) = embed.color {
+        embed_json["color"] = json!(color);
+    }
+    let mut body = json!({ "embeds": [embed_json] });
+    if !message.is_empty() {
+        body["content"] = json!(truncate(message, 2000));
+    }
+    body
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_body_omits_empty_content_and_truncates() {
+        let embed = Embed { title: "t".repeat(300), description: "Routine finished".to_string(), color: Some(0x5865F2) };
+        let body = embed_body("", &embed);
+        assert!(body.get("content").is_none());
+        assert_eq!(body["embeds"][0]["title"].as_str().unwrap().chars().count(), 256);
+        assert_eq!(body["embeds"][0]["description"], "Routine finished");
+        assert_eq!(body["embeds"][0]["color"], 0x5865F2);
+        assert_eq!(embed_body("hi", &embed)["content"], "hi");
+    }
+
+    #[tokio::test]
+    async fn test_command_channel_stops_and_restarts() {
+        // Stands in for poll_commands: waits out its interval until told to stop
+        let poll = |mut rx: watch::Receiver<bool>| async move {
+            while !stop_requested(std::time::Duration::from_secs(3600), &mut rx).await {}
+            Ok(())
+        };
+        let stopped = || async {
+            for _ in 0..200 {
+                if !command_channel_running() {
+                    return true;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            false
+        };
+
+        assert!(spawn_command_channel(poll));
+        assert!(command_channel_running());
+        assert!(!spawn_command_channel(poll), "a second poller was started");
+
+        assert!(stop_command_channel());
+        assert!(stopped().await);
+        assert!(!stop_command_channel());
+
+        assert!(spawn_command_channel(poll));
+        assert!(command_channel_running());
+        assert!(stop_command_channel());
+        assert!(stopped().await);
+    }
+
+    #[tokio::test]
+    async fn test_stop_requested_cuts_the_interval_short() {
+        let (tx, mut rx) = watch::channel(false);
+        assert!(!stop_requested(std::time::Duration::from_millis(1), &mut rx).await);
+
+        let started = std::time::Instant::now();
+        let stop = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            tx.send(true).unwrap();
+        });
+        assert!(stop_requested(std::time::Duration::from_secs(3600), &mut rx).await);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        stop.await.unwrap();
+
+        // A dropped sender stops the loop too
+        let (tx, mut rx) = watch::channel(false);
+        drop(tx);
+        assert!(stop_requested(std::time::Duration::from_secs(3600), &mut rx).await);
+    }
+}
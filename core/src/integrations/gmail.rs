@@ -1,8 +1,8 @@
 #![allow(dead_code)]
 use anyhow::Result;
 use reqwest::Client;
+use crate::integrations::rate_limit;
 use serde::Deserialize;
-use crate::integrations::google_auth;
 
 #[derive(Debug, Deserialize)]
 struct MessageList {
@@ -38,14 +38,31 @@ pub struct GmailClient {
 }
 
 impl GmailClient {
+    /// Uses the shared, token-cached auth from `integrations::registry`.
     pub async fn new() -> Result<Self> {
-        let auth = google_auth::get_authenticator().await?;
-        let token = google_auth::get_access_token(&auth).await?;
-        
-        Ok(Self {
-            client: Client::new(),
-            access_token: token,
-        })
+        crate::integrations::registry::gmail().await
+    }
+
+    /// Build from an already-issued token (see `integrations::registry`).
+    pub fn with_token(client: Client, access_token: String) -> Self {
+        Self { client, access_token }
+    }
+
+    /// Read-only auth check: the mailbox address, without listing any mail.
+    pub async fn test_connection(&self) -> Result<String> {
+        let resp = rate_limit::send(
+            "gmail",
+            self.client
+                .get("https://gmail.googleapis.com/gmail/v1/users/me/profile")
+                .bearer_auth(&self.access_token),
+        )
+        .await?;
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            return Err(anyhow::anyhow!("Gmail API Error: {}", err));
+        }
+        let profile: serde_json::Value = resp.json().await?;
+        Ok(profile["emailAddress"].as_str().unwrap_or("unknown").to_string())
     }
 
     /// List recent messages from inbox
@@ -55,13 +72,13 @@ impl GmailClient {
             max_results
         );
         
-        let resp: MessageList = self.client
-            .get(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: MessageList = rate_limit::send(
+            "gmail",
+            self.client.get(&url).bearer_auth(&self.access_token),
+        )
+        .await?
+        .json()
+        .await?;
 
         let mut messages = Vec::new();
         
@@ -83,13 +100,13 @@ impl GmailClient {
             id
         );
         
-        let resp: MessageDetails = self.client
-            .get(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: MessageDetails = rate_limit::send(
+            "gmail",
+            self.client.get(&url).bearer_auth(&self.access_token),
+        )
+        .await?
+        .json()
+        .await?;
 
         let mut subject = String::from("(No Subject)");
         let mut from = String::from("(Unknown)");
@@ -116,13 +133,13 @@ impl GmailClient {
             id
         );
         
-        let resp: MessageDetails = self.client
-            .get(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: MessageDetails = rate_limit::send(
+            "gmail",
+            self.client.get(&url).bearer_auth(&self.access_token),
+        )
+        .await?
+        .json()
+        .await?;
 
         let mut content = String::new();
 
@@ -147,6 +164,7 @@ impl GmailClient {
     /// Send an email
     pub async fn send_message(&self, to: &str, subject: &str, body: &str) -> Result<String> {
         use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+        crate::safe_mode::check("sending email is")?;
         
         let email = format!(
             "To: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
@@ -157,14 +175,16 @@ impl GmailClient {
         
         let url = "https://gmail.googleapis.com/gmail/v1/users/me/messages/send";
         
-        let resp: serde_json::Value = self.client
-            .post(url)
-            .bearer_auth(&self.access_token)
-            .json(&serde_json::json!({ "raw": encoded }))
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: serde_json::Value = rate_limit::send(
+            "gmail",
+            self.client
+                .post(url)
+                .bearer_auth(&self.access_token)
+                .json(&serde_json::json!({ "raw": encoded })),
+        )
+        .await?
+        .json()
+        .await?;
 
         Ok(resp["id"].as_str().unwrap_or("sent").to_string())
     }
@@ -28,6 +28,11 @@ fn token_cache_path() -> PathBuf {
     path
 }
 
+/// True when a previous OAuth consent left a token cache on disk.
+pub fn has_cached_token() -> bool {
+    credentials_path().exists() && token_cache_path().exists()
+}
+
 /// Type alias for the authenticator used throughout the Google integration
 pub type GoogleAuthenticator = yup_oauth2::authenticator::Authenticator<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>;
 
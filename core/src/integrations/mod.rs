@@ -1,5 +1,9 @@
 pub mod telegram;
+pub mod discord;
 pub mod notion;
 pub mod google_auth;
 pub mod gmail;
 pub mod calendar;
+pub mod registry;
+pub mod rate_limit;
+pub mod connection_test;
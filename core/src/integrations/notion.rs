@@ -1,6 +1,7 @@
 use reqwest::Client;
 use serde_json::json;
 use anyhow::Result;
+use crate::integrations::rate_limit;
 
 pub struct NotionClient {
     token: String,
@@ -22,8 +23,25 @@ impl NotionClient {
         Ok(Self::new(&token))
     }
 
+    /// Read-only auth check: the integration's own bot user.
+    pub async fn test_connection(&self) -> Result<String> {
+        let req = self.client.get("https://api.notion.com/v1/users/me")
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Notion-Version", "2022-06-28");
+        let resp = rate_limit::send("notion", req).await?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            return Err(anyhow::anyhow!("Notion API Error: {}", err));
+        }
+
+        let me: serde_json::Value = resp.json().await?;
+        Ok(me["name"].as_str().unwrap_or("integration").to_string())
+    }
+
     /// Create a new page in a database
     pub async fn create_page(&self, database_id: &str, title: &str, content: &str) -> Result<String> {
+        crate::safe_mode::check("writing to Notion is")?;
         let url = "https://api.notion.com/v1/pages";
 
         let body = json!({
@@ -44,13 +62,12 @@ impl NotionClient {
             ]
         });
 
-        let resp = self.client.post(url)
+        let req = self.client.post(url)
             .header("Authorization", format!("Bearer {}", self.token))
             .header("Notion-Version", "2022-06-28")
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
+            .json(&body);
+        let resp = rate_limit::send("notion", req).await?;
 
         if !resp.status().is_success() {
             let err = resp.text().await?;
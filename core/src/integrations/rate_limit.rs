@@ -0,0 +1,277 @@
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Shared throttle + retry for outbound integration calls (gmail, calendar,
+// notion, n8n). Each service gets one limiter per process so bursts from
+// different commands are spaced out together. 429 and 5xx responses are
+// retried with exponential backoff, honoring Retry-After when present.
+// A 5xx can arrive after the server already did the work, so it is only
+// retried for idempotent methods (GET/HEAD/PUT/DELETE/OPTIONS) or requests
+// sent with `send_idempotent`; a POST that sends mail or creates an event is
+// retried only on 429, which means it was never processed.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    pub service: String,
+    pub retry_after_secs: u64,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} rate limited, retry after {}s",
+            self.service, self.retry_after_secs
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Minimum spacing between requests.
+    pub min_interval: Duration,
+    /// Retries after the first attempt on 429/5xx.
+    pub max_retries: u32,
+    /// First backoff delay; doubled on each retry.
+    pub base_backoff: Duration,
+}
+
+impl RateLimitConfig {
+    /// `STEER_<SERVICE>_RPS`, `STEER_<SERVICE>_MAX_RETRIES`, `STEER_<SERVICE>_BACKOFF_MS`.
+    pub fn from_env(service: &str) -> Self {
+        let prefix = format!("STEER_{}", service.to_uppercase());
+        let rps = env_f64(&format!("{}_RPS", prefix)).filter(|v| *v > 0.0).unwrap_or(5.0);
+        Self {
+            min_interval: Duration::from_secs_f64(1.0 / rps),
+            max_retries: env_u64(&format!("{}_MAX_RETRIES", prefix)).unwrap_or(3) as u32,
+            base_backoff: Duration::from_millis(env_u64(&format!("{}_BACKOFF_MS", prefix)).unwrap_or(500)),
+        }
+    }
+}
+
+pub struct RateLimiter {
+    service: String,
+    config: RateLimitConfig,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+lazy_static::lazy_static! {
+    static ref LIMITERS: Mutex<HashMap<String, Arc<RateLimiter>>> = Mutex::new(HashMap::new());
+}
+
+/// Process-wide limiter for `service`, configured from the environment on first use.
+pub fn limiter(service: &str) -> Arc<RateLimiter> {
+    let mut map = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    map.entry(service.to_string())
+        .or_insert_with(|| Arc::new(RateLimiter::new(service, RateLimitConfig::from_env(service))))
+        .clone()
+}
+
+/// Send `request` through the shared limiter for `service`.
+pub async fn send(service: &str, request: RequestBuilder) -> anyhow::Result<Response> {
+    limiter(service).send(request).await
+}
+
+/// `send` for a request that is safe to repeat whatever its method (e.g. a
+/// POST that activates a workflow), so 5xx responses are retried too.
+pub async fn send_idempotent(service: &str, request: RequestBuilder) -> anyhow::Result<Response> {
+    limiter(service).send_with(request, true).await
+}
+
+impl RateLimiter {
+    pub fn new(service: &str, config: RateLimitConfig) -> Self {
+        Self {
+            service: service.to_string(),
+            config,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Wait until this service may issue another request.
+    async fn acquire(&self) {
+        let wait = {
+            let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = next.filter(|t| *t > now).unwrap_or(now);
+            *next = Some(slot + self.config.min_interval);
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Send with throttling and retry. Exhausted 429s become a `RateLimited`
+    /// error; an exhausted 5xx is returned as-is so callers keep their own
+    /// error messages.
+    pub async fn send(&self, request: RequestBuilder) -> anyhow::Result<Response> {
+        self.send_with(request, false).await
+    }
+
+    /// `send`; `idempotent` also retries 5xx for methods that aren't idempotent by definition.
+    pub async fn send_with(&self, request: RequestBuilder, idempotent: bool) -> anyhow::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let req = request
+                .try_clone()
+                .ok_or_else(|| anyhow::anyhow!("{} request body cannot be retried", self.service))?;
+            let (client, req) = req.build_split();
+            let req = req?;
+            let idempotent = idempotent || is_idempotent(req.method());
+            self.acquire().await;
+            let resp = client.execute(req).await?;
+
+            let status = resp.status();
+            if !is_retryable(status, idempotent) {
+                return Ok(resp);
+            }
+            let retry_after = retry_after(&resp);
+            if attempt >= self.config.max_retries {
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    let secs = retry_after.unwrap_or(self.backoff(attempt)).as_secs().max(1);
+                    return Err(RateLimited { service: self.service.clone(), retry_after_secs: secs }.into());
+                }
+                return Ok(resp);
+            }
+
+            let delay = retry_after.unwrap_or_else(|| self.backoff(attempt));
+            println!(
+                "⏳ [{}] {} — retrying in {}ms ({}/{})",
+                self.service,
+                status,
+                delay.as_millis(),
+                attempt + 1,
+                self.config.max_retries
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.config.base_backoff.saturating_mul(2u32.saturating_pow(attempt))
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+}
+
+/// 429 means the request wasn't processed; a 5xx may come after it was.
+fn is_retryable(status: StatusCode, idempotent: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (idempotent && status.is_server_error())
+}
+
+/// Retry-After in seconds (the HTTP-date form is ignored). Capped at 60s.
+fn retry_after(resp: &Response) -> Option<Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|secs| Duration::from_secs(secs.min(60)))
+}
+
+fn env_u64(key: &str) -> Option<u64> {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+fn env_f64(key: &str) -> Option<f64> {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Local server that answers 429 for the first `failures` requests, then 200.
+    async fn flaky_server(failures: usize) -> (String, Arc<AtomicUsize>) {
+        failing_server(failures, axum::http::StatusCode::TOO_MANY_REQUESTS).await
+    }
+
+    /// Local server that answers `status` for the first `failures` requests (any method), then 200.
+    async fn failing_server(failures: usize, status: axum::http::StatusCode) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::any(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        (status, "try again")
+                    } else {
+                        (axum::http::StatusCode::OK, "ok")
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        (format!("http://{}/", addr), hits)
+    }
+
+    fn fast_config(max_retries: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            min_interval: Duration::from_millis(1),
+            max_retries,
+            base_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_429_then_succeeds() {
+        let (url, hits) = flaky_server(1).await;
+        let limiter = RateLimiter::new("test", fast_config(3));
+        let resp = limiter.send(reqwest::Client::new().get(&url)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_return_rate_limited() {
+        let (url, hits) = flaky_server(usize::MAX).await;
+        let limiter = RateLimiter::new("gmail", fast_config(2));
+        let err = limiter.send(reqwest::Client::new().get(&url)).await.unwrap_err();
+        let limited = err.downcast_ref::<RateLimited>().expect("typed rate limit error");
+        assert_eq!(limited.service, "gmail");
+        assert!(limited.retry_after_secs >= 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_post_is_not_retried_on_5xx() {
+        let limiter = RateLimiter::new("gmail", fast_config(3));
+        let client = reqwest::Client::new();
+
+        // The send may already have happened: no second email
+        let (url, hits) = failing_server(1, axum::http::StatusCode::BAD_GATEWAY).await;
+        let resp = limiter.send(client.post(&url).body("mail")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // GETs, and POSTs marked idempotent, are retried
+        let (url, hits) = failing_server(1, axum::http::StatusCode::BAD_GATEWAY).await;
+        assert_eq!(limiter.send(client.get(&url)).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        let (url, hits) = failing_server(1, axum::http::StatusCode::BAD_GATEWAY).await;
+        assert_eq!(limiter.send_with(client.post(&url), true).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // A 429 POST was never processed, so it is retried
+        let (url, hits) = flaky_server(1).await;
+        assert_eq!(limiter.send(client.post(&url).body("mail")).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+}
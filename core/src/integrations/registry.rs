@@ -0,0 +1,101 @@
+use anyhow::Result;
+use reqwest::Client;
+use tokio::sync::{Mutex, OnceCell};
+use crate::integrations::calendar::CalendarClient;
+use crate::integrations::gmail::GmailClient;
+use crate::integrations::google_auth::{self, GoogleAuthenticator};
+
+// Shared Google auth for the gmail/calendar commands. The authenticator is
+// built once per process and the access token is cached until it gets close
+// to expiry, so repeated commands skip the OAuth round trip.
+
+/// Refresh this long before the token actually expires.
+const REFRESH_MARGIN_SECS: i64 = 300;
+
+struct CachedToken {
+    token: String,
+    expires_at: Option<i64>, // unix seconds
+}
+
+static AUTH: OnceCell<GoogleAuthenticator> = OnceCell::const_new();
+static TOKEN: Mutex<Option<CachedToken>> = Mutex::const_new(None);
+static HTTP: OnceCell<Client> = OnceCell::const_new();
+
+async fn authenticator() -> Result<&'static GoogleAuthenticator> {
+    AUTH.get_or_try_init(google_auth::get_authenticator).await
+}
+
+async fn http() -> &'static Client {
+    HTTP.get_or_init(|| async { Client::new() }).await
+}
+
+fn needs_refresh(expires_at: Option<i64>, now: i64) -> bool {
+    match expires_at {
+        Some(exp) => exp - REFRESH_MARGIN_SECS <= now,
+        None => false,
+    }
+}
+
+/// A valid access token, refreshed proactively when it is about to expire.
+pub async fn access_token() -> Result<String> {
+    let mut cached = TOKEN.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    if let Some(c) = cached.as_ref() {
+        if !needs_refresh(c.expires_at, now) {
+            return Ok(c.token.clone());
+        }
+    }
+
+    let auth = authenticator().await?;
+    let token = if cached.is_some() {
+        auth.force_refreshed_token(google_auth::ALL_SCOPES).await?
+    } else {
+        auth.token(google_auth::ALL_SCOPES).await?
+    };
+    let value = token
+        .token()
+        .ok_or_else(|| anyhow::anyhow!("Failed to get access token"))?
+        .to_string();
+    *cached = Some(CachedToken {
+        token: value.clone(),
+        expires_at: token.expiration_time().map(|t| t.unix_timestamp()),
+    });
+    Ok(value)
+}
+
+pub async fn gmail() -> Result<GmailClient> {
+    let token = access_token().await?;
+    Ok(GmailClient::with_token(http().await.clone(), token))
+}
+
+pub async fn calendar() -> Result<CalendarClient> {
+    let token = access_token().await?;
+    Ok(CalendarClient::with_token(http().await.clone(), token))
+}
+
+/// Authenticate in the background at startup, but only when a token is
+/// already cached on disk (never opens a browser consent flow unprompted).
+pub fn warm_up() {
+    if !google_auth::has_cached_token() {
+        return;
+    }
+    tokio::spawn(async {
+        if let Err(e) = access_token().await {
+            eprintln!("⚠️ Google auth warm-up failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = 1_000_000;
+        assert!(!needs_refresh(None, now));
+        assert!(!needs_refresh(Some(now + 3600), now));
+        assert!(needs_refresh(Some(now + 60), now));
+        assert!(needs_refresh(Some(now - 10), now));
+    }
+}
@@ -1,10 +1,169 @@
 use reqwest::Client;
 use anyhow::Result;
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::watch;
+
+const API_BASE: &str = "https://api.telegram.org";
+
+lazy_static::lazy_static! {
+    /// Stop signal of the running poller, if any.
+    static ref POLLER: Mutex<Option<watch::Sender<bool>>> = Mutex::new(None);
+    static ref HEALTH: PollHealth = PollHealth::default();
+    /// When a message last went out, per tracked run (see `track_sends`).
+    static ref LAST_SENT: Mutex<std::collections::HashMap<String, chrono::DateTime<chrono::Utc>>> = Mutex::new(Default::default());
+}
+
+tokio::task_local! {
+    /// Run whose sends are recorded, for routine success checks.
+    static SEND_SCOPE: String;
+}
+
+/// Run `work` with its successful `send`s recorded under `run`, so one
+/// routine's check can't be satisfied by another run's message.
+pub async fn track_sends<F: Future>(run: String, work: F) -> F::Output {
+    SEND_SCOPE.scope(run, work).await
+}
+
+/// Time of the most recent send made inside `track_sends(run, ..)`. Reading
+/// it forgets the run.
+pub fn take_last_sent(run: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    LAST_SENT.lock().unwrap_or_else(|e| e.into_inner()).remove(run)
+}
+
+fn record_sent() {
+    if let Ok(run) = SEND_SCOPE.try_with(|run| run.clone()) {
+        LAST_SENT.lock().unwrap_or_else(|e| e.into_inner()).insert(run, chrono::Utc::now());
+    }
+}
 
 pub struct TelegramBot {
     token: String,
     chat_id: String,
     client: Client,
+    api_base: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    #[serde(default)]
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+/// Why a `getUpdates` call failed. A 409 means someone else is reading this
+/// bot's updates (another poller, or a webhook) and is handled apart from
+/// ordinary network or API errors.
+#[derive(Debug)]
+pub enum PollError {
+    Conflict(String),
+    Other(anyhow::Error),
+}
+
+impl std::fmt::Display for PollError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conflict(description) => write!(f, "409 Conflict: {}", description),
+            Self::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// reqwest errors print their URL, and ours carry the bot token (`/bot<token>/`)
+impl From<reqwest::Error> for PollError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Other(e.without_url().into())
+    }
+}
+
+/// Long-poll settings: how long Telegram may hold each `getUpdates`, and
+/// the delay after a failed one, doubling up to `backoff_max`.
+#[derive(Debug, Clone, Copy)]
+pub struct PollConfig {
+    pub timeout: Duration,
+    pub backoff_initial: Duration,
+    pub backoff_max: Duration,
+}
+
+impl PollConfig {
+    pub fn from_env() -> Self {
+        let secs = |key: &str, default: u64| std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(default);
+        Self {
+            timeout: Duration::from_secs(secs("TELEGRAM_POLL_TIMEOUT_SECS", 30).min(50)),
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(secs("TELEGRAM_POLL_BACKOFF_MAX_SECS", 60).max(1)),
+        }
+    }
+
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1).min(16));
+        self.backoff_initial.saturating_mul(factor).min(self.backoff_max)
+    }
+}
+
+/// Poll counters for `/metrics`.
+#[derive(Default)]
+pub struct PollHealth {
+    polls: AtomicU64,
+    errors: AtomicU64,
+    conflicts: AtomicU64,
+    updates: AtomicU64,
+    backoff_ms: AtomicU64,
+    last_ok_unix: AtomicU64,
+}
+
+impl PollHealth {
+    fn record_ok(&self, updates: usize) {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        self.updates.fetch_add(updates as u64, Ordering::Relaxed);
+        self.backoff_ms.store(0, Ordering::Relaxed);
+        self.last_ok_unix.store(chrono::Utc::now().timestamp().max(0) as u64, Ordering::Relaxed);
+    }
+
+    fn record_error(&self, error: &PollError, backoff: Duration) {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        if matches!(error, PollError::Conflict(_)) {
+            self.conflicts.fetch_add(1, Ordering::Relaxed);
+        }
+        self.backoff_ms.store(backoff.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn render_metrics(&self) -> String {
+        let polls = self.polls.load(Ordering::Relaxed);
+        if polls == 0 {
+            return String::new();
+        }
+        format!(
+            "# TYPE steer_telegram_polls_total counter\nsteer_telegram_polls_total {}\n\
+             # TYPE steer_telegram_poll_errors_total counter\nsteer_telegram_poll_errors_total {}\n\
+             # TYPE steer_telegram_poll_conflicts_total counter\nsteer_telegram_poll_conflicts_total {}\n\
+             # TYPE steer_telegram_updates_total counter\nsteer_telegram_updates_total {}\n\
+             # TYPE steer_telegram_poll_backoff_ms gauge\nsteer_telegram_poll_backoff_ms {}\n\
+             # TYPE steer_telegram_last_poll_ok_timestamp gauge\nsteer_telegram_last_poll_ok_timestamp {}\n",
+            polls,
+            self.errors.load(Ordering::Relaxed),
+            self.conflicts.load(Ordering::Relaxed),
+            self.updates.load(Ordering::Relaxed),
+            self.backoff_ms.load(Ordering::Relaxed),
+            self.last_ok_unix.load(Ordering::Relaxed),
+        )
+    }
 }
 
 impl TelegramBot {
@@ -13,23 +172,70 @@ impl TelegramBot {
             token: token.to_string(),
             chat_id: chat_id.to_string(),
             client: Client::new(),
+            api_base: API_BASE.to_string(),
         }
     }
 
+    /// Point the bot at another Bot API server (a local one, or a test double).
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/bot{}/{}", self.api_base, self.token, method)
+    }
+
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok();
         let token = std::env::var("TELEGRAM_BOT_TOKEN")
             .map_err(|_| anyhow::anyhow!("TELEGRAM_BOT_TOKEN not set"))?;
         let chat_id = std::env::var("TELEGRAM_CHAT_ID")
             .map_err(|_| anyhow::anyhow!("TELEGRAM_CHAT_ID not set"))?;
-        Ok(Self::new(&token, &chat_id))
+        let bot = Self::new(&token, &chat_id);
+        match std::env::var("TELEGRAM_API_BASE") {
+            Ok(base) if !base.trim().is_empty() => Ok(bot.with_api_base(base.trim())),
+            _ => Ok(bot),
+        }
+    }
+
+    /// Read-only auth check (`getMe` + `getChat`): returns "@bot → chat" without sending anything.
+    pub async fn test_connection(&self) -> Result<String> {
+        let me: serde_json::Value = self
+            .client
+            .get(self.method_url("getMe"))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        if me["ok"].as_bool() != Some(true) {
+            return Err(anyhow::anyhow!("Telegram API Error: {}", me["description"].as_str().unwrap_or("invalid token")));
+        }
+        let chat: serde_json::Value = self
+            .client
+            .get(self.method_url("getChat"))
+            .query(&[("chat_id", self.chat_id.as_str())])
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        if chat["ok"].as_bool() != Some(true) {
+            return Err(anyhow::anyhow!("Telegram chat {} not reachable: {}", self.chat_id, chat["description"].as_str().unwrap_or("unknown")));
+        }
+        let chat_name = chat["result"]["title"]
+            .as_str()
+            .or_else(|| chat["result"]["username"].as_str())
+            .unwrap_or(&self.chat_id);
+        Ok(format!("@{} → {}", me["result"]["username"].as_str().unwrap_or("bot"), chat_name))
     }
 
     pub async fn send(&self, message: &str) -> Result<()> {
-        let url = format!(
-            "https://api.telegram.org/bot{}/sendMessage",
-            self.token
-        );
+        crate::safe_mode::check("sending messages is")?;
+        let url = self.method_url("sendMessage");
 
         let params = [
             ("chat_id", self.chat_id.as_str()),
@@ -37,13 +243,325 @@ impl TelegramBot {
             ("parse_mode", "Markdown"),
         ];
 
-        let resp = self.client.post(&url).form(&params).send().await?;
+        let resp = self.client.post(&url).form(&params).send().await.map_err(reqwest::Error::without_url)?;
 
         if !resp.status().is_success() {
-            let err = resp.text().await?;
+            let err = resp.text().await.map_err(reqwest::Error::without_url)?;
             return Err(anyhow::anyhow!("Telegram API Error: {}", err));
         }
 
+        record_sent();
         Ok(())
     }
+
+    /// One `getUpdates` call; Telegram holds it open for up to `timeout`.
+    pub async fn get_updates(&self, offset: i64, timeout: Duration) -> std::result::Result<Vec<Update>, PollError> {
+        let resp = self
+            .client
+            .get(self.method_url("getUpdates"))
+            .query(&[("offset", offset.to_string()), ("timeout", timeout.as_secs().to_string())])
+            // Leave room past the long-poll window before calling the connection dead
+            .timeout(timeout + Duration::from_secs(10))
+            .send()
+            .await?;
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        let description = body["description"].as_str().unwrap_or("no description").to_string();
+        if status == reqwest::StatusCode::CONFLICT {
+            return Err(PollError::Conflict(description));
+        }
+        if !status.is_success() || body["ok"].as_bool() != Some(true) {
+            return Err(PollError::Other(anyhow::anyhow!("Telegram API Error ({}): {}", status, description)));
+        }
+        serde_json::from_value(body["result"].clone()).map_err(|e| PollError::Other(e.into()))
+    }
+
+    /// A 409 caused by a webhook is ours to fix: getUpdates and webhooks are
+    /// mutually exclusive. Another poller on the same token is not, so that
+    /// case only backs off until it goes away.
+    async fn recover_from_conflict(&self, description: &str) {
+        if !description.to_lowercase().contains("webhook") {
+            eprintln!("⚠️ [Telegram] Another poller is reading updates for this bot; backing off.");
+            return;
+        }
+        match self.client.post(self.method_url("deleteWebhook")).send().await {
+            Ok(resp) if resp.status().is_success() => println!("📨 [Telegram] Removed the bot's webhook so polling can take over."),
+            Ok(resp) => eprintln!("⚠️ [Telegram] deleteWebhook failed: {}", resp.status()),
+            Err(e) => eprintln!("⚠️ [Telegram] deleteWebhook failed: {}", e.without_url()),
+        }
+    }
+
+    /// Long-poll loop: hands each new text message from the configured chat
+    /// to `handler` and sends back any reply. Messages waiting before the
+    /// poller started are skipped. Failed polls back off exponentially and a
+    /// success resets the delay. Returns once `shutdown` turns true (or its
+    /// sender is dropped), including mid-poll.
+    pub async fn start_polling<F, Fut>(
+        &self,
+        config: PollConfig,
+        health: &PollHealth,
+        handler: F,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<()>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Option<String>>,
+    {
+        // None until the backlog has been skipped
+        let mut offset: Option<i64> = None;
+        let mut failures = 0u32;
+
+        loop {
+            if *shutdown.borrow() {
+                return Ok(());
+            }
+            // offset -1 asks for only the newest pending update, without waiting
+            let (request_offset, timeout) = match offset {
+                Some(o) => (o, config.timeout),
+                None => (-1, Duration::ZERO),
+            };
+            let result = tokio::select! {
+                result = self.get_updates(request_offset, timeout) => result,
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        return Ok(());
+                    }
+                    continue;
+                }
+            };
+
+            let updates = match result {
+                Ok(updates) => {
+                    failures = 0;
+                    health.record_ok(updates.len());
+                    updates
+                }
+                Err(e) => {
+                    failures += 1;
+                    let delay = config.backoff(failures);
+                    health.record_error(&e, delay);
+                    eprintln!("⚠️ [Telegram] Poll failed ({} in a row, retrying in {:?}): {}", failures, delay, e);
+                    if let PollError::Conflict(description) = &e {
+                        self.recover_from_conflict(description).await;
+                    }
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        changed = shutdown.changed() => {
+                            if changed.is_err() || *shutdown.borrow() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let next = updates.iter().map(|u| u.update_id + 1).max();
+            if offset.is_none() {
+                offset = Some(next.unwrap_or(0));
+                continue;
+            }
+            if let Some(next) = next {
+                offset = Some(next);
+            }
+            for update in updates {
+                let Some(message) = update.message else { continue };
+                if message.chat.id.to_string() != self.chat_id {
+                    continue;
+                }
+                let text = message.text.unwrap_or_default().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(reply) = handler(text).await {
+                    if let Err(e) = self.send(&reply).await {
+                        eprintln!("⚠️ [Telegram] Reply failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Start the Telegram poller in the background. Returns `Ok(false)` if it is
+/// already running.
+pub fn start_poller() -> Result<bool> {
+    let bot = TelegramBot::from_env()?;
+    let mut slot = POLLER.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.as_ref().is_some_and(|tx| !tx.is_closed()) {
+        return Ok(false);
+    }
+    let (tx, rx) = watch::channel(false);
+    tokio::spawn(async move {
+        match bot.start_polling(PollConfig::from_env(), &HEALTH, super::discord::command_reply, rx).await {
+            Ok(()) => println!("📨 Telegram poller stopped."),
+            Err(e) => eprintln!("⚠️  Telegram poller stopped: {}", e),
+        }
+    });
+    *slot = Some(tx);
+    Ok(true)
+}
+
+/// Signal the poller to stop, even mid-poll. Returns whether it was running.
+pub fn stop_poller() -> bool {
+    let tx = POLLER.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match tx {
+        Some(tx) => tx.send(true).is_ok(),
+        None => false,
+    }
+}
+
+/// Prometheus text for `/metrics`: poll, error and 409 counts, current backoff.
+pub fn render_metrics() -> String {
+    HEALTH.render_metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Query, State};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct MockApi {
+        polls: Mutex<Vec<String>>,
+        sent: Mutex<Vec<String>>,
+    }
+
+    async fn mock_get_updates(
+        State(api): State<Arc<MockApi>>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> (axum::http::StatusCode, Json<serde_json::Value>) {
+        use axum::http::StatusCode;
+        let offset = query.get("offset").cloned().unwrap_or_default();
+        let call = {
+            let mut polls = api.polls.lock().unwrap();
+            polls.push(offset.clone());
+            polls.len()
+        };
+        let message = |id: i64, chat: i64, text: &str| {
+            serde_json::json!({ "update_id": id, "message": { "chat": { "id": chat }, "text": text } })
+        };
+        match call {
+            1 => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "ok": false, "description": "Bad Gateway" }))),
+            2 => (
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({ "ok": false, "description": "Conflict: terminated by other getUpdates request" })),
+            ),
+            // The backlog: skipped, never handled
+            3 => (StatusCode::OK, Json(serde_json::json!({ "ok": true, "result": [message(41, 7, "old")] }))),
+            4 => (
+                StatusCode::OK,
+                Json(serde_json::json!({ "ok": true, "result": [message(42, 99, "ping"), message(43, 7, "ping")] })),
+            ),
+            _ => {
+                // Hold the long poll open like Telegram does
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                (StatusCode::OK, Json(serde_json::json!({ "ok": true, "result": [] })))
+            }
+        }
+    }
+
+    async fn mock_send(
+        State(api): State<Arc<MockApi>>,
+        axum::extract::Form(form): axum::extract::Form<HashMap<String, String>>,
+    ) -> Json<serde_json::Value> {
+        api.sent.lock().unwrap().push(format!("{}:{}", form["chat_id"], form["text"]));
+        Json(serde_json::json!({ "ok": true }))
+    }
+
+    #[tokio::test]
+    async fn test_poller_backs_off_through_errors_then_handles_updates() {
+        let api = Arc::new(MockApi::default());
+        let app = Router::new()
+            .route("/:bot/getUpdates", get(mock_get_updates))
+            .route("/:bot/sendMessage", post(mock_send))
+            .with_state(api.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let bot = TelegramBot::new("TOKEN", "7").with_api_base(&format!("http://{}", addr));
+        let config = PollConfig {
+            timeout: Duration::from_secs(30),
+            backoff_initial: Duration::from_millis(20),
+            backoff_max: Duration::from_millis(50),
+        };
+        let health = Arc::new(PollHealth::default());
+        let (tx, rx) = watch::channel(false);
+        let poller = {
+            let health = health.clone();
+            tokio::spawn(async move {
+                bot.start_polling(config, &health, |text| async move { Some(format!("re: {}", text)) }, rx).await
+            })
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while api.polls.lock().unwrap().len() < 5 {
+            assert!(tokio::time::Instant::now() < deadline, "poller stalled: {:?}", api.polls.lock().unwrap());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Errors retry from the start; after the backlog, polling resumes past it
+        assert_eq!(*api.polls.lock().unwrap(), vec!["-1", "-1", "-1", "42", "44"]);
+        // Only the configured chat is answered
+        assert_eq!(*api.sent.lock().unwrap(), vec!["7:re: ping"]);
+        let metrics = health.render_metrics();
+        assert!(metrics.contains("steer_telegram_poll_errors_total 2\n"), "{}", metrics);
+        assert!(metrics.contains("steer_telegram_poll_conflicts_total 1\n"), "{}", metrics);
+        assert!(metrics.contains("steer_telegram_poll_backoff_ms 0\n"), "{}", metrics);
+
+        // Shutdown interrupts the open long poll
+        tx.send(true).unwrap();
+        let stopped = tokio::time::timeout(Duration::from_secs(2), poller).await;
+        assert!(matches!(stopped, Ok(Ok(Ok(())))), "{:?}", stopped);
+    }
+
+    #[tokio::test]
+    async fn test_sends_are_tracked_per_run() {
+        let api = Arc::new(MockApi::default());
+        let app = Router::new().route("/:bot/sendMessage", post(mock_send)).with_state(api.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let bot = TelegramBot::new("TOKEN", "7").with_api_base(&format!("http://{}", addr));
+
+        let sender = format!("run-{}", uuid::Uuid::new_v4());
+        let idle = format!("run-{}", uuid::Uuid::new_v4());
+        track_sends(sender.clone(), bot.send("briefing")).await.unwrap();
+        track_sends(idle.clone(), async {}).await;
+        bot.send("untracked").await.unwrap();
+
+        assert!(take_last_sent(&sender).is_some());
+        assert_eq!(take_last_sent(&sender), None);
+        assert_eq!(take_last_sent(&idle), None);
+        assert_eq!(api.sent.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let config = PollConfig {
+            timeout: Duration::from_secs(30),
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+        };
+        let delays: Vec<u64> = (1..=8).map(|n| config.backoff(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(config.backoff(u32::MAX).as_secs(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_network_errors_do_not_print_the_token() {
+        // Nothing listens on port 1: every call fails before a response
+        let bot = TelegramBot::new("123456:SECRET-TOKEN", "7").with_api_base("http://127.0.0.1:1");
+        let poll = bot.get_updates(0, Duration::ZERO).await.unwrap_err().to_string();
+        let send = bot.send("hi").await.unwrap_err().to_string();
+        let check = bot.test_connection().await.unwrap_err().to_string();
+        for message in [poll, send, check] {
+            assert!(!message.contains("SECRET-TOKEN"), "{}", message);
+        }
+    }
 }
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use atspi::proxy::accessible::AccessibleProxy;
+use atspi::proxy::action::ActionProxy;
+use atspi::zbus::{self, proxy::CacheProperties};
+use atspi::{AccessibilityConnection, Interface, ObjectRef, State};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+// [Linux Accessibility] AT-SPI2 counterpart of `macos::accessibility::snapshot`.
+// The tree has the same shape (focused app → focused window → children with
+// role/title), with AT-SPI role names mapped to their AX equivalents. Nodes
+// that expose an action also get a `ref` ("e1", "e2", …) that `click <ref>`
+// resolves against the latest snapshot.
+
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+/// Levels below the window, as on macOS (window children + 2).
+const MAX_DEPTH: usize = 2;
+/// Nodes read per snapshot; browsers expose thousands.
+const MAX_NODES: usize = 500;
+
+/// AT-SPI role name → AX role. Unlisted roles keep their AT-SPI name.
+const ROLES: &[(&str, &str)] = &[
+    ("push button", "AXButton"),
+    ("toggle button", "AXCheckBox"),
+    ("check box", "AXCheckBox"),
+    ("radio button", "AXRadioButton"),
+    ("text", "AXTextField"),
+    ("entry", "AXTextField"),
+    ("password text", "AXSecureTextField"),
+    ("label", "AXStaticText"),
+    ("link", "AXLink"),
+    ("menu item", "AXMenuItem"),
+    ("menu", "AXMenu"),
+    ("menu bar", "AXMenuBar"),
+    ("combo box", "AXComboBox"),
+    ("list", "AXList"),
+    ("list item", "AXCell"),
+    ("page tab", "AXRadioButton"),
+    ("tool bar", "AXToolbar"),
+    ("panel", "AXGroup"),
+    ("filler", "AXGroup"),
+    ("image", "AXImage"),
+];
+
+lazy_static::lazy_static! {
+    /// Refs handed out by the latest snapshot.
+    static ref REFS: Mutex<HashMap<String, ObjectRef>> = Mutex::new(HashMap::new());
+}
+
+fn ax_role(role_name: &str) -> String {
+    ROLES
+        .iter()
+        .find(|(atspi, _)| *atspi == role_name)
+        .map(|(_, ax)| ax.to_string())
+        .unwrap_or_else(|| role_name.to_string())
+}
+
+/// One element as read from the bus, before it becomes JSON.
+struct RawNode {
+    role_name: String,
+    name: String,
+    /// Set when the element has the Action interface.
+    clickable: Option<ObjectRef>,
+    children: Vec<RawNode>,
+}
+
+/// JSON nodes for `nodes`, numbering clickable ones in document order.
+fn to_json(nodes: Vec<RawNode>, refs: &mut Vec<ObjectRef>) -> Vec<Value> {
+    nodes
+        .into_iter()
+        .map(|node| {
+            let mut value = json!({ "role": ax_role(&node.role_name) });
+            if !node.name.is_empty() {
+                value["title"] = json!(node.name);
+            }
+            if let Some(obj) = node.clickable {
+                refs.push(obj);
+                value["ref"] = json!(format!("e{}", refs.len()));
+            }
+            value["children"] = json!(to_json(node.children, refs));
+            value
+        })
+        .collect()
+}
+
+async fn proxy(conn: &zbus::Connection, obj: &ObjectRef) -> Result<AccessibleProxy<'static>> {
+    Ok(AccessibleProxy::builder(conn)
+        .destination(obj.name.clone())?
+        .path(obj.path.clone())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?)
+}
+
+fn read_children<'a>(
+    conn: &'a zbus::Connection,
+    parent: &'a AccessibleProxy<'a>,
+    depth: usize,
+    budget: &'a mut usize,
+) -> Pin<Box<dyn Future<Output = Vec<RawNode>> + Send + 'a>> {
+    Box::pin(async move {
+        let mut nodes = Vec::new();
+        let Ok(children) = parent.get_children().await else {
+            return nodes;
+        };
+        for child in children {
+            if *budget == 0 {
+                break;
+            }
+            *budget -= 1;
+            let Ok(element) = proxy(conn, &child).await else { continue };
+            let role_name = element.get_role_name().await.unwrap_or_default();
+            let name = element.name().await.unwrap_or_default();
+            let clickable = match element.get_interfaces().await {
+                Ok(interfaces) if interfaces.contains(Interface::Action) => Some(child.clone()),
+                _ => None,
+            };
+            let children = if depth < MAX_DEPTH {
+                read_children(conn, &element, depth + 1, budget).await
+            } else {
+                Vec::new()
+            };
+            nodes.push(RawNode { role_name, name, clickable, children });
+        }
+        nodes
+    })
+}
+
+async fn connect() -> Result<AccessibilityConnection> {
+    AccessibilityConnection::new()
+        .await
+        .context("AT-SPI accessibility bus not available (is at-spi2-core running?)")
+}
+
+/// The app named like `scope` (or any app) and its active window.
+async fn focused_window<'a>(conn: &'a zbus::Connection, scope: Option<&str>) -> Result<(String, AccessibleProxy<'a>)> {
+    let root = AccessibleProxy::builder(conn)
+        .destination(REGISTRY)?
+        .path(ROOT_PATH)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    let scope = scope.map(|s| s.to_lowercase());
+    let mut fallback = None;
+    for app_ref in root.get_children().await? {
+        let Ok(app) = proxy(conn, &app_ref).await else { continue };
+        let app_name = app.name().await.unwrap_or_default();
+        if let Some(scope) = &scope {
+            if !app_name.to_lowercase().contains(scope) {
+                continue;
+            }
+        }
+        for window_ref in app.get_children().await.unwrap_or_default() {
+            let Ok(window) = proxy(conn, &window_ref).await else { continue };
+            let active = window.get_state().await.map(|s| s.contains(State::Active)).unwrap_or(false);
+            if active {
+                return Ok((app_name, window));
+            }
+            // A named app without an active window: use its first window
+            if scope.is_some() && fallback.is_none() {
+                fallback = Some((app_name.clone(), window));
+            }
+        }
+    }
+    fallback.ok_or_else(|| anyhow::anyhow!("No focused window"))
+}
+
+pub async fn snapshot(scope: Option<String>) -> Value {
+    println!("[Linux] Capturing Snapshot (AT-SPI)...");
+    let conn = match connect().await {
+        Ok(conn) => conn,
+        Err(e) => return json!({ "error": format!("{:#}", e) }),
+    };
+    let conn = conn.connection();
+    let (app_title, window) = match focused_window(conn, scope.as_deref()).await {
+        Ok(found) => found,
+        Err(e) => return json!({ "error": e.to_string() }),
+    };
+    let window_title = window.name().await.unwrap_or_default();
+    let mut budget = MAX_NODES;
+    let raw = read_children(conn, &window, 0, &mut budget).await;
+
+    let mut refs = Vec::new();
+    let children = to_json(raw, &mut refs);
+    *REFS.lock().unwrap_or_else(|e| e.into_inner()) =
+        refs.into_iter().enumerate().map(|(i, obj)| (format!("e{}", i + 1), obj)).collect();
+
+    json!({
+        "role": "AXApplication",
+        "title": app_title,
+        "focused_window": {
+            "role": "AXWindow",
+            "title": window_title,
+            "children": children
+        }
+    })
+}
+
+/// Run the default action (press, activate, jump) of `element_ref` from the latest snapshot.
+pub async fn click_ref(element_ref: &str) -> Result<()> {
+    let obj = REFS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(element_ref)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Unknown ref '{}' (run snap first)", element_ref))?;
+    let conn = connect().await?;
+    let action = ActionProxy::builder(conn.connection())
+        .destination(obj.name.as_str())?
+        .path(obj.path.as_str())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    if action.do_action(0).await? {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("'{}' did not accept the click", element_ref))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(role: &str, name: &str, clickable: bool, children: Vec<RawNode>) -> RawNode {
+        RawNode { role_name: role.to_string(), name: name.to_string(), clickable: clickable.then(ObjectRef::default), children }
+    }
+
+    #[test]
+    fn test_tree_matches_the_macos_shape_with_refs() {
+        let raw = vec![
+            node("tool bar", "", false, vec![node("push button", "Back", true, vec![])]),
+            node("entry", "Search", true, vec![]),
+            node("heading", "Results", false, vec![]),
+        ];
+        let mut refs = Vec::new();
+        let tree = to_json(raw, &mut refs);
+
+        assert_eq!(refs.len(), 2);
+        assert_eq!(tree[0], json!({ "role": "AXToolbar", "children": [{ "role": "AXButton", "title": "Back", "ref": "e1", "children": [] }] }));
+        assert_eq!(tree[1]["role"], "AXTextField");
+        assert_eq!(tree[1]["ref"], "e2");
+        assert_eq!(tree[2]["role"], "heading");
+        assert!(tree[2].get("ref").is_none());
+    }
+}
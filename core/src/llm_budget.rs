@@ -0,0 +1,167 @@
+use chrono::Datelike;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// [Safety] Process-wide daily token budget shared by every LLMClient clone
+// (analyzer, scheduler, executor, API). Checked before each request, charged
+// from the provider's `usage` block afterwards, so concurrent in-flight calls
+// can overshoot the cap by at most one response each.
+static TOKENS_USED: AtomicU64 = AtomicU64::new(0);
+static BUDGET_DAY: AtomicI64 = AtomicI64::new(0);
+
+lazy_static::lazy_static! {
+    /// (task, model) → (calls, tokens) since start, so cost can be attributed per task.
+    static ref TASK_USAGE: Mutex<BTreeMap<(String, String), (u64, u64)>> = Mutex::new(BTreeMap::new());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LLM daily token budget exceeded ({} / {} tokens); calls are blocked until tomorrow (UTC) or STEER_LLM_DAILY_TOKEN_BUDGET is raised",
+            self.used, self.limit
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub used_today: u64,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+}
+
+/// `STEER_LLM_DAILY_TOKEN_BUDGET`; unset or 0 means unlimited.
+pub fn daily_limit() -> Option<u64> {
+    std::env::var("STEER_LLM_DAILY_TOKEN_BUDGET")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|v| *v > 0)
+}
+
+/// Reset the counter when the UTC day changes.
+fn roll_day() {
+    let today = chrono::Utc::now().date_naive().num_days_from_ce() as i64;
+    let prev = BUDGET_DAY.load(Ordering::Relaxed);
+    if prev != today
+        && BUDGET_DAY
+            .compare_exchange(prev, today, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    {
+        TOKENS_USED.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Call before sending a request; errors once today's usage has reached the cap.
+pub fn check() -> Result<(), BudgetExceeded> {
+    check_against(daily_limit())
+}
+
+fn check_against(limit: Option<u64>) -> Result<(), BudgetExceeded> {
+    roll_day();
+    let used = TOKENS_USED.load(Ordering::Relaxed);
+    match limit {
+        Some(limit) if used >= limit => Err(BudgetExceeded { used, limit }),
+        _ => Ok(()),
+    }
+}
+
+/// Charge the `usage.total_tokens` of a provider response against today's budget.
+pub fn record_usage(response: &Value) {
+    let tokens = response["usage"]["total_tokens"].as_u64().unwrap_or(0);
+    if tokens == 0 {
+        return;
+    }
+    roll_day();
+    TOKENS_USED.fetch_add(tokens, Ordering::Relaxed);
+}
+
+/// Count a call of `task` on `model` and its tokens (not tied to the daily cap).
+pub fn record_task_usage(task: &str, model: &str, response: &Value) {
+    let tokens = response["usage"]["total_tokens"].as_u64().unwrap_or(0);
+    let mut usage = TASK_USAGE.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = usage.entry((task.to_string(), model.to_string())).or_insert((0, 0));
+    entry.0 += 1;
+    entry.1 += tokens;
+}
+
+pub fn status() -> BudgetStatus {
+    roll_day();
+    let used_today = TOKENS_USED.load(Ordering::Relaxed);
+    let limit = daily_limit();
+    BudgetStatus {
+        used_today,
+        limit,
+        remaining: limit.map(|l| l.saturating_sub(used_today)),
+    }
+}
+
+/// Prometheus text for `/metrics`. Budget gauges are omitted when no cap is set.
+pub fn render_metrics() -> String {
+    let s = status();
+    let mut out = format!(
+        "# TYPE steer_llm_tokens_used_today gauge\n\
+steer_llm_tokens_used_today {}\n",
+        s.used_today
+    );
+    if let (Some(limit), Some(remaining)) = (s.limit, s.remaining) {
+        out.push_str(&format!(
+            "# TYPE steer_llm_token_budget gauge\n\
+steer_llm_token_budget {}\n\
+# TYPE steer_llm_tokens_remaining gauge\n\
+steer_llm_tokens_remaining {}\n",
+            limit, remaining
+        ));
+    }
+    let usage = TASK_USAGE.lock().unwrap_or_else(|e| e.into_inner());
+    if !usage.is_empty() {
+        out.push_str("# TYPE steer_llm_task_calls_total counter\n");
+        for ((task, model), (calls, _)) in usage.iter() {
+            out.push_str(&format!("steer_llm_task_calls_total{{task=\"{}\",model=\"{}\"}} {}\n", task, model, calls));
+        }
+        out.push_str("# TYPE steer_llm_task_tokens_total counter\n");
+        for ((task, model), (_, tokens)) in usage.iter() {
+            out.push_str(&format!("steer_llm_task_tokens_total{{task=\"{}\",model=\"{}\"}} {}\n", task, model, tokens));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_blocks_once_spent() {
+        record_usage(&serde_json::json!({ "usage": { "total_tokens": 5 } }));
+        assert!(check_against(None).is_ok());
+        assert!(check_against(Some(u64::MAX)).is_ok());
+
+        let err = check_against(Some(1)).unwrap_err();
+        assert!(err.used >= 5);
+        assert_eq!(err.limit, 1);
+    }
+
+    #[test]
+    fn test_task_usage_is_labelled_with_model() {
+        let task = format!("test_{}", uuid::Uuid::new_v4().simple());
+        record_task_usage(&task, "gpt-4o-mini", &serde_json::json!({ "usage": { "total_tokens": 40 } }));
+        record_task_usage(&task, "gpt-4o-mini", &serde_json::json!({ "usage": { "total_tokens": 2 } }));
+        let metrics = render_metrics();
+        let labels = format!("{{task=\"{}\",model=\"gpt-4o-mini\"}}", task);
+        assert!(metrics.contains(&format!("steer_llm_task_calls_total{} 2\n", labels)));
+        assert!(metrics.contains(&format!("steer_llm_task_tokens_total{} 42\n", labels)));
+    }
+}
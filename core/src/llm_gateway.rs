@@ -4,15 +4,70 @@ use serde_json::{json, Value};
 use serde::{Serialize, Deserialize};
 use std::env;
 use crate::recommendation::AutomationProposal;
-use crate::context_pruning;
+use crate::{context_pruning, llm_budget, llm_pacing};
+use sha2::{Digest, Sha256};
 
 #[derive(Clone)]
 pub struct LLMClient {
     client: Client,
     api_key: String,
-    model: String,
+    session_key: Option<String>,
 }
 
+/// Kinds of LLM work, each with its own model: vision and planning need a
+/// strong model, intent parsing and recommendation wording a cheap one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmTask {
+    Planning,
+    Vision,
+    Routine,
+    Workflow,
+    Quality,
+    Chat,
+    Recommendation,
+    Embedding,
+}
+
+impl LlmTask {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Planning => "planning",
+            Self::Vision => "vision",
+            Self::Routine => "routine",
+            Self::Workflow => "workflow",
+            Self::Quality => "quality",
+            Self::Chat => "chat",
+            Self::Recommendation => "recommendation",
+            Self::Embedding => "embedding",
+        }
+    }
+
+    fn default_model(&self) -> &'static str {
+        match self {
+            Self::Chat | Self::Recommendation => "gpt-4o-mini",
+            Self::Embedding => "text-embedding-3-small",
+            _ => "gpt-4o",
+        }
+    }
+}
+
+/// Model for `task`: `STEER_LLM_MODEL_<TASK>`, else `STEER_LLM_MODEL`, else the
+/// task's built-in default. Embeddings never take the chat-model default.
+pub fn model_for(task: LlmTask) -> String {
+    resolve_model(task, |key| env::var(key).ok())
+}
+
+fn resolve_model(task: LlmTask, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let configured = |key: &str| lookup(key).map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    configured(&format!("STEER_LLM_MODEL_{}", task.name().to_uppercase()))
+        .or_else(|| if task == LlmTask::Embedding { None } else { configured("STEER_LLM_MODEL") })
+        .unwrap_or_else(|| task.default_model().to_string())
+}
+
+
+/// Error of every call made by an `offline` client.
+pub const LITE_MODE: &str = "No LLM configured (lite mode): set OPENAI_API_KEY";
+
 impl LLMClient {
     pub fn new() -> Result<Self> {
         dotenv::dotenv().ok(); // Load .env
@@ -24,10 +79,97 @@ impl LLMClient {
         Ok(Self {
             client,
             api_key,
-            model: "gpt-4o".to_string(), // Use a smart model for planning
+            session_key: None,
         })
     }
 
+    /// A client with no provider, for lite mode: every call fails with
+    /// `LITE_MODE` without touching the network (e.g. replaying a stored
+    /// routine plan, whose steps mostly need no LLM).
+    pub fn offline() -> Self {
+        Self {
+            client: Client::new(),
+            api_key: String::new(),
+            session_key: None,
+        }
+    }
+
+    /// Authorized POST to the provider; fails without an API key or once the
+    /// daily token budget is spent (`llm_budget::BudgetExceeded`).
+    fn post(&self, url: &str) -> Result<reqwest::RequestBuilder> {
+        if self.api_key.is_empty() {
+            return Err(anyhow::anyhow!(LITE_MODE));
+        }
+        llm_budget::check()?;
+        Ok(self.client.post(url).bearer_auth(&self.api_key))
+    }
+
+    /// Tag logged LLM calls with a session (e.g. one goal run) so they can be listed together.
+    pub fn with_session(mut self, session_key: &str) -> Self {
+        self.session_key = Some(session_key.to_string());
+        self
+    }
+
+    /// Charge a response to the daily budget and to `task`'s usage on
+    /// `/metrics`, and log the call as `purpose`. Every provider call goes
+    /// through here, so the cap and the call log see all of them.
+    fn charge(&self, task: LlmTask, purpose: &str, request: &Value, response: &Value, started: std::time::Instant) {
+        llm_budget::record_usage(response);
+        llm_budget::record_task_usage(task.name(), request["model"].as_str().unwrap_or("unknown"), response);
+        self.record_call(purpose, request, response, started);
+    }
+
+    /// Persist a call to `llm_calls` when `STEER_LLM_CALL_LOG` is enabled.
+    /// Only a hash of the prompt is kept unless `STEER_LLM_CALL_LOG_VERBOSE` is set.
+    fn record_call(&self, purpose: &str, request: &Value, response: &Value, started: std::time::Instant) {
+        if !env_flag("STEER_LLM_CALL_LOG") {
+            return;
+        }
+        // Chat messages, embedding input or a local model's prompt
+        let prompt = ["messages", "input", "prompt"]
+            .iter()
+            .find_map(|k| request.get(*k))
+            .map(|p| p.to_string())
+            .unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(prompt.as_bytes());
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .or_else(|| response["response"].as_str())
+            .map(|c| c.chars().take(2000).collect::<String>())
+            .or_else(|| response.get("error").map(|e| e.to_string()));
+
+        let call = crate::db::LlmCall {
+            session_key: self.session_key.clone(),
+            purpose: purpose.to_string(),
+            model: request["model"].as_str().unwrap_or("unknown").to_string(),
+            prompt_hash: format!("{:x}", hasher.finalize()),
+            prompt: env_flag("STEER_LLM_CALL_LOG_VERBOSE").then_some(prompt),
+            tokens: response["usage"]["total_tokens"].as_i64(),
+            latency_ms: started.elapsed().as_millis() as i64,
+            response: content,
+            ..Default::default()
+        };
+        if let Err(e) = crate::db::insert_llm_call(&call) {
+            eprintln!("⚠️ Failed to log LLM call: {}", e);
+        }
+    }
+
+    /// Cheap reachability check against the provider (used by /health).
+    pub async fn ping(&self, timeout: std::time::Duration) -> bool {
+        if self.api_key.is_empty() {
+            return false;
+        }
+        self.client
+            .get("https://api.openai.com/v1/models")
+            .bearer_auth(&self.api_key)
+            .timeout(timeout)
+            .send()
+            .await
+            .map(|r| r.status().is_success())
+            .unwrap_or(false)
+    }
+
     #[allow(dead_code)]
     pub async fn plan_next_step(&self, goal: &str, ui_tree: &Value, action_history: &[String]) -> Result<Value> {
         let system_prompt = r#"
@@ -73,7 +215,7 @@ Output ONLY valid JSON.
         );
 
         let request_body = json!({
-            "model": &self.model,
+            "model": model_for(LlmTask::Planning),
             "messages": [
                 { "role": "system", "content": system_prompt },
                 { "role": "user", "content": user_msg }
@@ -82,8 +224,8 @@ Output ONLY valid JSON.
             "temperature": 0.0
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&request_body)
             .send()
             .await?;
@@ -94,6 +236,7 @@ Output ONLY valid JSON.
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Planning, "plan_next_step", &request_body, &body, started);
         let content_str = body["choices"][0]["message"]["content"].as_str()
             .ok_or_else(|| anyhow::anyhow!("No content in LLM response"))?;
             
@@ -124,20 +267,21 @@ Output ONLY valid JSON.
         );
 
         let body = json!({
-            "model": self.model,
+            "model": model_for(LlmTask::Routine),
             "messages": [
                 {"role": "system", "content": "You are a helpful assistant that analyzes user behavior patterns."},
                 {"role": "user", "content": prompt}
             ]
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Routine, "analyze_routine", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("No analysis generated.")
@@ -146,6 +290,64 @@ Output ONLY valid JSON.
         Ok(content)
     }
 
+    /// End-of-day report from captured activity plus today's calendar. Returns the raw JSON object.
+    pub async fn summarize_day(&self, logs: &[String], calendar: &[(String, String, String)]) -> Result<Value> {
+        let sample = if logs.len() > 100 {
+            let mut s = logs[0..50].to_vec();
+            s.extend_from_slice(&logs[logs.len()-50..]);
+            s
+        } else {
+            logs.to_vec()
+        };
+        let calendar_text = if calendar.is_empty() {
+            "(no calendar data)".to_string()
+        } else {
+            calendar.iter()
+                .map(|(_, title, start)| format!("- {} at {}", title, start))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let system_prompt = r#"
+You write a short end-of-day report for the user from their activity logs and calendar.
+Output ONLY a JSON object:
+{
+  "headline": "one sentence describing the day",
+  "highlights": ["what the user mainly worked on"],
+  "meetings": ["meeting title - short note"],
+  "suggestions": ["one concrete suggestion for tomorrow"]
+}
+"#;
+        let user_msg = format!("CALENDAR (today):\n{}\n\nACTIVITY LOGS:\n{}", calendar_text, sample.join("\n"));
+
+        let request_body = json!({
+            "model": model_for(LlmTask::Routine),
+            "messages": [
+                { "role": "system", "content": system_prompt },
+                { "role": "user", "content": user_msg }
+            ],
+            "temperature": 0.3,
+            "response_format": { "type": "json_object" }
+        });
+
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Day Summary API Error: {}", error_text));
+        }
+
+        let body: Value = response.json().await?;
+        self.charge(LlmTask::Routine, "summarize_day", &request_body, &body, started);
+        let content = body["choices"][0]["message"]["content"].as_str()
+            .ok_or_else(|| anyhow::anyhow!("No content"))?;
+        Ok(serde_json::from_str(content)?)
+    }
+
     pub async fn recommend_automation(&self, logs: &[String]) -> Result<String> {
         if logs.is_empty() {
             return Ok("No data to assist recommendation.".to_string());
@@ -180,20 +382,21 @@ Output ONLY valid JSON.
         );
 
         let body = json!({
-            "model": self.model,
+            "model": model_for(LlmTask::Routine),
             "messages": [
                 {"role": "system", "content": "You are a pragmatic automation engineer. You write safe, effective scripts."},
                 {"role": "user", "content": prompt}
             ]
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Routine, "recommend_automation", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("No recommendation generated.")
@@ -244,20 +447,21 @@ You are an expert n8n Workflow Architect. Generate VALID, EXECUTABLE n8n workflo
         let system_prompt = format!("{}\n{}\n\nNow generate a workflow for the user request. Output ONLY the JSON.", base_prompt, dynamic_context);
 
         let body = json!({
-            "model": self.model,
+            "model": model_for(LlmTask::Workflow),
             "messages": [
                 {"role": "system", "content": system_prompt},
                 {"role": "user", "content": user_prompt}
             ]
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Workflow, "build_n8n_workflow", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("{}")
@@ -291,20 +495,21 @@ Now output the CORRECTED JSON.
 "##, user_prompt, error_msg);
 
         let body = json!({
-            "model": self.model,
+            "model": model_for(LlmTask::Workflow),
             "messages": [
                 {"role": "system", "content": system_prompt},
                 {"role": "user", "content": bad_json}
             ]
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Workflow, "fix_n8n_workflow", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("{}")
@@ -317,7 +522,7 @@ Now output the CORRECTED JSON.
     /// Analyze screen content using Vision API
     pub async fn analyze_screen(&self, prompt: &str, image_b64: &str) -> Result<String, Box<dyn std::error::Error>> {
         let body = json!({
-            "model": "gpt-4o", 
+            "model": model_for(LlmTask::Vision),
             "messages": [
                 {
                     "role": "user",
@@ -335,13 +540,15 @@ Now output the CORRECTED JSON.
             "max_tokens": 500
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        llm_pacing::pace_vision(body["model"].as_str().unwrap_or_default()).await;
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Vision, "analyze_screen", &body, &res_json, started);
         
         if let Some(err) = res_json.get("error") {
             return Err(anyhow::anyhow!("OpenAI API Error: {:?}", err).into());
@@ -355,9 +562,55 @@ Now output the CORRECTED JSON.
         Ok(content)
     }
 
+    /// Same as [`Self::analyze_screen`] but over text already taken from the
+    /// page, e.g. a browser tab's `innerText`.
+    pub async fn analyze_page_text(&self, prompt: &str, page_text: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let body = json!({
+            "model": model_for(LlmTask::Vision),
+            "messages": [
+                { "role": "system", "content": "You read the text of the user's current web page. Answer only from that text." },
+                { "role": "user", "content": format!("{}\n\n--- PAGE TEXT ---\n{}", prompt, page_text) }
+            ],
+            "max_tokens": 500
+        });
+
+        llm_pacing::pace_vision(body["model"].as_str().unwrap_or_default()).await;
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
+            .json(&body)
+            .send()
+            .await?;
+
+        let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Vision, "analyze_page_text", &body, &res_json, started);
+
+        if let Some(err) = res_json.get("error") {
+            return Err(anyhow::anyhow!("OpenAI API Error: {:?}", err).into());
+        }
+
+        Ok(res_json["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string())
+    }
+
+    /// Vision call that also reports how sure the model is, so a guess can be
+    /// told apart from a reading. See [`ScreenReading`].
+    pub async fn analyze_screen_structured(&self, prompt: &str, image_b64: &str) -> Result<ScreenReading, Box<dyn std::error::Error>> {
+        let full_prompt = format!(
+            "{}\n\nReply ONLY with a JSON object: \
+             {{\"text\": <your answer>, \"confidence\": <0.0-1.0, how sure you are>, \
+             \"not_found\": <true if what was asked about is not visible>, \
+             \"bounds\": <[x, y, width, height] in screenshot pixels of what you read, or null>}}",
+            prompt
+        );
+        let reply = self.analyze_screen(&full_prompt, image_b64).await?;
+        Ok(ScreenReading::parse(&reply))
+    }
+
     pub async fn score_quality(&self, system_prompt: &str, payload: &serde_json::Value) -> Result<String> {
         let body = json!({
-            "model": "gpt-4o",
+            "model": model_for(LlmTask::Quality),
             "messages": [
                 { "role": "system", "content": system_prompt },
                 { "role": "user", "content": payload.to_string() }
@@ -366,8 +619,8 @@ Now output the CORRECTED JSON.
             "response_format": { "type": "json_object" }
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
@@ -377,7 +630,9 @@ Now output the CORRECTED JSON.
             return Err(anyhow::anyhow!("Quality scoring API Error: {}", error_text));
         }
 
+        let request_body = body;
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Quality, "score_quality", &request_body, &body, started);
         let content = body["choices"][0]["message"]["content"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("No content in quality scoring response"))?;
@@ -501,7 +756,7 @@ Your goal is to detect Repetitive Manual Work (Toil) from user logs and propose
         );
 
         let body = json!({
-            "model": self.model,
+            "model": model_for(LlmTask::Routine),
             "messages": [
                 {"role": "system", "content": system_prompt},
                 {"role": "user", "content": prompt}
@@ -510,8 +765,8 @@ Your goal is to detect Repetitive Manual Work (Toil) from user logs and propose
             "response_format": { "type": "json_object" }
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
@@ -522,6 +777,7 @@ Your goal is to detect Repetitive Manual Work (Toil) from user logs and propose
         }
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Routine, "propose_workflow", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("{}");
@@ -551,7 +807,7 @@ Output format: Just the intent description in 1-2 sentences.
         let user_msg = format!("LOGS:\n{}", log_text);
 
         let request_body = json!({
-            "model": "gpt-4o", // Strong model for reasoning
+            "model": model_for(LlmTask::Planning),
             "messages": [
                 { "role": "system", "content": system_prompt },
                 { "role": "user", "content": user_msg }
@@ -559,8 +815,8 @@ Output format: Just the intent description in 1-2 sentences.
             "temperature": 0.3
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&request_body)
             .send()
             .await?;
@@ -571,6 +827,7 @@ Output format: Just the intent description in 1-2 sentences.
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Planning, "analyze_tendency", &request_body, &body, started);
         let content = body["choices"][0]["message"]["content"].as_str()
             .ok_or_else(|| anyhow::anyhow!("No content"))?;
             
@@ -597,6 +854,7 @@ Available commands:
 - notion_create: Create notion page. Params: title, content
 - build_workflow: Create n8n automation. Params: description
 - create_routine: Schedule recurring task. Params: cron (CRON format e.g., '0 9 * * *'), prompt (instruction), name (short title)
+- run_routine: Run a saved routine now. Params: id (number, preferred) or name
 - system_status: Show system status. No params.
 - help: Show help. No params.
 - unknown: Cannot parse. Params: original_text
@@ -626,14 +884,14 @@ Return JSON only:
         messages.push(json!({ "role": "user", "content": user_input }));
 
         let request_body = json!({
-            "model": "gpt-4o-mini",
+            "model": model_for(LlmTask::Chat),
             "messages": messages,
             "temperature": 0.1,
             "response_format": { "type": "json_object" }
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&request_body)
             .send()
             .await?;
@@ -644,6 +902,7 @@ Return JSON only:
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Chat, "parse_intent", &request_body, &body, started);
         let content = body["choices"][0]["message"]["content"].as_str()
             .ok_or_else(|| anyhow::anyhow!("No content"))?;
             
@@ -654,13 +913,13 @@ Return JSON only:
     /// Generate embeddings for RAG
     pub async fn get_embedding(&self, text: &str) -> Result<Vec<f32>> {
         let request_body = json!({
-            "model": "text-embedding-3-small",
+            "model": model_for(LlmTask::Embedding),
             "input": text,
             //"dimensions": 1536 // Default
         });
 
-        let response = self.client.post("https://api.openai.com/v1/embeddings")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/embeddings")?
             .json(&request_body)
             .send()
             .await?;
@@ -671,6 +930,7 @@ Return JSON only:
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Embedding, "get_embedding", &request_body, &body, started);
         let vector = body["data"][0]["embedding"].as_array()
             .ok_or_else(|| anyhow::anyhow!("Invalid embedding response"))?
             .iter()
@@ -709,7 +969,7 @@ Guidelines:
         );
 
         let request_body = json!({
-            "model": "gpt-4o-mini",
+            "model": model_for(LlmTask::Recommendation),
             "messages": [
                 { "role": "system", "content": system_prompt },
                 { "role": "user", "content": user_msg }
@@ -718,8 +978,8 @@ Guidelines:
             "response_format": { "type": "json_object" }
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&request_body)
             .send()
             .await?;
@@ -730,6 +990,7 @@ Guidelines:
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Recommendation, "generate_recommendation_from_pattern", &request_body, &body, started);
         let content = body["choices"][0]["message"]["content"].as_str()
             .ok_or_else(|| anyhow::anyhow!("No content"))?;
             
@@ -766,7 +1027,7 @@ Guidelines:
         );
 
         let body = json!({
-            "model": "gpt-4o",
+            "model": model_for(LlmTask::Planning),
             "messages": [
                 { "role": "system", "content": "You are a Solution Architect. Propose the best stack for the user's goal." },
                 { "role": "user", "content": prompt }
@@ -774,13 +1035,14 @@ Guidelines:
             "response_format": { "type": "json_object" }
         });
 
-        let res = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let res = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&body)
             .send()
             .await?;
 
         let res_json: serde_json::Value = res.json().await?;
+        self.charge(LlmTask::Planning, "propose_solution_stack", &body, &res_json, started);
         let content = res_json["choices"][0]["message"]["content"]
             .as_str()
             .unwrap_or("{}");
@@ -802,6 +1064,7 @@ Guidelines:
         // Default Ollama local URL
         let url = "http://localhost:11434/api/generate";
 
+        let started = std::time::Instant::now();
         let res = self.client.post(url)
             .json(&body)
             .send()
@@ -815,6 +1078,8 @@ Guidelines:
                 }
                 
                 let val: Value = response.json().await?;
+                // Local: nothing to charge to the budget, but the call is logged
+                self.record_call("inference_local", &body, &val, started);
                 let content = val["response"].as_str().unwrap_or("").to_string();
                 Ok(content)
             },
@@ -845,6 +1110,67 @@ Guidelines:
         (true, "llama3".to_string())
     }
 
+    /// A few sentences on what an email says and what, if anything, it asks of the reader.
+    pub async fn summarize_email(&self, email: &str) -> Result<String> {
+        let request_body = json!({
+            "model": model_for(LlmTask::Chat),
+            "messages": [
+                { "role": "system", "content": "Summarize the email in 2-4 sentences for a personal archive: who wrote, what it is about, and any request, deadline or amount. Answer in the email's language. No preamble." },
+                { "role": "user", "content": email }
+            ],
+            "temperature": 0.2
+        });
+
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Email summary error: {}", error_text));
+        }
+
+        let body: Value = response.json().await?;
+        self.charge(LlmTask::Chat, "summarize_email", &request_body, &body, started);
+        body["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("No summary generated"))
+    }
+
+    /// Second opinion on one risky step; returns the raw JSON verdict (see `supervisor::parse_verdict`).
+    pub async fn review_step(&self, proposal: &str) -> Result<String> {
+        let request_body = json!({
+            "model": model_for(LlmTask::Planning),
+            "messages": [
+                { "role": "system", "content": "You are the supervisor of a desktop automation agent. You see the user's goal and the single step the agent is about to run. Reply with JSON only: {\"verdict\": \"accept\" | \"review\" | \"escalate\", \"reason\": \"...\"}. accept: the step clearly serves the goal and its effect is what the user wants. review: the step is wrong or riskier than needed; say in reason what the agent should do instead. escalate: the step may destroy data, send something or cannot be undone, and the goal does not clearly ask for that; reason is shown to the user." },
+                { "role": "user", "content": proposal }
+            ],
+            "temperature": 0.0
+        });
+
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Supervisor error: {}", error_text));
+        }
+
+        let body: Value = response.json().await?;
+        self.charge(LlmTask::Planning, "review_step", &request_body, &body, started);
+        body["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|c| c.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("No supervisor verdict"))
+    }
+
     pub async fn analyze_user_feedback(&self, feedback: &str, history_summary: &str) -> Result<FeedbackAnalysis> {
         let system_prompt = r#"
 You are a product assistant. Analyze user feedback and decide whether to refine the goal.
@@ -862,7 +1188,7 @@ Guidelines:
         let user_msg = format!("History: {}\nUser feedback: {}", history_summary, feedback);
 
         let request_body = json!({
-            "model": "gpt-4o-mini",
+            "model": model_for(LlmTask::Chat),
             "messages": [
                 { "role": "system", "content": system_prompt },
                 { "role": "user", "content": user_msg }
@@ -871,8 +1197,8 @@ Guidelines:
             "response_format": { "type": "json_object" }
         });
 
-        let response = self.client.post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+        let started = std::time::Instant::now();
+        let response = self.post("https://api.openai.com/v1/chat/completions")?
             .json(&request_body)
             .send()
             .await?;
@@ -883,6 +1209,7 @@ Guidelines:
         }
 
         let body: Value = response.json().await?;
+        self.charge(LlmTask::Chat, "analyze_user_feedback", &request_body, &body, started);
         let content = body["choices"][0]["message"]["content"].as_str()
             .ok_or_else(|| anyhow::anyhow!("No content"))?;
         let parsed: Value = serde_json::from_str(content)?;
@@ -899,3 +1226,224 @@ pub struct FeedbackAnalysis {
     pub action: String,
     pub new_goal: Option<String>,
 }
+
+/// Result of [`LLMClient::analyze_screen_structured`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreenReading {
+    pub text: String,
+    pub confidence: f64,
+    #[serde(default)]
+    pub not_found: bool,
+    /// `[x, y, width, height]` of the region the answer was read from.
+    #[serde(default)]
+    pub bounds: Option<[f64; 4]>,
+}
+
+impl ScreenReading {
+    /// Parse a model reply. A reply that isn't the requested JSON keeps its
+    /// text but gets zero confidence, so callers treat it as a guess.
+    pub fn parse(reply: &str) -> Self {
+        let json = reply
+            .find('{')
+            .zip(reply.rfind('}'))
+            .filter(|(start, end)| start < end)
+            .and_then(|(start, end)| serde_json::from_str::<Value>(&reply[start..=end]).ok());
+        let Some(json) = json else {
+            return Self { text: reply.trim().to_string(), confidence: 0.0, not_found: false, bounds: None };
+        };
+        let text = match &json["text"] {
+            Value::String(s) => s.trim().to_string(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        Self {
+            text,
+            confidence: json["confidence"].as_f64().unwrap_or(0.0).clamp(0.0, 1.0),
+            not_found: json["not_found"].as_bool().unwrap_or(false),
+            bounds: serde_json::from_value(json["bounds"].clone()).ok(),
+        }
+    }
+
+    /// Found, non-empty and at least as confident as `STEER_VISION_MIN_CONFIDENCE`.
+    pub fn is_reliable(&self) -> bool {
+        !self.not_found && !self.text.is_empty() && self.confidence >= min_vision_confidence()
+    }
+}
+
+pub fn min_vision_confidence() -> f64 {
+    env::var("STEER_VISION_MIN_CONFIDENCE")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0.6)
+}
+
+/// Call `read` until it returns a reliable reading, up to `attempts` times.
+/// An unreliable last reading is an error rather than an answer.
+pub async fn read_reliably<F, Fut>(attempts: u32, read: F) -> Result<ScreenReading>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: std::future::Future<Output = Result<ScreenReading>>,
+{
+    read_until(attempts, ScreenReading::is_reliable, read).await
+}
+
+/// `read_reliably` for a yes/no question: "not found" is a confident no
+/// (the text asked about isn't on screen), not a reason to ask again.
+pub async fn answer_reliably<F, Fut>(attempts: u32, read: F) -> Result<bool>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: std::future::Future<Output = Result<ScreenReading>>,
+{
+    let reading = read_until(attempts, |r| r.not_found || r.is_reliable(), read).await?;
+    Ok(!reading.not_found && reading.text.trim().to_uppercase().starts_with("YES"))
+}
+
+async fn read_until<F, Fut>(attempts: u32, accept: impl Fn(&ScreenReading) -> bool, mut read: F) -> Result<ScreenReading>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: std::future::Future<Output = Result<ScreenReading>>,
+{
+    let mut last = None;
+    for attempt in 1..=attempts.max(1) {
+        let reading = read(attempt).await?;
+        if accept(&reading) {
+            return Ok(reading);
+        }
+        println!(
+            "      🤔 Unreliable vision reading (confidence {:.2}{}), attempt {}/{}",
+            reading.confidence,
+            if reading.not_found { ", not found" } else { "" },
+            attempt,
+            attempts.max(1)
+        );
+        last = Some(reading);
+    }
+    let last = last.expect("at least one attempt");
+    if last.not_found {
+        Err(anyhow::anyhow!("Vision could not find what was asked about"))
+    } else {
+        Err(anyhow::anyhow!("Vision reading not reliable (confidence {:.2}): {}", last.confidence, last.text))
+    }
+}
+
+fn env_flag(key: &str) -> bool {
+    env::var(key)
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_provider_call_is_charged_and_logged() {
+        // Split so these literals don't count themselves
+        let source = include_str!("llm_gateway.rs");
+        let posts = source.matches(concat!("self.", "post(\"https://")).count();
+        let charged = source.matches(concat!("self.", "charge(LlmTask::")).count();
+        assert!(posts > 0);
+        assert_eq!(posts, charged, "a provider call skips charge (budget, task usage and call log)");
+    }
+
+    #[test]
+    fn test_each_task_resolves_to_its_model() {
+        let config = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        };
+
+        let none = config(&[]);
+        assert_eq!(resolve_model(LlmTask::Vision, none), "gpt-4o");
+        assert_eq!(resolve_model(LlmTask::Chat, config(&[])), "gpt-4o-mini");
+
+        let configured = config(&[
+            ("STEER_LLM_MODEL_VISION", "gpt-4o-mini"),
+            ("STEER_LLM_MODEL_WORKFLOW", " o3 "),
+            ("STEER_LLM_MODEL_CHAT", ""),
+            ("STEER_LLM_MODEL", "gpt-4.1"),
+        ]);
+        let resolved: Vec<(&str, String)> = [
+            LlmTask::Planning,
+            LlmTask::Vision,
+            LlmTask::Routine,
+            LlmTask::Workflow,
+            LlmTask::Quality,
+            LlmTask::Chat,
+            LlmTask::Recommendation,
+            LlmTask::Embedding,
+        ]
+        .iter()
+        .map(|t| (t.name(), resolve_model(*t, configured)))
+        .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                ("planning", "gpt-4.1".to_string()),
+                ("vision", "gpt-4o-mini".to_string()),
+                ("routine", "gpt-4.1".to_string()),
+                ("workflow", "o3".to_string()),
+                ("quality", "gpt-4.1".to_string()),
+                ("chat", "gpt-4.1".to_string()),
+                ("recommendation", "gpt-4.1".to_string()),
+                ("embedding", "text-embedding-3-small".to_string()),
+            ]
+        );
+    }
+
+    /// Stands in for the vision provider: replies from a fixed script.
+    fn mock_provider(replies: &[&str]) -> impl FnMut(u32) -> std::future::Ready<Result<ScreenReading>> {
+        let replies: Vec<String> = replies.iter().map(|r| r.to_string()).collect();
+        move |attempt| std::future::ready(Ok(ScreenReading::parse(&replies[attempt as usize - 1])))
+    }
+
+    #[test]
+    fn test_parse_screen_reading() {
+        let reading = ScreenReading::parse(
+            "```json\n{\"text\": \"$1,204.50\", \"confidence\": 0.92, \"not_found\": false, \"bounds\": [10, 20, 80, 14]}\n```",
+        );
+        assert_eq!(reading.text, "$1,204.50");
+        assert_eq!(reading.bounds, Some([10.0, 20.0, 80.0, 14.0]));
+        assert!(reading.is_reliable());
+
+        let prose = ScreenReading::parse("Probably 42");
+        assert_eq!(prose.text, "Probably 42");
+        assert!(!prose.is_reliable());
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_is_retried_not_accepted() {
+        let low = r#"{"text": "1204", "confidence": 0.2, "not_found": false}"#;
+        let sure = r#"{"text": "1,204.50", "confidence": 0.9, "not_found": false}"#;
+        let missing = r#"{"text": "", "confidence": 0.9, "not_found": true}"#;
+
+        let reading = read_reliably(2, mock_provider(&[low, sure])).await.unwrap();
+        assert_eq!(reading.text, "1,204.50");
+
+        let err = read_reliably(2, mock_provider(&[low, low])).await.unwrap_err();
+        assert!(err.to_string().contains("not reliable"));
+
+        let err = read_reliably(1, mock_provider(&[missing])).await.unwrap_err();
+        assert!(err.to_string().contains("could not find"));
+    }
+
+    #[tokio::test]
+    async fn test_not_found_answers_a_yes_no_question_with_no() {
+        let missing = r#"{"text": "", "confidence": 0.9, "not_found": true}"#;
+        let low = r#"{"text": "YES", "confidence": 0.2, "not_found": false}"#;
+        let yes = r#"{"text": "YES", "confidence": 0.9, "not_found": false}"#;
+
+        // Answered on the first reading, without a second capture
+        let mut calls = 0;
+        let answer = answer_reliably(2, |attempt| {
+            calls += 1;
+            mock_provider(&[missing, yes])(attempt)
+        })
+        .await
+        .unwrap();
+        assert!(!answer);
+        assert_eq!(calls, 1);
+
+        assert!(answer_reliably(2, mock_provider(&[low, yes])).await.unwrap());
+        assert!(answer_reliably(2, mock_provider(&[low, low])).await.is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// [LLM Pacing] Back-to-back steps can send vision calls faster than the
+// provider's rate limit allows, and the 429s that follow only cost time. A
+// minimum interval between vision calls, per model, makes the agent pace
+// itself instead: each call reserves the next free slot for its model and
+// waits for it, so concurrent callers queue up rather than burst (a token
+// bucket holding one token).
+//
+//   STEER_VISION_MIN_INTERVAL_MS            gap between vision calls on any model (default 0 = off)
+//   STEER_VISION_MIN_INTERVAL_MS_<MODEL>    for one model, e.g. ..._GPT_4O_MINI (non-alphanumerics become `_`)
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct PacingStats {
+    interval_ms: u64,
+    waits: u64,
+    waited_ms: u64,
+}
+
+#[derive(Default)]
+pub struct Pacer {
+    /// model → when its next call may start
+    next_slot: Mutex<HashMap<String, Instant>>,
+    stats: Mutex<BTreeMap<String, PacingStats>>,
+}
+
+impl Pacer {
+    /// Take the next slot for `model` and return how long to wait for it.
+    fn reserve(&self, model: &str, interval: Duration, now: Instant) -> Duration {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = next_slot.get(model).copied().filter(|s| *s > now).unwrap_or(now);
+        next_slot.insert(model.to_string(), slot + interval);
+        let wait = slot - now;
+
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats.entry(model.to_string()).or_default();
+        entry.interval_ms = interval.as_millis() as u64;
+        if !wait.is_zero() {
+            entry.waits += 1;
+            entry.waited_ms += wait.as_millis() as u64;
+        }
+        wait
+    }
+
+    /// Wait until a call on `model` keeps `interval` from the previous one.
+    pub async fn pace(&self, model: &str, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+        let wait = self.reserve(model, interval, Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn render_metrics(&self) -> String {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        if stats.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("# TYPE steer_llm_vision_min_interval_ms gauge\n");
+        for (model, s) in stats.iter() {
+            out.push_str(&format!("steer_llm_vision_min_interval_ms{{model=\"{}\"}} {}\n", model, s.interval_ms));
+        }
+        out.push_str("# TYPE steer_llm_vision_paced_calls_total counter\n");
+        for (model, s) in stats.iter() {
+            out.push_str(&format!("steer_llm_vision_paced_calls_total{{model=\"{}\"}} {}\n", model, s.waits));
+        }
+        out.push_str("# TYPE steer_llm_vision_pacing_wait_ms_total counter\n");
+        for (model, s) in stats.iter() {
+            out.push_str(&format!("steer_llm_vision_pacing_wait_ms_total{{model=\"{}\"}} {}\n", model, s.waited_ms));
+        }
+        out
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref VISION: Pacer = Pacer::default();
+}
+
+/// Minimum gap between vision calls on `model`.
+pub fn vision_interval(model: &str) -> Duration {
+    interval_from(model, |key| std::env::var(key).ok())
+}
+
+fn interval_from(model: &str, lookup: impl Fn(&str) -> Option<String>) -> Duration {
+    let suffix: String = model
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    let ms = |key: &str| lookup(key).and_then(|v| v.trim().parse::<u64>().ok());
+    Duration::from_millis(
+        ms(&format!("STEER_VISION_MIN_INTERVAL_MS_{}", suffix))
+            .or_else(|| ms("STEER_VISION_MIN_INTERVAL_MS"))
+            .unwrap_or(0),
+    )
+}
+
+/// Call before each vision request on `model`.
+pub async fn pace_vision(model: &str) {
+    VISION.pace(model, vision_interval(model)).await;
+}
+
+/// Prometheus text for `/metrics`: the interval in effect and time spent waiting, per model.
+pub fn render_metrics() -> String {
+    VISION.render_metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rapid_calls_are_spaced_by_the_interval() {
+        let pacer = Pacer::default();
+        let interval = Duration::from_millis(80);
+        let started = Instant::now();
+        pacer.pace("gpt-4o", interval).await;
+        let first = started.elapsed();
+        pacer.pace("gpt-4o", interval).await;
+        let second = started.elapsed();
+        assert!(first < interval, "first call waited {:?}", first);
+        assert!(second - first >= interval - Duration::from_millis(1), "calls only {:?} apart", second - first);
+
+        // Each model has its own slot
+        let other = Instant::now();
+        pacer.pace("gpt-4o-mini", interval).await;
+        assert!(other.elapsed() < interval);
+
+        let metrics = pacer.render_metrics();
+        assert!(metrics.contains("steer_llm_vision_min_interval_ms{model=\"gpt-4o\"} 80\n"), "{}", metrics);
+        assert!(metrics.contains("steer_llm_vision_paced_calls_total{model=\"gpt-4o\"} 1\n"), "{}", metrics);
+        assert!(metrics.contains("steer_llm_vision_paced_calls_total{model=\"gpt-4o-mini\"} 0\n"), "{}", metrics);
+    }
+
+    #[test]
+    fn test_reservations_queue_concurrent_callers() {
+        let pacer = Pacer::default();
+        let now = Instant::now();
+        let gap = Duration::from_millis(500);
+        assert_eq!(pacer.reserve("m", gap, now), Duration::ZERO);
+        assert_eq!(pacer.reserve("m", gap, now), gap);
+        assert_eq!(pacer.reserve("m", gap, now), gap * 2);
+        // After the queue drains, calls go straight through again
+        assert_eq!(pacer.reserve("m", gap, now + gap * 4), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_interval_prefers_the_model_setting() {
+        let env = |key: &str| match key {
+            "STEER_VISION_MIN_INTERVAL_MS_GPT_4O_MINI" => Some("250".to_string()),
+            "STEER_VISION_MIN_INTERVAL_MS" => Some("1000".to_string()),
+            _ => None,
+        };
+        assert_eq!(interval_from("gpt-4o-mini", env), Duration::from_millis(250));
+        assert_eq!(interval_from("gpt-4o", env), Duration::from_millis(1000));
+        assert_eq!(interval_from("gpt-4o", |_| None), Duration::ZERO);
+    }
+}
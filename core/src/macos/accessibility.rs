@@ -1,6 +1,6 @@
 use serde_json::{json, Value};
 use accessibility_sys::{
-    AXUIElementCopyAttributeValue, AXUIElementCreateSystemWide, AXUIElementRef,
+    AXIsProcessTrusted, AXUIElementCopyAttributeValue, AXUIElementCreateSystemWide, AXUIElementRef,
 };
 use core_foundation::base::{CFTypeRef, TCFType};
 use core_foundation::string::CFString;
@@ -35,6 +35,22 @@ impl Drop for AxElement {
     }
 }
 
+/// Whether this process has been granted Accessibility permission.
+pub fn is_trusted() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+/// Whether this process may capture the screen (Screen Recording permission).
+/// Only checks; never shows the system prompt.
+pub fn screen_capture_allowed() -> bool {
+    unsafe { CGPreflightScreenCaptureAccess() }
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+}
+
 pub fn snapshot(_scope: Option<String>) -> Value {
     println!("[MacOS] Capturing Snapshot (Native)...");
 
@@ -2,14 +2,21 @@ use core_graphics::event::{CGEventTap, CGEventType, CGEventTapLocation, CGEventT
 use std::thread;
 use tokio::sync::mpsc;
 use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
-use chrono::Utc;
 use serde_json::json;
-use uuid::Uuid;
-use crate::schema::{EventEnvelope, ResourceContext};
+use crate::schema::{Backlog, EmitError, Emitted, EventEnvelope, ResourceContext};
 
 // Hardcoded for MVP to avoid crate version mismatches
 // kCGKeyboardEventKeycode = 9
 const KEYCODE_FIELD: u32 = 9;
+// kCGEventTargetUnixProcessID = 40, set once events are annotated with their target
+const TARGET_PID_FIELD: u32 = 40;
+// PROC_PIDPATHINFO_MAXSIZE
+const PID_PATH_MAX: usize = 4096;
+
+/// Distinct input events held back while the analyzer channel is full.
+const BACKLOG_CAPACITY: usize = 500;
+/// How often held-back events are retried when no new input arrives.
+const BACKLOG_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
 
 pub fn start_event_tap(tx: mpsc::Sender<String>) -> anyhow::Result<()> {
     println!("[MacOS] Starting Native Event Tap...");
@@ -22,17 +29,37 @@ pub fn start_event_tap(tx: mpsc::Sender<String>) -> anyhow::Result<()> {
             CGEventType::LeftMouseDown,
         ];
 
+        // Bursts wait here (identical ones coalesced) instead of being dropped
+        let backlog = std::sync::Arc::new(std::sync::Mutex::new(Backlog::new(BACKLOG_CAPACITY)));
+        Backlog::flush_periodically(backlog.clone(), tx.clone(), BACKLOG_FLUSH_INTERVAL);
+        let sensitive = crate::monitoring::SensitiveContext::from_env();
+        let last_target = std::sync::atomic::AtomicI32::new(0);
+
+        // Annotated session events carry the app they go to (see TARGET_PID_FIELD)
         let tap_result = CGEventTap::new(
-            CGEventTapLocation::HID,
+            CGEventTapLocation::AnnotatedSession,
             CGEventTapPlacement::HeadInsertEventTap,
             CGEventTapOptions::ListenOnly,
             events,
             move |_proxy, type_, event| {
-                let log_json = match type_ {
+                // [Sensitive Context] Check the app input goes to as soon as it changes,
+                // not at the app watcher's next poll, so the first keystrokes in a
+                // password manager are already suppressed
+                let target = event.get_integer_value_field(TARGET_PID_FIELD) as i32;
+                if target > 0 && last_target.swap(target, std::sync::atomic::Ordering::SeqCst) != target {
+                    if let Some(app) = process_path(target).as_deref().and_then(crate::monitoring::app_name_from_path) {
+                        crate::monitoring::observe_input_app(&sensitive, &app, Some(&tx));
+                    }
+                }
+                if crate::monitoring::is_capture_suppressed() {
+                    return Some(event.to_owned());
+                }
+                let envelope = match type_ {
                     CGEventType::KeyDown | CGEventType::KeyUp => {
                         // CGEventField represents the keycode field index
                         let keycode = event.get_integer_value_field(KEYCODE_FIELD);
-                        let envelope = base_envelope(
+                        let kind = if matches!(type_, CGEventType::KeyDown) { "down" } else { "up" };
+                        EventEnvelope::new(
                             "native_tap",
                             "system",
                             "key_input",
@@ -41,13 +68,12 @@ pub fn start_event_tap(tx: mpsc::Sender<String>) -> anyhow::Result<()> {
                                 resource_type: "input".to_string(),
                                 id: "keyboard".to_string(),
                             }),
-                            json!({ "keycode": keycode }),
-                        );
-                        serde_json::to_value(envelope).unwrap_or_else(|_| json!({}))
+                            json!({ "keycode": keycode, "kind": kind }),
+                        )
                     },
                     CGEventType::LeftMouseDown => {
                         let loc = event.location();
-                        let envelope = base_envelope(
+                        EventEnvelope::new(
                             "native_tap",
                             "system",
                             "click",
@@ -57,23 +83,19 @@ pub fn start_event_tap(tx: mpsc::Sender<String>) -> anyhow::Result<()> {
                                 id: "mouse".to_string(),
                             }),
                             json!({ "location": { "x": loc.x, "y": loc.y } }),
-                        );
-                        serde_json::to_value(envelope).unwrap_or_else(|_| json!({}))
+                        )
                     }
                     _ => return Some(event.to_owned()),
                 };
 
                 // Non-blocking send
-                if let Ok(log) = serde_json::to_string(&log_json) {
-                    if let Err(e) = tx.try_send(log) {
-                        use tokio::sync::mpsc::error::TrySendError;
-                        match e {
-                            TrySendError::Full(_) => eprintln!("⚠️ [MacOS] Event Channel Full! Dropping event (increase buffer?)."),
-                            TrySendError::Closed(_) => eprintln!("⚠️ [MacOS] Event Channel Closed."),
-                        }
-                    }
-                } else {
-                    eprintln!("⚠️ [MacOS] Failed to serialize event; dropping.");
+                let mut backlog = backlog.lock().unwrap_or_else(|e| e.into_inner());
+                match backlog.emit(&tx, &envelope) {
+                    Ok(Emitted::Coalesced) => crate::analyzer::record_coalesced(),
+                    Ok(_) => {}
+                    Err(EmitError::Full) => crate::analyzer::record_dropped("native_tap"),
+                    Err(EmitError::Closed) => eprintln!("⚠️ [MacOS] Event Channel Closed."),
+                    Err(e) => eprintln!("⚠️ [MacOS] {}; dropping.", e),
                 }
 
                 Some(event.to_owned())
@@ -100,34 +122,23 @@ pub fn start_event_tap(tx: mpsc::Sender<String>) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Executable path of process `pid`, if it is still running.
+fn process_path(pid: i32) -> Option<String> {
+    let mut buf = vec![0u8; PID_PATH_MAX];
+    let len = unsafe { proc_pidpath(pid, buf.as_mut_ptr() as *mut std::ffi::c_void, buf.len() as u32) };
+    if len <= 0 {
+        return None;
+    }
+    buf.truncate(len as usize);
+    String::from_utf8(buf).ok()
+}
+
 #[link(name = "CoreFoundation", kind = "framework")]
 extern "C" {
     fn CFRunLoopRun();
 }
 
-fn base_envelope(
-    source: &str,
-    app: &str,
-    event_type: &str,
-    priority: &str,
-    resource: Option<ResourceContext>,
-    payload: serde_json::Value,
-) -> EventEnvelope {
-    EventEnvelope {
-        schema_version: "1.0".to_string(),
-        event_id: Uuid::new_v4().to_string(),
-        ts: Utc::now().to_rfc3339(),
-        source: source.to_string(),
-        app: app.to_string(),
-        event_type: event_type.to_string(),
-        priority: priority.to_string(),
-        resource,
-        payload,
-        privacy: None,
-        pid: None,
-        window_id: None,
-        window_title: None,
-        browser_url: None,
-        raw: None,
-    }
+// libproc, part of libSystem
+extern "C" {
+    fn proc_pidpath(pid: i32, buffer: *mut std::ffi::c_void, buffersize: u32) -> i32;
 }
@@ -2,20 +2,52 @@ mod schema;
 mod session; // Added
 mod policy;
 mod llm_gateway;
+mod llm_budget;
+mod llm_pacing;
 mod analyzer;
 mod db;
 mod notifier;
 mod monitor;
 mod applescript;
+mod clipboard;
+mod click_recovery;
+mod debug_overlay;
 mod n8n_api;
+mod n8n_fallback;
 mod dependency_check;
 mod scheduler;
+mod routine_check;
 mod executor; // Added
+mod agent_error;
+mod step_confirmation;
+mod supervisor;
+mod task_registry;
+mod goal_checklist;
+mod goal_analysis;
+mod action_schema;
+mod plan_validation;
+mod text_input;
+mod observer;
+mod screen_extract;
+mod day_summary;
+mod screen_cache;
+mod screen_describe;
+mod screen_redaction;
+mod selection;
+mod golden;
+mod permissions;
+mod action_budget;
+mod run_state;
+mod app_focus;
+mod screen_access;
+mod session_artifacts;
 mod visual_driver;
 mod integrations;
 mod recommendation;
 mod workflow_schema;
+mod workflow_export;
 mod pattern_detector;
+mod event_clusters;
 mod feedback_collector;
 mod api_server;
 mod orchestrator; // Added
@@ -44,6 +76,14 @@ mod tool_result_guard;
 mod consistency_check;
 mod static_checks;
 mod singleton_lock;
+mod safe_mode;
+mod disabled_actions;
+mod step_delay;
+mod undo_stack;
+mod monitoring;
+mod paths;
+mod prompt_templates;
+mod crash_report;
 mod nl_automation;
 mod intent_router;
 mod slot_filler;
@@ -53,8 +93,13 @@ mod verification_engine;
 mod approval_gate;
 mod nl_store;
 mod browser_automation;
+mod web_search;
+mod ui_snapshot;
+mod reporting;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(target_os = "linux")]
+mod linux;
 
 use crate::schema::{AgentAction, EventEnvelope};
 use chrono::Utc;
@@ -67,43 +112,19 @@ use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt};
 async fn main() -> anyhow::Result<()> {
     // [Self-Healing] Panic Hook
     if !env_flag("STEER_PANIC_STD") {
-        std::panic::set_hook(Box::new(|info| {
-            let backtrace = std::backtrace::Backtrace::capture();
-            let timestamp = chrono::Utc::now().to_rfc3339();
-            
-            let msg = if let Some(s) = info.payload().downcast_ref::<&str>() {
-                *s
-            } else if let Some(s) = info.payload().downcast_ref::<String>() {
-                &**s
-            } else {
-                "Unknown panic"
-            };
-            
-            let location = info.location().map(|l| format!("{}:{}", l.file(), l.line())).unwrap_or_else(|| "unknown".to_string());
-            
-            let log_entry = format!(
-                "[{}] CRASH REPORT\nMessage: {}\nLocation: {}\nBacktrace:\n{:#?}\n--------------------------------------------------\n",
-                timestamp, msg, location, backtrace
-            );
-            
-            // Ensure log directory exists
-            let home = std::env::var("HOME").unwrap_or("/".to_string());
-            let log_dir = std::path::Path::new(&home).join(".steer/logs");
-            if let Ok(_) = std::fs::create_dir_all(&log_dir) {
-                let log_file = log_dir.join("crash.log");
-                if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(log_file) {
-                     use std::io::Write;
-                     let _ = writeln!(file, "{}", log_entry);
-                }
-            }
-            
-            eprintln!("❌ FATAL ERROR: {}", msg);
-            eprintln!("📄 Crash report saved to ~/.steer/logs/crash.log");
-        }));
+        crash_report::install_panic_hook();
     } else {
         eprintln!("⚠️  Panic hook disabled (STEER_PANIC_STD=1).");
     }
 
+    if std::env::args().any(|a| a == "--force-unlock") && singleton_lock::force_unlock() {
+        println!("🔓 Removed existing instance lock (--force-unlock).");
+    }
+
+    if std::env::args().any(|a| a == "--safe-mode") {
+        safe_mode::enable();
+    }
+
     let _lock = match singleton_lock::acquire_lock() {
         Ok(guard) => guard,
         Err(err) => {
@@ -113,19 +134,39 @@ async fn main() -> anyhow::Result<()> {
     };
 
     println!("🤖 Local OS Agent (Rust Native Mode) Started!");
+    if safe_mode::is_enabled() {
+        println!("🛡️  SAFE MODE: monitoring only. Goals, shell commands and UI actions are disabled.");
+    }
+    if monitoring::is_paused() {
+        println!("⏸️  Monitoring is paused (persisted). Nothing is captured until 'resume'.");
+    }
     println!("--------------------------------------------------");
     
     // 0. System Health Check
     let health = dependency_check::SystemHealth::check_all();
     health.print_report();
 
+    let custom_prompts = prompt_templates::reload();
+    if !custom_prompts.is_empty() {
+        println!("📝 Custom prompt templates: {}", custom_prompts.join(", "));
+    }
+
     println!("Type 'help' for commands. (Needs Accessibility Permissions)");
+    let perms = permissions::preflight();
+    for p in perms.permissions.iter().filter(|p| p.state == permissions::PermissionState::Missing) {
+        println!("⚠️  {} permission missing ({} won't work). Grant it in {}", p.name, p.needed_for, p.settings_path);
+    }
     println!("--------------------------------------------------");
 
     // 0. Init Check
     if let Err(e) = db::init() {
         eprintln!("Failed to init DB: {}", e);
     }
+    safe_mode::audit_startup();
+    let expired = session_artifacts::cleanup(session_artifacts::Retention::from_env());
+    if !expired.is_empty() {
+        println!("🧹 Removed artifacts of {} old session(s).", expired.len());
+    }
     
     // 1. Init LLM
     let llm_client = match llm_gateway::LLMClient::new() {
@@ -136,16 +177,30 @@ async fn main() -> anyhow::Result<()> {
         }
     };
     
-    // 2. Start Scheduler (Brain)
-    if let Some(llm) = &llm_client {
-        let scheduler = scheduler::Scheduler::new(llm.clone());
-        scheduler.start();
-        println!("🧠 Brain Routine Scheduler Active.");
-    }
+    // 2. Start Scheduler (Brain); in lite mode it still runs the heuristic pattern job
+    scheduler::Scheduler::new(llm_client.clone()).start();
+    println!("🧠 Brain Routine Scheduler Active.");
+
+    // Expire async exec results nobody picked up
+    let exec_ttl_secs = std::env::var("STEER_EXEC_RESULT_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(3600);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            match db::expire_stale_exec_results(exec_ttl_secs) {
+                Ok(n) if n > 0 => println!("⌛ Expired {} stale exec results", n),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️ Exec result sweep failed: {}", e),
+            }
+        }
+    });
 
     // 1. Start Native Event Tap (replaces IPC Adapter)
     // [Paranoid Audit] Increased capacity to 1000 to prevent dropping mouse bursts
-    let (log_tx, mut log_rx) = tokio::sync::mpsc::channel::<String>(1000);
+    let (log_tx, log_rx) = tokio::sync::mpsc::channel::<String>(1000);
     
     #[cfg(target_os = "macos")]
     {
@@ -157,32 +212,19 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // 2. Start "Shadow Analyzer" (Decoupled Module)
-    // CRITICAL FIX: Always consume log_rx, even without LLM
-    if let Some(c) = llm_client.clone() {
-        let llm_client_ref = std::sync::Arc::new(c);
-        analyzer::spawn(log_rx, llm_client_ref);
-    } else {
-        // Fallback: Just save events to DB without LLM analysis
-        tokio::spawn(async move {
-            while let Some(log_json) = log_rx.recv().await {
-                if let Err(e) = db::insert_event(&log_json) {
-                    eprintln!("DB insert error: {}", e);
-                }
-            }
-        });
-        println!("⚠️  Running in lite mode (no LLM, events still saved)");
+    // Runs without an LLM too (lite mode): storage, sessions and template recommendations
+    analyzer::spawn(log_rx, llm_client.clone().map(std::sync::Arc::new));
+    if llm_client.is_none() {
+        println!("⚠️  Running in lite mode (no LLM). Run 'capabilities' for what still works.");
     }
 
     // 4. Start HTTP API Server for Desktop GUI
     println!("🌐 Starting Desktop API Server...");
     let llm_for_api = llm_client.clone();
-    tokio::spawn(async move {
-        if let Err(e) = api_server::start_api_server(llm_for_api).await {
-            eprintln!("❌ FATAL: Desktop API Server failed to start: {}", e);
-            eprintln!("   (The application cannot function without the API server. Exiting...)");
-            std::process::exit(1);
-        }
-    });
+    if let Err(e) = api_server::start_api_server(llm_for_api, Some(log_tx.clone())).await {
+        eprintln!("❌ Desktop API Server failed to start: {}", e);
+        eprintln!("   (The desktop GUI won't be able to connect; the CLI keeps running.)");
+    }
 
     // 5. Start File Watcher
     // Watch Downloads folder
@@ -196,13 +238,39 @@ async fn main() -> anyhow::Result<()> {
         println!("👀 Watching for changes in {}", downloads);
     }
 
+    // Authenticate Google integrations in the background so the first gmail/calendar command is fast
+    integrations::registry::warm_up();
+
     // 6. Start App Watcher (Active Window Poller)
     monitor::spawn_app_watcher(log_tx.clone());
     println!("👀 Watching for active application changes...");
 
+    // 7. Discord command channel (bot token + allow-listed channel only)
+    if env_flag("DISCORD_COMMANDS_ENABLED") {
+        match integrations::discord::start_command_channel() {
+            Ok(_) => println!("💬 Discord command channel active."),
+            Err(e) => println!("⚠️  Discord command channel not started: {}", e),
+        }
+    }
+    if env_flag("TELEGRAM_POLLING_ENABLED") {
+        match integrations::telegram::start_poller() {
+            Ok(_) => println!("📨 Telegram poller active."),
+            Err(e) => println!("⚠️  Telegram poller not started: {}", e),
+        }
+    }
+
     let mut policy = policy::PolicyEngine::new(); // Starts LOCKED
     let mut res_mon = monitor::ResourceMonitor::new();
 
+    // Orchestrator holds no per-request state, so build it once for the REPL
+    let orchestrator = match orchestrator::Orchestrator::new().await {
+        Ok(orch) => Some(orch),
+        Err(e) => {
+            println!("⚠️  Super Agent unavailable: {}", e);
+            None
+        }
+    };
+
     // 5. User Input Loop (REPL)
     let stdin = io::stdin();
     let mut reader = io::BufReader::new(stdin);
@@ -229,26 +297,62 @@ async fn main() -> anyhow::Result<()> {
             "help" => {
                 println!("Commands:");
                 println!("  snap [scope]          - Take UI snapshot");
-                println!("  click <id>            - Click element by ID");
+                println!("  snap_export <path> [scope] - Save a UI snapshot as JSON (for offline selector work)");
+                println!("  describe              - What the agent sees now: vision narrative + accessibility elements");
+                println!("  click <id>            - Click element by ID (a snapshot ref like e3 on Linux)");
                 println!("  type <text>           - Type text");
                 println!("  unlock                - Unlock Write Policy");
                 println!("  status                - Show system status");
+                println!("  pause [persist]       - Stop capturing activity (persist: stay paused after restart)");
+                println!("  resume                - Capture activity again");
+                println!("  config history [key] [N] - Show recent changes to approval policies, the allowlist and monitoring");
+                println!("  capabilities          - Show which actions this platform supports");
+                println!("  golden record <name> <goal> - Run a goal and save its plans and actions as a regression golden");
+                println!("  golden check          - Replay every golden and show actions that changed");
+                println!("  observe <question>    - Answer a question about the screen (read-only)");
+                println!("  undo                  - Revert the last reversible goal step (typing, bringing up an app)");
+                println!("  extract <field:type, ...> - Read typed fields off the screen as JSON");
+                println!("  outline <goal>        - Preview the steps a goal would take (nothing is run)");
+                println!("  search <query>        - Open the first web search result in Chrome");
                 println!("  recommendations [N]   - List pending workflow recommendations");
-                println!("  approve <id>          - Approve and create n8n workflow");
-                println!("  reject <id>           - Reject recommendation");
+                println!("  approve <id> [id...]  - Approve and create n8n workflows");
+                println!("  retry <id>            - Rebuild a failed recommendation's workflow using its last error");
+                println!("  reject <id> [id...]   - Reject recommendations");
+                println!("  promote <id> [schedule] - Schedule a recommendation as a routine (cron or plain language; default: its trigger)");
+                println!("  explain <id> - Show the evidence and pattern behind a recommendation's confidence");
+                println!("  template list | add <id> <file.json> [description] | use <id> [param=value ...] - Reusable n8n workflows with {{{{param}}}} placeholders");
+                println!("  export_workflow <id|all> <path> - Save an approved recommendation's n8n workflow as JSON (all: every one, into a folder)");
+                println!("  reject below <conf>   - Reject every pending recommendation below a confidence");
                 println!("  analyze_patterns      - Detect behavior patterns and generate recommendations");
-                println!("  quality               - Show workflow quality metrics");
+                println!("  quality               - Show workflow quality and recommendation metrics");
+                println!("  routines              - List routines with their schedule and last run");
+                println!("  summary day [hours]   - End-of-day report from activity + calendar");
+                println!("  routine run <id>      - Run a routine immediately (alias: replay <id>)");
+                println!("  routine check <id> <json|off> - Set the check that decides whether a run succeeded");
+                println!("  sessions [N]          - List stored goal sessions and their size");
+                println!("  sessions purge <days> - Delete sessions idle for more than <days>");
+                println!("  sessions cleanup      - Apply artifact retention (STEER_ARTIFACT_RETENTION_DAYS / STEER_ARTIFACT_MAX_MB) now");
+                println!("  sessions files <key>  - List a session's artifact folder (screenshots, transcript)");
+                println!("  routine add-nl \"<prompt>\" when \"<schedule>\" - Create a routine from plain language");
+                println!("  test integrations     - Check each integration's credentials (read-only)");
                 println!("  telegram <msg>        - Send Telegram message");
+                println!("  discord <msg>         - Send Discord message");
                 println!("  notion <title>|<body> - Create Notion page");
                 println!("  gmail list [N]        - List recent N emails");
                 println!("  gmail read <id>       - Read email by ID");
                 println!("  gmail send <to>|<subj>|<body> - Send email");
+                println!("  email file <id>       - Summarize an email and file it in Notion");
+                println!("  prompts [reload]      - List prompt templates / re-read them from disk");
                 println!("  calendar today        - Today's events");
                 println!("  calendar week         - This week's events");
                 println!("  calendar add <title>|<start>|<end> - Add event");
                 println!("  exit                  - Quit");
             },
-            "exit" | "quit" => break,
+            "exit" | "quit" => {
+                integrations::discord::stop_command_channel();
+                integrations::telegram::stop_poller();
+                break;
+            }
             "unlock" => {
                 policy.unlock();
                 println!("[Policy] Write Lock UNLOCKED.");
@@ -260,12 +364,136 @@ async fn main() -> anyhow::Result<()> {
             "snap" => {
                 let scope = if parts.len() > 1 { Some(parts[1].to_string()) } else { None };
                 println!("[MacOS] Snapshotting...");
-                #[cfg(target_os = "macos")]
-                {
-                    let tree = macos::accessibility::snapshot(scope);
+                if cfg!(any(target_os = "macos", target_os = "linux")) {
+                    let tree = ui_snapshot::capture(scope).await;
                     println!("📄 Snapshot:\n{}", serde_json::to_string_pretty(&tree)?);
+                } else {
+                    println!("⚠️  snap is supported only on macOS and Linux. Run 'capabilities' for what works here.");
+                }
+            }
+            "describe" => {
+                println!("👁️  Describing the screen...");
+                let description = screen_describe::describe(llm_client.as_ref()).await;
+                println!("{}", description.render());
+            }
+            "snap_export" => {
+                let Some(path) = parts.get(1) else {
+                    println!("Usage: snap_export <path> [scope]");
+                    continue;
+                };
+                let scope = parts.get(2).map(|s| s.to_string());
+                match ui_snapshot::export(path, scope).await {
+                    Ok((target, doc)) => match ui_snapshot::snapshot_error(&doc) {
+                        Some(e) => println!("⚠️  Saved to {}, but the snapshot is incomplete: {}", target.display(), e),
+                        None => println!("💾 Snapshot saved to {}", target.display()),
+                    },
+                    Err(e) => println!("❌ Snapshot export failed: {:#}", e),
+                }
+            }
+            "search" => {
+                if parts.len() < 2 { println!("Usage: search <query>"); continue; }
+                let query = parts[1..].join(" ");
+                println!("🔎 Searching {} for '{}'...", web_search::engine().name, query);
+                match tokio::task::spawn_blocking(move || web_search::open_first_result(&query)).await {
+                    Ok(Ok(url)) => println!("✅ Opened {}", url),
+                    Ok(Err(e)) => println!("❌ Search failed: {:#}", e),
+                    Err(e) => println!("❌ Search failed: {}", e),
+                }
+            }
+            "undo" => match command_queue::enqueue_ui(undo_stack::undo_last).await {
+                Ok(Some(entry)) => println!(
+                    "↩️  Undid '{}': {} ({} more undoable)",
+                    entry.step,
+                    entry.inverse.describe(),
+                    undo_stack::pending()
+                ),
+                Ok(None) => println!("(Nothing to undo)"),
+                Err(e) => println!("❌ Undo failed: {}", e),
+            },
+            "observe" => {
+                if parts.len() < 2 { println!("Usage: observe <question>"); continue; }
+                let question = parts[1..].join(" ");
+                let Some(llm) = llm_client.clone() else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                match observer::ObserveController::new(llm).answer(&question).await {
+                    Ok(answer) => println!("👁️ {}", answer),
+                    Err(e) => println!("❌ Observe failed: {}", e),
+                }
+            }
+            "extract" => {
+                if parts.len() < 2 { println!("Usage: extract <field:type, ...>"); continue; }
+                let schema = match screen_extract::ExtractSchema::parse(&parts[1..].join(" ")) {
+                    Ok(schema) => schema,
+                    Err(e) => { println!("❌ {}", e); continue; }
+                };
+                let Some(llm) = llm_client.clone() else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                match screen_extract::extract(&llm, &schema).await {
+                    Ok(data) => println!("🔎 {}", serde_json::to_string_pretty(&data).unwrap_or_default()),
+                    Err(e) => println!("❌ Extract failed: {}", e),
+                }
+            }
+            "outline" => {
+                if parts.len() < 2 { println!("Usage: outline <goal>"); continue; }
+                let goal = parts[1..].join(" ");
+                let Some(llm) = llm_client.clone() else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                match executor::AgentExecutor::new(llm).outline(&goal).await {
+                    Ok(steps) => {
+                        println!("🗺️  Plan for '{}' ({} steps):", goal, steps.len());
+                        for (i, step) in steps.iter().enumerate() {
+                            println!("   {}. {} [{}]", i + 1, step.description, step.action);
+                        }
+                    }
+                    Err(e) => println!("❌ Outline failed [{}]: {}", e.code(), e),
                 }
             }
+            "golden" if parts.get(1) == Some(&"record") => {
+                // golden record <name> <goal>
+                let (Some(name), true) = (parts.get(2), parts.len() > 3) else {
+                    println!("Usage: golden record <name> <goal>");
+                    continue;
+                };
+                let goal = parts[3..].join(" ");
+                let Some(llm) = llm_client.clone() else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                let executor = executor::AgentExecutor::new(llm).with_recording(&goal);
+                match executor.execute_goal(&goal).await {
+                    Ok(outcome) => println!("✅ {}", outcome),
+                    Err(e) => println!("⚠️  Goal ended with [{}]: {} (recorded anyway)", e.code(), e),
+                }
+                let Some(recorded) = executor.take_recording() else { continue };
+                match golden::save(&recorded, name) {
+                    Ok(path) => println!("📼 Golden '{}' saved: {} replies, {} actions → {}", name, recorded.responses.len(), recorded.actions.len(), path.display()),
+                    Err(e) => println!("❌ Failed to save golden: {}", e),
+                }
+            }
+            "golden" => {
+                // golden [check]: replay every golden and report changed actions
+                match golden::check_all(&golden::dir()).await {
+                    Ok(changed) if changed.is_empty() => println!("✅ Every golden in {} replays unchanged", golden::dir().display()),
+                    Ok(changed) => {
+                        for (name, differences) in changed {
+                            println!("❌ {}:", name);
+                            for d in differences {
+                                println!("   {}", d);
+                            }
+                        }
+                    }
+                    Err(e) => println!("❌ Golden check failed: {}", e),
+                }
+            }
+            "capabilities" | "caps" => {
+                dependency_check::print_capabilities(llm_client.is_some());
+            }
             "type" => {
                 if parts.len() < 2 { println!("Usage: type <text>"); continue; }
                 let text = parts[1..].join(" ");
@@ -274,7 +502,7 @@ async fn main() -> anyhow::Result<()> {
                     Ok(_) => {
                         println!("✅ Policy Passed");
                         #[cfg(target_os = "macos")]
-                        if let Err(e) = macos::actions::type_text(&text) {
+                        if let Err(e) = command_queue::enqueue_ui(move || macos::actions::type_text(&text)).await {
                             println!("❌ Type failed: {}", e);
                         }
                     }
@@ -291,6 +519,10 @@ async fn main() -> anyhow::Result<()> {
                         if let Err(e) = macos::actions::click_element(id) {
                             println!("❌ Click failed: {}", e);
                         }
+                        #[cfg(target_os = "linux")]
+                        if let Err(e) = linux::accessibility::click_ref(id).await {
+                            println!("❌ Click failed: {}", e);
+                        }
                     }
                     Err(e) => println!("⛔️ Policy Blocked: {}", e),
                 }
@@ -298,12 +530,38 @@ async fn main() -> anyhow::Result<()> {
             "exec" => {
                 if parts.len() < 2 { println!("Usage: exec <command>"); continue; }
                 let cmd = parts[1..].join(" ");
-                
+                let cwd = Some(executor::shell_workdir());
+
+                let remembered = approval_gate::exec_policy(&cmd, cwd.as_deref());
+                if remembered == Some(approval_gate::ExecPolicy::DenyAlways) {
+                    println!("⛔️ Denied by a saved deny-always policy: {}", cmd);
+                    continue;
+                }
+                let allow_always = remembered == Some(approval_gate::ExecPolicy::AllowAlways);
+                if allow_always {
+                    println!("✅ Allowed by a saved allow-always policy.");
+                }
+
                 // [Phase 8] Security Sandboxing
-                match security::CommandClassifier::classify(&cmd) {
+                let impact = shell_analysis::assess_impact(&cmd);
+                let classified = security::CommandClassifier::classify(&cmd);
+                let level = security::escalate_with_impact(classified, &impact);
+                if level != classified {
+                    println!("🔎 Impact analysis raised the safety level to {:?}.", level);
+                }
+                match level {
+                    security::SafetyLevel::Critical if allow_always => {
+                        // Not asked again, but never silently
+                        println!("⛔️ CRITICAL WARNING: This command is flagged as DANGEROUS.");
+                        println!("   Command: {}", cmd);
+                        println!("   Impact: {}", impact.summary());
+                        println!("   Running without confirmation: a saved allow-always policy covers it.");
+                    },
+                    _ if allow_always => {}
                     security::SafetyLevel::Critical => {
                         println!("⛔️ CRITICAL WARNING: This command is flagged as DANGEROUS.");
                         println!("   Command: {}", cmd);
+                        println!("   Impact: {}", impact.summary());
                         println!("   To execute, type 'CONFIRM':");
                         
                         buffer.clear();
@@ -316,6 +574,7 @@ async fn main() -> anyhow::Result<()> {
                     security::SafetyLevel::Warning => {
                          println!("⚠️  WARNING: This command may modify your system.");
                          println!("   Command: {}", cmd);
+                         println!("   Impact: {}", impact.summary());
                          println!("   Execute? (y/n):");
                          
                          buffer.clear();
@@ -330,22 +589,26 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
 
-                let cwd = std::env::current_dir()
-                    .ok()
-                    .map(|p| p.to_string_lossy().to_string());
                 let action = AgentAction::ShellExecution { command: cmd.clone() };
                 match policy.check_with_context(&action, cwd.as_deref()) {
                     Ok(_) => {
                         println!("⚙️  Executing: '{}'", cmd);
-                        match executor::run_shell(&cmd).await {
+                        match executor::run_shell_in(&cmd, cwd.as_deref(), &[]).await {
                             Ok(out) => println!("Output:\n{}", out),
                             Err(e) => println!("❌ Exec failed: {}", e),
                         }
                     },
+                    Err(e) if e.reason == policy::BlockReason::ActionDisabled => {
+                        // No approval can turn a disabled action back on
+                        println!("⛔️ {}", e);
+                    },
                     Err(e) => {
-                        if let Ok(Some(_approval)) = db::find_valid_exec_approval(&cmd, cwd.as_deref()) {
+                        // A saved allow-always stands in for an approval, nothing more
+                        let approved = (allow_always && approval_gate::allow_always_overrides(e.reason))
+                            || matches!(db::find_valid_exec_approval(&cmd, cwd.as_deref()), Ok(Some(_)));
+                        if approved {
                             println!("✅ Approved command found. Executing: '{}'", cmd);
-                            match executor::run_shell(&cmd).await {
+                            match executor::run_shell_in(&cmd, cwd.as_deref(), &[]).await {
                                 Ok(out) => println!("Output:\n{}", out),
                                 Err(e) => println!("❌ Exec failed: {}", e),
                             }
@@ -356,6 +619,7 @@ async fn main() -> anyhow::Result<()> {
                                 println!("📝 Exec approval requested: {}", approval.id);
                                 println!("   Approve once: POST /api/exec-approvals/{}/approve", approval.id);
                                 println!("   Approve always: POST /api/exec-approvals/{}/approve ({{\"decision\":\"allow-always\"}})", approval.id);
+                                println!("   Deny always: POST /api/exec-approvals/{}/reject ({{\"decision\":\"deny-always\"}})", approval.id);
                             } else {
                                 println!("⛔️ Policy Blocked: {}", e);
                             }
@@ -364,40 +628,171 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
             "open" => {
-                if parts.len() < 2 { println!("Usage: open <url>"); continue; }
-                let url = parts[1];
-                println!("🌐 Opening URL: {}", url);
-                if let Err(e) = executor::open_url(url) {
-                    println!("❌ Open failed: {}", e);
+                if parts.len() < 2 { println!("Usage: open <url> | open <path> [with <app>]"); continue; }
+                let arg = parts[1..].join(" ");
+                if arg.contains("://") {
+                    println!("🌐 Opening URL: {}", arg);
+                    if let Err(e) = executor::open_url(&arg) {
+                        println!("❌ Open failed: {}", e);
+                    }
+                    continue;
+                }
+                let (path, app) = match arg.rsplit_once(" with ") {
+                    Some((path, app)) => (path.to_string(), Some(app.trim().to_string())),
+                    None => (arg.clone(), None),
+                };
+                if let Err(e) = policy.check(&AgentAction::OpenFile { path: path.clone(), app: app.clone() }) {
+                    println!("⛔️ Policy Blocked: {}", e);
+                    continue;
+                }
+                match executor::open_file(&path, app.as_deref()) {
+                    Ok(resolved) => println!("📂 Opened {}", resolved.display()),
+                    Err(e) => println!("❌ Open failed: {}", e),
                 }
             }
             "fake_log" => {
                  // Simulate log
                  #[cfg(target_os = "macos")]
                  {
-                     let event = EventEnvelope {
-                         schema_version: "1.0".to_string(),
-                         event_id: Uuid::new_v4().to_string(),
-                         ts: Utc::now().to_rfc3339(),
-                         source: "debug".to_string(),
-                         app: "FakeApp".to_string(),
-                         event_type: "simulated".to_string(),
-                         priority: "P2".to_string(),
-                         resource: None,
-                         payload: json!({"note": "simulated"}),
-                         privacy: None,
-                         pid: None,
-                         window_id: None,
-                         window_title: None,
-                         browser_url: None,
-                         raw: None,
-                     };
-                     if let Ok(log) = serde_json::to_string(&event) {
-                         let _ = log_tx.send(log).await;
+                     let event = EventEnvelope::new("debug", "FakeApp", "simulated", "P2", None, json!({"note": "simulated"}));
+                     if let Err(e) = schema::emit(&log_tx, &event) {
+                         println!("⚠️ Simulated log not sent: {}", e);
+                         continue;
                      }
                      println!("✅ Simulated Log Sent");
                  }
             }
+            "routine" if parts.get(1) == Some(&"add-nl") => {
+                // routine add-nl "<prompt>" when "<natural schedule>"
+                let rest = input.splitn(3, char::is_whitespace).nth(2).unwrap_or("").trim();
+                let Some((prompt, schedule)) = rest.split_once(" when ") else {
+                    println!("Usage: routine add-nl \"<prompt>\" when \"<schedule>\"");
+                    continue;
+                };
+                let prompt = prompt.trim().trim_matches('"').trim().to_string();
+                let schedule = schedule.trim().trim_matches('"').trim().to_string();
+                match scheduler::parse_natural_schedule(&schedule) {
+                    Ok(cron) => {
+                        println!("🗓️  '{}' → cron '{}'", schedule, cron);
+                        println!("   Create routine for: {} ? (y/n):", prompt);
+                        buffer.clear();
+                        if reader.read_line(&mut buffer).await? == 0 { break; }
+                        if buffer.trim().to_lowercase() != "y" {
+                            println!("❌ Aborted.");
+                            continue;
+                        }
+                        let name: String = prompt.chars().take(40).collect();
+                        match db::create_routine(&name, &cron, &prompt) {
+                            Ok(id) => println!("✅ Routine #{} created ({})", id, cron),
+                            Err(e) => println!("❌ Failed to create routine: {}", e),
+                        }
+                    }
+                    Err(e) => println!("❌ Couldn't understand schedule: {}", e),
+                }
+            }
+            "routine" if parts.get(1) == Some(&"check") => {
+                // routine check <id> {"kind": "telegram_sent", "within_secs": 60} | off
+                let rest = input.splitn(4, char::is_whitespace).nth(3).unwrap_or("").trim();
+                let Some(id) = parts.get(2).and_then(|s| s.trim_start_matches('#').parse::<i64>().ok()).filter(|_| !rest.is_empty()) else {
+                    println!("Usage: routine check <id> <json|off>");
+                    continue;
+                };
+                let check = if rest == "off" {
+                    None
+                } else {
+                    match routine_check::parse(rest) {
+                        Ok(_) => Some(rest),
+                        Err(e) => {
+                            println!("❌ {}", e);
+                            continue;
+                        }
+                    }
+                };
+                match db::set_routine_success_check(id, check) {
+                    Ok(()) if check.is_some() => println!("✅ Routine #{} now succeeds only if {} passes", id, rest),
+                    Ok(()) => println!("✅ Routine #{} success check removed", id),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => println!("❌ Routine #{} not found", id),
+                    Err(e) => println!("❌ Failed to set success check: {}", e),
+                }
+            }
+            "routine" | "replay" if parts[0] == "replay" || parts.get(1) == Some(&"run") => {
+                let arg = if parts[0] == "replay" { parts.get(1) } else { parts.get(2) };
+                let Some(id) = arg.and_then(|s| s.trim_start_matches('#').parse::<i64>().ok()) else {
+                    println!("Usage: routine run <id> | replay <id>");
+                    continue;
+                };
+                // Without an LLM the routine's stored plan is replayed
+                match scheduler::run_routine_now(llm_client.as_ref(), id).await {
+                    Ok(res) => println!("✅ Routine #{} finished: {}", id, res),
+                    Err(e) => println!("❌ Routine #{} failed [{}]: {}", id, e.code(), e),
+                }
+            }
+            "sessions" if parts.get(1) == Some(&"cleanup") => {
+                let expired = session_artifacts::cleanup(session_artifacts::Retention::from_env());
+                println!("🧹 Removed artifacts of {} session(s).", expired.len());
+            }
+            "sessions" if parts.get(1) == Some(&"files") => {
+                let Some(key) = parts.get(2) else {
+                    println!("Usage: sessions files <session_key>");
+                    continue;
+                };
+                let files = session_artifacts::artifacts(key);
+                println!("📁 {} ({} file(s))", session_artifacts::session_dir(key).display(), files.len());
+                for f in files {
+                    println!("   {}  {:.1} KB  {}", f.name, f.bytes as f64 / 1024.0, f.modified_at);
+                }
+            }
+            "sessions" if parts.get(1) == Some(&"purge") => {
+                let Some(days) = parts.get(2).and_then(|s| s.parse::<i64>().ok()) else {
+                    println!("Usage: sessions purge <older_than_days>");
+                    continue;
+                };
+                match db::purge_run_sessions(days) {
+                    Ok(keys) => {
+                        session_artifacts::remove_sessions(&keys);
+                        println!("🧹 Purged {} session(s) idle for more than {} day(s).", keys.len(), days);
+                    }
+                    Err(e) => println!("❌ Purge failed: {}", e),
+                }
+            }
+            "sessions" => {
+                let limit = parts.get(1).and_then(|s| s.parse::<i64>().ok()).unwrap_or(10);
+                let (total, bytes) = db::run_session_totals().unwrap_or((0, 0));
+                println!("🗂️  {} stored session(s), {:.1} KB", total, bytes as f64 / 1024.0);
+                for s in db::list_run_sessions(limit).unwrap_or_default() {
+                    println!(
+                        "   {}  last {}  {} steps, {} LLM calls, {:.1} KB",
+                        s.session_key, s.last_activity_at, s.steps, s.llm_calls, s.bytes as f64 / 1024.0
+                    );
+                }
+            }
+            "summary" if parts.get(1) == Some(&"day") => {
+                let hours = parts.get(2).and_then(|s| s.parse::<i64>().ok()).unwrap_or(24);
+                let Some(brain) = &llm_client else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                println!("📝 Summarizing the last {}h...", hours);
+                match day_summary::summarize_day(brain, hours).await {
+                    Ok(summary) => {
+                        println!("\n📅 {}", summary.headline);
+                        for h in &summary.highlights { println!("   • {}", h); }
+                        if !summary.meetings.is_empty() {
+                            println!("   Meetings:");
+                            for m in &summary.meetings { println!("   - {}", m); }
+                        }
+                        if !summary.top_apps.is_empty() {
+                            let apps: Vec<String> = summary.top_apps.iter().map(|(a, n)| format!("{} ({})", a, n)).collect();
+                            println!("   Top apps: {}", apps.join(", "));
+                        }
+                        for s in &summary.suggestions { println!("   💡 {}", s); }
+                        if !summary.calendar_available {
+                            println!("   (Calendar not connected; based on activity only)");
+                        }
+                    }
+                    Err(e) => println!("❌ Day summary failed: {}", e),
+                }
+            }
             "routine" => {
                 if let Some(brain) = &llm_client {
                     println!("🧠 Analyzing daily routine (last 24h)...");
@@ -418,7 +813,7 @@ async fn main() -> anyhow::Result<()> {
                         Err(e) => println!("❌ DB Query failed: {}", e),
                     }
                 } else {
-                    println!("⚠️  LLM Client not available.");
+                    print_lite_mode_notice();
                 }
             }
             "recommend" => {
@@ -441,7 +836,7 @@ async fn main() -> anyhow::Result<()> {
                         Err(e) => println!("❌ DB Query failed: {}", e),
                     }
                 } else {
-                    println!("⚠️  LLM Client not available.");
+                    print_lite_mode_notice();
                 }
             }
             "analyze_patterns" | "detect" => {
@@ -472,9 +867,11 @@ async fn main() -> anyhow::Result<()> {
                                     &pattern.sample_events
                                 ).await {
                                     Ok(mut proposal) => {
-                                        // [Explainability] Inject hard evidence manually
-                                        proposal.evidence.push(format!("Pattern: {}", pattern.description));
-                                        proposal.evidence.push(format!("Frequency: {} occurrences in last 7 days", pattern.occurrences));
+                                        // [Explainability] Weighted evidence from the pattern itself
+                                        for item in recommendation::pattern_evidence(&pattern) {
+                                            proposal.add_evidence(item);
+                                        }
+                                        proposal.apply_evidence();
                                         
                                         if proposal.confidence >= 0.7 {
                                             if let Ok(true) = db::insert_recommendation(&proposal) {
@@ -493,17 +890,68 @@ async fn main() -> anyhow::Result<()> {
             }
             "quality" | "metrics" => {
                 let collector = feedback_collector::FeedbackCollector::new();
-                let metrics = collector.get_quality_metrics();
                 println!("📈 Workflow Quality Metrics:");
-                println!("   {}", metrics);
+                println!("   {}", reporting::QualityView::from(collector.get_quality_metrics()));
+                println!("   {}", reporting::RecommendationMetricsView::load());
+            }
+            "routines" => match reporting::RoutineView::load_all() {
+                Ok(routines) if routines.is_empty() => println!("(No routines yet)"),
+                Ok(routines) => {
+                    println!("⏰ Routines:");
+                    for routine in routines {
+                        println!("{}", routine);
+                    }
+                }
+                Err(e) => println!("❌ Failed to load routines: {}", e),
+            },
+            "pause" => {
+                let persist = parts.get(1) == Some(&"persist");
+                match monitoring::pause(Some(&log_tx), persist) {
+                    Ok(true) => {}
+                    Ok(false) if persist => println!("⏸️  Already paused; it will now also stay paused after restart."),
+                    Ok(false) => println!("(Monitoring is already paused)"),
+                    Err(e) => println!("❌ Failed to pause monitoring: {}", e),
+                }
             }
+            "resume" => match monitoring::resume(Some(&log_tx)) {
+                Ok(true) => {}
+                Ok(false) => println!("(Monitoring is not paused)"),
+                Err(e) => println!("❌ Failed to resume monitoring: {}", e),
+            },
             "status" => {
                 println!("📊 System Status:");
+                if safe_mode::is_enabled() {
+                    println!("   🛡️ Safe mode: automation disabled");
+                }
+                let m = monitoring::status();
+                if m.paused {
+                    println!("   ⏸️ Monitoring paused since {}{}", m.since.as_deref().unwrap_or("?"), if m.persisted { " (persisted)" } else { "" });
+                } else if m.sensitive {
+                    println!("   🙈 Capture stopped: a sensitive app is in front");
+                }
                 println!("   {}", res_mon.get_status());
                 println!("   Top Apps:");
                 for (name, usage) in res_mon.get_high_usage_apps() {
                     println!("   - {}: {:.1}%", name, usage);
                 }
+                for cap in dependency_check::capabilities(llm_client.is_some()).iter().filter(|c| !c.supported) {
+                    println!("   Unsupported here: {}", cap.name);
+                }
+                let a = analyzer::status();
+                println!(
+                    "   Analyzer: received {} / processed {} / dropped {} (in flight {}, {} queued on {} workers)",
+                    a.received, a.processed, a.dropped, a.in_flight, a.queued, a.workers
+                );
+                let b = llm_budget::status();
+                match b.limit {
+                    Some(limit) => println!("   LLM tokens today: {} / {}", b.used_today, limit),
+                    None => println!("   LLM tokens today: {} (no budget set)", b.used_today),
+                }
+                let r = screen_cache::stats();
+                println!(
+                    "   Vision reuse: {} screenshots reused, {} vision calls skipped",
+                    r.frames_reused, r.vision_calls_skipped
+                );
             }
             "recommendations" | "recs" => {
                 let limit = parts.get(1).and_then(|s| s.parse::<i64>().ok()).unwrap_or(5);
@@ -514,12 +962,7 @@ async fn main() -> anyhow::Result<()> {
                         } else {
                             println!("🧩 Pending recommendations:");
                             for rec in recs {
-                                println!(
-                                    "  [{}] {} (confidence {:.2})",
-                                    rec.id, rec.title, rec.confidence
-                                );
-                                println!("       Trigger: {}", rec.trigger);
-                                println!("       Summary: {}", rec.summary);
+                                println!("{}", reporting::RecommendationView::from(rec));
                             }
                         }
                     }
@@ -527,55 +970,282 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
             "approve" => {
-                if parts.len() < 2 { println!("Usage: approve <id>"); continue; }
-                let id: i64 = match parts[1].parse() {
-                    Ok(v) => v,
-                    Err(_) => { println!("Usage: approve <id>"); continue; }
+                let Some(ids) = parse_ids(&parts[1..]) else {
+                    println!("Usage: approve <id> [id...]");
+                    continue;
+                };
+                if let Err(e) = safe_mode::check("new workflows are") {
+                    println!("❌ {}", e);
+                    continue;
+                }
+                let Some(brain) = &llm_client else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                for id in ids {
+                    let rec = match db::get_recommendation(id) {
+                        Ok(Some(r)) => r,
+                        Ok(None) => { println!("No recommendation found for id {}", id); continue; }
+                        Err(e) => { println!("❌ Failed to read recommendation: {}", e); continue; }
+                    };
+
+                    println!("🏗️  Building n8n workflow for '{}'...", rec.title);
+                    build_recommendation_workflow(brain, &rec, &rec.n8n_prompt).await;
+                }
+            }
+            "config" => {
+                if parts.get(1) != Some(&"history") {
+                    println!("Usage: config history [key-prefix] [N]");
+                    continue;
+                }
+                let (prefix, limit) = match (parts.get(2), parts.get(3)) {
+                    (Some(n), None) if n.parse::<i64>().is_ok() => (None, n.parse().unwrap_or(20)),
+                    (prefix, n) => (prefix.copied(), n.and_then(|n| n.parse().ok()).unwrap_or(20)),
+                };
+                match db::list_config_changes(prefix, limit) {
+                    Ok(changes) if changes.is_empty() => println!("   (No config changes recorded)"),
+                    Ok(changes) => {
+                        println!("🧾 Config changes (newest first):");
+                        for c in changes {
+                            println!(
+                                "   {}  {}: {} → {}",
+                                c.changed_at,
+                                c.config_key,
+                                c.old_value.as_deref().unwrap_or("(unset)"),
+                                c.new_value.as_deref().unwrap_or("(removed)")
+                            );
+                        }
+                    }
+                    Err(e) => println!("❌ Failed to read config history: {}", e),
+                }
+            }
+            "retry" => {
+                let Some(id) = parts.get(1).and_then(|s| s.trim_start_matches('#').parse::<i64>().ok()) else {
+                    println!("Usage: retry <id>");
+                    continue;
+                };
+                if let Err(e) = safe_mode::check("new workflows are") {
+                    println!("❌ {}", e);
+                    continue;
+                }
+                let Some(brain) = &llm_client else {
+                    print_lite_mode_notice();
+                    continue;
                 };
                 let rec = match db::get_recommendation(id) {
                     Ok(Some(r)) => r,
                     Ok(None) => { println!("No recommendation found for id {}", id); continue; }
                     Err(e) => { println!("❌ Failed to read recommendation: {}", e); continue; }
                 };
-
-                let Some(brain) = &llm_client else {
-                    println!("⚠️  LLM Client not available.");
+                if rec.status == "approved" {
+                    println!("Recommendation {} is already approved (workflow {}).", id, rec.workflow_id.as_deref().unwrap_or("?"));
                     continue;
+                }
+                let last_error = match db::get_recommendation_last_error(id) {
+                    Ok(Some(e)) => e,
+                    Ok(None) => { println!("Recommendation {} has no recorded failure; use 'approve {}'.", id, id); continue; }
+                    Err(e) => { println!("❌ Failed to read recommendation: {}", e); continue; }
                 };
-
-                println!("🏗️  Building n8n workflow for '{}'...", rec.title);
-                match brain.build_n8n_workflow(&rec.n8n_prompt).await {
-                    Ok(json_str) => {
-                        let n8n_url = std::env::var("N8N_API_URL").unwrap_or_else(|_| "http://localhost:5678".to_string());
-                        let n8n_key = std::env::var("N8N_API_KEY").unwrap_or_default();
-                        let n8n = n8n_api::N8nApi::new(&format!("{}/api/v1", n8n_url), &n8n_key);
-
-                        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                            match n8n.create_workflow(&rec.title, &val, true).await {
-                                Ok(workflow_id) => {
-                                    if let Err(e) = db::mark_recommendation_approved(id, &workflow_id, &json_str) {
-                                        println!("⚠️  Workflow created but failed to update DB: {}", e);
-                                    }
-                                    println!("✅ Workflow created! ID: {}", workflow_id);
-                                }
-                                Err(e) => println!("❌ API Import failed: {}", e),
-                            }
-                        } else {
-                            println!("❌ LLM produced invalid JSON.");
+                println!("🔁 Rebuilding '{}' (last error: {})...", rec.title, last_error);
+                let prompt = workflow_schema::correction_prompt(&rec.n8n_prompt, &last_error);
+                build_recommendation_workflow(brain, &rec, &prompt).await;
+            }
+            "reject" => {
+                // reject <id> [id...]  |  reject below <confidence>
+                let ids = if parts.get(1) == Some(&"below") {
+                    let Some(threshold) = parts.get(2).and_then(|v| v.parse::<f64>().ok()) else {
+                        println!("Usage: reject below <confidence>");
+                        continue;
+                    };
+                    match db::pending_recommendation_ids_below(threshold) {
+                        Ok(ids) => ids,
+                        Err(e) => { println!("❌ Failed to read recommendations: {}", e); continue; }
+                    }
+                } else {
+                    let Some(ids) = parse_ids(&parts[1..]) else {
+                        println!("Usage: reject <id> [id...] | reject below <confidence>");
+                        continue;
+                    };
+                    ids
+                };
+                if ids.is_empty() {
+                    println!("(Nothing to reject)");
+                    continue;
+                }
+                match db::update_recommendations_status(&ids, "rejected") {
+                    Ok(n) => println!("🗑️  Rejected {} recommendation(s): {:?}", n, ids),
+                    Err(e) => println!("❌ Failed to reject recommendations: {}", e),
+                }
+            }
+            "promote" => {
+                // promote <id> [cron | natural schedule]
+                let Some(id) = parts.get(1).and_then(|s| s.trim_start_matches('#').parse::<i64>().ok()) else {
+                    println!("Usage: promote <id> [schedule]");
+                    continue;
+                };
+                let rec = match db::get_recommendation(id) {
+                    Ok(Some(rec)) => rec,
+                    Ok(None) => { println!("❌ Recommendation #{} not found", id); continue; }
+                    Err(e) => { println!("❌ Failed to read recommendation: {}", e); continue; }
+                };
+                let given = parts[2..].join(" ").trim().trim_matches('"').to_string();
+                let schedule = if given.trim().is_empty() { rec.trigger.clone() } else { given };
+                let cron = match db::validate_cron(&schedule) {
+                    Ok(()) => schedule.trim().to_string(),
+                    Err(_) => match scheduler::parse_natural_schedule(&schedule) {
+                        Ok(cron) => cron,
+                        Err(e) => {
+                            println!("❌ Couldn't turn '{}' into a schedule: {}", schedule, e);
+                            println!("   Give one explicitly: promote {} \"every weekday at 9am\"", id);
+                            continue;
                         }
+                    },
+                };
+                if let Err(e) = safe_mode::check("new routines are") {
+                    println!("❌ {}", e);
+                    continue;
+                }
+                match db::promote_recommendation_to_routine(id, &cron) {
+                    Ok(routine_id) => println!("✅ Recommendation #{} is now routine #{} ({}): {}", id, routine_id, cron, rec.title),
+                    Err(e) => println!("❌ Failed to promote recommendation: {}", e),
+                }
+            }
+            "explain" => {
+                let Some(id) = parts.get(1).and_then(|p| p.trim_start_matches('#').parse::<i64>().ok()) else {
+                    println!("Usage: explain <id>");
+                    continue;
+                };
+                match db::get_recommendation(id) {
+                    Ok(Some(rec)) => {
+                        let pattern = rec.pattern_id.as_deref().and_then(|p| db::get_pattern_info(p).ok().flatten());
+                        println!("{}", recommendation::explain(&rec, pattern).render());
                     }
-                    Err(e) => println!("❌ Generation failed: {}", e),
+                    Ok(None) => println!("❌ Recommendation #{} not found", id),
+                    Err(e) => println!("❌ Failed to load recommendation: {}", e),
                 }
             }
-            "reject" => {
-                if parts.len() < 2 { println!("Usage: reject <id>"); continue; }
-                let id: i64 = match parts[1].parse() {
-                    Ok(v) => v,
-                    Err(_) => { println!("Usage: reject <id>"); continue; }
+            "export_workflow" => {
+                // export_workflow <id> <path> | export_workflow all <dir>
+                if parts.len() < 3 {
+                    println!("Usage: export_workflow <id> <path> | export_workflow all <dir>");
+                    continue;
+                }
+                let path = parts[2..].join(" ");
+                if parts[1] == "all" {
+                    match workflow_export::export_approved(&path) {
+                        Ok(files) if files.is_empty() => println!("📭 No approved workflows to export."),
+                        Ok(files) => {
+                            for file in &files {
+                                println!("   {}", file.display());
+                            }
+                            println!("💾 Exported {} workflow(s) to {}", files.len(), path.trim().trim_matches('"'));
+                        }
+                        Err(e) => println!("❌ Failed to export workflows: {}", e),
+                    }
+                    continue;
+                }
+                let Ok(id) = parts[1].trim_start_matches('#').parse::<i64>() else {
+                    println!("Usage: export_workflow <id> <path> | export_workflow all <dir>");
+                    continue;
                 };
-                match db::update_recommendation_status(id, "rejected") {
-                    Ok(()) => println!("🗑️  Recommendation {} rejected.", id),
-                    Err(e) => println!("❌ Failed to reject recommendation: {}", e),
+                match workflow_export::export_workflow(id, &path) {
+                    Ok(file) => println!("💾 Workflow for recommendation #{} saved to {}", id, file.display()),
+                    Err(e) => println!("❌ Failed to export workflow: {:#}", e),
+                }
+            }
+            "template" => {
+                let usage = "Usage: template list | template add <id> <file.json> [description] | template use <id> [param=value ...]";
+                match parts.get(1).copied() {
+                    Some("list") => match db::list_templates() {
+                        Ok(templates) if templates.is_empty() => println!("📭 No workflow templates."),
+                        Ok(templates) => {
+                            println!("📋 Workflow templates:");
+                            for t in &templates {
+                                let params = t.params();
+                                println!(
+                                    "   {:<18} {}{}",
+                                    t.id,
+                                    t.name,
+                                    if t.builtin { " (default)" } else { "" }
+                                );
+                                if !t.description.is_empty() {
+                                    println!("   {:<18} {}", "", t.description);
+                                }
+                                if !params.is_empty() {
+                                    println!("   {:<18} params: {}", "", params.join(", "));
+                                }
+                            }
+                        }
+                        Err(e) => println!("❌ Failed to list templates: {}", e),
+                    },
+                    Some("add") if parts.len() >= 4 => {
+                        let (id, path) = (parts[2], parts[3].trim_matches('"'));
+                        let json_str = match std::fs::read_to_string(path) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                println!("❌ Failed to read {}: {}", path, e);
+                                continue;
+                            }
+                        };
+                        // Placeholders go inside JSON strings, so the template itself parses
+                        let val = match serde_json::from_str::<serde_json::Value>(&json_str) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                println!("❌ {} is not valid JSON: {}", path, e);
+                                continue;
+                            }
+                        };
+                        let name = val["name"].as_str().filter(|n| !n.trim().is_empty()).unwrap_or(id).to_string();
+                        let description = parts[4..].join(" ");
+                        match db::add_template(id, &name, &description, &json_str) {
+                            Ok(()) => {
+                                let params = db::template_params(&json_str);
+                                println!("✅ Template '{}' saved ({} param(s){}{})", id, params.len(), if params.is_empty() { "" } else { ": " }, params.join(", "));
+                            }
+                            Err(e) => println!("❌ Failed to save template: {}", e),
+                        }
+                    }
+                    Some("use") if parts.len() >= 3 => {
+                        if let Err(e) = safe_mode::check("new workflows are") {
+                            println!("❌ {}", e);
+                            continue;
+                        }
+                        let id = parts[2];
+                        let params = parse_template_params(&parts[3..]);
+                        let json_str = match db::instantiate_template(id, &params) {
+                            Ok(json) => json,
+                            Err(db::WriteError::NotFound(_)) => {
+                                println!("❌ No template '{}' (see 'template list')", id);
+                                continue;
+                            }
+                            Err(e) => {
+                                println!("❌ {}", e);
+                                continue;
+                            }
+                        };
+                        let val = match serde_json::from_str::<serde_json::Value>(&json_str) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                println!("❌ Template '{}' produced invalid JSON: {}", id, e);
+                                continue;
+                            }
+                        };
+                        let problems = workflow_schema::validate_n8n_workflow(&val);
+                        if !problems.is_empty() {
+                            println!("❌ Workflow validation failed: {}", problems.join("; "));
+                            continue;
+                        }
+                        let name = val["name"].as_str().unwrap_or(id).to_string();
+                        let n8n_url = std::env::var("N8N_API_URL").unwrap_or_else(|_| "http://localhost:5678".to_string());
+                        let n8n_key = std::env::var("N8N_API_KEY").unwrap_or_default();
+                        let n8n = n8n_api::N8nApi::new(&format!("{}/api/v1", n8n_url), &n8n_key);
+                        println!("🏗️  Creating '{}' from template '{}'...", name, id);
+                        match n8n.create_workflow(&name, &val, workflow_schema::auto_activate_enabled()).await {
+                            Ok(workflow_id) => println!("✅ Workflow created! ID: {}", workflow_id),
+                            Err(e) => println!("❌ API Import failed: {}", e),
+                        }
+                    }
+                    _ => println!("{}", usage),
                 }
             }
             "control" => {
@@ -593,6 +1263,10 @@ async fn main() -> anyhow::Result<()> {
             }
             "build_workflow" => {
                 if parts.len() < 2 { println!("Usage: build_workflow <prompt>"); continue; }
+                if let Err(e) = safe_mode::check("new workflows are") {
+                    println!("❌ {}", e);
+                    continue;
+                }
                 let prompt = parts[1..].join(" ");
                 
                 if let Some(brain) = &llm_client {
@@ -608,18 +1282,30 @@ async fn main() -> anyhow::Result<()> {
                             
                             // Parse JSON string to Value
                             if let Ok(val) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                                match n8n.create_workflow("Agent Generated Workflow", &val, true).await {
-                                    Ok(id) => println!("✅ Workflow Created! ID: {}\n   (Check your n8n dashboard)", id),
-                                    Err(e) => {
-                                        println!("❌ API Import failed: {}", e);
-                                        println!("👻 Activating Visual Fallback (Phantom Hand)...");
-                                        // Trigger visual fallback
-                                        let fallback = visual_driver::n8n_fallback_create_workflow();
-                                        if let Err(ve) = fallback.execute(None).await {
-                                            println!("❌ Visual Fallback also failed: {}", ve);
-                                        }
-                                    },
+                                let problems = workflow_schema::validate_n8n_workflow(&val);
+                                if !problems.is_empty() {
+                                    println!("❌ Workflow validation failed: {}", problems.join("; "));
+                                    continue;
                                 }
+                                let outcome = n8n_fallback::import_workflow(&n8n, "Agent Generated Workflow", &val, workflow_schema::auto_activate_enabled()).await;
+                                match &outcome {
+                                    n8n_fallback::ImportOutcome::ImportedViaApi { workflow_id } => {
+                                        println!("✅ Workflow Created! ID: {}\n   (Check your n8n dashboard)", workflow_id)
+                                    }
+                                    n8n_fallback::ImportOutcome::ImportedViaUi { workflow_id: Some(id), .. } => {
+                                        println!("✅ Workflow Created after visual fallback. ID: {}", id)
+                                    }
+                                    n8n_fallback::ImportOutcome::ImportedViaUi { workflow_id: None, session_key } => {
+                                        println!("👻 Visual fallback completed, but the API import still failed; finish the workflow in the n8n editor (session {}).", session_key)
+                                    }
+                                    n8n_fallback::ImportOutcome::FailedBoth { ui_error, session_key, .. } => {
+                                        println!("❌ Visual Fallback also failed: {} (session {})", ui_error, session_key)
+                                    }
+                                    n8n_fallback::ImportOutcome::Unreachable { retry_in_secs, .. } => {
+                                        println!("❌ n8n unreachable; visual fallback skipped. Retrying allowed in {}s.", retry_in_secs)
+                                    }
+                                }
+                                println!("   Import result: {}", outcome.as_str());
                             } else {
                                 println!("❌ LLM produced invalid JSON.");
                             }
@@ -627,7 +1313,17 @@ async fn main() -> anyhow::Result<()> {
                         Err(e) => println!("❌ Generation failed: {}", e),
                     }
                 } else {
-                    println!("⚠️  LLM Client not available.");
+                    print_lite_mode_notice();
+                }
+            }
+            "test" => {
+                if parts.get(1) != Some(&"integrations") {
+                    println!("Usage: test integrations");
+                    continue;
+                }
+                println!("🔌 Testing integrations (read-only)...");
+                for report in integrations::connection_test::test_all().await {
+                    println!("  {} {:<9} {}", report.icon(), report.service, report.detail);
                 }
             }
             "telegram" => {
@@ -644,6 +1340,20 @@ async fn main() -> anyhow::Result<()> {
                     Err(e) => println!("⚠️  Telegram not configured: {}", e),
                 }
             }
+            "discord" => {
+                if parts.len() < 2 { println!("Usage: discord <message>"); continue; }
+                let message = parts[1..].join(" ");
+                println!("💬 Sending to Discord...");
+                match integrations::discord::DiscordClient::from_env() {
+                    Ok(client) => {
+                        match client.send(&message).await {
+                            Ok(_) => println!("✅ Message sent!"),
+                            Err(e) => println!("❌ Failed: {}", e),
+                        }
+                    }
+                    Err(e) => println!("⚠️  Discord not configured: {}", e),
+                }
+            }
             "notion" => {
                 // Usage: notion <title> | <content>
                 if parts.len() < 2 { println!("Usage: notion <title> | <content>"); continue; }
@@ -669,6 +1379,31 @@ async fn main() -> anyhow::Result<()> {
                     Err(e) => println!("⚠️  Notion not configured: {}", e),
                 }
             }
+            "email" => {
+                if parts.len() < 3 || parts[1] != "file" {
+                    println!("Usage: email file <id>");
+                    continue;
+                }
+                let Some(llm) = &llm_client else {
+                    print_lite_mode_notice();
+                    continue;
+                };
+                println!("🗂️  Filing email {}...", parts[2]);
+                match orchestrator::file_email(llm, parts[2]).await {
+                    Ok(page_id) => println!("✅ Filed! Notion page ID: {}", page_id),
+                    Err(e) => println!("❌ Failed: {:#}", e),
+                }
+            }
+            "prompts" => {
+                if parts.get(1) == Some(&"reload") {
+                    let loaded = prompt_templates::reload();
+                    println!("🔄 Reloaded prompt templates ({} custom)", loaded.len());
+                }
+                println!("Prompt templates ({}):", prompt_templates::prompts_dir().display());
+                for (name, custom) in prompt_templates::list() {
+                    println!("  {:<8} {}", name, if custom { format!("custom ({}.txt)", name) } else { "built-in".to_string() });
+                }
+            }
             "gmail" => {
                 if parts.len() < 2 { 
                     println!("Usage: gmail list [N] | gmail read <id> | gmail send <to>|<subj>|<body>"); 
@@ -809,14 +1544,14 @@ async fn main() -> anyhow::Result<()> {
             }
             // Super Agent Mode (Unified Orchestrator)
             _ => {
-                if let Ok(orch) = orchestrator::Orchestrator::new().await {
+                if let Some(orch) = &orchestrator {
                    println!("🤖 Super Agent: Processing '{}'...", input);
                    match orch.handle_request(input).await {
                        Ok(resp) => println!("{}", resp),
                        Err(e) => println!("❌ Super Agent Error: {}", e),
                    }
                 } else {
-                   println!("⚠️  Orchestrator could not initialization.");
+                   println!("⚠️  Orchestrator could not be initialized (see startup log).");
                 }
             }
         }
@@ -825,6 +1560,78 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Build a workflow for `rec` from `prompt`, import it into n8n and record the
+/// outcome: approved with the workflow id, or failed with the reason (which
+/// `retry` feeds back into the next build).
+async fn build_recommendation_workflow(brain: &llm_gateway::LLMClient, rec: &db::Recommendation, prompt: &str) {
+    let fail = |msg: String| {
+        println!("❌ {}", msg);
+        if let Err(e) = db::mark_recommendation_failed(rec.id, &msg) {
+            println!("⚠️  Failed to record the error: {}", e);
+        }
+    };
+    let json_str = match brain.build_n8n_workflow(prompt).await {
+        Ok(json_str) => json_str,
+        Err(e) => return fail(format!("Generation failed: {}", e)),
+    };
+    let Ok(val) = serde_json::from_str::<serde_json::Value>(&json_str) else {
+        return fail("LLM produced invalid JSON.".to_string());
+    };
+    let problems = workflow_schema::validate_n8n_workflow(&val);
+    if !problems.is_empty() {
+        return fail(format!("Workflow validation failed: {}", problems.join("; ")));
+    }
+
+    let n8n_url = std::env::var("N8N_API_URL").unwrap_or_else(|_| "http://localhost:5678".to_string());
+    let n8n_key = std::env::var("N8N_API_KEY").unwrap_or_default();
+    let n8n = n8n_api::N8nApi::new(&format!("{}/api/v1", n8n_url), &n8n_key);
+    match n8n.create_workflow(&rec.title, &val, workflow_schema::auto_activate_enabled()).await {
+        Ok(workflow_id) => {
+            if let Err(e) = db::mark_recommendation_approved(rec.id, &workflow_id, &json_str) {
+                println!("⚠️  Workflow created but failed to update DB: {}", e);
+            }
+            println!("✅ Workflow created! ID: {}", workflow_id);
+        }
+        Err(e) => fail(format!("API Import failed: {}", e)),
+    }
+}
+
+/// Shown by commands that need an LLM when running in lite mode.
+fn print_lite_mode_notice() {
+    println!("⚠️  This command needs an LLM (set OPENAI_API_KEY). Lite mode: run 'capabilities' for what works without one.");
+}
+
+/// `chat_id=123 keyword=invoice due` → {chat_id: "123", keyword: "invoice due"};
+/// words without `=` continue the previous value.
+fn parse_template_params(args: &[&str]) -> std::collections::HashMap<String, String> {
+    let mut params = std::collections::HashMap::new();
+    let mut last: Option<String> = None;
+    for arg in args {
+        match arg.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                params.insert(key.to_string(), value.to_string());
+                last = Some(key.to_string());
+            }
+            _ => {
+                if let Some(value) = last.as_ref().and_then(|k| params.get_mut(k)) {
+                    value.push(' ');
+                    value.push_str(arg);
+                }
+            }
+        }
+    }
+    params
+}
+
+/// "3 5 7" or "3,5,7" → [3, 5, 7]; `None` if empty or any id is not a number.
+fn parse_ids(args: &[&str]) -> Option<Vec<i64>> {
+    let ids: Vec<&str> = args.iter().flat_map(|a| a.split(',')).filter(|s| !s.is_empty()).collect();
+    if ids.is_empty() {
+        return None;
+    }
+    ids.iter().map(|s| s.parse().ok()).collect()
+}
+
 fn env_flag(key: &str) -> bool {
     std::env::var(key)
         .ok()
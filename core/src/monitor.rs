@@ -2,10 +2,8 @@ use sysinfo::System;
 use notify::{Watcher, RecursiveMode, Result as NotifyResult, RecommendedWatcher, Config};
 use tokio::sync::mpsc;
 use std::path::Path;
-use chrono::Utc;
 use serde_json::json;
-use uuid::Uuid;
-use crate::schema::{EventEnvelope, ResourceContext};
+use crate::schema::{emit_blocking, EventEnvelope, ResourceContext};
 
 // --- Resource Monitor ---
 
@@ -53,6 +51,68 @@ impl ResourceMonitor {
 
 // --- File Watcher ---
 
+/// What kind of file landed, so patterns can key on "a PDF arrived" rather
+/// than on opaque paths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileKind {
+    /// Lowercased, without the dot.
+    pub extension: Option<String>,
+    pub mime: &'static str,
+    /// document, spreadsheet, image, video, audio, archive, installer, code or other.
+    pub category: &'static str,
+}
+
+const FILE_TYPES: &[(&[&str], &str, &str)] = &[
+    (&["pdf"], "application/pdf", "document"),
+    (&["doc"], "application/msword", "document"),
+    (&["docx"], "application/vnd.openxmlformats-officedocument.wordprocessingml.document", "document"),
+    (&["pages"], "application/vnd.apple.pages", "document"),
+    (&["rtf"], "application/rtf", "document"),
+    (&["txt"], "text/plain", "document"),
+    (&["md"], "text/markdown", "document"),
+    (&["csv"], "text/csv", "spreadsheet"),
+    (&["xls"], "application/vnd.ms-excel", "spreadsheet"),
+    (&["xlsx"], "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", "spreadsheet"),
+    (&["numbers"], "application/vnd.apple.numbers", "spreadsheet"),
+    (&["png"], "image/png", "image"),
+    (&["jpg", "jpeg"], "image/jpeg", "image"),
+    (&["gif"], "image/gif", "image"),
+    (&["heic"], "image/heic", "image"),
+    (&["webp"], "image/webp", "image"),
+    (&["mp4"], "video/mp4", "video"),
+    (&["m4v"], "video/x-m4v", "video"),
+    (&["mov"], "video/quicktime", "video"),
+    (&["mp3"], "audio/mpeg", "audio"),
+    (&["wav"], "audio/wav", "audio"),
+    (&["m4a"], "audio/mp4", "audio"),
+    (&["zip"], "application/zip", "archive"),
+    (&["tar"], "application/x-tar", "archive"),
+    (&["gz", "tgz"], "application/gzip", "archive"),
+    (&["7z"], "application/x-7z-compressed", "archive"),
+    (&["rar"], "application/vnd.rar", "archive"),
+    (&["dmg"], "application/x-apple-diskimage", "installer"),
+    (&["pkg"], "application/vnd.apple.installer+xml", "installer"),
+    (&["json"], "application/json", "code"),
+    (&["rs"], "text/x-rust", "code"),
+    (&["py"], "text/x-python", "code"),
+    (&["js"], "text/javascript", "code"),
+    (&["ts"], "text/x-typescript", "code"),
+    (&["sh"], "application/x-sh", "code"),
+    (&["html"], "text/html", "code"),
+    (&["css"], "text/css", "code"),
+];
+
+/// Classify a file by its extension.
+pub fn classify_file(path: &Path) -> FileKind {
+    let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    let (mime, category) = extension
+        .as_deref()
+        .and_then(|ext| FILE_TYPES.iter().find(|(exts, _, _)| exts.contains(&ext)))
+        .map(|(_, mime, category)| (*mime, *category))
+        .unwrap_or(("application/octet-stream", "other"));
+    FileKind { extension, mime, category }
+}
+
 pub fn spawn_file_watcher(
     path: String,
     log_tx: mpsc::Sender<String>
@@ -71,16 +131,20 @@ pub fn spawn_file_watcher(
             match res {
                 Ok(event) => {
                     // Filter for Create/Modify
+                    if crate::monitoring::is_capture_suppressed() {
+                        continue;
+                    }
                     if event.kind.is_create() || event.kind.is_modify() {
                         for path in event.paths {
                             let filename = path.file_name().unwrap_or_default().to_string_lossy();
                             if !filename.starts_with('.') { // Ignore hidden files
                                 let path_str = path.to_string_lossy().to_string();
+                                let kind = classify_file(&path);
                                 let resource = ResourceContext {
                                     resource_type: "file".to_string(),
                                     id: path_str.clone(),
                                 };
-                                let event = base_envelope(
+                                let event = EventEnvelope::new(
                                     "filesystem",
                                     "filesystem",
                                     "file_created",
@@ -88,16 +152,16 @@ pub fn spawn_file_watcher(
                                     Some(resource),
                                     json!({
                                         "path": path_str,
-                                        "filename": filename.to_string()
+                                        "filename": filename.to_string(),
+                                        "extension": kind.extension,
+                                        "mime": kind.mime,
+                                        "category": kind.category
                                     }),
                                 );
 
-                                if let Ok(log) = serde_json::to_string(&event) {
-                                    if let Err(e) = log_tx.blocking_send(log) {
-                                        eprintln!("⚠️ [Monitor] Channel Full/Closed. Dropping file event: {}", e);
-                                    }
-                                } else {
-                                    eprintln!("⚠️ [Monitor] Failed to serialize file event.");
+                                if let Err(e) = emit_blocking(&log_tx, &event) {
+                                    crate::analyzer::record_dropped("file_watcher");
+                                    eprintln!("⚠️ [Monitor] Dropping file event: {}", e);
                                 }
                             }
                         }
@@ -113,97 +177,142 @@ pub fn spawn_file_watcher(
 
 // --- App Watcher (Active Window Poller) ---
 
+/// A committed app switch after debouncing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppSwitch {
+    pub app: String,
+    pub previous_app: Option<String>,
+    pub previous_dwell_ms: u64,
+}
+
+/// Suppresses alt-tab flutter: an app only counts as "switched to" once it has
+/// stayed frontmost for `dwell`, and A→B→A within that window emits nothing.
+pub struct AppSwitchDebouncer {
+    dwell: std::time::Duration,
+    committed: Option<(String, std::time::Instant)>,
+    pending: Option<(String, std::time::Instant)>,
+}
+
+impl AppSwitchDebouncer {
+    pub fn new(dwell: std::time::Duration) -> Self {
+        Self { dwell, committed: None, pending: None }
+    }
+
+    pub fn observe(&mut self, app: &str, now: std::time::Instant) -> Option<AppSwitch> {
+        if self.committed.as_ref().map(|(a, _)| a == app).unwrap_or(false) {
+            // Came back before the other app settled
+            self.pending = None;
+            return None;
+        }
+
+        let since = match &self.pending {
+            Some((pending_app, since)) if pending_app == app => *since,
+            _ => {
+                self.pending = Some((app.to_string(), now));
+                now
+            }
+        };
+        if now.duration_since(since) < self.dwell {
+            return None;
+        }
+
+        self.pending = None;
+        let previous = self.committed.replace((app.to_string(), since));
+        Some(AppSwitch {
+            app: app.to_string(),
+            previous_dwell_ms: previous
+                .as_ref()
+                .map(|(_, at)| since.saturating_duration_since(*at).as_millis() as u64)
+                .unwrap_or(0),
+            previous_app: previous.map(|(a, _)| a),
+        })
+    }
+}
+
 pub fn spawn_app_watcher(
     log_tx: mpsc::Sender<String>
 ) {
     std::thread::spawn(move || {
-        let mut last_app = String::new();
+        let dwell_ms: u64 = std::env::var("STEER_APP_SWITCH_DWELL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2000);
+        let mut debouncer = AppSwitchDebouncer::new(std::time::Duration::from_millis(dwell_ms));
+        let sensitive = crate::monitoring::SensitiveContext::from_env();
         
         loop {
             // Poll every 2 seconds
             std::thread::sleep(std::time::Duration::from_secs(2));
+            if crate::monitoring::is_paused() {
+                // Start over after a pause instead of counting it as dwell time
+                debouncer = AppSwitchDebouncer::new(std::time::Duration::from_millis(dwell_ms));
+                continue;
+            }
             
             // Get frontmost app name via AppleScript
-            let output = std::process::Command::new("osascript")
-                .arg("-e")
-                .arg("tell application \"System Events\" to name of first application process whose frontmost is true")
-                .output();
+            let output = crate::applescript::timed_output(
+                "frontmost_app",
+                std::process::Command::new("osascript")
+                    .arg("-e")
+                    .arg("tell application \"System Events\" to name of first application process whose frontmost is true"),
+            );
 
             if let Ok(out) = output {
-                if out.status.success() {
-                    let current_app = String::from_utf8_lossy(&out.stdout).trim().to_string();
-                    if !current_app.is_empty() && current_app != last_app {
-                        last_app = current_app.clone();
-
-                        // [Context Enrichment] Get Window Title & URL
-                        let (window_title, browser_url) = crate::applescript::get_active_window_context()
-                            .unwrap_or_else(|_| ("".to_string(), "".to_string()));
-
-                        let resource = ResourceContext {
-                            resource_type: "app".to_string(),
-                            id: current_app.clone(),
-                        };
-                        let mut event = base_envelope(
-                            "app_watcher",
-                            &current_app,
-                            "app_switch",
-                            "P2",
-                            Some(resource),
-                            json!({
-                                "app": current_app,
-                                "window_title": window_title,
-                                "browser_url": browser_url,
-                            }),
-                        );
-                        if !window_title.is_empty() {
-                            event.window_title = Some(window_title);
-                        }
-                        if !browser_url.is_empty() {
-                            event.browser_url = Some(browser_url);
-                        }
+                let current_app = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                if current_app.is_empty() {
+                    continue;
+                }
+                // [Sensitive Context] Nothing about the app is recorded, not even the switch to it
+                let url = if sensitive.needs_url() {
+                    crate::applescript::get_active_window_context().map(|(_, url)| url).unwrap_or_default()
+                } else {
+                    String::new()
+                };
+                if crate::monitoring::observe_frontmost(&sensitive, &current_app, &url, Some(&log_tx)) {
+                    debouncer = AppSwitchDebouncer::new(std::time::Duration::from_millis(dwell_ms));
+                    continue;
+                }
+                if let Some(switch) = debouncer.observe(&current_app, std::time::Instant::now()) {
+                    // [Context Enrichment] Get Window Title & URL
+                    let (window_title, browser_url) = crate::applescript::get_active_window_context()
+                        .unwrap_or_else(|_| ("".to_string(), "".to_string()));
 
-                        if let Ok(log) = serde_json::to_string(&event) {
-                            if let Err(e) = log_tx.blocking_send(log) {
-                                eprintln!("Failed to send app log: {}", e);
-                                break;
-                            }
-                        }
+                    let resource = ResourceContext {
+                        resource_type: "app".to_string(),
+                        id: current_app.clone(),
+                    };
+                    let mut event = EventEnvelope::new(
+                        "app_watcher",
+                        &current_app,
+                        "app_switch",
+                        "P2",
+                        Some(resource),
+                        json!({
+                            "app": current_app,
+                            "window_title": window_title,
+                            "browser_url": browser_url,
+                            "previous_app": switch.previous_app,
+                            "previous_dwell_ms": switch.previous_dwell_ms,
+                        }),
+                    );
+                    if !window_title.is_empty() {
+                        event.window_title = Some(window_title);
+                    }
+                    if !browser_url.is_empty() {
+                        event.browser_url = Some(browser_url);
+                    }
+
+                    if let Err(e) = emit_blocking(&log_tx, &event) {
+                        crate::analyzer::record_dropped("app_watcher");
+                        eprintln!("Failed to send app log: {}", e);
+                        break;
                     }
-                } else if !last_app.is_empty() {
-                    last_app = String::new();
                 }
             }
         }
     });
 }
 
-fn base_envelope(
-    source: &str,
-    app: &str,
-    event_type: &str,
-    priority: &str,
-    resource: Option<ResourceContext>,
-    payload: serde_json::Value,
-) -> EventEnvelope {
-    EventEnvelope {
-        schema_version: "1.0".to_string(),
-        event_id: Uuid::new_v4().to_string(),
-        ts: Utc::now().to_rfc3339(),
-        source: source.to_string(),
-        app: app.to_string(),
-        event_type: event_type.to_string(),
-        priority: priority.to_string(),
-        resource,
-        payload,
-        privacy: None,
-        pid: None,
-        window_id: None,
-        window_title: None,
-        browser_url: None,
-        raw: None,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +320,50 @@ mod tests {
     use std::io::Write;
     use std::time::Duration;
 
+    #[test]
+    fn test_app_switch_debounce() {
+        let t0 = std::time::Instant::now();
+        let secs = |n: u64| t0 + Duration::from_secs(n);
+        let mut d = AppSwitchDebouncer::new(Duration::from_secs(2));
+
+        // First app settles after the dwell time
+        assert_eq!(d.observe("Safari", secs(0)), None);
+        let first = d.observe("Safari", secs(2)).unwrap();
+        assert_eq!(first.app, "Safari");
+        assert_eq!(first.previous_app, None);
+
+        // A -> B -> A flutter is coalesced away
+        assert_eq!(d.observe("Slack", secs(10)), None);
+        assert_eq!(d.observe("Safari", secs(11)), None);
+        assert_eq!(d.observe("Safari", secs(14)), None);
+
+        // A real switch reports how long the previous app was in front
+        assert_eq!(d.observe("Notes", secs(20)), None);
+        let switch = d.observe("Notes", secs(22)).unwrap();
+        assert_eq!(switch.app, "Notes");
+        assert_eq!(switch.previous_app.as_deref(), Some("Safari"));
+        assert_eq!(switch.previous_dwell_ms, 20_000);
+    }
+
+    #[test]
+    fn test_classify_file() {
+        let pdf = classify_file(Path::new("/Users/me/Downloads/Invoice-0042.PDF"));
+        assert_eq!(pdf.extension.as_deref(), Some("pdf"));
+        assert_eq!(pdf.mime, "application/pdf");
+        assert_eq!(pdf.category, "document");
+        assert_eq!(classify_file(Path::new("photo.heic")).category, "image");
+        // Each format has its own type, not the family's oldest one
+        let mime = |name: &str| classify_file(Path::new(name)).mime;
+        assert_eq!(mime("report.docx"), "application/vnd.openxmlformats-officedocument.wordprocessingml.document");
+        assert_eq!(mime("budget.xlsx"), "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet");
+        assert_eq!(mime("voice.m4a"), "audio/mp4");
+        assert_eq!(mime("backup.tar.gz"), "application/gzip");
+        assert_eq!(mime("notes.md"), "text/markdown");
+        let unknown = classify_file(Path::new("Makefile"));
+        assert_eq!(unknown.extension, None);
+        assert_eq!(unknown.category, "other");
+    }
+
     #[test]
     fn test_file_watcher_integration() {
         let temp_dir = std::env::temp_dir().join("steer_monitor_test");
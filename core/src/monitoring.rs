@@ -0,0 +1,382 @@
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+// [Monitoring Pause] Privacy switch for sensitive work: while paused the
+// event tap, the file and app watchers and `POST /events` capture nothing,
+// but the process (API, scheduler, REPL) keeps running. Pausing and resuming
+// are themselves recorded as `monitoring_paused` / `monitoring_resumed`
+// events. A pause can be persisted (a marker file in STEER_HOME) so the agent
+// also starts paused after a restart; resume always clears it.
+//
+// [Sensitive Context] Separately from the pause switch, capture stops on its
+// own while a sensitive app (password manager, banking site) is frontmost.
+// The app watcher checks each frontmost app against the configured lists
+// every 2 s; on macOS the event tap also checks the app its input goes to,
+// so a password typed right after switching is never captured. Entering and
+// leaving such an app is recorded only as a `sensitive_context`
+// marker that says whether capture stopped or started, never which app it was.
+//
+//   STEER_CAPTURE_SENSITIVE_APPS   apps that stop capture (default: common password managers)
+//   STEER_CAPTURE_SENSITIVE_URLS   URL patterns that stop capture, `*` matches anything (default: none)
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MonitoringStatus {
+    pub paused: bool,
+    /// The pause survives a restart
+    pub persisted: bool,
+    pub since: Option<String>,
+    /// A sensitive app is frontmost, so nothing is captured either
+    pub sensitive: bool,
+}
+
+pub struct Monitoring {
+    paused: AtomicBool,
+    since: Mutex<Option<String>>,
+    marker: PathBuf,
+    sensitive: AtomicBool,
+}
+
+impl Monitoring {
+    /// Starts paused when the marker from a persisted pause exists.
+    pub fn new(marker: PathBuf) -> Self {
+        let since = std::fs::read_to_string(&marker).ok().map(|s| s.trim().to_string());
+        Self { paused: AtomicBool::new(since.is_some()), since: Mutex::new(since), marker, sensitive: AtomicBool::new(false) }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Paused, or a sensitive app is frontmost.
+    pub fn is_capture_suppressed(&self) -> bool {
+        self.is_paused() || self.sensitive.load(Ordering::SeqCst)
+    }
+
+    /// Note the frontmost app (and its URL, if a browser); returns whether it
+    /// is sensitive. A change in either direction emits a `sensitive_context` marker.
+    pub fn observe_frontmost(&self, rules: &SensitiveContext, app: &str, url: &str, events: Option<&mpsc::Sender<String>>) -> bool {
+        let sensitive = rules.matches(app, url);
+        if self.sensitive.swap(sensitive, Ordering::SeqCst) != sensitive {
+            announce(events, "sensitive_context", serde_json::json!({ "active": sensitive }));
+        }
+        sensitive
+    }
+
+    /// `observe_frontmost` for the app input was just sent to, whose URL isn't
+    /// known. An app matching by name is sensitive at once; one that doesn't
+    /// only clears the flag when no URL patterns are set, otherwise the app
+    /// watcher decides once it has looked up the URL.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))] // only the native event tap sees input targets
+    pub fn observe_input_app(&self, rules: &SensitiveContext, app: &str, events: Option<&mpsc::Sender<String>>) -> bool {
+        if rules.matches(app, "") || !rules.needs_url() {
+            return self.observe_frontmost(rules, app, "", events);
+        }
+        self.sensitive.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> MonitoringStatus {
+        MonitoringStatus {
+            paused: self.is_paused(),
+            persisted: self.marker.exists(),
+            since: self.since.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            sensitive: self.sensitive.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Stop capturing. Returns false when already paused (a persisted pause
+    /// can still be requested on top of an unpersisted one).
+    pub fn pause(&self, events: Option<&mpsc::Sender<String>>, persist: bool) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let changed = !self.paused.swap(true, Ordering::SeqCst);
+        let mut since = self.since.lock().unwrap_or_else(|e| e.into_inner());
+        if changed {
+            *since = Some(now.clone());
+        }
+        if persist {
+            if let Some(dir) = self.marker.parent() {
+                std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            let at = since.clone().unwrap_or(now);
+            std::fs::write(&self.marker, at).with_context(|| format!("Failed to write {}", self.marker.display()))?;
+        }
+        drop(since);
+        if changed {
+            announce(events, "monitoring_paused", serde_json::json!({ "persisted": persist }));
+        }
+        Ok(changed)
+    }
+
+    /// Capture again and forget a persisted pause. Returns false when not paused.
+    pub fn resume(&self, events: Option<&mpsc::Sender<String>>) -> Result<bool> {
+        if self.marker.exists() {
+            std::fs::remove_file(&self.marker).with_context(|| format!("Failed to remove {}", self.marker.display()))?;
+        }
+        let changed = self.paused.swap(false, Ordering::SeqCst);
+        *self.since.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        if changed {
+            announce(events, "monitoring_resumed", serde_json::json!({ "persisted": false }));
+        }
+        Ok(changed)
+    }
+}
+
+/// Apps and URL patterns that stop capture while frontmost.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SensitiveContext {
+    pub apps: Vec<String>,
+    pub url_patterns: Vec<String>,
+}
+
+impl SensitiveContext {
+    pub fn from_env() -> Self {
+        let list = |key: &str| std::env::var(key).ok().map(|raw| {
+            raw.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>()
+        });
+        Self {
+            apps: list("STEER_CAPTURE_SENSITIVE_APPS").unwrap_or_else(|| {
+                ["1Password", "1Password 7", "Bitwarden", "KeePassXC", "Dashlane", "LastPass", "Keychain Access", "Passwords"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            }),
+            url_patterns: list("STEER_CAPTURE_SENSITIVE_URLS").unwrap_or_default(),
+        }
+    }
+
+    /// The frontmost URL only matters (and is only looked up) when URL patterns are set.
+    pub fn needs_url(&self) -> bool {
+        !self.url_patterns.is_empty()
+    }
+
+    pub fn matches(&self, app: &str, url: &str) -> bool {
+        let app = app.trim().trim_end_matches(".app").to_lowercase();
+        let url = url.trim().to_lowercase();
+        (!app.is_empty() && self.apps.iter().any(|a| a.to_lowercase() == app))
+            || (!url.is_empty() && self.url_patterns.iter().any(|p| wildcard_contains(&url, &p.to_lowercase())))
+    }
+}
+
+/// `pattern` occurs somewhere in `text`, with `*` matching any run of characters.
+fn wildcard_contains(text: &str, pattern: &str) -> bool {
+    let mut rest = text;
+    for part in pattern.split('*').filter(|p| !p.is_empty()) {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn announce(events: Option<&mpsc::Sender<String>>, event_type: &str, payload: serde_json::Value) {
+    let Some(tx) = events else { return };
+    let event = crate::schema::EventEnvelope::new(
+        "monitoring",
+        "system",
+        event_type,
+        "P1",
+        None,
+        payload,
+    );
+    if let Err(e) = crate::schema::emit(tx, &event) {
+        eprintln!("⚠️ [Monitoring] Could not record {}: {}", event_type, e);
+    }
+}
+
+lazy_static! {
+    static ref MONITORING: Monitoring = Monitoring::new(crate::paths::steer_home().join("monitoring_paused"));
+}
+
+/// The pause switch alone (see `is_capture_suppressed`).
+pub fn is_paused() -> bool {
+    MONITORING.is_paused()
+}
+
+/// Why an on-demand capture (screen description, UI snapshot) was refused.
+pub const CAPTURE_SUPPRESSED: &str = "Capture is off while monitoring is paused or a sensitive app is frontmost";
+
+/// Checked by every capture source before it records anything. The app
+/// watcher checks `is_paused` instead, since it has to keep watching to see
+/// a sensitive app leave the foreground.
+pub fn is_capture_suppressed() -> bool {
+    MONITORING.is_capture_suppressed()
+}
+
+pub fn status() -> MonitoringStatus {
+    MONITORING.status()
+}
+
+/// See `Monitoring::observe_frontmost`; logs when capture stops or starts again.
+pub fn observe_frontmost(rules: &SensitiveContext, app: &str, url: &str, events: Option<&mpsc::Sender<String>>) -> bool {
+    log_sensitive_change(|| MONITORING.observe_frontmost(rules, app, url, events))
+}
+
+/// See `Monitoring::observe_input_app`; logs when capture stops or starts again.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))] // only the native event tap sees input targets
+pub fn observe_input_app(rules: &SensitiveContext, app: &str, events: Option<&mpsc::Sender<String>>) -> bool {
+    log_sensitive_change(|| MONITORING.observe_input_app(rules, app, events))
+}
+
+fn log_sensitive_change(observe: impl FnOnce() -> bool) -> bool {
+    let was = MONITORING.status().sensitive;
+    let sensitive = observe();
+    if sensitive != was {
+        println!("{}", if sensitive { "🙈 Sensitive app in front: capture stopped" } else { "👀 Sensitive app left the front: capture resumed" });
+    }
+    sensitive
+}
+
+/// App name from an executable path: the outermost `.app` bundle
+/// ("/Applications/1Password.app/Contents/MacOS/1Password" is "1Password"),
+/// or the file name for a bare executable.
+pub fn app_name_from_path(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    let name = parts
+        .iter()
+        .find_map(|p| p.strip_suffix(".app"))
+        .or_else(|| parts.last().copied())?;
+    Some(name.to_string())
+}
+
+fn audit(before: &MonitoringStatus) {
+    let label = |s: &MonitoringStatus| match (s.paused, s.persisted) {
+        (false, _) => "active",
+        (true, false) => "paused",
+        (true, true) => "paused (persisted)",
+    };
+    let after = MONITORING.status();
+    if let Err(e) = crate::db::record_config_change("monitoring", Some(label(before)), Some(label(&after))) {
+        eprintln!("⚠️ [Monitoring] Could not audit the change: {}", e);
+    }
+}
+
+pub fn pause(events: Option<&mpsc::Sender<String>>, persist: bool) -> Result<bool> {
+    let before = MONITORING.status();
+    let changed = MONITORING.pause(events, persist)?;
+    audit(&before);
+    if changed {
+        println!("⏸️  Monitoring paused{}", if persist { " (stays paused after restart)" } else { "" });
+    }
+    Ok(changed)
+}
+
+pub fn resume(events: Option<&mpsc::Sender<String>>) -> Result<bool> {
+    let before = MONITORING.status();
+    let changed = MONITORING.resume(events)?;
+    audit(&before);
+    if changed {
+        println!("▶️  Monitoring resumed");
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn received(rx: &mut mpsc::Receiver<String>) -> Vec<String> {
+        let mut types = Vec::new();
+        while let Ok(json) = rx.try_recv() {
+            let event: crate::schema::EventEnvelope = serde_json::from_str(&json).unwrap();
+            types.push(event.event_type);
+        }
+        types
+    }
+
+    #[test]
+    fn test_pause_and_resume_announce_and_persist() {
+        let dir = std::env::temp_dir().join(format!("steer-monitoring-{}", uuid::Uuid::new_v4()));
+        let marker = dir.join("monitoring_paused");
+        let (tx, mut rx) = mpsc::channel(8);
+
+        let monitoring = Monitoring::new(marker.clone());
+        assert!(!monitoring.is_paused());
+        assert!(monitoring.pause(Some(&tx), false).unwrap());
+        assert!(!monitoring.pause(Some(&tx), false).unwrap());
+        assert_eq!(received(&mut rx), vec!["monitoring_paused"]);
+        assert!(!monitoring.status().persisted);
+
+        // An unpersisted pause is gone after a restart; a persisted one isn't
+        assert!(!Monitoring::new(marker.clone()).is_paused());
+        monitoring.pause(Some(&tx), true).unwrap();
+        let restarted = Monitoring::new(marker.clone());
+        assert!(restarted.is_paused());
+        assert_eq!(restarted.status().since, monitoring.status().since);
+
+        assert!(restarted.resume(Some(&tx)).unwrap());
+        assert!(!restarted.resume(Some(&tx)).unwrap());
+        assert_eq!(received(&mut rx), vec!["monitoring_resumed"]);
+        assert!(!marker.exists());
+        assert!(!Monitoring::new(marker).is_paused());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_sensitive_frontmost_app_stops_capture() {
+        let marker = std::env::temp_dir().join(format!("steer-monitoring-{}", uuid::Uuid::new_v4())).join("monitoring_paused");
+        let (tx, mut rx) = mpsc::channel(8);
+        let rules = SensitiveContext {
+            apps: vec!["1Password".to_string()],
+            url_patterns: vec!["*.mybank.com/*".to_string()],
+        };
+        let monitoring = Monitoring::new(marker);
+
+        assert!(!monitoring.observe_frontmost(&rules, "Safari", "https://example.com/", Some(&tx)));
+        assert!(!monitoring.is_capture_suppressed());
+        assert!(received(&mut rx).is_empty());
+
+        assert!(monitoring.observe_frontmost(&rules, "1password", "", Some(&tx)));
+        assert!(monitoring.is_capture_suppressed());
+        assert!(!monitoring.is_paused());
+        assert!(monitoring.status().sensitive);
+        // Staying in front emits nothing more
+        monitoring.observe_frontmost(&rules, "1Password", "", Some(&tx));
+        assert!(!monitoring.observe_frontmost(&rules, "Safari", "https://example.com/", Some(&tx)));
+        assert!(!monitoring.is_capture_suppressed());
+
+        assert!(monitoring.observe_frontmost(&rules, "Google Chrome", "https://www.MyBank.com/accounts", Some(&tx)));
+        assert!(!rules.matches("Google Chrome", "https://mybank.com.evil.io"));
+
+        let mut markers = Vec::new();
+        while let Ok(json) = rx.try_recv() {
+            let event: crate::schema::EventEnvelope = serde_json::from_str(&json).unwrap();
+            assert_eq!(event.event_type, "sensitive_context");
+            // Says only whether capture stopped, not which app or page
+            assert!(!json.contains("1Password") && !json.contains("mybank"), "{}", json);
+            markers.push(event.payload["active"].as_bool().unwrap());
+        }
+        assert_eq!(markers, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_input_to_a_sensitive_app_stops_capture_at_once() {
+        let marker = std::env::temp_dir().join(format!("steer-monitoring-{}", uuid::Uuid::new_v4())).join("monitoring_paused");
+        let (tx, mut rx) = mpsc::channel(8);
+        let by_name = SensitiveContext { apps: vec!["1Password".to_string()], url_patterns: vec![] };
+        let monitoring = Monitoring::new(marker);
+
+        let app = app_name_from_path("/Applications/1Password.app/Contents/MacOS/1Password").unwrap();
+        assert!(monitoring.observe_input_app(&by_name, &app, Some(&tx)));
+        assert!(monitoring.is_capture_suppressed());
+        assert!(!monitoring.observe_input_app(&by_name, "TextEdit", Some(&tx)));
+        assert_eq!(received(&mut rx), vec!["sensitive_context", "sensitive_context"]);
+
+        // With URL patterns the app watcher has to look up the URL before clearing
+        let with_urls = SensitiveContext { url_patterns: vec!["*.mybank.com/*".to_string()], ..by_name };
+        assert!(monitoring.observe_input_app(&with_urls, "1Password", Some(&tx)));
+        assert!(monitoring.observe_input_app(&with_urls, "Google Chrome", Some(&tx)));
+        assert!(!monitoring.observe_frontmost(&with_urls, "Google Chrome", "https://example.com/", Some(&tx)));
+
+        assert_eq!(app_name_from_path("/usr/local/bin/kitty").as_deref(), Some("kitty"));
+        assert_eq!(
+            app_name_from_path("/Applications/1Password.app/Contents/Library/LoginItems/Helper.app/Contents/MacOS/Helper").as_deref(),
+            Some("1Password")
+        );
+        assert_eq!(app_name_from_path(""), None);
+    }
+}
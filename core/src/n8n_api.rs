@@ -3,6 +3,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use anyhow::Result;
+use crate::integrations::rate_limit;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +32,37 @@ pub struct Credential {
     pub type_name: String,
 }
 
+/// n8n didn't answer properly: it returned a server error. Connection
+/// failures and timeouts arrive as `reqwest::Error`; see `is_unavailable`.
+#[derive(Debug)]
+pub struct Unavailable(pub String);
+
+impl std::fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Unavailable {}
+
+/// Whether `error` says n8n itself is down (connection refused, timeout,
+/// 5xx), rather than that it refused this particular request.
+pub fn is_unavailable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<Unavailable>()
+            || cause.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// The CLI fallback's error, keeping the API error as its cause when that one
+/// showed n8n was down, so callers can still tell.
+fn fallback_error(api_error: Option<anyhow::Error>, cli_error: anyhow::Error) -> anyhow::Error {
+    match api_error {
+        Some(api_error) if is_unavailable(&api_error) => api_error.context(cli_error.to_string()),
+        _ => cli_error,
+    }
+}
+
 #[allow(dead_code)]
 pub struct N8nApi {
     base_url: String,
@@ -54,6 +86,12 @@ impl N8nApi {
         Ok(Self::new(&base_url, &api_key))
     }
 
+    /// Non-intrusive liveness probe (never starts the server).
+    pub async fn is_reachable(&self, timeout: std::time::Duration) -> bool {
+        let health_url = self.base_url.replace("localhost", "127.0.0.1").replace("/api/v1", "/");
+        self.client.get(&health_url).timeout(timeout).send().await.is_ok()
+    }
+
     /// Check if n8n is running, and start it if not
     pub async fn ensure_server_running(&self) -> Result<()> {
         // Use 127.0.0.1 to avoid macOS localhost DNS lag
@@ -127,13 +165,30 @@ impl N8nApi {
         }
     }
 
-    /// List available credentials
-    pub async fn list_credentials(&self) -> Result<Vec<Credential>> {
-        let url = format!("{}/credentials", self.base_url);
+    /// Strict read-only auth check for `test integrations`: unlike
+    /// `verify_auth`, any non-success status is an error.
+    pub async fn test_connection(&self) -> Result<String> {
+        if self.api_key.is_empty() {
+            return Err(anyhow::anyhow!("N8N_API_KEY not set"));
+        }
+        let url = format!("{}/workflows?limit=1", self.base_url);
         let resp = self.client.get(&url)
             .header("X-N8N-API-KEY", &self.api_key)
+            .timeout(std::time::Duration::from_secs(5))
             .send()
             .await?;
+        if !resp.status().is_success() {
+            return Err(anyhow::anyhow!("n8n API returned {}", resp.status()));
+        }
+        Ok(self.base_url.clone())
+    }
+
+    /// List available credentials
+    pub async fn list_credentials(&self) -> Result<Vec<Credential>> {
+        let url = format!("{}/credentials", self.base_url);
+        let req = self.client.get(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        let resp = rate_limit::send("n8n", req).await?;
             
         if !resp.status().is_success() {
             return Ok(Vec::new()); // Return empty if failed (e.g. auth error)
@@ -154,6 +209,7 @@ impl N8nApi {
 
     /// Create a new workflow (Hybrid: API first, then CLI fallback with ID retrieval)
     pub async fn create_workflow(&self, name: &str, workflow_json: &Value, active: bool) -> Result<String> {
+        crate::safe_mode::check("n8n workflow changes are")?;
         // 1. Validate JSON (repair to minimal workflow if empty)
         let mut normalized = workflow_json.clone();
         
@@ -193,11 +249,15 @@ impl N8nApi {
         }
 
         // 3. Try API
+        let mut api_error = None;
         if !self.api_key.is_empty() && self.api_key != "placeholder" {
             println!("🌐 Attempting to create workflow via API...");
             match self.create_workflow_api(name, &normalized, active).await {
                 Ok(id) => return Ok(id),
-                Err(e) => println!("⚠️ API creation failed ({}). Falling back to CLI...", e),
+                Err(e) => {
+                    println!("⚠️ API creation failed ({}). Falling back to CLI...", e);
+                    api_error = Some(e);
+                }
             }
         } else {
             println!("ℹ️ No API Key configured. Using CLI mode.");
@@ -205,12 +265,13 @@ impl N8nApi {
 
         // 4. Fallback to CLI (Strict Local Check)
         if !self.base_url.contains("localhost") && !self.base_url.contains("127.0.0.1") {
-             return Err(anyhow::anyhow!("❌ CLI Fallback aborted: n8n is remote ({}). CLI only works for local instances.", self.base_url));
+             let cli_error = anyhow::anyhow!("❌ CLI Fallback aborted: n8n is remote ({}). CLI only works for local instances.", self.base_url);
+             return Err(fallback_error(api_error, cli_error));
         }
 
         // 5. Run CLI Import
         if let Err(e) = self.create_workflow_cli(name, &normalized, active).await {
-            return Err(anyhow::anyhow!("❌ CLI Fallback Failed: {}", e));
+            return Err(fallback_error(api_error, anyhow::anyhow!("❌ CLI Fallback Failed: {}", e)));
         }
         
         // 6. Retrieve ID from DB (Crucial Step for Management)
@@ -230,14 +291,17 @@ impl N8nApi {
         // NOTE: Some n8n versions reject `active` as read-only on create.
         // We always create inactive here; activation can be done via a separate endpoint if needed.
 
-        let resp = self.client.post(&url)
+        let req = self.client.post(&url)
             .header("X-N8N-API-KEY", &self.api_key)
-            .json(&body)
-            .send()
-            .await?;
+            .json(&body);
+        let resp = rate_limit::send("n8n", req).await?;
 
-        if !resp.status().is_success() {
+        let status = resp.status();
+        if !status.is_success() {
             let error_text = resp.text().await?;
+            if status.is_server_error() {
+                return Err(Unavailable(format!("n8n API Error ({}): {}", status, error_text)).into());
+            }
             return Err(anyhow::anyhow!("n8n API Error: {}", error_text));
         }
 
@@ -399,12 +463,13 @@ impl N8nApi {
 
     /// Activate a workflow
     pub async fn activate_workflow(&self, id: &str) -> Result<()> {
+        crate::safe_mode::check("n8n workflow changes are")?;
         let url = format!("{}/workflows/{}/activate", self.base_url, id);
         
-        let resp = self.client.post(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.post(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        // Setting the same state twice is harmless
+        let resp = rate_limit::send_idempotent("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -415,12 +480,13 @@ impl N8nApi {
 
     /// Deactivate a workflow
     pub async fn deactivate_workflow(&self, id: &str) -> Result<()> {
+        crate::safe_mode::check("n8n workflow changes are")?;
         let url = format!("{}/workflows/{}/deactivate", self.base_url, id);
         
-        let resp = self.client.post(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.post(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        // Setting the same state twice is harmless
+        let resp = rate_limit::send_idempotent("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -433,10 +499,9 @@ impl N8nApi {
     pub async fn get_workflow(&self, id: &str) -> Result<WorkflowStatus> {
         let url = format!("{}/workflows/{}", self.base_url, id);
         
-        let resp = self.client.get(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.get(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        let resp = rate_limit::send("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -457,10 +522,9 @@ impl N8nApi {
     pub async fn list_workflows(&self) -> Result<Vec<WorkflowStatus>> {
         let url = format!("{}/workflows", self.base_url);
         
-        let resp = self.client.get(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.get(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        let resp = rate_limit::send("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -485,13 +549,13 @@ impl N8nApi {
 
     /// Execute a workflow manually
     pub async fn execute_workflow(&self, id: &str) -> Result<ExecutionResult> {
+        crate::safe_mode::check("running n8n workflows is")?;
         let url = format!("{}/workflows/{}/run", self.base_url, id);
         
-        let resp = self.client.post(&url)
+        let req = self.client.post(&url)
             .header("X-N8N-API-KEY", &self.api_key)
-            .json(&json!({}))
-            .send()
-            .await?;
+            .json(&json!({}));
+        let resp = rate_limit::send("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -512,10 +576,9 @@ impl N8nApi {
     pub async fn list_executions(&self, workflow_id: &str, limit: u32) -> Result<Vec<ExecutionResult>> {
         let url = format!("{}/executions?workflowId={}&limit={}", self.base_url, workflow_id, limit);
         
-        let resp = self.client.get(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.get(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        let resp = rate_limit::send("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -542,10 +605,9 @@ impl N8nApi {
     pub async fn delete_workflow(&self, id: &str) -> Result<()> {
         let url = format!("{}/workflows/{}", self.base_url, id);
         
-        let resp = self.client.delete(&url)
-            .header("X-N8N-API-KEY", &self.api_key)
-            .send()
-            .await?;
+        let req = self.client.delete(&url)
+            .header("X-N8N-API-KEY", &self.api_key);
+        let resp = rate_limit::send("n8n", req).await?;
 
         if !resp.status().is_success() {
             let error_text = resp.text().await?;
@@ -0,0 +1,387 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use crate::db;
+use crate::n8n_api::{N8nApi, WorkflowStatus};
+use std::collections::HashSet;
+use crate::visual_driver;
+
+// [Phantom Hand] When the n8n API import fails, the visual fallback drives the
+// n8n UI instead. Each completed UI step is recorded under a session key in
+// the goal step transcript, so a retry resumes where the last attempt stopped
+// instead of starting over. Once the UI steps finish, the API import is tried
+// again: the UI run often fixes whatever blocked it (login, server start).
+//
+//   STEER_N8N_FALLBACK_RETRIES=1    extra attempts at the UI steps
+//
+// When n8n itself is down, driving the UI only burns time, so a circuit
+// breaker counts consecutive API failures that show it is down (connection
+// errors, timeouts, 5xx); n8n refusing the workflow doesn't count. After STEER_N8N_BREAKER_THRESHOLD
+// of them it opens: imports fail fast with "n8n unreachable" and no fallback
+// until the cooldown ends. The next import after that is a single API probe
+// with no fallback. If the probe fails the breaker re-opens with double the
+// cooldown (capped at MAX_COOLDOWN_SECS); if it succeeds the breaker closes.
+//
+//   STEER_N8N_BREAKER_THRESHOLD=3       consecutive API failures before opening
+//   STEER_N8N_BREAKER_COOLDOWN_SECS=60  first cooldown; doubles on each re-open
+
+const DECISION_SOURCE: &str = "n8n_fallback";
+const MAX_COOLDOWN_SECS: i64 = 1800;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    ImportedViaApi { workflow_id: String },
+    /// The UI steps completed. `workflow_id` is set when the API import then
+    /// succeeded or the UI's new workflow (an id not listed before it ran) turned up.
+    ImportedViaUi { workflow_id: Option<String>, session_key: String },
+    FailedBoth { api_error: String, ui_error: String, session_key: String },
+    /// The breaker is open; neither the API nor the UI was driven (or only
+    /// the half-open API probe was, and it failed).
+    Unreachable { api_error: Option<String>, retry_in_secs: i64 },
+}
+
+impl ImportOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ImportedViaApi { .. } => "imported_via_api",
+            Self::ImportedViaUi { .. } => "imported_via_ui",
+            Self::FailedBoth { .. } => "failed_both",
+            Self::Unreachable { .. } => "n8n_unreachable",
+        }
+    }
+}
+
+fn fallback_retries() -> u32 {
+    std::env::var("STEER_N8N_FALLBACK_RETRIES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BreakerStatus {
+    /// "closed", "open" or "half_open" (cooldown over, next import probes the API)
+    pub state: &'static str,
+    pub consecutive_failures: u32,
+    pub threshold: u32,
+    pub retry_in_secs: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// What an import may do right now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Admission {
+    /// Try the API and fall back to the UI if it fails
+    Normal,
+    /// Cooldown over: try the API once, but don't drive the UI
+    Probe,
+    Rejected { retry_in_secs: i64 },
+}
+
+#[derive(Debug)]
+pub struct Breaker {
+    threshold: u32,
+    base_cooldown_secs: i64,
+    consecutive_failures: u32,
+    /// Times the breaker has opened since it last closed; sets the cooldown
+    trips: u32,
+    open_until: Option<chrono::DateTime<chrono::Utc>>,
+    last_error: Option<String>,
+}
+
+impl Breaker {
+    pub fn new(threshold: u32, base_cooldown_secs: i64) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            base_cooldown_secs: base_cooldown_secs.max(1),
+            consecutive_failures: 0,
+            trips: 0,
+            open_until: None,
+            last_error: None,
+        }
+    }
+
+    fn from_env() -> Self {
+        Self::new(env_or("STEER_N8N_BREAKER_THRESHOLD", 3), env_or("STEER_N8N_BREAKER_COOLDOWN_SECS", 60))
+    }
+
+    pub fn admit(&self, now: chrono::DateTime<chrono::Utc>) -> Admission {
+        match self.open_until {
+            Some(until) if now < until => Admission::Rejected { retry_in_secs: (until - now).num_seconds().max(1) },
+            Some(_) => Admission::Probe,
+            None => Admission::Normal,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.trips = 0;
+        self.open_until = None;
+        self.last_error = None;
+    }
+
+    /// Count an API failure. Returns true when the breaker is (now) open, in
+    /// which case the UI fallback should not run.
+    pub fn record_failure(&mut self, now: chrono::DateTime<chrono::Utc>, error: &str) -> bool {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.to_string());
+        let probing = self.open_until.is_some();
+        if !probing && self.consecutive_failures < self.threshold {
+            return false;
+        }
+        let cooldown = self.base_cooldown_secs.saturating_mul(1 << self.trips.min(20)).min(MAX_COOLDOWN_SECS);
+        self.trips += 1;
+        self.open_until = Some(now + chrono::Duration::seconds(cooldown));
+        true
+    }
+
+    pub fn status(&self, now: chrono::DateTime<chrono::Utc>) -> BreakerStatus {
+        let (state, retry_in_secs) = match self.admit(now) {
+            Admission::Normal => ("closed", None),
+            Admission::Probe => ("half_open", None),
+            Admission::Rejected { retry_in_secs } => ("open", Some(retry_in_secs)),
+        };
+        BreakerStatus {
+            state,
+            consecutive_failures: self.consecutive_failures,
+            threshold: self.threshold,
+            retry_in_secs,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+lazy_static! {
+    static ref BREAKER: Mutex<Breaker> = Mutex::new(Breaker::from_env());
+}
+
+fn breaker() -> std::sync::MutexGuard<'static, Breaker> {
+    BREAKER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn breaker_status() -> BreakerStatus {
+    breaker().status(chrono::Utc::now())
+}
+
+/// Index of the first UI step not yet completed in `session_key`.
+pub fn resume_point(session_key: &str) -> usize {
+    db::list_goal_steps(session_key, 1000)
+        .unwrap_or_default()
+        .iter()
+        .filter(|s| s.outcome == "success")
+        .map(|s| s.step_index as usize + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+fn record_ui_step(session_key: &str, index: usize, description: &str, outcome: &str, error: Option<String>) {
+    let entry = db::GoalStep {
+        session_key: session_key.to_string(),
+        step_index: index as i64,
+        action_type: "VISUAL".to_string(),
+        description: description.to_string(),
+        decision_source: DECISION_SOURCE.to_string(),
+        outcome: outcome.to_string(),
+        error,
+        ..Default::default()
+    };
+    if let Err(e) = db::insert_goal_step(&entry) {
+        eprintln!("⚠️ Failed to record fallback step: {}", e);
+    }
+}
+
+/// Run (or resume) the visual fallback steps for `session_key`.
+pub async fn run_visual_fallback(session_key: &str) -> anyhow::Result<()> {
+    let driver = visual_driver::n8n_fallback_create_workflow();
+    let start = resume_point(session_key);
+    if start >= driver.steps().len() {
+        return Ok(());
+    }
+    if start > 0 {
+        println!("👻 Resuming visual fallback at step {}/{}", start + 1, driver.steps().len());
+    }
+    let result = driver
+        .execute_from(None, start, |i, step| record_ui_step(session_key, i, &step.description, "success", None))
+        .await;
+    if let Err(e) = &result {
+        let failed_at = resume_point(session_key);
+        let description = driver.steps().get(failed_at).map(|s| s.description.clone()).unwrap_or_default();
+        record_ui_step(session_key, failed_at, &description, "failed", Some(e.to_string()));
+    }
+    result
+}
+
+/// Import `workflow` through the API, falling back to the UI (with retries
+/// that resume from the last completed step) and then retrying the API.
+/// Fails fast with `Unreachable` while the breaker is open.
+pub async fn import_workflow(n8n: &N8nApi, name: &str, workflow: &Value, active: bool) -> ImportOutcome {
+    let admission = breaker().admit(chrono::Utc::now());
+    if let Admission::Rejected { retry_in_secs } = admission {
+        return ImportOutcome::Unreachable { api_error: None, retry_in_secs };
+    }
+    let (api_error, unavailable) = match n8n.create_workflow(name, workflow, active).await {
+        Ok(workflow_id) => {
+            breaker().record_success();
+            return ImportOutcome::ImportedViaApi { workflow_id };
+        }
+        Err(e) => (e.to_string(), crate::n8n_api::is_unavailable(&e)),
+    };
+    println!("❌ API Import failed: {}", api_error);
+    let opened = {
+        let now = chrono::Utc::now();
+        let mut breaker = breaker();
+        if unavailable {
+            breaker.record_failure(now, &api_error).then(|| breaker.status(now))
+        } else {
+            // n8n answered, so it is up: a rejected workflow is no reason to stop trying it
+            breaker.record_success();
+            None
+        }
+    };
+    if let Some(status) = opened {
+        println!(
+            "🔌 n8n unreachable after {} consecutive failure(s); skipping visual fallback",
+            status.consecutive_failures
+        );
+        return ImportOutcome::Unreachable { api_error: Some(api_error), retry_in_secs: status.retry_in_secs.unwrap_or(0) };
+    }
+    println!("👻 Activating Visual Fallback (Phantom Hand)...");
+    // Ids present before the UI runs, so the one it creates can be told apart
+    // from older workflows that happen to share the name
+    let known: Option<HashSet<String>> = n8n
+        .list_workflows()
+        .await
+        .ok()
+        .map(|list| list.into_iter().map(|w| w.id).collect());
+
+    let session_key = format!("n8n-fallback-{}", uuid::Uuid::new_v4());
+    let attempts = fallback_retries() + 1;
+    let mut ui_error = String::new();
+    for attempt in 1..=attempts {
+        match run_visual_fallback(&session_key).await {
+            Ok(()) => {
+                ui_error.clear();
+                break;
+            }
+            Err(e) => {
+                ui_error = e.to_string();
+                println!("⚠️ Visual fallback attempt {}/{} failed: {}", attempt, attempts, ui_error);
+            }
+        }
+    }
+    if !ui_error.is_empty() {
+        return ImportOutcome::FailedBoth { api_error, ui_error, session_key };
+    }
+
+    // The UI run may have fixed what blocked the API; don't create a duplicate if it already exists
+    let existing = match &known {
+        Some(known) => n8n.list_workflows().await.ok().and_then(|list| created_since(known, &list)),
+        None => None,
+    };
+    let workflow_id = match existing {
+        Some(id) => Some(id),
+        None => n8n.create_workflow(name, workflow, active).await.ok(),
+    };
+    if workflow_id.is_some() {
+        breaker().record_success();
+    }
+    ImportOutcome::ImportedViaUi { workflow_id, session_key }
+}
+
+/// The id of the one workflow in `now` that isn't in `before`; `None` when
+/// there is none or several (something else created one meanwhile).
+fn created_since(before: &HashSet<String>, now: &[WorkflowStatus]) -> Option<String> {
+    let mut created = now.iter().filter(|w| !before.contains(&w.id));
+    match (created.next(), created.next()) {
+        (Some(workflow), None) => Some(workflow.id.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_point_follows_recorded_progress() {
+        db::init().ok();
+        let session = format!("n8n-fallback-{}", uuid::Uuid::new_v4());
+        assert_eq!(resume_point(&session), 0);
+
+        record_ui_step(&session, 0, "Open n8n", "success", None);
+        record_ui_step(&session, 1, "Wait", "success", None);
+        record_ui_step(&session, 2, "Click Create Workflow", "failed", Some("Critical Click Failed".to_string()));
+        assert_eq!(resume_point(&session), 2);
+
+        record_ui_step(&session, 2, "Click Create Workflow", "success", None);
+        assert_eq!(resume_point(&session), 3);
+    }
+
+    #[test]
+    fn test_ui_workflow_is_found_by_id_not_name() {
+        let workflow = |id: &str, name: &str| WorkflowStatus {
+            id: id.to_string(),
+            name: name.to_string(),
+            active: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let before: HashSet<String> = ["1".to_string()].into();
+        // An older workflow with the same name is not the one the UI created
+        let now = vec![workflow("1", "Agent Generated Workflow"), workflow("7", "My workflow")];
+        assert_eq!(created_since(&before, &now), Some("7".to_string()));
+        assert_eq!(created_since(&before, &now[..1]), None);
+
+        let crowded = vec![workflow("1", "Agent Generated Workflow"), workflow("7", "My workflow"), workflow("8", "Other")];
+        assert_eq!(created_since(&before, &crowded), None);
+    }
+
+    #[test]
+    fn test_repeated_failures_trip_breaker_and_suppress_fallback() {
+        let start = chrono::Utc::now();
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+        let mut breaker = Breaker::new(3, 60);
+
+        // The first two failures still get the UI fallback; the third opens the breaker
+        assert_eq!(breaker.admit(at(0)), Admission::Normal);
+        assert!(!breaker.record_failure(at(0), "connection refused"));
+        assert!(!breaker.record_failure(at(1), "connection refused"));
+        assert!(breaker.record_failure(at(2), "connection refused"));
+        assert_eq!(breaker.admit(at(3)), Admission::Rejected { retry_in_secs: 59 });
+        assert_eq!(breaker.status(at(3)).state, "open");
+
+        // After the cooldown one probe is allowed; failing it doubles the cooldown
+        assert_eq!(breaker.admit(at(62)), Admission::Probe);
+        assert!(breaker.record_failure(at(62), "connection refused"));
+        assert_eq!(breaker.admit(at(63)), Admission::Rejected { retry_in_secs: 119 });
+
+        // A successful probe closes it and resets the count
+        assert_eq!(breaker.admit(at(183)), Admission::Probe);
+        breaker.record_success();
+        assert_eq!(breaker.status(at(183)), BreakerStatus {
+            state: "closed",
+            consecutive_failures: 0,
+            threshold: 3,
+            retry_in_secs: None,
+            last_error: None,
+        });
+        assert!(!breaker.record_failure(at(184), "timeout"));
+    }
+
+    #[tokio::test]
+    async fn test_only_an_unreachable_n8n_counts_against_the_breaker() {
+        use crate::n8n_api::{is_unavailable, Unavailable};
+        assert!(is_unavailable(&Unavailable("n8n API Error (502 Bad Gateway): upstream".to_string()).into()));
+        assert!(is_unavailable(&anyhow::Error::new(Unavailable("down".to_string())).context("❌ CLI Fallback Failed: npx")));
+        assert!(!is_unavailable(&anyhow::anyhow!("n8n API Error: request/body must have required property 'name'")));
+
+        // Nothing listens on the discard port: a connection error
+        let refused = reqwest::Client::new().get("http://127.0.0.1:9/").send().await.unwrap_err();
+        assert!(is_unavailable(&refused.into()));
+    }
+}
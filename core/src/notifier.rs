@@ -1,7 +1,127 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use anyhow::{Context, Result};
 use crate::send_policy::{self, SendDecision};
 
+// [Throttle] Proactive notifications (recommendations and the like) go through
+// `notify`, which allows at most NOTIFY_MAX_PER_MINUTE per category and drops
+// a repeat of the same notification (title and message) within
+// NOTIFY_DEDUP_SECS; a shared title or message alone says nothing about
+// whether it is a repeat. Held-back ones are
+// counted and later sent as one "N new <category>" notification. Direct
+// `send` calls (step confirmations) are never held back.
+
+const THROTTLE_TITLE: &str = "Steer OS";
+
+struct Throttle {
+    per_minute: usize,
+    dedup: Duration,
+    /// Send times in the last minute, per category
+    sent: HashMap<String, VecDeque<Instant>>,
+    /// Last send time per (category, hash of title and message)
+    seen: HashMap<(String, u64), Instant>,
+    /// Notifications held back since the last send, per category
+    held: HashMap<String, usize>,
+}
+
+impl Throttle {
+    fn new(per_minute: usize, dedup: Duration) -> Self {
+        Self { per_minute: per_minute.max(1), dedup, sent: HashMap::new(), seen: HashMap::new(), held: HashMap::new() }
+    }
+
+    fn from_env() -> Self {
+        let env_u64 = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        };
+        Self::new(env_u64("NOTIFY_MAX_PER_MINUTE", 3) as usize, Duration::from_secs(env_u64("NOTIFY_DEDUP_SECS", 60)))
+    }
+
+    fn recent(&mut self, category: &str, now: Instant) -> &mut VecDeque<Instant> {
+        let window = self.sent.entry(category.to_string()).or_default();
+        while window.front().is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(60)) {
+            window.pop_front();
+        }
+        window
+    }
+
+    /// True if the notification may go out now; otherwise it is counted as held.
+    fn admit(&mut self, category: &str, title: &str, message: &str, now: Instant) -> bool {
+        let mut hasher = DefaultHasher::new();
+        (title, message).hash(&mut hasher);
+        let key = (category.to_string(), hasher.finish());
+        let duplicate = self.seen.get(&key).is_some_and(|t| now.duration_since(*t) < self.dedup);
+        let per_minute = self.per_minute;
+        let window = self.recent(category, now);
+        if duplicate || window.len() >= per_minute {
+            *self.held.entry(category.to_string()).or_default() += 1;
+            return false;
+        }
+        window.push_back(now);
+        self.seen.insert(key, now);
+        true
+    }
+
+    /// Held counts that may go out as a summary now: the dedup window since
+    /// the category's last send has passed and the rate allows one more.
+    fn flush(&mut self, now: Instant) -> Vec<(String, usize)> {
+        let categories: Vec<String> = self.held.keys().cloned().collect();
+        let mut ready = Vec::new();
+        for category in categories {
+            let (per_minute, dedup) = (self.per_minute, self.dedup);
+            let window = self.recent(&category, now);
+            let quiet = window.back().is_none_or(|t| now.duration_since(*t) >= dedup);
+            if quiet && window.len() < per_minute {
+                window.push_back(now);
+                if let Some(count) = self.held.remove(&category) {
+                    ready.push((category, count));
+                }
+            }
+        }
+        ready.sort();
+        ready
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref THROTTLE: Mutex<Throttle> = Mutex::new(Throttle::from_env());
+}
+
+fn summary(category: &str, count: usize) -> String {
+    format!("{} new {}", count, category)
+}
+
+fn send_held(now: Instant) {
+    let ready = THROTTLE.lock().map(|mut t| t.flush(now)).unwrap_or_default();
+    for (category, count) in ready {
+        let _ = send(THROTTLE_TITLE, &summary(&category, count));
+    }
+}
+
+/// Send a proactive notification in `category` (a plural noun such as
+/// "recommendations"), subject to the per-category rate limit and dedup.
+pub fn notify(category: &str, title: &str, message: &str) -> Result<()> {
+    let now = Instant::now();
+    send_held(now);
+    let admitted = THROTTLE.lock().map(|mut t| t.admit(category, title, message, now)).unwrap_or(true);
+    if admitted {
+        return send(title, message);
+    }
+    println!("🔕 [NOTIFICATION] Held back ({}): {}: {}", category, title, message);
+    // Make sure the held count goes out even if nothing else is sent
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        let wait = THROTTLE.lock().map(|t| t.dedup).unwrap_or_default().max(Duration::from_secs(1));
+        handle.spawn(async move {
+            tokio::time::sleep(wait).await;
+            send_held(Instant::now());
+        });
+    }
+    Ok(())
+}
+
 pub fn send(title: &str, message: &str) -> Result<()> {
     if matches!(send_policy::should_send(title, message), SendDecision::Deny) {
         println!("🔕 [NOTIFICATION] Suppressed by policy: {}: {}", title, message);
@@ -14,15 +134,97 @@ pub fn send(title: &str, message: &str) -> Result<()> {
         // Using Debug formatter {:?} adds surrounding quotes and escapes internal quotes
         let script = format!("display notification {:?} with title {:?}", message, title);
         
-        Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
+        crate::applescript::timed_output("notification", Command::new("osascript").arg("-e").arg(script))
             .context("Failed to send notification via osascript")?;
     }
     
+    // Mirroring is a send; safe mode keeps notifications local
+    if discord_enabled() && !crate::safe_mode::is_enabled() {
+        forward_to_discord(title, message);
+    }
+
     // Fallback log for non-macOS (or debugging)
     println!("\n🔔 [NOTIFICATION] {}: {}\n", title, message);
     
     Ok(())
 }
+
+fn discord_enabled() -> bool {
+    std::env::var("NOTIFY_DISCORD")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Discord blurple, the sidebar color of mirrored notifications.
+const NOTIFICATION_COLOR: u32 = 0x5865F2;
+
+/// Mirror the notification to Discord as an embed without blocking the caller.
+fn forward_to_discord(title: &str, message: &str) {
+    let handle = match tokio::runtime::Handle::try_current() {
+        Ok(h) => h,
+        Err(_) => return,
+    };
+    let client = match crate::integrations::discord::DiscordClient::from_env() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("⚠️ [NOTIFICATION] Discord not configured: {}", e);
+            return;
+        }
+    };
+    let embed = crate::integrations::discord::Embed {
+        title: title.to_string(),
+        description: message.to_string(),
+        color: Some(NOTIFICATION_COLOR),
+    };
+    handle.spawn(async move {
+        if let Err(e) = client.send_embed("", &embed).await {
+            eprintln!("⚠️ [NOTIFICATION] Discord send failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_burst_collapses_to_one_plus_a_count() {
+        let mut throttle = Throttle::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        let admitted: Vec<bool> = (0..10)
+            .map(|i| throttle.admit("recommendations", "💡 New Workflow Idea", "Rename screenshots", start + Duration::from_millis(i * 100)))
+            .collect();
+        assert_eq!(admitted.iter().filter(|a| **a).count(), 1);
+        assert!(admitted[0]);
+
+        // Nothing more until the dedup window has passed
+        assert!(throttle.flush(start + Duration::from_secs(1)).is_empty());
+        let ready = throttle.flush(start + Duration::from_secs(61));
+        assert_eq!(ready, vec![("recommendations".to_string(), 9)]);
+        assert_eq!(summary(&ready[0].0, ready[0].1), "9 new recommendations");
+        assert!(throttle.flush(start + Duration::from_secs(200)).is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit_is_per_category() {
+        let mut throttle = Throttle::new(2, Duration::from_secs(5));
+        let now = Instant::now();
+        assert!(throttle.admit("recommendations", "Idea A", "", now));
+        assert!(throttle.admit("recommendations", "Idea B", "", now));
+        assert!(!throttle.admit("recommendations", "Idea C", "", now));
+        assert!(throttle.admit("alerts", "Disk almost full", "", now));
+
+        // A minute later the rate allows the summary of what was held
+        assert!(throttle.flush(now + Duration::from_secs(30)).is_empty());
+        assert_eq!(throttle.flush(now + Duration::from_secs(60)), vec![("recommendations".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_same_title_with_another_message_is_not_a_repeat() {
+        let mut throttle = Throttle::new(3, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(throttle.admit("recommendations", "Steer OS", "✨ New Idea: Sort downloads", now));
+        assert!(throttle.admit("recommendations", "Steer OS", "✨ New Idea: Rename screenshots", now));
+        assert!(!throttle.admit("recommendations", "Steer OS", "✨ New Idea: Sort downloads", now));
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use crate::llm_gateway::LLMClient;
+use crate::visual_driver::VisualDriver;
+
+// Read-only counterpart to AgentExecutor: looks at the screen and answers a
+// question about it. It has no action dispatch at all, so it is safe to run on
+// machines where automation is risky.
+
+pub struct ObserveController {
+    llm: LLMClient,
+}
+
+impl ObserveController {
+    pub fn new(llm: LLMClient) -> Self {
+        Self { llm }
+    }
+
+    /// Capture the screen (plus the accessibility tree on macOS) and answer `question`.
+    pub async fn answer(&self, question: &str) -> Result<String> {
+        println!("👁️ [Observe] Capturing screen for: '{}'", question);
+        let image_b64 = tokio::task::spawn_blocking(VisualDriver::capture_screen).await??;
+
+        let prompt = prompt(question, ui_snapshot());
+        let reading = VisualDriver::read_screen(&self.llm, &prompt, Some(image_b64))
+            .await
+            .map_err(|e| anyhow::anyhow!("Vision analysis failed: {}", e))?;
+        Ok(reading.text)
+    }
+}
+
+fn prompt(question: &str, tree: Option<String>) -> String {
+    let mut prompt = format!(
+        "You are observing the user's screen in READ-ONLY mode. \
+         Answer the question using only what is visible. \
+         Do not propose clicking, typing or running anything.\n\nQUESTION: {}",
+        question
+    );
+    if let Some(tree) = tree {
+        prompt.push_str("\n\nACCESSIBILITY TREE (may be truncated):\n");
+        prompt.push_str(&tree);
+    }
+    prompt
+}
+
+#[cfg(target_os = "macos")]
+fn ui_snapshot() -> Option<String> {
+    const MAX_SNAPSHOT_CHARS: usize = 6000;
+    let tree = crate::macos::accessibility::snapshot(None);
+    let text = serde_json::to_string(&tree).ok()?;
+    Some(text.chars().take(MAX_SNAPSHOT_CHARS).collect())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn ui_snapshot() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_asks_for_a_read_only_answer() {
+        let text = prompt("Which tab is open?", None);
+        assert!(text.contains("READ-ONLY"));
+        assert!(text.ends_with("QUESTION: Which tab is open?"));
+
+        let text = prompt("Which tab is open?", Some(r#"{"role":"AXWindow"}"#.to_string()));
+        assert!(text.ends_with("ACCESSIBILITY TREE (may be truncated):\n{\"role\":\"AXWindow\"}"), "{}", text);
+    }
+}
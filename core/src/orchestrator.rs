@@ -5,8 +5,9 @@ use crate::project_scanner::ProjectScanner;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::future::Future;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskType {
     Coding,         // Modify files, refactor, build (DAACS style)
     Workflow,       // Recurring automation, data piping (n8n)
@@ -14,6 +15,7 @@ pub enum TaskType {
     Research,       // RFI, clarifications (Phase 1.5)
 }
 
+/// Stateless between requests (clients only), so one instance can be reused.
 pub struct Orchestrator {
     llm: LLMClient,
     n8n: N8nApi,
@@ -36,7 +38,7 @@ impl Orchestrator {
         println!("🧠 Orchestrator: Analyzing request '{}'...", user_request);
 
         // 1. Classification (intent analysis)
-        let task_type = self.classify_intent(user_request).await?;
+        let task_type = Self::classify(user_request);
         println!("   👉 Classified as: {:?}", task_type);
 
         match task_type {
@@ -47,22 +49,19 @@ impl Orchestrator {
         }
     }
 
-    async fn classify_intent(&self, request: &str) -> Result<TaskType> {
-        let _prompt = format!("Classify this user request... {}", request);
-
-        // ... (Logic)
-        
+    /// Routing decision only (no execution), so callers can inspect or test it.
+    pub fn classify(request: &str) -> TaskType {
         let r = request.to_lowercase();
         if r.contains("n8n") || r.contains("workflow") || r.contains("daily") || r.contains("every") {
-            return Ok(TaskType::Workflow);
+            TaskType::Workflow
         } else if r.contains("click") || r.contains("open") || r.contains("type") || r.contains("mouse") {
-            return Ok(TaskType::OsOperation);
+            TaskType::OsOperation
         } else if r.contains("code") || r.contains("rust") || r.contains("file") || r.contains("project") {
-            return Ok(TaskType::Coding);
+            TaskType::Coding
+        } else {
+            // Default
+            TaskType::Research
         }
-        
-        // Default
-        Ok(TaskType::Research)
     }
 
     // --- Handlers ---
@@ -107,3 +106,110 @@ impl Orchestrator {
         Ok(format!("(Analyst) I need more clarification on '{}'. Are you asking for a code change or a workflow?", request))
     }
 }
+
+/// The Notion database filed emails go to: `STEER_EMAIL_FILE_DATABASE_ID`,
+/// else `NOTION_DATABASE_ID`.
+fn email_file_database() -> Result<String> {
+    ["STEER_EMAIL_FILE_DATABASE_ID", "NOTION_DATABASE_ID"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Set STEER_EMAIL_FILE_DATABASE_ID or NOTION_DATABASE_ID to file emails"))
+}
+
+/// The `Subject:` header of a `GmailClient::get_message` text.
+fn email_subject(message: &str) -> String {
+    message
+        .lines()
+        .find_map(|l| l.strip_prefix("Subject:"))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "(no subject)".to_string())
+}
+
+/// Read → summarize → append, with each building block passed in.
+/// Returns the id of the page that was created.
+async fn file_email_with<RF, SF, AF>(
+    id: &str,
+    read: impl FnOnce(String) -> RF,
+    summarize: impl FnOnce(String) -> SF,
+    append: impl FnOnce(String, String) -> AF,
+) -> Result<String>
+where
+    RF: Future<Output = Result<String>>,
+    SF: Future<Output = Result<String>>,
+    AF: Future<Output = Result<String>>,
+{
+    let message = read(id.to_string()).await.with_context(|| format!("Failed to read email {}", id))?;
+    let subject = email_subject(&message);
+    let summary = summarize(message).await.context("Failed to summarize email")?;
+    let content = format!("{}\n\nGmail message: {}", summary, id);
+    append(subject, content).await.context("Failed to file summary")
+}
+
+/// `email file <id>`: summarize a Gmail message with the LLM and file the
+/// summary as a page (titled with the subject) in the configured Notion
+/// database. Returns the Notion page id.
+pub async fn file_email(llm: &LLMClient, id: &str) -> Result<String> {
+    crate::safe_mode::check("filing email to Notion is")?;
+    let database_id = email_file_database()?;
+    let gmail = crate::integrations::gmail::GmailClient::new().await?;
+    let notion = crate::integrations::notion::NotionClient::from_env()?;
+    file_email_with(
+        id,
+        |id| async move { gmail.get_message(&id).await },
+        |message| async move { llm.summarize_email(&message).await },
+        |title, content| async move { notion.create_page(&database_id, &title, &content).await },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_routes_representative_inputs() {
+        assert_eq!(Orchestrator::classify("Open Safari and search for flights"), TaskType::OsOperation);
+        assert_eq!(Orchestrator::classify("click the Login button"), TaskType::OsOperation);
+        assert_eq!(Orchestrator::classify("Every morning send me a news summary"), TaskType::Workflow);
+        assert_eq!(Orchestrator::classify("build an n8n flow for invoices"), TaskType::Workflow);
+        assert_eq!(Orchestrator::classify("refactor the rust project"), TaskType::Coding);
+        assert_eq!(Orchestrator::classify("what's the weather like?"), TaskType::Research);
+    }
+
+    #[tokio::test]
+    async fn test_file_email_composes_read_summarize_and_append() {
+        let filed = std::sync::Mutex::new(Vec::new());
+        let page = file_email_with(
+            "18c2f",
+            |id| async move { Ok(format!("Subject: Invoice #42\nFrom: billing@acme.test\n\n---\n\nPlease pay {} by Friday", id)) },
+            |message| async move {
+                assert!(message.contains("Please pay"));
+                Ok("Acme asks for payment of invoice 42 by Friday.".to_string())
+            },
+            |title, content| {
+                filed.lock().unwrap().push((title, content));
+                async { Ok("page-1".to_string()) }
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page, "page-1");
+        let filed = filed.into_inner().unwrap();
+        assert_eq!(filed[0].0, "Invoice #42");
+        assert!(filed[0].1.starts_with("Acme asks") && filed[0].1.ends_with("Gmail message: 18c2f"));
+
+        let unreadable = file_email_with(
+            "missing",
+            |_| async { Err(anyhow::anyhow!("404 Not Found")) },
+            |_| async { Ok(String::new()) },
+            |_, _| async { panic!("nothing should be filed") },
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{:#}", unreadable).contains("Failed to read email missing: 404"));
+        assert_eq!(email_subject("From: x\n\n---\n\nhi"), "(no subject)");
+    }
+}
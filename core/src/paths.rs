@@ -0,0 +1,20 @@
+use std::path::{Path, PathBuf};
+
+/// Root directory for agent state (`STEER_HOME`, default `~/.steer`).
+pub fn steer_home() -> PathBuf {
+    if let Ok(dir) = std::env::var("STEER_HOME") {
+        if !dir.trim().is_empty() {
+            return PathBuf::from(dir);
+        }
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".steer")
+}
+
+/// `path` with a leading `~` expanded against `home` (`~user` is left as is).
+pub fn expand_home(path: &str, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => home.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(path),
+    }
+}
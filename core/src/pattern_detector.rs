@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use crate::db;
+use crate::recommendation::{proposal_from_pattern, AutomationProposal};
 
 /// Detected pattern from user behavior logs
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +24,7 @@ pub enum PatternType {
     KeywordRepeat,    // 반복 키워드 입력
     FilePattern,      // 파일 작업 패턴
     TimeBasedAction,  // 시간 기반 반복 작업
+    SingleEvent,      // 분석 규칙에 걸린 단일 이벤트 (반복 전)
 }
 
 impl PatternType {
@@ -32,6 +34,7 @@ impl PatternType {
             Self::KeywordRepeat => "keyword_repeat",
             Self::FilePattern => "file_pattern",
             Self::TimeBasedAction => "time_based",
+            Self::SingleEvent => "single_event",
         }
     }
 }
@@ -53,20 +56,127 @@ impl Default for PatternConfig {
     }
 }
 
+// [Analyzer Rules] Patterns need three repeats, so an obviously automatable
+// one-off (a bank statement lands in Downloads and gets uploaded by hand) is
+// never suggested to a new user. A rule maps one event (type, optionally app
+// and payload text) to a suggested automation; a single match proposes it at
+// the rule's lower confidence. Built-in rules have categories that can be
+// switched off; more rules come from a JSON file (an array of rules).
+//
+//   STEER_ANALYZER_RULES_DISABLED="downloads"     categories to skip ("all" for every rule)
+//   STEER_ANALYZER_RULES_FILE=~/steer-rules.json   extra rules
+
+/// A single event worth suggesting an automation for on first sight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalyzerRule {
+    pub category: String,
+    pub event_type: String,
+    /// The event's app (envelope or payload), case-insensitive; any when unset
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Text the file path must contain, case-insensitive (e.g. a folder);
+    /// the payload text for events that aren't about a file
+    #[serde(default)]
+    pub contains: Option<String>,
+    pub suggestion: String,
+    #[serde(default = "default_rule_confidence")]
+    pub confidence: f64,
+}
+
+fn default_rule_confidence() -> f64 {
+    0.5
+}
+
+impl AnalyzerRule {
+    pub fn matches(&self, event: &serde_json::Value) -> bool {
+        let event_type = event.get("event_type").or_else(|| event.get("type")).and_then(|v| v.as_str());
+        if event_type != Some(self.event_type.as_str()) {
+            return false;
+        }
+        let payload = event.get("payload").or_else(|| event.get("data"));
+        if let Some(app) = &self.app {
+            let apps = [event.get("app"), payload.and_then(|p| p.get("app"))];
+            let app_matches = apps.iter().flatten().filter_map(|v| v.as_str()).any(|a| a.eq_ignore_ascii_case(app));
+            if !app_matches {
+                return false;
+            }
+        }
+        match &self.contains {
+            // The path or name only, so the rest of the payload (mime, category) can't match
+            Some(text) => payload.is_some_and(|p| {
+                let file = ["path", "filename"].iter().find_map(|k| p.get(k).and_then(|v| v.as_str()));
+                let haystack = file.or(p.as_str()).map(str::to_string).unwrap_or_else(|| p.to_string());
+                haystack.to_lowercase().contains(&text.to_lowercase())
+            }),
+            None => true,
+        }
+    }
+}
+
+pub fn default_rules() -> Vec<AnalyzerRule> {
+    let rule = |category: &str, contains: &str, suggestion: &str| AnalyzerRule {
+        category: category.to_string(),
+        event_type: "file_created".to_string(),
+        app: None,
+        contains: Some(contains.to_string()),
+        suggestion: suggestion.to_string(),
+        confidence: default_rule_confidence(),
+    };
+    // Only ~/Downloads is watched for files, so rules on other folders never fire
+    vec![rule("downloads", "/Downloads/", "File new downloads (statements, invoices) into the right folder or upload them automatically")]
+}
+
+/// `rules` minus the disabled categories (comma-separated, "all" for every one).
+pub fn select_rules(rules: Vec<AnalyzerRule>, disabled: &str) -> Vec<AnalyzerRule> {
+    let disabled: Vec<String> = disabled
+        .split(',')
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty())
+        .collect();
+    if disabled.iter().any(|c| c == "all") {
+        return Vec::new();
+    }
+    rules.into_iter().filter(|r| !disabled.contains(&r.category.to_lowercase())).collect()
+}
+
+/// Built-in rules plus `STEER_ANALYZER_RULES_FILE`, minus disabled categories.
+pub fn rules_from_env() -> Vec<AnalyzerRule> {
+    let mut rules = default_rules();
+    if let Ok(path) = std::env::var("STEER_ANALYZER_RULES_FILE") {
+        let path = path.trim();
+        let expanded = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+            _ => path.to_string(),
+        };
+        match std::fs::read_to_string(&expanded).map_err(|e| e.to_string()).and_then(|raw| {
+            serde_json::from_str::<Vec<AnalyzerRule>>(&raw).map_err(|e| e.to_string())
+        }) {
+            Ok(extra) => rules.extend(extra),
+            Err(e) => eprintln!("⚠️ [Analyzer Rules] Ignoring {}: {}", expanded, e),
+        }
+    }
+    select_rules(rules, &std::env::var("STEER_ANALYZER_RULES_DISABLED").unwrap_or_default())
+}
+
 /// Pattern detector engine
 pub struct PatternDetector {
     config: PatternConfig,
+    rules: Vec<AnalyzerRule>,
 }
 
 impl PatternDetector {
     pub fn new() -> Self {
-        Self {
-            config: PatternConfig::default(),
-        }
+        Self::with_config(PatternConfig::default())
     }
 
     pub fn with_config(config: PatternConfig) -> Self {
-        Self { config }
+        Self { config, rules: rules_from_env() }
+    }
+
+    /// Replace the analyzer rules (tests, custom sources).
+    pub fn with_rules(mut self, rules: Vec<AnalyzerRule>) -> Self {
+        self.rules = rules;
+        self
     }
 
     /// Analyze logs and detect patterns (uses DB)
@@ -234,9 +344,11 @@ impl PatternDetector {
             .collect()
     }
 
-    /// Detect file operation patterns
+    /// Detect file operation patterns: repeated file types, and a file type
+    /// followed by the same app (e.g. a PDF lands, then Preview opens).
     fn detect_file_patterns(&self, events: &[String]) -> Vec<DetectedPattern> {
         let mut file_ops: HashMap<String, (u32, Vec<String>)> = HashMap::new();
+        let mut last_file_ext: Option<String> = None;
         
         for event_str in events {
             if let Ok(val) = serde_json::from_str::<serde_json::Value>(event_str) {
@@ -246,23 +358,41 @@ impl PatternDetector {
                      .unwrap_or("");
                  
                  // Support simple string data or object payload
-                 let path_opt = val.get("payload").or_else(|| val.get("data")).and_then(|d| {
+                 let payload = val.get("payload").or_else(|| val.get("data"));
+                 let path_opt = payload.and_then(|d| {
                      if d.is_string() { d.as_str() }
                      else { d.get("path").and_then(|p| p.as_str()).or_else(|| d.as_str()) }
                  });
 
                  if event_type == "file_created" || event_type == "file_modified" {
-                    if let Some(path) = path_opt {
-                         if let Some(ext) = std::path::Path::new(path)
-                            .extension()
-                            .and_then(|e| e.to_str()) 
-                        {
-                            let key = format!("ext:{}", ext);
-                            let entry = file_ops.entry(key).or_insert((0, vec![]));
-                            entry.0 += 1;
-                            if entry.1.len() < 3 {
-                                entry.1.push(event_str.clone());
-                            }
+                    // Typed events carry the extension; older ones only the path
+                    let ext = payload
+                        .and_then(|p| p.get("extension"))
+                        .and_then(|e| e.as_str())
+                        .map(|e| e.to_lowercase())
+                        .or_else(|| {
+                            std::path::Path::new(path_opt?)
+                                .extension()
+                                .and_then(|e| e.to_str())
+                                .map(|e| e.to_lowercase())
+                        });
+                    if let Some(ext) = ext {
+                        let key = format!("ext:{}", ext);
+                        let entry = file_ops.entry(key).or_insert((0, vec![]));
+                        entry.0 += 1;
+                        if entry.1.len() < 3 {
+                            entry.1.push(event_str.clone());
+                        }
+                        last_file_ext = Some(ext);
+                    }
+                 } else if event_type == "app_switch" || event_type == "system.open" {
+                    let app = payload.and_then(|p| p.get("app")).and_then(|v| v.as_str()).unwrap_or("");
+                    if let Some(ext) = last_file_ext.take().filter(|_| !app.is_empty()) {
+                        let key = format!("then:{}:{}", ext, app);
+                        let entry = file_ops.entry(key).or_insert((0, vec![]));
+                        entry.0 += 1;
+                        if entry.1.len() < 3 {
+                            entry.1.push(event_str.clone());
                         }
                     }
                  }
@@ -272,7 +402,10 @@ impl PatternDetector {
         file_ops.into_iter()
             .filter(|(_, (count, _))| *count >= 3)
             .map(|(pattern, (count, samples))| {
-                let description = format!("File pattern: {}", pattern.replace("ext:", "."));
+                let description = match pattern.strip_prefix("then:").and_then(|rest| rest.split_once(':')) {
+                    Some((ext, app)) => format!("File pattern: .{} file, then {}", ext, app),
+                    None => format!("File pattern: {}", pattern.replace("ext:", ".")),
+                };
                 let pattern_id = self.stable_pattern_id(&PatternType::FilePattern, &description);
                 DetectedPattern {
                     pattern_id,
@@ -353,6 +486,51 @@ impl PatternDetector {
             .collect()
     }
 
+    /// One pattern per analyzer rule that at least one event matches. Not
+    /// held to the repeat thresholds; the rule's confidence stands in for
+    /// similarity.
+    pub fn detect_rule_events(&self, events: &[String]) -> Vec<DetectedPattern> {
+        let parsed: Vec<(&String, serde_json::Value)> = events
+            .iter()
+            .filter_map(|e| serde_json::from_str(e).ok().map(|v| (e, v)))
+            .collect();
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let matched: Vec<&String> = parsed.iter().filter(|(_, v)| rule.matches(v)).map(|(e, _)| *e).collect();
+                if matched.is_empty() {
+                    return None;
+                }
+                let description = format!("Suggested ({}): {}", rule.category, rule.suggestion);
+                Some(DetectedPattern {
+                    pattern_id: self.stable_pattern_id(&PatternType::SingleEvent, &description),
+                    pattern_type: PatternType::SingleEvent,
+                    description,
+                    occurrences: matched.len() as u32,
+                    similarity_score: rule.confidence.clamp(0.0, 1.0),
+                    sample_events: matched.into_iter().take(3).cloned().collect(),
+                    detected_at: Utc::now(),
+                })
+            })
+            .collect()
+    }
+
+    /// The recommendations the patterns in the DB would produce, without
+    /// saving anything.
+    pub fn propose(&self) -> Vec<AutomationProposal> {
+        let events = db::get_recent_events(self.config.lookback_days * 24).unwrap_or_default();
+        self.propose_with_events(&events)
+    }
+
+    /// `propose` over provided events, analyzer rule suggestions included.
+    pub fn propose_with_events(&self, events: &[String]) -> Vec<AutomationProposal> {
+        self.analyze_with_events(events)
+            .into_iter()
+            .chain(self.detect_rule_events(events))
+            .map(|p| proposal_from_pattern(&p))
+            .collect()
+    }
+
     /// Check if a pattern should generate a recommendation
     pub fn should_recommend(&self, pattern: &DetectedPattern) -> bool {
         pattern.occurrences >= self.config.min_occurrences
@@ -428,6 +606,86 @@ mod tests {
         assert!(p.description.contains(".pdf"));
     }
 
+    #[test]
+    fn test_file_type_then_app_detection() {
+        let detector = PatternDetector::new();
+        let mut events = Vec::new();
+        for i in 0..3 {
+            events.push(json!({
+                "event_type": "file_created",
+                "payload": {"path": format!("/Downloads/invoice-{}", i), "extension": "pdf", "mime": "application/pdf"}
+            }).to_string());
+            events.push(json!({"event_type": "app_switch", "payload": {"app": "Preview"}}).to_string());
+        }
+
+        let descriptions: Vec<String> = detector
+            .analyze_with_events(&events)
+            .into_iter()
+            .filter(|p| p.pattern_type == PatternType::FilePattern)
+            .map(|p| p.description)
+            .collect();
+        assert!(descriptions.contains(&"File pattern: .pdf".to_string()));
+        assert!(descriptions.contains(&"File pattern: .pdf file, then Preview".to_string()));
+    }
+
+    #[test]
+    fn test_proposals_carry_their_pattern_and_evidence() {
+        let app = format!("PreviewApp-{}", uuid::Uuid::new_v4());
+        let events: Vec<String> = (0..3)
+            .map(|_| json!({"type": "app_switch", "data": {"app": app}}).to_string())
+            .chain(std::iter::once(json!({"type": "app_switch", "data": {"app": "Chrome"}}).to_string()))
+            .collect();
+
+        let proposals = PatternDetector::new().propose_with_events(&events);
+        assert_eq!(proposals.len(), 1);
+        assert!(proposals[0].title.contains(&app));
+        assert!(proposals[0].pattern_id.is_some());
+        assert!(!proposals[0].evidence.is_empty());
+    }
+
+    #[test]
+    fn test_configured_rule_fires_on_one_event() {
+        let rule = AnalyzerRule {
+            category: "statements".to_string(),
+            event_type: "file_created".to_string(),
+            app: None,
+            contains: Some("/downloads/statement".to_string()),
+            suggestion: "Upload new bank statements to the accountant's folder".to_string(),
+            confidence: 0.55,
+        };
+        let detector = PatternDetector::new().with_rules(vec![rule.clone()]);
+        let events = vec![
+            json!({"event_type": "file_created", "app": "filesystem", "payload": {"path": "/Users/me/Downloads/Statement-2024-05.pdf"}}).to_string(),
+            json!({"event_type": "app_switch", "payload": {"app": "Safari"}}).to_string(),
+        ];
+
+        // Too few repeats for a pattern, but the rule fires
+        assert!(detector.analyze_with_events(&events).is_empty());
+        let fired = detector.detect_rule_events(&events);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].pattern_type, PatternType::SingleEvent);
+        assert_eq!(fired[0].occurrences, 1);
+        assert_eq!(fired[0].similarity_score, 0.55);
+        assert!(fired[0].description.contains("accountant"));
+
+        let proposals = detector.propose_with_events(&events);
+        assert_eq!(proposals.len(), 1);
+        assert!(proposals[0].confidence < 0.8);
+
+        // App-scoped rule, and a disabled category
+        let in_mail = AnalyzerRule { app: Some("mail".to_string()), contains: None, ..rule.clone() };
+        assert!(!in_mail.matches(&serde_json::from_str(&events[0]).unwrap()));
+        assert!(select_rules(vec![rule.clone()], "Statements").is_empty());
+        assert_eq!(select_rules(vec![rule.clone()], "downloads"), vec![rule.clone()]);
+        assert!(select_rules(default_rules(), "all").is_empty());
+
+        // Only the file path counts, not the rest of the payload
+        let pdfs = AnalyzerRule { contains: Some("pdf".to_string()), ..rule };
+        let event = |path: &str| json!({"event_type": "file_created", "payload": {"path": path, "filename": "x", "mime": "application/pdf"}});
+        assert!(!pdfs.matches(&event("/Users/me/Downloads/notes.txt")));
+        assert!(pdfs.matches(&event("/Users/me/Downloads/invoice.pdf")));
+    }
+
     #[test]
     fn test_time_pattern_detection() {
         let detector = PatternDetector::new();
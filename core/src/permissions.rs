@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+// [Permissions] One place that says which OS permissions the agent has.
+// `/health` folds this into a single ok/not-ok line; the desktop app reads
+// `GET /api/permissions` to show a setup wizard and a banner naming exactly
+// what is missing and where to grant it, and re-checks when its window gets
+// focus (the user usually comes back from System Settings).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Missing,
+    /// Turned off by configuration (e.g. STEER_SCREEN_RECORDING=off)
+    Disabled,
+    /// This platform doesn't gate it
+    NotRequired,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Permission {
+    pub name: &'static str,
+    pub state: PermissionState,
+    /// What stops working without it
+    pub needed_for: &'static str,
+    /// Where the user grants it
+    pub settings_path: &'static str,
+    /// Without it the agent can't act at all
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PermissionReport {
+    pub permissions: Vec<Permission>,
+    /// Names of missing permissions
+    pub missing: Vec<&'static str>,
+    /// Every required permission is granted
+    pub ready: bool,
+    pub checked_at: String,
+}
+
+/// Check each permission now. Only reads the current state; never shows a
+/// system prompt.
+pub fn preflight() -> PermissionReport {
+    #[cfg(target_os = "macos")]
+    {
+        let screen_disabled = std::env::var("STEER_SCREEN_RECORDING")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "0" | "off" | "false" | "no"))
+            .unwrap_or(false);
+        report(
+            Some(crate::macos::accessibility::is_trusted()),
+            Some(crate::macos::accessibility::screen_capture_allowed()),
+            screen_disabled,
+        )
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        report(None, None, false)
+    }
+}
+
+/// `None` means the platform has no such permission.
+fn report(accessibility: Option<bool>, screen_recording: Option<bool>, screen_disabled: bool) -> PermissionReport {
+    let state = |granted: Option<bool>| match granted {
+        None => PermissionState::NotRequired,
+        Some(true) => PermissionState::Granted,
+        Some(false) => PermissionState::Missing,
+    };
+    let screen_state = if screen_disabled { PermissionState::Disabled } else { state(screen_recording) };
+    let permissions = vec![
+        Permission {
+            name: "accessibility",
+            state: state(accessibility),
+            needed_for: "clicking, typing and reading other apps",
+            settings_path: "System Settings > Privacy & Security > Accessibility",
+            required: true,
+        },
+        Permission {
+            name: "screen_recording",
+            state: screen_state,
+            needed_for: "vision checks, screenshots and EXTRACT outside a browser",
+            settings_path: "System Settings > Privacy & Security > Screen Recording",
+            required: false,
+        },
+    ];
+    let missing = permissions.iter().filter(|p| p.state == PermissionState::Missing).map(|p| p.name).collect();
+    let ready = permissions.iter().all(|p| !p.required || p.state != PermissionState::Missing);
+    PermissionReport { permissions, missing, ready, checked_at: chrono::Utc::now().to_rfc3339() }
+}
+
+impl PermissionReport {
+    /// One line for `/health` and the REPL.
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = self
+            .permissions
+            .iter()
+            .map(|p| {
+                let state = match p.state {
+                    PermissionState::Granted => "granted",
+                    PermissionState::Missing => "missing",
+                    PermissionState::Disabled => "off",
+                    PermissionState::NotRequired => "not required",
+                };
+                format!("{} {}", p.name.replace('_', " "), state)
+            })
+            .collect();
+        parts.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(report: &PermissionReport, name: &str) -> PermissionState {
+        report.permissions.iter().find(|p| p.name == name).unwrap().state
+    }
+
+    #[test]
+    fn test_report_names_each_missing_permission() {
+        let all = report(Some(true), Some(true), false);
+        assert!(all.ready);
+        assert!(all.missing.is_empty());
+        assert_eq!(all.summary(), "accessibility granted; screen recording granted");
+
+        // Screen recording alone doesn't stop the agent, but is still reported
+        let no_screen = report(Some(true), Some(false), false);
+        assert!(no_screen.ready);
+        assert_eq!(no_screen.missing, vec!["screen_recording"]);
+
+        let no_ax = report(Some(false), Some(true), false);
+        assert!(!no_ax.ready);
+        assert_eq!(no_ax.missing, vec!["accessibility"]);
+
+        let disabled = report(Some(true), Some(false), true);
+        assert_eq!(state(&disabled, "screen_recording"), PermissionState::Disabled);
+        assert!(disabled.missing.is_empty());
+
+        let other_os = report(None, None, false);
+        assert!(other_os.ready);
+        assert_eq!(state(&other_os, "accessibility"), PermissionState::NotRequired);
+
+        let json = serde_json::to_value(&no_ax).unwrap();
+        assert_eq!(json["permissions"][0]["state"], "missing");
+        assert_eq!(json["ready"], false);
+    }
+}
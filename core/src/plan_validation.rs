@@ -0,0 +1,336 @@
+use anyhow::Result;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::action_schema;
+use crate::executor::PlanStep;
+
+// [Plan Validation] Plans come back from the LLM as free-form JSON. Steps with
+// an unknown action or missing arguments used to degrade silently into a
+// WAIT, and the model never learned what it got wrong. Validation errors are
+// now fed back into the next planning prompt so the model can correct itself.
+//
+// A reply that isn't valid JSON at all is repaired before that: first with a
+// tolerant parse (trailing commas, single quotes, comments), then with one
+// "return valid JSON only" request that shows the model its own reply. The
+// request uses one of the fix attempts; if it fails too, the parse error is
+// fed back like any other. Outcomes are counted for `/metrics`.
+
+static PARSE_FAILURES: AtomicU64 = AtomicU64::new(0);
+static REPAIRED_LENIENT: AtomicU64 = AtomicU64::new(0);
+static REPAIRED_BY_LLM: AtomicU64 = AtomicU64::new(0);
+static UNREPAIRED: AtomicU64 = AtomicU64::new(0);
+
+/// Normalize action names in place and return one message per invalid step.
+pub fn validate_plan(plan: &mut [PlanStep]) -> Vec<String> {
+    if plan.is_empty() {
+        return vec!["the plan has no steps".to_string()];
+    }
+    let mut errors = Vec::new();
+    for (i, step) in plan.iter_mut().enumerate() {
+        step.action_type = step.action_type.trim().to_uppercase();
+        if let Some(problem) = step_problem(step) {
+            errors.push(format!("step {} ({}): {}", i + 1, step.action_type, problem));
+        }
+    }
+    errors
+}
+
+fn step_problem(step: &PlanStep) -> Option<String> {
+    match action_schema::lookup(&step.action_type) {
+        Some(spec) => spec.check(step.target.as_deref(), step.value.as_deref()),
+        None => Some(format!("unknown action '{}'", step.action_type)),
+    }
+}
+
+/// Guidance appended to the planning prompt after an invalid plan.
+pub fn corrective_feedback(errors: &[String]) -> String {
+    format!(
+        "\n\nYour last plan was invalid because:\n- {}\nValid actions are: {}. Fix these problems and output the full plan again.",
+        errors.join("\n- "),
+        action_schema::names().join(", ")
+    )
+}
+
+/// The part of an LLM response that should be the JSON array of steps.
+fn plan_json(response: &str) -> String {
+    let start = response.find('[').unwrap_or(0);
+    let end = response.rfind(']').map(|i| i + 1).unwrap_or(response.len());
+    let sliced = if start < end { &response[start..end] } else { response };
+    sliced.replace("```json", "").replace("```", "").trim().to_string()
+}
+
+/// Extract the JSON array of steps from an LLM response (tolerates markdown fences).
+pub fn parse_plan(response: &str) -> Result<Vec<PlanStep>> {
+    let cleaned = plan_json(response);
+    serde_json::from_str(&cleaned).map_err(|e| anyhow::anyhow!("plan is not a valid JSON array of steps ({}): {}", e, cleaned))
+}
+
+/// `parse_plan` after rewriting the common near-JSON mistakes.
+fn parse_plan_leniently(response: &str) -> Option<Vec<PlanStep>> {
+    serde_json::from_str(&lenient_json(&plan_json(response))).ok()
+}
+
+/// `text` with single-quoted strings double-quoted, `//` and `/* */`
+/// comments removed and trailing commas before `]` / `}` dropped. Strict
+/// JSON passes through unchanged.
+fn lenient_json(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    // The quote that opened the string we are in, if any
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => match c {
+                '\\' => {
+                    let next = chars.next();
+                    match next {
+                        // \' needs no escape once the string is double-quoted
+                        Some('\'') if q == '\'' => out.push('\''),
+                        Some(n) => {
+                            out.push('\\');
+                            out.push(n);
+                        }
+                        None => out.push('\\'),
+                    }
+                }
+                '"' if q == '\'' => out.push_str("\\\""),
+                _ if c == q => {
+                    out.push('"');
+                    quote = None;
+                }
+                _ => out.push(c),
+            },
+            None => match c {
+                '"' | '\'' => {
+                    out.push('"');
+                    quote = Some(c);
+                }
+                '/' if chars.peek() == Some(&'/') => {
+                    while chars.peek().is_some_and(|n| *n != '\n') {
+                        chars.next();
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut last = ' ';
+                    for n in chars.by_ref() {
+                        if last == '*' && n == '/' {
+                            break;
+                        }
+                        last = n;
+                    }
+                }
+                ']' | '}' => {
+                    let kept = out.trim_end().len();
+                    out.truncate(kept);
+                    if out.ends_with(',') {
+                        out.pop();
+                    }
+                    out.push(c);
+                }
+                _ => out.push(c),
+            },
+        }
+    }
+    out
+}
+
+/// The one-shot correction request for a reply that wasn't JSON.
+fn repair_prompt(response: &str, error: &anyhow::Error) -> String {
+    format!(
+        "Your last reply could not be parsed ({}). Return valid JSON only: the same plan as a JSON array of steps, with no markdown and no other text.\n\nYour reply was:\n{}",
+        error, response
+    )
+}
+
+/// Start of the error for a plan that stayed invalid after every fix attempt.
+pub const INVALID_PLAN: &str = "Plan failed validation";
+
+/// Ask for a plan via `ask`, re-asking up to `max_fixes` times with the
+/// validation errors appended to the prompt.
+pub async fn request_valid_plan<F, Fut>(base_prompt: &str, max_fixes: u32, mut ask: F) -> Result<Vec<PlanStep>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut prompt = base_prompt.to_string();
+    let mut fixes = 0;
+    // Whether the last request was the JSON repair
+    let mut repairing = false;
+    loop {
+        let response = ask(prompt).await?;
+        let parsed = match parse_plan(&response) {
+            Ok(plan) => Ok(plan),
+            Err(e) => match parse_plan_leniently(&response) {
+                Some(plan) => {
+                    if !repairing {
+                        PARSE_FAILURES.fetch_add(1, Ordering::Relaxed);
+                        REPAIRED_LENIENT.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(plan)
+                }
+                None => Err(e),
+            },
+        };
+        let errors = match parsed {
+            Ok(mut plan) => {
+                if repairing {
+                    REPAIRED_BY_LLM.fetch_add(1, Ordering::Relaxed);
+                }
+                let errors = validate_plan(&mut plan);
+                if errors.is_empty() {
+                    return Ok(plan);
+                }
+                errors
+            }
+            Err(e) => {
+                if !repairing {
+                    PARSE_FAILURES.fetch_add(1, Ordering::Relaxed);
+                    if fixes < max_fixes {
+                        println!("🧩 [Plan] Reply was not JSON; asking for valid JSON only.");
+                        fixes += 1;
+                        repairing = true;
+                        prompt = repair_prompt(&response, &e);
+                        continue;
+                    }
+                }
+                UNREPAIRED.fetch_add(1, Ordering::Relaxed);
+                vec![e.to_string()]
+            }
+        };
+        repairing = false;
+
+        println!("🧩 [Plan] Invalid plan ({}): {}", fixes + 1, errors.join("; "));
+        if fixes >= max_fixes {
+            return Err(anyhow::anyhow!("{}: {}", INVALID_PLAN, errors.join("; ")));
+        }
+        fixes += 1;
+        prompt = format!("{}{}", base_prompt, corrective_feedback(&errors));
+    }
+}
+
+/// Prometheus text for `/metrics`: replies that weren't JSON and how each was repaired.
+pub fn render_metrics() -> String {
+    let failures = PARSE_FAILURES.load(Ordering::Relaxed);
+    if failures == 0 {
+        return String::new();
+    }
+    let mut out = String::from("# TYPE steer_plan_json_parse_failures_total counter\n");
+    out.push_str(&format!("steer_plan_json_parse_failures_total {}\n", failures));
+    out.push_str("# TYPE steer_plan_json_repairs_total counter\n");
+    out.push_str(&format!("steer_plan_json_repairs_total{{method=\"lenient\"}} {}\n", REPAIRED_LENIENT.load(Ordering::Relaxed)));
+    out.push_str(&format!("steer_plan_json_repairs_total{{method=\"llm\"}} {}\n", REPAIRED_BY_LLM.load(Ordering::Relaxed)));
+    out.push_str("# TYPE steer_plan_json_unrepaired_total counter\n");
+    out.push_str(&format!("steer_plan_json_unrepaired_total {}\n", UNREPAIRED.load(Ordering::Relaxed)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_validate_plan_reports_bad_steps() {
+        let mut plan: Vec<PlanStep> = serde_json::from_str(
+            r#"[
+                {"description":"open","action_type":"activate","value":"Safari","verification":""},
+                {"description":"click","action_type":"CLICK","verification":""},
+                {"description":"hover","action_type":"HOVER","target":"menu","verification":""}
+            ]"#,
+        )
+        .unwrap();
+        let errors = validate_plan(&mut plan);
+        assert_eq!(plan[0].action_type, "ACTIVATE");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("step 2 (CLICK)"));
+        assert!(errors[1].contains("unknown action 'HOVER'"));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_action_feeds_back_into_next_prompt() {
+        let prompts: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen = prompts.clone();
+        let plan = request_valid_plan("PLAN:", 1, move |prompt| {
+            let seen = seen.clone();
+            async move {
+                let mut seen = seen.lock().unwrap();
+                seen.push(prompt);
+                Ok(if seen.len() == 1 {
+                    r#"[{"description":"x","action_type":"CLICKK","target":"OK","verification":""}]"#.to_string()
+                } else {
+                    r#"[{"description":"x","action_type":"CLICK","target":"OK","verification":""}]"#.to_string()
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(plan[0].action_type, "CLICK");
+        let prompts = prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[0], "PLAN:");
+        assert!(prompts[1].contains("Your last plan was invalid because"));
+        assert!(prompts[1].contains("unknown action 'CLICKK'"));
+        assert!(prompts[1].contains("Valid actions are: CLICK, TYPE"));
+    }
+
+    #[test]
+    fn test_lenient_parse_recovers_trailing_commas_and_single_quotes() {
+        let trailing = r#"[{"description":"open","action_type":"ACTIVATE","value":"Notes","verification":"",},]"#;
+        assert!(parse_plan(trailing).is_err());
+        let plan = parse_plan_leniently(trailing).unwrap();
+        assert_eq!(plan[0].value.as_deref(), Some("Notes"));
+
+        let quoted = "Here you go:\n[{'description': 'say \"hi\"', 'action_type': 'TYPE', 'value': 'it\\'s me', 'verification': ''}] // done";
+        assert!(parse_plan(quoted).is_err());
+        let plan = parse_plan_leniently(quoted).unwrap();
+        assert_eq!(plan[0].description, "say \"hi\"");
+        assert_eq!(plan[0].value.as_deref(), Some("it's me"));
+
+        // Commas and quotes inside strings are left alone
+        let strict = r#"[{"description":"a, ]","action_type":"TYPE","value":"it's","verification":""}]"#;
+        assert_eq!(lenient_json(strict), strict);
+        assert_eq!(lenient_json("[1, /* two */ 2, // three\n]"), "[1,  2]");
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_reply_gets_one_json_only_correction() {
+        let prompts: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen = prompts.clone();
+        let plan = request_valid_plan("PLAN:", 1, move |prompt| {
+            let seen = seen.clone();
+            async move {
+                let mut seen = seen.lock().unwrap();
+                seen.push(prompt);
+                Ok(if seen.len() == 1 {
+                    "Sure! First click OK.".to_string()
+                } else {
+                    r#"[{"description":"x","action_type":"CLICK","target":"OK","verification":""}]"#.to_string()
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(plan[0].target.as_deref(), Some("OK"));
+        {
+            let prompts = prompts.lock().unwrap();
+            assert!(prompts[1].starts_with("Your last reply could not be parsed"), "{}", prompts[1]);
+            assert!(prompts[1].ends_with("Sure! First click OK."));
+        }
+        assert!(render_metrics().contains("steer_plan_json_repairs_total{method=\"llm\"}"));
+
+        // A reply the tolerant parser fixes needs no extra request
+        let asked = Arc::new(Mutex::new(0));
+        let count = asked.clone();
+        let plan = request_valid_plan("PLAN:", 0, move |_| {
+            *count.lock().unwrap() += 1;
+            async { Ok("[{'description':'x','action_type':'CLICK','target':'OK','verification':''},]".to_string()) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(*asked.lock().unwrap(), 1);
+    }
+}
@@ -2,6 +2,8 @@ use crate::schema::AgentAction;
 use crate::security;
 use crate::shell_analysis;
 use crate::db;
+use crate::disabled_actions::{self, DisabledActions};
+use crate::safe_mode;
 use crate::tool_policy;
 use std::env;
 
@@ -12,6 +14,76 @@ pub enum SecurityLevel {
     Critical,
 }
 
+/// Why a step was blocked, for clients that explain blocks by category
+/// instead of parsing the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    /// `STEER_TOOL_ALLOWLIST` / `STEER_TOOL_DENYLIST`.
+    ToolPolicy,
+    /// Shell command not on the allowlist and not approved.
+    ShellNotAllowed,
+    /// `STEER_APP_ALLOWLIST` / `STEER_APP_DENYLIST`.
+    AppNotAllowed,
+    SafeMode,
+    /// The write lock is engaged; the action needs approval.
+    WriteLock,
+    SensitiveApp,
+    DangerousCommand,
+    UserDenied,
+    /// The run used up its `STEER_ACTION_BUDGET` for this action.
+    ActionBudget,
+    /// `STEER_DISABLED_ACTIONS` switches this action type off.
+    ActionDisabled,
+    /// A step uses a clipboard value no USE_CLIPBOARD step has filled yet.
+    NothingCopied,
+    /// OPEN_FILE on an app bundle, script or other executable.
+    LaunchesProgram,
+    /// `STEER_URL_ALLOWLIST` / `STEER_URL_DENYLIST`.
+    UrlNotAllowed,
+    /// A URL that isn't a plain http(s) link to a host.
+    UnsafeUrl,
+}
+
+impl BlockReason {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ToolPolicy => "tool_policy",
+            Self::ShellNotAllowed => "shell_not_allowed",
+            Self::AppNotAllowed => "app_not_allowed",
+            Self::SafeMode => "safe_mode",
+            Self::WriteLock => "write_lock",
+            Self::SensitiveApp => "sensitive_app",
+            Self::DangerousCommand => "dangerous_command",
+            Self::UserDenied => "user_denied",
+            Self::ActionBudget => "action_budget",
+            Self::ActionDisabled => "action_disabled",
+            Self::NothingCopied => "nothing_copied",
+            Self::LaunchesProgram => "launches_program",
+            Self::UrlNotAllowed => "url_not_allowed",
+            Self::UnsafeUrl => "unsafe_url",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blocked {
+    pub reason: BlockReason,
+    pub message: String,
+}
+
+impl Blocked {
+    fn new(reason: BlockReason, message: impl Into<String>) -> Self {
+        Self { reason, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for Blocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 pub struct PolicyEngine {
     pub write_lock: bool,
 }
@@ -21,33 +93,56 @@ impl PolicyEngine {
         Self { write_lock: true } // Default Locked
     }
 
-    pub fn check(&self, action: &AgentAction) -> Result<(), String> {
+    pub fn check(&self, action: &AgentAction) -> Result<(), Blocked> {
         self.check_with_context(action, None)
     }
 
-    pub fn check_with_context(&self, action: &AgentAction, cwd: Option<&str>) -> Result<(), String> {
+    pub fn check_with_context(&self, action: &AgentAction, cwd: Option<&str>) -> Result<(), Blocked> {
         if !tool_policy::is_action_allowed(action) {
-            return Err("Tool policy blocked this action.".to_string());
+            return Err(Blocked::new(BlockReason::ToolPolicy, "Tool policy blocked this action."));
         }
         if let AgentAction::ShellExecution { command } = action {
+            DisabledActions::from_env()
+                .check(disabled_actions::SHELL)
+                .map_err(|e| Blocked::new(BlockReason::ActionDisabled, e))?;
             if !is_shell_command_allowed(command, cwd) {
-                return Err("Shell command not in allowlist. Approval required.".to_string());
+                return Err(Blocked::new(BlockReason::ShellNotAllowed, "Shell command not in allowlist. Approval required."));
             }
         }
+        if let AgentAction::SystemOpen { app } = action {
+            check_app(app, &AppLists::from_env())?;
+        }
+        if let AgentAction::OpenUrl { url } = action {
+            check_url(url, &HostLists::from_env())?;
+        }
+        if let AgentAction::OpenFile { path, app } = action {
+            let home = env::var_os("HOME").map(std::path::PathBuf::from);
+            let target = crate::executor::resolve_existing_path(path, home.as_deref())
+                .unwrap_or_else(|_| std::path::PathBuf::from(path.trim()));
+            check_open_file(&target, app.as_deref(), crate::executor::default_open_app, &AppLists::from_env())?;
+        }
+
+        self.gate(self.classify(action), safe_mode::is_enabled())
+    }
 
-        let level = self.classify(action);
+    /// Whether an action at `level` may run under the write lock and safe mode.
+    fn gate(&self, level: SecurityLevel, safe_mode: bool) -> Result<(), Blocked> {
+        if level != SecurityLevel::Safe && safe_mode {
+            return Err(Blocked::new(BlockReason::SafeMode, safe_mode::blocked_message("actions are")));
+        }
         match level {
             SecurityLevel::Safe => Ok(()),
             SecurityLevel::Caution => {
                 if self.write_lock {
-                    Err("Write Lock Engaged: Action requires approval.".to_string())
+                    Err(Blocked::new(BlockReason::WriteLock, "Write Lock Engaged: Action requires approval."))
                 } else {
                     Ok(())
                 }
             }
-            SecurityLevel::Critical => {
-                Err("Critical Action: Requires explicit 2FA/Confirmation (Not implemented).".to_string())
-            }
+            SecurityLevel::Critical => Err(Blocked::new(
+                BlockReason::DangerousCommand,
+                "Critical Action: Requires explicit 2FA/Confirmation (Not implemented).",
+            )),
         }
     }
 
@@ -56,7 +151,8 @@ impl PolicyEngine {
             AgentAction::UiSnapshot { .. } | AgentAction::UiFind { .. } | AgentAction::SystemSearch { .. } => SecurityLevel::Safe,
             AgentAction::UiClick { .. } | AgentAction::UiClickText { .. } | AgentAction::KeyboardType { .. } => SecurityLevel::Caution,
             AgentAction::UiType { .. } => SecurityLevel::Caution,
-            AgentAction::SystemOpen { .. } => SecurityLevel::Caution,
+            // Sensitive apps, hosts and file handlers are blocked in `check_with_context`
+            AgentAction::SystemOpen { .. } | AgentAction::OpenUrl { .. } | AgentAction::OpenFile { .. } => SecurityLevel::Caution,
             AgentAction::ShellExecution { command } => {
                 match security::CommandClassifier::classify(command) {
                     security::SafetyLevel::Critical => SecurityLevel::Critical,
@@ -69,14 +165,23 @@ impl PolicyEngine {
     }
     
     pub fn unlock(&mut self) {
-        self.write_lock = false;
+        self.set_write_lock(false);
         println!("[Policy] Write Lock UNLOCKED.");
     }
     
     pub fn lock(&mut self) {
-        self.write_lock = true;
+        self.set_write_lock(true);
         println!("[Policy] Write Lock ENGAGED.");
     }
+
+    /// Change the write lock and note it in the config audit log.
+    fn set_write_lock(&mut self, locked: bool) {
+        let label = |locked: bool| if locked { "locked" } else { "unlocked" };
+        let before = std::mem::replace(&mut self.write_lock, locked);
+        if let Err(e) = crate::db::record_config_change("write_lock", Some(label(before)), Some(label(locked))) {
+            eprintln!("⚠️ [Policy] Could not audit the write lock change: {}", e);
+        }
+    }
 }
 
 fn is_shell_command_allowed(command: &str, cwd: Option<&str>) -> bool {
@@ -120,6 +225,193 @@ fn is_shell_command_allowed(command: &str, cwd: Option<&str>) -> bool {
     true
 }
 
+/// `STEER_SENSITIVE_APPS`: apps that are never opened or focused without
+/// approval, even when the write lock is off.
+fn sensitive_apps() -> Vec<String> {
+    match env::var("STEER_SENSITIVE_APPS") {
+        Ok(raw) => parse_list(&raw),
+        Err(_) => [
+            "Terminal",
+            "iTerm",
+            "iTerm2",
+            "Keychain Access",
+            "System Settings",
+            "System Preferences",
+            "Disk Utility",
+            "Activity Monitor",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect(),
+    }
+}
+
+/// App allow/deny lists and sensitive apps, as configured.
+struct AppLists {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    sensitive: Vec<String>,
+}
+
+impl AppLists {
+    fn from_env() -> Self {
+        Self {
+            allow: parse_list(&env::var("STEER_APP_ALLOWLIST").unwrap_or_default()),
+            deny: parse_list(&env::var("STEER_APP_DENYLIST").unwrap_or_default()),
+            sensitive: sensitive_apps(),
+        }
+    }
+}
+
+/// Extensions of things that run rather than open: app bundles, scripts,
+/// installers and launchers.
+const LAUNCHABLE_EXTENSIONS: &[&str] = &[
+    "app", "command", "sh", "bash", "zsh", "tool", "terminal", "workflow", "scpt", "scptd",
+    "applescript", "pkg", "mpkg", "prefpane", "desktop", "appimage", "jar",
+];
+
+/// Why opening `target` would run a program instead of showing a document.
+pub fn launches_program(target: &std::path::Path) -> Option<&'static str> {
+    let ext = target.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if LAUNCHABLE_EXTENSIONS.contains(&ext.as_str()) {
+        return Some(if ext == "app" { "an app bundle" } else { "a script or launcher" });
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(target) {
+            if meta.is_file() && meta.permissions().mode() & 0o111 != 0 {
+                return Some("an executable");
+            }
+        }
+    }
+    None
+}
+
+/// OPEN_FILE runs whichever app handles `target` (the given `app`, else the
+/// default from `default_app`), so that app goes through the same lists as
+/// OPEN_APP. Executables and bundles are refused outright.
+fn check_open_file(
+    target: &std::path::Path,
+    app: Option<&str>,
+    default_app: impl FnOnce(&std::path::Path) -> Option<String>,
+    lists: &AppLists,
+) -> Result<(), Blocked> {
+    if let Some(kind) = launches_program(target) {
+        return Err(Blocked::new(
+            BlockReason::LaunchesProgram,
+            format!("OPEN_FILE won't launch {} ({}); use OPEN_APP or SHELL.", target.display(), kind),
+        ));
+    }
+    let handler = match app.map(str::trim).filter(|a| !a.is_empty()) {
+        Some(app) => Some(app.to_string()),
+        None => default_app(target),
+    };
+    let Some(handler) = handler else {
+        if lists.allow.is_empty() || lists.allow.iter().any(|a| a == "*") {
+            return Ok(());
+        }
+        return Err(Blocked::new(
+            BlockReason::AppNotAllowed,
+            format!("Can't tell which app opens {}, so the app allowlist can't be checked.", target.display()),
+        ));
+    };
+    app_launch_allowed(&handler, &lists.allow, &lists.deny).map_err(|e| Blocked::new(BlockReason::AppNotAllowed, e))?;
+    if matches_app(&handler, &lists.sensitive) {
+        return Err(Blocked::new(
+            BlockReason::SensitiveApp,
+            format!("Critical Action: {} would open in {}, which requires explicit confirmation.", target.display(), handler),
+        ));
+    }
+    Ok(())
+}
+
+/// Opening or switching to `app`: it must pass the app lists and not be a
+/// sensitive app.
+fn check_app(app: &str, lists: &AppLists) -> Result<(), Blocked> {
+    app_launch_allowed(app, &lists.allow, &lists.deny).map_err(|e| Blocked::new(BlockReason::AppNotAllowed, e))?;
+    if matches_app(app, &lists.sensitive) {
+        return Err(Blocked::new(
+            BlockReason::SensitiveApp,
+            format!("Critical Action: opening {} requires explicit confirmation.", app.trim()),
+        ));
+    }
+    Ok(())
+}
+
+/// `STEER_URL_DENYLIST` / `STEER_URL_ALLOWLIST`: hosts the agent may open. A
+/// listed host also covers its subdomains.
+struct HostLists {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl HostLists {
+    fn from_env() -> Self {
+        let hosts = |key: &str| {
+            parse_list(&env::var(key).unwrap_or_default())
+                .into_iter()
+                .map(|h| h.trim_start_matches("*.").to_lowercase())
+                .collect()
+        };
+        Self { allow: hosts("STEER_URL_ALLOWLIST"), deny: hosts("STEER_URL_DENYLIST") }
+    }
+}
+
+fn matches_host(host: &str, list: &[String]) -> bool {
+    list.iter().any(|h| host == h || host.strip_suffix(h.as_str()).is_some_and(|rest| rest.ends_with('.')))
+}
+
+/// Opening `url`: only http(s) links, since other schemes (`file:`,
+/// `x-apple.systempreferences:`, `ssh:`, ...) hand off to whatever app
+/// registered them; then the host goes through the URL lists. A bare
+/// `example.com` is taken as https.
+fn check_url(url: &str, hosts: &HostLists) -> Result<(), Blocked> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url)
+        .or_else(|_| reqwest::Url::parse(&format!("https://{}", url)))
+        .map_err(|e| Blocked::new(BlockReason::UnsafeUrl, format!("Not a valid URL: {} ({})", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(Blocked::new(
+            BlockReason::UnsafeUrl,
+            format!("Critical Action: {}: URLs open another app and require explicit confirmation.", parsed.scheme()),
+        ));
+    }
+    let Some(host) = parsed.host_str().map(|h| h.trim_end_matches('.').to_lowercase()) else {
+        return Err(Blocked::new(BlockReason::UnsafeUrl, format!("URL has no host: {}", url)));
+    };
+    if matches_host(&host, &hosts.deny) {
+        return Err(Blocked::new(BlockReason::UrlNotAllowed, format!("Host '{}' is on the URL denylist (STEER_URL_DENYLIST).", host)));
+    }
+    if !hosts.allow.is_empty() && !hosts.allow.iter().any(|h| h == "*") && !matches_host(&host, &hosts.allow) {
+        return Err(Blocked::new(
+            BlockReason::UrlNotAllowed,
+            format!("Host '{}' is not on the URL allowlist (STEER_URL_ALLOWLIST).", host),
+        ));
+    }
+    Ok(())
+}
+
+fn app_launch_allowed(app: &str, allow: &[String], deny: &[String]) -> Result<(), String> {
+    // "Activate whatever is in front" doesn't name an app
+    let app = app.trim();
+    if app.eq_ignore_ascii_case("frontmost") {
+        return Ok(());
+    }
+    if matches_app(app, deny) {
+        return Err(format!("App '{}' is on the app denylist (STEER_APP_DENYLIST).", app));
+    }
+    if !allow.is_empty() && !allow.iter().any(|a| a == "*") && !matches_app(app, allow) {
+        return Err(format!("App '{}' is not on the app allowlist (STEER_APP_ALLOWLIST).", app));
+    }
+    Ok(())
+}
+
+fn matches_app(app: &str, list: &[String]) -> bool {
+    let name = app.trim().trim_end_matches(".app").rsplit('/').next().unwrap_or("").to_lowercase();
+    !name.is_empty() && list.iter().any(|s| s.to_lowercase() == name)
+}
+
 fn parse_list(raw: &str) -> Vec<String> {
     raw.split(',')
         .map(|s| s.trim().to_string())
@@ -185,6 +477,56 @@ mod tests {
         assert!(policy.check(&action).is_ok());
     }
 
+    #[test]
+    fn test_lock_changes_are_audited() {
+        crate::db::init().ok();
+        let audited = |old: &str, new: &str| {
+            crate::db::list_config_changes(Some("write_lock"), 10_000)
+                .unwrap()
+                .iter()
+                .filter(|c| c.old_value.as_deref() == Some(old) && c.new_value.as_deref() == Some(new))
+                .count()
+        };
+        let (unlocks, locks) = (audited("locked", "unlocked"), audited("unlocked", "locked"));
+        let mut policy = PolicyEngine::new();
+        policy.unlock();
+        policy.lock();
+        // Locking a locked engine changes nothing and isn't recorded
+        policy.lock();
+        assert!(audited("locked", "unlocked") > unlocks);
+        assert_eq!(audited("unlocked", "locked"), locks + 1);
+    }
+
+    #[test]
+    fn test_sensitive_app_blocked_even_when_unlocked() {
+        let list = vec!["Terminal".to_string(), "Keychain Access".to_string()];
+        assert!(matches_app("terminal", &list));
+        assert!(matches_app("/Applications/Utilities/Keychain Access.app", &list));
+        assert!(!matches_app("Safari", &list));
+        assert!(!matches_app("", &list));
+
+        let lists = AppLists { allow: vec![], deny: vec![], sensitive: list };
+        assert_eq!(check_app("Terminal", &lists).unwrap_err().reason, BlockReason::SensitiveApp);
+        assert!(check_app("Safari", &lists).is_ok());
+    }
+
+    #[test]
+    fn test_app_allow_and_deny_lists() {
+        let none: Vec<String> = Vec::new();
+        let allow = vec!["Safari".to_string(), "Notes".to_string()];
+        let deny = vec!["Messages".to_string()];
+
+        assert!(app_launch_allowed("Anything", &none, &none).is_ok());
+        assert!(app_launch_allowed("notes", &allow, &none).is_ok());
+        let err = app_launch_allowed("Mail", &allow, &none).unwrap_err();
+        assert!(err.contains("'Mail' is not on the app allowlist"));
+        assert!(app_launch_allowed("Messages", &none, &deny).unwrap_err().contains("denylist"));
+        assert!(app_launch_allowed("Messages", &deny, &deny).is_err());
+        // URLs aren't apps; they are checked by host (see check_url)
+        assert!(app_launch_allowed("https://example.com", &allow, &none).is_err());
+        assert!(app_launch_allowed("frontmost", &allow, &none).is_ok());
+    }
+
     #[test]
     fn test_dangerous_shell_blocked() {
         let policy = PolicyEngine::new();
@@ -192,4 +534,85 @@ mod tests {
         // Should be Critical
         assert!(policy.check(&action).is_err());
     }
+
+    #[test]
+    fn test_blocks_carry_a_reason() {
+        let locked = PolicyEngine::new();
+        let blocked = locked.gate(SecurityLevel::Caution, false).unwrap_err();
+        assert_eq!(blocked.reason, BlockReason::WriteLock);
+        assert_eq!(blocked.to_string(), blocked.message);
+        assert_eq!(locked.gate(SecurityLevel::Safe, true), Ok(()));
+        assert_eq!(locked.gate(SecurityLevel::Caution, true).unwrap_err().reason, BlockReason::SafeMode);
+
+        let mut open = PolicyEngine::new();
+        open.unlock();
+        assert_eq!(open.gate(SecurityLevel::Caution, false), Ok(()));
+        assert_eq!(open.gate(SecurityLevel::Critical, false).unwrap_err().reason, BlockReason::DangerousCommand);
+        let lists = AppLists { allow: vec![], deny: vec![], sensitive: vec!["Terminal".to_string()] };
+        assert_eq!(check_app("terminal", &lists).unwrap_err().reason, BlockReason::SensitiveApp);
+        assert_eq!(serde_json::to_value(BlockReason::DangerousCommand).unwrap(), "dangerous_command");
+        assert_eq!(BlockReason::ShellNotAllowed.code(), "shell_not_allowed");
+    }
+
+    #[test]
+    fn test_open_file_checks_the_handling_app_and_refuses_programs() {
+        let lists = AppLists {
+            allow: vec![],
+            deny: vec!["Messages".to_string()],
+            sensitive: vec!["Terminal".to_string()],
+        };
+        let no_default = |_: &std::path::Path| None;
+        let terminal = std::path::Path::new("/System/Applications/Utilities/Terminal.app");
+        let blocked = check_open_file(terminal, None, no_default, &lists).unwrap_err();
+        assert_eq!(blocked.reason, BlockReason::LaunchesProgram);
+        for script in ["run.command", "deploy.sh", "x.tool", "Setup.pkg"] {
+            let blocked = check_open_file(std::path::Path::new(script), Some("Preview"), no_default, &lists).unwrap_err();
+            assert_eq!(blocked.reason, BlockReason::LaunchesProgram, "{}", script);
+        }
+
+        let exe = std::env::temp_dir().join(format!("steer-exe-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&exe, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+            assert_eq!(check_open_file(&exe, None, no_default, &lists).unwrap_err().reason, BlockReason::LaunchesProgram);
+        }
+        std::fs::remove_file(&exe).ok();
+
+        // The default handler is checked like an explicit app
+        let doc = std::path::Path::new("/tmp/notes.txt");
+        let blocked = check_open_file(doc, None, |_| Some("Terminal".to_string()), &lists).unwrap_err();
+        assert_eq!(blocked.reason, BlockReason::SensitiveApp);
+        let blocked = check_open_file(doc, None, |_| Some("Messages".to_string()), &lists).unwrap_err();
+        assert_eq!(blocked.reason, BlockReason::AppNotAllowed);
+        assert!(check_open_file(doc, None, |_| Some("TextEdit".to_string()), &lists).is_ok());
+        assert!(check_open_file(doc, Some("terminal"), |_| Some("TextEdit".to_string()), &lists).is_err());
+
+        // An unknown handler is only a problem when an allowlist is in force
+        assert!(check_open_file(doc, None, no_default, &lists).is_ok());
+        let allow_only = AppLists { allow: vec!["Preview".to_string()], ..lists };
+        assert_eq!(check_open_file(doc, None, no_default, &allow_only).unwrap_err().reason, BlockReason::AppNotAllowed);
+    }
+
+    #[test]
+    fn test_urls_are_checked_by_scheme_and_host() {
+        let open = HostLists { allow: vec![], deny: vec!["evil.example".to_string()] };
+        assert!(check_url("https://docs.rs/regex", &open).is_ok());
+        assert!(check_url("docs.rs", &open).is_ok());
+        assert_eq!(check_url("https://login.evil.example/x", &open).unwrap_err().reason, BlockReason::UrlNotAllowed);
+        assert_eq!(check_url("https://EVIL.example./", &open).unwrap_err().reason, BlockReason::UrlNotAllowed);
+        assert!(check_url("https://notevil.example", &open).is_ok());
+        for url in ["file:///etc/passwd", "x-apple.systempreferences:com.apple.preference.security", "ssh://host", "javascript:alert(1)"] {
+            assert_eq!(check_url(url, &open).unwrap_err().reason, BlockReason::UnsafeUrl, "{}", url);
+        }
+
+        let allow = HostLists { allow: vec!["github.com".to_string()], deny: vec![] };
+        assert!(check_url("https://gist.github.com/a", &allow).is_ok());
+        assert_eq!(check_url("https://example.com", &allow).unwrap_err().reason, BlockReason::UrlNotAllowed);
+
+        let step = AgentAction::OpenUrl { url: "https://example.com".to_string() };
+        assert_eq!(tool_policy::action_kind(&step), "system.open_url");
+        assert_eq!(PolicyEngine { write_lock: false }.classify(&step), SecurityLevel::Caution);
+    }
 }
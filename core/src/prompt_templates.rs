@@ -0,0 +1,164 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+// [Prompt Templates] The planner prompts are compiled in, so tuning them
+// (another language, an app the defaults handle badly) used to need a rebuild.
+// A file `<STEER_HOME>/prompts/<name>.txt` now replaces the built-in template
+// of that name. Files are read at startup and on `prompts reload`.
+// Placeholders are written `{name}`; unknown ones are left as written, so
+// JSON examples in a template need no escaping.
+
+const PLAN: &str = "You are an autonomous GUI Agent. Your goal is: '{goal}'.
+{goal_hint}
+Break this goal down into a linear sequence of concrete computer actions for {os}.
+{actions}
+Prefer WAIT_FOR over WAIT when waiting for something to load.
+Use FOCUS_WINDOW instead of ACTIVATE when the app may have several windows (browser profiles, Finder).
+Use EXTRACT to read names, emails, dates or amounts off the screen instead of guessing them.
+Use READ_SELECTION to read the selected text (e.g. after select-all) instead of copying it; it leaves the clipboard alone.
+Use USE_CLIPBOARD (target = read_number|search_query|text) when the goal says to use what the user copied; later steps can write {{read_number}}, {{search_query}} or {{text}} to use it.
+Use DISMISS_DIALOGS when an unexpected dialog or sheet is in the way.
+Use SCREENSHOT (target = short label) only when the goal asks to capture or document the screen.
+Use REPLY (value = the answer) when the goal is a question for you rather than a task.
+Use REPORT (value = what is wrong) when you are stuck or the goal can't be done from here; the user sees it right away.
+Pre-Check: Visual cue to verify action is possible (e.g. 'Search bar visible').
+Verification: Key visual cue to check success (e.g. 'Results appeared').
+
+Output ONLY valid JSON array of objects:
+[{ \"description\": \"...\", \"action_type\": \"CLICK\", \"target\": \"Login Button\", \"pre_check\": \"Login page visible\", \"verification\": \"Login form appears\" }, ...]";
+
+const REPLAN: &str = "You are an autonomous GUI Agent. The previous plan failed.
+Goal: '{goal}'.
+{goal_hint}
+Failed step: '{failed_step}' (type: {failed_action}, target: {failed_target}, value: {failed_value}).
+Failure type: {failure_type}.
+Strategy hint: {strategy_hint}.
+Replan with safer, simpler steps that avoid the failure.
+{actions}
+Prefer WAIT_FOR over WAIT when waiting for something to load.
+Use FOCUS_WINDOW instead of ACTIVATE when the app may have several windows (browser profiles, Finder).
+Use EXTRACT to read names, emails, dates or amounts off the screen instead of guessing them.
+Use READ_SELECTION to read the selected text (e.g. after select-all) instead of copying it; it leaves the clipboard alone.
+Use USE_CLIPBOARD (target = read_number|search_query|text) when the goal says to use what the user copied; later steps can write {{read_number}}, {{search_query}} or {{text}} to use it.
+Use DISMISS_DIALOGS when an unexpected dialog or sheet is in the way.
+Use SCREENSHOT (target = short label) only when the goal asks to capture or document the screen.
+Use REPLY (value = the answer) when the goal is a question for you rather than a task.
+Use REPORT (value = what is wrong) when you are stuck or the goal can't be done from here; the user sees it right away.
+Pre-Check: Visual cue to verify action is possible.
+Verification: Key visual cue to check success.
+
+Output ONLY valid JSON array of objects:
+[{ \"description\": \"...\", \"action_type\": \"CLICK\", \"target\": \"Login Button\", \"pre_check\": \"Login page visible\", \"verification\": \"Login form appears\" }, ...]";
+
+/// Built-in templates by name (the file name without `.txt`).
+const BUILT_IN: &[(&str, &str)] = &[("plan", PLAN), ("replan", REPLAN)];
+
+lazy_static::lazy_static! {
+    static ref OVERRIDES: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+    static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{([a-z_]+)\}").expect("Invalid placeholder pattern");
+}
+
+pub fn prompts_dir() -> PathBuf {
+    crate::paths::steer_home().join("prompts")
+}
+
+/// Non-empty `<name>.txt` files in `dir` for the built-in template names.
+fn load_from(dir: &Path) -> HashMap<String, String> {
+    let mut loaded = HashMap::new();
+    for (name, _) in BUILT_IN {
+        let path = dir.join(format!("{}.txt", name));
+        match std::fs::read_to_string(&path) {
+            Ok(text) if !text.trim().is_empty() => {
+                loaded.insert(name.to_string(), text);
+            }
+            Ok(_) => eprintln!("⚠️ Ignoring empty prompt template {}", path.display()),
+            Err(_) => {}
+        }
+    }
+    loaded
+}
+
+/// Re-read the template files. Returns the names now overridden, sorted.
+pub fn reload() -> Vec<String> {
+    let loaded = load_from(&prompts_dir());
+    let mut names: Vec<String> = loaded.keys().cloned().collect();
+    names.sort();
+    *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = loaded;
+    names
+}
+
+/// Every template name and whether a file currently overrides it.
+pub fn list() -> Vec<(&'static str, bool)> {
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+    BUILT_IN.iter().map(|(name, _)| (*name, overrides.contains_key(*name))).collect()
+}
+
+/// Replace each `{key}` in `template` with its value in `vars`, in one pass
+/// (a value that itself contains `{goal}` stays as it is).
+fn fill(template: &str, vars: &[(&str, String)]) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            vars.iter()
+                .find(|(key, _)| *key == &caps[1])
+                .map(|(_, value)| value.clone())
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn os_name() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "macOS",
+        "linux" => "Linux",
+        "windows" => "Windows",
+        other => other,
+    }
+}
+
+/// Template `name` (from file, else built in) with `vars` filled in, plus
+/// `{os}` and `{date}` (local YYYY-MM-DD).
+pub fn render(name: &str, vars: &[(&str, String)]) -> String {
+    let built_in = BUILT_IN.iter().find(|(n, _)| *n == name).map(|(_, t)| *t).unwrap_or_default();
+    let template = OVERRIDES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+        .unwrap_or_else(|| built_in.to_string());
+    let mut all: Vec<(&str, String)> = vars.to_vec();
+    all.push(("os", os_name().to_string()));
+    all.push(("date", chrono::Local::now().format("%Y-%m-%d").to_string()));
+    fill(&template, &all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_are_filled_once_and_unknown_ones_kept() {
+        let vars = [("goal", "open {goal}".to_string()), ("actions", "CLICK, TYPE".to_string())];
+        let out = fill("Goal: {goal}. Actions: {actions}. [{ \"a\": 1 }] {unknown}", &vars);
+        assert_eq!(out, "Goal: open {goal}. Actions: CLICK, TYPE. [{ \"a\": 1 }] {unknown}");
+
+        let plan = render("plan", &[("goal", "Send the report".to_string())]);
+        assert!(plan.contains("Your goal is: 'Send the report'") && plan.contains(os_name()));
+        assert!(plan.contains("[{ \"description\""));
+    }
+
+    #[test]
+    fn test_files_override_built_in_templates() {
+        let dir = std::env::temp_dir().join(format!("steer_prompts_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("plan.txt"), "목표: {goal}").unwrap();
+        std::fs::write(dir.join("replan.txt"), "  \n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a template").unwrap();
+
+        let loaded = load_from(&dir);
+        assert_eq!(loaded.keys().collect::<Vec<_>>(), vec!["plan"]);
+        assert_eq!(fill(&loaded["plan"], &[("goal", "메모 만들기".to_string())]), "목표: 메모 만들기");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
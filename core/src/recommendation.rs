@@ -12,7 +12,7 @@ pub struct AutomationProposal {
     pub n8n_prompt: String,
     // [Explainability]
     #[serde(default)]
-    pub evidence: Vec<String>,
+    pub evidence: Vec<Evidence>,
     #[serde(default)]
     pub pattern_id: Option<String>,
 }
@@ -33,15 +33,265 @@ impl Default for AutomationProposal {
 }
 
 impl AutomationProposal {
+    /// Dedup key based on what the automation does (trigger + actions),
+    /// insensitive to case, punctuation and action ordering.
     pub fn fingerprint(&self) -> String {
-        format!(
-            "{}::{}",
-            self.title.trim().to_lowercase(),
-            self.trigger.trim().to_lowercase()
-        )
+        let mut actions: Vec<String> = self
+            .actions
+            .iter()
+            .map(|a| normalize_text(a))
+            .filter(|a| !a.is_empty())
+            .collect();
+        actions.sort();
+        actions.dedup();
+
+        if actions.is_empty() {
+            // No actions to distinguish by; fall back to the title
+            return format!("{}::{}", normalize_text(&self.trigger), normalize_text(&self.title));
+        }
+        format!("{}::{}", normalize_text(&self.trigger), actions.join("|"))
+    }
+
+    /// Add `item`, keeping the higher weight when the same source and detail
+    /// is already present.
+    pub fn add_evidence(&mut self, item: Evidence) {
+        match self.evidence.iter_mut().find(|e| e.source == item.source && e.detail == item.detail) {
+            Some(existing) => existing.weight = existing.weight.max(item.weight),
+            None => self.evidence.push(item),
+        }
+    }
+
+    /// Count the model's own confidence as one more independent piece of
+    /// evidence (noisy-OR), so evidence only ever adds to it: a confident
+    /// proposal never drops below the bar because its pattern is still young.
+    pub fn apply_evidence(&mut self) {
+        let support = evidence_score(&self.evidence);
+        self.confidence = 1.0 - (1.0 - self.confidence.clamp(0.0, 1.0)) * (1.0 - support);
+    }
+}
+
+/// One reason a recommendation was made, and how much it counts toward its
+/// confidence (0..1).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Evidence {
+    /// pattern, frequency, similarity, keywords or sample
+    pub source: String,
+    pub detail: String,
+    pub weight: f64,
+}
+
+impl Evidence {
+    pub fn new(source: &str, detail: impl Into<String>, weight: f64) -> Self {
+        Self { source: source.to_string(), detail: detail.into(), weight: weight.clamp(0.0, 1.0) }
+    }
+}
+
+// Rows stored before evidence was structured hold plain strings
+impl<'de> Deserialize<'de> for Evidence {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Structured { source: String, detail: String, #[serde(default)] weight: f64 },
+            Note(String),
+        }
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Structured { source, detail, weight } => Evidence { source, detail, weight },
+            Stored::Note(detail) => Evidence { source: "note".to_string(), detail, weight: 0.0 },
+        })
+    }
+}
+
+/// Combined support of independent pieces of evidence: 1 - Π(1 - weight).
+pub fn evidence_score(evidence: &[Evidence]) -> f64 {
+    1.0 - evidence.iter().fold(1.0, |rest, e| rest * (1.0 - e.weight.clamp(0.0, 1.0)))
+}
+
+/// Weighted evidence for a detected pattern. Repetition counts most, saturating
+/// at 10 occurrences; how alike the occurrences were counts next.
+/// An analyzer rule match counts the rule's confidence in place of similarity.
+pub fn pattern_evidence(pattern: &crate::pattern_detector::DetectedPattern) -> Vec<Evidence> {
+    let single = pattern.pattern_type == crate::pattern_detector::PatternType::SingleEvent;
+    let mut evidence = vec![
+        Evidence::new("pattern", pattern.description.clone(), 0.1),
+        Evidence::new(
+            "frequency",
+            format!("Found {} occurrences", pattern.occurrences),
+            (pattern.occurrences as f64 / 10.0).min(1.0) * 0.5,
+        ),
+        if single {
+            Evidence::new(
+                "rule",
+                format!("Matched an analyzer rule ({:.0}% confidence)", pattern.similarity_score * 100.0),
+                pattern.similarity_score * 0.3,
+            )
+        } else {
+            Evidence::new(
+                "similarity",
+                format!("{:.0}% alike", pattern.similarity_score * 100.0),
+                pattern.similarity_score * 0.3,
+            )
+        },
+    ];
+    if let Some(sample) = pattern.sample_events.first() {
+        let snippet: String = sample.chars().take(140).collect();
+        let snippet = if snippet.len() < sample.len() { format!("{}...", snippet) } else { snippet };
+        evidence.push(Evidence::new("sample", snippet, 0.0));
+    }
+    evidence
+}
+
+/// The recommendation a detected pattern turns into, before an LLM has
+/// written it up (see `/api/analyze`).
+pub fn proposal_from_pattern(pattern: &crate::pattern_detector::DetectedPattern) -> AutomationProposal {
+    if pattern.pattern_type == crate::pattern_detector::PatternType::SingleEvent {
+        return AutomationProposal {
+            title: pattern.description.clone(),
+            summary: format!(
+                "Seen {} time(s); suggested by an analyzer rule before it became a routine.",
+                pattern.occurrences
+            ),
+            // Per rule, so suggestions from different rules don't dedupe each other
+            trigger: format!("Single event: {}", pattern.description),
+            actions: vec!["Automate".to_string()],
+            n8n_prompt: format!("Create an automation for: {}", pattern.description),
+            confidence: pattern.similarity_score,
+            evidence: pattern_evidence(pattern),
+            pattern_id: Some(pattern.pattern_id.clone()),
+        };
+    }
+    AutomationProposal {
+        title: format!("New Pattern: {}", pattern.description),
+        summary: format!("Detected {} repeats. AI suggests automating this.", pattern.occurrences),
+        trigger: format!("Pattern Type: {:?}", pattern.pattern_type),
+        actions: vec!["Analyze".to_string(), "Automate".to_string()],
+        n8n_prompt: format!("Create an automation for: {}", pattern.description),
+        confidence: pattern.similarity_score,
+        evidence: pattern_evidence(pattern),
+        pattern_id: Some(pattern.pattern_id.clone()),
+    }
+}
+
+/// Save `proposals` as pending recommendations; returns how many were new.
+pub fn persist_proposals(proposals: &[AutomationProposal]) -> usize {
+    let mut inserted = 0;
+    for proposal in proposals {
+        match crate::db::insert_recommendation(proposal) {
+            Ok(true) => inserted += 1,
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to save pattern: {}", e),
+        }
+    }
+    inserted
+}
+
+/// The detected pattern a recommendation came from, as stored with it.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PatternInfo {
+    pub pattern_id: String,
+    pub pattern_type: String,
+    pub description: String,
+    pub occurrences: i64,
+    pub similarity: f64,
+}
+
+/// Why a stored recommendation was made and how sure it is (`explain <id>`).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Explanation {
+    pub id: i64,
+    pub title: String,
+    pub status: String,
+    pub confidence: f64,
+    /// What the weighted evidence alone supports (see `evidence_score`)
+    pub evidence_score: f64,
+    /// Strongest first; weight-0 items (samples, old notes) are context only
+    pub evidence: Vec<Evidence>,
+    pub pattern_id: Option<String>,
+    /// None when the pattern is no longer stored
+    pub pattern: Option<PatternInfo>,
+    /// From the pattern, else from the frequency evidence
+    pub occurrences: Option<i64>,
+}
+
+pub fn explain(rec: &crate::db::Recommendation, pattern: Option<PatternInfo>) -> Explanation {
+    let mut evidence = rec.evidence.clone();
+    evidence.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap_or(std::cmp::Ordering::Equal));
+    let occurrences = pattern.as_ref().map(|p| p.occurrences).or_else(|| {
+        evidence.iter().filter(|e| e.source == "frequency").find_map(|e| {
+            e.detail.split_whitespace().find_map(|word| word.parse::<i64>().ok())
+        })
+    });
+    Explanation {
+        id: rec.id,
+        title: rec.title.clone(),
+        status: rec.status.clone(),
+        confidence: rec.confidence,
+        evidence_score: evidence_score(&evidence),
+        evidence,
+        pattern_id: rec.pattern_id.clone(),
+        pattern,
+        occurrences,
+    }
+}
+
+impl Explanation {
+    /// Plain-text version for the REPL and the API's `text` field.
+    pub fn render(&self) -> String {
+        let pct = |v: f64| format!("{:.0}%", v * 100.0);
+        let mut lines = vec![format!("#{} {} ({}), confidence {}", self.id, self.title, self.status, pct(self.confidence))];
+
+        if self.evidence.is_empty() {
+            lines.push("No evidence was stored with this recommendation.".to_string());
+        } else {
+            lines.push("Evidence:".to_string());
+            for e in &self.evidence {
+                if e.weight > 0.0 {
+                    lines.push(format!("  + {:<10} {:>4}  {}", e.source, pct(e.weight), e.detail));
+                } else {
+                    lines.push(format!("    {:<10}       {}", e.source, e.detail));
+                }
+            }
+        }
+
+        match (&self.pattern, &self.pattern_id) {
+            (Some(p), _) => lines.push(format!(
+                "Pattern: {} [{}] {}: seen {} times, {} alike",
+                p.pattern_id, p.pattern_type, p.description, p.occurrences, pct(p.similarity)
+            )),
+            (None, Some(id)) => lines.push(format!("Pattern: {} (no longer stored)", id)),
+            (None, None) => lines.push("Pattern: none (not made from a detected pattern)".to_string()),
+        }
+        if let Some(n) = self.occurrences {
+            lines.push(format!("Occurrences: {}", n));
+        }
+
+        let weighted = self.evidence.iter().filter(|e| e.weight > 0.0).count();
+        if weighted == 0 {
+            lines.push(format!("Confidence: {} is the model's or template's own estimate; no weighted evidence backs it.", pct(self.confidence)));
+        } else {
+            lines.push(format!(
+                "Confidence: the evidence alone supports {} (1 - product of (1 - weight) over {} item(s)); the stored {} also reflects the model's or template's own estimate.",
+                pct(self.evidence_score),
+                weighted,
+                pct(self.confidence)
+            ));
+        }
+        lines.join("\n")
     }
 }
 
+/// Lowercase, strip punctuation and collapse whitespace.
+pub fn normalize_text(input: &str) -> String {
+    input
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 // --- Template Engine ---
 
 pub struct Template {
@@ -192,11 +442,9 @@ impl TemplateMatcher {
                     .collect();
                 
                 // Construct Evidence (The Trust UX part)
-                let evidence = vec![
-                    format!("Pattern: {}", pattern.description),
-                    format!("Frequency: Found {} occurrences", pattern.occurrences),
-                    format!("Matched: {}", matched_keywords.join(", ")),
-                ];
+                let mut evidence = pattern_evidence(pattern);
+                evidence.retain(|e| e.source != "sample");
+                evidence.push(Evidence::new("keywords", format!("Matched: {}", matched_keywords.join(", ")), match_bonus));
 
                 return Some(AutomationProposal {
                     title: tmpl.title.to_string(),
@@ -274,6 +522,29 @@ mod tests {
     use chrono::Utc;
     use serde_json::json;
 
+    #[test]
+    fn test_fingerprint_normalization() {
+        let a = AutomationProposal {
+            title: "Daily Invoice Digest".to_string(),
+            trigger: "Every day at 9am".to_string(),
+            actions: vec!["Fetch invoices".to_string(), "Send to Slack!".to_string()],
+            ..Default::default()
+        };
+        let b = AutomationProposal {
+            title: "Morning invoice summary".to_string(),
+            trigger: "every day, at 9AM.".to_string(),
+            actions: vec!["send to slack".to_string(), "fetch  invoices".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        let c = AutomationProposal {
+            actions: vec!["Fetch invoices".to_string()],
+            ..a.clone()
+        };
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
     #[test]
     fn test_token_extraction() {
         let pattern = DetectedPattern {
@@ -321,4 +592,91 @@ mod tests {
         assert_eq!(p.title, "Email Follow-Up Reminder");
         assert!(p.evidence.len() > 0);
     }
+
+    #[test]
+    fn test_evidence_scoring_and_dedupe() {
+        let mut proposal = AutomationProposal { confidence: 0.9, ..Default::default() };
+        proposal.add_evidence(Evidence::new("frequency", "Found 4 occurrences", 0.2));
+        proposal.add_evidence(Evidence::new("frequency", "Found 4 occurrences", 0.4));
+        proposal.add_evidence(Evidence::new("similarity", "85% alike", 0.25));
+        assert_eq!(proposal.evidence.len(), 2);
+        assert_eq!(proposal.evidence[0].weight, 0.4);
+
+        // 1 - (0.6 * 0.75) = 0.55; combined with the model's 0.9: 1 - (0.1 * 0.45)
+        assert!((evidence_score(&proposal.evidence) - 0.55).abs() < 1e-9);
+        proposal.apply_evidence();
+        assert!((proposal.confidence - 0.955).abs() < 1e-9);
+
+        // Thin evidence never pulls a confident proposal under the 0.7 bar
+        let mut confident = AutomationProposal { confidence: 0.8, ..Default::default() };
+        confident.add_evidence(Evidence::new("frequency", "Found 1 occurrences", 0.05));
+        confident.apply_evidence();
+        // 1 - (0.2 * 0.95)
+        assert!((confident.confidence - 0.81).abs() < 1e-9);
+        let mut bare = AutomationProposal { confidence: 0.8, ..Default::default() };
+        bare.apply_evidence();
+        assert!((bare.confidence - 0.8).abs() < 1e-9);
+
+        // Legacy rows stored plain strings
+        let legacy: Vec<Evidence> = serde_json::from_str(r#"["Pattern: Chrome → Slack", {"source":"frequency","detail":"x","weight":0.3}]"#).unwrap();
+        assert_eq!(legacy[0], Evidence::new("note", "Pattern: Chrome → Slack", 0.0));
+        assert_eq!(legacy[1].weight, 0.3);
+    }
+
+    #[test]
+    fn test_explain_renders_evidence_pattern_and_confidence() {
+        let rec = crate::db::Recommendation {
+            id: 7,
+            status: "pending".to_string(),
+            title: "Upload invoices".to_string(),
+            summary: String::new(),
+            trigger: String::new(),
+            actions: vec![],
+            n8n_prompt: String::new(),
+            confidence: 0.725,
+            workflow_id: None,
+            workflow_json: None,
+            evidence: vec![
+                Evidence::new("sample", "key_input invoice", 0.0),
+                Evidence::new("similarity", "85% alike", 0.25),
+                Evidence::new("frequency", "Found 4 occurrences", 0.4),
+            ],
+            pattern_id: Some("p-1".to_string()),
+            last_error: None,
+        };
+
+        // Without the pattern row, the count comes from the frequency evidence
+        let without = explain(&rec, None);
+        assert_eq!(without.occurrences, Some(4));
+        assert_eq!(without.evidence[0].source, "frequency");
+        assert!((without.evidence_score - 0.55).abs() < 1e-9);
+        assert!(without.render().contains("Pattern: p-1 (no longer stored)"));
+
+        let pattern = PatternInfo {
+            pattern_id: "p-1".to_string(),
+            pattern_type: "keyword_repeat".to_string(),
+            description: "Repeated keyword: 'invoice'".to_string(),
+            occurrences: 6,
+            similarity: 0.85,
+        };
+        let text = explain(&rec, Some(pattern)).render();
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            vec![
+                "#7 Upload invoices (pending), confidence 72%",
+                "Evidence:",
+                "  + frequency   40%  Found 4 occurrences",
+                "  + similarity  25%  85% alike",
+                "    sample           key_input invoice",
+                "Pattern: p-1 [keyword_repeat] Repeated keyword: 'invoice': seen 6 times, 85% alike",
+                "Occurrences: 6",
+                "Confidence: the evidence alone supports 55% (1 - product of (1 - weight) over 2 item(s)); the stored 72% also reflects the model's or template's own estimate.",
+            ]
+        );
+
+        let bare = crate::db::Recommendation { evidence: vec![], pattern_id: None, ..rec };
+        let text = explain(&bare, None).render();
+        assert!(text.contains("No evidence was stored"), "{}", text);
+        assert!(text.contains("no weighted evidence backs it"), "{}", text);
+    }
 }
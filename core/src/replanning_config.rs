@@ -41,6 +41,15 @@ fn build_strategies() -> HashMap<&'static str, ReplanStrategy> {
             fix_hint: Some("Add a WAIT step before retrying; verify UI is stable."),
         },
     );
+    map.insert(
+        "goal_incomplete",
+        ReplanStrategy {
+            stop: false,
+            reason: "All steps ran but the goal checklist is not satisfied",
+            severity: "medium",
+            fix_hint: Some("Continue from the current screen; only add the steps needed to satisfy the unmet checks."),
+        },
+    );
     map.insert(
         "network_error",
         ReplanStrategy {
@@ -107,6 +116,46 @@ fn build_strategies() -> HashMap<&'static str, ReplanStrategy> {
     map
 }
 
+/// When fixed recovery steps (replan templates, checklist-forced steps) may
+/// replace the LLM's own plan: only once LLM-planned work has failed at
+/// least `min_llm_failures` times in the run.
+///
+///   STEER_FORCED_PLANS=aggressive|conservative   preset (default aggressive)
+///   STEER_FORCED_PLAN_MIN_FAILURES=N             overrides the preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForcedPlanGate {
+    pub min_llm_failures: u32,
+}
+
+impl ForcedPlanGate {
+    /// Recovery steps take over on the first failure.
+    pub const AGGRESSIVE: Self = Self { min_llm_failures: 1 };
+    /// The LLM gets to replan its own failure once before recovery steps take over.
+    pub const CONSERVATIVE: Self = Self { min_llm_failures: 2 };
+
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var("STEER_FORCED_PLANS").ok().as_deref(),
+            std::env::var("STEER_FORCED_PLAN_MIN_FAILURES").ok().as_deref(),
+        )
+    }
+
+    fn from_values(preset: Option<&str>, min_failures: Option<&str>) -> Self {
+        let base = match preset.map(|p| p.trim().to_lowercase()).as_deref() {
+            Some("conservative") => Self::CONSERVATIVE,
+            _ => Self::AGGRESSIVE,
+        };
+        match min_failures.and_then(|v| v.trim().parse().ok()) {
+            Some(min_llm_failures) => Self { min_llm_failures },
+            None => base,
+        }
+    }
+
+    pub fn allows(&self, llm_failures: u32) -> bool {
+        llm_failures >= self.min_llm_failures
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +173,14 @@ mod tests {
         assert!(!strategy.stop);
         assert!(strategy.reason.contains("Unknown"));
     }
+
+    #[test]
+    fn forced_plan_gate_presets() {
+        assert_eq!(ForcedPlanGate::from_values(None, None), ForcedPlanGate::AGGRESSIVE);
+        assert_eq!(ForcedPlanGate::from_values(Some("Conservative"), None), ForcedPlanGate::CONSERVATIVE);
+        assert_eq!(ForcedPlanGate::from_values(Some("conservative"), Some("3")).min_llm_failures, 3);
+        assert!(ForcedPlanGate::AGGRESSIVE.allows(1));
+        assert!(!ForcedPlanGate::CONSERVATIVE.allows(1));
+        assert!(ForcedPlanGate::CONSERVATIVE.allows(2));
+    }
 }
@@ -0,0 +1,277 @@
+use crate::db;
+use crate::feedback_collector;
+use crate::recommendation::Evidence;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// [Reporting] View-models shared by the REPL and the HTTP API. Both render
+// recommendations, metrics and routines from the same `db` rows; building
+// them here once means a field added for the GUI shows up in the CLI too,
+// and the two can't disagree on derived numbers like the approval rate.
+// The JSON shape is what the desktop app reads (`rec.title`,
+// `rec.confidence`, `r.cron_expression`, `r.enabled`, `r.next_run`).
+// `Display` is the REPL's rendering.
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecommendationView {
+    pub id: i64,
+    pub status: String,
+    pub title: String,
+    pub summary: String,
+    pub trigger: String,
+    pub confidence: f64,
+    /// Why it was recommended
+    pub evidence: Vec<Evidence>,
+    pub last_error: Option<String>,
+}
+
+impl From<db::Recommendation> for RecommendationView {
+    fn from(rec: db::Recommendation) -> Self {
+        Self {
+            id: rec.id,
+            status: rec.status,
+            title: rec.title,
+            summary: rec.summary,
+            trigger: rec.trigger,
+            confidence: rec.confidence,
+            evidence: rec.evidence,
+            last_error: rec.last_error,
+        }
+    }
+}
+
+impl fmt::Display for RecommendationView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  [{}] {} (confidence {:.2})", self.id, self.title, self.confidence)?;
+        writeln!(f, "       Trigger: {}", self.trigger)?;
+        write!(f, "       Summary: {}", self.summary)?;
+        if let Some(error) = &self.last_error {
+            write!(f, "\n       Last error: {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecommendationMetricsView {
+    pub total: i64,
+    pub approved: i64,
+    pub rejected: i64,
+    pub failed: i64,
+    pub pending: i64,
+    pub later: i64,
+    /// Percent of all recommendations that were approved
+    pub approval_rate: f64,
+    pub last_created_at: Option<String>,
+    pub by_status: BTreeMap<String, i64>,
+}
+
+impl RecommendationMetricsView {
+    /// Current metrics; all zero when the database can't be read.
+    pub fn load() -> Self {
+        db::get_recommendation_metrics()
+            .map(Self::from)
+            .unwrap_or_else(|_| Self::from(db::RecommendationMetrics {
+                total: 0,
+                approved: 0,
+                rejected: 0,
+                failed: 0,
+                pending: 0,
+                later: 0,
+                last_created_at: None,
+                by_status: Default::default(),
+            }))
+    }
+}
+
+impl From<db::RecommendationMetrics> for RecommendationMetricsView {
+    fn from(metrics: db::RecommendationMetrics) -> Self {
+        let approval_rate = if metrics.total > 0 {
+            (metrics.approved as f64 / metrics.total as f64) * 100.0
+        } else {
+            0.0
+        };
+        Self {
+            total: metrics.total,
+            approved: metrics.approved,
+            rejected: metrics.rejected,
+            failed: metrics.failed,
+            pending: metrics.pending,
+            later: metrics.later,
+            approval_rate,
+            last_created_at: metrics.last_created_at,
+            by_status: metrics.by_status,
+        }
+    }
+}
+
+impl fmt::Display for RecommendationMetricsView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Recommendations: {} total, {} pending, {} approved ({:.1}%), {} rejected, {} failed, {} later",
+            self.total, self.pending, self.approved, self.approval_rate, self.rejected, self.failed, self.later
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct QualityView {
+    pub total: u32,
+    pub success: u32,
+    /// Percent of executions that succeeded
+    pub rate: f64,
+}
+
+impl From<feedback_collector::QualityMetrics> for QualityView {
+    fn from(metrics: feedback_collector::QualityMetrics) -> Self {
+        Self { total: metrics.total_executions, success: metrics.successful_executions, rate: metrics.success_rate }
+    }
+}
+
+impl fmt::Display for QualityView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Quality: {}/{} executions ({:.1}% success rate)", self.success, self.total, self.rate)
+    }
+}
+
+/// A routine plus a one-line preview of what its last recorded run did.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutineView {
+    #[serde(flatten)]
+    pub routine: db::Routine,
+    pub preview: Option<String>,
+}
+
+impl RoutineView {
+    pub fn new(routine: db::Routine, steps: &[db::GoalStep]) -> Self {
+        let preview = Some(crate::scheduler::step_preview(steps)).filter(|p| !p.is_empty());
+        Self { routine, preview }
+    }
+
+    /// Every routine, with the preview of its last run.
+    pub fn load_all() -> rusqlite::Result<Vec<Self>> {
+        Ok(db::get_all_routines()?
+            .into_iter()
+            .map(|routine| {
+                let steps = db::get_routine_steps(routine.id).unwrap_or_default();
+                Self::new(routine, &steps)
+            })
+            .collect())
+    }
+}
+
+impl fmt::Display for RoutineView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = &self.routine;
+        write!(
+            f,
+            "  [{}] {} {} ({}) next: {}",
+            r.id,
+            if r.enabled { "ON " } else { "OFF" },
+            r.name,
+            r.cron_expression,
+            r.next_run.as_deref().unwrap_or("pending")
+        )?;
+        if let Some(preview) = &self.preview {
+            write!(f, "\n       Last run: {}", preview)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recommendation() -> db::Recommendation {
+        db::Recommendation {
+            id: 7,
+            status: "failed".to_string(),
+            title: "Backup downloads".to_string(),
+            summary: "Copy new PDFs to Drive".to_string(),
+            trigger: "File created in Downloads".to_string(),
+            actions: vec!["upload".to_string()],
+            n8n_prompt: "When a PDF lands in Downloads, upload it".to_string(),
+            confidence: 0.82,
+            workflow_id: None,
+            workflow_json: None,
+            evidence: vec![Evidence::new("frequency", "12 times this week", 0.6)],
+            pattern_id: None,
+            last_error: Some("node type unknown".to_string()),
+        }
+    }
+
+    fn keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn test_recommendation_view_has_frontend_shape() {
+        let view = RecommendationView::from(recommendation());
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(keys(&json), vec!["confidence", "evidence", "id", "last_error", "status", "summary", "title", "trigger"]);
+        assert_eq!(json["title"], "Backup downloads");
+        assert_eq!(json["confidence"], 0.82);
+        assert_eq!(json["evidence"][0]["source"], "frequency");
+
+        let text = view.to_string();
+        assert!(text.starts_with("  [7] Backup downloads (confidence 0.82)"));
+        assert!(text.ends_with("Last error: node type unknown"));
+    }
+
+    #[test]
+    fn test_metrics_views_have_frontend_shape() {
+        let metrics = RecommendationMetricsView::from(db::RecommendationMetrics {
+            total: 8,
+            approved: 2,
+            rejected: 3,
+            failed: 1,
+            pending: 2,
+            later: 0,
+            last_created_at: Some("2026-01-02T03:04:05Z".to_string()),
+            by_status: BTreeMap::from([("approved".to_string(), 2), ("pending".to_string(), 2)]),
+        });
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(
+            keys(&json),
+            vec!["approval_rate", "approved", "by_status", "failed", "last_created_at", "later", "pending", "rejected", "total"]
+        );
+        assert_eq!(json["approval_rate"], 25.0);
+        assert_eq!(json["by_status"]["pending"], 2);
+        assert!(metrics.to_string().contains("2 approved (25.0%)"));
+
+        let quality = QualityView::from(feedback_collector::QualityMetrics {
+            total_executions: 4,
+            successful_executions: 3,
+            success_rate: 75.0,
+        });
+        assert_eq!(serde_json::to_value(&quality).unwrap(), serde_json::json!({ "total": 4, "success": 3, "rate": 75.0 }));
+        assert_eq!(quality.to_string(), "Quality: 3/4 executions (75.0% success rate)");
+    }
+
+    #[test]
+    fn test_routine_view_flattens_routine_fields() {
+        let routine = db::Routine {
+            id: 3,
+            name: "Morning briefing".to_string(),
+            cron_expression: "0 0 9 * * *".to_string(),
+            prompt: "Summarize my inbox".to_string(),
+            enabled: true,
+            last_run: None,
+            next_run: Some("2026-01-02T09:00:00Z".to_string()),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            success_check: None,
+        };
+        let view = RoutineView { routine, preview: Some("open Mail → summarize".to_string()) };
+        let json = serde_json::to_value(&view).unwrap();
+        for key in ["id", "name", "cron_expression", "enabled", "next_run", "preview"] {
+            assert!(json.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(json["enabled"], true);
+        assert!(view.to_string().contains("[3] ON  Morning briefing (0 0 9 * * *) next: 2026-01-02T09:00:00Z"));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+// [Routine Check] A routine's executor returning Ok only means the plan ran
+// to the end, not that the briefing actually went out. A routine can carry a
+// success check, evaluated after the run, and the check decides the recorded
+// status. Stored as JSON on the routine:
+//
+//   {"kind": "telegram_sent", "within_secs": 60}
+//   {"kind": "shell", "command": "cat ~/briefing.md", "exit_code": 0}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuccessCheck {
+    /// A Telegram message went out no more than `within_secs` ago
+    TelegramSent {
+        #[serde(default = "default_within_secs")]
+        within_secs: i64,
+    },
+    /// `command` exits with `exit_code`
+    Shell {
+        command: String,
+        #[serde(default)]
+        exit_code: i32,
+    },
+}
+
+fn default_within_secs() -> i64 {
+    60
+}
+
+/// Parse and validate a stored check.
+pub fn parse(text: &str) -> Result<SuccessCheck, String> {
+    let check: SuccessCheck = serde_json::from_str(text).map_err(|e| format!("Invalid success check: {}", e))?;
+    match &check {
+        SuccessCheck::TelegramSent { within_secs } if *within_secs <= 0 => {
+            Err("Invalid success check: within_secs must be positive".to_string())
+        }
+        SuccessCheck::Shell { command, .. } if command.trim().is_empty() => {
+            Err("Invalid success check: empty command".to_string())
+        }
+        SuccessCheck::Shell { command, .. } => match crate::shell_actions::should_block_shell(command) {
+            Some(reason) => Err(format!("Invalid success check: {}", reason)),
+            None => Ok(check),
+        },
+        _ => Ok(check),
+    }
+}
+
+/// Evaluate `check`. `last_telegram` is when a Telegram message last went
+/// out; `run_shell` runs a command and returns its exit code.
+pub fn evaluate_with(
+    check: &SuccessCheck,
+    now: chrono::DateTime<chrono::Utc>,
+    last_telegram: Option<chrono::DateTime<chrono::Utc>>,
+    run_shell: impl FnOnce(&str) -> anyhow::Result<i32>,
+) -> Result<(), String> {
+    match check {
+        SuccessCheck::TelegramSent { within_secs } => match last_telegram {
+            Some(sent) if (now - sent).num_seconds() <= *within_secs => Ok(()),
+            Some(sent) => Err(format!(
+                "last Telegram message was sent {}s ago (wanted within {}s)",
+                (now - sent).num_seconds(),
+                within_secs
+            )),
+            None => Err("no Telegram message was sent".to_string()),
+        },
+        SuccessCheck::Shell { command, exit_code } => match run_shell(command) {
+            Ok(code) if code == *exit_code => Ok(()),
+            Ok(code) => Err(format!("`{}` exited with {} (wanted {})", command, code, exit_code)),
+            Err(e) => Err(format!("`{}` could not run: {}", command, e)),
+        },
+    }
+}
+
+/// How long a shell check may run before it is killed and fails.
+const SHELL_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Evaluate a stored check for the run tracked as `run` (see
+/// `telegram::track_sends`). Shell checks go through the executor's shell
+/// path, so the policy, allowlist and approvals apply.
+pub async fn evaluate(text: String, run: String) -> Result<(), String> {
+    let check = parse(&text)?;
+    let shell = match &check {
+        SuccessCheck::Shell { command, .. } => {
+            Some(run_shell_check(command, &crate::disabled_actions::DisabledActions::from_env()).await)
+        }
+        SuccessCheck::TelegramSent { .. } => None,
+    };
+    let last_telegram = crate::integrations::telegram::take_last_sent(&run);
+    evaluate_with(&check, chrono::Utc::now(), last_telegram, |_| {
+        shell.unwrap_or_else(|| Err(anyhow::anyhow!("not a shell check")))
+    })
+}
+
+/// Exit code of a shell check, refused outright while SHELL is disabled.
+async fn run_shell_check(command: &str, disabled: &crate::disabled_actions::DisabledActions) -> anyhow::Result<i32> {
+    disabled.check(crate::disabled_actions::SHELL).map_err(anyhow::Error::msg)?;
+    crate::executor::run_shell_status(command, SHELL_CHECK_TIMEOUT).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_parse_with_defaults_and_reject_unsafe_commands() {
+        assert_eq!(parse(r#"{"kind": "telegram_sent"}"#).unwrap(), SuccessCheck::TelegramSent { within_secs: 60 });
+        assert_eq!(
+            parse(r#"{"kind": "shell", "command": "test -s briefing.md"}"#).unwrap(),
+            SuccessCheck::Shell { command: "test -s briefing.md".to_string(), exit_code: 0 }
+        );
+        assert!(parse(r#"{"kind": "shell", "command": "ls; rm -rf ~"}"#).unwrap_err().contains("composite"));
+        assert!(parse(r#"{"kind": "telegram_sent", "within_secs": 0}"#).is_err());
+        assert!(parse(r#"{"kind": "email_sent"}"#).is_err());
+    }
+
+    #[test]
+    fn test_checks_decide_from_telegram_time_and_exit_code() {
+        let now = chrono::Utc::now();
+        let telegram = SuccessCheck::TelegramSent { within_secs: 60 };
+        let no_shell = |_: &str| -> anyhow::Result<i32> { unreachable!() };
+        assert!(evaluate_with(&telegram, now, Some(now - chrono::Duration::seconds(20)), no_shell).is_ok());
+        assert!(evaluate_with(&telegram, now, Some(now - chrono::Duration::seconds(90)), no_shell).unwrap_err().contains("90s ago"));
+        assert_eq!(evaluate_with(&telegram, now, None, no_shell).unwrap_err(), "no Telegram message was sent");
+
+        let shell = SuccessCheck::Shell { command: "grep -q sent log.txt".to_string(), exit_code: 0 };
+        assert!(evaluate_with(&shell, now, None, |_| Ok(0)).is_ok());
+        assert!(evaluate_with(&shell, now, None, |_| Ok(1)).unwrap_err().contains("exited with 1"));
+        assert!(evaluate_with(&shell, now, None, |_| Err(anyhow::anyhow!("no sh"))).unwrap_err().contains("could not run"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_check_is_refused_while_shell_is_disabled() {
+        use crate::disabled_actions::DisabledActions;
+        let err = run_shell_check("pwd", &DisabledActions::parse("SHELL")).await.unwrap_err();
+        assert!(err.to_string().contains("SHELL actions are disabled"), "{}", err);
+        assert_eq!(run_shell_check("pwd", &DisabledActions::parse("OPEN_FILE")).await.unwrap(), 0);
+    }
+}
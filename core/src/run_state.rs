@@ -0,0 +1,207 @@
+use anyhow::Result;
+
+// [Run State] Data a goal run picks up along the way for later steps to use.
+// USE_CLIPBOARD takes what the user already copied (a number, a search query,
+// any text) so a task can be primed with their own data; later steps refer to
+// it with `{{read_number}}`, `{{search_query}}` or `{{text}}` in their target
+// or value, filled in just before the step runs.
+// A step that refers to a value no USE_CLIPBOARD has filled yet (paste before
+// copy) is stopped in every app, rather than typing the literal placeholder
+// or an empty value into whatever is in front.
+
+/// How USE_CLIPBOARD reads the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardUse {
+    ReadNumber,
+    SearchQuery,
+    Text,
+}
+
+impl ClipboardUse {
+    pub const NAMES: &'static [&'static str] = &["read_number", "search_query", "text"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "read_number" | "number" => Some(Self::ReadNumber),
+            "search_query" | "query" => Some(Self::SearchQuery),
+            "text" | "" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Longest search query taken from the clipboard.
+const MAX_QUERY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunState {
+    pub last_read_number: Option<f64>,
+    pub search_query: Option<String>,
+    pub text: Option<String>,
+}
+
+impl RunState {
+    /// Store `clipboard` as `kind`; returns the value stored, as later steps see it.
+    pub fn use_clipboard(&mut self, kind: ClipboardUse, clipboard: &str) -> Result<String> {
+        match kind {
+            ClipboardUse::ReadNumber => {
+                let number = first_number(clipboard)
+                    .ok_or_else(|| anyhow::anyhow!("Clipboard has no number to read ({} chars)", clipboard.chars().count()))?;
+                self.last_read_number = Some(number);
+                Ok(format_number(number))
+            }
+            ClipboardUse::SearchQuery => {
+                let query: String = clipboard.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(MAX_QUERY_CHARS).collect();
+                if query.is_empty() {
+                    anyhow::bail!("Clipboard is empty; nothing to search for");
+                }
+                self.search_query = Some(query.clone());
+                Ok(query)
+            }
+            ClipboardUse::Text => {
+                if clipboard.trim().is_empty() {
+                    anyhow::bail!("Clipboard is empty");
+                }
+                self.text = Some(clipboard.to_string());
+                Ok(clipboard.to_string())
+            }
+        }
+    }
+
+    /// `text` with placeholders for values this run has filled in; the rest are left as written.
+    pub fn fill(&self, text: &str) -> String {
+        let mut filled = text.to_string();
+        if let Some(n) = self.last_read_number {
+            filled = filled.replace("{{read_number}}", &format_number(n));
+        }
+        if let Some(q) = &self.search_query {
+            filled = filled.replace("{{search_query}}", q);
+        }
+        if let Some(t) = &self.text {
+            filled = filled.replace("{{text}}", t);
+        }
+        filled
+    }
+
+    /// Placeholders in `text` this run has no value for yet.
+    pub fn unfilled(&self, text: &str) -> Vec<&'static str> {
+        [
+            ("{{read_number}}", self.last_read_number.is_some()),
+            ("{{search_query}}", self.search_query.is_some()),
+            ("{{text}}", self.text.is_some()),
+        ]
+        .into_iter()
+        .filter(|(placeholder, filled)| !filled && text.contains(placeholder))
+        .map(|(placeholder, _)| placeholder)
+        .collect()
+    }
+
+    /// Err with the reason when `step` (as filled) still needs a value that
+    /// nothing has copied in yet.
+    pub fn check_primed(&self, step: &crate::executor::PlanStep) -> Result<()> {
+        let mut missing = self.unfilled(step.target.as_deref().unwrap_or_default());
+        for placeholder in self.unfilled(step.value.as_deref().unwrap_or_default()) {
+            if !missing.contains(&placeholder) {
+                missing.push(placeholder);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "{} step uses {} before anything was copied in (a USE_CLIPBOARD step must come first)",
+            step.action_type,
+            missing.join(", ")
+        )
+    }
+
+    pub fn fill_step(&self, step: &crate::executor::PlanStep) -> crate::executor::PlanStep {
+        let mut step = step.clone();
+        step.target = step.target.map(|t| self.fill(&t));
+        step.value = step.value.map(|v| self.fill(&v));
+        step
+    }
+}
+
+/// First number in `text`: "Total: $1,234.50" reads as 1234.5.
+fn first_number(text: &str) -> Option<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    let start = chars.iter().position(|c| c.is_ascii_digit())?;
+    let negative = start > 0 && chars[start - 1] == '-';
+    let digits: String = chars[start..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit() || **c == '.' || **c == ',')
+        .filter(|c| **c != ',')
+        .collect();
+    let number: f64 = digits.trim_end_matches('.').parse().ok()?;
+    Some(if negative { -number } else { number })
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_use_clipboard_as_read_number_sets_last_read_number() {
+        let mut state = RunState::default();
+        let kind = ClipboardUse::parse("read_number").unwrap();
+        assert_eq!(state.use_clipboard(kind, "Invoice total: $1,234.50 (due Friday)").unwrap(), "1234.5");
+        assert_eq!(state.last_read_number, Some(1234.5));
+
+        assert_eq!(state.use_clipboard(kind, "  -42 ").unwrap(), "-42");
+        assert_eq!(state.last_read_number, Some(-42.0));
+        // A clipboard without a number leaves the last one alone
+        assert!(state.use_clipboard(kind, "no digits here").is_err());
+        assert_eq!(state.last_read_number, Some(-42.0));
+
+        assert_eq!(state.fill("Pay {{read_number}} EUR"), "Pay -42 EUR");
+        // Placeholders for values not read yet stay as written
+        assert_eq!(state.fill("{{search_query}}"), "{{search_query}}");
+    }
+
+    #[test]
+    fn test_use_clipboard_as_query_and_text() {
+        let mut state = RunState::default();
+        assert_eq!(state.use_clipboard(ClipboardUse::SearchQuery, "  rust\n async  traits\n").unwrap(), "rust async traits");
+        assert!(state.use_clipboard(ClipboardUse::Text, " \n").is_err());
+        state.use_clipboard(ClipboardUse::Text, "line one\nline two").unwrap();
+        assert_eq!(state.fill("q={{search_query}} | {{text}}"), "q=rust async traits | line one\nline two");
+        assert_eq!(ClipboardUse::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_paste_before_copy_is_blocked_in_textedit() {
+        let step = |action_type: &str, value: &str| crate::executor::PlanStep {
+            description: format!("{} {}", action_type.to_lowercase(), value),
+            action_type: action_type.to_string(),
+            target: None,
+            value: Some(value.to_string()),
+            verification: String::new(),
+            pre_check: None,
+        };
+        let mut state = RunState::default();
+        assert!(state.check_primed(&step("ACTIVATE", "TextEdit")).is_ok());
+
+        // Nothing copied yet: the step would type the literal placeholder
+        let paste = step("TYPE", "Notes: {{text}}");
+        let err = state.check_primed(&state.fill_step(&paste)).unwrap_err().to_string();
+        assert!(err.contains("TYPE step uses {{text}} before anything was copied"), "{}", err);
+        assert_eq!(state.unfilled("{{search_query}} {{text}} {{text}}"), vec!["{{search_query}}", "{{text}}"]);
+
+        state.use_clipboard(ClipboardUse::Text, "meeting at 3").unwrap();
+        let filled = state.fill_step(&paste);
+        assert!(state.check_primed(&filled).is_ok());
+        assert_eq!(filled.value.as_deref(), Some("Notes: meeting at 3"));
+        // The other kinds are still unfilled
+        assert!(state.check_primed(&step("URL", "https://duckduckgo.com/?q={{search_query}}")).is_err());
+    }
+
+}
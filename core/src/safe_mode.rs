@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// [Safe Mode] Monitoring only. The event tap, watchers, analyzer and API keep
+// running, but every path that acts on the machine (goal steps, shell
+// commands, UI automation, opening URLs) or writes somewhere else (n8n
+// workflows, routines, email, Notion, calendar, chat messages) refuses at
+// dispatch. Unlike the write lock this can't be unlocked at runtime; restart
+// without it instead.
+//
+//   --safe-mode  or  STEER_SAFE_MODE=1
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn safe mode on for the rest of the process (the `--safe-mode` flag).
+pub fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+        || std::env::var("STEER_SAFE_MODE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false)
+}
+
+/// Note in the config audit log when this run's safe mode differs from the
+/// last one recorded. It can't change while running, so startup is the
+/// only time to check (after the database is open).
+pub fn audit_startup() {
+    if let Err(e) = audit(is_enabled()) {
+        eprintln!("⚠️ Could not audit safe mode: {}", e);
+    }
+}
+
+fn audit(enabled: bool) -> rusqlite::Result<()> {
+    let label = |on: bool| if on { "on" } else { "off" };
+    let last = crate::db::list_config_changes(Some("safe_mode"), 1)?
+        .into_iter()
+        .next()
+        .and_then(|c| c.new_value)
+        .unwrap_or_else(|| label(false).to_string());
+    crate::db::record_config_change("safe_mode", Some(&last), Some(label(enabled)))
+}
+
+pub fn blocked_message(what: &str) -> String {
+    format!("Safe mode: {} disabled (started with --safe-mode or STEER_SAFE_MODE)", what)
+}
+
+/// Error out when safe mode is on; call right before acting.
+pub fn check(what: &str) -> anyhow::Result<()> {
+    guard(is_enabled(), what)
+}
+
+fn guard(enabled: bool, what: &str) -> anyhow::Result<()> {
+    if enabled {
+        return Err(anyhow::anyhow!(blocked_message(what)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_records_a_change_of_safe_mode() {
+        crate::db::init().ok();
+        let history = || crate::db::list_config_changes(Some("safe_mode"), 10_000).unwrap();
+        audit(false).unwrap();
+        let before = history().len();
+
+        audit(true).unwrap();
+        audit(true).unwrap();
+        let changes = history();
+        assert_eq!(changes.len(), before + 1);
+        assert_eq!((changes[0].old_value.as_deref(), changes[0].new_value.as_deref()), (Some("off"), Some("on")));
+
+        audit(false).unwrap();
+        assert_eq!(history()[0].new_value.as_deref(), Some("off"));
+    }
+
+    #[test]
+    fn test_guard_blocks_only_in_safe_mode() {
+        // Not via enable(): that would switch safe mode on for every test in the process
+        assert!(guard(false, "shell commands are").is_ok());
+        let err = guard(true, "shell commands are").unwrap_err();
+        assert!(err.to_string().starts_with("Safe mode: shell commands are disabled"));
+    }
+}
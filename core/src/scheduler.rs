@@ -1,17 +1,23 @@
 use tokio::time::{self, Duration};
 use crate::db;
+use crate::agent_error::AgentError;
+use crate::executor::{AgentExecutor, GoalOutcome, PlanStep};
 use crate::llm_gateway::LLMClient;
 use std::sync::Arc;
 use std::str::FromStr;
 use cron::Schedule;
 
 pub struct Scheduler {
-    llm: Arc<LLMClient>,
+    /// `None` in lite mode: routines can't run, the pattern job falls back to templates
+    llm: Option<Arc<LLMClient>>,
 }
 
+/// Error stored on routine runs skipped because no LLM is configured.
+pub const LLM_UNAVAILABLE: &str = "[llm_unavailable] Routines need an LLM (set OPENAI_API_KEY); running in lite mode";
+
 impl Scheduler {
-    pub fn new(llm: LLMClient) -> Self {
-        Self { llm: Arc::new(llm) }
+    pub fn new(llm: Option<LLMClient>) -> Self {
+        Self { llm: llm.map(Arc::new) }
     }
 
     pub fn start(&self) {
@@ -70,45 +76,87 @@ impl Scheduler {
                           }
                     }
 
-                    let prompt = routine.prompt.clone();
-                    let llm_clone = llm.clone();
+                    // Lite mode replays the plan of the routine's last successful run
+                    let (llm_clone, plan) = match llm.clone() {
+                        Some(llm) => (llm, None),
+                        None => match stored_plan(routine.id) {
+                            Some(plan) => (Arc::new(LLMClient::offline()), Some(plan)),
+                            None => {
+                                eprintln!("⚠️ Routine #{} skipped: no LLM (lite mode) and no stored plan", routine.id);
+                                if let Some(id) = run_id {
+                                    let _ = db::finish_routine_run(id, "failed", Some(LLM_UNAVAILABLE));
+                                }
+                                continue;
+                            }
+                        },
+                    };
+                    let routine_id = routine.id;
+                    let prompt = interpolate_prompt(&routine.prompt, chrono::Local::now());
+                    let success_check = routine.success_check.clone();
                     
                     tokio::spawn(async move {
                         let _permit = permit; // Drop permit when task finishes
                         println!("   ▶️ running routine logic: '{}'...", prompt);
                         
                         // Instantiate Executor on the fly (lightweight enough)
-                        let executor = crate::executor::AgentExecutor::new((*llm_clone).clone());
-                        let mut attempt: u32 = 0;
-                        loop {
-                            match executor.execute_goal(&prompt).await {
-                                Ok(res) => {
-                                    if attempt > 0 {
-                                        println!("✅ Routine '{}' Recovered after {} retries: {}", prompt, attempt, res);
-                                    } else {
-                                        println!("✅ Routine '{}' Completed: {}", prompt, res);
-                                    }
-                                    if let Some(id) = run_id {
-                                        let _ = db::finish_routine_run(id, "success", None);
-                                    }
-                                    break;
-                                },
-                                Err(e) => {
-                                    attempt += 1;
-                                    let err_msg = e.to_string();
-                                    let err_type = classify_error(&err_msg);
-                                    let stored_error = format!("[{}] {}", err_type, err_msg);
-                                    if attempt > max_retries {
-                                        eprintln!("❌ Routine '{}' Failed after {} attempts: {}", prompt, attempt, stored_error);
-                                        if let Some(id) = run_id {
-                                            let _ = db::finish_routine_run(id, "failed", Some(&stored_error));
+                        let executor = AgentExecutor::new((*llm_clone).clone());
+                        if let Some(id) = run_id {
+                            let _ = db::set_routine_run_session(id, executor.session_key());
+                        }
+                        let task = crate::task_registry::register(crate::task_registry::TaskKind::Routine, &prompt)
+                            .with_session(executor.session_key());
+                        let attempts = async {
+                            let mut attempt: u32 = 0;
+                            loop {
+                                match run_goal(&executor, &prompt, plan.as_deref()).await {
+                                    Ok(res) => {
+                                        // Retrying won't get past what the agent stopped to report
+                                        if let Some(stored_error) = reported_failure(&res) {
+                                            eprintln!("❌ Routine '{}' stopped and reported: {}", prompt, stored_error);
+                                            return Err(stored_error);
+                                        }
+                                        if attempt > 0 {
+                                            println!("✅ Routine '{}' Recovered after {} retries: {}", prompt, attempt, res);
+                                        } else {
+                                            println!("✅ Routine '{}' Completed: {}", prompt, res);
                                         }
-                                        break;
-                                    }
-                                    println!("⚠️ Routine '{}' attempt {} failed. Retrying in {}s...", prompt, attempt, retry_delay_secs);
-                                    time::sleep(Duration::from_secs(retry_delay_secs * attempt as u64)).await;
-                                },
+                                        return Ok(());
+                                    },
+                                    Err(e) => {
+                                        attempt += 1;
+                                        let err_msg = e.to_string();
+                                        let err_type = classify_error(&err_msg);
+                                        let stored_error = format!("[{}] {}", err_type, err_msg);
+                                        if attempt > max_retries {
+                                            eprintln!("❌ Routine '{}' Failed after {} attempts: {}", prompt, attempt, stored_error);
+                                            return Err(stored_error);
+                                        }
+                                        println!("⚠️ Routine '{}' attempt {} failed. Retrying in {}s...", prompt, attempt, retry_delay_secs);
+                                        time::sleep(Duration::from_secs(retry_delay_secs * attempt as u64)).await;
+                                    },
+                                }
+                            }
+                        };
+                        let run = executor.session_key().to_string();
+                        let checked = async {
+                            let result = crate::integrations::telegram::track_sends(run.clone(), attempts).await;
+                            let result = apply_success_check(result, success_check.as_deref(), |text| {
+                                crate::routine_check::evaluate(text, run.clone())
+                            })
+                            .await;
+                            if result.is_ok() {
+                                remember_plan(routine_id, &executor);
+                            }
+                            if let Err(e) = &result {
+                                if e.starts_with("[check_failed]") {
+                                    eprintln!("❌ Routine '{}' ran but its success check failed: {}", prompt, e);
+                                }
                             }
+                            result
+                        };
+                        let status = run_isolated(run_id, task.run(checked)).await;
+                        if status == "cancelled" {
+                            println!("🛑 Routine '{}' cancelled", prompt);
                         }
                     });
                 }
@@ -116,41 +164,509 @@ impl Scheduler {
         });
 
         // Separate loop for Passive Analysis (Background Brain)
+        let interval_secs = pattern_job_interval_secs();
+        if interval_secs == 0 {
+            println!("🧠 [Background] Pattern analysis job disabled (STEER_PATTERN_JOB_INTERVAL_SECS=0)");
+            return;
+        }
         let llm_for_analysis = self.llm.clone();
         tokio::spawn(async move {
             loop {
-                // Analysis runs every 5 minutes
-                time::sleep(Duration::from_secs(300)).await;
-                
-                println!("🧠 [Background] Analyzing recent behavior patterns...");
-                let detector = crate::pattern_detector::PatternDetector::new();
-                let patterns = detector.analyze();
-
-                for pattern in patterns {
-                    // High confidence/occurrence only for auto-notification
-                    if pattern.occurrences >= 5 && pattern.similarity_score >= 0.85 {
-                         let brain = &llm_for_analysis;
-                         if let Ok(proposal) = brain.generate_recommendation_from_pattern(
-                             &pattern.description, 
-                             &pattern.sample_events
-                         ).await {
-                             if proposal.confidence >= 0.8 {
-                                 // Check if already recommended to avoid spam
-                                 if let Ok(true) = db::insert_recommendation(&proposal) {
-                                      let _ = crate::notifier::send(
-                                          "💡 New Workflow Idea", 
-                                          &format!("I noticed you do '{}' a lot. Shall I automate it?", proposal.title)
-                                      );
-                                 }
-                             }
-                         }
-                    }
-                }
+                time::sleep(Duration::from_secs(interval_secs)).await;
+                run_pattern_job(llm_for_analysis.as_deref()).await;
             }
         });
     }
 }
 
+/// Outcome of the most recent unattended pattern analysis (see `/api/patterns/job`).
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PatternJobStatus {
+    pub last_run_at: Option<String>,
+    pub patterns_found: usize,
+    pub candidates_added: usize,
+    pub recommendations_created: usize,
+    /// Semantic event clusters from the last run (0 unless STEER_EVENT_EMBEDDINGS is on)
+    pub semantic_clusters: usize,
+    pub total_candidates: i64,
+    pub interval_secs: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref PATTERN_JOB: std::sync::Mutex<PatternJobStatus> = std::sync::Mutex::new(PatternJobStatus::default());
+}
+
+/// `STEER_PATTERN_JOB_INTERVAL_SECS` (default 300, 0 disables the job).
+fn pattern_job_interval_secs() -> u64 {
+    std::env::var("STEER_PATTERN_JOB_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(300)
+}
+
+pub fn pattern_job_status() -> PatternJobStatus {
+    let mut status = PATTERN_JOB.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    status.total_candidates = db::count_routine_candidates().unwrap_or(0);
+    status.interval_secs = pattern_job_interval_secs();
+    status
+}
+
+/// Recommendation for a strong pattern: written by the LLM when there is one,
+/// otherwise (lite mode) the best matching built-in template, if any.
+async fn propose(
+    llm: Option<&LLMClient>,
+    matcher: &crate::recommendation::TemplateMatcher,
+    pattern: &crate::pattern_detector::DetectedPattern,
+) -> Option<crate::recommendation::AutomationProposal> {
+    match llm {
+        Some(llm) => llm
+            .generate_recommendation_from_pattern(&pattern.description, &pattern.sample_events)
+            .await
+            .ok(),
+        None => matcher.match_pattern(pattern),
+    }
+}
+
+/// Detect patterns, persist new ones as routine candidates, and turn the
+/// strongest into recommendations (with a notification). Without an LLM the
+/// recommendations come from templates and semantic clustering is skipped.
+pub async fn run_pattern_job(llm: Option<&LLMClient>) -> PatternJobStatus {
+    println!("🧠 [Background] Analyzing recent behavior patterns...");
+    let min_occurrences: u32 = std::env::var("STEER_PATTERN_MIN_OCCURRENCES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(5);
+    let detector = crate::pattern_detector::PatternDetector::new();
+    let patterns = detector.analyze();
+    let matcher = crate::recommendation::TemplateMatcher::new();
+
+    let mut candidates_added = 0;
+    let mut recommendations_created = 0;
+    for pattern in &patterns {
+        match db::insert_routine_candidate(pattern) {
+            Ok(true) => candidates_added += 1,
+            Ok(false) => {}
+            Err(e) => eprintln!("⚠️ Failed to save routine candidate: {}", e),
+        }
+
+        // High confidence/occurrence only for auto-notification
+        if pattern.occurrences < min_occurrences || pattern.similarity_score < 0.85 {
+            continue;
+        }
+        if let Some(proposal) = propose(llm, &matcher, pattern).await {
+            if proposal.confidence >= 0.8 {
+                // Check if already recommended to avoid spam
+                if let Ok(true) = db::insert_recommendation(&proposal) {
+                    recommendations_created += 1;
+                    let _ = crate::notifier::notify(
+                        "recommendations",
+                        "💡 New Workflow Idea",
+                        &format!("I noticed you do '{}' a lot. Shall I automate it?", proposal.title),
+                    );
+                }
+            }
+        }
+    }
+
+    let semantic_clusters = if let Some(llm) = llm.filter(|_| crate::event_clusters::enabled()) {
+        match crate::event_clusters::refresh(llm).await {
+            Ok(clusters) => clusters.len(),
+            Err(e) => {
+                eprintln!("⚠️ Event clustering failed: {}", e);
+                0
+            }
+        }
+    } else {
+        0
+    };
+
+    if candidates_added > 0 || recommendations_created > 0 {
+        println!(
+            "🧠 [Background] {} patterns, {} new candidates, {} new recommendations",
+            patterns.len(),
+            candidates_added,
+            recommendations_created
+        );
+    }
+
+    {
+        let mut status = PATTERN_JOB.lock().unwrap_or_else(|e| e.into_inner());
+        status.last_run_at = Some(chrono::Utc::now().to_rfc3339());
+        status.patterns_found = patterns.len();
+        status.candidates_added = candidates_added;
+        status.recommendations_created = recommendations_created;
+        status.semantic_clusters = semantic_clusters;
+    }
+    pattern_job_status()
+}
+
+/// The routine to run: `id` when given, else the only routine called `name`.
+/// A name shared by several routines is an error listing their ids.
+pub fn resolve_routine(id: Option<i64>, name: Option<&str>) -> Result<i64, String> {
+    if let Some(id) = id {
+        return Ok(id);
+    }
+    let name = name.map(str::trim).filter(|n| !n.is_empty()).ok_or("Give a routine id or name")?;
+    let ids = db::find_routine_ids_by_name(name).map_err(|e| e.to_string())?;
+    match ids.as_slice() {
+        [] => Err(format!("No routine named '{}'", name)),
+        [id] => Ok(*id),
+        many => Err(format!(
+            "{} routines are named '{}' ({}); run one by id",
+            many.len(),
+            name,
+            many.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Text of a panic payload (`panic!` with a literal or a formatted message).
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Await a routine run and record how it ended in `routine_runs`. `work`
+/// resolves to `None` when cancelled, else the run's result. A panic inside
+/// it is caught and recorded as a failed run, so one broken routine can't
+/// leave its run stuck at `running` or unwind into the scheduler. Returns the
+/// recorded status.
+async fn run_isolated<F>(run_id: Option<i64>, work: F) -> &'static str
+where
+    F: std::future::Future<Output = Option<Result<(), String>>>,
+{
+    use futures::FutureExt;
+    let (status, error) = match std::panic::AssertUnwindSafe(work).catch_unwind().await {
+        Ok(Some(Ok(()))) => ("success", None),
+        Ok(Some(Err(e))) => ("failed", Some(e)),
+        Ok(None) => ("cancelled", None),
+        Err(payload) => {
+            let message = panic_message(&*payload);
+            eprintln!("💥 Routine panicked: {}", message);
+            ("failed", Some(format!("[panic] {}", message)))
+        }
+    };
+    if let Some(id) = run_id {
+        if let Err(e) = db::finish_routine_run(id, status, error.as_deref()) {
+            eprintln!("⚠️ Failed to record routine run #{}: {}", id, e);
+        }
+    }
+    status
+}
+
+/// A run that returned Ok only counts as a success when the routine's
+/// success check (if it has one) passes too; otherwise the check's reason
+/// becomes the run's error.
+async fn apply_success_check<F>(
+    result: Result<(), String>,
+    check: Option<&str>,
+    evaluate: impl FnOnce(String) -> F,
+) -> Result<(), String>
+where
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    result?;
+    match check {
+        Some(check) => evaluate(check.to_string()).await.map_err(|reason| format!("[check_failed] {}", reason)),
+        None => Ok(()),
+    }
+}
+
+/// A run that ended with only REPORT messages stopped to tell the user why
+/// instead of doing the routine's job, so it counts as failed with them.
+fn reported_failure(outcome: &crate::executor::GoalOutcome) -> Option<String> {
+    match outcome {
+        crate::executor::GoalOutcome::Reported(text) => Some(format!("[reported] {}", text)),
+        _ => None,
+    }
+}
+
+/// Run `prompt` with the planner, or replay a stored `plan` (lite mode).
+async fn run_goal(executor: &AgentExecutor, prompt: &str, plan: Option<&[PlanStep]>) -> Result<GoalOutcome, AgentError> {
+    match plan {
+        Some(plan) => executor.replay_plan(prompt, plan.to_vec()).await,
+        None => executor.execute_goal(prompt).await,
+    }
+}
+
+/// Keep the steps of a successful run, for replay without an LLM.
+fn remember_plan(routine_id: i64, executor: &AgentExecutor) {
+    let plan = executor.completed_plan();
+    if plan.is_empty() {
+        return;
+    }
+    let stored = serde_json::to_string(&plan).map_err(|e| e.to_string()).and_then(|json| {
+        db::set_routine_plan(routine_id, &json).map_err(|e| e.to_string())
+    });
+    if let Err(e) = stored {
+        eprintln!("⚠️ Plan of routine #{} not stored: {}", routine_id, e);
+    }
+}
+
+/// The plan stored by the routine's last successful run, if any.
+fn stored_plan(routine_id: i64) -> Option<Vec<PlanStep>> {
+    let json = db::get_routine_plan(routine_id).ok().flatten()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Execute a routine immediately, outside of its cron schedule.
+/// Records a `routine_runs` entry but leaves `next_run` untouched.
+/// Without an LLM (`None`) the routine's stored plan is replayed.
+pub async fn run_routine_now(llm: Option<&LLMClient>, id: i64) -> Result<GoalOutcome, AgentError> {
+    let routine = db::get_routine(id)?
+        .ok_or_else(|| AgentError::Other(format!("Routine #{} not found", id)))?;
+    let (llm, plan) = match llm {
+        Some(llm) => (llm.clone(), None),
+        None => match stored_plan(routine.id) {
+            Some(plan) => (LLMClient::offline(), Some(plan)),
+            None => return Err(AgentError::Llm(format!("{} (routine #{} has no stored plan to replay)", LLM_UNAVAILABLE, routine.id))),
+        },
+    };
+
+    println!("⏰ Manually running Routine #{}: {}", routine.id, routine.name);
+    let run_id = db::create_routine_run(routine.id).ok();
+
+    let prompt = interpolate_prompt(&routine.prompt, chrono::Local::now());
+    let executor = AgentExecutor::new(llm);
+    if let Some(id) = run_id {
+        let _ = db::set_routine_run_session(id, executor.session_key());
+    }
+    let task = crate::task_registry::register(crate::task_registry::TaskKind::Routine, &prompt)
+        .with_session(executor.session_key());
+    let run = executor.session_key().to_string();
+    let goal = crate::integrations::telegram::track_sends(run.clone(), run_goal(&executor, &prompt, plan.as_deref()));
+    let result = match futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(task.run(goal))).await {
+        Ok(Some(result)) => result,
+        Ok(None) => Err(AgentError::Cancelled(format!("Routine #{} was cancelled", routine.id))),
+        Err(payload) => Err(AgentError::Other(format!("Routine panicked: {}", panic_message(&*payload)))),
+    };
+    match result {
+        Ok(res) => {
+            let checked = match reported_failure(&res) {
+                Some(stored_error) => Err(stored_error),
+                None => {
+                    apply_success_check(Ok(()), routine.success_check.as_deref(), |text| {
+                        crate::routine_check::evaluate(text, run)
+                    })
+                    .await
+                }
+            };
+            if let Err(reason) = checked {
+                eprintln!("❌ Routine '{}' ran but didn't succeed: {}", routine.prompt, reason);
+                if let Some(id) = run_id {
+                    let _ = db::finish_routine_run(id, "failed", Some(&reason));
+                }
+                return Err(AgentError::Verification(reason));
+            }
+            println!("✅ Routine '{}' Completed: {}", routine.prompt, res);
+            remember_plan(routine.id, &executor);
+            if let Some(id) = run_id {
+                let _ = db::finish_routine_run(id, "success", None);
+            }
+            Ok(res)
+        }
+        Err(e) => {
+            let err_msg = e.to_string();
+            let stored_error = format!("[{}] {}", classify_error(&err_msg), err_msg);
+            eprintln!("❌ Routine '{}' Failed: {}", routine.prompt, stored_error);
+            if let Some(id) = run_id {
+                let status = if matches!(e, AgentError::Cancelled(_)) { "cancelled" } else { "failed" };
+                let _ = db::finish_routine_run(id, status, Some(&stored_error));
+            }
+            Err(e)
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PROMPT_PLACEHOLDER_RE: regex::Regex =
+        regex::Regex::new(r"\{\{\s*([a-z]+)(?::([A-Za-z0-9_.\-]+))?\s*(?:\|([^}]*))?\}\}").expect("Invalid placeholder pattern");
+}
+
+/// Fill routine prompt placeholders at run time:
+/// `{{date}}` (YYYY-MM-DD), `{{time}}` (HH:MM), `{{weekday}}`,
+/// `{{env:NAME}}` and `{{config:key}}` (reads `STEER_ROUTINE_<KEY>`).
+/// `{{...|fallback}}` supplies a value for when the variable is unset; otherwise
+/// it becomes empty. Env names that look like credentials are never expanded.
+/// Unknown placeholders are left as written.
+pub fn interpolate_prompt(prompt: &str, now: chrono::DateTime<chrono::Local>) -> String {
+    PROMPT_PLACEHOLDER_RE
+        .replace_all(prompt, |caps: &regex::Captures| {
+            let fallback = caps.get(3).map(|m| m.as_str().trim().to_string()).unwrap_or_default();
+            let arg = caps.get(2).map(|m| m.as_str());
+            let value = match (&caps[1], arg) {
+                ("date", None) => Some(now.format("%Y-%m-%d").to_string()),
+                ("time", None) => Some(now.format("%H:%M").to_string()),
+                ("weekday", None) => Some(now.format("%A").to_string()),
+                ("env", Some(name)) if looks_secret(name) => {
+                    eprintln!("⚠️ Routine prompt asked for {}; credentials are not expanded", name);
+                    None
+                }
+                ("env", Some(name)) => std::env::var(name).ok(),
+                ("config", Some(key)) => {
+                    let var = format!("STEER_ROUTINE_{}", key.to_uppercase().replace(['.', '-'], "_"));
+                    std::env::var(var).ok()
+                }
+                _ => return caps[0].to_string(),
+            };
+            value.filter(|v| !v.trim().is_empty()).unwrap_or(fallback)
+        })
+        .into_owned()
+}
+
+fn looks_secret(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL"]
+        .iter()
+        .any(|marker| upper.contains(marker))
+}
+
+lazy_static::lazy_static! {
+    static ref EVERY_MINUTES_RE: regex::Regex =
+        regex::Regex::new(r"every\s+(\d+)\s*(?:minutes?|mins?)|(\d+)\s*분\s*마다").expect("Invalid interval pattern");
+    static ref KOREAN_TIME_RE: regex::Regex =
+        regex::Regex::new(r"(\d{1,2})\s*시(?:\s*(\d{1,2})\s*분|\s*반)?").expect("Invalid Korean time pattern");
+    static ref ENGLISH_TIME_RE: regex::Regex =
+        regex::Regex::new(r"(?:at\s+)?\b(\d{1,2})(?::(\d{2}))?\s*(am|pm|a\.m\.|p\.m\.)?").expect("Invalid time pattern");
+}
+
+/// Turn a schedule phrase ("every weekday at 9am", "매일 아침 9시", "every 15 minutes")
+/// into a 6-field cron expression understood by the scheduler.
+pub fn parse_natural_schedule(text: &str) -> Result<String, String> {
+    let t = text.trim().to_lowercase();
+    if t.is_empty() {
+        return Err("Schedule is empty".to_string());
+    }
+
+    // Interval schedules
+    if let Some(caps) = EVERY_MINUTES_RE.captures(&t) {
+        let n: u32 = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0);
+        if n == 0 || n >= 60 {
+            return Err(format!("Unsupported minute interval: {}", n));
+        }
+        return finish_cron(format!("0 */{} * * * *", n));
+    }
+    if t.contains("every hour") || t.contains("hourly") || t.contains("매시간") || t.contains("매 시간") {
+        return finish_cron("0 0 * * * *".to_string());
+    }
+
+    // Day-of-week
+    let days: [(&str, &str, &str); 7] = [
+        ("monday", "월요일", "Mon"),
+        ("tuesday", "화요일", "Tue"),
+        ("wednesday", "수요일", "Wed"),
+        ("thursday", "목요일", "Thu"),
+        ("friday", "금요일", "Fri"),
+        ("saturday", "토요일", "Sat"),
+        ("sunday", "일요일", "Sun"),
+    ];
+    let dow = if t.contains("weekday") || t.contains("평일") {
+        "Mon-Fri".to_string()
+    } else if t.contains("weekend") || t.contains("주말") {
+        "Sat,Sun".to_string()
+    } else {
+        let named: Vec<&str> = days
+            .iter()
+            .filter(|(en, ko, _)| t.contains(en) || t.contains(ko))
+            .map(|(_, _, cron)| *cron)
+            .collect();
+        if named.is_empty() { "*".to_string() } else { named.join(",") }
+    };
+
+    // Time of day
+    let (hour, minute) = parse_time_of_day(&t)
+        .ok_or_else(|| format!("Couldn't find a time of day in '{}'", text.trim()))?;
+
+    finish_cron(format!("0 {} {} * * {}", minute, hour, dow))
+}
+
+fn parse_time_of_day(t: &str) -> Option<(u32, u32)> {
+    if t.contains("noon") || t.contains("정오") {
+        return Some((12, 0));
+    }
+    if t.contains("midnight") || t.contains("자정") {
+        return Some((0, 0));
+    }
+
+    let pm_hint = t.contains("오후") || t.contains("저녁") || t.contains("밤") || t.contains("evening") || t.contains("tonight");
+
+    if let Some(caps) = KOREAN_TIME_RE.captures(t) {
+        let mut hour: u32 = caps.get(1)?.as_str().parse().ok()?;
+        let minute: u32 = match caps.get(2) {
+            Some(m) => m.as_str().parse().ok()?,
+            None if caps.get(0)?.as_str().ends_with('반') => 30,
+            None => 0,
+        };
+        if pm_hint && hour < 12 {
+            hour += 12;
+        }
+        return (hour < 24 && minute < 60).then_some((hour, minute));
+    }
+
+    for caps in ENGLISH_TIME_RE.captures_iter(t) {
+        let has_colon = caps.get(2).is_some();
+        let meridiem = caps.get(3).map(|m| m.as_str().starts_with('p'));
+        let explicit_at = caps.get(0)?.as_str().starts_with("at");
+        if !has_colon && meridiem.is_none() && !explicit_at {
+            continue; // bare number, e.g. "every 2 days"
+        }
+        let mut hour: u32 = caps.get(1)?.as_str().parse().ok()?;
+        let minute: u32 = caps.get(2).and_then(|m| m.as_str().parse().ok()).unwrap_or(0);
+        match meridiem {
+            Some(true) if hour < 12 => hour += 12,
+            Some(false) if hour == 12 => hour = 0,
+            None if pm_hint && hour < 12 => hour += 12,
+            _ => {}
+        }
+        return (hour < 24 && minute < 60).then_some((hour, minute));
+    }
+    None
+}
+
+fn finish_cron(cron: String) -> Result<String, String> {
+    db::validate_cron(&cron)?;
+    Ok(cron)
+}
+
+/// Step types that don't say what a routine does.
+const PREVIEW_SKIPPED: &[&str] = &["WAIT", "WAIT_FOR", "SCREENSHOT", "DISMISS_DIALOGS", "CHECK"];
+const PREVIEW_MAX_STEPS: usize = 8;
+const PREVIEW_MAX_CHARS: usize = 40;
+
+/// One-line preview of what a routine does ("Open Safari → Search for flights → …"),
+/// from the successful steps of a recorded run.
+pub fn step_preview(steps: &[db::GoalStep]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for step in steps {
+        if step.outcome != "success" || PREVIEW_SKIPPED.contains(&step.action_type.as_str()) {
+            continue;
+        }
+        let text = step.description.trim().trim_end_matches('.');
+        let text = if text.is_empty() { step.action_type.to_lowercase() } else { text.to_string() };
+        let text = if text.chars().count() > PREVIEW_MAX_CHARS {
+            format!("{}…", text.chars().take(PREVIEW_MAX_CHARS - 1).collect::<String>().trim_end())
+        } else {
+            text
+        };
+        if parts.last() != Some(&text) {
+            parts.push(text);
+        }
+    }
+    if parts.len() > PREVIEW_MAX_STEPS {
+        let more = parts.len() - PREVIEW_MAX_STEPS;
+        parts.truncate(PREVIEW_MAX_STEPS);
+        parts.push(format!("+{} more", more));
+    }
+    parts.join(" → ")
+}
+
 fn classify_error(message: &str) -> &'static str {
     let msg = message.to_lowercase();
     if msg.contains("permission") || msg.contains("access") || msg.contains("denied") {
@@ -165,3 +681,214 @@ fn classify_error(message: &str) -> &'static str {
         "execution"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(action_type: &str, description: &str, outcome: &str) -> db::GoalStep {
+        db::GoalStep {
+            action_type: action_type.to_string(),
+            description: description.to_string(),
+            outcome: outcome.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_step_preview_is_stable_for_a_known_run() {
+        let steps = vec![
+            recorded("ACTIVATE", "Open Safari.", "success"),
+            recorded("WAIT", "Wait for the page", "success"),
+            recorded("CLICK", "Click the search field", "failed"),
+            recorded("TYPE", "Search for cheap flights to Jeju next weekend", "success"),
+            recorded("TYPE", "Search for cheap flights to Jeju next weekend", "success"),
+            recorded("EXTRACT", "Copy the cheapest price", "success"),
+            recorded("ACTIVATE", "Paste into Notes", "success"),
+        ];
+        let preview = "Open Safari → Search for cheap flights to Jeju next w… → Copy the cheapest price → Paste into Notes";
+        assert_eq!(step_preview(&steps), preview);
+        assert_eq!(step_preview(&steps), preview);
+
+        let long: Vec<db::GoalStep> = (1..=10).map(|i| recorded("CLICK", &format!("Click tab {}", i), "success")).collect();
+        assert!(step_preview(&long).ends_with("Click tab 8 → +2 more"));
+        assert_eq!(step_preview(&[]), "");
+    }
+
+    #[tokio::test]
+    async fn test_panicking_routine_is_recorded_and_the_next_one_still_runs() {
+        db::init().ok();
+        let routine_id = -1 - uuid::Uuid::new_v4().as_u128() as u32 as i64;
+        let broken = db::create_routine_run(routine_id).unwrap();
+        let healthy = db::create_routine_run(routine_id).unwrap();
+
+        let status = run_isolated(Some(broken), async {
+            let results: Vec<Result<(), String>> = Vec::new();
+            Some(results[0].clone())
+        })
+        .await;
+        assert_eq!(status, "failed");
+        assert_eq!(run_isolated(Some(healthy), async { Some(Ok(())) }).await, "success");
+
+        let runs: Vec<db::RoutineRun> = db::list_routine_runs(10_000)
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.routine_id == routine_id)
+            .collect();
+        let run = |id: i64| runs.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(run(broken).status, "failed");
+        assert!(run(broken).error.as_deref().unwrap().starts_with("[panic] index out of bounds"));
+        assert!(run(broken).finished_at.is_some());
+        assert_eq!(run(healthy).status, "success");
+    }
+
+    #[tokio::test]
+    async fn test_routine_that_ran_but_failed_its_check_is_recorded_as_failed() {
+        db::init().ok();
+        let routine_id = -1 - uuid::Uuid::new_v4().as_u128() as u32 as i64;
+        let unsent = db::create_routine_run(routine_id).unwrap();
+        let unchecked = db::create_routine_run(routine_id).unwrap();
+
+        // The executor returned Ok, but no Telegram message went out
+        let check = r#"{"kind": "telegram_sent", "within_secs": 60}"#;
+        let evaluate = |text: String| async move {
+            let check = crate::routine_check::parse(&text)?;
+            crate::routine_check::evaluate_with(&check, chrono::Utc::now(), None, |_| unreachable!())
+        };
+        let status = run_isolated(Some(unsent), async { Some(apply_success_check(Ok(()), Some(check), evaluate).await) }).await;
+        assert_eq!(status, "failed");
+        let status = run_isolated(Some(unchecked), async { Some(apply_success_check(Ok(()), None, evaluate).await) }).await;
+        assert_eq!(status, "success");
+
+        // A failed run stays failed with its own error
+        let failed = apply_success_check(Err("[network] offline".to_string()), Some(check), evaluate).await;
+        assert_eq!(failed.unwrap_err(), "[network] offline");
+
+        let runs = db::list_routine_runs(10_000).unwrap();
+        let run = |id: i64| runs.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(run(unsent).status, "failed");
+        assert_eq!(run(unsent).error.as_deref(), Some("[check_failed] no Telegram message was sent"));
+        assert_eq!(run(unchecked).status, "success");
+    }
+
+    #[tokio::test]
+    async fn test_routine_that_only_reported_is_recorded_as_failed() {
+        use crate::executor::GoalOutcome;
+        db::init().ok();
+        let routine_id = -1 - uuid::Uuid::new_v4().as_u128() as u32 as i64;
+        let stuck = db::create_routine_run(routine_id).unwrap();
+
+        let outcome = GoalOutcome::Reported("Stuck at: the bank asks for a one-time code".to_string());
+        let result = reported_failure(&outcome).map_or(Ok(()), Err);
+        assert_eq!(run_isolated(Some(stuck), async { Some(result) }).await, "failed");
+        assert_eq!(reported_failure(&GoalOutcome::Completed), None);
+        assert_eq!(reported_failure(&GoalOutcome::Replied("It's 42.".to_string())), None);
+
+        let runs = db::list_routine_runs(10_000).unwrap();
+        let run = runs.iter().find(|r| r.id == stuck).unwrap();
+        assert_eq!(run.error.as_deref(), Some("[reported] Stuck at: the bank asks for a one-time code"));
+    }
+
+    #[tokio::test]
+    async fn test_lite_mode_replays_the_stored_plan() {
+        db::init().ok();
+        let name = format!("Answer {}", uuid::Uuid::new_v4());
+        let id = db::create_routine(&name, "0 0 9 * * *", "What is the answer?").unwrap();
+
+        // Nothing has succeeded yet, so there is nothing to replay
+        let err = run_routine_now(None, id).await.unwrap_err();
+        assert!(err.to_string().contains("[llm_unavailable]"), "{}", err);
+
+        let plan = r#"[{"description": "Answer", "action_type": "REPLY", "target": null, "value": "42", "verification": "", "pre_check": null}]"#;
+        db::set_routine_plan(id, plan).unwrap();
+        let outcome = run_routine_now(None, id).await.unwrap();
+        assert_eq!(outcome, GoalOutcome::Replied("42".to_string()));
+        assert_eq!(stored_plan(id).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_now_records_a_run_and_keeps_the_schedule() {
+        db::init().ok();
+        let name = format!("Run now {}", uuid::Uuid::new_v4());
+        let id = db::create_routine(&name, "0 0 9 * * *", "What is the answer?").unwrap();
+        let plan = r#"[{"description": "Answer", "action_type": "REPLY", "target": null, "value": "42", "verification": "", "pre_check": null}]"#;
+        db::set_routine_plan(id, plan).unwrap();
+        let before = db::get_routine(id).unwrap().unwrap();
+
+        run_routine_now(None, id).await.unwrap();
+        let after = db::get_routine(id).unwrap().unwrap();
+        assert_eq!((after.next_run, after.last_run), (before.next_run, before.last_run));
+        let runs: Vec<db::RoutineRun> = db::list_routine_runs(10_000).unwrap().into_iter().filter(|r| r.routine_id == id).collect();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, "success");
+
+        let missing = run_routine_now(None, i64::MIN).await.unwrap_err();
+        assert_eq!(missing, AgentError::Other(format!("Routine #{} not found", i64::MIN)));
+    }
+
+    #[test]
+    fn test_resolve_routine_by_id_or_unique_name() {
+        db::init().ok();
+        let shared = format!("Report {}", uuid::Uuid::new_v4());
+        let first = db::create_routine(&shared, "0 0 9 * * *", "send report").unwrap();
+        assert_eq!(resolve_routine(None, Some(&shared.to_uppercase())), Ok(first));
+
+        let second = db::create_routine(&shared, "0 0 18 * * *", "send report").unwrap();
+        let err = resolve_routine(None, Some(&shared)).unwrap_err();
+        assert!(err.contains(&format!("#{}, #{}", first, second)), "{}", err);
+        assert_eq!(resolve_routine(Some(second), Some(&shared)), Ok(second));
+        assert!(resolve_routine(None, Some("no such routine xyz")).is_err());
+        assert!(resolve_routine(None, None).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_prompt_at_run_time() {
+        use chrono::TimeZone;
+        let now = chrono::Local.with_ymd_and_hms(2026, 3, 9, 8, 5, 0).unwrap();
+        std::env::set_var("STEER_ROUTINE_RECIPIENT", "team@example.com");
+        std::env::set_var("STEER_TEST_API_TOKEN", "sk-should-not-leak");
+
+        assert_eq!(
+            interpolate_prompt("Send the {{date}} {{ time }} report to {{config:recipient}}", now),
+            "Send the 2026-03-09 08:05 report to team@example.com"
+        );
+        assert_eq!(
+            interpolate_prompt("Hi {{env:STEER_TEST_UNSET_NAME|there}}, {{env:STEER_TEST_API_TOKEN}}!", now),
+            "Hi there, !"
+        );
+        assert_eq!(interpolate_prompt("Keep {{unknown}} and {{date:x}}", now), "Keep {{unknown}} and {{date:x}}");
+    }
+
+    #[tokio::test]
+    async fn test_propose_uses_templates_without_llm() {
+        use crate::pattern_detector::{DetectedPattern, PatternType};
+        let matcher = crate::recommendation::TemplateMatcher::new();
+        let mut pattern = DetectedPattern {
+            pattern_id: "lite".to_string(),
+            pattern_type: PatternType::KeywordRepeat,
+            description: "Repeated usage of 'invoice'".to_string(),
+            occurrences: 10,
+            similarity_score: 0.9,
+            sample_events: vec![serde_json::json!({"type": "ui.type", "data": {"text": "sending invoice"}}).to_string()],
+            detected_at: chrono::Utc::now(),
+        };
+        let proposal = propose(None, &matcher, &pattern).await.expect("template match");
+        assert_eq!(proposal.title, "Email Follow-Up Reminder");
+
+        pattern.description = "Repeated usage of 'zzz'".to_string();
+        pattern.sample_events.clear();
+        assert!(propose(None, &matcher, &pattern).await.is_none());
+    }
+
+    #[test]
+    fn test_parse_natural_schedule() {
+        assert_eq!(parse_natural_schedule("every day at 9am").unwrap(), "0 0 9 * * *");
+        assert_eq!(parse_natural_schedule("every weekday at 9am").unwrap(), "0 0 9 * * Mon-Fri");
+        assert_eq!(parse_natural_schedule("every monday and friday at 6:30 pm").unwrap(), "0 30 18 * * Mon,Fri");
+        assert_eq!(parse_natural_schedule("every 15 minutes").unwrap(), "0 */15 * * * *");
+        assert_eq!(parse_natural_schedule("hourly").unwrap(), "0 0 * * * *");
+        assert_eq!(parse_natural_schedule("매일 아침 9시").unwrap(), "0 0 9 * * *");
+        assert_eq!(parse_natural_schedule("평일 오후 6시 반").unwrap(), "0 30 18 * * Mon-Fri");
+        assert!(parse_natural_schedule("sometime soon").is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "action", content = "payload")]
@@ -23,6 +25,10 @@ pub enum AgentAction {
     // System
     #[serde(rename = "system.open")]
     SystemOpen { app: String },
+    #[serde(rename = "system.open_url")]
+    OpenUrl { url: String },
+    #[serde(rename = "system.open_file")]
+    OpenFile { path: String, #[serde(default)] app: Option<String> },
     #[serde(rename = "system.search")]
     SystemSearch { query: String },
     #[serde(rename = "system.terminate")]
@@ -79,4 +85,480 @@ pub struct EventEnvelope {
     
     #[serde(default)]
     pub raw: Option<serde_json::Value>,
+
+    /// Per-source sequence number, stamped when the event is emitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// How many identical events this one stands for after a burst was coalesced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<u32>,
+}
+
+pub const CURRENT_SCHEMA_VERSION: &str = "1.0";
+
+/// `source` of events the agent emits about its own actions.
+pub const AGENT_EVENT_SOURCE: &str = "agent_executor";
+
+impl EventEnvelope {
+    /// Envelope stamped with the current schema version, a fresh id and the current time.
+    pub fn new(
+        source: &str,
+        app: &str,
+        event_type: &str,
+        priority: &str,
+        resource: Option<ResourceContext>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION.to_string(),
+            event_id: uuid::Uuid::new_v4().to_string(),
+            ts: chrono::Utc::now().to_rfc3339(),
+            source: source.to_string(),
+            app: app.to_string(),
+            event_type: event_type.to_string(),
+            priority: priority.to_string(),
+            resource,
+            payload,
+            privacy: None,
+            pid: None,
+            window_id: None,
+            window_title: None,
+            browser_url: None,
+            raw: None,
+            seq: None,
+            repeat: None,
+        }
+    }
+
+    /// Keystrokes and clicks, which legitimately repeat (a double letter, a
+    /// double click), as opposed to reports of a state such as an app switch.
+    pub fn is_input(&self) -> bool {
+        matches!(self.event_type.as_str(), "key_input" | "click")
+    }
+
+    /// Same event apart from id, time and sequence number.
+    pub fn same_content(&self, other: &EventEnvelope) -> bool {
+        let resource = |e: &EventEnvelope| e.resource.as_ref().map(|r| (r.resource_type.clone(), r.id.clone()));
+        self.source == other.source
+            && self.app == other.app
+            && self.event_type == other.event_type
+            && self.payload == other.payload
+            && self.window_title == other.window_title
+            && self.browser_url == other.browser_url
+            && resource(self) == resource(other)
+    }
+
+    /// The executor is about to run `step` (0-based) of `plan` (action types, in order).
+    pub fn agent_action(goal: &str, step: usize, action: &str, description: &str, plan: &[String]) -> Self {
+        Self::new(
+            AGENT_EVENT_SOURCE,
+            "agent",
+            "agent_action",
+            "P2",
+            Some(ResourceContext {
+                resource_type: "goal".to_string(),
+                id: goal.to_string(),
+            }),
+            serde_json::json!({
+                "goal": goal,
+                "step": step,
+                "action": action,
+                "description": description,
+                "plan": plan,
+            }),
+        )
+    }
+
+    /// A REPORT step's message for the user (see `executor::GoalOutcome::Reported`).
+    pub fn agent_report(goal: &str, step: usize, text: &str) -> Self {
+        Self::new(
+            AGENT_EVENT_SOURCE,
+            "agent",
+            "agent_report",
+            "P1",
+            Some(ResourceContext {
+                resource_type: "goal".to_string(),
+                id: goal.to_string(),
+            }),
+            serde_json::json!({
+                "goal": goal,
+                "step": step,
+                "text": text,
+            }),
+        )
+    }
+
+    /// Parse an incoming event, accepting the legacy `{type, timestamp}` keys,
+    /// filling optional fields with defaults and rejecting anything that
+    /// lacks a source/event type or carries an unsupported schema version.
+    pub fn normalize_json(input: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(input).map_err(|e| format!("not valid JSON: {}", e))?;
+        Self::normalize_value(value)
+    }
+
+    /// `normalize_json` for an already parsed event (e.g. one read back from the database).
+    pub fn normalize_value(value: serde_json::Value) -> Result<Self, String> {
+        let serde_json::Value::Object(mut obj) = value else {
+            return Err("event must be a JSON object".to_string());
+        };
+        migrate(&mut obj)?;
+        let obj = &obj;
+
+        let str_field = |keys: &[&str]| -> Option<String> {
+            keys.iter()
+                .filter_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+                .map(|s| s.trim().to_string())
+                .find(|s| !s.is_empty())
+        };
+
+        let schema_version = CURRENT_SCHEMA_VERSION.to_string();
+
+        let source = str_field(&["source"]).ok_or_else(|| "missing required field 'source'".to_string())?;
+        let event_type = str_field(&["event_type", "type"])
+            .ok_or_else(|| "missing required field 'event_type'".to_string())?;
+
+        let ts = match str_field(&["ts", "timestamp"]) {
+            Some(ts) => {
+                chrono::DateTime::parse_from_rfc3339(&ts)
+                    .map_err(|_| format!("invalid timestamp '{}'", ts))?;
+                ts
+            }
+            None => chrono::Utc::now().to_rfc3339(),
+        };
+
+        Ok(Self {
+            schema_version,
+            event_id: str_field(&["event_id"]).unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            ts,
+            source,
+            app: str_field(&["app"]).unwrap_or_else(|| "unknown".to_string()),
+            event_type,
+            priority: str_field(&["priority"]).unwrap_or_else(|| "P2".to_string()),
+            resource: obj.get("resource").and_then(|v| serde_json::from_value(v.clone()).ok()),
+            payload: obj
+                .get("payload")
+                .or_else(|| obj.get("data"))
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+            privacy: obj.get("privacy").and_then(|v| serde_json::from_value(v.clone()).ok()),
+            pid: obj.get("pid").and_then(|v| v.as_u64()).map(|v| v as u32),
+            window_id: str_field(&["window_id"]),
+            window_title: str_field(&["window_title"]),
+            browser_url: str_field(&["browser_url"]),
+            raw: obj.get("raw").cloned().filter(|v| !v.is_null()),
+            seq: obj.get("seq").and_then(|v| v.as_u64()),
+            repeat: obj.get("repeat").and_then(|v| v.as_u64()).map(|v| v as u32),
+        })
+    }
+}
+
+// [Schema Migration] Envelopes are upgraded one version at a time to the
+// current shape before they are read. Events without a version predate 1.0.
+// A version newer than this build knows is rejected rather than guessed at.
+
+type Upgrade = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+/// Upgrade steps, oldest first: each turns an envelope of the previous version into this one.
+const MIGRATIONS: &[(&str, Upgrade)] = &[("1.0", upgrade_to_1_0)];
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.trim().split_once('.').unwrap_or((version.trim(), "0"));
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// 0.x envelopes named the fields `type`, `timestamp` and `data`, and had no priority.
+fn upgrade_to_1_0(obj: &mut serde_json::Map<String, serde_json::Value>) {
+    for (old, new) in [("type", "event_type"), ("timestamp", "ts"), ("data", "payload")] {
+        let missing = !matches!(obj.get(new), Some(v) if !v.is_null() && v.as_str() != Some(""));
+        if let Some(value) = obj.remove(old) {
+            if missing {
+                obj.insert(new.to_string(), value);
+            }
+        }
+    }
+    obj.entry("priority").or_insert_with(|| serde_json::json!("P2"));
+}
+
+/// Bring `obj` up to `CURRENT_SCHEMA_VERSION`, or explain why it can't be read.
+fn migrate(obj: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let version = match obj.get("schema_version") {
+        None | Some(serde_json::Value::Null) => "0.9".to_string(),
+        Some(serde_json::Value::String(v)) if v.trim().is_empty() => "0.9".to_string(),
+        Some(serde_json::Value::String(v)) => v.trim().to_string(),
+        Some(other) => other.to_string(),
+    };
+    let parsed = parse_version(&version).ok_or_else(|| format!("unsupported schema_version '{}'", version))?;
+    let current = parse_version(CURRENT_SCHEMA_VERSION).unwrap_or((1, 0));
+    if parsed > current {
+        return Err(format!(
+            "unsupported schema_version '{}': newer than {} (update the agent to read it)",
+            version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+    for (target, upgrade) in MIGRATIONS {
+        if parse_version(target).is_some_and(|t| parsed < t) {
+            upgrade(obj);
+            obj.insert("schema_version".to_string(), serde_json::json!(target));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum EmitError {
+    Serialize(String),
+    /// The channel is at capacity; the event was dropped.
+    Full,
+    Closed,
+}
+
+impl std::fmt::Display for EmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitError::Serialize(e) => write!(f, "failed to serialize event: {}", e),
+            EmitError::Full => write!(f, "event channel full"),
+            EmitError::Closed => write!(f, "event channel closed"),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref NEXT_SEQ: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Copy of `envelope` with the next sequence number of its source, unless it has one.
+fn stamp(envelope: &EventEnvelope) -> EventEnvelope {
+    let mut stamped = envelope.clone();
+    if stamped.seq.is_none() {
+        let mut next = NEXT_SEQ.lock().unwrap_or_else(|e| e.into_inner());
+        let seq = next.entry(stamped.source.clone()).or_insert(0);
+        *seq += 1;
+        stamped.seq = Some(*seq);
+    }
+    stamped
+}
+
+fn try_send(tx: &tokio::sync::mpsc::Sender<String>, envelope: &EventEnvelope) -> Result<(), EmitError> {
+    use tokio::sync::mpsc::error::TrySendError;
+    let log = serde_json::to_string(envelope).map_err(|e| EmitError::Serialize(e.to_string()))?;
+    tx.try_send(log).map_err(|e| match e {
+        TrySendError::Full(_) => EmitError::Full,
+        TrySendError::Closed(_) => EmitError::Closed,
+    })
+}
+
+/// Send an envelope to the analyzer channel without waiting (drops it when full).
+pub fn emit(tx: &tokio::sync::mpsc::Sender<String>, envelope: &EventEnvelope) -> Result<(), EmitError> {
+    try_send(tx, &stamp(envelope))
+}
+
+/// Like `emit`, but waits for room; only for sensor threads outside the runtime.
+pub fn emit_blocking(tx: &tokio::sync::mpsc::Sender<String>, envelope: &EventEnvelope) -> Result<(), EmitError> {
+    let log = serde_json::to_string(&stamp(envelope)).map_err(|e| EmitError::Serialize(e.to_string()))?;
+    tx.blocking_send(log).map_err(|_| EmitError::Closed)
+}
+
+/// What `Backlog::emit` did with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emitted {
+    Sent,
+    /// Waiting in the backlog for room in the channel.
+    Queued,
+    /// Folded into the identical event before it (its `repeat` went up).
+    Coalesced,
+}
+
+/// Events a burst-prone sender could not hand over because the channel was
+/// full. They are sent ahead of newer events, so a source's order is kept,
+/// and identical consecutive ones collapse into one with a `repeat` count.
+/// An event is only dropped once `capacity` distinct events are waiting.
+pub struct Backlog {
+    pending: VecDeque<EventEnvelope>,
+    capacity: usize,
+}
+
+impl Backlog {
+    pub fn new(capacity: usize) -> Self {
+        Self { pending: VecDeque::new(), capacity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Send waiting events, oldest first, until the channel is full again.
+    pub fn flush(&mut self, tx: &tokio::sync::mpsc::Sender<String>) -> Result<(), EmitError> {
+        while let Some(front) = self.pending.front() {
+            match try_send(tx, front) {
+                Ok(()) => {}
+                Err(EmitError::Full) => return Ok(()),
+                Err(EmitError::Closed) => return Err(EmitError::Closed),
+                Err(e) => {
+                    self.pending.pop_front();
+                    return Err(e);
+                }
+            }
+            self.pending.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Keep flushing `backlog` every `interval` on a thread of its own, so
+    /// waiting events go out once the channel drains even if no new event
+    /// arrives to push them. Stops when the channel closes.
+    pub fn flush_periodically(
+        backlog: std::sync::Arc<Mutex<Backlog>>,
+        tx: tokio::sync::mpsc::Sender<String>,
+        interval: std::time::Duration,
+    ) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            if tx.is_closed() {
+                return;
+            }
+            let mut backlog = backlog.lock().unwrap_or_else(|e| e.into_inner());
+            if !backlog.is_empty() && matches!(backlog.flush(&tx), Err(EmitError::Closed)) {
+                return;
+            }
+        })
+    }
+
+    /// Like `emit`, but an event that doesn't fit waits here instead of being
+    /// dropped. `Err(Full)` means the backlog itself is full.
+    pub fn emit(&mut self, tx: &tokio::sync::mpsc::Sender<String>, envelope: &EventEnvelope) -> Result<Emitted, EmitError> {
+        let envelope = stamp(envelope);
+        self.flush(tx)?;
+        if self.pending.is_empty() {
+            match try_send(tx, &envelope) {
+                Ok(()) => return Ok(Emitted::Sent),
+                Err(EmitError::Full) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if let Some(last) = self.pending.back_mut() {
+            if last.same_content(&envelope) {
+                last.repeat = Some(last.repeat.unwrap_or(1) + 1);
+                return Ok(Emitted::Coalesced);
+            }
+        }
+        if self.pending.len() >= self.capacity {
+            return Err(EmitError::Full);
+        }
+        self.pending.push_back(envelope);
+        Ok(Emitted::Queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_action_envelope() {
+        let plan = vec!["ACTIVATE".to_string(), "TYPE".to_string()];
+        let before = chrono::Utc::now();
+        let event = EventEnvelope::agent_action("write a note", 1, "TYPE", "Type the note", &plan);
+
+        assert_eq!(event.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(event.source, AGENT_EVENT_SOURCE);
+        assert_eq!(event.event_type, "agent_action");
+        let ts = chrono::DateTime::parse_from_rfc3339(&event.ts).unwrap();
+        assert!(ts >= before && ts <= chrono::Utc::now());
+        assert_eq!(event.payload["step"], 1);
+        assert_eq!(event.payload["plan"], serde_json::json!(["ACTIVATE", "TYPE"]));
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        emit(&tx, &event).unwrap();
+        assert!(matches!(emit(&tx, &event), Err(EmitError::Full)));
+        let round_trip = EventEnvelope::normalize_json(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(round_trip.event_id, event.event_id);
+        assert_eq!(round_trip.payload["goal"], "write a note");
+    }
+
+    #[test]
+    fn test_older_envelopes_are_upgraded_and_newer_ones_rejected() {
+        let current = EventEnvelope::normalize_json(
+            r#"{"schema_version":"1.0","event_id":"e1","ts":"2024-01-01T09:00:00Z","source":"macos_monitor","app":"Safari","event_type":"app_switch","priority":"P1","payload":{"to":"Safari"}}"#,
+        )
+        .unwrap();
+        assert_eq!((current.event_id.as_str(), current.priority.as_str()), ("e1", "P1"));
+        assert_eq!(current.payload["to"], "Safari");
+
+        let old = EventEnvelope::normalize_json(
+            r#"{"schema_version":"0.9","event_id":"e0","timestamp":"2023-06-01T12:00:00Z","source":"macos_monitor","app":"Mail","type":"key_input","data":{"keycode":36}}"#,
+        )
+        .unwrap();
+        assert_eq!(old.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(old.event_type, "key_input");
+        assert_eq!(old.ts, "2023-06-01T12:00:00Z");
+        assert_eq!(old.payload["keycode"], 36);
+        assert_eq!(old.priority, "P2");
+
+        for future in ["2.0", "1.1"] {
+            let input = format!(r#"{{"schema_version":"{}","source":"x","event_type":"y"}}"#, future);
+            let err = EventEnvelope::normalize_json(&input).unwrap_err();
+            assert!(err.contains("newer than 1.0"), "{}", err);
+        }
+        assert!(EventEnvelope::normalize_json(r#"{"schema_version":"v1","source":"x","event_type":"y"}"#).is_err());
+    }
+
+    #[test]
+    fn test_backlog_keeps_order_and_coalesces_bursts() {
+        let source = format!("test_burst_{}", uuid::Uuid::new_v4());
+        let key = |code: i64| EventEnvelope::new(&source, "system", "key_input", "P2", None, serde_json::json!({ "keycode": code }));
+        // 3 distinct keys, the same key 8 times, 3 more distinct keys
+        let burst: Vec<EventEnvelope> = (1..=3).map(key).chain((0..8).map(|_| key(9))).chain((10..=12).map(key)).collect();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let mut backlog = Backlog::new(16);
+        let results: Vec<Emitted> = burst.iter().map(|e| backlog.emit(&tx, e).unwrap()).collect();
+        assert_eq!(results.iter().filter(|r| **r == Emitted::Sent).count(), 4);
+        assert_eq!(results.iter().filter(|r| **r == Emitted::Coalesced).count(), 6);
+        assert_eq!(backlog.len(), 4);
+
+        let mut received = Vec::new();
+        while !backlog.is_empty() || !rx.is_empty() {
+            while let Ok(log) = rx.try_recv() {
+                received.push(EventEnvelope::normalize_json(&log).unwrap());
+            }
+            backlog.flush(&tx).unwrap();
+        }
+
+        let codes: Vec<i64> = received.iter().map(|e| e.payload["keycode"].as_i64().unwrap()).collect();
+        assert_eq!(codes, vec![1, 2, 3, 9, 9, 10, 11, 12]);
+        assert_eq!(received[4].repeat, Some(7));
+        // Nothing lost: every event is either delivered or counted in a repeat
+        let delivered: u32 = received.iter().map(|e| e.repeat.unwrap_or(1)).sum();
+        assert_eq!(delivered as usize, burst.len());
+        let seqs: Vec<u64> = received.iter().map(|e| e.seq.unwrap()).collect();
+        assert!(seqs.windows(2).all(|w| w[0] < w[1]), "sequence out of order: {:?}", seqs);
+    }
+
+    #[test]
+    fn test_backlog_flushes_on_a_timer() {
+        let source = format!("test_idle_{}", uuid::Uuid::new_v4());
+        let key = |code: i64| EventEnvelope::new(&source, "system", "key_input", "P2", None, serde_json::json!({ "keycode": code }));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let backlog = std::sync::Arc::new(Mutex::new(Backlog::new(16)));
+        {
+            let mut backlog = backlog.lock().unwrap();
+            assert_eq!(backlog.emit(&tx, &key(1)).unwrap(), Emitted::Sent);
+            assert_eq!(backlog.emit(&tx, &key(2)).unwrap(), Emitted::Queued);
+        }
+        let flusher = Backlog::flush_periodically(backlog.clone(), tx, std::time::Duration::from_millis(10));
+
+        // No further input: the waiting key still goes out once there is room
+        let first = EventEnvelope::normalize_json(&rx.blocking_recv().unwrap()).unwrap();
+        let second = EventEnvelope::normalize_json(&rx.blocking_recv().unwrap()).unwrap();
+        assert_eq!((first.payload["keycode"].as_i64(), second.payload["keycode"].as_i64()), (Some(1), Some(2)));
+        assert!(backlog.lock().unwrap().is_empty());
+
+        drop(rx);
+        flusher.join().unwrap();
+    }
 }
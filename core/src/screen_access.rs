@@ -0,0 +1,152 @@
+// [Screen Access] Screen recording is only needed by steps that look at the
+// screen (vision checks, EXTRACT without page text, observe). Goals that only
+// run shell commands, open URLs or call integrations must not fail because
+// the permission is missing, so nothing checks it up front: vision checks are
+// skipped with a note, and the permission error surfaces from the first
+// capture that is actually needed.
+//
+//   STEER_SCREEN_RECORDING=off    never capture, even when permitted
+//   STEER_PREFLIGHT_TRIES=3        checks before a capture reports "denied"
+
+/// Whether screenshots can be taken right now.
+pub fn available() -> bool {
+    resolve(std::env::var("STEER_SCREEN_RECORDING").ok().as_deref(), platform_allows)
+}
+
+fn resolve(setting: Option<&str>, granted: impl FnOnce() -> bool) -> bool {
+    let off = setting
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "0" | "off" | "false" | "no"))
+        .unwrap_or(false);
+    !off && granted()
+}
+
+fn platform_allows() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        crate::macos::accessibility::screen_capture_allowed()
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+/// Error for a capture attempted without access. Worded so the executor
+/// classifies it as `permission_denied` and stops instead of retrying.
+pub fn denied_message() -> String {
+    "Screen recording permission not granted: allow this app in System Settings > Privacy & Security > Screen Recording (or unset STEER_SCREEN_RECORDING=off)".to_string()
+}
+
+/// Fail with [`denied_message`] unless screenshots can be taken. Right after
+/// the permission is toggled macOS can keep answering "denied" for a moment
+/// (TCC caches the old answer), so a denial is re-checked with backoff
+/// before it is reported.
+pub fn require() -> anyhow::Result<()> {
+    let setting = std::env::var("STEER_SCREEN_RECORDING").ok();
+    let retry = PreflightRetry::from_env();
+    // Only a macOS permission denial can clear up; "off" or another OS won't
+    let transient = cfg!(target_os = "macos") && resolve(setting.as_deref(), || true);
+    let tries = if transient { retry.tries } else { 1 };
+    let granted = retry_preflight(
+        tries,
+        retry.delay,
+        || resolve(setting.as_deref(), platform_allows),
+        std::thread::sleep,
+    );
+    if granted {
+        return Ok(());
+    }
+    if tries > 1 {
+        return Err(anyhow::anyhow!(
+            "{} (still denied after {} checks over {:.1}s; if you just granted it, restart the app)",
+            denied_message(),
+            tries,
+            retry.total_wait(tries).as_secs_f64()
+        ));
+    }
+    Err(anyhow::anyhow!(denied_message()))
+}
+
+/// How often a denied preflight is re-checked before giving up.
+#[derive(Debug, Clone, Copy)]
+pub struct PreflightRetry {
+    pub tries: u32,
+    /// Wait before the second check; doubles after each further denial
+    pub delay: std::time::Duration,
+}
+
+impl PreflightRetry {
+    /// `STEER_PREFLIGHT_TRIES` (default 3, minimum 1) and
+    /// `STEER_PREFLIGHT_RETRY_DELAY_MS` (default 500).
+    pub fn from_env() -> Self {
+        let tries = std::env::var("STEER_PREFLIGHT_TRIES")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(3u32)
+            .max(1);
+        let delay_ms = std::env::var("STEER_PREFLIGHT_RETRY_DELAY_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(500u64);
+        Self { tries, delay: std::time::Duration::from_millis(delay_ms) }
+    }
+
+    fn total_wait(&self, tries: u32) -> std::time::Duration {
+        (0..tries.saturating_sub(1)).map(|i| self.delay * 2u32.saturating_pow(i)).sum()
+    }
+}
+
+/// Run `check` up to `tries` times, sleeping `delay`, `2 * delay`, ... between
+/// denials. True as soon as one check passes.
+pub fn retry_preflight(
+    tries: u32,
+    delay: std::time::Duration,
+    mut check: impl FnMut() -> bool,
+    mut sleep: impl FnMut(std::time::Duration),
+) -> bool {
+    for attempt in 0..tries.max(1) {
+        if attempt > 0 {
+            sleep(delay * 2u32.saturating_pow(attempt - 1));
+        }
+        if check() {
+            if attempt > 0 {
+                println!("🔐 Screen recording permission confirmed on check {}", attempt + 1);
+            }
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_setting_and_permission() {
+        assert!(resolve(None, || true));
+        assert!(!resolve(None, || false));
+        assert!(!resolve(Some("off"), || true));
+        assert!(resolve(Some("on"), || true));
+        assert!(denied_message().to_lowercase().contains("permission"));
+    }
+
+    #[test]
+    fn test_preflight_retries_a_transient_denial() {
+        use std::time::Duration;
+        let mut answers = vec![false, true].into_iter();
+        let mut slept = Vec::new();
+        assert!(retry_preflight(3, Duration::from_millis(500), || answers.next().unwrap(), |d| slept.push(d)));
+        assert_eq!(slept, vec![Duration::from_millis(500)]);
+
+        let mut checks = 0;
+        slept.clear();
+        assert!(!retry_preflight(3, Duration::from_millis(500), || { checks += 1; false }, |d| slept.push(d)));
+        assert_eq!(checks, 3);
+        assert_eq!(slept, vec![Duration::from_millis(500), Duration::from_millis(1000)]);
+
+        let retry = PreflightRetry { tries: 3, delay: Duration::from_millis(500) };
+        assert_eq!(retry.total_wait(3), Duration::from_millis(1500));
+        assert_eq!(retry.total_wait(1), Duration::ZERO);
+    }
+}
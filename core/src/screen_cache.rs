@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// [Vision Cache] Every vision check used to take a full screenshot and send it
+// to the LLM, even when nothing had happened since the previous check. A tiny
+// downscaled frame is hashed first; if it matches the last one and no write
+// action ran in between, the previous screenshot (and, for a repeated prompt,
+// the previous answer) is reused.
+//
+//   STEER_SCREEN_REUSE=0    always capture and ask (default on)
+
+static FRAMES_REUSED: AtomicU64 = AtomicU64::new(0);
+static VISION_CALLS_SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReuseStats {
+    pub frames_reused: u64,
+    pub vision_calls_skipped: u64,
+}
+
+pub fn enabled() -> bool {
+    std::env::var("STEER_SCREEN_REUSE")
+        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true)
+}
+
+pub fn stats() -> ReuseStats {
+    ReuseStats {
+        frames_reused: FRAMES_REUSED.load(Ordering::Relaxed),
+        vision_calls_skipped: VISION_CALLS_SKIPPED.load(Ordering::Relaxed),
+    }
+}
+
+pub fn render_metrics() -> String {
+    let s = stats();
+    format!(
+        "# TYPE steer_screen_frames_reused_total counter\n\
+steer_screen_frames_reused_total {}\n\
+# TYPE steer_vision_calls_skipped_total counter\n\
+steer_vision_calls_skipped_total {}\n",
+        s.frames_reused, s.vision_calls_skipped
+    )
+}
+
+/// Hash of a 32x32 capture of the primary screen (blocking; run via spawn_blocking).
+pub fn fingerprint() -> Result<u64> {
+    let id = uuid::Uuid::new_v4();
+    let shot = format!("/tmp/steer_fp_{}.png", id);
+    let small = format!("/tmp/steer_fp_{}.bmp", id);
+
+    let captured = Command::new("screencapture")
+        .args(["-x", "-t", "png", &shot])
+        .status()
+        .context("Failed to run screencapture command")?;
+    if !captured.success() {
+        return Err(anyhow::anyhow!("screencapture returned non-zero exit code"));
+    }
+    let scaled = Command::new("sips")
+        .args(["-z", "32", "32", "-s", "format", "bmp", &shot, "--out", &small])
+        .output()
+        .context("Failed to run sips");
+    let _ = std::fs::remove_file(&shot);
+    if !scaled?.status.success() {
+        return Err(anyhow::anyhow!("sips could not downscale the capture"));
+    }
+
+    let bytes = std::fs::read(&small).context("Failed to read downscaled frame");
+    let _ = std::fs::remove_file(&small);
+    let mut hasher = DefaultHasher::new();
+    bytes?.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Poll `probe` (a screen hash) until two readings in a row match, for at
+/// most `max`. The first reading waits `min`: an action's effect can take a
+/// moment to start, and a baseline taken before it would look settled.
+/// Without a hash (no screen access) just wait `fallback`. Returns when the
+/// first of the two matching readings was taken, measured from the call, so
+/// the time spent on the confirming reading isn't counted; `None` if the
+/// screen never settled.
+pub fn settle(mut probe: impl FnMut() -> Option<u64>, min: Duration, interval: Duration, max: Duration, fallback: Duration) -> Option<Duration> {
+    let started = Instant::now();
+    let deadline = started + max;
+    std::thread::sleep(min.min(max));
+    let mut taken = started.elapsed();
+    let Some(mut last) = probe() else {
+        std::thread::sleep(fallback.saturating_sub(min));
+        return None;
+    };
+    while Instant::now() < deadline {
+        std::thread::sleep(interval);
+        let now_taken = started.elapsed();
+        match probe() {
+            Some(now) if now == last => return Some(taken),
+            Some(now) => (last, taken) = (now, now_taken),
+            None => return None,
+        }
+    }
+    None
+}
+
+fn settle_screen(max: Duration) -> Option<Duration> {
+    let can_see = crate::screen_access::available();
+    settle(
+        || if can_see { fingerprint().ok() } else { None },
+        Duration::from_millis(150),
+        Duration::from_millis(100),
+        max,
+        Duration::from_millis(300),
+    )
+}
+
+/// Wait for the screen to stop changing after an action (a paste landing, a
+/// menu closing), instead of sleeping a fixed time. How long it took feeds
+/// `step_delay`; a wait that times out counts as `max`.
+pub fn wait_for_ui_settle(max: Duration) -> bool {
+    let settled = settle_screen(max);
+    // Without screen access nothing was measured, only the fallback slept
+    if crate::screen_access::available() {
+        crate::step_delay::record_settle(settled.unwrap_or(max));
+    }
+    settled.is_some()
+}
+
+/// `wait_for_ui_settle` for waits that aren't about an action landing, such
+/// as an app launching after ACTIVATE. These can take seconds, so they are
+/// not fed to `step_delay`.
+pub fn wait_for_screen_settle(max: Duration) -> bool {
+    settle_screen(max).is_some()
+}
+
+/// Last screenshot and the vision answers given for it, valid until the screen
+/// hash changes or a write action runs.
+#[derive(Debug, Default)]
+pub struct ScreenCache {
+    frame: Option<(u64, String)>,
+    answers: HashMap<String, bool>,
+}
+
+impl ScreenCache {
+    /// A write action ran: the next check must capture a fresh screenshot.
+    pub fn invalidate(&mut self) {
+        self.frame = None;
+        self.answers.clear();
+    }
+
+    /// The previous screenshot, if the screen still hashes to `fingerprint`.
+    pub fn frame(&self, fingerprint: u64) -> Option<String> {
+        let (fp, image) = self.frame.as_ref()?;
+        (*fp == fingerprint).then(|| {
+            FRAMES_REUSED.fetch_add(1, Ordering::Relaxed);
+            image.clone()
+        })
+    }
+
+    pub fn store_frame(&mut self, fingerprint: u64, image: String) {
+        if self.frame.as_ref().map(|(fp, _)| *fp) != Some(fingerprint) {
+            self.answers.clear();
+        }
+        self.frame = Some((fingerprint, image));
+    }
+
+    /// Answer already given for `prompt` on this exact screen.
+    pub fn answer(&self, fingerprint: u64, prompt: &str) -> Option<bool> {
+        if self.frame.as_ref().map(|(fp, _)| *fp) != Some(fingerprint) {
+            return None;
+        }
+        let answer = self.answers.get(prompt).copied();
+        if answer.is_some() {
+            VISION_CALLS_SKIPPED.fetch_add(1, Ordering::Relaxed);
+        }
+        answer
+    }
+
+    pub fn store_answer(&mut self, prompt: &str, answer: bool) {
+        if self.frame.is_some() {
+            self.answers.insert(prompt.to_string(), answer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settle_waits_for_two_equal_readings() {
+        let ms = Duration::from_millis;
+        let mut frames = vec![1, 2, 3, 3, 9].into_iter();
+        let mut reads = 0;
+        assert!(settle(|| { reads += 1; frames.next() }, ms(0), ms(1), ms(1000), ms(1)).is_some());
+        assert_eq!(reads, 4);
+
+        let mut changing = 0..;
+        assert_eq!(settle(|| changing.next(), ms(0), ms(1), ms(20), ms(1)), None);
+        assert_eq!(settle(|| None, ms(0), ms(1), ms(20), ms(1)), None);
+    }
+
+    #[test]
+    fn test_settle_takes_its_baseline_after_the_minimum_wait() {
+        let ms = Duration::from_millis;
+        let started = Instant::now();
+        let mut first_read = None;
+        // Still before the action's effect: the same frame twice would pass without the wait
+        assert!(settle(|| { first_read.get_or_insert_with(|| started.elapsed()); Some(7) }, ms(40), ms(1), ms(1000), ms(1)).is_some());
+        assert!(first_read.unwrap() >= ms(40));
+    }
+
+    #[test]
+    fn test_settle_time_leaves_out_the_confirming_reading() {
+        let ms = Duration::from_millis;
+        // Each reading is slow, like a capture and a downscale
+        let mut frames = vec![1, 2, 2].into_iter();
+        let slow = || { std::thread::sleep(ms(50)); frames.next() };
+        let took = settle(slow, ms(0), ms(1), ms(1000), ms(1)).unwrap();
+        // Settled at the second reading (after ~50ms), not after the third (~100ms)
+        assert!(took >= ms(50) && took < ms(100), "{:?}", took);
+    }
+
+    #[test]
+    fn test_reuse_until_screen_changes_or_write() {
+        let mut cache = ScreenCache::default();
+        assert!(cache.frame(1).is_none());
+
+        cache.store_frame(1, "img-a".to_string());
+        cache.store_answer("Is the dialog open?", true);
+        assert_eq!(cache.frame(1).as_deref(), Some("img-a"));
+        assert_eq!(cache.answer(1, "Is the dialog open?"), Some(true));
+        assert_eq!(cache.answer(1, "Is the button red?"), None);
+
+        // Screen changed: neither the frame nor the answer carries over
+        assert!(cache.frame(2).is_none());
+        assert_eq!(cache.answer(2, "Is the dialog open?"), None);
+        cache.store_frame(2, "img-b".to_string());
+        assert_eq!(cache.answer(2, "Is the dialog open?"), None);
+
+        cache.store_answer("Is the dialog open?", false);
+        cache.invalidate();
+        assert!(cache.frame(2).is_none());
+        assert_eq!(cache.answer(2, "Is the dialog open?"), None);
+    }
+}
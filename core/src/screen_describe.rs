@@ -0,0 +1,188 @@
+use serde::Serialize;
+use serde_json::Value;
+
+// [Describe Screen] A one-shot "what does the agent see right now" for support
+// and debugging, without starting a task. `describe` in the REPL and
+// `GET /api/screen/describe` capture the screen, ask the vision model for a
+// narrative with the clickable elements, and attach the accessibility
+// snapshot's summary and element list, so a failing step can be compared with
+// what the model and the tree each report for the same screen.
+
+const PROMPT: &str = "Describe the current screen and list clickable elements";
+
+/// Roles treated as clickable when the platform snapshot carries no refs (macOS).
+const INTERACTIVE_ROLES: &[&str] = &[
+    "AXButton",
+    "AXLink",
+    "AXTextField",
+    "AXTextArea",
+    "AXSearchField",
+    "AXCheckBox",
+    "AXRadioButton",
+    "AXPopUpButton",
+    "AXComboBox",
+    "AXMenuItem",
+    "AXMenuButton",
+    "AXTab",
+    "AXSlider",
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ElementRef {
+    /// `e<N>` on platforms whose snapshot numbers clickable elements
+    #[serde(rename = "ref")]
+    pub ref_id: Option<String>,
+    pub role: String,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenDescription {
+    pub captured_at: String,
+    pub narrative: Option<String>,
+    /// Why there is no narrative (no LLM, capture or vision failure)
+    pub narrative_error: Option<String>,
+    pub snapshot_summary: String,
+    pub snapshot_error: Option<String>,
+    pub elements: Vec<ElementRef>,
+}
+
+/// Clickable elements in `tree`, in document order.
+pub fn elements(tree: &Value) -> Vec<ElementRef> {
+    fn walk(node: &Value, out: &mut Vec<ElementRef>) {
+        let role = node["role"].as_str().unwrap_or_default();
+        let ref_id = node["ref"].as_str().map(str::to_string);
+        if ref_id.is_some() || INTERACTIVE_ROLES.contains(&role) {
+            out.push(ElementRef {
+                ref_id,
+                role: role.to_string(),
+                title: node["title"].as_str().or_else(|| node["value"].as_str()).map(str::to_string),
+            });
+        }
+        for child in node["children"].as_array().into_iter().flatten() {
+            walk(child, out);
+        }
+    }
+    let mut out = Vec::new();
+    for child in tree["focused_window"]["children"].as_array().into_iter().flatten() {
+        walk(child, &mut out);
+    }
+    out
+}
+
+/// One line: app, window and element counts.
+pub fn summary(tree: &Value) -> String {
+    fn count(node: &Value) -> usize {
+        node["children"].as_array().map(|c| c.iter().map(|n| 1 + count(n)).sum()).unwrap_or(0)
+    }
+    let app = tree["title"].as_str().filter(|t| !t.is_empty()).unwrap_or("unknown app");
+    let window = &tree["focused_window"];
+    if window.is_null() {
+        return format!("{}: no focused window", app);
+    }
+    let title = window["title"].as_str().filter(|t| !t.is_empty()).unwrap_or("untitled");
+    format!("{} - \"{}\": {} elements, {} clickable", app, title, count(window), elements(tree).len())
+}
+
+/// Capture the screen and describe it; each half reports its own failure.
+/// Neither half captures anything while capture is suppressed.
+pub async fn describe(llm: Option<&crate::llm_gateway::LLMClient>) -> ScreenDescription {
+    if crate::monitoring::is_capture_suppressed() {
+        let refused = Some(crate::monitoring::CAPTURE_SUPPRESSED.to_string());
+        return ScreenDescription {
+            captured_at: chrono::Utc::now().to_rfc3339(),
+            narrative: None,
+            narrative_error: refused.clone(),
+            snapshot_summary: "not captured".to_string(),
+            snapshot_error: refused,
+            elements: Vec::new(),
+        };
+    }
+    let tree = crate::ui_snapshot::capture(None).await;
+
+    let (narrative, narrative_error) = match llm {
+        None => (None, Some("No LLM client configured".to_string())),
+        Some(llm) => match crate::visual_driver::VisualDriver::capture_screen() {
+            Err(e) => (None, Some(format!("Screen capture failed: {}", e))),
+            Ok(b64) => match llm.analyze_screen(PROMPT, &b64).await {
+                Ok(text) => (Some(text), None),
+                Err(e) => (None, Some(format!("Vision call failed: {}", e))),
+            },
+        },
+    };
+
+    ScreenDescription {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        narrative,
+        narrative_error,
+        snapshot_summary: summary(&tree),
+        snapshot_error: tree["error"].as_str().map(str::to_string),
+        elements: elements(&tree),
+    }
+}
+
+impl ScreenDescription {
+    pub fn render(&self) -> String {
+        let mut lines = vec![format!("👁️  Screen at {}", self.captured_at)];
+        match (&self.narrative, &self.narrative_error) {
+            (Some(text), _) => lines.push(text.trim().to_string()),
+            (None, Some(e)) => lines.push(format!("⚠️  No narrative: {}", e)),
+            (None, None) => {}
+        }
+        lines.push(String::new());
+        lines.push(format!("🌳 Accessibility: {}", self.snapshot_summary));
+        if let Some(e) = &self.snapshot_error {
+            lines.push(format!("⚠️  Snapshot incomplete: {}", e));
+        }
+        for el in &self.elements {
+            lines.push(format!(
+                "   {:<5} {:<14} {}",
+                el.ref_id.as_deref().unwrap_or("-"),
+                el.role,
+                el.title.as_deref().unwrap_or("")
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_elements_and_summary_from_snapshot() {
+        let tree = json!({
+            "role": "AXApplication",
+            "title": "Safari",
+            "focused_window": {
+                "role": "AXWindow",
+                "title": "Checkout",
+                "children": [
+                    { "role": "AXToolbar", "children": [
+                        { "role": "AXButton", "title": "Back", "ref": "e1", "children": [] }
+                    ]},
+                    { "role": "AXGroup", "children": [
+                        { "role": "AXStaticText", "value": "Total: $12", "children": [] },
+                        { "role": "AXTextField", "value": "4111", "children": [] },
+                        { "role": "AXGroup", "title": "Pay", "ref": "e2", "children": [] }
+                    ]}
+                ]
+            }
+        });
+        assert_eq!(
+            elements(&tree),
+            vec![
+                ElementRef { ref_id: Some("e1".to_string()), role: "AXButton".to_string(), title: Some("Back".to_string()) },
+                ElementRef { ref_id: None, role: "AXTextField".to_string(), title: Some("4111".to_string()) },
+                ElementRef { ref_id: Some("e2".to_string()), role: "AXGroup".to_string(), title: Some("Pay".to_string()) },
+            ]
+        );
+        assert_eq!(summary(&tree), "Safari - \"Checkout\": 6 elements, 3 clickable");
+
+        let broken = json!({ "role": "AXApplication", "title": "Finder", "error": "No focused window" });
+        assert_eq!(summary(&broken), "Finder: no focused window");
+        assert!(elements(&broken).is_empty());
+    }
+}
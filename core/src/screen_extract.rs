@@ -0,0 +1,311 @@
+use anyhow::Result;
+use serde_json::{Map, Value};
+use crate::llm_gateway::LLMClient;
+use crate::visual_driver::VisualDriver;
+
+// [Extraction] Typed reads from the screen. The caller names the fields it
+// wants and their types; the vision model must answer with a JSON object of
+// exactly those fields, which is checked (and lightly coerced) before anyone
+// stores or acts on it. In a browser the tab's text is used instead of a
+// screenshot, so prices and long pages are read exactly rather than via OCR.
+//
+//   EXTRACT target: "name:string, email:email, date:date"
+//                or '{"name":"string","total":"number"}'
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Text,
+    Number,
+    Integer,
+    Boolean,
+    /// `YYYY-MM-DD`
+    Date,
+    Email,
+}
+
+impl FieldType {
+    pub const NAMES: &'static [&'static str] = &["string", "number", "integer", "boolean", "date", "email"];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "string" | "text" => Some(Self::Text),
+            "number" | "float" => Some(Self::Number),
+            "integer" | "int" => Some(Self::Integer),
+            "boolean" | "bool" => Some(Self::Boolean),
+            "date" => Some(Self::Date),
+            "email" => Some(Self::Email),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "string",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Boolean => "boolean",
+            Self::Date => "date (YYYY-MM-DD)",
+            Self::Email => "email",
+        }
+    }
+
+    /// The value as this type, or `None` if it doesn't fit.
+    fn coerce(&self, value: &Value) -> Option<Value> {
+        let text = value.as_str().map(str::trim);
+        match self {
+            Self::Text => text.filter(|t| !t.is_empty()).map(Value::from),
+            Self::Number => value
+                .as_f64()
+                .or_else(|| text?.replace(',', "").parse::<f64>().ok())
+                .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number)),
+            Self::Integer => value
+                .as_i64()
+                .or_else(|| text?.replace(',', "").parse::<i64>().ok())
+                .map(Value::from),
+            Self::Boolean => value.as_bool().or_else(|| match text?.to_lowercase().as_str() {
+                "true" | "yes" => Some(true),
+                "false" | "no" => Some(false),
+                _ => None,
+            }).map(Value::from),
+            Self::Date => text
+                .filter(|t| chrono::NaiveDate::parse_from_str(t, "%Y-%m-%d").is_ok())
+                .map(Value::from),
+            Self::Email => text
+                .filter(|t| matches!(t.split_once('@'), Some((user, host)) if !user.is_empty() && host.contains('.') && !t.contains(' ')))
+                .map(Value::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractSchema {
+    pub fields: Vec<(String, FieldType)>,
+}
+
+impl ExtractSchema {
+    /// `name:string, email:email` (type defaults to string) or a JSON object of name → type.
+    pub fn parse(spec: &str) -> std::result::Result<Self, String> {
+        let pairs: Vec<(String, String)> = match serde_json::from_str::<Value>(spec.trim()) {
+            Ok(Value::Object(map)) => map
+                .into_iter()
+                .map(|(name, ty)| (name, ty.as_str().unwrap_or_default().to_string()))
+                .collect(),
+            _ => spec
+                .split(',')
+                .filter(|entry| !entry.trim().is_empty())
+                .map(|entry| {
+                    let (name, ty) = entry.split_once(':').unwrap_or((entry, ""));
+                    (name.to_string(), ty.to_string())
+                })
+                .collect(),
+        };
+
+        let mut fields: Vec<(String, FieldType)> = Vec::new();
+        for (name, ty) in pairs {
+            let name = name.trim().to_string();
+            if name.is_empty() || fields.iter().any(|(n, _)| *n == name) {
+                return Err(format!("field names must be unique and non-empty (got '{}')", name));
+            }
+            let Some(kind) = FieldType::parse(&ty) else {
+                return Err(format!(
+                    "unknown type '{}' for field '{}' (use one of: {})",
+                    ty.trim(),
+                    name,
+                    FieldType::NAMES.join(", ")
+                ));
+            };
+            fields.push((name, kind));
+        }
+        if fields.is_empty() {
+            return Err("no fields to extract".to_string());
+        }
+        Ok(Self { fields })
+    }
+
+    fn prompt(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(name, kind)| format!("- \"{}\": {}", name, kind.as_str()))
+            .collect();
+        format!(
+            "Extract data from the screen. Reply ONLY with a JSON object with exactly these keys:\n{}\n\
+             Use null for a field that is not visible. Do not guess.",
+            fields.join("\n")
+        )
+    }
+
+    /// Check a model reply against the schema: every field present, each value
+    /// null or of its type. Returns the object with coerced values and unknown
+    /// keys dropped.
+    pub fn validate(&self, reply: &Value) -> std::result::Result<Value, Vec<String>> {
+        let Some(obj) = reply.as_object() else {
+            return Err(vec!["reply is not a JSON object".to_string()]);
+        };
+        let mut out = Map::new();
+        let mut errors = Vec::new();
+        for (name, kind) in &self.fields {
+            match obj.get(name) {
+                None => errors.push(format!("missing field '{}'", name)),
+                Some(Value::Null) => {
+                    out.insert(name.clone(), Value::Null);
+                }
+                Some(value) => match kind.coerce(value) {
+                    Some(v) => {
+                        out.insert(name.clone(), v);
+                    }
+                    None => errors.push(format!("field '{}' is not a valid {}: {}", name, kind.as_str(), value)),
+                },
+            }
+        }
+        if errors.is_empty() { Ok(Value::Object(out)) } else { Err(errors) }
+    }
+}
+
+/// First JSON object in a model reply (tolerates markdown fences and prose).
+fn parse_reply(reply: &str) -> std::result::Result<Value, String> {
+    let start = reply.find('{').ok_or("reply contains no JSON object")?;
+    let end = reply.rfind('}').filter(|e| *e > start).ok_or("reply contains no JSON object")?;
+    serde_json::from_str(&reply[start..=end]).map_err(|e| format!("reply is not valid JSON: {}", e))
+}
+
+/// Longest page text sent to the model; long pages are cut at this many chars.
+const MAX_PAGE_TEXT_CHARS: usize = 20_000;
+
+/// The active tab's text when `app` is a browser and `fetch` returns some,
+/// trimmed to [`MAX_PAGE_TEXT_CHARS`]. `None` means "use the screenshot".
+fn page_text_for(app: &str, fetch: impl FnOnce(&str) -> Result<String>) -> Option<String> {
+    if !crate::browser_automation::BROWSER_APPS.contains(&app) {
+        return None;
+    }
+    match fetch(app) {
+        Ok(text) if !text.trim().is_empty() => Some(text.trim().chars().take(MAX_PAGE_TEXT_CHARS).collect()),
+        Ok(_) => None,
+        Err(e) => {
+            println!("🔎 [Extract] Could not read {} tab text: {}", app, e);
+            None
+        }
+    }
+}
+
+enum Source {
+    Page(String),
+    Screen(String),
+}
+
+/// Ask for `schema` over `source`. An invalid reply is sent back once with the
+/// problems listed before giving up.
+async fn extract_from(llm: &LLMClient, schema: &ExtractSchema, source: &Source) -> Result<Value> {
+    let base_prompt = schema.prompt();
+    let mut prompt = base_prompt.clone();
+    let mut errors = Vec::new();
+    for _ in 0..2 {
+        let reply = match source {
+            Source::Page(text) => llm.analyze_page_text(&prompt, text).await,
+            Source::Screen(image_b64) => llm.analyze_screen(&prompt, image_b64).await,
+        }
+        .map_err(|e| anyhow::anyhow!("Vision analysis failed: {}", e))?;
+        errors = match parse_reply(&reply).map_err(|e| vec![e]).and_then(|v| schema.validate(&v)) {
+            Ok(data) => return Ok(data),
+            Err(errors) => errors,
+        };
+        println!("🔎 [Extract] Invalid reply: {}", errors.join("; "));
+        prompt = format!(
+            "{}\n\nYour last reply was invalid because:\n- {}",
+            base_prompt,
+            errors.join("\n- ")
+        );
+    }
+    Err(anyhow::anyhow!("Extraction failed validation: {}", errors.join("; ")))
+}
+
+/// Extract `schema` from what the user is looking at. In Chrome or Safari the
+/// tab's text is read exactly via JavaScript; the screenshot + vision path is
+/// only used when that isn't possible or doesn't yield a valid answer.
+pub async fn extract(llm: &LLMClient, schema: &ExtractSchema) -> Result<Value> {
+    let page_text = tokio::task::spawn_blocking(|| {
+        let app = crate::applescript::frontmost_app_name().ok()?;
+        page_text_for(app.trim(), crate::browser_automation::page_text)
+    })
+    .await?;
+    if let Some(text) = page_text {
+        match extract_from(llm, schema, &Source::Page(text)).await {
+            Ok(data) => return Ok(data),
+            Err(e) => println!("🔎 [Extract] Page text didn't answer ({}), falling back to vision", e),
+        }
+    }
+    let image_b64 = tokio::task::spawn_blocking(VisualDriver::capture_screen).await??;
+    extract_from(llm, schema, &Source::Screen(image_b64)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_schema_forms() {
+        let inline = ExtractSchema::parse("name, email:email, total:number").unwrap();
+        assert_eq!(
+            inline.fields,
+            vec![
+                ("name".to_string(), FieldType::Text),
+                ("email".to_string(), FieldType::Email),
+                ("total".to_string(), FieldType::Number)
+            ]
+        );
+        let json_form = ExtractSchema::parse(r#"{"date":"date","paid":"boolean"}"#).unwrap();
+        assert_eq!(json_form.fields.len(), 2);
+        assert!(ExtractSchema::parse("name:colour").unwrap_err().contains("unknown type 'colour'"));
+        assert!(ExtractSchema::parse("name, name").is_err());
+        assert!(ExtractSchema::parse("  ").is_err());
+    }
+
+    #[test]
+    fn test_validate_coerces_and_rejects() {
+        let schema = ExtractSchema::parse("name, email:email, date:date, total:number, seats:integer").unwrap();
+        let reply = parse_reply(
+            "```json\n{\"name\":\"Ada\",\"email\":\"ada@example.com\",\"date\":\"2026-10-17\",\"total\":\"1,234.5\",\"seats\":null,\"extra\":1}\n```",
+        )
+        .unwrap();
+        assert_eq!(
+            schema.validate(&reply).unwrap(),
+            json!({"name":"Ada","email":"ada@example.com","date":"2026-10-17","total":1234.5,"seats":null})
+        );
+
+        let errors = schema
+            .validate(&json!({"name":"Ada","email":"not an email","date":"17/10/2026","total":12}))
+            .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("field 'email' is not a valid email"));
+        assert!(errors[1].starts_with("field 'date' is not a valid date"));
+        assert_eq!(errors[2], "missing field 'seats'");
+    }
+
+    #[test]
+    fn test_page_text_preferred_in_browsers() {
+        let fetched = std::cell::Cell::new(0);
+        let fetch = |text: &'static str| {
+            let fetched = &fetched;
+            move |_: &str| -> Result<String> {
+                fetched.set(fetched.get() + 1);
+                Ok(text.to_string())
+            }
+        };
+
+        assert_eq!(page_text_for("Google Chrome", fetch("  AAPL 227.52 USD \n")).as_deref(), Some("AAPL 227.52 USD"));
+        assert_eq!(page_text_for("Safari", fetch("Results")).as_deref(), Some("Results"));
+        assert_eq!(fetched.get(), 2);
+
+        // Not a browser: the tab is never queried and vision is used
+        assert_eq!(page_text_for("Preview", fetch("ignored")), None);
+        assert_eq!(fetched.get(), 2);
+
+        // Empty page or a failing script also falls back to vision
+        assert_eq!(page_text_for("Safari", fetch("   ")), None);
+        assert_eq!(page_text_for("Google Chrome", |_| Err(anyhow::anyhow!("JavaScript from Apple Events is off"))), None);
+
+        let long = "x".repeat(MAX_PAGE_TEXT_CHARS + 10);
+        assert_eq!(page_text_for("Safari", |_| Ok(long.clone())).map(|t| t.len()), Some(MAX_PAGE_TEXT_CHARS));
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use std::process::Command;
+
+// [Screen Redaction] Vision calls send a full-screen capture to the cloud
+// model, including whatever else is open: chats, password managers, mail.
+// Before encoding, configured screen regions and the windows of configured
+// apps are blacked out, or the capture is limited to the frontmost window.
+// Masking happens on an uncompressed BMP (no image library needed); sips
+// turns the result into the JPEG that is sent. Off unless configured. If a
+// configured app's windows can't be located, no capture is sent at all.
+//
+//   STEER_VISION_REDACT_REGIONS       "x,y,w,h; x,y,w,h" in screen points, top-left origin
+//   STEER_VISION_REDACT_APPS          apps whose windows are blacked out, comma-separated
+//                                     (app or process name, or bundle id like com.tinyspeck.slackmacgap)
+//   STEER_VISION_FRONT_WINDOW_ONLY    send only the frontmost window (default off)
+
+/// A rectangle in screen points, or pixels once scaled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Rect {
+    /// "x, y, w, h" (commas, optional spaces).
+    pub fn parse(text: &str) -> Option<Self> {
+        let nums: Vec<f64> = text.split(',').map(|n| n.trim().parse::<f64>()).collect::<Result<_, _>>().ok()?;
+        match nums[..] {
+            [x, y, w, h] if w > 0.0 && h > 0.0 => Some(Self { x, y, w, h }),
+            _ => None,
+        }
+    }
+
+    /// This rect relative to `origin`, then multiplied by `scale`.
+    fn to_image(self, origin: (f64, f64), scale: f64) -> Self {
+        Self {
+            x: (self.x - origin.0) * scale,
+            y: (self.y - origin.1) * scale,
+            w: self.w * scale,
+            h: self.h * scale,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Redaction {
+    pub regions: Vec<Rect>,
+    pub apps: Vec<String>,
+    pub front_window_only: bool,
+}
+
+impl Redaction {
+    pub fn from_env() -> Self {
+        let var = |key: &str| std::env::var(key).unwrap_or_default();
+        let regions = var("STEER_VISION_REDACT_REGIONS");
+        let apps = var("STEER_VISION_REDACT_APPS");
+        Self {
+            regions: regions.split(';').filter(|r| !r.trim().is_empty()).filter_map(|r| {
+                let rect = Rect::parse(r);
+                if rect.is_none() {
+                    eprintln!("⚠️ [Redaction] Ignoring region '{}' (expected x,y,w,h)", r.trim());
+                }
+                rect
+            }).collect(),
+            apps: apps.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+            front_window_only: matches!(var("STEER_VISION_FRONT_WINDOW_ONLY").trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.regions.is_empty() || !self.apps.is_empty() || self.front_window_only
+    }
+}
+
+/// Black out `rects` (pixels, top-left origin) in a 24- or 32-bit BMP, in place.
+pub fn mask_bmp(bmp: &mut [u8], rects: &[Rect]) -> Result<()> {
+    let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+    if bmp.len() < 34 || &bmp[0..2] != b"BM" {
+        anyhow::bail!("Not a BMP image");
+    }
+    let offset = u32_at(bmp, 10) as usize;
+    let width = u32_at(bmp, 18) as i32;
+    let raw_height = u32_at(bmp, 22) as i32;
+    let bpp = u16_at(bmp, 28) as usize;
+    let compression = u32_at(bmp, 30);
+    // 3 = BI_BITFIELDS, which 32-bit BMPs use for plain BGRA
+    if !(bpp == 24 || bpp == 32) || !(compression == 0 || compression == 3) || width <= 0 {
+        anyhow::bail!("Unsupported BMP ({} bpp, compression {})", bpp, compression);
+    }
+    let (width, height) = (width as usize, raw_height.unsigned_abs() as usize);
+    let bottom_up = raw_height > 0;
+    let bytes_per_pixel = bpp / 8;
+    let stride = (bpp * width).div_ceil(32) * 4;
+    if bmp.len() < offset + stride * height {
+        anyhow::bail!("BMP pixel data is truncated");
+    }
+
+    for rect in rects {
+        let clamp = |v: f64, max: usize| (v.max(0.0) as usize).min(max);
+        let (x0, x1) = (clamp(rect.x.floor(), width), clamp((rect.x + rect.w).ceil(), width));
+        let (y0, y1) = (clamp(rect.y.floor(), height), clamp((rect.y + rect.h).ceil(), height));
+        for y in y0..y1 {
+            let row = if bottom_up { height - 1 - y } else { y };
+            let start = offset + row * stride;
+            for x in x0..x1 {
+                let px = start + x * bytes_per_pixel;
+                // Blue, green, red; alpha (if any) stays
+                bmp[px..px + 3].fill(0);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Window frames of `app` (the frontmost window when None); None when no
+/// process matches `app`. Errors rather than returning fewer frames, so
+/// nothing is left unmasked by accident.
+fn frames(app: Option<&str>) -> Result<Option<Vec<Rect>>> {
+    crate::applescript::window_frames(app)?.map(parse_frames).transpose()
+}
+
+fn parse_frames(lines: Vec<String>) -> Result<Vec<Rect>> {
+    lines
+        .iter()
+        .map(|f| Rect::parse(f).with_context(|| format!("Unreadable window frame '{}'", f)))
+        .collect()
+}
+
+/// The windows of every app in `apps`, through `lookup`. Any failed lookup
+/// fails the capture (fail closed). An app no process matches has nothing to
+/// mask, but that is also what a misspelled name looks like, so it's warned
+/// about.
+fn app_rects(apps: &[String], lookup: impl Fn(&str) -> Result<Option<Vec<Rect>>>) -> Result<Vec<Rect>> {
+    let mut rects = Vec::new();
+    for app in apps {
+        let found = lookup(app)
+            .with_context(|| format!("Can't locate {}'s windows to black them out; capture not sent", app))?;
+        match found {
+            Some(found) => rects.extend(found),
+            None => eprintln!(
+                "⚠️ [Redaction] No running process matches '{}' (STEER_VISION_REDACT_APPS takes an app or process name, or a bundle id)",
+                app
+            ),
+        }
+    }
+    Ok(rects)
+}
+
+fn run(cmd: &mut Command, what: &str) -> Result<()> {
+    let status = cmd.status().with_context(|| format!("Failed to run {}", what))?;
+    if !status.success() {
+        anyhow::bail!("{} returned non-zero exit code", what);
+    }
+    Ok(())
+}
+
+/// Capture the screen with `redaction` applied; Base64 JPEG like
+/// `VisualDriver::capture_screen` (blocking).
+pub fn capture(redaction: &Redaction) -> Result<String> {
+    let id = uuid::Uuid::new_v4();
+    let bmp_path = format!("/tmp/steer_vision_{}.bmp", id);
+    let jpg_path = format!("/tmp/steer_vision_{}.jpg", id);
+
+    let mut capture = Command::new("screencapture");
+    capture.args(["-x", "-t", "bmp"]);
+    // Where the captured image starts, in screen points
+    let mut origin = (0.0, 0.0);
+    let mut extent = None;
+    if redaction.front_window_only {
+        let front = frames(None)?.unwrap_or_default().into_iter().next().context("No frontmost window to capture")?;
+        capture.arg(format!("-R{},{},{},{}", front.x, front.y, front.w, front.h));
+        origin = (front.x, front.y);
+        extent = Some(front.w);
+    } else {
+        capture.arg("-C");
+    }
+    capture.arg(&bmp_path);
+    run(&mut capture, "screencapture")?;
+
+    let result = (|| {
+        let mut bmp = std::fs::read(&bmp_path).context("Failed to read captured image file")?;
+        // Captures are in pixels, frames in points (2x apart on Retina)
+        let width_pts = extent.or_else(|| {
+            crate::applescript::desktop_size().ok().and_then(|s| s.split(',').next()?.trim().parse::<f64>().ok())
+        });
+        let width_px = bmp.get(18..22).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64).unwrap_or(0.0);
+        let scale = width_pts.filter(|w| *w > 0.0).map(|w| width_px / w).unwrap_or(1.0);
+
+        let mut rects = redaction.regions.clone();
+        rects.extend(app_rects(&redaction.apps, |app| frames(Some(app)))?);
+        let rects: Vec<Rect> = rects.into_iter().map(|r| r.to_image(origin, scale)).collect();
+        mask_bmp(&mut bmp, &rects)?;
+        std::fs::write(&bmp_path, &bmp).context("Failed to write redacted image")?;
+
+        run(Command::new("sips").args(["-s", "format", "jpeg", &bmp_path, "--out", &jpg_path]), "sips")?;
+        let jpg = std::fs::read(&jpg_path).context("Failed to read redacted JPEG")?;
+        Ok(general_purpose::STANDARD.encode(jpg))
+    })();
+    let _ = std::fs::remove_file(&bmp_path);
+    let _ = std::fs::remove_file(&jpg_path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A white `width` x `height` BMP; negative `height` in the header means top-down.
+    fn white_bmp(width: usize, height: usize, bpp: usize, top_down: bool) -> Vec<u8> {
+        let stride = (bpp * width).div_ceil(32) * 4;
+        let mut bmp = vec![0u8; 54];
+        bmp[0..2].copy_from_slice(b"BM");
+        bmp[2..6].copy_from_slice(&((54 + stride * height) as u32).to_le_bytes());
+        bmp[10..14].copy_from_slice(&54u32.to_le_bytes());
+        bmp[14..18].copy_from_slice(&40u32.to_le_bytes());
+        bmp[18..22].copy_from_slice(&(width as i32).to_le_bytes());
+        let h = if top_down { -(height as i32) } else { height as i32 };
+        bmp[22..26].copy_from_slice(&h.to_le_bytes());
+        bmp[26..28].copy_from_slice(&1u16.to_le_bytes());
+        bmp[28..30].copy_from_slice(&(bpp as u16).to_le_bytes());
+        bmp.extend(std::iter::repeat_n(255u8, stride * height));
+        bmp
+    }
+
+    /// Whether pixel (x, y), top-left origin, is black.
+    fn is_black(bmp: &[u8], width: usize, height: usize, bpp: usize, top_down: bool, x: usize, y: usize) -> bool {
+        let stride = (bpp * width).div_ceil(32) * 4;
+        let row = if top_down { y } else { height - 1 - y };
+        let px = 54 + row * stride + x * bpp / 8;
+        bmp[px..px + 3] == [0, 0, 0]
+    }
+
+    #[test]
+    fn test_mask_region_is_blacked_out() {
+        for (bpp, top_down) in [(24, false), (32, false), (24, true)] {
+            let (w, h) = (5, 4);
+            let mut bmp = white_bmp(w, h, bpp, top_down);
+            // A region hanging off the right edge is clipped
+            let region = Rect::parse("1, 1, 10, 2").unwrap();
+            mask_bmp(&mut bmp, &[region]).unwrap();
+            for y in 0..h {
+                for x in 0..w {
+                    let expected = x >= 1 && (1..3).contains(&y);
+                    assert_eq!(is_black(&bmp, w, h, bpp, top_down, x, y), expected, "{} bpp, ({}, {})", bpp, x, y);
+                }
+            }
+        }
+        // The alpha byte of 32-bit pixels is kept
+        let mut bmp = white_bmp(1, 1, 32, false);
+        mask_bmp(&mut bmp, &[Rect { x: 0.0, y: 0.0, w: 1.0, h: 1.0 }]).unwrap();
+        assert_eq!(bmp[54..58], [0, 0, 0, 255]);
+
+        assert!(mask_bmp(&mut b"GIF89a".to_vec(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_regions_scale_from_points_to_pixels() {
+        let region = Rect::parse("100,50,20,10").unwrap();
+        // Retina capture of a window at (80, 40)
+        assert_eq!(region.to_image((80.0, 40.0), 2.0), Rect { x: 40.0, y: 20.0, w: 40.0, h: 20.0 });
+        assert_eq!(Rect::parse("1,2,3"), None);
+        assert_eq!(Rect::parse("1,2,0,4"), None);
+        assert!(!Redaction::default().is_active());
+    }
+
+    #[test]
+    fn test_unreadable_app_frames_refuse_the_capture() {
+        let apps = vec!["Slack".to_string(), "1Password".to_string()];
+        let slack = Rect { x: 0.0, y: 0.0, w: 100.0, h: 80.0 };
+        let lookup = |fail: bool| {
+            move |app: &str| match app {
+                "Slack" => Ok(Some(vec![slack])),
+                _ if fail => Err(anyhow::anyhow!("System Events timed out")),
+                // Not running: nothing to mask
+                _ => Ok(None),
+            }
+        };
+        assert_eq!(app_rects(&apps, lookup(false)).unwrap(), vec![slack]);
+
+        let err = app_rects(&apps, lookup(true)).unwrap_err();
+        assert!(format!("{:#}", err).contains("Can't locate 1Password's windows"), "{:#}", err);
+        // A frame that doesn't parse would leave its window unmasked too
+        assert!(parse_frames(vec!["10, 20, 300, 200".to_string(), "missing value".to_string()]).is_err());
+    }
+}
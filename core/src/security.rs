@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SafetyLevel {
     Safe,
     Warning,
@@ -40,3 +41,13 @@ impl CommandClassifier {
         SafetyLevel::Safe
     }
 }
+
+/// Raise the classifier's level when the impact analysis sees side effects it missed
+/// (e.g. `find . -delete` or a network call in the middle of a pipeline).
+pub fn escalate_with_impact(level: SafetyLevel, impact: &crate::shell_analysis::ShellImpact) -> SafetyLevel {
+    match level {
+        SafetyLevel::Safe if impact.destructive => SafetyLevel::Critical,
+        SafetyLevel::Safe if !impact.is_read_only() => SafetyLevel::Warning,
+        other => other,
+    }
+}
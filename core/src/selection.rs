@@ -0,0 +1,72 @@
+use anyhow::Result;
+
+// [Read Selection] Reading what the user (or a previous step) selected used to
+// mean select-all + copy, which overwrote the clipboard. READ_SELECTION and
+// `GET /api/context/selection` ask the accessibility API for the focused
+// element's selected text instead. Should the lookup still change the
+// clipboard (some apps copy on selection), what was there is put back.
+
+/// Currently selected text in the frontmost app, via accessibility.
+pub fn read() -> Result<String> {
+    #[cfg(target_os = "macos")]
+    {
+        read_with(
+            crate::macos::accessibility::get_selected_text,
+            crate::clipboard::get_text,
+            crate::clipboard::set_text,
+        )
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        anyhow::bail!("Reading the selection is supported only on macOS")
+    }
+}
+
+/// `read` with the accessibility lookup and the clipboard supplied by the caller.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))] // the AX lookup is macOS-only
+pub fn read_with(
+    selected_text: impl FnOnce() -> Option<String>,
+    get: impl Fn() -> Result<String>,
+    set: impl Fn(&str) -> Result<()>,
+) -> Result<String> {
+    let before = get().ok();
+    let text = selected_text();
+    if let Some(before) = before.filter(|b| get().ok().as_ref() != Some(b)) {
+        let _ = set(&before);
+    }
+    text.filter(|text| !text.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Nothing is selected in the focused element"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_selection_leaves_clipboard_alone() {
+        use std::cell::{Cell, RefCell};
+        let clipboard = RefCell::new("the user's copy".to_string());
+        let writes = Cell::new(0);
+        let get = || Ok(clipboard.borrow().clone());
+        let set = |text: &str| {
+            writes.set(writes.get() + 1);
+            *clipboard.borrow_mut() = text.to_string();
+            Ok(())
+        };
+
+        let text = read_with(|| Some("Groceries\nmilk, eggs".to_string()), get, set).unwrap();
+        assert_eq!(text, "Groceries\nmilk, eggs");
+        assert_eq!(writes.get(), 0);
+
+        // A lookup that copies on the side gets the user's copy put back
+        let copying = || {
+            *clipboard.borrow_mut() = "Groceries".to_string();
+            Some("Groceries".to_string())
+        };
+        assert_eq!(read_with(copying, get, set).unwrap(), "Groceries");
+        assert_eq!(*clipboard.borrow(), "the user's copy");
+
+        assert!(read_with(|| None, get, set).unwrap_err().to_string().contains("Nothing is selected"));
+        assert!(read_with(|| Some("  ".to_string()), get, set).is_err());
+    }
+}
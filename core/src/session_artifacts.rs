@@ -0,0 +1,347 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use std::path::PathBuf;
+
+// [Session Artifacts] Files a goal run keeps on purpose, as opposed to the
+// throwaway captures used for vision checks. A SCREENSHOT step saves the
+// current screen under `<STEER_HOME>/sessions/<session_key>/` and records the
+// path as the step's output, so the transcript doubles as a visual timeline.
+// When the run ends its step transcript is written next to them as
+// `transcript.json`, so one folder holds everything the run produced.
+// While the run goes, each recorded step is also appended to `transcript.log`
+// (one timestamped line with its decision source), so a run that crashes
+// midway still leaves a readable record to line up with crash.log.
+//
+// Folders are removed with their sessions (`sessions purge`) and by the
+// retention cleanup that runs at startup and on `sessions cleanup`:
+//
+//   STEER_ARTIFACT_RETENTION_DAYS   delete folders untouched for longer (default 30, 0 = keep)
+//   STEER_ARTIFACT_MAX_MB           then delete the oldest until all fit (default 1024, 0 = no limit)
+//   STEER_TRANSCRIPT_FSYNC          flush each transcript.log line to disk (default off)
+
+const SCREENSHOT_ACTION: &str = "SCREENSHOT";
+const TRANSCRIPT_FILE: &str = "transcript.json";
+const TRANSCRIPT_LOG: &str = "transcript.log";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Screenshot {
+    pub step_index: i64,
+    pub label: String,
+    pub path: String,
+    pub created_at: String,
+}
+
+pub fn session_dir(session_key: &str) -> PathBuf {
+    crate::paths::steer_home().join("sessions").join(file_safe(session_key))
+}
+
+/// `Before Submit!` → `before_submit`; empty labels become `screenshot`.
+fn file_safe(label: &str) -> String {
+    let mut out = String::new();
+    for c in label.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() || c == '-' {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_matches('_').chars().take(60).collect::<String>();
+    if out.is_empty() { "screenshot".to_string() } else { out }
+}
+
+/// Write a base64 JPEG (from `VisualDriver::capture_screen`) as step `step_index`'s
+/// screenshot. The step number prefix keeps files in timeline order.
+pub fn save_screenshot(session_key: &str, step_index: usize, label: &str, jpeg_b64: &str) -> Result<PathBuf> {
+    let dir = session_dir(session_key);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let bytes = general_purpose::STANDARD.decode(jpeg_b64).context("Screenshot is not valid base64")?;
+    let path = dir.join(format!("{:03}_{}.jpg", step_index + 1, file_safe(label)));
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Output recorded for a SCREENSHOT step.
+pub fn step_output(label: &str, path: &std::path::Path) -> String {
+    serde_json::json!({ "label": label, "path": path.display().to_string() }).to_string()
+}
+
+/// Screenshots a run saved, in the order they were taken.
+pub fn screenshots(session_key: &str) -> Vec<Screenshot> {
+    let steps = crate::db::list_goal_steps(session_key, 500).unwrap_or_default();
+    steps
+        .into_iter()
+        .filter(|s| s.action_type == SCREENSHOT_ACTION && s.outcome == "success")
+        .filter_map(|s| {
+            let output: serde_json::Value = serde_json::from_str(s.output.as_deref()?).ok()?;
+            Some(Screenshot {
+                step_index: s.step_index,
+                label: output["label"].as_str().unwrap_or_default().to_string(),
+                path: output["path"].as_str()?.to_string(),
+                created_at: s.created_at,
+            })
+        })
+        .collect()
+}
+
+/// A file in a session's artifact folder.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Artifact {
+    pub name: String,
+    pub path: String,
+    pub bytes: u64,
+    pub modified_at: String,
+}
+
+/// Everything in a session's artifact folder, by name (screenshots sort in step order).
+pub fn artifacts(session_key: &str) -> Vec<Artifact> {
+    let Ok(entries) = std::fs::read_dir(session_dir(session_key)) else {
+        return Vec::new();
+    };
+    let mut found: Vec<Artifact> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let meta = e.metadata().ok().filter(|m| m.is_file())?;
+            Some(Artifact {
+                name: e.file_name().to_string_lossy().to_string(),
+                path: e.path().display().to_string(),
+                bytes: meta.len(),
+                modified_at: meta.modified().map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()).unwrap_or_default(),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    found
+}
+
+/// Write the run's step transcript into its artifact folder. Runs that
+/// recorded no steps get no folder.
+pub fn save_transcript(session_key: &str, goal: &str) -> Result<Option<PathBuf>> {
+    let steps = crate::db::list_goal_steps(session_key, 500)?;
+    if steps.is_empty() {
+        return Ok(None);
+    }
+    let dir = session_dir(session_key);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(TRANSCRIPT_FILE);
+    let transcript = serde_json::json!({ "session_key": session_key, "goal": goal, "steps": steps });
+    std::fs::write(&path, serde_json::to_string_pretty(&transcript)?).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// `2024-05-01T09:00:00+00:00 step 3 [llm] CLICK blocked (policy_block): Click Send - error: ...`
+pub fn transcript_line(ts: &str, step: &crate::db::GoalStep) -> String {
+    let mut line = format!(
+        "{} step {} [{}] {} {}",
+        ts,
+        step.step_index + 1,
+        step.decision_source,
+        step.action_type,
+        step.outcome
+    );
+    if let Some(reason) = &step.block_reason {
+        line.push_str(&format!(" ({})", reason));
+    }
+    line.push_str(&format!(": {}", step.description));
+    if let Some(error) = &step.error {
+        line.push_str(&format!(" - error: {}", error));
+    }
+    // One step, one line, whatever the description held
+    line.replace(['\n', '\r'], " ")
+}
+
+/// Append `step` to its session's `transcript.log` as it is recorded.
+pub fn append_transcript(step: &crate::db::GoalStep) -> Result<PathBuf> {
+    use std::io::Write;
+    let dir = session_dir(&step.session_key);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(TRANSCRIPT_LOG);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", transcript_line(&chrono::Utc::now().to_rfc3339(), step))?;
+    let fsync = std::env::var("STEER_TRANSCRIPT_FSYNC").map(|v| matches!(v.trim(), "1" | "true")).unwrap_or(false);
+    if fsync {
+        file.sync_data()?;
+    }
+    Ok(path)
+}
+
+/// How long and how much session artifacts are kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    pub max_age: Option<chrono::Duration>,
+    pub max_bytes: Option<u64>,
+}
+
+impl Retention {
+    pub fn from_env() -> Self {
+        let num = |key: &str, default: u64| std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(default);
+        let days = num("STEER_ARTIFACT_RETENTION_DAYS", 30);
+        let mb = num("STEER_ARTIFACT_MAX_MB", 1024);
+        Self {
+            max_age: (days > 0).then(|| chrono::Duration::days(days as i64)),
+            max_bytes: (mb > 0).then_some(mb * 1024 * 1024),
+        }
+    }
+}
+
+/// Size and last change of one session folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionUsage {
+    pub session_key: String,
+    pub bytes: u64,
+    pub modified: chrono::DateTime<chrono::Utc>,
+}
+
+/// Folders to delete: those older than `max_age`, then the oldest of the
+/// rest until the total fits in `max_bytes`.
+pub fn select_expired(mut usage: Vec<SessionUsage>, retention: Retention, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+    usage.sort_by_key(|u| u.modified);
+    let mut expired = Vec::new();
+    let mut total: u64 = usage.iter().map(|u| u.bytes).sum();
+    for u in usage {
+        let too_old = retention.max_age.is_some_and(|age| now - u.modified > age);
+        let too_big = retention.max_bytes.is_some_and(|max| total > max);
+        if !too_old && !too_big {
+            continue;
+        }
+        total -= u.bytes;
+        expired.push(u.session_key);
+    }
+    expired
+}
+
+fn folder_usage(dir: &std::path::Path) -> (u64, Option<std::time::SystemTime>) {
+    let mut bytes = 0;
+    let mut newest = std::fs::metadata(dir).and_then(|m| m.modified()).ok();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()) {
+        if let Ok(meta) = entry.metadata() {
+            bytes += meta.len();
+            newest = newest.max(meta.modified().ok());
+        }
+    }
+    (bytes, newest)
+}
+
+/// Apply `retention` to every session folder; returns the removed session keys.
+pub fn cleanup(retention: Retention) -> Vec<String> {
+    let root = crate::paths::steer_home().join("sessions");
+    let usage: Vec<SessionUsage> = std::fs::read_dir(&root)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let (bytes, modified) = folder_usage(&e.path());
+            SessionUsage {
+                session_key: e.file_name().to_string_lossy().to_string(),
+                bytes,
+                modified: modified.map(chrono::DateTime::<chrono::Utc>::from).unwrap_or_else(chrono::Utc::now),
+            }
+        })
+        .collect();
+    let expired = select_expired(usage, retention, chrono::Utc::now());
+    remove_sessions(&expired);
+    expired
+}
+
+/// Delete the artifact folders of sessions that were purged from the database.
+pub fn remove_sessions(session_keys: &[String]) {
+    for key in session_keys {
+        let dir = session_dir(key);
+        if dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                eprintln!("⚠️ Failed to remove {}: {}", dir.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshots_are_saved_and_listed_in_order() {
+        crate::db::init().ok();
+        let session = uuid::Uuid::new_v4().to_string();
+        let jpeg = general_purpose::STANDARD.encode([0xFFu8, 0xD8, 0xFF, 0xD9]);
+
+        let path = save_screenshot(&session, 2, "Before Submit!", &jpeg).unwrap();
+        assert!(path.ends_with("003_before_submit.jpg"));
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0xFF, 0xD8, 0xFF, 0xD9]);
+
+        for (index, outcome, output) in [
+            (0, "success", None),
+            (2, "success", Some(step_output("Before Submit!", &path))),
+            (3, "failed", None),
+        ] {
+            crate::db::insert_goal_step(&crate::db::GoalStep {
+                session_key: session.clone(),
+                step_index: index,
+                action_type: if index == 0 { "CLICK" } else { SCREENSHOT_ACTION }.to_string(),
+                outcome: outcome.to_string(),
+                output,
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let shots = screenshots(&session);
+        assert_eq!(shots.len(), 1);
+        assert_eq!((shots[0].step_index, shots[0].label.as_str()), (2, "Before Submit!"));
+        assert_eq!(shots[0].path, path.display().to_string());
+
+        let transcript = save_transcript(&session, "document the form").unwrap().expect("steps recorded");
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&transcript).unwrap()).unwrap();
+        assert_eq!(saved["goal"], "document the form");
+        assert_eq!(saved["steps"].as_array().unwrap().len(), 3);
+        let names: Vec<String> = artifacts(&session).into_iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["003_before_submit.jpg", "transcript.json"]);
+        assert_eq!(save_transcript(&uuid::Uuid::new_v4().to_string(), "nothing ran").unwrap(), None);
+
+        remove_sessions(std::slice::from_ref(&session));
+        assert!(!session_dir(&session).exists());
+        assert!(artifacts(&session).is_empty());
+    }
+
+    #[test]
+    fn test_transcript_line_keeps_one_step_per_line() {
+        let step = crate::db::GoalStep {
+            step_index: 2,
+            action_type: "CLICK".to_string(),
+            description: "Click\nSend".to_string(),
+            decision_source: "llm".to_string(),
+            outcome: "blocked".to_string(),
+            error: Some("needs approval".to_string()),
+            block_reason: Some("write_lock".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            transcript_line("2024-05-01T09:00:00+00:00", &step),
+            "2024-05-01T09:00:00+00:00 step 3 [llm] CLICK blocked (write_lock): Click Send - error: needs approval"
+        );
+    }
+
+    #[test]
+    fn test_retention_drops_old_then_oldest_over_size() {
+        let now = chrono::Utc::now();
+        let usage = |key: &str, mb: u64, days_ago: i64| SessionUsage {
+            session_key: key.to_string(),
+            bytes: mb * 1024 * 1024,
+            modified: now - chrono::Duration::days(days_ago),
+        };
+        let sessions = vec![usage("fresh", 300, 1), usage("stale", 10, 40), usage("mid", 300, 5), usage("older", 300, 10)];
+        let retention = Retention { max_age: Some(chrono::Duration::days(30)), max_bytes: Some(700 * 1024 * 1024) };
+        // "stale" by age; then 900 MB left, so the oldest ("older") goes too
+        assert_eq!(select_expired(sessions.clone(), retention, now), vec!["stale", "older"]);
+
+        let keep_all = Retention { max_age: None, max_bytes: None };
+        assert!(select_expired(sessions.clone(), keep_all, now).is_empty());
+        let size_only = Retention { max_age: None, max_bytes: Some(350 * 1024 * 1024) };
+        assert_eq!(select_expired(sessions, size_only, now), vec!["stale", "older", "mid"]);
+    }
+}
@@ -27,6 +27,133 @@ pub fn analyze_shell_command(command: &str) -> ShellCommandAnalysis {
     }
 }
 
+/// What a command is expected to touch, for approval prompts and audit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShellImpact {
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+    pub network: bool,
+    pub destructive: bool,
+    /// Runs under sudo; what it does still decides whether it is destructive
+    pub elevated: bool,
+}
+
+impl ShellImpact {
+    pub fn is_read_only(&self) -> bool {
+        self.writes.is_empty() && !self.network && !self.destructive && !self.elevated
+    }
+
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.destructive {
+            parts.push("DESTRUCTIVE".to_string());
+        }
+        if self.elevated {
+            parts.push("sudo".to_string());
+        }
+        if self.network {
+            parts.push("network".to_string());
+        }
+        if !self.writes.is_empty() {
+            parts.push(format!("writes: {}", self.writes.join(", ")));
+        }
+        if !self.reads.is_empty() {
+            parts.push(format!("reads: {}", self.reads.join(", ")));
+        }
+        if parts.is_empty() {
+            "no file/network side effects detected".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+const NETWORK_PROGRAMS: &[&str] = &["curl", "wget", "ssh", "scp", "sftp", "nc", "ftp", "telnet", "rsync", "ping"];
+const DESTRUCTIVE_PROGRAMS: &[&str] = &["rm", "rmdir", "dd", "mkfs", "shred", "truncate", "kill", "killall", "pkill", "diskutil"];
+const WRITE_PROGRAMS: &[&str] = &["cp", "mv", "touch", "mkdir", "tee", "ln", "chmod", "chown", "rm", "rmdir", "truncate", "shred"];
+const READ_PROGRAMS: &[&str] = &["cat", "less", "more", "head", "tail", "grep", "wc", "ls", "stat", "file", "open"];
+
+pub fn assess_impact(command: &str) -> ShellImpact {
+    let mut impact = ShellImpact::default();
+
+    for segment in split_segments(command) {
+        let tokens: Vec<&str> = segment.split_whitespace().collect();
+        let mut idx = 0;
+        if tokens.first() == Some(&"sudo") {
+            impact.elevated = true;
+            idx = 1;
+            // sudo's own options, and the user or group some of them name
+            while let Some(option) = tokens.get(idx).filter(|t| t.starts_with('-')) {
+                idx += if matches!(*option, "-u" | "-g" | "-h" | "-p" | "-C") { 2 } else { 1 };
+            }
+        }
+        let Some(program) = tokens.get(idx).map(|p| p.rsplit('/').next().unwrap_or(*p)) else {
+            continue;
+        };
+        let mut args: Vec<&str> = Vec::new();
+        let mut redirect_next = false;
+        for token in &tokens[idx + 1..] {
+            if redirect_next {
+                impact.writes.push(token.to_string());
+                redirect_next = false;
+            } else if *token == ">" || *token == ">>" {
+                redirect_next = true;
+            } else if let Some(target) = token.strip_prefix(">>").or_else(|| token.strip_prefix('>')) {
+                impact.writes.push(target.to_string());
+            } else if !token.starts_with('-') {
+                args.push(token);
+            }
+        }
+
+        if NETWORK_PROGRAMS.contains(&program)
+            || (program == "git" && matches!(args.first(), Some(&"push") | Some(&"pull") | Some(&"clone") | Some(&"fetch")))
+            || (matches!(program, "npm" | "pip" | "pip3" | "brew" | "cargo") && args.first() == Some(&"install"))
+        {
+            impact.network = true;
+        }
+
+        let options = &tokens[(idx + 1).min(tokens.len())..];
+        if DESTRUCTIVE_PROGRAMS.contains(&program)
+            || (program == "mv" && has_flag(options, 'f', "--force"))
+            || (program == "git" && (segment.contains("reset --hard") || args.first() == Some(&"clean")))
+            || (matches!(program, "chmod" | "chown") && has_flag(options, 'R', "--recursive"))
+        {
+            impact.destructive = true;
+        }
+
+        if WRITE_PROGRAMS.contains(&program) {
+            // mv/cp: everything but the source is written; others write all args
+            let written: Vec<&str> = if matches!(program, "cp") {
+                args.last().copied().into_iter().collect()
+            } else if matches!(program, "chmod" | "chown") {
+                args.iter().skip(1).copied().collect()
+            } else {
+                args.clone()
+            };
+            impact.writes.extend(written.into_iter().map(|a| a.to_string()));
+            if program == "cp" && args.len() > 1 {
+                impact.reads.extend(args[..args.len() - 1].iter().map(|a| a.to_string()));
+            }
+        } else if READ_PROGRAMS.contains(&program) {
+            let skip = if program == "grep" { 1 } else { 0 };
+            impact.reads.extend(args.iter().skip(skip).map(|a| a.to_string()));
+        }
+    }
+
+    impact.reads.dedup();
+    impact.writes.dedup();
+    impact
+}
+
+/// Whether `tokens` set the flag `short` (alone or in a cluster like `-fv`)
+/// or spell out `long` exactly; `--preserve-root` sets neither `-r` nor `-f`.
+fn has_flag(tokens: &[&str], short: char, long: &str) -> bool {
+    tokens.iter().any(|t| match t.strip_prefix("--") {
+        Some(_) => *t == long || t.strip_prefix(long).is_some_and(|rest| rest.starts_with('=')),
+        None => t.strip_prefix('-').is_some_and(|cluster| cluster.chars().all(char::is_alphanumeric) && cluster.contains(short)),
+    })
+}
+
 fn split_segments(command: &str) -> Vec<String> {
     let mut segments = Vec::new();
     let mut buffer = String::new();
@@ -65,3 +192,45 @@ fn push_segment(segments: &mut Vec<String>, buffer: &mut String) {
     }
     buffer.clear();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_command() {
+        let impact = assess_impact("cat README.md | grep TODO notes.txt");
+        assert!(impact.is_read_only());
+        assert!(impact.reads.contains(&"README.md".to_string()));
+        assert!(impact.reads.contains(&"notes.txt".to_string()));
+    }
+
+    #[test]
+    fn test_destructive_command() {
+        let impact = assess_impact("rm -rf build && curl https://example.com > out.html");
+        assert!(impact.destructive);
+        assert!(impact.network);
+        assert!(impact.writes.contains(&"build".to_string()));
+        assert!(impact.writes.contains(&"out.html".to_string()));
+        assert!(!impact.is_read_only());
+    }
+
+    #[test]
+    fn test_flags_and_sudo_are_read_precisely() {
+        // Short flags in a cluster, long flags only when spelled exactly
+        assert!(assess_impact("mv -fv a.txt b.txt").destructive);
+        assert!(assess_impact("mv --force a.txt b.txt").destructive);
+        assert!(!assess_impact("mv --no-clobber --verbose a.txt b.txt").destructive);
+        assert!(!assess_impact("mv -v report-final.txt archive/").destructive);
+        assert!(assess_impact("chmod -R 755 dist").destructive);
+        assert!(!assess_impact("chmod -v 644 notes.txt").destructive);
+        assert!(!assess_impact("chown --from=root user notes.txt").destructive);
+        assert!(assess_impact("chown --recursive user dist").destructive);
+
+        // sudo alone doesn't make a command destructive, but it is never read-only
+        let read = assess_impact("sudo -u admin cat /etc/hosts");
+        assert!(!read.destructive && read.elevated && !read.is_read_only());
+        assert!(read.reads.contains(&"/etc/hosts".to_string()));
+        assert!(assess_impact("sudo rm -rf /tmp/build").destructive);
+    }
+}
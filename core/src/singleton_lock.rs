@@ -33,34 +33,78 @@ pub fn acquire_lock() -> Result<Option<LockGuard>, String> {
     }
 
     let stale_secs = env_i64("STEER_LOCK_STALE_SECS").unwrap_or(900);
+    acquire_at(&lock_path, stale_secs).map(Some)
+}
 
-    match fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
-        Ok(mut file) => {
-            let payload = LockPayload {
-                pid: std::process::id(),
-                created_at: chrono::Utc::now().to_rfc3339(),
-            };
-            let json = serde_json::to_string(&payload).unwrap_or_else(|_| "{}".to_string());
-            if let Err(err) = file.write_all(json.as_bytes()) {
-                return Err(format!("Failed to write lock file: {}", err));
+/// Take the lock at `lock_path`, reclaiming it once if its owner is gone or
+/// it is older than `stale_secs`. A lock that is back after the reclaim (or
+/// that couldn't be removed) is an error rather than another attempt.
+fn acquire_at(lock_path: &Path, stale_secs: i64) -> Result<LockGuard, String> {
+    const MAX_ATTEMPTS: u32 = 2;
+    for attempt in 1..=MAX_ATTEMPTS {
+        match fs::OpenOptions::new().write(true).create_new(true).open(lock_path) {
+            Ok(mut file) => {
+                let payload = LockPayload {
+                    pid: std::process::id(),
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                };
+                let json = serde_json::to_string(&payload).unwrap_or_else(|_| "{}".to_string());
+                if let Err(err) = file.write_all(json.as_bytes()) {
+                    return Err(format!("Failed to write lock file: {}", err));
+                }
+                return Ok(LockGuard { path: lock_path.to_path_buf() });
             }
-            Ok(Some(LockGuard { path: lock_path }))
-        }
-        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
-            if let Some(payload) = read_payload(&lock_path) {
-                if is_stale(&payload, stale_secs) {
-                    let _ = fs::remove_file(&lock_path);
-                    return acquire_lock();
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                let Some(payload) = read_payload(lock_path) else {
+                    return Err(format!(
+                        "Another instance is already running (lock exists at {}). Use --force-unlock if it is not.",
+                        lock_path.display()
+                    ));
+                };
+                let owner_alive = pid_alive(payload.pid);
+                if owner_alive && !is_stale(&payload, stale_secs) {
+                    return Err(format!(
+                        "Another instance is already running (pid {}, since {}).",
+                        payload.pid, payload.created_at
+                    ));
+                }
+                if attempt == MAX_ATTEMPTS {
+                    return Err(format!(
+                        "Stale lock at {} came back after reclaiming it (pid {}). Use --force-unlock.",
+                        lock_path.display(),
+                        payload.pid
+                    ));
+                }
+                if !owner_alive {
+                    println!("♻️  Reclaimed stale lock (pid {} is no longer running).", payload.pid);
+                } else {
+                    println!("♻️  Reclaimed stale lock (pid {}, older than {}s).", payload.pid, stale_secs);
+                }
+                if let Err(err) = fs::remove_file(lock_path) {
+                    return Err(format!("Failed to remove stale lock {}: {}", lock_path.display(), err));
                 }
-                return Err(format!(
-                    "Another instance is already running (pid {}, since {}).",
-                    payload.pid, payload.created_at
-                ));
             }
-            Err("Another instance is already running (lock exists).".to_string())
+            Err(err) => return Err(format!("Failed to acquire lock: {}", err)),
         }
-        Err(err) => Err(format!("Failed to acquire lock: {}", err)),
     }
+    unreachable!("every attempt returns or retries")
+}
+
+/// Remove the lock file regardless of owner. Returns true if one existed.
+pub fn force_unlock() -> bool {
+    force_unlock_at(&resolve_lock_path())
+}
+
+fn force_unlock_at(lock_path: &Path) -> bool {
+    fs::remove_file(lock_path).is_ok()
+}
+
+fn pid_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    let mut sys = sysinfo::System::new();
+    sys.refresh_process(sysinfo::Pid::from_u32(pid))
 }
 
 fn read_payload(path: &Path) -> Option<LockPayload> {
@@ -80,8 +124,12 @@ fn is_stale(payload: &LockPayload, stale_secs: i64) -> bool {
 }
 
 fn resolve_lock_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    Path::new(&home).join(".steer").join("steer.lock")
+    if let Ok(path) = std::env::var("STEER_LOCK_PATH") {
+        if !path.trim().is_empty() {
+            return PathBuf::from(path);
+        }
+    }
+    crate::paths::steer_home().join("steer.lock")
 }
 
 fn env_flag(key: &str) -> bool {
@@ -94,3 +142,44 @@ fn env_flag(key: &str) -> bool {
 fn env_i64(key: &str) -> Option<i64> {
     std::env::var(key).ok().and_then(|v| v.parse::<i64>().ok())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_lock() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("steer-lock-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("steer.lock")
+    }
+
+    fn write_payload(path: &Path, pid: u32, created_at: chrono::DateTime<chrono::Utc>) {
+        let payload = LockPayload { pid, created_at: created_at.to_rfc3339() };
+        fs::write(path, serde_json::to_string(&payload).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_is_reclaimed_and_live_lock_is_refused() {
+        let path = temp_lock();
+        // Our own pid counts as alive; an old timestamp makes it stale anyway
+        write_payload(&path, std::process::id(), chrono::Utc::now() - chrono::Duration::hours(2));
+        let guard = acquire_at(&path, 900).expect("stale lock reclaimed");
+        assert_eq!(read_payload(&path).unwrap().pid, std::process::id());
+
+        let err = acquire_at(&path, 900).unwrap_err();
+        assert!(err.contains("already running"), "{}", err);
+        drop(guard);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_unreadable_lock_needs_force_unlock() {
+        let path = temp_lock();
+        fs::write(&path, "garbage").unwrap();
+        assert!(acquire_at(&path, 900).unwrap_err().contains("--force-unlock"));
+
+        assert!(force_unlock_at(&path));
+        assert!(!force_unlock_at(&path));
+        assert!(acquire_at(&path, 900).is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+// "Plan only, then confirm" support: the executor parks a step here and waits
+// for the GUI/API to allow or deny it before touching the screen.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingConfirmation {
+    pub id: String,
+    pub goal: String,
+    pub step_index: usize,
+    pub action_type: String,
+    pub description: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmDecision {
+    Allow,
+    Deny,
+    TimedOut,
+}
+
+struct Entry {
+    info: PendingConfirmation,
+    reply: oneshot::Sender<bool>,
+}
+
+lazy_static! {
+    static ref PENDING: Mutex<HashMap<String, Entry>> = Mutex::new(HashMap::new());
+}
+
+/// Publish a step for approval and wait for a decision (or the timeout).
+pub async fn request(
+    goal: &str,
+    step_index: usize,
+    action_type: &str,
+    description: &str,
+    timeout: Duration,
+) -> ConfirmDecision {
+    let id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    let info = PendingConfirmation {
+        id: id.clone(),
+        goal: goal.to_string(),
+        step_index,
+        action_type: action_type.to_string(),
+        description: description.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.insert(id.clone(), Entry { info, reply: tx });
+    }
+
+    let _ = crate::notifier::send(
+        "Steer: confirm next step",
+        &format!("The agent wants to: {} ({}). Allow?", description, action_type),
+    );
+    println!("🙋 [Confirm] Step {} awaiting approval (id: {})", step_index + 1, id);
+
+    let decision = match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(true)) => ConfirmDecision::Allow,
+        Ok(Ok(false)) | Ok(Err(_)) => ConfirmDecision::Deny,
+        Err(_) => ConfirmDecision::TimedOut,
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.remove(&id);
+    }
+    decision
+}
+
+/// Resolve a pending step. Returns false if it no longer exists.
+pub fn resolve(id: &str, allow: bool) -> bool {
+    let entry = PENDING.lock().ok().and_then(|mut p| p.remove(id));
+    match entry {
+        Some(entry) => entry.reply.send(allow).is_ok(),
+        None => false,
+    }
+}
+
+pub fn list_pending() -> Vec<PendingConfirmation> {
+    PENDING
+        .lock()
+        .map(|p| p.values().map(|e| e.info.clone()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answer the pending step for `goal` as soon as it shows up.
+    fn answer(goal: String, allow: bool) -> tokio::task::JoinHandle<bool> {
+        tokio::spawn(async move {
+            for _ in 0..200 {
+                if let Some(pending) = list_pending().into_iter().find(|p| p.goal == goal) {
+                    return resolve(&pending.id, allow);
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            false
+        })
+    }
+
+    #[tokio::test]
+    async fn test_request_waits_for_the_decision() {
+        let goal = format!("confirm {}", uuid::Uuid::new_v4());
+        let answered = answer(goal.clone(), true);
+        assert_eq!(request(&goal, 0, "CLICK", "Click Send", Duration::from_secs(5)).await, ConfirmDecision::Allow);
+        assert!(answered.await.unwrap());
+
+        let answered = answer(goal.clone(), false);
+        assert_eq!(request(&goal, 1, "TYPE", "Type the reply", Duration::from_secs(5)).await, ConfirmDecision::Deny);
+        assert!(answered.await.unwrap());
+
+        // Nobody answers: the step times out and is no longer pending
+        assert_eq!(request(&goal, 2, "CLICK", "Click Send", Duration::from_millis(50)).await, ConfirmDecision::TimedOut);
+        assert!(!list_pending().iter().any(|p| p.goal == goal));
+        assert!(!resolve("no-such-step", true));
+    }
+}
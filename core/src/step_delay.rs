@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+// [Step Delay] The pauses between acting and looking (before a vision check,
+// before confirming typed text, before a click-recovery snapshot) used to be
+// fixed at 300ms-1s: too long on a fast machine, too short on a loaded one.
+// They now share one delay learned from how long `wait_for_ui_settle`
+// actually took recently (a moving average, clamped to min/max). A wait that
+// times out counts as its full length, so a slow machine backs off.
+//
+//   STEER_STEP_DELAY_MIN_MS    shortest delay (default 100)
+//   STEER_STEP_DELAY_MAX_MS    longest delay (default 2000)
+
+/// Delay before any settle has been observed (the old typical pause).
+const INITIAL_MS: f64 = 500.0;
+/// Weight of the newest observation in the moving average.
+const ALPHA: f64 = 0.3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveDelay {
+    average_ms: f64,
+    samples: u64,
+    min: Duration,
+    max: Duration,
+}
+
+impl AdaptiveDelay {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self { average_ms: INITIAL_MS, samples: 0, min, max: max.max(min) }
+    }
+
+    pub fn from_env() -> Self {
+        let ms = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(default)
+        };
+        Self::new(
+            Duration::from_millis(ms("STEER_STEP_DELAY_MIN_MS", 100)),
+            Duration::from_millis(ms("STEER_STEP_DELAY_MAX_MS", 2000)),
+        )
+    }
+
+    /// Fold in one observed settle time.
+    pub fn observe(&mut self, settle: Duration) {
+        let ms = settle.as_secs_f64() * 1000.0;
+        self.average_ms = if self.samples == 0 { ms } else { ALPHA * ms + (1.0 - ALPHA) * self.average_ms };
+        self.samples += 1;
+    }
+
+    /// The delay to use now.
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.average_ms.round() as u64).clamp(self.min, self.max)
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref DELAY: Mutex<AdaptiveDelay> = Mutex::new(AdaptiveDelay::from_env());
+}
+
+fn with_delay<T>(f: impl FnOnce(&mut AdaptiveDelay) -> T) -> T {
+    let mut guard = DELAY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Record how long the screen took to settle after an action.
+pub fn record_settle(settle: Duration) {
+    with_delay(|d| d.observe(settle));
+}
+
+pub fn current() -> Duration {
+    with_delay(|d| d.delay())
+}
+
+/// Wait the learned delay (async callers).
+pub async fn pause() {
+    tokio::time::sleep(current()).await;
+}
+
+/// Prometheus text for `/metrics`: the learned delay and how many settles it is based on.
+pub fn render_metrics() -> String {
+    let (delay, samples) = with_delay(|d| (d.delay(), d.samples()));
+    format!(
+        "# TYPE steer_step_delay_ms gauge\n\
+steer_step_delay_ms {}\n\
+# TYPE steer_ui_settle_samples_total counter\n\
+steer_ui_settle_samples_total {}\n",
+        delay.as_millis(),
+        samples
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_converges_toward_observed_settle_times() {
+        let ms = Duration::from_millis;
+        let mut delay = AdaptiveDelay::new(ms(100), ms(2000));
+        assert_eq!(delay.delay(), ms(500));
+
+        // A fast machine: settles in ~180ms
+        for _ in 0..20 {
+            delay.observe(ms(180));
+        }
+        assert!(delay.delay().abs_diff(ms(180)) <= ms(2), "{:?}", delay.delay());
+
+        // It slows down: the delay follows, a bit behind
+        delay.observe(ms(900));
+        let after_one = delay.delay();
+        assert!(after_one > ms(180) && after_one < ms(900), "{:?}", after_one);
+        for _ in 0..20 {
+            delay.observe(ms(900));
+        }
+        assert!(delay.delay().abs_diff(ms(900)) <= ms(2), "{:?}", delay.delay());
+        assert_eq!(delay.samples(), 41);
+
+        // Clamped at both ends
+        for _ in 0..20 {
+            delay.observe(ms(5));
+        }
+        assert_eq!(delay.delay(), ms(100));
+        for _ in 0..20 {
+            delay.observe(ms(10_000));
+        }
+        assert_eq!(delay.delay(), ms(2000));
+    }
+}
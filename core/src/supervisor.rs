@@ -0,0 +1,187 @@
+use crate::schema::AgentAction;
+use crate::shell_analysis;
+use crate::step_confirmation::ConfirmDecision;
+use anyhow::Result;
+use std::future::Future;
+
+// [Supervisor] Optional second opinion before a risky step runs. The policy
+// engine only knows action categories; the supervisor sees the goal and the
+// concrete step and answers accept / review / escalate. `review` sends the
+// executor back to planning with the supervisor's guidance, `escalate` asks
+// the user through the step confirmation channel. Off unless STEER_SUPERVISOR=1.
+
+/// Click targets that usually destroy something.
+const DESTRUCTIVE_WORDS: &[&str] = &[
+    "delete", "remove", "erase", "trash", "discard", "empty", "format", "uninstall", "wipe", "reset",
+    "삭제", "지우기", "비우기", "초기화",
+];
+
+/// Shortcuts (as typed by TYPE/keyboard steps) that delete or quit without asking.
+const DESTRUCTIVE_SHORTCUTS: &[&str] = &[
+    "cmd+delete", "cmd+backspace", "command+delete", "shift+delete", "cmd+shift+delete", "cmd+option+esc", "cmd+q",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Accept,
+    /// Guidance for the replan
+    Review(String),
+    /// Question for the user
+    Escalate(String),
+}
+
+/// What the executor does with the step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gate {
+    Proceed,
+    /// Replan with this guidance instead of running the step
+    Revise(String),
+    /// The user refused (or didn't answer) an escalation
+    Stop(String),
+}
+
+pub fn enabled() -> bool {
+    std::env::var("STEER_SUPERVISOR")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Shell commands that change things, clicks on delete-like targets and destructive shortcuts.
+pub fn is_risky(action: &AgentAction) -> bool {
+    match action {
+        AgentAction::ShellExecution { command } => shell_analysis::assess_impact(command).destructive,
+        AgentAction::Terminate => true,
+        AgentAction::UiClickText { text } | AgentAction::UiClick { element_id: text, .. } => {
+            let text = text.to_lowercase();
+            DESTRUCTIVE_WORDS.iter().any(|w| text.contains(w))
+        }
+        AgentAction::UiType { text } | AgentAction::KeyboardType { text, .. } => {
+            let text = text.to_lowercase().replace(' ', "");
+            DESTRUCTIVE_SHORTCUTS.iter().any(|s| text == *s)
+        }
+        _ => false,
+    }
+}
+
+/// Verdict from the supervisor's JSON reply. Anything unreadable escalates.
+pub fn parse_verdict(reply: &str) -> Verdict {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply,
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Verdict::Escalate(format!("Supervisor reply was not JSON: {}", reply.trim()));
+    };
+    let reason = value["reason"].as_str().unwrap_or("").trim().to_string();
+    match value["verdict"].as_str().map(|v| v.trim().to_lowercase()).as_deref() {
+        Some("accept") => Verdict::Accept,
+        Some("review") if !reason.is_empty() => Verdict::Review(reason),
+        Some("review") => Verdict::Escalate("Supervisor asked for a review without saying why".to_string()),
+        Some("escalate") => Verdict::Escalate(if reason.is_empty() { "Supervisor escalated this step".to_string() } else { reason }),
+        _ => Verdict::Escalate(format!("Unknown supervisor verdict: {}", value["verdict"])),
+    }
+}
+
+/// The goal and step as shown to the supervisor.
+pub fn proposal(goal: &str, action_type: &str, description: &str, target: Option<&str>, value: Option<&str>) -> String {
+    format!(
+        "Goal: {}\nNext step: {} ({})\nTarget: {}\nValue: {}",
+        goal,
+        description,
+        action_type,
+        target.unwrap_or("-"),
+        value.unwrap_or("-")
+    )
+}
+
+/// Turn the supervisor's verdict into what the executor does. A failed review
+/// escalates, and so does `review` when no replan is left (`can_revise`).
+pub async fn gate<R, RF, C, CF>(review: R, confirm: C, can_revise: bool) -> Gate
+where
+    R: FnOnce() -> RF,
+    RF: Future<Output = Result<Verdict>>,
+    C: FnOnce(String) -> CF,
+    CF: Future<Output = ConfirmDecision>,
+{
+    let verdict = review()
+        .await
+        .unwrap_or_else(|e| Verdict::Escalate(format!("Supervisor unavailable: {}", e)));
+    let question = match verdict {
+        Verdict::Accept => return Gate::Proceed,
+        Verdict::Review(guidance) if can_revise => return Gate::Revise(guidance),
+        Verdict::Review(guidance) => guidance,
+        Verdict::Escalate(reason) => reason,
+    };
+    match confirm(question.clone()).await {
+        ConfirmDecision::Allow => Gate::Proceed,
+        ConfirmDecision::Deny => Gate::Stop(question),
+        ConfirmDecision::TimedOut => Gate::Stop(format!("{} (no answer)", question)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(verdict: Result<Verdict>, answer: ConfirmDecision, can_revise: bool) -> (Gate, Option<String>) {
+        let asked = std::sync::Mutex::new(None);
+        let gate = gate(
+            || async { verdict },
+            |question| {
+                *asked.lock().unwrap() = Some(question);
+                async { answer }
+            },
+            can_revise,
+        )
+        .await;
+        let asked = asked.into_inner().unwrap();
+        (gate, asked)
+    }
+
+    #[tokio::test]
+    async fn test_each_verdict_drives_the_step() {
+        // accept runs the step without asking anyone
+        assert_eq!(run(Ok(Verdict::Accept), ConfirmDecision::Deny, true).await, (Gate::Proceed, None));
+
+        // review becomes replan guidance, or a question once replans are used up
+        let review = || Ok(Verdict::Review("Archive instead of deleting".to_string()));
+        assert_eq!(run(review(), ConfirmDecision::Deny, true).await, (Gate::Revise("Archive instead of deleting".to_string()), None));
+        let (gate, asked) = run(review(), ConfirmDecision::Allow, false).await;
+        assert_eq!((gate, asked.as_deref()), (Gate::Proceed, Some("Archive instead of deleting")));
+
+        // escalate asks the user, who decides
+        let escalate = || Ok(Verdict::Escalate("Deletes 40 files".to_string()));
+        assert_eq!(run(escalate(), ConfirmDecision::Allow, true).await.0, Gate::Proceed);
+        assert_eq!(run(escalate(), ConfirmDecision::Deny, true).await.0, Gate::Stop("Deletes 40 files".to_string()));
+        assert_eq!(run(escalate(), ConfirmDecision::TimedOut, true).await.0, Gate::Stop("Deletes 40 files (no answer)".to_string()));
+
+        // a supervisor that can't answer doesn't wave the step through
+        let (gate, asked) = run(Err(anyhow::anyhow!("budget exhausted")), ConfirmDecision::Deny, true).await;
+        assert!(matches!(gate, Gate::Stop(_)));
+        assert!(asked.unwrap().contains("budget exhausted"));
+    }
+
+    #[test]
+    fn test_verdicts_are_parsed_from_the_reply() {
+        assert_eq!(parse_verdict("```json\n{\"verdict\": \"accept\", \"reason\": \"\"}\n```"), Verdict::Accept);
+        assert_eq!(
+            parse_verdict("{\"verdict\": \"Review\", \"reason\": \"Use the Archive button\"}"),
+            Verdict::Review("Use the Archive button".to_string())
+        );
+        assert!(matches!(parse_verdict("{\"verdict\": \"review\"}"), Verdict::Escalate(_)));
+        assert!(matches!(parse_verdict("looks fine to me"), Verdict::Escalate(_)));
+        assert!(matches!(parse_verdict("{\"verdict\": \"maybe\"}"), Verdict::Escalate(_)));
+    }
+
+    #[test]
+    fn test_only_destructive_steps_are_risky() {
+        assert!(is_risky(&AgentAction::UiClickText { text: "Delete Message".to_string() }));
+        assert!(is_risky(&AgentAction::UiClickText { text: "휴지통 비우기".to_string() }));
+        assert!(is_risky(&AgentAction::ShellExecution { command: "rm -rf build".to_string() }));
+        assert!(is_risky(&AgentAction::UiType { text: "Cmd + Delete".to_string() }));
+        assert!(!is_risky(&AgentAction::UiClickText { text: "Send".to_string() }));
+        assert!(!is_risky(&AgentAction::UiType { text: "Please delete my old account".to_string() }));
+        assert!(!is_risky(&AgentAction::ShellExecution { command: "ls -la".to_string() }));
+        assert!(!is_risky(&AgentAction::SystemOpen { app: "Mail".to_string() }));
+    }
+}
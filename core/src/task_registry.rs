@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+// [Task Registry] Goals started from the API and routines started by the
+// scheduler run in background tasks that were invisible once launched. Each
+// one now registers here for as long as it runs, so `GET /api/tasks` can list
+// them and `POST /api/tasks/:id/cancel` can stop one. Cancelling drops the
+// run's future at its next await point, takes its queued UI work off the
+// command queue and records the cancellation in the run's session.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Goal,
+    Routine,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: TaskKind,
+    pub goal: String,
+    pub started_at: String,
+}
+
+struct Entry {
+    info: TaskInfo,
+    cancel: watch::Sender<bool>,
+}
+
+lazy_static::lazy_static! {
+    static ref TASKS: Mutex<HashMap<String, Entry>> = Mutex::new(HashMap::new());
+}
+
+/// A registered task; listed until it is run to the end or dropped.
+pub struct TaskHandle {
+    id: String,
+    cancelled: watch::Receiver<bool>,
+    /// Executor session to close out on cancel (`executor::record_cancelled`)
+    session_key: Option<String>,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Record a cancellation in executor session `session_key`.
+    pub fn with_session(mut self, session_key: &str) -> Self {
+        self.session_key = Some(session_key.to_string());
+        self
+    }
+
+    /// Drive `work` to completion, or `None` if the task is cancelled first.
+    pub async fn run<F: Future>(mut self, work: F) -> Option<F::Output> {
+        let mut work = Box::pin(work);
+        tokio::select! {
+            out = &mut work => return Some(out),
+            _ = self.cancelled.wait_for(|c| *c) => {}
+        }
+        // Dropping the run abandons whatever it had queued; clear it out now
+        drop(work);
+        let dropped = crate::command_queue::drop_abandoned();
+        if dropped > 0 {
+            println!("🛑 Dropped {} queued UI task(s) of cancelled task {}", dropped, self.id);
+        }
+        if let Some(key) = &self.session_key {
+            crate::executor::record_cancelled(key);
+        }
+        None
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        TASKS.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+    }
+}
+
+pub fn register(kind: TaskKind, goal: &str) -> TaskHandle {
+    let id = uuid::Uuid::new_v4().to_string();
+    let (cancel, cancelled) = watch::channel(false);
+    let info = TaskInfo {
+        id: id.clone(),
+        kind,
+        goal: goal.to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    TASKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.clone(), Entry { info, cancel });
+    TaskHandle { id, cancelled, session_key: None }
+}
+
+/// Running tasks, oldest first.
+pub fn list() -> Vec<TaskInfo> {
+    let tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut out: Vec<TaskInfo> = tasks.values().map(|e| e.info.clone()).collect();
+    out.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    out
+}
+
+/// Ask task `id` to stop. False if no such task is running.
+pub fn cancel(id: &str) -> bool {
+    let tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    match tasks.get(id) {
+        Some(entry) => {
+            println!("🛑 Cancelling {:?} task {}: {}", entry.info.kind, id, entry.info.goal);
+            entry.cancel.send_replace(true);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tasks_are_listed_until_done_and_can_be_cancelled() {
+        let quick = register(TaskKind::Routine, "send report");
+        let quick_id = quick.id().to_string();
+        assert!(list().iter().any(|t| t.id == quick_id && t.kind == TaskKind::Routine));
+        assert_eq!(quick.run(async { 7 }).await, Some(7));
+        assert!(!list().iter().any(|t| t.id == quick_id));
+
+        let slow = register(TaskKind::Goal, "watch the screen forever");
+        let slow_id = slow.id().to_string();
+        let running = tokio::spawn(slow.run(std::future::pending::<()>()));
+        tokio::task::yield_now().await;
+        assert!(cancel(&slow_id));
+        assert_eq!(running.await.unwrap(), None);
+        assert!(!list().iter().any(|t| t.id == slow_id));
+        assert!(!cancel(&slow_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cancel_drops_queued_work_and_records_the_session() {
+        crate::db::init().ok();
+        let lane = format!("cancel-test-{}", uuid::Uuid::new_v4());
+        let session = format!("cancel-test-{}", uuid::Uuid::new_v4());
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let task = register(TaskKind::Goal, "click until cancelled").with_session(&session);
+        let id = task.id().to_string();
+        let run_lane = lane.clone();
+        let running = tokio::spawn(task.run(async move {
+            let busy = crate::command_queue::enqueue_command_in_lane(
+                &run_lane,
+                Box::new(move || {
+                    released.recv_timeout(std::time::Duration::from_secs(5)).ok();
+                    Ok(String::new())
+                }),
+                None,
+            );
+            let queued = crate::command_queue::enqueue_command_in_lane(&run_lane, Box::new(|| Ok(String::new())), None);
+            tokio::join!(busy, queued)
+        }));
+        while crate::command_queue::get_lane_size(&lane).await < 2 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        assert!(cancel(&id));
+        assert!(running.await.unwrap().is_none());
+        // Only the task already running is left; the queued one is gone
+        assert_eq!(crate::command_queue::get_lane_size(&lane).await, 1);
+        release.send(()).ok();
+
+        let steps = crate::db::list_goal_steps(&session, 10).unwrap();
+        let last = steps.last().expect("cancel recorded");
+        assert_eq!((last.outcome.as_str(), last.step_index), ("cancelled", 0));
+    }
+}
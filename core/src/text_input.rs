@@ -0,0 +1,386 @@
+use anyhow::Result;
+use crate::applescript;
+
+// [Typing] AppleScript `keystroke` types one character at a time: slow for long
+// text and some apps drop characters. The injection method can be set per app
+// (or per step), and long text is pasted by default.
+//
+//   STEER_TYPE_METHOD=keystroke                  default for every app
+//   STEER_TYPE_METHOD_APPS="Slack=paste,Notes=ax_set_value"
+//   STEER_TYPE_PASTE_THRESHOLD=200               auto-paste above this length
+//
+// `keystroke` mangles emoji and many CJK characters (Hangul comes out as
+// jamo or `?`), so text that isn't plain ASCII is always pasted instead,
+// whatever method was asked for. AX set-value handles it fine and is kept.
+//
+// Typing can fail silently (focus lost, keystrokes dropped), leaving a blank
+// document. In text editors the start of the text is looked for afterwards;
+// if it's missing the app is refocused and the text typed once more.
+//
+//   STEER_VERIFY_TYPING=editors|on|off           default editors
+//   STEER_VERIFY_TYPING_APPS="TextEdit,Notes"    what counts as an editor
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectionMethod {
+    Keystroke,
+    /// Put the text on the clipboard, Cmd+V, then restore the clipboard.
+    Paste,
+    /// Set AXValue of the focused element directly (replaces its content).
+    AxSetValue,
+}
+
+impl InjectionMethod {
+    pub const NAMES: &'static [&'static str] = &["keystroke", "paste", "ax_set_value"];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "keystroke" | "type" => Some(Self::Keystroke),
+            "paste" | "clipboard" => Some(Self::Paste),
+            "ax_set_value" | "ax" | "set_value" => Some(Self::AxSetValue),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keystroke => "keystroke",
+            Self::Paste => "paste",
+            Self::AxSetValue => "ax_set_value",
+        }
+    }
+}
+
+struct TypingConfig {
+    default: Option<InjectionMethod>,
+    per_app: Vec<(String, InjectionMethod)>,
+    paste_threshold: usize,
+}
+
+impl TypingConfig {
+    fn from_env() -> Self {
+        let per_app = std::env::var("STEER_TYPE_METHOD_APPS")
+            .map(|v| parse_app_methods(&v))
+            .unwrap_or_default();
+        Self {
+            default: std::env::var("STEER_TYPE_METHOD").ok().and_then(|v| InjectionMethod::parse(&v)),
+            per_app,
+            paste_threshold: std::env::var("STEER_TYPE_PASTE_THRESHOLD")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(200),
+        }
+    }
+
+    /// Step override > app mapping > configured default > length-based choice,
+    /// with keystroke swapped for paste when it can't type `text` intact.
+    fn resolve(&self, app: Option<&str>, text: &str, requested: Option<InjectionMethod>) -> InjectionMethod {
+        match self.choose(app, text, requested) {
+            InjectionMethod::Keystroke if !keystroke_safe(text) => InjectionMethod::Paste,
+            method => method,
+        }
+    }
+
+    fn choose(&self, app: Option<&str>, text: &str, requested: Option<InjectionMethod>) -> InjectionMethod {
+        if let Some(method) = requested {
+            return method;
+        }
+        if let Some(app) = app {
+            if let Some((_, method)) = self.per_app.iter().find(|(name, _)| name.eq_ignore_ascii_case(app.trim())) {
+                return *method;
+            }
+        }
+        if let Some(method) = self.default {
+            return method;
+        }
+        if text.chars().count() > self.paste_threshold {
+            InjectionMethod::Paste
+        } else {
+            InjectionMethod::Keystroke
+        }
+    }
+}
+
+/// Whether `keystroke` can type `text` without corrupting it (plain ASCII only).
+pub fn keystroke_safe(text: &str) -> bool {
+    text.is_ascii()
+}
+
+/// "Slack=paste, Notes=ax_set_value" → [(Slack, Paste), (Notes, AxSetValue)]; bad entries are skipped.
+fn parse_app_methods(raw: &str) -> Vec<(String, InjectionMethod)> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (app, method) = entry.split_once('=')?;
+            let app = app.trim();
+            if app.is_empty() {
+                return None;
+            }
+            Some((app.to_string(), InjectionMethod::parse(method)?))
+        })
+        .collect()
+}
+
+/// Method that would be used to type `text` into `app`.
+pub fn resolve_method(app: Option<&str>, text: &str, requested: Option<InjectionMethod>) -> InjectionMethod {
+    TypingConfig::from_env().resolve(app, text, requested)
+}
+
+/// Type `text` into the frontmost app (blocking; run via spawn_blocking).
+pub fn type_text(text: &str, requested: Option<InjectionMethod>) -> Result<InjectionMethod> {
+    let app = if requested.is_none() { applescript::frontmost_app_name().ok() } else { None };
+    let method = resolve_method(app.as_deref(), text, requested);
+    if method == InjectionMethod::Paste && !keystroke_safe(text) {
+        println!("      ⌨️ Pasting text with non-ASCII characters (keystroke would mangle them)");
+    }
+    deliver(
+        text,
+        method,
+        |text| {
+            let script = format!("tell application \"System Events\" to keystroke {:?}", text);
+            applescript::run_named("type_keystroke", &script).map(|_| ())
+        },
+        applescript::paste_text,
+        applescript::set_focused_value,
+    )?;
+    Ok(method)
+}
+
+/// Send `text` with `method` through the matching injector.
+fn deliver(
+    text: &str,
+    method: InjectionMethod,
+    keystroke: impl FnOnce(&str) -> Result<()>,
+    paste: impl FnOnce(&str) -> Result<()>,
+    set_value: impl FnOnce(&str) -> Result<()>,
+) -> Result<()> {
+    match method {
+        InjectionMethod::Keystroke => keystroke(text),
+        InjectionMethod::Paste => paste(text),
+        InjectionMethod::AxSetValue => set_value(text),
+    }
+}
+
+const EDITOR_APPS: &[&str] = &[
+    "TextEdit", "Notes", "Pages", "Microsoft Word", "Visual Studio Code", "Code",
+    "Sublime Text", "BBEdit", "Obsidian", "Bear", "Xcode",
+];
+
+/// Whether typed text should be checked for in `app` (`STEER_VERIFY_TYPING`).
+pub fn should_verify(app: Option<&str>) -> bool {
+    match std::env::var("STEER_VERIFY_TYPING").unwrap_or_default().trim().to_lowercase().as_str() {
+        "on" | "1" | "true" | "all" => true,
+        "off" | "0" | "false" => false,
+        _ => {
+            let Some(app) = app else { return false };
+            let custom = std::env::var("STEER_VERIFY_TYPING_APPS").ok();
+            match custom.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                Some(list) => list.split(',').any(|a| a.trim().eq_ignore_ascii_case(app.trim())),
+                None => EDITOR_APPS.iter().any(|a| a.eq_ignore_ascii_case(app.trim())),
+            }
+        }
+    }
+}
+
+/// The part of `text` looked for on screen: its first line, at most 24 chars.
+/// `None` when there's nothing meaningful to look for.
+pub fn verification_probe(text: &str) -> Option<String> {
+    let first_line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let probe: String = first_line.chars().take(24).collect();
+    (probe.chars().count() >= 3).then(|| probe.trim_end().to_string())
+}
+
+/// Type via `type_once`, then confirm with `visible`. On a miss, `refocus`
+/// and look at the focused field (`field`, `None` when unreadable): text that
+/// landed there counts; a field unchanged since before typing gets the text
+/// once more. Anything else fails the step rather than risk typing it twice.
+pub async fn type_and_verify<T, TFut, V, VFut, R, RFut, F, FFut>(
+    probe: &str,
+    mut type_once: T,
+    mut visible: V,
+    mut refocus: R,
+    mut field: F,
+) -> Result<()>
+where
+    T: FnMut() -> TFut,
+    TFut: std::future::Future<Output = Result<()>>,
+    V: FnMut() -> VFut,
+    VFut: std::future::Future<Output = bool>,
+    R: FnMut() -> RFut,
+    RFut: std::future::Future<Output = Result<()>>,
+    F: FnMut() -> FFut,
+    FFut: std::future::Future<Output = Option<String>>,
+{
+    let before = field().await;
+    type_once().await?;
+    if visible().await {
+        return Ok(());
+    }
+    refocus().await?;
+    let after = field().await;
+    if after.as_deref().is_some_and(|value| value.contains(probe)) {
+        return Ok(());
+    }
+    if after.is_none() || after != before {
+        return Err(anyhow::anyhow!(
+            "Typed text did not appear on screen: '{}' (not typed again: the field changed or can't be read)",
+            probe
+        ));
+    }
+    println!("      ⚠️ Typed text '{}' not found and the field is unchanged; typing again", probe);
+    type_once().await?;
+    if visible().await {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!("Typed text did not appear on screen: '{}'", probe))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_method_precedence() {
+        let config = TypingConfig {
+            default: None,
+            per_app: parse_app_methods("Slack=paste, Notes=ax, Bogus=teleport, =paste"),
+            paste_threshold: 10,
+        };
+        assert_eq!(config.per_app.len(), 2);
+
+        assert_eq!(config.resolve(Some("Safari"), "short", None), InjectionMethod::Keystroke);
+        assert_eq!(config.resolve(Some("Safari"), "a much longer text", None), InjectionMethod::Paste);
+        assert_eq!(config.resolve(Some("slack"), "short", None), InjectionMethod::Paste);
+        assert_eq!(config.resolve(Some("Notes"), "short", None), InjectionMethod::AxSetValue);
+        assert_eq!(
+            config.resolve(Some("Slack"), "short", Some(InjectionMethod::Keystroke)),
+            InjectionMethod::Keystroke
+        );
+
+        let forced = TypingConfig { default: Some(InjectionMethod::Keystroke), ..config };
+        assert_eq!(forced.resolve(None, "a much longer text", None), InjectionMethod::Keystroke);
+    }
+
+    #[test]
+    fn test_unicode_text_is_pasted_intact() {
+        use std::cell::RefCell;
+        let config = TypingConfig {
+            default: Some(InjectionMethod::Keystroke),
+            per_app: parse_app_methods("Notes=ax"),
+            paste_threshold: 200,
+        };
+        let text = "회의 메모 🎉 — café";
+        assert!(!keystroke_safe(text));
+        assert!(keystroke_safe("Meeting notes: 10:00 @room-3"));
+
+        // Keystroke, even when asked for, gives way to paste; AX set-value is kept
+        assert_eq!(config.resolve(Some("Safari"), "plain text", None), InjectionMethod::Keystroke);
+        assert_eq!(config.resolve(Some("Safari"), text, None), InjectionMethod::Paste);
+        assert_eq!(config.resolve(None, text, Some(InjectionMethod::Keystroke)), InjectionMethod::Paste);
+        assert_eq!(config.resolve(Some("Notes"), text, None), InjectionMethod::AxSetValue);
+
+        // Through the paste path the target receives every character and the clipboard is restored
+        let board = RefCell::new("previous clipboard".to_string());
+        let received = RefCell::new(String::new());
+        deliver(
+            text,
+            config.resolve(Some("Safari"), text, None),
+            |_| panic!("keystroke must not be used"),
+            |t| {
+                crate::clipboard::paste_with(
+                    t,
+                    || Ok(board.borrow().clone()),
+                    |t| { *board.borrow_mut() = t.to_string(); Ok(()) },
+                    || { *received.borrow_mut() = board.borrow().clone(); Ok(()) },
+                    || {},
+                )
+            },
+            |_| panic!("set-value must not be used"),
+        )
+        .unwrap();
+        assert_eq!(*received.borrow(), text);
+        assert_eq!(*board.borrow(), "previous clipboard");
+    }
+
+    #[test]
+    fn test_verification_probe() {
+        assert_eq!(verification_probe("\n  Meeting notes for Friday's planning session\nsecond line").as_deref(), Some("Meeting notes for Friday"));
+        assert_eq!(verification_probe("ok"), None);
+        assert_eq!(verification_probe("   "), None);
+    }
+
+    #[tokio::test]
+    async fn test_missing_text_refocuses_then_fails() {
+        use std::cell::Cell;
+        let typed = Cell::new(0);
+        let refocused = Cell::new(0);
+
+        // The first attempt went nowhere (field unchanged); text shows up after the second
+        let result = type_and_verify(
+            "Hello",
+            || { typed.set(typed.get() + 1); async { Ok(()) } },
+            || { let seen = typed.get() >= 2; async move { seen } },
+            || { refocused.set(refocused.get() + 1); async { Ok(()) } },
+            || async { Some("Dear team,".to_string()) },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!((typed.get(), refocused.get()), (2, 1));
+
+        // Text never appears: the step fails instead of carrying on with a blank document
+        typed.set(0);
+        let err = type_and_verify(
+            "Hello",
+            || { typed.set(typed.get() + 1); async { Ok(()) } },
+            || async { false },
+            || async { Ok(()) },
+            || async { Some(String::new()) },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("did not appear"));
+        assert_eq!(typed.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_text_is_not_typed_twice_into_a_changed_field() {
+        use std::cell::{Cell, RefCell};
+        let typed = Cell::new(0);
+        let value = RefCell::new("Dear team,".to_string());
+        let type_once = || {
+            typed.set(typed.get() + 1);
+            value.borrow_mut().push_str(" Hel");
+            async { Ok(()) }
+        };
+
+        // Something landed in the field, just not the whole probe: no second copy
+        let err = type_and_verify("Hello", type_once, || async { false }, || async { Ok(()) }, || async { Some(value.borrow().clone()) })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not typed again"), "{}", err);
+        assert_eq!(typed.get(), 1);
+
+        // The text is in the field even though the screen check missed it
+        let result = type_and_verify(
+            "Hello",
+            || async { Ok(()) },
+            || async { false },
+            || async { Ok(()) },
+            || async { Some("Dear team, Hello".to_string()) },
+        )
+        .await;
+        assert!(result.is_ok());
+
+        // An unreadable field is never typed into twice
+        typed.set(0);
+        let err = type_and_verify(
+            "Hello",
+            || { typed.set(typed.get() + 1); async { Ok(()) } },
+            || async { false },
+            || async { Ok(()) },
+            || async { None },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not typed again"));
+        assert_eq!(typed.get(), 1);
+    }
+}
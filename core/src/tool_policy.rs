@@ -38,7 +38,7 @@ pub fn is_action_allowed(action: &AgentAction) -> bool {
     policy.is_allowed(tool_name)
 }
 
-fn action_kind(action: &AgentAction) -> &'static str {
+pub fn action_kind(action: &AgentAction) -> &'static str {
     match action {
         AgentAction::UiSnapshot { .. } => "ui.snapshot",
         AgentAction::UiFind { .. } => "ui.find",
@@ -47,6 +47,8 @@ fn action_kind(action: &AgentAction) -> &'static str {
         AgentAction::UiType { .. } => "ui.type",
         AgentAction::KeyboardType { .. } => "keyboard.type",
         AgentAction::SystemOpen { .. } => "system.open",
+        AgentAction::OpenUrl { .. } => "system.open_url",
+        AgentAction::OpenFile { .. } => "system.open_file",
         AgentAction::SystemSearch { .. } => "system.search",
         AgentAction::Terminate => "system.terminate",
         AgentAction::DebugFakeLog => "debug.fake_log",
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+// [UI Snapshot Export] `snap` only prints the accessibility tree. For building
+// and debugging selectors offline, `snap_export <path> [scope]` and
+// `GET /api/ui/snapshot` hand out the same tree wrapped with when and where
+// it was taken:
+//
+//   {"captured_at": "...", "platform": "macos", "scope": null, "snapshot": {...}}
+
+/// The focused window's accessibility tree (macOS AX API, Linux AT-SPI2).
+pub async fn capture(scope: Option<String>) -> Value {
+    #[cfg(target_os = "macos")]
+    {
+        tokio::task::spawn_blocking(move || crate::macos::accessibility::snapshot(scope))
+            .await
+            .unwrap_or_else(|e| json!({ "error": format!("Snapshot task failed: {}", e) }))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux::accessibility::snapshot(scope).await
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = scope;
+        json!({ "error": "Snapshots are supported only on macOS and Linux" })
+    }
+}
+
+/// `tree` wrapped with capture time, platform and scope.
+pub fn document(tree: Value, scope: Option<&str>) -> Value {
+    json!({
+        "captured_at": chrono::Utc::now().to_rfc3339(),
+        "platform": std::env::consts::OS,
+        "scope": scope,
+        "snapshot": tree,
+    })
+}
+
+/// Write `document` as pretty JSON to `path`, creating parent folders.
+/// Returns the path written.
+pub fn write(document: &Value, path: &str) -> Result<PathBuf> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("No export path given");
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let target = crate::paths::expand_home(trimmed, home.as_deref());
+    if let Some(dir) = target.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let body = serde_json::to_string_pretty(document)?;
+    std::fs::write(&target, body).with_context(|| format!("Failed to write {}", target.display()))?;
+    Ok(target)
+}
+
+/// Capture a snapshot and save it to `path`.
+pub async fn export(path: &str, scope: Option<String>) -> Result<(PathBuf, Value)> {
+    let doc = document(capture(scope.clone()).await, scope.as_deref());
+    let target = write(&doc, path)?;
+    Ok((target, doc))
+}
+
+/// Error reported by the platform snapshot, if it couldn't read the window.
+pub fn snapshot_error(document: &Value) -> Option<&str> {
+    document.get("snapshot").and_then(|s| s.get("error")).and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_writes_tree_with_metadata() {
+        let dir = std::env::temp_dir().join(format!("steer-snap-{}", uuid::Uuid::new_v4()));
+        let tree = json!({ "role": "AXApplication", "title": "Notes", "focused_window": { "role": "AXWindow", "children": [] } });
+        let doc = document(tree.clone(), Some("Notes"));
+        let target = write(&doc, dir.join("nested/snap.json").to_str().unwrap()).unwrap();
+
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&target).unwrap()).unwrap();
+        assert_eq!(saved["snapshot"], tree);
+        assert_eq!(saved["scope"], "Notes");
+        assert_eq!(saved["platform"], std::env::consts::OS);
+        assert_eq!(snapshot_error(&saved), None);
+        assert_eq!(snapshot_error(&document(json!({ "error": "No focused window" }), None)), Some("No focused window"));
+
+        assert_eq!(crate::paths::expand_home("~/snaps/a.json", Some(std::path::Path::new("/home/me"))), PathBuf::from("/home/me/snaps/a.json"));
+        assert_eq!(crate::paths::expand_home("~other/a.json", Some(std::path::Path::new("/home/me"))), PathBuf::from("~other/a.json"));
+        assert!(write(&doc, "  ").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
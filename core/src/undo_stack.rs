@@ -0,0 +1,365 @@
+use anyhow::Result;
+use std::sync::Mutex;
+
+use crate::applescript::FrontApp;
+use crate::executor::PlanStep;
+
+// [Undo] In interactive sessions the agent sometimes goes one step too far:
+// types into the wrong field, brings up the wrong app. Goal steps whose
+// effect can be taken back are recorded here with their inverse, and `undo`
+// (REPL) / `POST /api/agent/undo` reverts the most recent one:
+//
+//   TYPE                    → focus the app it typed into, delete that many characters
+//   ACTIVATE, FOCUS_WINDOW  → hide the app
+//
+// Everything else (CLICK, URL, OPEN_FILE, shell commands, sending mail) is not
+// undoable. So is typing that contains a newline (it may have submitted a
+// form) or that replaced the field's value (ax_set_value). Such a step, a
+// failed step or the start of the next goal after one ended clears the stack:
+// undoing past them would act on a screen they changed. Read-only steps
+// (EXTRACT, REPLY, ...) leave it alone. Apps are kept by bundle id, since app
+// and process names differ ("Visual Studio Code" runs as "Code"), and typing
+// is only deleted while the field it went into still has focus.
+// None of the steps write the clipboard, so there's no clipboard inverse.
+
+/// Entries kept; older ones drop off.
+const MAX_ENTRIES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inverse {
+    /// `field` is the focused element the text went into (see
+    /// `applescript::focused_element`)
+    DeleteTyped { app: FrontApp, field: String, chars: usize },
+    HideApp(FrontApp),
+}
+
+impl Inverse {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::DeleteTyped { app, chars, .. } => format!("delete {} typed character(s) in {}", chars, app.name),
+            Self::HideApp(app) => format!("hide {}", app.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoEntry {
+    /// The step's description
+    pub step: String,
+    pub inverse: Inverse,
+}
+
+/// How an inverse touches the desktop; tests supply their own.
+pub trait UndoTarget {
+    fn activate(&self, bundle_id: &str) -> Result<()>;
+    fn focused_element(&self) -> Result<String>;
+    fn delete_backward(&self, chars: usize) -> Result<()>;
+    fn hide_app(&self, bundle_id: &str) -> Result<()>;
+}
+
+struct Desktop;
+
+impl UndoTarget for Desktop {
+    fn activate(&self, bundle_id: &str) -> Result<()> {
+        crate::applescript::activate_bundle(bundle_id)
+    }
+
+    fn focused_element(&self) -> Result<String> {
+        crate::applescript::focused_element()
+    }
+
+    fn delete_backward(&self, chars: usize) -> Result<()> {
+        crate::applescript::delete_backward(chars)
+    }
+
+    fn hide_app(&self, bundle_id: &str) -> Result<()> {
+        crate::applescript::hide_app(bundle_id)
+    }
+}
+
+/// What was in front right after a step: the app, and for typing the
+/// focused element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Front {
+    pub app: Option<FrontApp>,
+    pub field: Option<String>,
+}
+
+/// Look up what `inverse_for` needs after a step of `action_type` (blocking).
+pub fn observe_front(action_type: &str) -> Front {
+    if !matches!(action_type, "TYPE" | "ACTIVATE" | "FOCUS_WINDOW") {
+        return Front::default();
+    }
+    let app = crate::applescript::frontmost_app().ok();
+    let field = (action_type == "TYPE").then(|| crate::applescript::focused_element().ok()).flatten();
+    Front { app, field }
+}
+
+/// The inverse of a step that just succeeded; `None` when it can't be undone,
+/// including when the app in front (or the focused field) is unknown.
+pub fn inverse_for(step: &PlanStep, front: &Front) -> Option<Inverse> {
+    let value = step.value.as_deref().unwrap_or_default();
+    let app = front.app.clone()?;
+    match step.action_type.as_str() {
+        "TYPE" => {
+            let method = step.target.as_deref().and_then(crate::text_input::InjectionMethod::parse);
+            let replaced = method == Some(crate::text_input::InjectionMethod::AxSetValue);
+            if value.is_empty() || value.contains(['\n', '\r']) || replaced {
+                return None;
+            }
+            Some(Inverse::DeleteTyped { app, field: front.field.clone()?, chars: value.chars().count() })
+        }
+        "ACTIVATE" | "FOCUS_WINDOW" => {
+            let requested = value.trim();
+            (!requested.is_empty() && !requested.eq_ignore_ascii_case("frontmost")).then_some(Inverse::HideApp(app))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    entries: Vec<UndoEntry>,
+    /// A goal ended; the next goal's first step starts a fresh stack
+    sealed: bool,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Record the outcome of a step: its inverse when it has one, otherwise
+    /// a barrier unless the step only read. Returns whether it was recorded.
+    pub fn record(&mut self, step: &PlanStep, front: &Front) -> bool {
+        if std::mem::take(&mut self.sealed) {
+            self.entries.clear();
+        }
+        match inverse_for(step, front) {
+            Some(inverse) => {
+                self.push(UndoEntry { step: step.description.clone(), inverse });
+                true
+            }
+            None => {
+                if !crate::action_schema::only_reads(&step.action_type) {
+                    self.entries.clear();
+                }
+                false
+            }
+        }
+    }
+
+    /// Nothing done so far can be undone any more.
+    pub fn barrier(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The current goal is over. Its steps stay undoable until the next goal
+    /// records one.
+    pub fn end_goal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Revert the most recent entry through `target`. `Ok(None)` when there is
+    /// nothing to undo; on failure the entry stays for another try.
+    pub fn undo_last(&mut self, target: &dyn UndoTarget) -> Result<Option<UndoEntry>> {
+        let Some(entry) = self.entries.pop() else {
+            return Ok(None);
+        };
+        let applied = match &entry.inverse {
+            Inverse::DeleteTyped { app, field, chars } => target.activate(&app.bundle_id).and_then(|_| {
+                let focused = target.focused_element()?;
+                if focused != *field {
+                    return Err(anyhow::anyhow!(
+                        "Focus moved off the field \"{}\" was typed into; click back into it and undo again",
+                        entry.step
+                    ));
+                }
+                target.delete_backward(*chars)
+            }),
+            Inverse::HideApp(app) => target.hide_app(&app.bundle_id),
+        };
+        match applied {
+            Ok(()) => Ok(Some(entry)),
+            Err(e) => {
+                self.entries.push(entry);
+                Err(e)
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref STACK: Mutex<UndoStack> = Mutex::new(UndoStack::default());
+}
+
+fn with_stack<T>(f: impl FnOnce(&mut UndoStack) -> T) -> T {
+    let mut guard = STACK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Record a step that just succeeded (see `UndoStack::record`).
+pub fn record(step: &PlanStep, front: &Front) -> bool {
+    with_stack(|s| s.record(step, front))
+}
+
+/// A step failed part way; what came before can't be undone safely.
+pub fn barrier() {
+    with_stack(UndoStack::barrier)
+}
+
+pub fn end_goal() {
+    with_stack(UndoStack::end_goal)
+}
+
+/// Revert the most recent undoable step on the desktop (blocking; run via
+/// `command_queue::enqueue_ui`).
+pub fn undo_last() -> Result<Option<UndoEntry>> {
+    crate::safe_mode::check("UI actions are")?;
+    with_stack(|s| s.undo_last(&Desktop))
+}
+
+pub fn pending() -> usize {
+    with_stack(|s| s.entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// A text field and the set of visible apps, by bundle id.
+    #[derive(Default)]
+    struct FakeDesktop {
+        field: RefCell<String>,
+        focused: RefCell<String>,
+        front: RefCell<Option<String>>,
+        hidden: RefCell<Vec<String>>,
+    }
+
+    impl UndoTarget for FakeDesktop {
+        fn activate(&self, bundle_id: &str) -> Result<()> {
+            *self.front.borrow_mut() = Some(bundle_id.to_string());
+            Ok(())
+        }
+
+        fn focused_element(&self) -> Result<String> {
+            Ok(self.focused.borrow().clone())
+        }
+
+        fn delete_backward(&self, chars: usize) -> Result<()> {
+            let mut field = self.field.borrow_mut();
+            let keep = field.chars().count().saturating_sub(chars);
+            *field = field.chars().take(keep).collect();
+            Ok(())
+        }
+
+        fn hide_app(&self, bundle_id: &str) -> Result<()> {
+            self.hidden.borrow_mut().push(bundle_id.to_string());
+            Ok(())
+        }
+    }
+
+    fn step(action_type: &str, target: Option<&str>, value: &str) -> PlanStep {
+        PlanStep {
+            description: format!("{} {}", action_type.to_lowercase(), value),
+            action_type: action_type.to_string(),
+            target: target.map(str::to_string),
+            value: Some(value.to_string()),
+            verification: String::new(),
+            pre_check: None,
+        }
+    }
+
+    fn front(name: &str, bundle_id: &str, field: Option<&str>) -> Front {
+        Front {
+            app: Some(FrontApp { name: name.to_string(), bundle_id: bundle_id.to_string() }),
+            field: field.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_typing_then_undo_clears_the_typed_text() {
+        let desktop = FakeDesktop::default();
+        *desktop.field.borrow_mut() = "Dear team, ".to_string();
+        *desktop.focused.borrow_mut() = "com.apple.Notes|AXTextArea|body".to_string();
+        let mut stack = UndoStack::default();
+
+        let typed = step("TYPE", None, "héllo wörld");
+        desktop.field.borrow_mut().push_str("héllo wörld");
+        let notes = front("Notes", "com.apple.Notes", Some("com.apple.Notes|AXTextArea|body"));
+        assert!(stack.record(&typed, &notes));
+        assert_eq!(stack.entries[0].inverse.describe(), "delete 11 typed character(s) in Notes");
+
+        let undone = stack.undo_last(&desktop).unwrap().unwrap();
+        assert_eq!(undone.step, "type héllo wörld");
+        assert_eq!(*desktop.field.borrow(), "Dear team, ");
+        assert_eq!(desktop.front.borrow().as_deref(), Some("com.apple.Notes"));
+        assert!(stack.undo_last(&desktop).unwrap().is_none());
+
+        // Bringing an app up is undone by hiding it, by bundle id rather than
+        // the name the plan used
+        let activate = step("ACTIVATE", None, "Visual Studio Code");
+        assert!(stack.record(&activate, &front("Code", "com.microsoft.VSCode", None)));
+        stack.undo_last(&desktop).unwrap();
+        assert_eq!(*desktop.hidden.borrow(), vec!["com.microsoft.VSCode".to_string()]);
+    }
+
+    #[test]
+    fn test_typing_is_not_deleted_once_focus_moved() {
+        let desktop = FakeDesktop::default();
+        *desktop.field.borrow_mut() = "secret".to_string();
+        *desktop.focused.borrow_mut() = "com.apple.mail|AXTextField|To".to_string();
+        let mut stack = UndoStack::default();
+
+        let typed = step("TYPE", None, "cret");
+        assert!(stack.record(&typed, &front("Mail", "com.apple.mail", Some("com.apple.mail|AXTextField|Subject"))));
+        let err = stack.undo_last(&desktop).unwrap_err();
+        assert!(err.to_string().contains("Focus moved"), "{}", err);
+        assert_eq!(*desktop.field.borrow(), "secret");
+        // Still there for another try once the field has focus again
+        assert_eq!(stack.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_irreversible_steps_and_new_goals_clear_the_stack() {
+        let notes = front("Notes", "com.apple.Notes", Some("field"));
+        let mut stack = UndoStack::default();
+        assert!(stack.record(&step("TYPE", None, "draft"), &notes));
+        // Reading leaves the stack alone; a click may have sent the draft
+        assert!(!stack.record(&step("EXTRACT", Some("subject:string"), ""), &notes));
+        assert_eq!(stack.entries.len(), 1);
+        assert!(!stack.record(&step("CLICK", Some("Send"), ""), &notes));
+        assert!(stack.entries.is_empty());
+
+        assert!(stack.record(&step("TYPE", None, "draft"), &notes));
+        stack.barrier();
+        assert!(stack.entries.is_empty());
+
+        // The last goal stays undoable until the next one does something
+        assert!(stack.record(&step("TYPE", None, "first goal"), &notes));
+        stack.end_goal();
+        assert_eq!(stack.entries.len(), 1);
+        assert!(stack.record(&step("TYPE", None, "second goal"), &notes));
+        assert_eq!(stack.entries.len(), 1);
+        assert_eq!(stack.entries[0].step, "type second goal");
+    }
+
+    #[test]
+    fn test_irreversible_steps_are_not_recorded() {
+        let mail = front("Mail", "com.apple.mail", Some("field"));
+        assert_eq!(inverse_for(&step("CLICK", Some("Send"), ""), &mail), None);
+        assert_eq!(inverse_for(&step("URL", None, "https://example.com"), &mail), None);
+        assert_eq!(inverse_for(&step("OPEN_FILE", Some("~/a.pdf"), ""), &mail), None);
+        // Enter may have submitted; a set value replaced whatever was there
+        assert_eq!(inverse_for(&step("TYPE", None, "rust\n"), &mail), None);
+        assert_eq!(inverse_for(&step("TYPE", Some("ax_set_value"), "rust"), &mail), None);
+        assert_eq!(inverse_for(&step("ACTIVATE", None, "frontmost"), &mail), None);
+        // Without the app or the focused field there is nothing safe to revert
+        assert_eq!(inverse_for(&step("TYPE", None, "rust"), &Front::default()), None);
+        assert_eq!(inverse_for(&step("TYPE", None, "rust"), &front("Mail", "com.apple.mail", None)), None);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::executor;
 use crate::applescript;
+use crate::click_recovery;
+use crate::command_queue;
+use crate::screen_cache::{self, ScreenCache};
 use std::thread;
 use std::time::Duration;
 use anyhow::{Context, Result};
@@ -11,13 +14,23 @@ use base64::{Engine as _, engine::general_purpose};
 pub enum UiAction {
     OpenUrl(String),
     Wait(u64), // Seconds
+    WaitFor { text: String, timeout_secs: u64 }, // Poll until text is visible
     Click(String), // Element description or AppleScript target
-    Type(String),
+    Type(String), // Injection method picked per app / text length (see text_input)
+    TypeWith { text: String, method: crate::text_input::InjectionMethod },
     Scroll(String), // "down" | "up"
     ActivateApp(String), // "frontmost" or app name
+    FocusWindow { app: String, title_contains: String }, // Raise a specific window of a multi-window app
     // Verify(String), // Removed: Legacy standalone verify unused
 }
 
+impl UiAction {
+    /// Anything that changes the screen (everything except waiting).
+    pub fn is_write(&self) -> bool {
+        !matches!(self, UiAction::Wait(_) | UiAction::WaitFor { .. })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SmartStep {
     pub action: UiAction,
@@ -51,15 +64,54 @@ impl SmartStep {
 
 pub struct VisualDriver {
     steps: Vec<SmartStep>,
+    read_only: bool,
+    /// Clicks that only succeeded after re-finding their target (see click_recovery).
+    recoveries: std::sync::Mutex<Vec<String>>,
+}
+
+/// Run `probe` every `every` until it says yes or `timeout` elapses; the
+/// probe always runs at least once. Returns whether it said yes.
+async fn poll_until<F, Fut>(timeout: Duration, every: Duration, mut probe: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if probe().await {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(every).await;
+    }
 }
 
 impl VisualDriver {
     pub fn new() -> Self {
-        Self { steps: Vec::new() }
+        Self { steps: Vec::new(), read_only: false, recoveries: std::sync::Mutex::new(Vec::new()) }
+    }
+
+    /// Notes on clicks that needed recovery during the last `execute`.
+    pub fn recoveries(&self) -> Vec<String> {
+        self.recoveries.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Observe mode: write actions are skipped at dispatch instead of executed.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
     }
 
     /// Capture the entire primary screen and return Base64 encoded JPEG
+    /// (redacted first when STEER_VISION_REDACT_* is configured).
     pub fn capture_screen() -> Result<String> {
+        crate::screen_access::require()?;
+        let redaction = crate::screen_redaction::Redaction::from_env();
+        if redaction.is_active() {
+            return crate::screen_redaction::capture(&redaction);
+        }
         let uuid = uuid::Uuid::new_v4();
         let output_path = format!("/tmp/steer_vision_{}.jpg", uuid);
         
@@ -91,51 +143,209 @@ impl VisualDriver {
         self
     }
 
-    // Helper for legacy support
-    pub fn add_legacy_step(&mut self, action: UiAction) -> &mut Self {
-        self.steps.push(SmartStep::new(action, "Legacy Step"));
-        self
+    /// Screenshot for a vision check: the cached one when the screen hash is
+    /// unchanged since the last check, otherwise a fresh capture. The hash is
+    /// `None` when fingerprinting is off or unavailable.
+    async fn screenshot(cache: &mut ScreenCache) -> Result<(String, Option<u64>)> {
+        let fingerprint = if screen_cache::enabled() {
+            tokio::task::spawn_blocking(screen_cache::fingerprint).await.ok().and_then(|r| r.ok())
+        } else {
+            None
+        };
+        if let Some(image) = fingerprint.and_then(|fp| cache.frame(fp)) {
+            println!("      ♻️ Screen unchanged, reusing previous capture");
+            return Ok((image, fingerprint));
+        }
+        let image = tokio::task::spawn_blocking(Self::capture_screen).await??;
+        if let Some(fp) = fingerprint {
+            cache.store_frame(fp, image.clone());
+        }
+        Ok((image, fingerprint))
     }
 
-    async fn verify_condition(llm: &crate::llm_gateway::LLMClient, prompt: &str) -> Result<bool> {
+    /// Ask vision whether `question` holds, reusing the previous answer when
+    /// the same question was already asked about this exact screen.
+    async fn ask_yes_no(llm: &crate::llm_gateway::LLMClient, cache: &mut ScreenCache, question: &str) -> Result<bool> {
+        let (b64, fingerprint) = Self::screenshot(cache).await?;
+        if let Some(answer) = fingerprint.and_then(|fp| cache.answer(fp, question)) {
+            println!("      ♻️ Same screen, skipping vision call");
+            return Ok(answer);
+        }
+        let mut first_image = Some(b64);
+        let answer = crate::llm_gateway::answer_reliably(2, |_| Self::vision_reading(llm, question, first_image.take())).await?;
+        cache.store_answer(question, answer);
+        Ok(answer)
+    }
+
+    /// Structured vision reading of `prompt`, retried on a fresh capture when
+    /// the model is unsure or can't find what was asked about. `first_image`
+    /// is used for the first attempt when the caller already has a capture.
+    pub async fn read_screen(
+        llm: &crate::llm_gateway::LLMClient,
+        prompt: &str,
+        first_image: Option<String>,
+    ) -> Result<crate::llm_gateway::ScreenReading> {
+        let mut first_image = first_image;
+        crate::llm_gateway::read_reliably(2, |_| Self::vision_reading(llm, prompt, first_image.take())).await
+    }
+
+    /// One structured reading of `prompt`, on `image` or a fresh capture.
+    async fn vision_reading(
+        llm: &crate::llm_gateway::LLMClient,
+        prompt: &str,
+        image: Option<String>,
+    ) -> Result<crate::llm_gateway::ScreenReading> {
+        let image = match image {
+            Some(image) => image,
+            None => tokio::task::spawn_blocking(Self::capture_screen).await??,
+        };
+        llm.analyze_screen_structured(prompt, &image)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn verify_condition(llm: &crate::llm_gateway::LLMClient, cache: &mut ScreenCache, prompt: &str) -> Result<bool> {
         println!("      👁️ Vision Check: '{}'", prompt);
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await; // Brief pause before capture
-        
-        match Self::capture_screen() {
-            Ok(b64) => {
-                let full_prompt = format!(
-                    "Screen Verification Task.\nCondition to verify: '{}'.\nReply ONLY with 'YES' or 'NO'.",
-                    prompt
-                );
-                match llm.analyze_screen(&full_prompt, &b64).await {
-                    Ok(resp) => {
-                        let success = resp.trim().to_uppercase().starts_with("YES");
-                        println!("      🤖 Result: {}", if success { "PASS" } else { "FAIL" });
-                        Ok(success)
-                    },
-                    Err(e) => {
-                        println!("      ⚠️ Vision API Error: {}", e);
-                        Ok(false) // Conservative failure
-                    }
-                }
-            },
+        crate::step_delay::pause().await; // Brief pause before capture
+
+        let full_prompt = format!(
+            "Screen Verification Task.\nCondition to verify: '{}'.\nReply ONLY with 'YES' or 'NO'.",
+            prompt
+        );
+        match Self::ask_yes_no(llm, cache, &full_prompt).await {
+            Ok(success) => {
+                println!("      🤖 Result: {}", if success { "PASS" } else { "FAIL" });
+                Ok(success)
+            }
             Err(e) => {
-                println!("      ⚠️ Capture Failed: {}", e);
-                Ok(false)
+                println!("      ⚠️ Vision Check Failed: {}", e);
+                Ok(false) // Conservative failure
+            }
+        }
+    }
+
+    /// Is `text` on screen? Checks the accessibility tree first (cheap), then asks vision.
+    async fn text_visible(llm: Option<&crate::llm_gateway::LLMClient>, cache: &mut ScreenCache, text: &str) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            let needle = text.to_lowercase();
+            let found = tokio::task::spawn_blocking(move || {
+                crate::macos::accessibility::snapshot(None)
+                    .to_string()
+                    .to_lowercase()
+                    .contains(&needle)
+            })
+            .await
+            .unwrap_or(false);
+            if found {
+                return true;
+            }
+        }
+
+        let Some(brain) = llm.filter(|_| crate::screen_access::available()) else { return false };
+        let prompt = format!(
+            "Is the text '{}' currently visible on screen? Reply ONLY with 'YES' or 'NO'.",
+            text
+        );
+        Self::ask_yes_no(brain, cache, &prompt).await.unwrap_or(false)
+    }
+
+    /// Type `text`, and in editors (see `text_input::should_verify`) confirm it
+    /// appeared, refocusing the app and retyping once if the field shows it
+    /// never got there.
+    async fn type_checked(
+        llm: Option<&crate::llm_gateway::LLMClient>,
+        text: &str,
+        method: Option<crate::text_input::InjectionMethod>,
+    ) -> Result<()> {
+        let app = tokio::task::spawn_blocking(applescript::frontmost_app_name).await.ok().and_then(|r| r.ok());
+        let probe = crate::text_input::verification_probe(text)
+            .filter(|_| crate::text_input::should_verify(app.as_deref()));
+        let Some(probe) = probe else {
+            return Self::type_text(text.to_string(), method).await;
+        };
+        crate::text_input::type_and_verify(
+            &probe,
+            || Self::type_text(text.to_string(), method),
+            || async {
+                crate::step_delay::pause().await;
+                Self::text_visible(llm, &mut ScreenCache::default(), &probe).await
+            },
+            || async {
+                command_queue::enqueue_ui(applescript::activate_frontmost_app).await?;
+                Ok(())
+            },
+            || async { tokio::task::spawn_blocking(applescript::focused_value).await.ok().and_then(|r| r.ok()) },
+        )
+        .await
+    }
+
+    /// Type `text` on the UI queue with `method`, or the one configured for the frontmost app.
+    async fn type_text(text: String, method: Option<crate::text_input::InjectionMethod>) -> Result<()> {
+        // Pasting takes roughly constant time; keystrokes scale with length
+        let secs = 5 + text.chars().count() as u64 / 20;
+
+        // [Survival] Run blocking script with timeout
+        let limit = std::time::Duration::from_secs(secs);
+        let task = command_queue::enqueue_ui_timeout(limit, move || crate::text_input::type_text(&text, method));
+
+        match task.await {
+            Ok(Ok(used)) => {
+                println!("      ⌨️  Typed via {}", used.as_str());
+                Ok(())
             }
+            Ok(Err(e)) => Err(anyhow::anyhow!("Type Failed: {}", e)),
+            Err(_) => Err(anyhow::anyhow!("Type Timed Out")),
         }
     }
 
+    /// Poll until `text` appears or `timeout_secs` elapses. Returns whether it appeared.
+    pub async fn wait_for_text(llm: Option<&crate::llm_gateway::LLMClient>, text: &str, timeout_secs: u64) -> bool {
+        // Vision polls cost an LLM call each, so poll less often when that's the only signal
+        let poll = if cfg!(target_os = "macos") { 1000 } else { 2000 };
+        // Nothing is dispatched while polling, so an unchanged screen keeps its answer
+        let cache = tokio::sync::Mutex::new(ScreenCache::default());
+        let cache = &cache;
+        poll_until(
+            Duration::from_secs(timeout_secs),
+            Duration::from_millis(poll),
+            move || async move { Self::text_visible(llm, &mut *cache.lock().await, text).await },
+        )
+        .await
+    }
+
+    pub fn steps(&self) -> &[SmartStep] {
+        &self.steps
+    }
+
     pub async fn execute(&self, llm: Option<&crate::llm_gateway::LLMClient>) -> Result<()> {
+        self.execute_from(llm, 0, |_, _| {}).await
+    }
+
+    /// Run the steps from index `start` on, calling `on_done` after each one
+    /// completes (including its post-check) so callers can record progress.
+    pub async fn execute_from(
+        &self,
+        llm: Option<&crate::llm_gateway::LLMClient>,
+        start: usize,
+        mut on_done: impl FnMut(usize, &SmartStep),
+    ) -> Result<()> {
         println!("👻 [Smart Visual Driver] Starting Verified Automation...");
-        
-        for (i, step) in self.steps.iter().enumerate() {
+        let mut cache = ScreenCache::default();
+
+        for (i, step) in self.steps.iter().enumerate().skip(start) {
             println!("   Step {}: {}", i + 1, step.description);
             
+            // Without screen recording, vision checks can't run; the action itself still can
+            let can_see = crate::screen_access::available();
+            if !can_see && (step.pre_verify.is_some() || step.post_verify.is_some()) {
+                println!("      🙈 No screen recording permission: skipping vision checks for this step");
+            }
+
             // 1. Pre-Verification
-            if let Some(pre_prompt) = &step.pre_verify {
+            if let Some(pre_prompt) = step.pre_verify.as_ref().filter(|_| can_see) {
                 if let Some(brain) = llm {
-                    if !Self::verify_condition(brain, pre_prompt).await? {
+                    if !Self::verify_condition(brain, &mut cache, pre_prompt).await? {
                          if step.critical {
                              return Err(anyhow::anyhow!("❌ Pre-check failed: {}", pre_prompt));
                          } else {
@@ -145,35 +355,57 @@ impl VisualDriver {
                 }
             }
 
+            if self.read_only && step.action.is_write() {
+                println!("      🔒 [Observe] Skipped write action: {:?}", step.action);
+                continue;
+            }
+            if step.action.is_write() {
+                crate::safe_mode::check("UI actions are")?;
+            }
+
             // 2. Action Execution
             match &step.action {
                 UiAction::OpenUrl(url) => {
-                    executor::open_url(url)?;
+                    let url = url.clone();
+                    command_queue::enqueue_ui(move || executor::open_url(&url)).await?;
                 }
                 UiAction::Wait(secs) => {
                     tokio::time::sleep(tokio::time::Duration::from_secs(*secs)).await;
                 }
+                UiAction::WaitFor { text, timeout_secs } => {
+                    println!("      ⏳ Waiting up to {}s for '{}'", timeout_secs, text);
+                    if Self::wait_for_text(llm, text, *timeout_secs).await {
+                        println!("      ✅ '{}' appeared", text);
+                    } else if step.critical {
+                        return Err(anyhow::anyhow!("Timeout waiting for '{}' after {}s", text, timeout_secs));
+                    } else {
+                        println!("      ⚠️ '{}' did not appear within {}s (non-critical).", text, timeout_secs);
+                    }
+                }
                 UiAction::Click(target) => {
-                    // Use frontmost application instead of hardcoded Safari
+                    // Frontmost app's window; re-finds the target on fresh snapshots if it went stale
                     let target_clone = target.clone();
-                    let script = format!(
-                        "tell application \"System Events\" to click button {:?} of window 1 of (first application process whose frontmost is true)",
-                        target_clone
-                    );
-                    
+                    let attempts = click_recovery::max_attempts();
+
                     // [Survival] Run blocking script with timeout
-                    let task = tokio::task::spawn_blocking(move || {
-                        applescript::run(&script)
+                    let limit = std::time::Duration::from_secs(5 * (1 + attempts as u64));
+                    let task = command_queue::enqueue_ui_timeout(limit, move || {
+                        let allow = |element: &click_recovery::UiElement| click_recovery::allowed(&target_clone, element);
+                        click_recovery::click(&click_recovery::FrontmostWindow, &target_clone, attempts, allow)
                     });
 
-                    match tokio::time::timeout(std::time::Duration::from_secs(5), task).await {
-                        Ok(Ok(Ok(_))) => {}, // Success
-                        Ok(Ok(Err(e))) => {
-                            println!("      (Click failed: {})", e);
-                            if step.critical { return Err(anyhow::anyhow!("Critical Click Failed: {}", e)); }
+                    match task.await {
+                        Ok(Ok(outcome)) => {
+                            if let Some(note) = outcome.note(target) {
+                                println!("      🩹 Click recovered: {}", note);
+                                if let Ok(mut recoveries) = self.recoveries.lock() {
+                                    recoveries.push(note);
+                                }
+                            }
                         }
-                        Ok(Err(_)) => { // JoinError
-                             return Err(anyhow::anyhow!("Task Panic"));
+                        Ok(Err(e)) => {
+                            println!("      (Click failed: {:#})", e);
+                            if step.critical { return Err(anyhow::anyhow!("Critical Click Failed: {:#}", e)); }
                         }
                         Err(_) => { // Timeout
                              println!("      (Click timed out)");
@@ -181,64 +413,73 @@ impl VisualDriver {
                         }
                     }
                 }
-                UiAction::Type(text) => {
-                    let text_clone = text.clone();
-                    let script = format!("tell application \"System Events\" to keystroke {:?}", text_clone);
-                    
-                    // [Survival] Run blocking script with timeout
-                    let task = tokio::task::spawn_blocking(move || {
-                        applescript::run(&script)
-                    });
-                    
-                    match tokio::time::timeout(std::time::Duration::from_secs(5), task).await {
-                        Ok(Ok(Ok(_))) => {},
-                        Ok(Ok(Err(e))) => return Err(anyhow::anyhow!("Type Failed: {}", e)),
-                        Ok(Err(_)) => return Err(anyhow::anyhow!("Task Panic")),
-                        Err(_) => return Err(anyhow::anyhow!("Type Timed Out")),
-                    }
-                }
+                UiAction::Type(text) => Self::type_checked(llm, text, None).await?,
+                UiAction::TypeWith { text, method } => Self::type_checked(llm, text, Some(*method)).await?,
                 UiAction::Scroll(direction) => {
                     let dir = direction.to_lowercase();
                     let key_code = if dir == "up" { 116 } else { 121 }; // page up/down
                     let script = format!("tell application \"System Events\" to key code {}", key_code);
-                    let task = tokio::task::spawn_blocking(move || {
+                    let task = command_queue::enqueue_ui_timeout(std::time::Duration::from_secs(5), move || {
                         applescript::run(&script)
                     });
-                    match tokio::time::timeout(std::time::Duration::from_secs(5), task).await {
-                        Ok(Ok(Ok(_))) => {},
-                        Ok(Ok(Err(e))) => return Err(anyhow::anyhow!("Scroll Failed: {}", e)),
-                        Ok(Err(_)) => return Err(anyhow::anyhow!("Task Panic")),
+                    match task.await {
+                        Ok(Ok(_)) => {},
+                        Ok(Err(e)) => return Err(anyhow::anyhow!("Scroll Failed: {}", e)),
                         Err(_) => return Err(anyhow::anyhow!("Scroll Timed Out")),
                     }
                 }
                 UiAction::ActivateApp(app) => {
-                    let app_name = app.clone();
-                    let task = tokio::task::spawn_blocking(move || {
-                        if app_name.to_lowercase() == "frontmost" {
-                            applescript::activate_frontmost_app()
-                        } else {
-                            applescript::activate_app(&app_name)
-                        }
-                    });
-                    match tokio::time::timeout(std::time::Duration::from_secs(5), task).await {
-                        Ok(Ok(Ok(_))) => {},
-                        Ok(Ok(Err(e))) => return Err(anyhow::anyhow!("Activate Failed: {}", e)),
-                        Ok(Err(_)) => return Err(anyhow::anyhow!("Task Panic")),
+                    let retry = crate::app_focus::FocusRetry::for_app(app);
+                    let task = if app.to_lowercase() == "frontmost" {
+                        command_queue::enqueue_ui_timeout(std::time::Duration::from_secs(5), || {
+                            applescript::activate_frontmost_app().map(|_| 1)
+                        })
+                        .await
+                    } else {
+                        // Each activation takes the UI lane on its own; the settles in between don't
+                        let limit = std::time::Duration::from_secs(5) * (1 + retry.attempts) + retry.worst_case();
+                        tokio::time::timeout(limit, crate::app_focus::ensure_app_focus(app, retry))
+                            .await
+                            .map_err(|_| command_queue::TimedOut)
+                    };
+                    match task {
+                        Ok(Ok(attempts)) if attempts > 1 => println!("   🎯 {} came to the front after {} attempts", app, attempts),
+                        Ok(Ok(_)) => {},
+                        Ok(Err(e)) => return Err(anyhow::anyhow!("Activate Failed: {}", e)),
                         Err(_) => return Err(anyhow::anyhow!("Activate Timed Out")),
                     }
                 }
+                UiAction::FocusWindow { app, title_contains } => {
+                    let (app_name, needle) = (app.clone(), title_contains.clone());
+                    let task = command_queue::enqueue_ui_timeout(std::time::Duration::from_secs(5), move || {
+                        applescript::focus_window(&app_name, &needle)
+                    });
+                    match task.await {
+                        Ok(Ok(true)) => {},
+                        Ok(Ok(false)) => {
+                            return Err(anyhow::anyhow!("Window not found: no {} window titled '{}'", app, title_contains))
+                        }
+                        Ok(Err(e)) => return Err(anyhow::anyhow!("Focus Window Failed: {}", e)),
+                        Err(_) => return Err(anyhow::anyhow!("Focus Window Timed Out")),
+                    }
+                }
+            }
+
+            if step.action.is_write() {
+                cache.invalidate();
             }
 
             // 3. Post-Verification
-            if let Some(post_prompt) = &step.post_verify {
+            if let Some(post_prompt) = step.post_verify.as_ref().filter(|_| can_see) {
                  if let Some(brain) = llm {
                     // Wait a bit for UI to settle
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                    if !Self::verify_condition(brain, post_prompt).await? && step.critical {
+                    crate::step_delay::pause().await;
+                    if !Self::verify_condition(brain, &mut cache, post_prompt).await? && step.critical {
                          return Err(anyhow::anyhow!("❌ Post-check failed: {}", post_prompt));
                     }
                 }
             }
+            on_done(i, step);
         }
         
         println!("👻 [Smart Visual Driver] Automation Complete.");
@@ -249,9 +490,52 @@ impl VisualDriver {
 // Pre-built sequences (Updated)
 pub fn n8n_fallback_create_workflow() -> VisualDriver {
     let mut driver = VisualDriver::new();
-    // Legacy support wrapper
-    driver.add_legacy_step(UiAction::OpenUrl("https://app.n8n.cloud".to_string()))
-          .add_legacy_step(UiAction::Wait(5))
-          .add_legacy_step(UiAction::Click("Create Workflow".to_string()));
+    driver.add_step(SmartStep::new(UiAction::OpenUrl("https://app.n8n.cloud".to_string()), "Open n8n"))
+          .add_step(SmartStep::new(UiAction::Wait(5), "Wait for n8n to load"))
+          .add_step(SmartStep::new(UiAction::Click("Create Workflow".to_string()), "Click Create Workflow"));
     driver
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_polls_until_the_text_shows() {
+        let mut polls = 0;
+        let shown = poll_until(Duration::from_secs(5), Duration::from_millis(1), || {
+            polls += 1;
+            let seen = polls == 3;
+            async move { seen }
+        })
+        .await;
+        assert!(shown);
+        assert_eq!(polls, 3);
+
+        let mut polls = 0;
+        let shown = poll_until(Duration::ZERO, Duration::from_millis(1), || {
+            polls += 1;
+            async { false }
+        })
+        .await;
+        assert!(!shown);
+        assert_eq!(polls, 1);
+        assert!(!UiAction::WaitFor { text: "Results".to_string(), timeout_secs: 1 }.is_write());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_driver_skips_write_actions() {
+        let mut driver = VisualDriver::new().read_only(true);
+        driver
+            .add_step(SmartStep::new(UiAction::OpenUrl("https://example.com".to_string()), "Open the page"))
+            .add_step(SmartStep::new(UiAction::Click("Delete".to_string()), "Click Delete"))
+            .add_step(SmartStep::new(UiAction::Type("secret".to_string()), "Type the note"))
+            .add_step(SmartStep::new(UiAction::Wait(0), "Wait for the page"));
+        let mut done = Vec::new();
+        driver.execute_from(None, 0, |i, _| done.push(i)).await.unwrap();
+        // Only the wait ran; nothing was opened, clicked or typed
+        assert_eq!(done, vec![3]);
+        assert!(UiAction::Click("Delete".to_string()).is_write());
+        assert!(!UiAction::Wait(0).is_write());
+    }
+}
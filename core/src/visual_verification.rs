@@ -35,12 +35,16 @@ pub async fn verify_screen(llm: &LLMClient, req: VisualVerifyRequest) -> Result<
             prompt
         );
         let mut response_text = None;
-        let ok = match llm.analyze_screen(&full_prompt, &b64).await {
-            Ok(resp) => {
-                response_text = Some(resp.clone());
-                resp.trim().to_uppercase().starts_with("YES")
+        // An unsure or not-found reading fails the check instead of counting as an answer
+        let ok = match VisualDriver::read_screen(llm, &full_prompt, Some(b64.clone())).await {
+            Ok(reading) => {
+                response_text = Some(reading.text.clone());
+                reading.text.to_uppercase().starts_with("YES")
+            }
+            Err(e) => {
+                response_text = Some(e.to_string());
+                false
             }
-            Err(_) => false,
         };
         verdicts.push(VisualVerdict {
             prompt,
@@ -0,0 +1,209 @@
+use anyhow::Result;
+
+// [Web Search] "Open the first result for X". The default strategy loads the
+// engine's result page and clicks the first organic result link (found by
+// the engine's result selector), so it works the same on every engine and
+// doesn't depend on redirect tricks. The "lucky" strategy jumps straight to
+// the first result where the engine supports it (Google's btnI, DuckDuckGo's
+// `\` prefix) and is also the fallback when the result page can't be read.
+
+pub struct SearchEngine {
+    pub name: &'static str,
+    /// Result page; `{query}` is replaced by the URL-encoded query.
+    pub search_url: &'static str,
+    /// Page that redirects to the first result, if the engine has one.
+    pub lucky_url: Option<&'static str>,
+    /// CSS selector of organic result links (or elements inside them), in page order.
+    pub result_selector: &'static str,
+}
+
+pub const ENGINES: &[SearchEngine] = &[
+    SearchEngine {
+        name: "google",
+        search_url: "https://www.google.com/search?q={query}",
+        lucky_url: Some("https://www.google.com/search?btnI=1&q={query}"),
+        result_selector: "#search a h3",
+    },
+    SearchEngine {
+        name: "duckduckgo",
+        search_url: "https://duckduckgo.com/?q={query}",
+        lucky_url: Some("https://duckduckgo.com/?q=%5C{query}"),
+        result_selector: "a[data-testid=\"result-title-a\"]",
+    },
+    SearchEngine {
+        name: "bing",
+        search_url: "https://www.bing.com/search?q={query}",
+        lucky_url: None,
+        result_selector: "#b_results li.b_algo h2 a",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirstResult {
+    /// Load the result page and click the first result link
+    Snapshot,
+    /// Use the engine's redirect to the first result
+    Lucky,
+}
+
+/// Engine from STEER_SEARCH_ENGINE (default google; unknown names fall back to google).
+pub fn engine() -> &'static SearchEngine {
+    let name = std::env::var("STEER_SEARCH_ENGINE").unwrap_or_default();
+    engine_named(&name)
+}
+
+pub fn engine_named(name: &str) -> &'static SearchEngine {
+    let name = name.trim().to_lowercase();
+    let name = match name.as_str() {
+        "ddg" => "duckduckgo",
+        other => other,
+    };
+    ENGINES.iter().find(|e| e.name == name).unwrap_or(&ENGINES[0])
+}
+
+/// Strategy from STEER_FIRST_RESULT (`snapshot`, the default, or `lucky`).
+pub fn strategy() -> FirstResult {
+    match std::env::var("STEER_FIRST_RESULT").unwrap_or_default().trim().to_lowercase().as_str() {
+        "lucky" => FirstResult::Lucky,
+        _ => FirstResult::Snapshot,
+    }
+}
+
+fn fill(template: &str, query: &str) -> String {
+    template.replace("{query}", &urlencoding::encode(query))
+}
+
+/// Result page template: STEER_SEARCH_URL (with `{query}`) or the engine's.
+pub fn search_template(engine: &SearchEngine) -> String {
+    match std::env::var("STEER_SEARCH_URL") {
+        Ok(custom) if custom.contains("{query}") => custom,
+        _ => engine.search_url.to_string(),
+    }
+}
+
+/// Result page for `query`; STEER_SEARCH_URL (with `{query}`) replaces the engine's base URL.
+pub fn search_url(engine: &SearchEngine, query: &str) -> String {
+    fill(&search_template(engine), query)
+}
+
+/// `template` with `{query}` replaced by the URL-encoded `query`.
+pub fn fill_template(template: &str, query: &str) -> String {
+    fill(template, query)
+}
+
+pub fn lucky_url(engine: &SearchEngine, query: &str) -> Option<String> {
+    engine.lucky_url.map(|template| fill(template, query))
+}
+
+/// Open the first result for `query`; returns the URL that was opened.
+/// `open` loads a URL; `click_first` clicks the first link matching a selector
+/// on the loaded page and returns its URL.
+pub fn open_first_result_with(
+    engine: &SearchEngine,
+    strategy: FirstResult,
+    query: &str,
+    results_url: &str,
+    mut open: impl FnMut(&str) -> Result<()>,
+    mut click_first: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<String> {
+    let lucky = lucky_url(engine, query);
+    if strategy == FirstResult::Lucky {
+        if let Some(url) = &lucky {
+            open(url)?;
+            return Ok(url.clone());
+        }
+    }
+
+    open(results_url)?;
+    let problem = match click_first(engine.result_selector) {
+        Ok(Some(url)) => return Ok(url),
+        Ok(None) => "no result link on the page".to_string(),
+        Err(e) => format!("{:#}", e),
+    };
+    match lucky {
+        Some(url) if strategy == FirstResult::Snapshot => {
+            println!("🔎 [Search] Result page unreadable ({}); using {}'s lucky redirect", problem, engine.name);
+            open(&url)?;
+            Ok(url)
+        }
+        _ => Err(anyhow::anyhow!("Could not open the first {} result: {}", engine.name, problem)),
+    }
+}
+
+/// Open the first result for `query` in the preferred browser (STEER_BROWSER)
+/// with the configured engine and strategy.
+pub fn open_first_result(query: &str) -> Result<String> {
+    let engine = engine();
+    open_first_result_with(
+        engine,
+        strategy(),
+        query,
+        &search_url(engine, query),
+        crate::executor::open_url,
+        |selector| {
+            // The result page renders after the URL opens; poll for the links
+            for _ in 0..5 {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                if let Some(url) = crate::browser_automation::click_first_result(selector)? {
+                    return Ok(Some(url));
+                }
+            }
+            Ok(None)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(engine: &str, strategy: FirstResult, page: Result<Option<String>>) -> (Result<String>, Vec<String>) {
+        let engine = engine_named(engine);
+        let mut opened = Vec::new();
+        let mut page = Some(page);
+        let result = open_first_result_with(
+            engine,
+            strategy,
+            "rust book",
+            &fill(engine.search_url, "rust book"),
+            |url| {
+                opened.push(url.to_string());
+                Ok(())
+            },
+            |_| page.take().unwrap(),
+        );
+        (result, opened)
+    }
+
+    #[test]
+    fn test_engines_build_their_urls() {
+        assert_eq!(engine_named("DDG").name, "duckduckgo");
+        assert_eq!(engine_named("altavista").name, "google");
+        assert_eq!(fill(engine_named("bing").search_url, "a&b c"), "https://www.bing.com/search?q=a%26b%20c");
+        assert_eq!(lucky_url(engine_named("duckduckgo"), "rust").as_deref(), Some("https://duckduckgo.com/?q=%5Crust"));
+        assert!(lucky_url(engine_named("bing"), "rust").is_none());
+    }
+
+    #[test]
+    fn test_first_result_is_clicked_and_lucky_is_the_fallback() {
+        let first = "https://doc.rust-lang.org/book/".to_string();
+        let (result, opened) = run("duckduckgo", FirstResult::Snapshot, Ok(Some(first.clone())));
+        assert_eq!(result.unwrap(), first);
+        assert_eq!(opened, vec!["https://duckduckgo.com/?q=rust%20book"]);
+
+        // An unreadable result page falls back to the lucky redirect
+        let (result, opened) = run("google", FirstResult::Snapshot, Err(anyhow::anyhow!("JavaScript from Apple Events is off")));
+        assert_eq!(result.unwrap(), "https://www.google.com/search?btnI=1&q=rust%20book");
+        assert_eq!(opened.len(), 2);
+
+        // ...unless the engine has none
+        let (result, _) = run("bing", FirstResult::Snapshot, Ok(None));
+        assert!(result.unwrap_err().to_string().contains("no result link"));
+
+        // lucky goes straight to the redirect; engines without one use the result page
+        let (result, opened) = run("google", FirstResult::Lucky, Ok(None));
+        assert_eq!((result.unwrap(), opened.len()), ("https://www.google.com/search?btnI=1&q=rust%20book".to_string(), 1));
+        let (result, _) = run("bing", FirstResult::Lucky, Ok(Some(first.clone())));
+        assert_eq!(result.unwrap(), first);
+    }
+}
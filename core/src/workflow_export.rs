@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use crate::db::{self, Recommendation};
+
+// [Workflow Export] `export_workflow <id|all> <path>` saves the n8n workflow
+// built for an approved recommendation as pretty JSON, so it can be imported
+// into another n8n instance or kept under version control. Paths may start
+// with `~`.
+
+/// File name a workflow is exported under: `<id>-<title slug>.json`.
+pub fn file_name(rec: &Recommendation) -> String {
+    let mut slug = String::new();
+    for c in rec.title.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_matches('-').chars().take(60).collect();
+    if slug.is_empty() {
+        format!("{}.json", rec.id)
+    } else {
+        format!("{}-{}.json", rec.id, slug.trim_end_matches('-'))
+    }
+}
+
+/// Write the n8n workflow built for a recommendation to `path`, pretty-printed.
+/// If `path` is an existing directory the file goes inside it, named by
+/// `file_name`. Returns the file written.
+pub fn export_workflow(rec_id: i64, path: &str) -> Result<PathBuf> {
+    let rec = db::get_recommendation(rec_id)?.with_context(|| format!("Recommendation #{} not found", rec_id))?;
+    write(&rec, &expand(path)?)
+}
+
+/// Export every approved recommendation that has a workflow into `dir`
+/// (created if missing). Returns the files written.
+pub fn export_approved(dir: &str) -> Result<Vec<PathBuf>> {
+    let dir = expand(dir)?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut written = Vec::new();
+    for rec in db::get_recommendations_with_filter(Some("approved"))? {
+        if rec.workflow_json.as_deref().is_some_and(|json| !json.trim().is_empty()) {
+            written.push(write(&rec, &dir)?);
+        }
+    }
+    Ok(written)
+}
+
+fn expand(path: &str) -> Result<PathBuf> {
+    let trimmed = path.trim().trim_matches('"');
+    if trimmed.is_empty() {
+        anyhow::bail!("No export path given");
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    Ok(crate::paths::expand_home(trimmed, home.as_deref()))
+}
+
+fn write(rec: &Recommendation, path: &Path) -> Result<PathBuf> {
+    let json = rec
+        .workflow_json
+        .as_deref()
+        .filter(|json| !json.trim().is_empty())
+        .with_context(|| format!("Recommendation #{} has no workflow yet (approve it first)", rec.id))?;
+    let workflow: serde_json::Value = serde_json::from_str(json)
+        .with_context(|| format!("Recommendation #{} has an unreadable workflow", rec.id))?;
+    let pretty = serde_json::to_string_pretty(&workflow)?;
+
+    let target = if path.is_dir() { path.join(file_name(rec)) } else { path.to_path_buf() };
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&target, pretty + "\n").with_context(|| format!("Failed to write {}", target.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recommendation::AutomationProposal;
+
+    #[test]
+    fn test_export_workflow_writes_pretty_json() {
+        db::init().ok();
+        let tag = uuid::Uuid::new_v4().to_string();
+        let proposal = AutomationProposal {
+            title: format!("Export: Daily Report {}", tag),
+            trigger: format!("export trigger {}", tag),
+            ..Default::default()
+        };
+        assert!(db::insert_recommendation(&proposal).unwrap());
+        let id = db::get_recommendations_by_statuses(&[], -1, 0)
+            .unwrap()
+            .into_iter()
+            .find(|r| r.trigger == proposal.trigger)
+            .map(|r| r.id)
+            .expect("inserted row");
+        let dir = std::env::temp_dir().join(format!("steer-export-{}", tag));
+        let at = |p: &Path| p.to_str().unwrap().to_string();
+
+        let err = export_workflow(id, &at(&dir.join("early.json"))).unwrap_err();
+        assert!(err.to_string().contains("has no workflow yet"), "{}", err);
+
+        db::mark_recommendation_approved(id, "wf-1", r#"{"name":"Daily","nodes":[]}"#).unwrap();
+        let file = export_workflow(id, &at(&dir.join("nested").join("daily.json"))).unwrap();
+        let text = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(text, "{\n  \"name\": \"Daily\",\n  \"nodes\": []\n}\n");
+
+        // An existing directory gets a generated file name
+        let file = export_workflow(id, &at(&dir)).unwrap();
+        assert_eq!(file, dir.join(format!("{}-export-daily-report-{}.json", id, tag)));
+
+        let all_dir = dir.join("all");
+        let written = export_approved(&at(&all_dir)).unwrap();
+        assert!(written.contains(&all_dir.join(file_name(&db::get_recommendation(id).unwrap().unwrap()))));
+
+        let missing = export_workflow(i64::MIN, &at(&dir)).unwrap_err();
+        assert!(missing.to_string().contains("not found"), "{}", missing);
+        assert!(export_workflow(id, "  ").is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
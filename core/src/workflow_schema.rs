@@ -144,6 +144,82 @@ impl DetectedPattern {
     }
 }
 
+/// Structural checks on LLM-built n8n workflow JSON before it is imported:
+/// nodes exist with unique names and types, there is a trigger, and every
+/// connection points at a real node. Returns one message per problem.
+pub fn validate_n8n_workflow(workflow: &serde_json::Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(nodes) = workflow["nodes"].as_array().filter(|n| !n.is_empty()) else {
+        return vec!["workflow has no nodes".to_string()];
+    };
+
+    let mut names: Vec<&str> = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        match node["name"].as_str().filter(|n| !n.trim().is_empty()) {
+            Some(name) if names.contains(&name) => errors.push(format!("duplicate node name '{}'", name)),
+            Some(name) => names.push(name),
+            None => errors.push(format!("node #{} has no name", i + 1)),
+        }
+        if node["type"].as_str().is_none_or(|t| t.trim().is_empty()) {
+            errors.push(format!("node #{} has no type", i + 1));
+        }
+    }
+
+    let has_trigger = nodes.iter().any(|n| {
+        let t = n["type"].as_str().unwrap_or("").to_lowercase();
+        t.contains("trigger") || t.ends_with(".webhook") || t.ends_with(".cron")
+    });
+    if !has_trigger {
+        errors.push("workflow has no trigger node".to_string());
+    }
+
+    match workflow.get("connections") {
+        None | Some(serde_json::Value::Null) => {}
+        Some(serde_json::Value::Object(connections)) => {
+            for (source, outputs) in connections {
+                if !names.contains(&source.as_str()) {
+                    errors.push(format!("connection from unknown node '{}'", source));
+                }
+                let targets = outputs
+                    .as_object()
+                    .into_iter()
+                    .flat_map(|o| o.values())
+                    .filter_map(|v| v.as_array())
+                    .flatten()
+                    .filter_map(|branch| branch.as_array())
+                    .flatten();
+                for target in targets {
+                    match target["node"].as_str() {
+                        Some(node) if names.contains(&node) => {}
+                        Some(node) => errors.push(format!("connection from '{}' to unknown node '{}'", source, node)),
+                        None => errors.push(format!("connection from '{}' has no target node", source)),
+                    }
+                }
+            }
+        }
+        Some(_) => errors.push("connections must be an object".to_string()),
+    }
+    errors
+}
+
+/// `STEER_N8N_AUTO_ACTIVATE`: activate approved workflows right after import (default false).
+pub fn auto_activate_enabled() -> bool {
+    std::env::var("STEER_N8N_AUTO_ACTIVATE")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Workflow request for a rebuild after a failed import: the original request
+/// plus why the previous attempt failed, so the model corrects that instead of
+/// repeating it.
+pub fn correction_prompt(request: &str, last_error: &str) -> String {
+    format!(
+        "{}\n\nA previous workflow built for this request failed with:\n{}\nBuild it again and avoid that error.",
+        request.trim(),
+        last_error.trim()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,6 +264,34 @@ mod tests {
         assert_eq!(rec.feedback.unwrap().error_message, Some("API Error".to_string()));
     }
 
+    #[test]
+    fn test_validate_n8n_workflow() {
+        let valid = json!({
+            "nodes": [
+                { "name": "Schedule", "type": "n8n-nodes-base.scheduleTrigger" },
+                { "name": "Notify", "type": "n8n-nodes-base.telegram" }
+            ],
+            "connections": { "Schedule": { "main": [[{ "node": "Notify", "type": "main", "index": 0 }]] } }
+        });
+        assert!(validate_n8n_workflow(&valid).is_empty());
+
+        let broken = json!({
+            "nodes": [
+                { "name": "Notify", "type": "n8n-nodes-base.telegram" },
+                { "name": "Notify", "type": "" }
+            ],
+            "connections": { "Start": { "main": [[{ "node": "Ghost", "type": "main", "index": 0 }]] } }
+        });
+        let errors = validate_n8n_workflow(&broken);
+        assert!(errors.contains(&"duplicate node name 'Notify'".to_string()));
+        assert!(errors.contains(&"node #2 has no type".to_string()));
+        assert!(errors.contains(&"workflow has no trigger node".to_string()));
+        assert!(errors.contains(&"connection from unknown node 'Start'".to_string()));
+        assert!(errors.contains(&"connection from 'Start' to unknown node 'Ghost'".to_string()));
+
+        assert_eq!(validate_n8n_workflow(&json!({ "nodes": [] })), vec!["workflow has no nodes"]);
+    }
+
     #[test]
     fn test_serialization() {
         let status = RecommendationStatus::Approved;
@@ -197,4 +301,11 @@ mod tests {
         let deserialized: RecommendationStatus = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, status);
     }
+
+    #[test]
+    fn test_correction_prompt_carries_the_last_error() {
+        let prompt = correction_prompt("Email me new invoices\n", " n8n API error 400: unknown node type ");
+        assert!(prompt.starts_with("Email me new invoices\n\n"));
+        assert!(prompt.contains("failed with:\nn8n API error 400: unknown node type\n"));
+    }
 }
@@ -1,6 +1,7 @@
 import { QueryClient, QueryClientProvider } from '@tanstack/react-query'
 import Launcher from '@/features/launcher/Launcher'
 import WidgetLayer from '@/features/widget/WidgetLayer' // Import new layer
+import PermissionBanner from '@/components/PermissionBanner'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { useEffect } from 'react'
 
@@ -48,6 +49,7 @@ function App() {
         <WidgetLayer />
       ) : (
         <div className="h-screen w-screen bg-transparent overflow-hidden relative flex items-center justify-center">
+          <PermissionBanner />
           <Launcher />
         </div>
       )}
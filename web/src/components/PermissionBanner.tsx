@@ -0,0 +1,101 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import { emit } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { ShieldAlert, RefreshCw, ChevronDown, ChevronUp } from "lucide-react";
+import { fetchPermissions } from "@/lib/api";
+import type { PermissionReport } from "@/lib/types";
+
+function isTauri(): boolean {
+    if (typeof window === "undefined") {
+        return false;
+    }
+    return Boolean(
+        (window as any).__TAURI_METADATA__ ||
+        (window as any).__TAURI__?.metadata ||
+        (window as any).__TAURI_INTERNALS__?.metadata
+    );
+}
+
+// Persistent banner + setup steps for missing macOS permissions. Re-checks
+// whenever the window regains focus (usually on return from System Settings)
+// and emits `permissions-changed` so other windows can react.
+export default function PermissionBanner() {
+    const [report, setReport] = useState<PermissionReport | null>(null);
+    const [expanded, setExpanded] = useState(true);
+    const [checking, setChecking] = useState(false);
+    const lastMissing = useRef<string | null>(null);
+
+    const check = useCallback(async () => {
+        setChecking(true);
+        try {
+            const next = await fetchPermissions();
+            setReport(next);
+            const missing = next.missing.join(",");
+            if (lastMissing.current !== null && lastMissing.current !== missing && isTauri()) {
+                await emit("permissions-changed", next);
+            }
+            lastMissing.current = missing;
+        } catch (error) {
+            // Core not reachable: nothing to report about permissions
+            console.error("Failed to check permissions:", error);
+        } finally {
+            setChecking(false);
+        }
+    }, []);
+
+    useEffect(() => {
+        check();
+        if (isTauri()) {
+            const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+                if (focused) {
+                    check();
+                }
+            });
+            return () => {
+                unlisten.then(f => f());
+            };
+        }
+        window.addEventListener("focus", check);
+        return () => window.removeEventListener("focus", check);
+    }, [check]);
+
+    const missing = report?.permissions.filter(p => p.state === "missing") ?? [];
+    if (missing.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="fixed top-3 left-1/2 -translate-x-1/2 z-50 w-full max-w-2xl rounded-xl border border-amber-500/40 bg-[#2a2418]/95 backdrop-blur-xl text-amber-100 shadow-lg">
+            <div className="flex items-center px-4 py-2 gap-2">
+                <ShieldAlert className="w-4 h-4 text-amber-400 shrink-0" />
+                <span className="flex-1 text-sm">
+                    {report?.ready ? "Some features are limited" : "Steer can't control other apps yet"}
+                    {": "}
+                    {missing.map(p => p.name.replace("_", " ")).join(", ")} permission missing
+                </span>
+                <button
+                    onClick={check}
+                    disabled={checking}
+                    className="text-xs bg-white/10 hover:bg-white/20 px-2 py-1 rounded flex items-center gap-1 disabled:opacity-50"
+                >
+                    <RefreshCw className={`w-3 h-3 ${checking ? "animate-spin" : ""}`} />
+                    Check again
+                </button>
+                <button onClick={() => setExpanded(e => !e)} className="text-amber-300 hover:text-amber-100">
+                    {expanded ? <ChevronUp className="w-4 h-4" /> : <ChevronDown className="w-4 h-4" />}
+                </button>
+            </div>
+            {expanded && (
+                <ol className="px-10 pb-3 text-xs text-amber-200/90 list-decimal space-y-1">
+                    {missing.map(p => (
+                        <li key={p.name}>
+                            Open <span className="font-medium text-amber-100">{p.settings_path}</span> and enable this app
+                            {" "}(needed for {p.needed_for}).
+                        </li>
+                    ))}
+                    <li>Come back to this window; the check runs again automatically.</li>
+                </ol>
+            )}
+        </div>
+    );
+}
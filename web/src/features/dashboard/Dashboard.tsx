@@ -388,7 +388,7 @@ function RecommendationsCard() {
         }
     };
 
-    const handleFeedbackSubmit = async (recId: number, goal: string, summary: string, evidence?: string[]) => {
+    const handleFeedbackSubmit = async (recId: number, goal: string, summary: string, evidence?: { detail: string }[]) => {
         const text = (feedbackText[recId] || "").trim();
         if (!text) {
             setFeedbackStatus((prev) => ({ ...prev, [recId]: "Feedback is required." }));
@@ -398,7 +398,7 @@ function RecommendationsCard() {
             const history = [
                 `Recommendation: ${goal}`,
                 summary ? `Summary: ${summary}` : "",
-                evidence && evidence.length > 0 ? `Evidence: ${evidence.slice(0, 2).join(" | ")}` : "",
+                evidence && evidence.length > 0 ? `Evidence: ${evidence.slice(0, 2).map((ev) => ev.detail).join(" | ")}` : "",
             ].filter(Boolean).join(" / ");
             const res = await sendFeedback(goal, text, history);
             setFeedbackStatus((prev) => ({ ...prev, [recId]: res.message || "Feedback submitted." }));
@@ -466,7 +466,10 @@ function RecommendationsCard() {
                                         {rec.evidence.slice(0, 3).map((ev, i) => (
                                             <li key={i} className="flex gap-1.5 items-start">
                                                 <span className="text-indigo-500/50 block mt-0.5">•</span>
-                                                <span className="opacity-90">{ev}</span>
+                                                <span className="opacity-90">{ev.detail}</span>
+                                                {ev.weight > 0 && (
+                                                    <span className="ml-auto text-indigo-400/70">{Math.round(ev.weight * 100)}%</span>
+                                                )}
                                             </li>
                                         ))}
                                     </ul>
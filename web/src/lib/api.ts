@@ -27,6 +27,7 @@ import {
     NLRunMetricsSchema,
     NLRunSchema,
     ContextSelectionSchema,
+    PermissionReportSchema,
     ProjectScanSchema,
     JudgmentSchema,
     type SystemStatus,
@@ -56,6 +57,7 @@ import {
     type NLRunMetrics,
     type NLRun,
     type ContextSelection,
+    type PermissionReport,
     type ProjectScan,
     type Judgment,
     type QualityScore,
@@ -227,6 +229,12 @@ export async function fetchSelectionContext(): Promise<ContextSelection> {
     return ContextSelectionSchema.parse(data);
 }
 
+// Which OS permissions the agent has (re-checked on window focus)
+export async function fetchPermissions(): Promise<PermissionReport> {
+    const { data } = await api.get("/permissions");
+    return PermissionReportSchema.parse(data);
+}
+
 export async function scanProject(maxFiles?: number, workdir?: string): Promise<ProjectScan> {
     const query = new URLSearchParams();
     if (maxFiles) query.set("max_files", String(maxFiles));
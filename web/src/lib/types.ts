@@ -23,6 +23,16 @@ export const RoutineSchema = z.object({
     next_run: z.string().nullable(),
 });
 
+// One weighted reason behind a recommendation (older rows stored plain strings)
+export const EvidenceSchema = z.union([
+    z.object({
+        source: z.string(),
+        detail: z.string(),
+        weight: z.number(),
+    }),
+    z.string().transform((detail) => ({ source: "note", detail, weight: 0 })),
+]);
+
 // Recommendation/Workflow Schema
 export const RecommendationSchema = z.object({
     id: z.number(),
@@ -30,7 +40,7 @@ export const RecommendationSchema = z.object({
     summary: z.string(),
     status: z.string(),
     confidence: z.number(),
-    evidence: z.array(z.string()).optional(), // [NEW] Explainability
+    evidence: z.array(EvidenceSchema).optional(), // [NEW] Explainability
     last_error: z.string().nullable().optional(),
 });
 
@@ -268,6 +278,21 @@ export const ContextSelectionSchema = z.object({
     error: z.string().optional(),
 });
 
+export const PermissionSchema = z.object({
+    name: z.string(),
+    state: z.enum(["granted", "missing", "disabled", "not_required"]),
+    needed_for: z.string(),
+    settings_path: z.string(),
+    required: z.boolean(),
+});
+
+export const PermissionReportSchema = z.object({
+    permissions: z.array(PermissionSchema),
+    missing: z.array(z.string()),
+    ready: z.boolean(),
+    checked_at: z.string(),
+});
+
 export const ProjectScanSchema = z.object({
     project_type: z.string(),
     files: z.array(z.string()),
@@ -310,5 +335,7 @@ export type ApprovalPolicy = z.infer<typeof ApprovalPolicySchema>;
 export type NLRunMetrics = z.infer<typeof NLRunMetricsSchema>;
 export type NLRun = z.infer<typeof NLRunSchema>;
 export type ContextSelection = z.infer<typeof ContextSelectionSchema>;
+export type Permission = z.infer<typeof PermissionSchema>;
+export type PermissionReport = z.infer<typeof PermissionReportSchema>;
 export type ProjectScan = z.infer<typeof ProjectScanSchema>;
 export type Judgment = z.infer<typeof JudgmentSchema>;